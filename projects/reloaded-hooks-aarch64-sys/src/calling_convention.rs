@@ -3,6 +3,7 @@ use crate::all_registers::AllRegisters::*;
 use derive_more::Deref;
 use derive_more::DerefMut;
 use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+use reloaded_hooks_portable::api::calling_convention_info::ParameterRegisterAssignment;
 use reloaded_hooks_portable::api::calling_convention_info::StackCleanup;
 use reloaded_hooks_portable::api::calling_convention_info::StackParameterOrder;
 
@@ -27,13 +28,16 @@ static AAPCS64: CallingConvention = CallingConvention {
         int_parameters: &[x0, x1, x2, x3, x4, x5, x6, x7],
         float_parameters: &[v0, v1, v2, v3, v4, v5, v6, v7],
         vector_parameters: &[],
-        return_register: x0, // Assuming x0 is for integers and v0 for floats
+        return_register: x0,
+        float_return_register: Some(v0),
         reserved_stack_space: 0,
         callee_saved_registers: &[x19, x20, x21, x22, x23, x24, x25, x26, x27, x28, x29],
         always_saved_registers: &[LR],
         stack_cleanup: StackCleanup::Caller,
         stack_parameter_order: StackParameterOrder::RightToLeft,
         required_stack_alignment: 16, // mandated by hardware
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
     },
 };
 
@@ -44,12 +48,15 @@ static MICROSOFT_ARM64: CallingConvention = CallingConvention {
         float_parameters: &[v0, v1, v2, v3, v4, v5, v6, v7],
         vector_parameters: &[],
         return_register: x0,
+        float_return_register: Some(v0),
         reserved_stack_space: 16, // Documented as 'Red zone'
         callee_saved_registers: &[x19, x20, x21, x22, x23, x24, x25, x26, x27, x28, x29],
         always_saved_registers: &[LR],
         stack_cleanup: StackCleanup::Caller,
         stack_parameter_order: StackParameterOrder::RightToLeft,
         required_stack_alignment: 16, // mandated by hardware
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
     },
 };
 
@@ -3,8 +3,8 @@ extern crate alloc;
 use super::{
     instruction_rewrite_result::InstructionRewriteResult,
     instructions::{
-        adr::rewrite_adr, b::rewrite_b, b_cond::rewrite_bcc, cbz::rewrite_cbz,
-        ldr_literal::rewrite_ldr_literal, tbz::rewrite_tbz,
+        adr::rewrite_adr, adrp_pair::rewrite_adrp_pair, b::rewrite_b, b_cond::rewrite_bcc,
+        cbz::rewrite_cbz, ldr_literal::rewrite_ldr_literal, tbz::rewrite_tbz,
     },
 };
 use crate::helpers::{vec_u32_to_u8, vec_u8_to_u32};
@@ -62,21 +62,41 @@ pub(crate) fn rewrite_code_aarch64(
     while old_ins_ptr < old_ins_end_ptr {
         let instruction = unsafe { read_unaligned(old_ins_ptr) };
 
+        // Rewrite ADRP + consumer pairs, if both are in the stolen code.
+        let next_ins_ptr = old_ins_ptr.wrapping_add(1);
+        let pair_result = if is_adr(instruction.to_le()) && next_ins_ptr < old_ins_end_ptr {
+            rewrite_adrp_pair(
+                instruction.to_le(),
+                unsafe { read_unaligned(next_ins_ptr) }.to_le(),
+                old_addr_ptr as usize,
+                current_new_address,
+                scratch_register,
+            )
+        } else {
+            None
+        };
+
         // Rewrite instruction.
-        let result = rewrite_instruction(
-            instruction.to_le(),
-            current_new_address,
-            old_addr_ptr as usize,
-            scratch_register,
-        );
+        let (result, num_instructions) = match pair_result {
+            Some(x) => (x, 2),
+            None => (
+                rewrite_instruction(
+                    instruction.to_le(),
+                    current_new_address,
+                    old_addr_ptr as usize,
+                    scratch_register,
+                ),
+                1,
+            ),
+        };
 
         match result {
             Ok(x) => {
                 x.append_to_buffer(&mut vec);
 
                 // Advance pointers
-                old_ins_ptr = old_ins_ptr.wrapping_add(1);
-                old_addr_ptr = old_addr_ptr.wrapping_add(1);
+                old_ins_ptr = old_ins_ptr.wrapping_add(num_instructions);
+                old_addr_ptr = old_addr_ptr.wrapping_add(num_instructions);
                 current_new_address = current_new_address.wrapping_add(x.size_bytes());
             }
             Err(x) => return Err(x),
@@ -105,7 +125,7 @@ fn rewrite_instruction(
     } else if is_tbz(instruction) {
         rewrite_tbz(instruction, source_address, dest_address, scratch_register)
     } else if is_ldr_literal(instruction) {
-        rewrite_ldr_literal(instruction, source_address, dest_address)
+        rewrite_ldr_literal(instruction, source_address, dest_address, scratch_register)
     } else {
         Ok(InstructionRewriteResult::Copy(instruction))
     }
@@ -135,6 +155,16 @@ pub(crate) fn is_ldr_literal(instruction: u32) -> bool {
     (instruction & 0x3b000000) == 0x18000000
 }
 
+/// Returns true for a branch target landing pad, i.e. `bti c`, `bti j`, `bti jc`, and
+/// `paciasp`/`pacibsp` which act as an implicit `bti c`.
+///
+/// These are copied as is when relocated, as they are hints (NOPs) outside of guarded pages.
+pub(crate) fn is_landing_pad(instruction: u32) -> bool {
+    ((instruction & 0xffffff3f) == 0xd503241f && instruction != 0xd503241f)
+        || instruction == 0xd503233f
+        || instruction == 0xd503237f
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -142,7 +172,7 @@ mod tests {
         test_helpers::instruction_buffer_as_hex_u8,
     };
 
-    use super::{is_adr, is_b_or_bl, is_bcc, is_cbz, is_ldr_literal, is_tbz};
+    use super::{is_adr, is_b_or_bl, is_bcc, is_cbz, is_landing_pad, is_ldr_literal, is_tbz};
     use rstest::rstest;
 
     #[allow(non_camel_case_types)]
@@ -194,6 +224,8 @@ mod tests {
     #[case::blr(0x00003FD6_u32.to_be(), InsType::Unknown)] // blr x0
     #[case::br(0x00001FD6_u32.to_be(), InsType::Unknown)] // br x0
     #[case::brk(0x000020D4_u32.to_be(), InsType::Unknown)] // brk #0
+    #[case::bti_c(0x5F2403D5_u32.to_be(), InsType::Unknown)] // bti c
+    #[case::bti_jc(0xDF2403D5_u32.to_be(), InsType::Unknown)] // bti jc
     #[case::cbnz(0x00FEFFB5_u32.to_be(), InsType::Cbz)] // cbnz x0, #0
     #[case::cbz(0xE0FDFFB4_u32.to_be(), InsType::Cbz)] // cbz x0, #0
     #[case::ccmn(0x001840BA_u32.to_be(), InsType::Unknown)] // ccmn x0, #0, #0x0, ne
@@ -319,6 +351,7 @@ mod tests {
     #[case::nop(0x1F2003D5_u32.to_be(), InsType::Unknown)] // nop
     #[case::orn(0x200022AA_u32.to_be(), InsType::Unknown)] // orn x0, x1, x2
     #[case::orr(0x000074B2_u32.to_be(), InsType::Unknown)] // orr x0, x0, #0x1000
+    #[case::paciasp(0x3F2303D5_u32.to_be(), InsType::Unknown)] // paciasp
     #[case::prfm(0x000000D8_u32.to_be(), InsType::LdrLiteral)] // prfm pldl1keep, #0 // sub-mode of LDR literal
     #[case::prfm(0x000080F9_u32.to_be(), InsType::Unknown)] // prfm pldl1keep, [x0]
     #[case::prfum(0x000081F8_u32.to_be(), InsType::Unknown)] // prfum pldl1keep, [x0, #0x10]
@@ -397,6 +430,40 @@ mod tests {
         assert!(get_ins_type(instruction) == expected_instruction);
     }
 
+    #[rstest]
+    #[case::bti(0x1F2403D5_u32.to_be(), false)] // bti
+    #[case::bti_c(0x5F2403D5_u32.to_be(), true)] // bti c
+    #[case::bti_j(0x9F2403D5_u32.to_be(), true)] // bti j
+    #[case::bti_jc(0xDF2403D5_u32.to_be(), true)] // bti jc
+    #[case::paciasp(0x3F2303D5_u32.to_be(), true)] // paciasp
+    #[case::pacibsp(0x7F2303D5_u32.to_be(), true)] // pacibsp
+    #[case::autiasp(0xBF2303D5_u32.to_be(), false)] // autiasp
+    #[case::nop(0x1F2003D5_u32.to_be(), false)] // nop
+    fn ensure_landing_pad_recognized(#[case] instruction: u32, #[case] expected: bool) {
+        assert_eq!(is_landing_pad(instruction), expected);
+    }
+
+    #[rstest]
+    // PACIASP + BL #0x1000 -> PACIASP + BL #0x1000 (PAC is SP based, so can be relocated)
+    #[case::paciasp("3f2303d500040094", 4096, 4096, "3f2303d500040094", Some(17))]
+    // BTI C + B #0x1000 -> BTI C + B #0x2000
+    #[case::bti_c("5f2403d500040014", 8192, 4096, "5f2403d500080014", Some(17))]
+    fn test_rewrite_landing_pad_cases(
+        #[case] old_instruction_hex: &str,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected_hex: &str,
+        #[case] scratch_register: Option<u8>,
+    ) {
+        test_rewrite(
+            old_instruction_hex,
+            old_address,
+            new_address,
+            expected_hex,
+            scratch_register,
+        );
+    }
+
     #[rstest]
     #[case("00040014", 8192, 4096, "00080014", Some(17))]
     #[case("00040014", 0x8000000, 0, "110004b020021fd6", Some(17))]
@@ -424,6 +491,33 @@ mod tests {
         );
     }
 
+    #[rstest]
+    // ADRP x16, #0x10000 + ADD x16, x16, #0x10 -> ADR x16, #0xf010
+    #[case::adrp_add_pair("9000009010420091", 0, 0x1000, "90800710", None)]
+    // ADRP x16, #0x10000 + LDR x16, [x16, #0x18] -> LDR x16, #0xf018
+    #[case::adrp_ldr_pair("90000090100e40f9", 0, 0x1000, "d0800758", None)]
+    // ADRP x16, #0x10000 (consumer not stolen) -> ADR x16, #0xf000
+    #[case::adrp_only("90000090", 0, 0x1000, "10800710", None)]
+    // ADRP x16, #0x10000 + ADD x17, x16, #0x10 (page still live) -> ADR x16, #0xf000 + ADD x17, x16, #0x10
+    #[case::adrp_add_not_pair("9000009011420091", 0, 0x1000, "1080071011420091", None)]
+    // NOP + ADRP x16, #0x10000 (consumer not stolen) -> NOP + ADR x16, #0xeffc
+    #[case::adrp_last("1f2003d590000090", 0, 0x1000, "1f2003d5f07f0710", None)]
+    fn test_rewrite_adrp_pair_cases(
+        #[case] old_instruction_hex: &str,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected_hex: &str,
+        #[case] scratch_register: Option<u8>,
+    ) {
+        test_rewrite(
+            old_instruction_hex,
+            old_address,
+            new_address,
+            expected_hex,
+            scratch_register,
+        );
+    }
+
     // Helper function to convert hex string to a byte vector.
     fn hex_str_to_bytes(hex_str: &str) -> Vec<u8> {
         hex::decode(hex_str).expect("Invalid hex string")
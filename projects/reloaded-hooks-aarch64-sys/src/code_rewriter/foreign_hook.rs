@@ -0,0 +1,99 @@
+use super::aarch64_rewriter::{is_adr, is_b_or_bl};
+use crate::instructions::{adr::Adr, b::B, branch_register::BranchRegister};
+use reloaded_hooks_portable::{
+    api::rewriter::foreign_hook::ForeignHook, helpers::read_code::read_code_as,
+};
+
+/// Recognises jumps placed at the start of a function by other hooking libraries.
+///
+/// - `b <target>` (older Reloaded.Hooks)
+/// - `ldr xN, #8; br xN` followed by the target address (Dobby, frida-gum)
+/// - `adrp xN, <page>; add xN, xN, #<offset>; br xN` (Dobby, frida-gum, Reloaded.Hooks)
+///
+/// # Safety
+///
+/// `address` must point to readable code.
+pub(crate) unsafe fn find_foreign_hook(address: usize) -> Option<ForeignHook> {
+    let instruction = read_code_as::<u32>(address).to_le();
+
+    // b <target>
+    if is_b_or_bl(instruction) && !B(instruction).is_link() {
+        let target = address.wrapping_add_signed(B(instruction).offset() as isize);
+        return Some(ForeignHook::new(4, target));
+    }
+
+    // ldr xN, #8
+    if instruction & 0xFFFFFFE0 == 0x58000040 {
+        let register = (instruction & 0x1F) as u8;
+        if read_code_as::<u32>(address + 4).to_le() != BranchRegister::new_br(register).0 {
+            return None;
+        }
+
+        let target = read_code_as::<u64>(address + 8).to_le();
+        return Some(ForeignHook::new(16, target as usize));
+    }
+
+    // adrp xN, <page>
+    let adrp = Adr(instruction);
+    if is_adr(instruction) && adrp.is_pageaddress() {
+        let register = adrp.rd();
+        let add = read_code_as::<u32>(address + 4).to_le();
+        let add_register = register as u32 | (register as u32) << 5;
+        if add & 0xFFC003FF != 0x91000000 | add_register
+            || read_code_as::<u32>(address + 8).to_le() != BranchRegister::new_br(register).0
+        {
+            return None;
+        }
+
+        let offset = ((add >> 10) & 0xFFF) as usize;
+        return Some(ForeignHook::new(12, adrp.extract_address(address) + offset));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Finds the foreign hook in `code`, padded such that reading past the end of shorter code is fine.
+    fn find_in(code: &str) -> (usize, Option<ForeignHook>) {
+        let mut code = hex::decode(code).unwrap();
+        code.resize(16, 0);
+        let address = code.as_ptr() as usize;
+        (address, unsafe { find_foreign_hook(address) })
+    }
+
+    #[rstest]
+    #[case::b("00040014", Some(0x1000))] // b #0x1000
+    #[case::b_backwards("00fcff17", Some(-0x1000))] // b #-0x1000
+    #[case::bl("00040094", None)] // bl #0x1000
+    #[case::nop("1f2003d5", None)]
+    fn find_branch(#[case] code: &str, #[case] offset: Option<isize>) {
+        let (address, result) = find_in(code);
+        let expected = offset.map(|x| ForeignHook::new(4, address.wrapping_add_signed(x)));
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case::x17("5100005820021fd6bc9a785634120000", Some(0x123456789ABC))] // ldr x17, #8; br x17
+    #[case::x16("5000005800021fd6bc9a785634120000", Some(0x123456789ABC))] // ldr x16, #8; br x16
+    #[case::mismatched_register("5100005800021fd6bc9a785634120000", None)] // ldr x17, #8; br x16
+    #[case::other_offset("7100005820021fd6bc9a785634120000", None)] // ldr x17, #12; br x17
+    fn find_ldr_br(#[case] code: &str, #[case] target: Option<usize>) {
+        let (_, result) = find_in(code);
+        assert_eq!(result, target.map(|x| ForeignHook::new(16, x)));
+    }
+
+    #[rstest]
+    #[case::same_page("11000090313e3e9120021fd6", Some(0xF8F))] // adrp x17, #0; add x17, x17, #0xf8f; br x17
+    #[case::next_page("110000b0313e3e9120021fd6", Some(0x1F8F))] // adrp x17, #0x1000; add x17, x17, #0xf8f; br x17
+    #[case::other_register("11000090303e3e9120021fd6", None)] // adrp x17, #0; add x16, x17, #0xf8f; br x17
+    #[case::no_branch("11000090313e3e911f2003d5", None)] // adrp x17, #0; add x17, x17, #0xf8f; nop
+    fn find_adrp_add_br(#[case] code: &str, #[case] page_offset: Option<usize>) {
+        let (address, result) = find_in(code);
+        let expected = page_offset.map(|x| ForeignHook::new(12, (address & !4095) + x));
+        assert_eq!(result, expected);
+    }
+}
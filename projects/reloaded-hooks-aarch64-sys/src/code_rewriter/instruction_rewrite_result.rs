@@ -33,6 +33,7 @@ pub(crate) enum InstructionRewriteResult {
     CbzAndBranchAbsolute(Box<[u32]>),
     LdrLiteral(u32),
     AdrpAndLdrUnsignedOffset(u32, u32),
+    AdrpAndAddAndLdrUnsignedOffset(u32, u32, u32),
     MovImmediateAndLdrLiteral(Box<[u32]>),
     MovImmediate1(u32), // in instruction count order
     MovImmediate2(u32, u32),
@@ -145,6 +146,11 @@ impl InstructionRewriteResult {
                 buf.push(*inst1);
                 buf.push(*inst2);
             }
+            InstructionRewriteResult::AdrpAndAddAndLdrUnsignedOffset(inst1, inst2, inst3) => {
+                buf.push(*inst1);
+                buf.push(*inst2);
+                buf.push(*inst3);
+            }
             InstructionRewriteResult::MovImmediateAndLdrLiteral(boxed) => {
                 buf.extend_from_slice(boxed.as_ref())
             }
@@ -203,6 +209,7 @@ impl InstructionRewriteResult {
             InstructionRewriteResult::CbzAndBranchAbsolute(boxed) => boxed.len() * 4,
             InstructionRewriteResult::LdrLiteral(_) => 4,
             InstructionRewriteResult::AdrpAndLdrUnsignedOffset(_, _) => 8,
+            InstructionRewriteResult::AdrpAndAddAndLdrUnsignedOffset(_, _, _) => 12,
             InstructionRewriteResult::MovImmediateAndLdrLiteral(boxed) => boxed.len() * 4,
             InstructionRewriteResult::None => 0,
             InstructionRewriteResult::Tbz(_) => 4,
@@ -35,8 +35,26 @@ pub(crate) fn rewrite_adr(
     old_address: usize,
     new_address: usize,
 ) -> InstructionRewriteResult {
-    let mut adr = Adr(instruction.to_le());
-    let old_target = adr.extract_address(old_address);
+    let adr = Adr(instruction.to_le());
+    rewrite_adr_target(adr.rd(), adr.extract_address(old_address), new_address)
+}
+
+/// Emits the equivalent of an `ADR` instruction which loads `old_target` into `destination`,
+/// placed at `new_address`.
+///
+/// # Parameters
+///
+/// * `destination`: The register to load the address into.
+/// * `old_target`: The address to load.
+/// * `new_address`: The address of the emitted instruction(s).
+pub(crate) fn rewrite_adr_target(
+    destination: u8,
+    old_target: usize,
+    new_address: usize,
+) -> InstructionRewriteResult {
+    let mut adr = Adr(0);
+    adr.set_opcode(0b10000);
+    adr.set_rd(destination);
 
     // Compute the difference between the new address and old target.
     let delta = (old_target as isize).wrapping_sub(new_address as isize);
@@ -75,8 +93,10 @@ mod tests {
     // Note: We reverse byte order of left due to little endian.
     // Move ADRP x0, 0x101000 to ADR x0, 0xFFFFF
     #[case::adrp_to_adr(0x000800B0_u32.to_be(), 0, 4097, "e0ff7f70")]
-    // Move ADRP x0, 0x101000 to ADRP x0, 0x102000 + ADD x0, x0, 1
-    #[case::within_4gib_range(0x000800B0_u32.to_be(), 4097, 0, "000800d000040091")]
+    // Move ADRP x0, 0x101000 to ADRP x0, 0x102000 (ADRP is relative to the page of PC)
+    #[case::within_4gib_range(0x000800B0_u32.to_be(), 4097, 0, "000800d0")]
+    // Move [PC = 0x100000], ADR x0, #4 to ADRP x0, 0x100000 + ADD x0, x0, 4
+    #[case::adr_within_4gib_range(0x20000010_u32.to_be(), 0x100000, 0, "00080090 00100091")]
     // Move ADRP x0, 0x101000 to ADRP x0, 0x102000
     #[case::within_4gib_range_no_offset (0x000800B0_u32.to_be(), 4096, 0, "000800d0")]
     // Move [PC = 0x100000000], ADRP, x0, 0x101000 to MOV IMMEDIATE 0x100101000
@@ -88,6 +108,6 @@ mod tests {
         #[case] expected_hex: &str,
     ) {
         let result = rewrite_adr(old_instruction, old_address, new_address);
-        assert_eq!(result.to_hex_string(), expected_hex);
+        assert_eq!(result.to_hex_string(), expected_hex.replace(' ', ""));
     }
 }
@@ -0,0 +1,144 @@
+extern crate alloc;
+
+use super::{adr::rewrite_adr_target, ldr_literal::rewrite_ldr_literal_target};
+use crate::{
+    code_rewriter::instruction_rewrite_result::InstructionRewriteResult,
+    instructions::{
+        add_immediate::AddImmediate, adr::Adr,
+        ldr_immediate_unsigned_offset::LdrImmediateUnsignedOffset,
+    },
+};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Rewrites an `ADRP` together with the instruction consuming its result, for a new address.
+///
+/// Compilers emit `ADRP` + `ADD` to compute an address, and `ADRP` + `LDR` to load from one.
+/// When both halves are stolen, the pair can be treated as a single `ADR` or `LDR (literal)`
+/// of the final address, which is shorter (and in range more often) than rewriting the `ADRP` alone.
+///
+/// The pair is only fused if the consumer overwrites the register loaded by `ADRP`; otherwise the
+/// page address is still live after the pair. When only the `ADRP` is stolen, it is rewritten on
+/// its own, which preserves the page address expected by the consumer in the original code.
+///
+/// # Parameters
+///
+/// * `adrp`: The `ADRP` instruction encoded as a 32-bit value.
+/// * `consumer`: The instruction following the `ADRP`.
+/// * `old_address`: The original address of the `ADRP` instruction.
+/// * `new_address`: The new address of the instructions.
+/// * `scratch_register`: Scratch register, passed onto [`rewrite_ldr_literal_target`].
+///
+/// # Returns
+///
+/// The rewritten pair, or `None` if the instructions are not a pair that can be fused.
+pub(crate) fn rewrite_adrp_pair(
+    adrp: u32,
+    consumer: u32,
+    old_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+) -> Option<Result<InstructionRewriteResult, CodeRewriterError>> {
+    let adrp = Adr(adrp.to_le());
+    let consumer = consumer.to_le();
+    let rd = adrp.rd();
+    if !adrp.is_pageaddress() || rd == 31 {
+        return None;
+    }
+
+    let page = adrp.extract_address(old_address);
+
+    // ADRP xd, page + ADD xd, xd, #lo12 -> ADR xd, target
+    if is_add_immediate_64(consumer) {
+        let add = AddImmediate(consumer);
+        if add.rd() != rd || add.rn() != rd || add.shift() {
+            return None;
+        }
+
+        let target = page.wrapping_add(add.imm12() as u16 as usize);
+        return Some(Ok(rewrite_adr_target(rd, target, new_address)));
+    }
+
+    // ADRP xd, page + LDR xd, [xd, #lo12] -> LDR xd, target
+    if is_ldr_unsigned_offset(consumer) {
+        let ldr = LdrImmediateUnsignedOffset(consumer);
+        if ldr.rn() != rd || ldr.rt() != rd {
+            return None;
+        }
+
+        // (size, opc) -> LDR (literal) mode, only general purpose loads overwrite the base.
+        let mode = match (ldr.size(), ldr.opc()) {
+            (0b10, 0b01) => 0b00, // LDR Wt
+            (0b11, 0b01) => 0b01, // LDR Xt
+            (0b10, 0b10) => 0b10, // LDRSW Xt
+            _ => return None,
+        };
+
+        let access_size = LdrImmediateUnsignedOffset::literal_access_size(mode, false);
+        let offset = (ldr.rn_offset() as u16 as usize) * access_size as usize;
+        let target = page.wrapping_add(offset);
+        return Some(rewrite_ldr_literal_target(
+            mode,
+            false,
+            rd,
+            target,
+            new_address,
+            scratch_register,
+        ));
+    }
+
+    None
+}
+
+/// ADD (immediate), 64-bit
+pub(crate) fn is_add_immediate_64(instruction: u32) -> bool {
+    (instruction & 0xff000000) == 0x91000000
+}
+
+/// LDR/PRFM (immediate, unsigned offset), general purpose registers
+pub(crate) fn is_ldr_unsigned_offset(instruction: u32) -> bool {
+    (instruction & 0x3f000000) == 0x39000000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    // ADRP x16, #0x10000 + ADD x16, x16, #0x10 -> ADR x16, #0x10010
+    #[case::adrp_add_to_adr(0x90000090_u32.to_be(), 0x10420091_u32.to_be(), 0, 0, "90000810")]
+    // ADRP x16, #0x10000 + ADD x16, x16, #0x10 -> ADRP x16, #0x10000 + ADD x16, x16, #0x10
+    #[case::adrp_add_4gib(0x90000090_u32.to_be(), 0x10420091_u32.to_be(), 0x100000, 0, "90080090 10420091")]
+    // ADRP x16, #0x10000 + LDR x16, [x16, #0x18] -> LDR x16, #0x10018
+    #[case::adrp_ldr_to_literal(0x90000090_u32.to_be(), 0x100e40f9_u32.to_be(), 0, 0, "d0000858")]
+    // ADRP x16, #0x10000 + LDR w16, [x16, #0x18] -> LDR w16, #0x10018
+    #[case::adrp_ldr_w_to_literal(0x90000090_u32.to_be(), 0x101a40b9_u32.to_be(), 0, 0, "d0000818")]
+    // ADRP x16, #0x10000 + LDRSW x16, [x16, #0x18] -> LDRSW x16, #0x10018
+    #[case::adrp_ldrsw_to_literal(0x90000090_u32.to_be(), 0x101a80b9_u32.to_be(), 0, 0, "d0000898")]
+    fn rewrite_pair(
+        #[case] adrp: u32,
+        #[case] consumer: u32,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let result = rewrite_adrp_pair(adrp, consumer, old_address, new_address, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.to_hex_string(), expected_hex.replace(' ', ""));
+    }
+
+    #[rstest]
+    #[case::add_other_destination(0x90000090_u32.to_be(), 0x11420091_u32.to_be())] // ADD x17, x16, #0x10
+    #[case::add_other_source(0x90000090_u32.to_be(), 0x30420091_u32.to_be())] // ADD x16, x17, #0x10
+    #[case::add_shifted(0x90000090_u32.to_be(), 0x10424091_u32.to_be())] // ADD x16, x16, #0x10, lsl #12
+    #[case::ldr_other_destination(0x90000090_u32.to_be(), 0x110e40f9_u32.to_be())] // LDR x17, [x16, #0x18]
+    #[case::ldr_simd(0x90000090_u32.to_be(), 0x100e40fd_u32.to_be())] // LDR d16, [x16, #0x18]
+    #[case::prfm(0x90000090_u32.to_be(), 0x000e80f9_u32.to_be())] // PRFM pldl1keep, [x16, #0x18]
+    #[case::adr(0x10000010_u32.to_be(), 0x10420091_u32.to_be())] // ADR x16, #0 + ADD x16, x16, #0x10
+    #[case::not_a_consumer(0x90000090_u32.to_be(), 0x1f2003d5_u32.to_be())] // NOP
+    fn rewrite_pair_not_fused(#[case] adrp: u32, #[case] consumer: u32) {
+        assert!(rewrite_adrp_pair(adrp, consumer, 0, 0, None).is_none());
+    }
+}
@@ -1,94 +1,160 @@
 extern crate alloc;
 
 use crate::{
-    all_registers::AllRegisters,
     code_rewriter::{
-        helpers::{emit_mov_upper_48_bits_const_to_reg, load_address_4g_with_remainder},
+        helpers::{
+            emit_mov_const_to_reg, emit_mov_upper_48_bits_const_to_reg,
+            load_address_4g_with_remainder,
+        },
         instruction_rewrite_result::InstructionRewriteResult,
     },
     instructions::{
-        ldr_immediate_unsigned_offset::LdrImmediateUnsignedOffset, ldr_literal::LdrLiteral,
+        add_immediate::AddImmediate, ldr_immediate_unsigned_offset::LdrImmediateUnsignedOffset,
+        ldr_literal::LdrLiteral,
     },
 };
-use alloc::vec::Vec;
-use reloaded_hooks_portable::api::{
-    jit::compiler::JitError, rewriter::code_rewriter::CodeRewriterError,
-};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
 
 /// Rewrites the `LDR Literal` (Load) instruction for a new address.
-/// This also covers STR, but for simplicity, we will just refer to it as LDR from now.
+/// This also covers `LDRSW`, SIMD `LDR` and `PRFM`, for simplicity, we will just refer to it as LDR from now.
 ///
 /// The `LDR Literal` instruction loads a value from a PC offset.
-/// specific condition flags. This function is designed to modify the `Bcc` instruction's
-/// encoding to adjust for a new memory location.
+/// This function is designed to modify the `LDR` instruction's encoding to adjust for a new memory location.
 ///
 /// # Parameters
 ///
 /// * `instruction`: The original `LDR` instruction encoded as a 32-bit value.
 /// * `old_address`: The original address associated with the `LDR` instruction.
 /// * `new_address`: The new address of the instruction.
+/// * `scratch_register`: Register used to hold the address for SIMD loads and prefetches.
 ///
 /// # Behaviour
 ///
-/// The Branch Conditional instruction is rewritten as one of the following:
+/// The LDR instruction is rewritten as one of the following:
 /// - LDR Literal
 /// - ADRP + LDR (w/ Unsigned Offset)
+/// - ADRP + ADD + LDR (w/ Unsigned Offset)
 /// - MOV Address to Register + LDR
 pub(crate) fn rewrite_ldr_literal(
     instruction: u32,
     old_address: usize,
     new_address: usize,
+    scratch_register: Option<u8>,
 ) -> Result<InstructionRewriteResult, CodeRewriterError> {
     let orig_ins = LdrLiteral(instruction.to_le());
     let orig_target = (old_address as isize).wrapping_add(orig_ins.offset() as isize);
-    let delta = orig_target.wrapping_sub(new_address as isize);
+    rewrite_ldr_literal_target(
+        orig_ins.mode(),
+        orig_ins.is_simd(),
+        orig_ins.rt(),
+        orig_target as usize,
+        new_address,
+        scratch_register,
+    )
+}
+
+/// Emits the equivalent of a `LDR Literal` instruction which loads from `target`, placed at `new_address`.
+///
+/// # Parameters
+///
+/// * `mode`: The mode of the literal instruction, see [`LdrLiteral`].
+/// * `is_simd`: True if this is a SIMD / FP load.
+/// * `rt`: The register to load into, or the prefetch operation for `PRFM`.
+/// * `target`: The address to load from.
+/// * `new_address`: The address of the emitted instruction(s).
+/// * `scratch_register`: Register used to hold the address for SIMD loads and prefetches.
+pub(crate) fn rewrite_ldr_literal_target(
+    mode: u8,
+    is_simd: bool,
+    rt: u8,
+    target: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+) -> Result<InstructionRewriteResult, CodeRewriterError> {
+    let delta = (target as isize).wrapping_sub(new_address as isize);
 
     // Output as another LDR if within 1MiB range
     if (-0x100000..=0xFFFFF).contains(&delta) {
         return Ok(InstructionRewriteResult::LdrLiteral(
-            LdrLiteral::new_load_literal(
-                orig_ins.mode(),
-                orig_ins.rt(),
-                orig_ins.is_simd(),
-                delta as i32,
-            )
-            .unwrap()
-            .0
-            .to_le(),
+            LdrLiteral::new_load_literal(mode, rt, is_simd, delta as i32)
+                .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?
+                .0
+                .to_le(),
         ));
     }
 
-    // If it's a 'prefetch' operation, discard it entirely; because prefetch with multiple
-    // operations will be less efficient.
-    if orig_ins.mode() == 0b11 {
-        return Ok(InstructionRewriteResult::None);
-    }
+    // General purpose loads can use the destination register to hold the address,
+    // SIMD loads and prefetches need a scratch register.
+    let is_prefetch = !is_simd && mode == 0b11;
+    let base = if is_simd || is_prefetch {
+        match scratch_register {
+            Some(x) => x,
+            // If it's a 'prefetch' operation, discard it entirely; it's only a hint.
+            None if is_prefetch => return Ok(InstructionRewriteResult::None),
+            None => {
+                return Err(CodeRewriterError::NoScratchRegister(
+                    "rewrite_ldr_literal".to_string(),
+                ))
+            }
+        }
+    } else {
+        rt
+    };
 
     // Output as:
     // - ADRP
-    // - ADD (Optional)
+    // - ADD (Optional, if remainder is not encodable in LDR)
     // - LDR
-    // + 4 for 'next instruction', since we are placing a BCC first.
     if (-0x100000000..=0xFFFFFFFF).contains(&delta) {
-        let load = load_address_4g_with_remainder(new_address, orig_target as usize, orig_ins.rt());
-        let ldr = get_ldr_immediate_for_literal(orig_ins, load.1 as i32)
-            .unwrap()
-            .0
-            .to_le();
-        return Ok(InstructionRewriteResult::AdrpAndLdrUnsignedOffset(
-            load.0, ldr,
-        ));
+        let load = load_address_4g_with_remainder(new_address, target, base);
+        return match LdrImmediateUnsignedOffset::new_from_literal(
+            mode,
+            is_simd,
+            rt,
+            load.1 as i32,
+            base,
+        ) {
+            Ok(ldr) => Ok(InstructionRewriteResult::AdrpAndLdrUnsignedOffset(
+                load.0,
+                ldr.0.to_le(),
+            )),
+            Err(_) => Ok(InstructionRewriteResult::AdrpAndAddAndLdrUnsignedOffset(
+                load.0,
+                AddImmediate::new(true, base, base, load.1 as u16)
+                    .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?
+                    .0
+                    .to_le(),
+                get_ldr_immediate_for_literal(mode, is_simd, rt, 0, base)?,
+            )),
+        };
+    }
+
+    // Prefetching with a sequence of moves is less efficient than not prefetching.
+    if is_prefetch {
+        return Ok(InstructionRewriteResult::None);
     }
 
     // Output as:
     // - MOV Immediate
     // - LDR
-    let mov_instr = emit_mov_upper_48_bits_const_to_reg(orig_ins.rt(), orig_target as usize); // Assuming emit_mov_const_to_reg can handle this as well
-    let ldr = get_ldr_immediate_for_literal(orig_ins, (orig_target & 0xFFFF) as i32);
-
     let mut result = Vec::with_capacity(5);
-    mov_instr.append_to_buffer(&mut result);
-    result.push(ldr.unwrap().0.to_le());
+    match LdrImmediateUnsignedOffset::new_from_literal(
+        mode,
+        is_simd,
+        rt,
+        (target & 0xFFFF) as i32,
+        base,
+    ) {
+        Ok(ldr) => {
+            emit_mov_upper_48_bits_const_to_reg(base, target).append_to_buffer(&mut result);
+            result.push(ldr.0.to_le());
+        }
+        Err(_) => {
+            emit_mov_const_to_reg(base, target).append_to_buffer(&mut result);
+            result.push(get_ldr_immediate_for_literal(mode, is_simd, rt, 0, base)?);
+        }
+    }
 
     Ok(InstructionRewriteResult::MovImmediateAndLdrLiteral(
         result.into_boxed_slice(),
@@ -96,42 +162,15 @@ pub(crate) fn rewrite_ldr_literal(
 }
 
 fn get_ldr_immediate_for_literal(
-    orig_ins: LdrLiteral,
+    mode: u8,
+    is_simd: bool,
+    rt: u8,
     offset: i32,
-) -> Result<LdrImmediateUnsignedOffset, JitError<AllRegisters>> {
-    if orig_ins.is_simd() {
-        LdrImmediateUnsignedOffset::new_mov_from_reg_vector(orig_ins.rt(), orig_ins.rt(), offset)
-    } else {
-        // 32 bit
-        if orig_ins.mode() == 0b00 {
-            LdrImmediateUnsignedOffset::new_mov_from_reg(
-                false,
-                orig_ins.rt(),
-                offset,
-                orig_ins.rt(),
-            )
-        }
-        // 64 bit
-        else if orig_ins.mode() == 0b01 {
-            LdrImmediateUnsignedOffset::new_mov_from_reg(true, orig_ins.rt(), offset, orig_ins.rt())
-        }
-        // 32 bit, signed
-        else {
-            // Intuition says:
-            // https://developer.arm.com/documentation/ddi0602/2022-03/Base-Instructions/LDR--immediate---Load-Register--immediate--
-            // 'Shared Decode' section.
-
-            // But, disassemblers don't like this, so we're using
-            // https://developer.arm.com/documentation/ddi0602/2022-03/Base-Instructions/LDRSW--immediate---Load-Register-Signed-Word--immediate--
-            LdrImmediateUnsignedOffset::new_mov_from_reg_with_opc(
-                false,
-                orig_ins.rt(),
-                offset,
-                orig_ins.rt(),
-                0b10,
-            )
-        }
-    }
+    base: u8,
+) -> Result<u32, CodeRewriterError> {
+    LdrImmediateUnsignedOffset::new_from_literal(mode, is_simd, rt, offset, base)
+        .map(|x| x.0.to_le())
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))
 }
 
 #[cfg(test)]
@@ -170,7 +209,41 @@ mod tests {
         #[case] new_address: usize,
         #[case] expected_hex: &str,
     ) {
-        let result = rewrite_ldr_literal(old_instruction, old_address, new_address).unwrap();
+        let result = rewrite_ldr_literal(old_instruction, old_address, new_address, None).unwrap();
         assert_eq!(result.to_hex_string(), expected_hex);
     }
+
+    #[rstest]
+    // [Within 4GiB, needs scratch register]
+    #[case::ldr_simd_s_4GiB(0x0000001C_u32.to_be(), 0x100000, 0, "11080090 200240bd")] // LDR s0, #0 -> adrp x17, #0x100000 + ldr s0, [x17]
+    #[case::ldr_simd_d_4GiB(0x0000005C_u32.to_be(), 0x100000, 0, "11080090 200240fd")] // LDR d0, #0 -> adrp x17, #0x100000 + ldr d0, [x17]
+    #[case::ldr_simd_q_4GiB(0x0000009C_u32.to_be(), 0x100000, 0, "11080090 2002c03d")] // LDR q0, #0 -> adrp x17, #0x100000 + ldr q0, [x17]
+    #[case::ldr_simd_q_4GiB_offset(0x0010009C_u32.to_be(), 0x100000, 0, "11080090 2082c03d")] // LDR q0, #512 -> adrp x17, #0x100000 + ldr q0, [x17, #512]
+    #[case::prfm_4GiB(0x081000D8_u32.to_be(), 0x100000, 0, "11080090 280281f9")]
+    // PRFM PLIL1KEEP, #512 -> adrp x17, #0x100000 + prfm plil1keep, [x17, #512]
+    // [Within 4GiB, remainder not a multiple of access size]
+    #[case::ldr_64bit_4GiB_unaligned(0x20000058_u32.to_be(), 0x100000, 0, "00080090 00100091 000040f9")] // LDR x0, #4 -> adrp x0, #0x100000 + add x0, x0, #4 + ldr x0, [x0]
+    // [Last Resort]
+    #[case::ldr_simd_q_last_resort(0x0010009C_u32.to_be(), 0x100000000, 0, "1100a0d2 3100c0f2 2082c03d")] // LDR q0, #512 -> mov x17 + ldr q0, [x17, #512]
+    #[case::ldr_64bit_last_resort_unaligned(0x20000058_u32.to_be(), 0x10000FFF0, 0, "80fe9fd2 0000a0f2 2000c0f2 000040f9")] // LDR x0, #4 -> mov x0, 0x10000fff4 + ldr x0, [x0]
+    #[case::prfm_last_resort(0x081000D8_u32.to_be(), 0x100000000, 0, "")] // PRFM PLIL1KEEP, #512 -> none
+    fn test_rewrite_ldr_with_scratch(
+        #[case] old_instruction: u32,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let result =
+            rewrite_ldr_literal(old_instruction, old_address, new_address, Some(17)).unwrap();
+        assert_eq!(result.to_hex_string(), expected_hex.replace(' ', ""));
+    }
+
+    #[test]
+    fn test_rewrite_ldr_simd_without_scratch() {
+        let result = rewrite_ldr_literal(0x0000009C_u32.to_be(), 0x100000, 0, None);
+        assert!(matches!(
+            result,
+            Err(CodeRewriterError::NoScratchRegister(_))
+        ));
+    }
 }
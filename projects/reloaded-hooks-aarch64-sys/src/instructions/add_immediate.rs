@@ -22,16 +22,16 @@ bitfield! {
     /// Shift to apply to the immediate value.
     /// 0 -> 0
     /// 1 -> LSL 12 (i.e. multiply by 4096)
-    shift, set_shift: 22;
+    pub shift, set_shift: 22;
 
     /// Immediate value to add.
-    i16, imm12, set_imm12: 21, 10;
+    pub i16, imm12, set_imm12: 21, 10;
 
     /// Register number for the source.
-    rn, set_rn: 9, 5;
+    pub rn, set_rn: 9, 5;
 
     /// Register number for the destination.
-    rd, set_rd: 4, 0;
+    pub rd, set_rd: 4, 0;
 }
 
 impl AddImmediate {
@@ -77,6 +77,7 @@ impl Adr {
     /// # Returns
     ///
     /// The calculated absolute address which the ADR instruction will load into the register.
+    /// For ADRP, this is relative to the page containing `base_address`.
     pub fn extract_address(&self, base_address: usize) -> usize {
         let immhi = self.immhi();
         let immlo = self.immlo() as i32;
@@ -84,7 +85,7 @@ impl Adr {
         // Combine the immhi and immlo to get the full immediate value.
         let offset = (immhi << 2) | (immlo & 0b11);
         if self.is_pageaddress() {
-            (base_address as i64 + (offset as i64 * 4096)) as usize
+            ((base_address & !4095) as i64 + (offset as i64 * 4096)) as usize
         } else {
             (base_address as i64 + offset as i64) as usize
         }
@@ -37,7 +37,19 @@ impl LdpImmediate {
         dst_2: u8,
         stack_offset: i32,
     ) -> Result<Self, JitError<AllRegisters>> {
-        Self::ldp_common(is_64bit, dst_1, dst_2, stack_offset, 0b10100011) // post index
+        Self::ldp_common(is_64bit, dst_1, dst_2, 31, stack_offset, 0b10100011) // post index
+    }
+
+    /// Creates a `LDP` instruction which loads 2 registers from the address in `base`, then
+    /// adds `offset` to `base`.
+    pub fn new_load_pair_post_indexed(
+        is_64bit: bool,
+        dst_1: u8,
+        dst_2: u8,
+        base: u8,
+        offset: i32,
+    ) -> Result<Self, JitError<AllRegisters>> {
+        Self::ldp_common(is_64bit, dst_1, dst_2, base, offset, 0b10100011) // post index
     }
 
     pub fn new_mov_from_stack(
@@ -46,13 +58,14 @@ impl LdpImmediate {
         dst_2: u8,
         stack_offset: i32,
     ) -> Result<Self, JitError<AllRegisters>> {
-        Self::ldp_common(is_64bit, dst_1, dst_2, stack_offset, 0b10100101) // signed offset
+        Self::ldp_common(is_64bit, dst_1, dst_2, 31, stack_offset, 0b10100101) // signed offset
     }
 
     fn ldp_common(
         is_64bit: bool,
         dst_1: u8,
         dst_2: u8,
+        base: u8,
         stack_offset: i32,
         opcode: u8,
     ) -> Result<LdpImmediate, JitError<AllRegisters>> {
@@ -104,8 +117,8 @@ impl LdpImmediate {
         value.set_opcode(opcode); // variant-specific opcode
         value.set_size(if is_64bit { 10 } else { 0 });
 
-        // Set Stack Pointer as Source Register
-        value.set_rn(31);
+        // Set Source Register, 31 for Stack Pointer
+        value.set_rn(base);
 
         // Set parameters
         value.set_rn_offset(encoded_offset as u8);
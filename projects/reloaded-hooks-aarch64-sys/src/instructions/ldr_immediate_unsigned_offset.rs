@@ -1,6 +1,6 @@
 extern crate alloc;
 
-use super::errors::must_be_divisible_by;
+use super::errors::{exceeds_maximum_range, must_be_divisible_by};
 use crate::all_registers::AllRegisters;
 use crate::instructions::errors::return_stack_out_of_range;
 use bitfield::bitfield;
@@ -15,22 +15,22 @@ bitfield! {
     u8;
 
     /// Size field. 1 if 64-bit register, else 0.
-    size, set_size: 31, 30;
+    pub size, set_size: 31, 30;
 
     /// The raw opcode used for this operation.
-    opcode, set_opcode: 29, 24;
+    pub opcode, set_opcode: 29, 24;
 
     /// The operation used, dictates if this is a load or store.
-    opc, set_opc: 23, 22;
+    pub opc, set_opc: 23, 22;
 
     /// Source register to which the immediate offset is added.
-    i16, rn_offset, set_rn_offset: 21, 10;
+    pub i16, rn_offset, set_rn_offset: 21, 10;
 
     /// Register number for the first operand (source), 31 for SP.
-    rn, set_rn: 9, 5;
+    pub rn, set_rn: 9, 5;
 
     /// Register number for the destination where the result will be stored.
-    rt, set_rt: 4, 0;
+    pub rt, set_rt: 4, 0;
 }
 
 impl LdrImmediateUnsignedOffset {
@@ -105,6 +105,33 @@ impl LdrImmediateUnsignedOffset {
         Self::new_mov_from_reg_with_opc(is_64bit, destination, source_offset, source, 0b01)
     }
 
+    /// Creates a `LDRB` (if `is_load`) or `STRB` instruction, accessing the byte at `offset` from
+    /// the address in `address`.
+    pub fn new_byte(
+        is_load: bool,
+        register: u8,
+        offset: i32,
+        address: u8,
+    ) -> Result<Self, JitError<AllRegisters>> {
+        if !(0..=4095).contains(&offset) {
+            return Err(exceeds_maximum_range(
+                "[LDRB/STRB Immediate Unsigned Offset]",
+                "0..4095",
+                offset as isize,
+            ));
+        }
+
+        let mut value = LdrImmediateUnsignedOffset(0);
+        value.set_opcode(0b111001);
+        value.set_opc(if is_load { 0b01 } else { 0b00 });
+        value.set_size(0b00); // 8-bit
+
+        value.set_rn(address);
+        value.set_rt(register);
+        value.set_rn_offset(offset as i16);
+        Ok(value)
+    }
+
     pub fn new_mov_from_stack(
         is_64bit: bool,
         destination: u8,
@@ -156,6 +183,72 @@ impl LdrImmediateUnsignedOffset {
         Ok(value)
     }
 
+    /// Creates the unsigned offset equivalent of a `LDR (literal)` instruction.
+    /// This covers `LDR`, `LDRSW`, SIMD `LDR` and `PRFM`.
+    ///
+    /// # Parameters
+    /// - `mode`: The mode of the literal instruction, see [`LdrLiteral`](super::ldr_literal::LdrLiteral).
+    /// - `is_simd`: True if this is a SIMD / FP load.
+    /// - `destination`: The register to load into, or the prefetch operation for `PRFM`.
+    /// - `source_offset`: Offset from `source`, must be a multiple of [`Self::literal_access_size`].
+    /// - `source`: The register holding the base address.
+    pub fn new_from_literal(
+        mode: u8,
+        is_simd: bool,
+        destination: u8,
+        source_offset: i32,
+        source: u8,
+    ) -> Result<Self, JitError<AllRegisters>> {
+        let access_size = Self::literal_access_size(mode, is_simd);
+        if (source_offset & (access_size - 1)) != 0 {
+            return Err(must_be_divisible_by(
+                "[LDR Immediate Unsigned Offset]",
+                source_offset as isize,
+                access_size as isize,
+            ));
+        }
+
+        if !(0..=4095 * access_size).contains(&source_offset) {
+            return Err(return_stack_out_of_range(
+                "[LDR Immediate Unsigned Offset]",
+                "0..4095 * access size",
+                source_offset as isize,
+            ));
+        }
+
+        // (size, opc) for each literal mode.
+        let (size, opc) = match (is_simd, mode) {
+            (false, 0b00) => (0b10, 0b01), // LDR Wt
+            (false, 0b01) => (0b11, 0b01), // LDR Xt
+            (false, 0b10) => (0b10, 0b10), // LDRSW Xt
+            (false, _) => (0b11, 0b10),    // PRFM
+            (true, 0b00) => (0b10, 0b01),  // LDR St
+            (true, 0b01) => (0b11, 0b01),  // LDR Dt
+            (true, _) => (0b00, 0b11),     // LDR Qt
+        };
+
+        let mut value = LdrImmediateUnsignedOffset(0);
+        value.set_opcode(if is_simd { 0b111101 } else { 0b111001 });
+        value.set_size(size);
+        value.set_opc(opc);
+        value.set_rn(source);
+        value.set_rt(destination);
+        value.set_rn_offset((source_offset / access_size) as i16);
+        Ok(value)
+    }
+
+    /// Returns the number of bytes accessed by a `LDR (literal)` instruction with the given mode.
+    /// The offset of the unsigned offset form is scaled by this value.
+    pub fn literal_access_size(mode: u8, is_simd: bool) -> i32 {
+        match (is_simd, mode) {
+            (false, 0b01) | (false, 0b11) => 8,
+            (false, _) => 4,
+            (true, 0b00) => 4,
+            (true, 0b01) => 8,
+            (true, _) => 16,
+        }
+    }
+
     pub fn new_mov_from_stack_vector(
         destination: u8,
         stack_offset: i32,
@@ -0,0 +1,97 @@
+extern crate alloc;
+
+use bitfield::bitfield;
+
+// https://developer.arm.com/documentation/ddi0602/2022-03/Base-Instructions/LDAXR--Load-Acquire-Exclusive-Register-
+// https://developer.arm.com/documentation/ddi0602/2022-03/Base-Instructions/STLXR--Store-Release-Exclusive-Register-
+bitfield! {
+    /// `LoadStoreExclusive` represents the bitfields of the LDXR/LDAXR and STXR/STLXR instructions
+    /// in AArch64 architecture.
+    pub struct LoadStoreExclusive(u32);
+    impl Debug;
+    u8;
+
+    /// Size of the accessed value.
+    /// 0b10 for 32-bit and 0b11 for 64-bit.
+    pub size, set_size: 31, 30;
+
+    /// Opcode for the instruction, should be `0b0010000`.
+    opcode, set_opcode: 29, 23;
+
+    /// True if this is a load, false if this is a store.
+    pub is_load, set_is_load: 22;
+
+    /// Register receiving the status of the store (0 on success). Must be `0b11111` for loads.
+    pub rs, set_rs: 20, 16;
+
+    /// True for the acquire (load) / release (store) variants.
+    pub is_ordered, set_is_ordered: 15;
+
+    /// Second register of pair variants, unused, must be `0b11111`.
+    rt2, set_rt2: 14, 10;
+
+    /// Register number holding the address.
+    pub rn, set_rn: 9, 5;
+
+    /// Register number of the loaded/stored value.
+    pub rt, set_rt: 4, 0;
+}
+
+impl LoadStoreExclusive {
+    /// Create a new LDAXR instruction, loading `target` from the address in `address`.
+    pub fn new_load_acquire(is_64bit: bool, target: u8, address: u8) -> Self {
+        Self::new_common(is_64bit, true, 0b11111, target, address)
+    }
+
+    /// Create a new STLXR instruction, storing `source` to the address in `address`.
+    /// `status` receives 0 if the store succeeded, else 1.
+    pub fn new_store_release(is_64bit: bool, status: u8, source: u8, address: u8) -> Self {
+        Self::new_common(is_64bit, false, status, source, address)
+    }
+
+    fn new_common(is_64bit: bool, is_load: bool, rs: u8, rt: u8, rn: u8) -> Self {
+        let mut value = LoadStoreExclusive(0);
+        value.set_size(if is_64bit { 0b11 } else { 0b10 });
+        value.set_opcode(0b0010000);
+        value.set_is_load(is_load);
+        value.set_rs(rs);
+        value.set_is_ordered(true);
+        value.set_rt2(0b11111);
+        value.set_rn(rn);
+        value.set_rt(rt);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(true, 17, 16, "11fe5fc8")] // ldaxr x17, [x16]
+    #[case(false, 1, 0, "01fc5f88")] // ldaxr w1, [x0]
+    fn load_acquire(
+        #[case] is_64bit: bool,
+        #[case] target: u8,
+        #[case] address: u8,
+        #[case] expected_hex: &str,
+    ) {
+        let ins = LoadStoreExclusive::new_load_acquire(is_64bit, target, address);
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+    }
+
+    #[rstest]
+    #[case(true, 15, 17, 16, "11fe0fc8")] // stlxr w15, x17, [x16]
+    fn store_release(
+        #[case] is_64bit: bool,
+        #[case] status: u8,
+        #[case] source: u8,
+        #[case] address: u8,
+        #[case] expected_hex: &str,
+    ) {
+        let ins = LoadStoreExclusive::new_store_release(is_64bit, status, source, address);
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+    }
+}
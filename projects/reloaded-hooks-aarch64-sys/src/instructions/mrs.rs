@@ -0,0 +1,49 @@
+use bitfield::bitfield;
+
+// https://developer.arm.com/documentation/ddi0602/2022-03/Base-Instructions/MRS--Move-System-Register-
+bitfield! {
+    /// `Mrs` represents the bitfields of the MRS instruction in AArch64 architecture,
+    /// which reads a system register into a general purpose register.
+    pub struct Mrs(u32);
+    impl Debug;
+    u8;
+
+    /// Opcode for the instruction, should be `0b1101010100111`.
+    /// The lowest bit is the low bit of `op0`, always 1 for registers accessible from EL0.
+    u16, opcode, set_opcode: 31, 19;
+
+    /// The system register to read, encoded as `op1:CRn:CRm:op2`.
+    u16, system_register, set_system_register: 18, 5;
+
+    /// Register number for the destination where the result will be stored.
+    pub rt, set_rt: 4, 0;
+}
+
+impl Mrs {
+    /// Encoding of `TPIDR_EL0` (op1 = 3, CRn = 13, CRm = 0, op2 = 2).
+    const TPIDR_EL0: u16 = 0b01111010000010;
+
+    /// Create a new `MRS Xt, TPIDR_EL0` instruction, which reads the thread pointer.
+    pub fn new_read_thread_pointer(destination: u8) -> Self {
+        let mut value = Mrs(0);
+        value.set_opcode(0b1101010100111);
+        value.set_system_register(Self::TPIDR_EL0);
+        value.set_rt(destination);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, "40d03bd5")] // mrs x0, tpidr_el0
+    #[case(17, "51d03bd5")] // mrs x17, tpidr_el0
+    fn read_thread_pointer(#[case] destination: u8, #[case] expected_hex: &str) {
+        let ins = Mrs::new_read_thread_pointer(destination);
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+    }
+}
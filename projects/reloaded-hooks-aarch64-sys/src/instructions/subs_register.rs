@@ -0,0 +1,73 @@
+use bitfield::bitfield;
+
+bitfield! {
+    /// `SubsRegister` represents the bitfields of the SUBS (shifted register) instruction
+    /// in AArch64 architecture. The bitfields are described as follows:
+    pub struct SubsRegister(u32);
+    impl Debug;
+    u8;
+
+    /// Set flag determines whether the operation is 32 or 64 bits.
+    /// 0 for 32-bit and 1 for 64-bit.
+    sf, set_sf: 31;
+
+    /// Opcode for the SUBS instruction, generally `0b1101011`.
+    opcode, set_opcode: 30, 24;
+
+    /// Defines the type of shift to be applied to `rm`. Generally `0b00`.
+    shift_type, set_shift_type: 23, 22;
+
+    /// Always 0.
+    unk, set_unk: 21;
+
+    /// Register number for the second operand (source).
+    rm, set_rm: 20, 16;
+
+    /// Number of bits to shift `rm` by (unsigned).
+    shift_amount, set_shift_amount: 15, 10;
+
+    /// Register number for the first operand (source).
+    rn, set_rn: 9, 5;
+
+    /// Register number for the destination where the result will be stored.
+    rd, set_rd: 4, 0;
+}
+
+impl SubsRegister {
+    /// Create a new CMP instruction, which compares `left` with `right` and sets the flags.
+    /// Note that CMP is an alias for SUBS with the destination being 'ZR' or 'WZR'.
+    pub fn new_cmp(is_64bit: bool, left: u8, right: u8) -> Self {
+        // Note: Compiler is smart enough to optimize this away as a constant
+        // Which is why we moved the non-constant stuff to the bottom.
+        let mut value = SubsRegister(0);
+        value.set_opcode(0b1101011);
+        value.set_shift_type(0b00);
+        value.set_shift_amount(0);
+        value.set_rd(31); // ZR for 64-bit, WZR for 32-bit
+
+        value.set_sf(is_64bit);
+        value.set_rn(left);
+        value.set_rm(right);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(true, 30, 10, "df030aeb")] // cmp x30, x10
+    #[case(false, 1, 2, "3f00026b")] // cmp w1, w2
+    fn cmp(
+        #[case] is_64bit: bool,
+        #[case] left: u8,
+        #[case] right: u8,
+        #[case] expected_hex: &str,
+    ) {
+        let ins = SubsRegister::new_cmp(is_64bit, left, right);
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+    }
+}
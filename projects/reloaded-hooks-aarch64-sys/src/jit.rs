@@ -6,9 +6,13 @@ use crate::{
     helpers::{vec_i32_to_u8, vec_u8_to_i32},
     instructions::b::B,
     jit_instructions::{
+        atomic_add::encode_atomic_add,
         branch_absolute::{encode_call_absolute, encode_jump_absolute},
         branch_ip_relative::{encode_call_ip_relative, encode_jump_ip_relative},
         branch_relative::{encode_call_relative, encode_jump_relative},
+        enter_guard::encode_enter_guard,
+        exit_guard::encode_exit_guard,
+        filter_caller::encode_filter_caller,
         jump_absolute_indirect::encode_jump_absolute_indirect,
         mov::encode_mov,
         mov_from_stack::encode_mov_from_stack,
@@ -27,7 +31,6 @@ use alloc::vec::Vec;
 use core::{
     hint::unreachable_unchecked,
     mem::{self, size_of},
-    ptr::read_unaligned,
 };
 use reloaded_hooks_portable::api::jit::{
     call_relative_operation::CallRelativeOperation,
@@ -36,6 +39,7 @@ use reloaded_hooks_portable::api::jit::{
     jump_relative_operation::JumpRelativeOperation,
     operation::Operation,
 };
+use reloaded_hooks_portable::helpers::read_code::read_code_as;
 
 pub struct JitAarch64 {}
 
@@ -91,6 +95,13 @@ impl Jit<AllRegisters> for JitAarch64 {
         JitCapabilities::CAN_MULTI_PUSH
             | JitCapabilities::CAN_ENCODE_IP_RELATIVE_CALL
             | JitCapabilities::CAN_ENCODE_IP_RELATIVE_JUMP
+            | JitCapabilities::EMITS_LANDING_PADS
+    }
+
+    fn landing_pad() -> &'static [u8] {
+        // bti jc; stubs may be entered via `blr` and `br` with any register.
+        // This is a hint (NOP) on hardware without BTI, or outside of guarded pages.
+        &[0xDF, 0x24, 0x03, 0xD5]
     }
 
     fn max_branch_bytes() -> u32 {
@@ -98,7 +109,7 @@ impl Jit<AllRegisters> for JitAarch64 {
     }
 
     fn fill_nops(arr: &mut [u8]) {
-        const NOP: [u8; 4] = [0xD5, 0x03, 0x20, 0x1F];
+        const NOP: [u8; 4] = [0x1F, 0x20, 0x03, 0xD5]; // nop
 
         // Ensure the array length is a multiple of 4 (size of an ARM64 instruction)
         for chunk in arr.chunks_mut(4) {
@@ -141,7 +152,7 @@ impl Jit<AllRegisters> for JitAarch64 {
         }
 
         // Need to do from BE for some reason.
-        let num: u32 = u32::from_le(unsafe { read_unaligned(ins_address as *const u32) });
+        let num: u32 = u32::from_le(unsafe { read_code_as::<u32>(ins_address) });
         let instruction = B(num);
 
         if !is_b_or_bl(instruction.0) {
@@ -202,5 +213,26 @@ fn encode_instruction_aarch64(
         Operation::MultiPush(x) => encode_multi_push(x, pc, buf),
         Operation::MultiPop(x) => encode_multi_pop(x, pc, buf),
         Operation::MovToStack(_) => unsafe { unreachable_unchecked() }, // unreachable because JITCapabilities doesn't opt in
+        Operation::AtomicAdd(x) => encode_atomic_add(x, pc, buf),
+        Operation::EnterGuard(x) => encode_enter_guard(x, pc, buf),
+        Operation::ExitGuard(x) => encode_exit_guard(x, pc, buf),
+        Operation::FilterCaller(x) => encode_filter_caller(x, pc, buf),
+        // Only needed for moving between register kinds with no direct move, e.g. x87 to SSE.
+        Operation::MovFloatFromStack(x) => Err(JitError::InvalidRegister(x.target)),
+        Operation::MovFloatToStack(x) => Err(JitError::InvalidRegister(x.register)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jit::JitAarch64;
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use reloaded_hooks_portable::api::jit::compiler::Jit;
+
+    #[test]
+    fn can_fill_nops() {
+        let mut arr = [0u8; 8];
+        JitAarch64::fill_nops(&mut arr);
+        assert_eq!(instruction_buffer_as_hex_u8(&arr), "1f2003d51f2003d5");
     }
 }
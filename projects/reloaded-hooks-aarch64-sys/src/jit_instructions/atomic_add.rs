@@ -0,0 +1,112 @@
+extern crate alloc;
+
+use super::push_constant::encode_mov_constant_to_reg;
+use crate::{
+    all_registers::AllRegisters,
+    instructions::{
+        add_immediate::AddImmediate, cbz::Cbz, errors::exceeds_maximum_range,
+        load_store_exclusive::LoadStoreExclusive, sub_immediate::SubImmediate,
+    },
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::AtomicAdd};
+
+/// Encoded as MOVZ/MOVK + LDAXR/ADD/STLXR/CBNZ loop.
+/// Uses 3 scratch registers; for the address, the value and the store status.
+pub fn encode_atomic_add(
+    x: &AtomicAdd<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    if x.value.unsigned_abs() > 4095 {
+        return Err(exceeds_maximum_range(
+            "[Atomic Add]",
+            "-4095..4095",
+            x.value as isize,
+        ));
+    }
+
+    let scratch = x.scratch.borrow();
+    let mut regs = scratch.iter().filter(|reg| reg.is_64());
+    let (address, value, status) = match (regs.next(), regs.next(), regs.next()) {
+        (Some(address), Some(value), Some(status)) => (
+            address.register_number() as u8,
+            value.register_number() as u8,
+            status.register_number() as u8,
+        ),
+        _ => {
+            return Err(JitError::NoScratchRegister(
+                "for AtomicAdd. 3 registers are required.".to_string(),
+            ))
+        }
+    };
+
+    encode_mov_constant_to_reg(x.address, address, pc, buf)?;
+
+    let load = LoadStoreExclusive::new_load_acquire(true, value, address);
+    let add = if x.value >= 0 {
+        AddImmediate::new(true, value, value, x.value as u16)?.0
+    } else {
+        SubImmediate::new(true, value, value, x.value.unsigned_abs() as u16)?.0
+    };
+    let store = LoadStoreExclusive::new_store_release(true, status, value, address);
+
+    // Retry from the load if another thread wrote to the value in the meantime.
+    let retry = Cbz::assemble_cbnz(-12, status, false)?;
+
+    buf.push(load.0.to_le() as i32);
+    buf.push(add.to_le() as i32);
+    buf.push(store.0.to_le() as i32);
+    buf.push(retry.0.to_le() as i32);
+    *pc += 16;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::atomic_add::encode_atomic_add;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // movz x9, #0x1234; ldaxr x10, [x9]; add x10, x10, #1; stlxr w11, x10, [x9]; cbnz w11, -12
+    #[case(0x1234, 1, vec![x9, x10, x11], "894682d22afd5fc84a0500912afd0bc8abffff35")]
+    // movz x9, #0x1234; ldaxr x10, [x9]; sub x10, x10, #1; stlxr w11, x10, [x9]; cbnz w11, -12
+    #[case(0x1234, -1, vec![x9, x10, x11], "894682d22afd5fc84a0500d12afd0bc8abffff35")]
+    // Vector registers are skipped.
+    #[case(0x1234, 1, vec![v0, x9, x10, x11], "894682d22afd5fc84a0500912afd0bc8abffff35")]
+    fn standard_cases(
+        #[case] address: usize,
+        #[case] value: i32,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = AtomicAdd::new(address, value, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_atomic_add(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(vec![x9, x10])]
+    #[case(vec![x9, x10, v0])]
+    fn error_on_insufficient_scratch(#[case] scratch: Vec<AllRegisters>) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = AtomicAdd::new(0x1234, 1, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_atomic_add(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
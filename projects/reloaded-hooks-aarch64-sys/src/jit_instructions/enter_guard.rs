@@ -0,0 +1,147 @@
+extern crate alloc;
+
+use super::{branch_absolute::encode_jump_absolute, branch_relative::encode_jump_relative};
+use crate::{
+    all_registers::AllRegisters,
+    instructions::{
+        cbz::Cbz, errors::exceeds_maximum_range,
+        ldr_immediate_unsigned_offset::LdrImmediateUnsignedOffset, mov_immediate::MovImmediate,
+        mrs::Mrs,
+    },
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError,
+    operation_aliases::{EnterGuard, JumpAbs, JumpRel},
+};
+
+/// Encoded as MRS + LDRB + CBZ over a branch to the bypass address + MOVZ + STRB.
+/// Uses 2 scratch registers; for the thread pointer and the flag.
+pub fn encode_enter_guard(
+    x: &EnterGuard<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !(0..=4095).contains(&x.tls_offset) {
+        return Err(exceeds_maximum_range(
+            "[Enter Guard]",
+            "0..4095",
+            x.tls_offset,
+        ));
+    }
+
+    let scratch = x.scratch.borrow();
+    let mut regs = scratch.iter().filter(|reg| reg.is_64());
+    let (thread_pointer, flag) = match (regs.next(), regs.next()) {
+        (Some(thread_pointer), Some(flag)) => (*thread_pointer, *flag),
+        _ => {
+            return Err(JitError::NoScratchRegister(
+                "for EnterGuard. 2 registers are required.".to_string(),
+            ))
+        }
+    };
+
+    let tp = thread_pointer.register_number() as u8;
+    let flag_num = flag.register_number() as u8;
+    let offset = x.tls_offset as i32;
+
+    let read_tp = Mrs::new_read_thread_pointer(tp);
+    let load = LdrImmediateUnsignedOffset::new_byte(true, flag_num, offset, tp)?;
+
+    // Branch to bypass, placed after the CBZ. The flag register is free to use at that point.
+    let mut bypass_pc = *pc + 12;
+    let mut bypass = Vec::<i32>::new();
+    let jump = JumpRel {
+        target_address: x.bypass_address,
+        scratch_register: flag,
+    };
+
+    if encode_jump_relative(&jump, &mut bypass_pc, &mut bypass).is_err() {
+        bypass_pc = *pc + 12;
+        bypass.clear();
+        encode_jump_absolute(
+            &JumpAbs {
+                scratch_register: flag,
+                target_address: x.bypass_address,
+            },
+            &mut bypass_pc,
+            &mut bypass,
+        )?;
+    }
+
+    let skip_bypass = Cbz::assemble_cbz(4 + (bypass.len() * 4) as i32, flag_num, false)?;
+    let set = MovImmediate::new_movz(false, flag_num, 1, 0)?;
+    let store = LdrImmediateUnsignedOffset::new_byte(false, flag_num, offset, tp)?;
+
+    buf.push(read_tp.0.to_le() as i32);
+    buf.push(load.0.to_le() as i32);
+    buf.push(skip_bypass.0.to_le() as i32);
+    buf.extend_from_slice(&bypass);
+    buf.push(set.0.to_le() as i32);
+    buf.push(store.0.to_le() as i32);
+    *pc = bypass_pc + 8;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::enter_guard::encode_enter_guard;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // mrs x9, tpidr_el0; ldrb w10, [x9, #16]; cbz w10, #8; b #0x1000; movz w10, #1; strb w10, [x9, #16]
+    #[case(16, 0x100C, vec![x9, x10], "49d03bd52a4140394a000034000400142a0080522a410039")]
+    // mrs x9, tpidr_el0; ldrb w10, [x9, #16]; cbz w10, #20; movz x10, #0x5678; movk x10, #0x1234, lsl #16; movk x10, #0x7fff, lsl #32; br x10; movz w10, #1; strb w10, [x9, #16]
+    #[case(16, 0x7FFF12345678, vec![v0, x9, x10], "49d03bd52a414039aa0000340acf8ad28a46a2f2eaffcff240011fd62a0080522a410039")]
+    fn standard_cases(
+        #[case] tls_offset: isize,
+        #[case] bypass_address: usize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = EnterGuard::new(tls_offset, bypass_address, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_enter_guard(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(16, vec![x9])]
+    #[case(16, vec![x9, v0])]
+    fn error_on_insufficient_scratch(
+        #[case] tls_offset: isize,
+        #[case] scratch: Vec<AllRegisters>,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = EnterGuard::new(tls_offset, 0x1000, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_enter_guard(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+
+    #[rstest]
+    #[case(-16)]
+    #[case(4096)]
+    fn error_on_out_of_range_offset(#[case] tls_offset: isize) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let scratch = vec![x9, x10];
+        let operation = EnterGuard::new(tls_offset, 0x1000, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_enter_guard(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
@@ -0,0 +1,96 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters,
+    instructions::{
+        errors::exceeds_maximum_range, ldr_immediate_unsigned_offset::LdrImmediateUnsignedOffset,
+        mrs::Mrs,
+    },
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::ExitGuard};
+
+/// Encoded as MRS + STRB of the zero register.
+/// Uses 1 scratch register; for the thread pointer.
+pub fn encode_exit_guard(
+    x: &ExitGuard<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !(0..=4095).contains(&x.tls_offset) {
+        return Err(exceeds_maximum_range(
+            "[Exit Guard]",
+            "0..4095",
+            x.tls_offset,
+        ));
+    }
+
+    let tp = x
+        .scratch
+        .borrow()
+        .iter()
+        .find(|reg| reg.is_64())
+        .map(|reg| reg.register_number() as u8)
+        .ok_or_else(|| JitError::NoScratchRegister("for ExitGuard.".to_string()))?;
+
+    let read_tp = Mrs::new_read_thread_pointer(tp);
+    let clear = LdrImmediateUnsignedOffset::new_byte(false, 31, x.tls_offset as i32, tp)?; // wzr
+
+    buf.push(read_tp.0.to_le() as i32);
+    buf.push(clear.0.to_le() as i32);
+    *pc += 8;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::exit_guard::encode_exit_guard;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(16, vec![x9], "49d03bd53f410039")] // mrs x9, tpidr_el0; strb wzr, [x9, #16]
+    #[case(4095, vec![v0, x17], "51d03bd53ffe3f39")] // mrs x17, tpidr_el0; strb wzr, [x17, #4095]
+    fn standard_cases(
+        #[case] tls_offset: isize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = ExitGuard::new(tls_offset, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_exit_guard(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(16, vec![], false)]
+    #[case(16, vec![v0], false)]
+    #[case(4096, vec![x9], true)]
+    fn error_cases(
+        #[case] tls_offset: isize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] is_range_error: bool,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = ExitGuard::new(tls_offset, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_exit_guard(&operation, &mut pc, &mut buf);
+        if is_range_error {
+            assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+        } else {
+            assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+extern crate alloc;
+
+use super::{
+    branch_absolute::encode_jump_absolute, branch_relative::encode_jump_relative,
+    push_constant::encode_mov_constant_to_reg,
+};
+use crate::{
+    all_registers::AllRegisters,
+    instructions::{
+        b::B, bcc::Bcc, cbz::Cbz, ldp_immediate::LdpImmediate,
+        ldr_immediate_unsigned_offset::LdrImmediateUnsignedOffset, subs_register::SubsRegister,
+    },
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError,
+    operation_aliases::{FilterCaller, JumpAbs, JumpRel},
+};
+
+/// Register number of the link register, which holds the return address.
+const LINK_REGISTER: u8 = 30;
+
+/// Condition code for 'unsigned lower'.
+const CONDITION_LO: u8 = 0b0011;
+
+/// Condition code for 'unsigned higher or same'.
+const CONDITION_HS: u8 = 0b0010;
+
+/// Encoded as MOVZ/MOVK + LDR + B over a branch to the bypass address, followed by a
+/// LDP + CBZ + CMP + B.LO + CMP + B.HS loop over the table of ranges.
+/// Uses 3 scratch registers; for the table entry, and the start and end of each range.
+pub fn encode_filter_caller(
+    x: &FilterCaller<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let scratch = x.scratch.borrow();
+    let mut regs = scratch.iter().filter(|reg| reg.is_64());
+    let (entry, start, end) = match (regs.next(), regs.next(), regs.next()) {
+        (Some(entry), Some(start), Some(end)) => (*entry, *start, *end),
+        _ => {
+            return Err(JitError::NoScratchRegister(
+                "for FilterCaller. 3 registers are required.".to_string(),
+            ))
+        }
+    };
+
+    let entry_num = entry.register_number() as u8;
+    let start_num = start.register_number() as u8;
+    let end_num = end.register_number() as u8;
+
+    encode_mov_constant_to_reg(x.table_pointer, entry_num, pc, buf)?;
+    let load_table = LdrImmediateUnsignedOffset::new_mov_from_reg(true, entry_num, 0, entry_num)?;
+
+    // Branch to bypass, placed before the loop. The start register is free to use at that point.
+    let mut bypass_pc = *pc + 8;
+    let mut bypass = Vec::<i32>::new();
+    let jump = JumpRel {
+        target_address: x.bypass_address,
+        scratch_register: start,
+    };
+
+    if encode_jump_relative(&jump, &mut bypass_pc, &mut bypass).is_err() {
+        bypass_pc = *pc + 8;
+        bypass.clear();
+        encode_jump_absolute(
+            &JumpAbs {
+                scratch_register: start,
+                target_address: x.bypass_address,
+            },
+            &mut bypass_pc,
+            &mut bypass,
+        )?;
+    }
+
+    let bypass_len = (bypass.len() * 4) as i32;
+    let skip_bypass = B::assemble_b(4 + bypass_len)?;
+    let load_range =
+        LdpImmediate::new_load_pair_post_indexed(true, start_num, end_num, entry_num, 16)?;
+    let at_end = Cbz::assemble_cbz(-(bypass_len + 4), end_num, true)?;
+    let cmp_start = SubsRegister::new_cmp(true, LINK_REGISTER, start_num);
+    let below_start = Bcc::assemble_bcc(CONDITION_LO, -12)?;
+    let cmp_end = SubsRegister::new_cmp(true, LINK_REGISTER, end_num);
+    let above_end = Bcc::assemble_bcc(CONDITION_HS, -20)?;
+
+    buf.push(load_table.0.to_le() as i32);
+    buf.push(skip_bypass.0.to_le() as i32);
+    buf.extend_from_slice(&bypass);
+    buf.push(load_range.0.to_le() as i32);
+    buf.push(at_end.0.to_le() as i32);
+    buf.push(cmp_start.0.to_le() as i32);
+    buf.push(below_start.0.to_le() as i32);
+    buf.push(cmp_end.0.to_le() as i32);
+    buf.push(above_end.0.to_le() as i32);
+    *pc = bypass_pc + 24;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::filter_caller::encode_filter_caller;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // movz x9, #0x2000; ldr x9, [x9]; b #8; b #0x1000; ldp x10, x11, [x9], #16; cbz x11, #-8;
+    // cmp x30, x10; b.lo #-12; cmp x30, x11; b.hs #-20
+    #[case(0x2000, 0x100C, vec![x9, x10, x11], "090084d2290140f902000014000400142a2dc1a8cbffffb4df030aeba3ffff54df030beb62ffff54")]
+    // movz x9, #0x2000; ldr x9, [x9]; b #20; movz x10, #0x5678; movk x10, #0x1234, lsl #16; movk x10, #0x7fff, lsl #32; br x10;
+    // ldp x10, x11, [x9], #16; cbz x11, #-20; cmp x30, x10; b.lo #-12; cmp x30, x11; b.hs #-20
+    #[case(0x2000, 0x7FFF12345678, vec![v0, x9, x10, x11], "090084d2290140f9050000140acf8ad28a46a2f2eaffcff240011fd62a2dc1a86bffffb4df030aeba3ffff54df030beb62ffff54")]
+    fn standard_cases(
+        #[case] table_pointer: usize,
+        #[case] bypass_address: usize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = FilterCaller::new(
+            table_pointer,
+            bypass_address,
+            Rc::new(RefCell::new(scratch)),
+        );
+
+        assert!(encode_filter_caller(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(vec![x9, x10])]
+    #[case(vec![x9, x10, v0])]
+    fn error_on_insufficient_scratch(#[case] scratch: Vec<AllRegisters>) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = FilterCaller::new(0x2000, 0x1000, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_filter_caller(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
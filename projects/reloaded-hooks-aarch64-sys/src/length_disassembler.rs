@@ -1,4 +1,10 @@
-use reloaded_hooks_portable::api::length_disassembler::LengthDisassembler;
+use crate::{
+    code_rewriter::aarch64_rewriter::{is_b_or_bl, is_bcc, is_cbz, is_tbz},
+    instructions::{b::B, bcc::Bcc, cbz::Cbz, tbz::Tbz},
+};
+use reloaded_hooks_portable::{
+    api::length_disassembler::LengthDisassembler, helpers::read_code::read_code_as,
+};
 
 // Assume Instruction and CodeRewriterError are defined elsewhere
 pub struct LengthDisassemblerAarch64;
@@ -10,6 +16,44 @@ impl LengthDisassembler for LengthDisassemblerAarch64 {
         let length = (min_length + 3) & !3;
         (length, length / 4)
     }
+
+    fn find_branch_into_stolen_region(
+        code_address: usize,
+        stolen_length: usize,
+        scan_length: usize,
+    ) -> Option<(usize, usize)> {
+        let stolen_end = code_address + stolen_length;
+
+        for address in (code_address..stolen_end + (scan_length & !3)).step_by(4) {
+            let instruction = unsafe { read_code_as::<u32>(address) }.to_le();
+            let offset = match branch_offset(instruction) {
+                Some(offset) => offset,
+                None => continue,
+            };
+
+            let target = address.wrapping_add(offset as usize);
+            if target > code_address && target < stolen_end {
+                return Some((address, target));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the offset of a direct branch (B, BL, B.cond, CBZ/CBNZ, TBZ/TBNZ), if the instruction is one.
+fn branch_offset(instruction: u32) -> Option<isize> {
+    if is_b_or_bl(instruction) {
+        Some(B(instruction).offset() as isize)
+    } else if is_bcc(instruction) {
+        Some(Bcc(instruction).offset())
+    } else if is_cbz(instruction) {
+        Some(Cbz(instruction).offset() as isize)
+    } else if is_tbz(instruction) {
+        Some(Tbz(instruction).offset() as isize)
+    } else {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -35,6 +79,35 @@ mod tests {
         assert_eq!(result.1, expected_num_ins);
     }
 
+    #[rstest]
+    #[case::no_branch("20008052410040f9830040f9", 8, 4, None)] // mov w0, #1 + ldr x1, [x2] + ldr x3, [x4]
+    #[case::b_into_stolen("20008052410040f9ffffff17", 8, 4, Some((8, 4)))] // mov w0, #1 + ldr x1, [x2] + b #-4
+    #[case::b_to_start("20008052410040f9feffff17", 8, 4, None)] // mov w0, #1 + ldr x1, [x2] + b #-8
+    #[case::bl_to_end("20008052410040f900000094", 8, 4, None)] // mov w0, #1 + ldr x1, [x2] + bl #0
+    #[case::bcc_into_stolen("20008052410040f9e1ffff54", 8, 4, Some((8, 4)))] // mov w0, #1 + ldr x1, [x2] + b.ne #-4
+    #[case::cbz_into_stolen("20008052410040f9e0ffffb4", 8, 4, Some((8, 4)))] // mov w0, #1 + ldr x1, [x2] + cbz x0, #-4
+    #[case::tbz_into_stolen("20008052410040f9e0ff0736", 8, 4, Some((8, 4)))] // mov w0, #1 + ldr x1, [x2] + tbz w0, #0, #-4
+    #[case::in_stolen_region("200000b4410040f9", 8, 0, Some((0, 4)))] // cbz x0, #4 + ldr x1, [x2]
+    #[case::outside_window("20008052410040f9ffffff17", 8, 0, None)] // b #-4 is not scanned
+    fn can_find_branch_into_stolen_region(
+        #[case] instructions: String,
+        #[case] stolen_length: usize,
+        #[case] scan_length: usize,
+        #[case] expected_offsets: Option<(usize, usize)>,
+    ) {
+        let ins_vec = str_to_vec(instructions);
+        let code_address = ins_vec.as_ptr() as usize;
+        let result = LengthDisassemblerAarch64::find_branch_into_stolen_region(
+            code_address,
+            stolen_length,
+            scan_length,
+        );
+        assert_eq!(
+            result.map(|(source, target)| (source - code_address, target - code_address)),
+            expected_offsets
+        );
+    }
+
     fn str_to_vec(hex: String) -> Vec<u8> {
         hex.as_bytes()
             .chunks(2)
@@ -21,11 +21,13 @@ pub mod calling_convention;
 /// Rewriting the code from one address to another!
 pub(crate) mod code_rewriter {
     pub mod aarch64_rewriter;
+    pub mod foreign_hook;
     pub mod helpers;
     pub mod instruction_rewrite_result;
 
     pub(crate) mod instructions {
         pub mod adr;
+        pub mod adrp_pair;
         pub mod b;
         pub mod b_cond;
         pub mod cbz;
@@ -48,21 +50,28 @@ pub(crate) mod instructions {
     pub mod ldr_immediate_post_indexed;
     pub mod ldr_immediate_unsigned_offset;
     pub mod ldr_literal;
+    pub mod load_store_exclusive;
     pub mod mov_immediate;
+    pub mod mrs;
     pub mod orr;
     pub mod orr_vector;
     pub mod stp_immediate;
     pub mod str_immediate_pre_indexed;
     pub mod sub_immediate;
+    pub mod subs_register;
     pub mod tbz;
 }
 
 /// This namespace contains the code for encoding the JIT instructions
 /// using the raw instructions in the [`crate::instructions`] namespace.
 pub(crate) mod jit_instructions {
+    pub mod atomic_add;
     pub mod branch_absolute;
     pub mod branch_ip_relative;
     pub mod branch_relative;
+    pub mod enter_guard;
+    pub mod exit_guard;
+    pub mod filter_caller;
     pub mod jump_absolute_indirect;
     pub mod load_pc_relative_address;
     pub mod load_pc_relative_value;
@@ -1,8 +1,13 @@
 extern crate alloc;
 
 use crate::all_registers::AllRegisters;
+use crate::code_rewriter::{aarch64_rewriter::is_landing_pad, foreign_hook::find_foreign_hook};
 use alloc::vec::Vec;
-use reloaded_hooks_portable::api::rewriter::code_rewriter::{CodeRewriter, CodeRewriterError};
+use reloaded_hooks_portable::api::rewriter::{
+    code_rewriter::{CodeRewriter, CodeRewriterError},
+    foreign_hook::ForeignHook,
+};
+use reloaded_hooks_portable::helpers::read_code::read_code_as;
 
 pub struct CodeRewriterAarch64;
 
@@ -11,6 +16,19 @@ impl CodeRewriter<AllRegisters> for CodeRewriterAarch64 {
         20 // b rel to MOVZ + MOVK + LDR + BR.
     }
 
+    unsafe fn landing_pad_length(address: usize) -> usize {
+        let instruction = read_code_as::<u32>(address).to_le();
+        if is_landing_pad(instruction) {
+            4
+        } else {
+            0
+        }
+    }
+
+    unsafe fn find_foreign_hook(address: usize) -> Option<ForeignHook> {
+        find_foreign_hook(address)
+    }
+
     unsafe fn rewrite_code_with_buffer(
         old_code: *const u8,
         old_code_size: usize,
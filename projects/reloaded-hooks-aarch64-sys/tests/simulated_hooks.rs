@@ -0,0 +1,395 @@
+//! Creates hooks in simulated memory, such that hook creation can be tested on any host.
+//! The produced code is never executed, its bytes are compared against the expected encoding instead.
+
+mod asm;
+
+mod tests {
+    use crate::asm::calculator::{
+        CALCULATOR_ADD, CALL_CALCULATOR_ADD, CALL_CALCULATOR_ADD_CALL_OFFSET,
+        CALL_CALCULATOR_ADD_TARGET_FUNCTION_OFFSET,
+    };
+    use reloaded_hooks_aarch64_sys::all_registers::AllRegisters;
+    use reloaded_hooks_aarch64_sys::calling_convention::CallingConvention;
+    use reloaded_hooks_aarch64_sys::jit::JitAarch64;
+    use reloaded_hooks_aarch64_sys::length_disassembler::LengthDisassemblerAarch64;
+    use reloaded_hooks_aarch64_sys::rewriter::CodeRewriterAarch64;
+    use reloaded_hooks_portable::api::buffers::simulated_buffer::SimulatedBuffer;
+    use reloaded_hooks_portable::api::buffers::simulated_buffer_factory::SimulatedBufferFactory;
+    use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+    use reloaded_hooks_portable::api::function_info::{BasicFunctionInfo, ParameterType};
+    use reloaded_hooks_portable::api::hooks::assembly::assembly_hook::create_assembly_hook;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook::create_branch_hook_with_callback;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook_fast::create_branch_hook_fast_with_callback;
+    use reloaded_hooks_portable::api::platforms::simulated_memory::{
+        get_simulated_memory, register_simulated_memory,
+    };
+    use reloaded_hooks_portable::api::settings::assembly_hook_settings::AssemblyHookSettings;
+    use reloaded_hooks_portable::api::settings::basic_hook_settings::BasicHookSettings;
+    use reloaded_hooks_portable::api::settings::function_hook_settings::FunctionHookSettings;
+
+    static ADD_INFO: BasicFunctionInfo =
+        BasicFunctionInfo::new(&[ParameterType::i64, ParameterType::i64]);
+
+    /// Reads `len` bytes of simulated memory, as hex.
+    fn read_hex(address: usize, len: usize) -> String {
+        hex::encode(get_simulated_memory().read(address, len))
+    }
+
+    #[test]
+    fn assembly_hook_relocates_branch() {
+        register_simulated_memory();
+        let base = 0x4000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD);
+
+        // Steal `stp`, `mov` and `bl add_fn` from `add_wrapper`.
+        let hook_addr = base + 8;
+        let code = &[0x21u8, 0x04, 0x00, 0x91];
+        let settings =
+            AssemblyHookSettings::new_minimal(hook_addr, code.as_ptr() as usize, code.len(), 12)
+                .with_scratch_register(AllRegisters::x7);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitAarch64,
+                AllRegisters,
+                LengthDisassemblerAarch64,
+                CodeRewriterAarch64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        assert_eq!(
+            read_hex(hook_addr, 12),
+            concat!(
+                "fe070014", // b 0x40002000
+                "1f2003d5", // nop
+                "1f2003d5", // nop
+            )
+        );
+        assert_eq!(
+            read_hex(base + 0x2000, 24),
+            concat!(
+                "df2403d5", // bti jc
+                "21040091", // add x1, x1, #1
+                "fd7bbfa9", // stp x29, x30, [sp, #-16]!
+                "fd030091", // mov x29, sp
+                "fcf7ff97", // bl 0x40000000 (add_fn)
+                "00f8ff17", // b 0x40000014
+            )
+        );
+    }
+
+    #[test]
+    fn assembly_hook_with_enable_disable() {
+        register_simulated_memory();
+        let memory = get_simulated_memory();
+        let add_addr = 0x1000_0000;
+        memory.map_code(add_addr, &CALCULATOR_ADD);
+
+        // add x1, x1, #1
+        let code = &[0x21u8, 0x04, 0x00, 0x91];
+        let settings =
+            AssemblyHookSettings::new_minimal(add_addr, code.as_ptr() as usize, code.len(), 20)
+                .with_scratch_register(AllRegisters::x7);
+
+        let hook = unsafe {
+            create_assembly_hook::<
+                JitAarch64,
+                AllRegisters,
+                LengthDisassemblerAarch64,
+                CodeRewriterAarch64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+            .with_uninstall_on_drop(true)
+        };
+
+        // Stolen bytes not needed for the branch are filled with nops.
+        let hooked = concat!(
+            "00080014", // b 0x10002000
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "c0035fd6", // ret
+        );
+        let enabled = concat!(
+            "df2403d5", // bti jc
+            "21040091", // add x1, x1, #1
+            "0000018b", // add x0, x0, x1
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "fef7ff17", // b 0x10000014
+        );
+        let disabled = concat!(
+            "df2403d5", // bti jc
+            "0000018b", // add x0, x0, x1
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "1f2003d5", // nop
+            "fff7ff17", // b 0x10000014
+        );
+
+        let stub = add_addr + 0x2000;
+        assert_eq!(read_hex(add_addr, 24), hooked);
+        assert_eq!(read_hex(stub, 32), enabled);
+
+        hook.disable();
+        assert_eq!(read_hex(add_addr, 24), hooked);
+        assert_eq!(read_hex(stub, 28), disabled);
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 32), enabled);
+
+        drop(hook);
+        assert_eq!(memory.read(add_addr, 24), CALCULATOR_ADD);
+        assert_eq!(memory.num_unprotected_regions(), 0);
+    }
+
+    #[test]
+    fn assembly_hook_keeps_landing_pad_at_hook_address() {
+        register_simulated_memory();
+        let base = 0x5000_0000;
+        let function = concat!(
+            "3f2303d5", // paciasp
+            "0000018b", // add x0, x0, x1
+            "bf2303d5", // autiasp
+            "c0035fd6", // ret
+        );
+        get_simulated_memory().map_code(base, &hex::decode(function).unwrap());
+
+        // add x1, x1, #1
+        let code = &[0x21u8, 0x04, 0x00, 0x91];
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 8)
+                .with_scratch_register(AllRegisters::x7);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitAarch64,
+                AllRegisters,
+                LengthDisassemblerAarch64,
+                CodeRewriterAarch64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        // `paciasp` is a landing pad for callers using `blr`, so the hook goes after it.
+        assert_eq!(
+            read_hex(base, 8),
+            concat!(
+                "3f2303d5", // paciasp
+                "ff070014", // b 0x50002000
+            )
+        );
+        assert_eq!(
+            read_hex(base + 0x2000, 16),
+            concat!(
+                "df2403d5", // bti jc
+                "21040091", // add x1, x1, #1
+                "0000018b", // add x0, x0, x1
+                "fff7ff17", // b 0x50000008
+            )
+        );
+    }
+
+    #[test]
+    fn assembly_hook_chains_in_front_of_foreign_hook() {
+        register_simulated_memory();
+        let base = 0x7000_0000;
+        let foreign_hook = concat!(
+            "51000058",         // ldr x17, #8
+            "20021fd6",         // br x17
+            "0001007000000000", // 0x70000100
+        );
+        let mut function = hex::decode(foreign_hook).unwrap();
+        function.resize(0x100, 0);
+        function.extend_from_slice(&CALCULATOR_ADD);
+        get_simulated_memory().map_code(base, &function);
+
+        // add x1, x1, #1
+        let code = &[0x21u8, 0x04, 0x00, 0x91];
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 16)
+                .with_scratch_register(AllRegisters::x7);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitAarch64,
+                AllRegisters,
+                LengthDisassemblerAarch64,
+                CodeRewriterAarch64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        // The foreign hook's literal isn't relocated as code; we branch to its target instead.
+        assert_eq!(
+            read_hex(base, 16),
+            concat!(
+                "00080014", // b 0x70002000
+                "1f2003d5", // nop
+                "1f2003d5", // nop
+                "1f2003d5", // nop
+            )
+        );
+        assert_eq!(
+            read_hex(base + 0x2000, 16),
+            concat!(
+                "df2403d5", // bti jc
+                "21040091", // add x1, x1, #1
+                "3ef8ff17", // b 0x70000100
+                "01f8ff17", // b 0x70000010
+            )
+        );
+    }
+
+    #[test]
+    fn branch_hook_fast() {
+        register_simulated_memory();
+        let base = 0x2000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_CALL_OFFSET;
+        let settings = BasicHookSettings::new_with_scratch_register(
+            hook_addr,
+            base + CALL_CALCULATOR_ADD_TARGET_FUNCTION_OFFSET,
+            Some(AllRegisters::x7),
+        );
+
+        let mut original = 0;
+        unsafe {
+            create_branch_hook_fast_with_callback::<
+                JitAarch64,
+                AllRegisters,
+                LengthDisassemblerAarch64,
+                CodeRewriterAarch64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings, |x| original = x)
+            .unwrap();
+        }
+
+        assert_eq!(original, base);
+        assert_eq!(read_hex(hook_addr, 4), "03000094"); // bl 0x2000001C (target_function)
+    }
+
+    #[test]
+    fn branch_hook_fast_via_stub() {
+        register_simulated_memory();
+        let base = 0x6000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_CALL_OFFSET;
+        let settings = BasicHookSettings::new_with_scratch_register(
+            hook_addr,
+            0x1234_5678_9ABC,
+            Some(AllRegisters::x7),
+        );
+
+        unsafe {
+            create_branch_hook_fast_with_callback::<
+                JitAarch64,
+                AllRegisters,
+                LengthDisassemblerAarch64,
+                CodeRewriterAarch64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings, |_| {})
+            .unwrap();
+        }
+
+        assert_eq!(read_hex(hook_addr, 4), "fc070094"); // bl 0x60002000
+        assert_eq!(
+            read_hex(base + 0x2000, 20),
+            concat!(
+                "df2403d5", // bti jc
+                "875793d2", // mov x7, #0x9abc
+                "07cfaaf2", // movk x7, #0x5678, lsl #16
+                "8746c2f2", // movk x7, #0x1234, lsl #32
+                "e0001fd6", // br x7
+            )
+        );
+    }
+
+    #[test]
+    fn branch_hook_with_wrapper() {
+        register_simulated_memory();
+        let base = 0x3000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_CALL_OFFSET;
+        let basic_settings = BasicHookSettings::new_with_scratch_register(
+            hook_addr,
+            0x1234_5678_9ABC,
+            Some(AllRegisters::x7),
+        );
+
+        // Different conventions, to force a wrapper.
+        let settings = FunctionHookSettings::<
+            AllRegisters,
+            BasicFunctionInfo,
+            GenericCallingConvention<AllRegisters>,
+        >::new(
+            basic_settings,
+            true,
+            ADD_INFO,
+            CallingConvention::aapcs64(),
+            CallingConvention::microsoft(),
+            None,
+        );
+
+        let mut original = 0;
+        let hook = unsafe {
+            create_branch_hook_with_callback::<
+                JitAarch64,
+                AllRegisters,
+                LengthDisassemblerAarch64,
+                CodeRewriterAarch64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+                BasicFunctionInfo,
+                GenericCallingConvention<AllRegisters>,
+            >(&settings, |x| original = x)
+            .unwrap()
+        };
+
+        // The target is out of range, so the wrapper calls it via a register which isn't a parameter.
+        let wrapper = concat!(
+            "df2403d5", // bti jc
+            "fe8f1ff8", // str x30, [sp, #-8]!
+            "ff2300d1", // sub sp, sp, #8
+            "e10301aa", // mov x1, x1
+            "e00300aa", // mov x0, x0
+            "ff4300d1", // sub sp, sp, #16
+            "825793d2", // mov x2, #0x9abc
+            "02cfaaf2", // movk x2, #0x5678, lsl #16
+            "8246c2f2", // movk x2, #0x1234, lsl #32
+            "40003fd6", // blr x2
+            "ff630091", // add sp, sp, #24
+            "fe8740f8", // ldr x30, [sp], #8
+            "c0035fd6", // ret
+        );
+
+        let stub = base + 0x2000;
+        assert_eq!(original, base);
+        assert_eq!(read_hex(hook_addr, 4), "fc070094"); // bl 0x30002000
+        assert_eq!(read_hex(stub, 52), wrapper);
+
+        hook.disable();
+        assert_eq!(read_hex(stub, 8), "df2403d5fff7ff17"); // bti jc; b 0x30000000 (add_fn)
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 52), wrapper);
+    }
+}
@@ -0,0 +1,157 @@
+use derive_enum_all_values::AllValues;
+use reloaded_hooks_portable::api::traits::register_info::{
+    KnownRegisterType, KnownRegisterType::*, RegisterInfo,
+};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, AllValues, Default)]
+pub enum AllRegisters {
+    // Range 0b0000 - 0b1111 (0-15)
+    // 32 bit general purpose registers.
+    r0,
+    r1,
+    r2,
+    r3,
+    r4,
+    r5,
+    r6,
+    r7,
+    r8,
+    r9,
+    r10,
+    r11,
+    #[default]
+    r12, // ip
+    sp, // r13
+    lr, // r14
+
+    // `pc` (r15) is not listed, as it can't be used as a general purpose register.
+    // Instructions which read it are handled by the code rewriter instead.
+
+    // Range 0b10000 - 0b11111 (16-31)
+    // 64 bit VFP registers. (VFPv3-D16, present on all ARMv7 Android devices)
+    d0 = 16,
+    d1,
+    d2,
+    d3,
+    d4,
+    d5,
+    d6,
+    d7,
+    d8,
+    d9,
+    d10,
+    d11,
+    d12,
+    d13,
+    d14,
+    d15,
+}
+
+/// Registers which can be handed out by the wrapper generator.
+static USABLE_REGISTERS: &[AllRegisters] = {
+    use AllRegisters::*;
+    &[
+        r0, r1, r2, r3, r4, r5, r6, r7, r8, r9, r10, r11, r12, sp, lr, d0, d1, d2, d3, d4, d5, d6,
+        d7, d8, d9, d10, d11, d12, d13, d14, d15,
+    ]
+};
+
+impl AllRegisters {
+    pub fn register_number(&self) -> u32 {
+        // Mask the lower 4 bits to get the register number
+        (*self as u32) & 0b1111
+    }
+
+    pub fn size(&self) -> usize {
+        if self.is_gpr() {
+            4
+        } else {
+            8
+        }
+    }
+
+    /// True if this is one of the general purpose registers `r0` - `lr`.
+    pub fn is_gpr(&self) -> bool {
+        *self as u32 & 0b10000 == 0
+    }
+
+    /// True if this is one of the VFP registers `d0` - `d15`.
+    pub fn is_vfp(&self) -> bool {
+        *self as u32 & 0b10000 != 0
+    }
+}
+
+impl RegisterInfo for AllRegisters {
+    fn size_in_bytes(&self) -> usize {
+        self.size()
+    }
+
+    fn is_stack_pointer(&self) -> bool {
+        self == &AllRegisters::sp
+    }
+
+    fn register_type(&self) -> KnownRegisterType {
+        if self.is_gpr() {
+            GeneralPurpose32
+        } else {
+            FloatingPoint
+        }
+    }
+
+    fn extend(&self) -> Self {
+        *self
+    }
+
+    fn all_registers() -> &'static [Self]
+    where
+        Self: Sized,
+    {
+        USABLE_REGISTERS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use reloaded_hooks_portable::api::traits::register_info::KnownRegisterType;
+    use reloaded_hooks_portable::api::traits::register_info::KnownRegisterType::*;
+    use reloaded_hooks_portable::api::traits::register_info::RegisterInfo;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(r0, 0)]
+    #[case(r7, 7)]
+    #[case(r12, 12)]
+    #[case(sp, 13)]
+    #[case(lr, 14)]
+    #[case(d0, 0)]
+    #[case(d8, 8)]
+    #[case(d15, 15)]
+    fn register_number(#[case] register: AllRegisters, #[case] expected_number: u32) {
+        assert_eq!(register.register_number(), expected_number);
+    }
+
+    #[rstest]
+    #[case(r0, GeneralPurpose32, 4)]
+    #[case(lr, GeneralPurpose32, 4)]
+    #[case(d0, FloatingPoint, 8)]
+    #[case(d15, FloatingPoint, 8)]
+    fn register_type(
+        #[case] register: AllRegisters,
+        #[case] expected_type: KnownRegisterType,
+        #[case] expected_size: usize,
+    ) {
+        assert_eq!(register.register_type(), expected_type);
+        assert_eq!(register.size_in_bytes(), expected_size);
+    }
+
+    #[test]
+    fn all_registers_are_usable() {
+        let all = AllRegisters::all_registers();
+        assert_eq!(all.len(), 31);
+        assert!(all.contains(&sp));
+        assert!(all.contains(&d15));
+    }
+}
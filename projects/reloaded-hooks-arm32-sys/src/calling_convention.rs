@@ -0,0 +1,124 @@
+use crate::all_registers::AllRegisters;
+use crate::all_registers::AllRegisters::*;
+use derive_more::Deref;
+use derive_more::DerefMut;
+use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+use reloaded_hooks_portable::api::calling_convention_info::ParameterRegisterAssignment;
+use reloaded_hooks_portable::api::calling_convention_info::StackCleanup;
+use reloaded_hooks_portable::api::calling_convention_info::StackParameterOrder;
+
+/// A variant of `GenericCallingConvention` for 32-bit ARM.
+///
+/// This struct is specialized for 32-bit ARM and includes the base AAPCS (soft-float, used by
+/// e.g. `armeabi-v7a` on Android) and the AAPCS-VFP (hard-float, used by e.g. `armhf` Linux)
+/// calling conventions.
+///
+/// # Examples
+///
+/// ```rust
+/// use reloaded_hooks_arm32_sys::calling_convention::CallingConvention;
+/// let aapcs_convention = CallingConvention::aapcs();
+/// ```
+#[derive(Debug, Clone, PartialEq, DerefMut, Deref)]
+pub struct CallingConvention<'a> {
+    convention: GenericCallingConvention<'a, AllRegisters>,
+}
+
+// https://github.com/ARM-software/abi-aa/blob/main/aapcs32/aapcs32.rst
+static AAPCS: CallingConvention = CallingConvention {
+    convention: GenericCallingConvention::<AllRegisters> {
+        int_parameters: &[r0, r1, r2, r3],
+        float_parameters: &[], // Passed in integer registers or on the stack.
+        vector_parameters: &[],
+        return_register: r0,
+        float_return_register: None, // Returned in r0 alongside integers.
+        reserved_stack_space: 0,
+        callee_saved_registers: &[
+            r4, r5, r6, r7, r8, r9, r10, r11, d8, d9, d10, d11, d12, d13, d14, d15,
+        ],
+        always_saved_registers: &[lr],
+        stack_cleanup: StackCleanup::Caller,
+        stack_parameter_order: StackParameterOrder::RightToLeft,
+        required_stack_alignment: 8,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
+    },
+};
+
+static AAPCS_VFP: CallingConvention = CallingConvention {
+    convention: GenericCallingConvention::<AllRegisters> {
+        int_parameters: &[r0, r1, r2, r3],
+        float_parameters: &[d0, d1, d2, d3, d4, d5, d6, d7],
+        vector_parameters: &[],
+        return_register: r0,
+        float_return_register: Some(d0),
+        reserved_stack_space: 0,
+        callee_saved_registers: &[
+            r4, r5, r6, r7, r8, r9, r10, r11, d8, d9, d10, d11, d12, d13, d14, d15,
+        ],
+        always_saved_registers: &[lr],
+        stack_cleanup: StackCleanup::Caller,
+        stack_parameter_order: StackParameterOrder::RightToLeft,
+        required_stack_alignment: 8,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
+    },
+};
+
+impl<'a> CallingConvention<'a> {
+    /// Base ARM AAPCS calling convention (soft-float).
+    /// - Integer parameters: R0 to R3 for the first four integer or pointer arguments.
+    /// - Float parameters:   Passed in R0 to R3 like integers, then on the stack.
+    /// - Additional parameters: Passed on the stack.
+    /// - Return register:    R0 (and R1 for 64-bit values)
+    /// - Cleanup:            Caller
+    pub fn aapcs() -> &'a Self {
+        &AAPCS
+    }
+
+    /// ARM AAPCS-VFP calling convention (hard-float).
+    /// - Integer parameters: R0 to R3 for the first four integer or pointer arguments.
+    /// - Float parameters:   D0 to D7 (S0 to S15) for the first floating-point arguments.
+    /// - Additional parameters: Passed on the stack.
+    /// - Return register:    R0 (integer), D0/S0 (float)
+    /// - Cleanup:            Caller
+    pub fn aapcs_vfp() -> &'a Self {
+        &AAPCS_VFP
+    }
+
+    // Add a method to select ARM calling convention based on PresetCallingConvention
+    pub fn from_preset(convention_type: PresetCallingConvention) -> &'a Self {
+        match convention_type {
+            PresetCallingConvention::AAPCS => Self::aapcs(),
+            PresetCallingConvention::AAPCSVFP => Self::aapcs_vfp(),
+        }
+    }
+
+    // Retrieves the default calling convention for the currently running machine.
+    pub fn default_for_current_platform() -> &'a Self {
+        if cfg!(target_abi = "eabihf") {
+            Self::aapcs_vfp()
+        } else {
+            Self::aapcs()
+        }
+    }
+}
+
+/// Enum representing various calling conventions with detailed information.
+pub enum PresetCallingConvention {
+    /// Base ARM AAPCS calling convention (soft-float).
+    /// - Integer parameters: R0 to R3 for the first four integer or pointer arguments.
+    /// - Float parameters:   Passed in R0 to R3 like integers, then on the stack.
+    /// - Additional parameters: Passed on the stack.
+    /// - Return register:    R0 (and R1 for 64-bit values)
+    /// - Cleanup:            Caller
+    AAPCS,
+
+    /// ARM AAPCS-VFP calling convention (hard-float).
+    /// - Integer parameters: R0 to R3 for the first four integer or pointer arguments.
+    /// - Float parameters:   D0 to D7 (S0 to S15) for the first floating-point arguments.
+    /// - Additional parameters: Passed on the stack.
+    /// - Return register:    R0 (integer), D0/S0 (float)
+    /// - Cleanup:            Caller
+    AAPCSVFP,
+}
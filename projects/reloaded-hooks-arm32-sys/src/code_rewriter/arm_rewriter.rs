@@ -0,0 +1,252 @@
+extern crate alloc;
+
+use super::{
+    helpers::{apply_condition, apply_sign},
+    instructions::{adr::rewrite_adr, branch::rewrite_branch_to, ldr_literal::rewrite_ldr_literal},
+};
+use crate::{
+    helpers::{push_arm, read_arm},
+    instruction_set::InstructionSet,
+    instructions::{branch::decode_arm_branch, load_store::LoadStoreKind},
+};
+use alloc::{format, string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Rewrites ARM (A32) code from one address to another.
+///
+/// Given an original block of code starting at `old_address`, this function
+/// will modify any relative addressing instructions to make them compatible
+/// with a new location starting at `new_address`.
+///
+/// # Parameters
+///
+/// * `old_code`: A pointer to the start of the original block of code.
+/// * `old_code_size`: Amount of bytes to rewrite.
+/// * `old_address`: The address to assume as the source location of the old code.
+/// * `new_address`: The new address for the instructions.
+/// * `scratch_register`
+///     - A scratch general purpose register that can be used for operations.
+///     - This scratch register may or may not be used depending on the code being rewritten.
+///
+/// # Behaviour
+///
+/// The following instructions are rewritten; everything else is copied as is:
+/// - `B`, `BL`, `BLX (immediate)` (any condition)
+/// - `LDR (literal)`, `LDRB (literal)`
+/// - `ADR` (`ADD`/`SUB rd, pc, #imm`)
+///
+/// Conditional instructions stay conditional when expanded into multiple instructions.
+///
+/// # Returns
+///
+/// Either a re-encode error, in which case the operation fails, or a vector to consume.
+pub(crate) fn rewrite_code_arm(
+    old_code: *const u8,
+    old_code_size: usize,
+    old_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+    existing_buffer: &mut Vec<u8>,
+) -> Result<(), CodeRewriterError> {
+    let start = existing_buffer.len();
+    let mut offset = 0;
+
+    while offset < old_code_size {
+        if offset + 4 > old_code_size {
+            return Err(CodeRewriterError::InsufficientBytes);
+        }
+
+        let instruction = unsafe { read_arm(old_code.wrapping_add(offset)) };
+        let source_address = old_address.wrapping_add(offset);
+        let dest_address = new_address.wrapping_add(existing_buffer.len() - start);
+
+        let rewritten = rewrite_instruction(
+            instruction,
+            offset,
+            source_address,
+            dest_address,
+            scratch_register,
+            existing_buffer,
+        )?;
+
+        if !rewritten {
+            push_arm(existing_buffer, instruction);
+        }
+
+        offset += 4;
+    }
+
+    Ok(())
+}
+
+/// Rewrites a single instruction, returning `false` if it should be copied as is.
+fn rewrite_instruction(
+    instruction: u32,
+    offset: usize,
+    source_address: usize,
+    dest_address: usize,
+    scratch_register: Option<u8>,
+    buf: &mut Vec<u8>,
+) -> Result<bool, CodeRewriterError> {
+    let isa = InstructionSet::Arm;
+    let condition = (instruction >> 28) as u8;
+
+    if let Some(branch) = decode_arm_branch(instruction, source_address) {
+        rewrite_branch_to(
+            isa,
+            branch.condition,
+            branch.link,
+            branch.target,
+            dest_address,
+            scratch_register,
+            buf,
+        )?;
+        return Ok(true);
+    }
+
+    let start = buf.len();
+    if is_ldr_literal(instruction) {
+        let kind = if instruction & (1 << 22) != 0 {
+            LoadStoreKind::Ldrb
+        } else {
+            LoadStoreKind::Ldr
+        };
+
+        let rt = ((instruction >> 12) & 0xF) as u8;
+        let imm12 = (instruction & 0xFFF) as usize;
+        let address = apply_sign(isa.literal_base(source_address), imm12, instruction);
+        rewrite_ldr_literal(isa, kind, rt, address, dest_address, scratch_register, buf)?;
+    } else if is_adr(instruction) {
+        let rd = ((instruction >> 12) & 0xF) as u8;
+        if rd == 15 {
+            return Err(CodeRewriterError::FailedToReencode(
+                offset,
+                format!("{:08x}", instruction),
+                "ADR to PC is not supported.".to_string(),
+            ));
+        }
+
+        let imm8 = instruction & 0xFF;
+        let rotation = (instruction >> 8) & 0xF;
+        let imm = imm8.rotate_right(rotation * 2) as usize;
+        let value = apply_sign(isa.literal_base(source_address), imm, instruction);
+        rewrite_adr(isa, rd, value, dest_address, buf);
+    } else {
+        return Ok(false);
+    }
+
+    apply_condition(buf, start, condition);
+    Ok(true)
+}
+
+/// `LDR (literal)` or `LDRB (literal)`, i.e. `LDR rt, [pc, #imm]` without writeback.
+pub(crate) fn is_ldr_literal(instruction: u32) -> bool {
+    (instruction & 0x0F3F0000) == 0x051F0000 && (instruction >> 28) != 0xF
+}
+
+/// `ADR`, i.e. `ADD rd, pc, #imm` or `SUB rd, pc, #imm`.
+pub(crate) fn is_adr(instruction: u32) -> bool {
+    let opcode = instruction & 0x0FFF0000;
+    (opcode == 0x028F0000 || opcode == 0x024F0000) && (instruction >> 28) != 0xF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_adr, is_ldr_literal, rewrite_code_arm};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::ldr_literal("00019fe5", true, false)] // ldr r0, [pc, #0x100]
+    #[case::ldrb_literal("08105fe5", true, false)] // ldrb r1, [pc, #-8]
+    #[case::ldr("000091e5", false, false)] // ldr r0, [r1]
+    #[case::ldr_pc_writeback("0001bfe5", false, false)] // ldr r0, [pc, #0x100]!
+    #[case::pld("00f1dff5", false, false)] // pld [pc, #0x100]
+    #[case::adr_add("010c8fe2", false, true)] // add r0, pc, #0x100
+    #[case::adr_sub("08104fe2", false, true)] // sub r1, pc, #8
+    #[case::add("010c81e2", false, false)] // add r0, r1, #0x100
+    #[case::adds_pc("010c9fe2", false, false)] // adds r0, pc, #0x100
+    fn can_classify_instruction(
+        #[case] hex: &str,
+        #[case] expected_ldr: bool,
+        #[case] expected_adr: bool,
+    ) {
+        let instruction = u32::from_str_radix(hex, 16).unwrap().to_be();
+        assert_eq!(is_ldr_literal(instruction), expected_ldr);
+        assert_eq!(is_adr(instruction), expected_adr);
+    }
+
+    #[rstest]
+    // mov r0, r1 -> copied
+    #[case::copy("0100a0e1", 0x1000, 0, "0100a0e1")]
+    // b #0xff8 at 0x1000 -> b #0x1ff8
+    #[case::b("fe0300ea", 0x1000, 0, "fe0700ea")]
+    // bl at 0x12345678 to 0x12345678 -> movw r12, #0x5678; movt r12, #0x1234; blx r12
+    #[case::bl_far("feffffeb", 0x12345678, 0, "78c605e334c241e33cff2fe1")]
+    // blx to 0x12345679 (Thumb) -> movw r12, #0x5679; movt r12, #0x1234; blx r12
+    #[case::blx_far("fefffffa", 0x12345678, 0, "79c605e334c241e33cff2fe1")]
+    // bne at 0x12345678 -> beq (skip); ldr pc, [pc, #-4]; .word 0x12345678
+    #[case::bne_far("feffff1a", 0x12345678, 0, "0100000a04f01fe578563412")]
+    // ldr r0, [pc, #-8] at 0x1000 -> ldr r0, [pc, #0xff8]
+    #[case::ldr_literal("08001fe5", 0x1000, 0, "f80f9fe5")]
+    // ldrne r0, [pc, #-8] at 0x12345678 -> movwne r0, #0x5678; movtne r0, #0x1234; ldrne r0, [r0]
+    #[case::ldr_literal_far_cond("08001f15", 0x12345678, 0, "780605133402411300009015")]
+    // addeq r0, pc, #0 at 0x12345678 -> movweq r0, #0x5680; movteq r0, #0x1234
+    #[case::adr_far_cond("00008f02", 0x12345678, 0, "8006050334024103")]
+    // mov r0, r1 + b #0xff8 -> mov r0, r1 + b #0x1ff8 (branch is now 4 bytes further into the buffer)
+    #[case::b_after_mov("0100a0e1fe0300ea", 0x1000, 0, "0100a0e1fe0700ea")]
+    fn can_rewrite_code(
+        #[case] old_instruction_hex: &str,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let old_instruction_bytes = hex::decode(old_instruction_hex).unwrap();
+        let mut new_code = Vec::new();
+        let result = rewrite_code_arm(
+            old_instruction_bytes.as_ptr(),
+            old_instruction_bytes.len(),
+            old_address,
+            new_address,
+            Some(12),
+            &mut new_code,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&new_code), expected_hex);
+    }
+
+    #[rstest]
+    // Only half of an instruction.
+    #[case::truncated("0100", None, CodeRewriterError::InsufficientBytes)]
+    // Far call without a scratch register.
+    #[case::no_scratch("feffffeb", None, CodeRewriterError::NoScratchRegister(String::new()))]
+    // add pc, pc, #0
+    #[case::adr_to_pc(
+        "00f08fe2",
+        Some(12),
+        CodeRewriterError::FailedToReencode(0, String::new(), String::new())
+    )]
+    fn error_cases(
+        #[case] old_instruction_hex: &str,
+        #[case] scratch_register: Option<u8>,
+        #[case] expected: CodeRewriterError,
+    ) {
+        let old_instruction_bytes = hex::decode(old_instruction_hex).unwrap();
+        let mut new_code = Vec::new();
+        let result = rewrite_code_arm(
+            old_instruction_bytes.as_ptr(),
+            old_instruction_bytes.len(),
+            0x12345678,
+            0,
+            scratch_register,
+            &mut new_code,
+        );
+
+        assert_eq!(
+            core::mem::discriminant(&result.unwrap_err()),
+            core::mem::discriminant(&expected)
+        );
+    }
+}
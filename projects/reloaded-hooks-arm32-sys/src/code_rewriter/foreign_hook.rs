@@ -0,0 +1,144 @@
+use crate::instructions::branch::{decode_arm_branch, decode_thumb_branch, CONDITION_AL};
+use reloaded_hooks_portable::{
+    api::rewriter::foreign_hook::ForeignHook, helpers::read_code::read_code_as,
+};
+
+/// `ldr pc, [pc, #-4]`
+const ARM_LDR_PC: u32 = 0xE51FF004;
+
+/// First halfword of `ldr.w pc, [pc, #imm]`
+const THUMB_LDR_W_PC: u16 = 0xF8DF;
+
+/// Recognises jumps placed at the start of ARM code by other hooking libraries.
+///
+/// - `b <target>` (older Reloaded.Hooks)
+/// - `ldr pc, [pc, #-4]` followed by the target address (Dobby, frida-gum, Substrate)
+///
+/// Jumps which switch to Thumb code are not recognised; the hook would have to be made
+/// with the other rewriter.
+///
+/// # Safety
+///
+/// `address` must point to readable code.
+pub(crate) unsafe fn find_foreign_hook_arm(address: usize) -> Option<ForeignHook> {
+    let instruction = read_code_as::<u32>(address).to_le();
+
+    // b <target>
+    if let Some(branch) = decode_arm_branch(instruction, address) {
+        return (branch.condition == CONDITION_AL && !branch.link && branch.target & 1 == 0)
+            .then(|| ForeignHook::new(4, branch.target));
+    }
+
+    // ldr pc, [pc, #-4]
+    if instruction == ARM_LDR_PC {
+        let target = read_code_as::<u32>(address + 4).to_le() as usize;
+        return (target & 1 == 0).then(|| ForeignHook::new(8, target));
+    }
+
+    None
+}
+
+/// Recognises jumps placed at the start of Thumb code by other hooking libraries.
+///
+/// - `b.w <target>` or 16-bit `b <target>` (older Reloaded.Hooks)
+/// - `ldr.w pc, [pc, #imm]` followed by the target address (Dobby, frida-gum, Substrate)
+///
+/// The returned target is the raw address of the code, i.e. without bit 0 set, the same
+/// way hook addresses of Thumb code are given. Jumps which switch to ARM code are not recognised.
+///
+/// # Safety
+///
+/// `address` must point to readable code.
+pub(crate) unsafe fn find_foreign_hook_thumb(address: usize) -> Option<ForeignHook> {
+    let address = address & !1;
+    let first = read_code_as::<u16>(address).to_le();
+    let second = read_code_as::<u16>(address + 2).to_le();
+
+    // ldr.w pc, [pc, #imm]; where the literal directly follows the instruction.
+    if first == THUMB_LDR_W_PC && second & 0xF000 == 0xF000 {
+        let literal = (address + 4) & !3;
+        if literal + (second & 0xFFF) as usize != address + 4 {
+            return None;
+        }
+
+        let target = read_code_as::<u32>(address + 4).to_le() as usize;
+        return (target & 1 == 1).then(|| ForeignHook::new(8, target & !1));
+    }
+
+    // b.w <target>, b <target>
+    let branch = decode_thumb_branch(first, second, address)?;
+    if branch.condition != CONDITION_AL || branch.link || branch.target & 1 == 0 {
+        return None;
+    }
+
+    let length = if first & 0xF800 == 0xE000 { 2 } else { 4 };
+    Some(ForeignHook::new(length, branch.target & !1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Copies `code` to a 4 byte aligned buffer at the given offset, such that reading
+    /// past the end of shorter code is fine.
+    fn buffer(code: &str, offset: usize) -> (Vec<u32>, usize) {
+        let code = hex::decode(code).unwrap();
+        let mut buf = vec![0u32; 8];
+        let address = buf.as_mut_ptr() as usize + offset;
+        unsafe { core::ptr::copy_nonoverlapping(code.as_ptr(), address as *mut u8, code.len()) };
+        (buf, address)
+    }
+
+    #[rstest]
+    #[case::b("fe0300ea", Some((4, 0x1000)))] // b #0x1000
+    #[case::b_backwards("fcfbffea", Some((4, -0x1008)))] // b #-0x1008
+    #[case::beq("fe03000a", None)] // beq #0x1000
+    #[case::bl("fe0300eb", None)] // bl #0x1000
+    #[case::blx("fe0300fa", None)] // blx #0x1000
+    #[case::nop("00f020e3", None)]
+    fn find_arm_branch(#[case] code: &str, #[case] expected: Option<(usize, isize)>) {
+        let (_buf, address) = buffer(code, 0);
+        let expected =
+            expected.map(|(len, x)| ForeignHook::new(len, address.wrapping_add_signed(x)));
+        assert_eq!(unsafe { find_foreign_hook_arm(address) }, expected);
+    }
+
+    #[rstest]
+    #[case::arm("04f01fe578563412", Some(0x12345678))] // ldr pc, [pc, #-4]
+    #[case::to_thumb("04f01fe579563412", None)] // ldr pc, [pc, #-4]
+    #[case::other_offset("00f01fe578563412", None)] // ldr pc, [pc, #-0]
+    fn find_arm_ldr_pc(#[case] code: &str, #[case] target: Option<usize>) {
+        let (_buf, address) = buffer(code, 0);
+        let expected = target.map(|x| ForeignHook::new(8, x));
+        assert_eq!(unsafe { find_foreign_hook_arm(address) }, expected);
+    }
+
+    #[rstest]
+    #[case::b_w("00f0febf", Some((4, 0x1000)))] // b.w #0x1000
+    #[case::b_w_backwards("fef7fcbf", Some((4, -0x1004)))] // b.w #-0x1004
+    #[case::b("fee7", Some((2, 0)))] // b #0
+    #[case::beq("00f0fe87", None)] // beq.w #0x1000
+    #[case::bl("00f0feff", None)] // bl #0x1000
+    #[case::blx("00f0feef", None)] // blx #0x1000
+    #[case::nop("00bf", None)]
+    fn find_thumb_branch(#[case] code: &str, #[case] expected: Option<(usize, isize)>) {
+        let (_buf, address) = buffer(code, 0);
+        let expected =
+            expected.map(|(len, x)| ForeignHook::new(len, address.wrapping_add_signed(x)));
+        assert_eq!(unsafe { find_foreign_hook_thumb(address) }, expected);
+        assert_eq!(unsafe { find_foreign_hook_thumb(address | 1) }, expected);
+    }
+
+    #[rstest]
+    #[case::aligned(0, "dff800f079563412", Some(0x12345678))] // ldr.w pc, [pc, #0]
+    #[case::unaligned(2, "dff802f079563412", Some(0x12345678))] // ldr.w pc, [pc, #2]
+    #[case::aligned_wrong_offset(0, "dff802f079563412", None)] // ldr.w pc, [pc, #2]
+    #[case::to_arm(0, "dff800f078563412", None)] // ldr.w pc, [pc, #0]
+    #[case::not_pc(0, "dff800e079563412", None)] // ldr.w lr, [pc, #0]
+    fn find_thumb_ldr_pc(#[case] offset: usize, #[case] code: &str, #[case] target: Option<usize>) {
+        let (_buf, address) = buffer(code, offset);
+        let expected = target.map(|x| ForeignHook::new(8, x));
+        assert_eq!(unsafe { find_foreign_hook_thumb(address) }, expected);
+    }
+}
@@ -0,0 +1,98 @@
+extern crate alloc;
+
+use crate::{
+    instruction_set::InstructionSet,
+    instructions::{
+        branch::{encode_branch, encode_branch_register, CONDITION_AL},
+        data_processing::{encode_mov_constant, with_condition},
+    },
+    jit_instructions::branch_absolute::encode_jump_absolute_literal,
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Emits a jump from `new_address` to `target_address`.
+///
+/// # Parameters
+///
+/// * `isa`: Instruction set of the emitted code.
+/// * `target_address`: Interworking address to jump to; bit 0 set if the target is Thumb code.
+/// * `new_address`: The address the first emitted instruction will be placed at.
+/// * `buf`: The buffer to which the instructions will be appended.
+///
+/// # Behaviour
+///
+/// The jump is emitted as one of the following:
+/// - B (B.W in Thumb)
+/// - LDR PC, [PC, #-4] + address (LDR.W PC, [PC] + address in Thumb)
+pub(crate) fn emit_jump(
+    isa: InstructionSet,
+    target_address: usize,
+    new_address: usize,
+    buf: &mut Vec<u8>,
+) {
+    if encode_branch(isa, new_address, target_address, CONDITION_AL, false, buf).is_ok() {
+        return;
+    }
+
+    // LDR to PC with a constant offset can't fail.
+    let mut pc = new_address;
+    encode_jump_absolute_literal(target_address, isa, &mut pc, buf).unwrap();
+}
+
+/// Emits a call from `new_address` to `target_address`.
+///
+/// # Parameters
+///
+/// * `isa`: Instruction set of the emitted code.
+/// * `target_address`: Interworking address to call; bit 0 set if the target is Thumb code.
+/// * `new_address`: The address the first emitted instruction will be placed at.
+/// * `scratch_register`: Register used to hold the target when it is further than `BL` can reach.
+/// * `buf`: The buffer to which the instructions will be appended.
+///
+/// # Behaviour
+///
+/// The call is emitted as one of the following:
+/// - BL (or BLX)
+/// - MOVW + MOVT + BLX
+pub(crate) fn emit_call(
+    isa: InstructionSet,
+    target_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+    buf: &mut Vec<u8>,
+) -> Result<(), CodeRewriterError> {
+    if encode_branch(isa, new_address, target_address, CONDITION_AL, true, buf).is_ok() {
+        return Ok(());
+    }
+
+    let scratch = scratch_register
+        .ok_or_else(|| CodeRewriterError::NoScratchRegister("emit_call".to_string()))?;
+
+    encode_mov_constant(isa, scratch, target_address as u32, buf);
+    encode_branch_register(isa, true, scratch, buf);
+    Ok(())
+}
+
+/// Makes all ARM (A32) instructions in `buf`, starting at `start`, conditional.
+/// Used when rewriting conditional instructions into multiple instructions.
+pub(crate) fn apply_condition(buf: &mut [u8], start: usize, condition: u8) {
+    if condition == CONDITION_AL {
+        return;
+    }
+
+    for chunk in buf[start..].chunks_exact_mut(4) {
+        let instruction = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        chunk.copy_from_slice(&with_condition(instruction, condition).to_le_bytes());
+    }
+}
+
+/// Adds or subtracts `imm` from `base`, depending on the U bit (23) of the instruction.
+/// Note: For ARM `ADR`, bit 23 distinguishes `ADD` from `SUB`.
+pub(crate) fn apply_sign(base: usize, imm: usize, instruction: u32) -> usize {
+    if instruction & (1 << 23) != 0 {
+        base.wrapping_add(imm)
+    } else {
+        base.wrapping_sub(imm)
+    }
+}
@@ -0,0 +1,66 @@
+extern crate alloc;
+
+use crate::{
+    instruction_set::InstructionSet,
+    instructions::data_processing::{encode_adr, encode_mov_constant},
+};
+use alloc::vec::Vec;
+
+/// Rewrites an `ADR` (`ADD`/`SUB rd, pc, #imm`) producing `value`, for a new address.
+///
+/// # Parameters
+///
+/// * `isa`: Instruction set of the instruction.
+/// * `rd`: The destination register.
+/// * `value`: The address computed by the original instruction.
+/// * `new_address`: The new address of the instruction.
+/// * `buf`: The buffer to which the instructions will be appended.
+///
+/// # Behaviour
+///
+/// The instruction is rewritten as one of the following:
+/// - ADR
+/// - MOVW + MOVT
+pub(crate) fn rewrite_adr(
+    isa: InstructionSet,
+    rd: u8,
+    value: usize,
+    new_address: usize,
+    buf: &mut Vec<u8>,
+) {
+    let offset = value.wrapping_sub(isa.literal_base(new_address)) as isize;
+    if encode_adr(isa, rd, offset, buf).is_err() {
+        encode_mov_constant(isa, rd, value as u32, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    // add r0, pc, #0x100
+    #[case::arm_in_range(Arm, 0, 0x1108, 0x1000, "010c8fe2")]
+    // sub r1, pc, #8
+    #[case::arm_sub(Arm, 1, 0x1000, 0x1000, "08104fe2")]
+    // movw r2, #0x5678; movt r2, #0x1234
+    #[case::arm_far(Arm, 2, 0x12345678, 0x1000, "782605e3342241e3")]
+    // addw r0, pc, #0x100
+    #[case::thumb_in_range(Thumb, 0, 0x1104, 0x1002, "0ff20010")]
+    // movw r3, #0x5678; movt r3, #0x1234
+    #[case::thumb_far(Thumb, 3, 0x12345678, 0x1000, "45f27863c1f23423")]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] rd: u8,
+        #[case] value: usize,
+        #[case] new_address: usize,
+        #[case] expected: &str,
+    ) {
+        let mut buf = Vec::new();
+        rewrite_adr(isa, rd, value, new_address, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected);
+    }
+}
@@ -0,0 +1,144 @@
+extern crate alloc;
+
+use crate::{
+    code_rewriter::helpers::{emit_call, emit_jump},
+    instruction_set::InstructionSet,
+    instructions::branch::{encode_branch, encode_branch_short, invert_condition, CONDITION_AL},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Rewrites a `B`, `B<cond>`, `BL` or `BLX (immediate)` to `target_address`, for a new address.
+///
+/// # Parameters
+///
+/// * `isa`: Instruction set of the branch.
+/// * `condition`: Condition code of the branch, [`CONDITION_AL`] if unconditional.
+/// * `link`: True if the branch is a call (`BL`, `BLX`).
+/// * `target_address`: Interworking address of the original target.
+/// * `new_address`: The new address of the instruction.
+/// * `scratch_register`: Register used to hold the target of calls that are too far for `BL`.
+/// * `buf`: The buffer to which the instructions will be appended.
+///
+/// # Behaviour
+///
+/// The branch instruction is rewritten as one of the following:
+/// - B<cond> / BL<cond> / BLX
+/// - B<!cond> + LDR PC (literal) + address
+/// - B<!cond> + MOVW + MOVT + BLX
+///
+/// The inverted branch is omitted for unconditional branches.
+pub(crate) fn rewrite_branch_to(
+    isa: InstructionSet,
+    condition: u8,
+    link: bool,
+    target_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+    buf: &mut Vec<u8>,
+) -> Result<(), CodeRewriterError> {
+    if encode_branch(isa, new_address, target_address, condition, link, buf).is_ok() {
+        return Ok(());
+    }
+
+    // Emit the branch first, so we know how far to skip when the condition does not hold.
+    let skip_size = match (condition, isa) {
+        (CONDITION_AL, _) => 0,
+        (_, InstructionSet::Arm) => 4,
+        (_, InstructionSet::Thumb) => 2,
+    };
+
+    let mut branch = Vec::new();
+    let branch_address = new_address.wrapping_add(skip_size);
+    if link {
+        emit_call(
+            isa,
+            target_address,
+            branch_address,
+            scratch_register,
+            &mut branch,
+        )?;
+    } else {
+        emit_jump(isa, target_address, branch_address, &mut branch);
+    }
+
+    if condition != CONDITION_AL {
+        // Skip is at most 16 bytes, so this can't fail.
+        let after_branch = branch_address.wrapping_add(branch.len());
+        encode_branch_short(
+            isa,
+            new_address,
+            after_branch,
+            invert_condition(condition),
+            buf,
+        )
+        .unwrap();
+    }
+
+    buf.extend_from_slice(&branch);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::instructions::branch::{CONDITION_EQ, CONDITION_NE};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    // b #0xff8 -> b #0x1ff8
+    #[case::arm_b(Arm, CONDITION_AL, false, 0x2000, 0, "fe0700ea")]
+    // bleq -> bleq
+    #[case::arm_bl_cond(Arm, CONDITION_EQ, true, 0x2000, 0, "fe07000b")]
+    // blx -> blx (ARM to Thumb)
+    #[case::arm_blx(Arm, CONDITION_AL, true, 0x2001, 0, "fe0700fa")]
+    // b (far) -> ldr pc, [pc, #-4]; .word 0x10000000
+    #[case::arm_b_far(Arm, CONDITION_AL, false, 0x10000000, 0, "04f01fe500000010")]
+    // beq (far) -> bne #0 (skip); ldr pc, [pc, #-4]; .word 0x10000000
+    #[case::arm_beq_far(Arm, CONDITION_EQ, false, 0x10000000, 0, "0100001a04f01fe500000010")]
+    // bl (far) -> movw r12, #0; movt r12, #0x1000; blx r12
+    #[case::arm_bl_far(Arm, CONDITION_AL, true, 0x10000000, 0, "00c000e300c041e33cff2fe1")]
+    // b.w #0x1ffc
+    #[case::thumb_b(Thumb, CONDITION_AL, false, 0x2001, 0, "01f0febf")]
+    // bne.w #0x1ffc
+    #[case::thumb_bne(Thumb, CONDITION_NE, false, 0x2001, 0, "41f0fe87")]
+    // b (far) -> nop; ldr.w pc, [pc]; .word 0x10000001 (aligned literal)
+    #[case::thumb_b_far(Thumb, CONDITION_AL, false, 0x10000001, 2, "00bfdff800f001000010")]
+    // bne (far) -> beq #8 (skip); nop; ldr.w pc, [pc]; .word 0x10000001
+    #[case::thumb_bne_far(Thumb, CONDITION_NE, false, 0x10000001, 0, "04d000bfdff800f001000010")]
+    // bl (far) -> movw r12, #1; movt r12, #0x1000; blx r12
+    #[case::thumb_bl_far(Thumb, CONDITION_AL, true, 0x10000001, 0, "40f2010cc1f2000ce047")]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] condition: u8,
+        #[case] link: bool,
+        #[case] target: usize,
+        #[case] new_address: usize,
+        #[case] expected: &str,
+    ) {
+        let mut buf = Vec::new();
+        rewrite_branch_to(
+            isa,
+            condition,
+            link,
+            target,
+            new_address,
+            Some(12),
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected);
+    }
+
+    #[test]
+    fn error_on_missing_scratch() {
+        let mut buf = Vec::new();
+        let result = rewrite_branch_to(Arm, CONDITION_AL, true, 0x10000000, 0, None, &mut buf);
+        assert!(matches!(
+            result,
+            Err(CodeRewriterError::NoScratchRegister(_))
+        ));
+    }
+}
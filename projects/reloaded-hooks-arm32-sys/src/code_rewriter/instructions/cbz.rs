@@ -0,0 +1,78 @@
+extern crate alloc;
+
+use crate::{
+    code_rewriter::helpers::emit_jump, instruction_set::InstructionSet,
+    instructions::branch::encode_cbz,
+};
+use alloc::vec::Vec;
+
+/// Rewrites a Thumb `CBZ` (or `CBNZ` if `nonzero` is true) to `target_address`, for a new address.
+///
+/// # Parameters
+///
+/// * `nonzero`: True if the instruction is a `CBNZ`.
+/// * `rn`: The register tested against zero.
+/// * `target_address`: Address of the original target.
+/// * `new_address`: The new address of the instruction.
+/// * `buf`: The buffer to which the instructions will be appended.
+///
+/// # Behaviour
+///
+/// The instruction is rewritten as one of the following:
+/// - CBZ
+/// - CBNZ + B.W
+/// - CBNZ + LDR.W PC, [PC] + address
+///
+/// (With CBZ and CBNZ swapped for CBNZ)
+pub(crate) fn rewrite_cbz(
+    nonzero: bool,
+    rn: u8,
+    target_address: usize,
+    new_address: usize,
+    buf: &mut Vec<u8>,
+) {
+    if encode_cbz(nonzero, rn, new_address, target_address, buf).is_ok() {
+        return;
+    }
+
+    // Emit the jump first, so we know how far to skip when the condition does not hold.
+    let mut jump = Vec::new();
+    let jump_address = new_address.wrapping_add(2);
+    emit_jump(
+        InstructionSet::Thumb,
+        target_address,
+        jump_address,
+        &mut jump,
+    );
+
+    // Skip is at most 10 bytes forward, so this can't fail.
+    let after_jump = jump_address.wrapping_add(jump.len());
+    encode_cbz(!nonzero, rn, new_address, after_jump, buf).unwrap();
+    buf.extend_from_slice(&jump);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    // cbz r0 -> cbz r0 (offset 4)
+    #[case::in_range(false, 0, 0x100d, 0x1004, "10b1")]
+    // cbnz r1, #0x800 -> cbz r1, #0 (skip); b.w #0x7fa
+    #[case::b_w(true, 1, 0x1801, 0x1000, "09b100f0fdbb")]
+    // cbz r2, (far) -> cbnz r2, #8 (skip); nop; ldr.w pc, [pc]; .word 0x10000001
+    #[case::far(false, 2, 0x10000001, 0x1000, "22b900bfdff800f001000010")]
+    fn standard_cases(
+        #[case] nonzero: bool,
+        #[case] rn: u8,
+        #[case] target: usize,
+        #[case] new_address: usize,
+        #[case] expected: &str,
+    ) {
+        let mut buf = Vec::new();
+        rewrite_cbz(nonzero, rn, target, new_address, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected);
+    }
+}
@@ -0,0 +1,142 @@
+extern crate alloc;
+
+use crate::{
+    instruction_set::InstructionSet,
+    instructions::{
+        data_processing::encode_mov_constant,
+        load_store::{encode_load_store, LoadStoreKind},
+    },
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Rewrites an `LDR (literal)` or `LDRB (literal)` reading from `address`, for a new address.
+///
+/// # Parameters
+///
+/// * `isa`: Instruction set of the instruction.
+/// * `kind`: Either [`LoadStoreKind::Ldr`] or [`LoadStoreKind::Ldrb`].
+/// * `rt`: The destination register.
+/// * `address`: The address read by the original instruction.
+/// * `new_address`: The new address of the instruction.
+/// * `scratch_register`: Register used to hold the address when `rt` is `pc`.
+/// * `buf`: The buffer to which the instructions will be appended.
+///
+/// # Behaviour
+///
+/// The instruction is rewritten as one of the following:
+/// - LDR rt, [pc, #offset]
+/// - MOVW rt + MOVT rt + LDR rt, [rt]
+/// - MOVW scratch + MOVT scratch + LDR pc, [scratch] (if `rt` is `pc`)
+pub(crate) fn rewrite_ldr_literal(
+    isa: InstructionSet,
+    kind: LoadStoreKind,
+    rt: u8,
+    address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+    buf: &mut Vec<u8>,
+) -> Result<(), CodeRewriterError> {
+    let offset = address.wrapping_sub(isa.literal_base(new_address)) as isize;
+    let in_range = (-4095..=4095).contains(&offset)
+        && encode_load_store(isa, kind, rt, 15, offset as i32, buf).is_ok();
+
+    if !in_range {
+        let address_register = if rt == 15 {
+            scratch_register.ok_or_else(|| {
+                CodeRewriterError::NoScratchRegister("rewrite_ldr_literal".to_string())
+            })?
+        } else {
+            rt
+        };
+
+        // Offset is 0, so this can't fail.
+        encode_mov_constant(isa, address_register, address as u32, buf);
+        encode_load_store(isa, kind, rt, address_register, 0, buf).unwrap();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    // ldr r0, [pc, #0x100]
+    #[case::arm_in_range(Arm, LoadStoreKind::Ldr, 0, 0x1108, 0x1000, "00019fe5")]
+    // ldrb r1, [pc, #-8]
+    #[case::arm_ldrb(Arm, LoadStoreKind::Ldrb, 1, 0x1000, 0x1000, "08105fe5")]
+    // movw r2, #0x5678; movt r2, #0x1234; ldr r2, [r2]
+    #[case::arm_far(
+        Arm,
+        LoadStoreKind::Ldr,
+        2,
+        0x12345678,
+        0x1000,
+        "782605e3342241e3002092e5"
+    )]
+    // movw r12, #0x5678; movt r12, #0x1234; ldr pc, [r12]
+    #[case::arm_far_pc(
+        Arm,
+        LoadStoreKind::Ldr,
+        15,
+        0x12345678,
+        0x1000,
+        "78c605e334c241e300f09ce5"
+    )]
+    // ldr.w r0, [pc, #0x100]
+    #[case::thumb_in_range(Thumb, LoadStoreKind::Ldr, 0, 0x1104, 0x1002, "dff80001")]
+    // movw r3, #0x5678; movt r3, #0x1234; ldr.w r3, [r3]
+    #[case::thumb_far(
+        Thumb,
+        LoadStoreKind::Ldr,
+        3,
+        0x12345678,
+        0x1000,
+        "45f27863c1f23423d3f80030"
+    )]
+    // movw r12, #0x5678; movt r12, #0x1234; ldr.w pc, [r12]
+    #[case::thumb_far_pc(
+        Thumb,
+        LoadStoreKind::Ldr,
+        15,
+        0x12345678,
+        0x1000,
+        "45f2786cc1f2342cdcf800f0"
+    )]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] kind: LoadStoreKind,
+        #[case] rt: u8,
+        #[case] address: usize,
+        #[case] new_address: usize,
+        #[case] expected: &str,
+    ) {
+        let mut buf = Vec::new();
+        rewrite_ldr_literal(isa, kind, rt, address, new_address, Some(12), &mut buf).unwrap();
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected);
+    }
+
+    #[test]
+    fn error_on_missing_scratch() {
+        let mut buf = Vec::new();
+        let result = rewrite_ldr_literal(
+            Arm,
+            LoadStoreKind::Ldr,
+            15,
+            0x12345678,
+            0x1000,
+            None,
+            &mut buf,
+        );
+
+        assert!(matches!(
+            result,
+            Err(CodeRewriterError::NoScratchRegister(_))
+        ));
+    }
+}
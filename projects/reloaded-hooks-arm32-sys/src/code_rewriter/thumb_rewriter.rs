@@ -0,0 +1,411 @@
+extern crate alloc;
+
+use super::{
+    helpers::apply_sign,
+    instructions::{
+        adr::rewrite_adr, branch::rewrite_branch_to, cbz::rewrite_cbz,
+        ldr_literal::rewrite_ldr_literal,
+    },
+};
+use crate::{
+    helpers::{read_thumb16, read_thumb32, thumb_instruction_length},
+    instruction_set::InstructionSet,
+    instructions::{
+        branch::{decode_cbz, decode_thumb_branch},
+        load_store::LoadStoreKind,
+    },
+};
+use alloc::{format, string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Rewrites Thumb-2 (T32) code from one address to another.
+///
+/// Given an original block of code starting at `old_address`, this function
+/// will modify any relative addressing instructions to make them compatible
+/// with a new location starting at `new_address`.
+///
+/// # Parameters
+///
+/// * `old_code`: A pointer to the start of the original block of code.
+/// * `old_code_size`: Amount of bytes to rewrite.
+/// * `old_address`: The address to assume as the source location of the old code.
+/// * `new_address`: The new address for the instructions.
+/// * `scratch_register`
+///     - A scratch general purpose register that can be used for operations.
+///     - This scratch register may or may not be used depending on the code being rewritten.
+///
+/// Bit 0 of the addresses is ignored, so interworking addresses can be passed.
+///
+/// # Behaviour
+///
+/// The following instructions are rewritten; everything else is copied as is:
+/// - `B`, `B<cond>` (16 and 32-bit), `BL`, `BLX (immediate)`
+/// - `CBZ`, `CBNZ`
+/// - `LDR (literal)` (16 and 32-bit), `LDRB (literal)`
+/// - `ADR` (16 and 32-bit)
+///
+/// Rewritten instructions usually change size, so PC relative instructions inside `IT` blocks
+/// can't be rewritten and produce an error, as do `IT` blocks which don't end within the code.
+///
+/// # Returns
+///
+/// Either a re-encode error, in which case the operation fails, or a vector to consume.
+pub(crate) fn rewrite_code_thumb(
+    old_code: *const u8,
+    old_code_size: usize,
+    old_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+    existing_buffer: &mut Vec<u8>,
+) -> Result<(), CodeRewriterError> {
+    let old_address = old_address & !1;
+    let new_address = new_address & !1;
+    let start = existing_buffer.len();
+    let mut offset = 0;
+
+    // Instructions left in the current IT block, and offset of the IT instruction.
+    let mut it_remaining = 0;
+    let mut it_offset = 0;
+
+    while offset < old_code_size {
+        if offset + 2 > old_code_size {
+            return Err(CodeRewriterError::InsufficientBytes);
+        }
+
+        let ins_ptr = old_code.wrapping_add(offset);
+        let first = unsafe { read_thumb16(ins_ptr) };
+        let length = thumb_instruction_length(first);
+        if offset + length > old_code_size {
+            return Err(CodeRewriterError::InsufficientBytes);
+        }
+
+        let instruction = match length {
+            4 => unsafe { read_thumb32(ins_ptr) },
+            _ => first as u32,
+        };
+
+        let source_address = old_address.wrapping_add(offset);
+        let dest_address = new_address.wrapping_add(existing_buffer.len() - start);
+        let rewritten = rewrite_instruction(
+            instruction,
+            length,
+            offset,
+            source_address,
+            dest_address,
+            scratch_register,
+            existing_buffer,
+        )?;
+
+        if rewritten && it_remaining > 0 {
+            return Err(reencode_error(
+                offset,
+                instruction,
+                length,
+                "PC relative instruction inside an IT block.",
+            ));
+        }
+
+        if !rewritten {
+            let bytes = unsafe { core::slice::from_raw_parts(ins_ptr, length) };
+            existing_buffer.extend_from_slice(bytes);
+        }
+
+        if it_remaining > 0 {
+            it_remaining -= 1;
+        } else if is_it(instruction, length) {
+            it_remaining = 4 - (first & 0xF).trailing_zeros();
+            it_offset = offset;
+        }
+
+        offset += length;
+    }
+
+    if it_remaining > 0 {
+        let it = unsafe { read_thumb16(old_code.wrapping_add(it_offset)) };
+        return Err(reencode_error(
+            it_offset,
+            it as u32,
+            2,
+            "IT block extends past the end of the rewritten code.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Rewrites a single instruction, returning `false` if it should be copied as is.
+///
+/// 32-bit instructions hold the first halfword in the upper 16 bits.
+fn rewrite_instruction(
+    instruction: u32,
+    length: usize,
+    offset: usize,
+    source_address: usize,
+    dest_address: usize,
+    scratch_register: Option<u8>,
+    buf: &mut Vec<u8>,
+) -> Result<bool, CodeRewriterError> {
+    let isa = InstructionSet::Thumb;
+    let literal_base = isa.literal_base(source_address);
+
+    let (first, second) = match length {
+        4 => ((instruction >> 16) as u16, instruction as u16),
+        _ => (instruction as u16, 0),
+    };
+
+    if let Some(branch) = decode_thumb_branch(first, second, source_address) {
+        rewrite_branch_to(
+            isa,
+            branch.condition,
+            branch.link,
+            branch.target,
+            dest_address,
+            scratch_register,
+            buf,
+        )?;
+        return Ok(true);
+    }
+
+    if length == 2 {
+        if let Some((nonzero, rn, target)) = decode_cbz(first, source_address) {
+            rewrite_cbz(nonzero, rn, target, dest_address, buf);
+        } else if is_ldr_literal_16(first) {
+            let rt = ((first >> 8) & 0b111) as u8;
+            let address = literal_base + (first & 0xFF) as usize * 4;
+            rewrite_ldr_literal(
+                isa,
+                LoadStoreKind::Ldr,
+                rt,
+                address,
+                dest_address,
+                scratch_register,
+                buf,
+            )?;
+        } else if is_adr_16(first) {
+            let rd = ((first >> 8) & 0b111) as u8;
+            let value = literal_base + (first & 0xFF) as usize * 4;
+            rewrite_adr(isa, rd, value, dest_address, buf);
+        } else {
+            return Ok(false);
+        }
+
+        return Ok(true);
+    }
+
+    if let Some(kind) = ldr_literal_32_kind(instruction) {
+        let rt = ((instruction >> 12) & 0xF) as u8;
+        let address = apply_sign(literal_base, (instruction & 0xFFF) as usize, instruction);
+        rewrite_ldr_literal(isa, kind, rt, address, dest_address, scratch_register, buf)?;
+    } else if is_adr_32(instruction) {
+        let rd = ((instruction >> 8) & 0xF) as u8;
+        if rd == 15 {
+            return Err(reencode_error(
+                offset,
+                instruction,
+                length,
+                "ADR to PC is not supported.",
+            ));
+        }
+
+        let i = (instruction >> 26) & 1;
+        let imm3 = (instruction >> 12) & 0b111;
+        let imm8 = instruction & 0xFF;
+        let imm = (i << 11 | imm3 << 8 | imm8) as usize;
+
+        // Unlike LDR, bit 23 is set for SUB.
+        let value = if instruction & (1 << 23) != 0 {
+            literal_base.wrapping_sub(imm)
+        } else {
+            literal_base.wrapping_add(imm)
+        };
+
+        rewrite_adr(isa, rd, value, dest_address, buf);
+    } else {
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+fn reencode_error(
+    offset: usize,
+    instruction: u32,
+    length: usize,
+    reason: &str,
+) -> CodeRewriterError {
+    let bytes = match length {
+        4 => format!("{:08x}", instruction),
+        _ => format!("{:04x}", instruction),
+    };
+
+    CodeRewriterError::FailedToReencode(offset, bytes, reason.to_string())
+}
+
+/// `IT` (If-Then), which makes up to 4 following instructions conditional.
+pub(crate) fn is_it(instruction: u32, length: usize) -> bool {
+    length == 2 && (instruction & 0xFF00) == 0xBF00 && (instruction & 0xF) != 0
+}
+
+/// 16-bit `LDR rt, [pc, #imm]`.
+pub(crate) fn is_ldr_literal_16(instruction: u16) -> bool {
+    (instruction & 0xF800) == 0x4800
+}
+
+/// 16-bit `ADR rd, #imm`.
+pub(crate) fn is_adr_16(instruction: u16) -> bool {
+    (instruction & 0xF800) == 0xA000
+}
+
+/// 32-bit `LDR.W rt, [pc, #imm]` or `LDRB.W rt, [pc, #imm]`; `None` if neither.
+/// (`LDRB` to `pc` is `PLD`, which is not a load)
+pub(crate) fn ldr_literal_32_kind(instruction: u32) -> Option<LoadStoreKind> {
+    match instruction & 0xFF7F0000 {
+        0xF85F0000 => Some(LoadStoreKind::Ldr),
+        0xF81F0000 if (instruction >> 12) & 0xF != 15 => Some(LoadStoreKind::Ldrb),
+        _ => None,
+    }
+}
+
+/// 32-bit `ADR.W rd, #imm`, i.e. `ADDW rd, pc, #imm` or `SUBW rd, pc, #imm`.
+pub(crate) fn is_adr_32(instruction: u32) -> bool {
+    let opcode = instruction & 0xFBFF8000;
+    opcode == 0xF20F0000 || opcode == 0xF2AF0000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_adr_16, is_adr_32, is_it, is_ldr_literal_16, ldr_literal_32_kind, rewrite_code_thumb,
+    };
+    use crate::{
+        instructions::load_store::LoadStoreKind, test_helpers::instruction_buffer_as_hex_u8,
+    };
+    use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::ldr_literal("4048", true, false, false)] // ldr r0, [pc, #0x100]
+    #[case::ldr("0868", false, false, false)] // ldr r0, [r1]
+    #[case::adr("40a0", false, true, false)] // adr r0, #0x100
+    #[case::add_sp("40a8", false, false, false)] // add r0, sp, #0x100
+    #[case::it("08bf", false, false, true)] // it eq
+    #[case::itete("0bbf", false, false, true)] // itete eq
+    #[case::nop("00bf", false, false, false)] // nop
+    fn can_classify_16bit_instruction(
+        #[case] hex: &str,
+        #[case] expected_ldr: bool,
+        #[case] expected_adr: bool,
+        #[case] expected_it: bool,
+    ) {
+        let instruction = u16::from_str_radix(hex, 16).unwrap().to_be();
+        assert_eq!(is_ldr_literal_16(instruction), expected_ldr);
+        assert_eq!(is_adr_16(instruction), expected_adr);
+        assert_eq!(is_it(instruction as u32, 2), expected_it);
+    }
+
+    #[rstest]
+    #[case::ldr_w("dff80001", Some(LoadStoreKind::Ldr), false)] // ldr.w r0, [pc, #0x100]
+    #[case::ldr_w_neg("5ff80001", Some(LoadStoreKind::Ldr), false)] // ldr.w r0, [pc, #-0x100]
+    #[case::ldrb_w("9ff80001", Some(LoadStoreKind::Ldrb), false)] // ldrb.w r0, [pc, #0x100]
+    #[case::pld("9ff800f1", None, false)] // pld [pc, #0x100]
+    #[case::ldr_reg("d1f80001", None, false)] // ldr.w r0, [r1, #0x100]
+    #[case::addw("0ff20010", None, true)] // addw r0, pc, #0x100
+    #[case::subw("aff20010", None, true)] // subw r0, pc, #0x100
+    #[case::addw_reg("01f20010", None, false)] // addw r0, r1, #0x100
+    fn can_classify_32bit_instruction(
+        #[case] hex: &str,
+        #[case] expected_ldr: Option<LoadStoreKind>,
+        #[case] expected_adr: bool,
+    ) {
+        let bytes = hex::decode(hex).unwrap();
+        let instruction = (u16::from_le_bytes([bytes[0], bytes[1]]) as u32) << 16
+            | u16::from_le_bytes([bytes[2], bytes[3]]) as u32;
+        assert_eq!(ldr_literal_32_kind(instruction), expected_ldr);
+        assert_eq!(is_adr_32(instruction), expected_adr);
+    }
+
+    #[rstest]
+    // mov r0, r1 + add.w r0, r0, #1 -> copied
+    #[case::copy("084600f10100", 0x1000, 0, "084600f10100")]
+    // b #0 at 0x1000 -> b.w #0xffc
+    #[case::b_16("fee7", 0x1000, 0, "00f0febf")]
+    // b.w #0 at 0x1001 (interworking address) -> b.w #0xffc
+    #[case::b_w("fff7febf", 0x1001, 0, "00f0febf")]
+    // bl at 0x12345678 -> movw r12, #0x5679; movt r12, #0x1234; blx r12
+    #[case::bl_far("fff7feff", 0x12345678, 0, "45f2796cc1f2342ce047")]
+    // blx at 0x12345678 -> movw r12, #0x5678; movt r12, #0x1234; blx r12 (to ARM)
+    #[case::blx_far("fff7feef", 0x12345678, 0, "45f2786cc1f2342ce047")]
+    // beq at 0x12345678 -> bne (skip); nop; ldr.w pc, [pc]; .word 0x12345679
+    #[case::beq_far("fed0", 0x12345678, 0, "04d100bfdff800f079563412")]
+    // cbz r0 at 0x1000 -> cbnz r0 (skip); b.w #0xffe
+    #[case::cbz("00b1", 0x1000, 0, "08b900f0ffbf")]
+    // ldr r0, [pc, #0] at 0x800 -> ldr.w r0, [pc, #0x800]
+    #[case::ldr_literal("0048", 0x800, 0, "dff80008")]
+    // adr r0, #0 at 0x12345678 -> movw r0, #0x567c; movt r0, #0x1234
+    #[case::adr_far("00a0", 0x12345678, 0, "45f27c60c1f23420")]
+    // subw r1, pc, #4 at 0x1000 -> addw r1, pc, #0xffc
+    #[case::adr_w("aff20401", 0x1000, 0, "0ff6fc71")]
+    // it eq + moveq r0, r1 + nop -> copied
+    #[case::it("08bf084600bf", 0x1000, 0, "08bf084600bf")]
+    // nop + b #0 -> nop + b.w #0xffc (the branch is now 2 bytes further into the buffer)
+    #[case::b_after_nop("00bffee7", 0x1000, 0, "00bf00f0febf")]
+    fn can_rewrite_code(
+        #[case] old_instruction_hex: &str,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let old_instruction_bytes = hex::decode(old_instruction_hex).unwrap();
+        let mut new_code = Vec::new();
+        let result = rewrite_code_thumb(
+            old_instruction_bytes.as_ptr(),
+            old_instruction_bytes.len(),
+            old_address,
+            new_address,
+            Some(12),
+            &mut new_code,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&new_code), expected_hex);
+    }
+
+    #[rstest]
+    // Only half of a 32-bit instruction.
+    #[case::truncated("00f0", Some(12), CodeRewriterError::InsufficientBytes)]
+    // Far call without a scratch register.
+    #[case::no_scratch("fff7feff", None, CodeRewriterError::NoScratchRegister(String::new()))]
+    // it eq + ldreq r0, [pc, #0]
+    #[case::pc_relative_in_it(
+        "08bf0048",
+        Some(12),
+        CodeRewriterError::FailedToReencode(0, String::new(), String::new())
+    )]
+    // itt eq + moveq r0, r1 (second instruction of the block is not in the code)
+    #[case::it_past_end(
+        "04bf0846",
+        Some(12),
+        CodeRewriterError::FailedToReencode(0, String::new(), String::new())
+    )]
+    fn error_cases(
+        #[case] old_instruction_hex: &str,
+        #[case] scratch_register: Option<u8>,
+        #[case] expected: CodeRewriterError,
+    ) {
+        let old_instruction_bytes = hex::decode(old_instruction_hex).unwrap();
+        let mut new_code = Vec::new();
+        let result = rewrite_code_thumb(
+            old_instruction_bytes.as_ptr(),
+            old_instruction_bytes.len(),
+            0x12345678,
+            0,
+            scratch_register,
+            &mut new_code,
+        );
+
+        assert_eq!(
+            core::mem::discriminant(&result.unwrap_err()),
+            core::mem::discriminant(&expected)
+        );
+    }
+}
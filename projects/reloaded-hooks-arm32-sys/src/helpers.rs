@@ -0,0 +1,75 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::ptr::read_unaligned;
+
+/// Writes an ARM (A32) instruction to the buffer.
+pub(crate) fn push_arm(buf: &mut Vec<u8>, instruction: u32) {
+    buf.extend_from_slice(&instruction.to_le_bytes());
+}
+
+/// Writes a 16-bit Thumb instruction to the buffer.
+pub(crate) fn push_thumb16(buf: &mut Vec<u8>, instruction: u16) {
+    buf.extend_from_slice(&instruction.to_le_bytes());
+}
+
+/// Writes a 32-bit Thumb instruction to the buffer.
+///
+/// `instruction` holds the first halfword in the upper 16 bits, as in the ARM manual;
+/// each halfword is stored in little endian, first halfword first.
+pub(crate) fn push_thumb32(buf: &mut Vec<u8>, instruction: u32) {
+    push_thumb16(buf, (instruction >> 16) as u16);
+    push_thumb16(buf, instruction as u16);
+}
+
+/// Reads an ARM (A32) instruction from the given address.
+pub(crate) unsafe fn read_arm(address: *const u8) -> u32 {
+    u32::from_le(read_unaligned(address as *const u32))
+}
+
+/// Reads a halfword of Thumb code from the given address.
+pub(crate) unsafe fn read_thumb16(address: *const u8) -> u16 {
+    u16::from_le(read_unaligned(address as *const u16))
+}
+
+/// Reads a 32-bit Thumb instruction from the given address, first halfword in the upper 16 bits.
+pub(crate) unsafe fn read_thumb32(address: *const u8) -> u32 {
+    ((read_thumb16(address) as u32) << 16) | read_thumb16(address.wrapping_add(2)) as u32
+}
+
+/// Returns the length of the Thumb instruction starting with the given halfword.
+///
+/// Halfwords starting with `0b11101`, `0b11110` or `0b11111` are the first half of a
+/// 32-bit instruction, everything else is a 16-bit instruction.
+pub(crate) fn thumb_instruction_length(first_halfword: u16) -> usize {
+    if first_halfword >> 11 >= 0b11101 {
+        4
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0x4770, 2)] // bx lr
+    #[case(0xbf00, 2)] // nop
+    #[case(0xe7fe, 2)] // b .
+    #[case(0xe92d, 4)] // push.w
+    #[case(0xf000, 4)] // b.w / bl
+    #[case(0xf8df, 4)] // ldr.w
+    fn can_get_thumb_instruction_length(#[case] halfword: u16, #[case] expected: usize) {
+        assert_eq!(thumb_instruction_length(halfword), expected);
+    }
+
+    #[test]
+    fn thumb32_is_stored_as_two_halfwords() {
+        let mut buf = Vec::new();
+        push_thumb32(&mut buf, 0xF8DFF000); // ldr.w pc, [pc]
+        assert_eq!(hex::encode(&buf), "dff800f0");
+        assert_eq!(unsafe { read_thumb32(buf.as_ptr()) }, 0xF8DFF000);
+    }
+}
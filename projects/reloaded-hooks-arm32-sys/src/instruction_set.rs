@@ -0,0 +1,116 @@
+/// The instruction set a piece of 32-bit ARM code is written in.
+///
+/// # Interworking
+///
+/// The processor switches between the two when branching to an address through a register,
+/// or via `BLX`; bit 0 of the target address selects the instruction set. Addresses with bit 0
+/// set are Thumb code, addresses with bit 0 clear are ARM code.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum InstructionSet {
+    /// A32. Fixed size, 4 byte instructions.
+    Arm,
+
+    /// T32 (Thumb-2). Mix of 2 and 4 byte instructions.
+    Thumb,
+}
+
+impl InstructionSet {
+    /// Returns the instruction set of the code at the given (interworking) address.
+    pub fn from_address(address: usize) -> Self {
+        if address & 1 != 0 {
+            InstructionSet::Thumb
+        } else {
+            InstructionSet::Arm
+        }
+    }
+
+    /// Value read from the PC register, relative to the address of the current instruction.
+    /// Reading PC yields the address of the current instruction + 8 in ARM, and + 4 in Thumb.
+    pub fn pc_offset(&self) -> usize {
+        match self {
+            InstructionSet::Arm => 8,
+            InstructionSet::Thumb => 4,
+        }
+    }
+
+    /// Value read from the PC register by the instruction at the given address.
+    pub fn read_pc(&self, instruction_address: usize) -> usize {
+        instruction_address.wrapping_add(self.pc_offset())
+    }
+
+    /// Base address used by PC relative loads (`LDR literal`, `ADR`) at the given address.
+    /// This is the PC, aligned down to 4 bytes (which only has an effect in Thumb).
+    pub fn literal_base(&self, instruction_address: usize) -> usize {
+        self.read_pc(instruction_address) & !3
+    }
+
+    /// Converts a code address in this instruction set to an interworking address,
+    /// i.e. sets bit 0 for Thumb code.
+    pub fn to_interworking_address(&self, address: usize) -> usize {
+        match self {
+            InstructionSet::Arm => address & !1,
+            InstructionSet::Thumb => address | 1,
+        }
+    }
+
+    /// Converts a branch target passed to the JIT for this instruction set to an
+    /// interworking address.
+    ///
+    /// The ARM JIT honours bit 0 of the target, so generated code can call into Thumb functions.
+    /// The Thumb JIT treats all targets as Thumb code, since addresses computed while hooking
+    /// (e.g. the address right after the stolen instructions) don't carry bit 0.
+    pub fn jit_branch_target(&self, target: usize) -> usize {
+        match self {
+            InstructionSet::Arm => target,
+            InstructionSet::Thumb => target | 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instruction_set::InstructionSet::{self, *};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0x1000, 0x1008, 0x1008)]
+    #[case(Arm, 0x1004, 0x100C, 0x100C)]
+    #[case(Thumb, 0x1000, 0x1004, 0x1004)]
+    #[case(Thumb, 0x1002, 0x1006, 0x1004)]
+    fn can_read_pc(
+        #[case] isa: InstructionSet,
+        #[case] address: usize,
+        #[case] expected_pc: usize,
+        #[case] expected_literal_base: usize,
+    ) {
+        assert_eq!(isa.read_pc(address), expected_pc);
+        assert_eq!(isa.literal_base(address), expected_literal_base);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x1001, 0x1000)]
+    #[case(Arm, 0x1000, 0x1000)]
+    #[case(Thumb, 0x1000, 0x1001)]
+    #[case(Thumb, 0x1001, 0x1001)]
+    fn can_make_interworking_address(
+        #[case] isa: InstructionSet,
+        #[case] address: usize,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(isa.to_interworking_address(address), expected);
+        assert_eq!(InstructionSet::from_address(expected), isa);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x1000, 0x1000)]
+    #[case(Arm, 0x1001, 0x1001)]
+    #[case(Thumb, 0x1000, 0x1001)]
+    #[case(Thumb, 0x1001, 0x1001)]
+    fn can_get_jit_branch_target(
+        #[case] isa: InstructionSet,
+        #[case] target: usize,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(isa.jit_branch_target(target), expected);
+    }
+}
@@ -0,0 +1,496 @@
+extern crate alloc;
+
+use super::errors::{cannot_change_instruction_set, exceeds_maximum_range};
+use crate::{
+    all_registers::AllRegisters,
+    helpers::{push_arm, push_thumb16, push_thumb32, thumb_instruction_length},
+    instruction_set::InstructionSet,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+pub const CONDITION_EQ: u8 = 0b0000;
+pub const CONDITION_NE: u8 = 0b0001;
+pub const CONDITION_HS: u8 = 0b0010;
+pub const CONDITION_LO: u8 = 0b0011;
+pub const CONDITION_AL: u8 = 0b1110;
+
+/// Returns the inverse of a condition code; e.g. `NE` for `EQ`.
+pub fn invert_condition(condition: u8) -> u8 {
+    condition ^ 1
+}
+
+/// A decoded immediate branch instruction (B, B<cond>, BL, BLX).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DecodedBranch {
+    /// Condition code of the branch, [`CONDITION_AL`] if unconditional.
+    pub condition: u8,
+
+    /// True if this is a branch with link (BL, BLX).
+    pub link: bool,
+
+    /// Interworking address of the target; bit 0 is set if the target is Thumb code.
+    pub target: usize,
+}
+
+/// Encodes a 4 byte `B`, `B<cond>`, `BL` or `BLX` to the given target.
+///
+/// # Parameters
+///
+/// - `isa`: Instruction set of the branch itself.
+/// - `address`: Address of the branch.
+/// - `target`: Interworking address of the target; bit 0 set if the target is Thumb code.
+/// - `condition`: Condition code, [`CONDITION_AL`] for an unconditional branch.
+/// - `link`: True to emit a call (`BL`, or `BLX` if the target is in the other instruction set).
+///
+/// # Remarks
+///
+/// Only calls can change instruction set; jumps to the other instruction set, and conditional calls
+/// return an error.
+pub fn encode_branch(
+    isa: InstructionSet,
+    address: usize,
+    target: usize,
+    condition: u8,
+    link: bool,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let exchange = InstructionSet::from_address(target) != isa;
+    if exchange && !(link && condition == CONDITION_AL) {
+        return Err(cannot_change_instruction_set("[Branch]", target));
+    }
+
+    if link && condition != CONDITION_AL && isa == InstructionSet::Thumb {
+        return Err(JitError::InvalidOffset(
+            "[Branch] Thumb can't encode conditional calls.".into(),
+        ));
+    }
+
+    match isa {
+        InstructionSet::Arm => {
+            let offset = (target & !1).wrapping_sub(isa.read_pc(address)) as isize;
+            if !(-0x2000000..=0x1FFFFFE).contains(&offset) {
+                return Err(exceeds_maximum_range("[Branch]", "-+32MiB", offset));
+            }
+
+            let imm24 = ((offset >> 2) as u32) & 0xFFFFFF;
+            let instruction = if exchange {
+                let h = ((offset >> 1) & 1) as u32;
+                0xFA000000 | h << 24 | imm24
+            } else if link {
+                0x0B000000 | (condition as u32) << 28 | imm24
+            } else {
+                0x0A000000 | (condition as u32) << 28 | imm24
+            };
+
+            push_arm(buf, instruction);
+        }
+        InstructionSet::Thumb => {
+            if exchange {
+                // BLX: Offset is relative to the aligned PC, and the ARM target must be aligned.
+                let offset = (target & !3).wrapping_sub(isa.literal_base(address)) as isize;
+                check_thumb_wide_range(offset)?;
+                push_thumb32(buf, encode_thumb_wide_offset(offset, 0xF000C000));
+            } else if link {
+                let offset = (target & !1).wrapping_sub(isa.read_pc(address)) as isize;
+                check_thumb_wide_range(offset)?;
+                push_thumb32(buf, encode_thumb_wide_offset(offset, 0xF000D000));
+            } else if condition == CONDITION_AL {
+                let offset = (target & !1).wrapping_sub(isa.read_pc(address)) as isize;
+                check_thumb_wide_range(offset)?;
+                push_thumb32(buf, encode_thumb_wide_offset(offset, 0xF0009000));
+            } else {
+                let offset = (target & !1).wrapping_sub(isa.read_pc(address)) as isize;
+                if !(-0x100000..=0xFFFFE).contains(&offset) {
+                    return Err(exceeds_maximum_range("[Branch]", "-+1MiB", offset));
+                }
+
+                let s = ((offset >> 20) & 1) as u32;
+                let j2 = ((offset >> 19) & 1) as u32;
+                let j1 = ((offset >> 18) & 1) as u32;
+                let imm6 = ((offset >> 12) & 0x3F) as u32;
+                let imm11 = ((offset >> 1) & 0x7FF) as u32;
+                push_thumb32(
+                    buf,
+                    0xF0008000
+                        | s << 26
+                        | (condition as u32) << 22
+                        | imm6 << 16
+                        | j1 << 13
+                        | j2 << 11
+                        | imm11,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes the shortest `B` or `B<cond>` to the given target, in the same instruction set.
+/// This is 4 bytes in ARM and 2 bytes in Thumb.
+///
+/// Used to branch within generated code, e.g. to skip over a few instructions.
+pub fn encode_branch_short(
+    isa: InstructionSet,
+    address: usize,
+    target: usize,
+    condition: u8,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let target = isa.to_interworking_address(target);
+    if isa == InstructionSet::Arm {
+        return encode_branch(isa, address, target, condition, false, buf);
+    }
+
+    let offset = (target & !1).wrapping_sub(isa.read_pc(address)) as isize;
+    if condition == CONDITION_AL {
+        if !(-2048..=2046).contains(&offset) {
+            return Err(exceeds_maximum_range("[Branch]", "-+2KiB", offset));
+        }
+
+        push_thumb16(buf, 0xE000 | ((offset >> 1) as u16 & 0x7FF));
+    } else {
+        if !(-256..=254).contains(&offset) {
+            return Err(exceeds_maximum_range("[Branch]", "-+256", offset));
+        }
+
+        push_thumb16(
+            buf,
+            0xD000 | (condition as u16) << 8 | ((offset >> 1) as u16 & 0xFF),
+        );
+    }
+
+    Ok(())
+}
+
+/// Encodes Thumb `CBZ rn, <target>` (or `CBNZ` if `nonzero` is true).
+/// The target must be 0..126 bytes after the PC.
+pub fn encode_cbz(
+    nonzero: bool,
+    rn: u8,
+    address: usize,
+    target: usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let offset = (target & !1).wrapping_sub(InstructionSet::Thumb.read_pc(address)) as isize;
+    if !(0..=126).contains(&offset) {
+        return Err(exceeds_maximum_range("[CBZ]", "0..126", offset));
+    }
+
+    let op = nonzero as u16;
+    let i = ((offset >> 6) & 1) as u16;
+    let imm5 = ((offset >> 1) & 0x1F) as u16;
+    push_thumb16(buf, 0xB100 | op << 11 | i << 9 | imm5 << 3 | rn as u16);
+    Ok(())
+}
+
+/// Encodes `BX rm` (or `BLX rm` if `link` is true).
+pub fn encode_branch_register(isa: InstructionSet, link: bool, rm: u8, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => {
+            let opcode = if link { 0xE12FFF30 } else { 0xE12FFF10 };
+            push_arm(buf, opcode | rm as u32);
+        }
+        InstructionSet::Thumb => {
+            let opcode = if link { 0x4780 } else { 0x4700 };
+            push_thumb16(buf, opcode | (rm as u16) << 3);
+        }
+    }
+}
+
+fn check_thumb_wide_range(offset: isize) -> Result<(), JitError<AllRegisters>> {
+    if !(-0x1000000..=0xFFFFFE).contains(&offset) {
+        return Err(exceeds_maximum_range("[Branch]", "-+16MiB", offset));
+    }
+
+    Ok(())
+}
+
+/// Encodes the `S:I1:I2:imm10:imm11` offset used by the Thumb B.W, BL and BLX instructions.
+fn encode_thumb_wide_offset(offset: isize, opcode: u32) -> u32 {
+    let s = ((offset >> 24) & 1) as u32;
+    let i1 = ((offset >> 23) & 1) as u32;
+    let i2 = ((offset >> 22) & 1) as u32;
+    let j1 = (i1 ^ 1) ^ s;
+    let j2 = (i2 ^ 1) ^ s;
+    let imm10 = ((offset >> 12) & 0x3FF) as u32;
+    let imm11 = ((offset >> 1) & 0x7FF) as u32;
+    opcode | s << 26 | imm10 << 16 | j1 << 13 | j2 << 11 | imm11
+}
+
+/// Decodes the `S:I1:I2:imm10:imm11` offset used by the Thumb B.W, BL and BLX instructions.
+fn decode_thumb_wide_offset(instruction: u32) -> isize {
+    let s = (instruction >> 26) & 1;
+    let j1 = (instruction >> 13) & 1;
+    let j2 = (instruction >> 11) & 1;
+    let i1 = (j1 ^ s) ^ 1;
+    let i2 = (j2 ^ s) ^ 1;
+    let imm10 = (instruction >> 16) & 0x3FF;
+    let imm11 = instruction & 0x7FF;
+    let raw = s << 24 | i1 << 23 | i2 << 22 | imm10 << 12 | imm11 << 1;
+    ((raw << 7) as i32 >> 7) as isize
+}
+
+/// Decodes an ARM `B`, `B<cond>`, `BL` or `BLX (immediate)` at the given address.
+pub fn decode_arm_branch(instruction: u32, address: usize) -> Option<DecodedBranch> {
+    if (instruction >> 25) & 0b111 != 0b101 {
+        return None;
+    }
+
+    let condition = (instruction >> 28) as u8;
+    let offset = (((instruction << 8) as i32) >> 6) as isize;
+    let pc = InstructionSet::Arm.read_pc(address);
+
+    if condition == 0b1111 {
+        // BLX (immediate), always switches to Thumb.
+        let h = ((instruction >> 24) & 1) as isize;
+        let target = (pc as isize).wrapping_add(offset + (h << 1)) as usize;
+        return Some(DecodedBranch {
+            condition: CONDITION_AL,
+            link: true,
+            target: target | 1,
+        });
+    }
+
+    Some(DecodedBranch {
+        condition,
+        link: (instruction >> 24) & 1 != 0,
+        target: (pc as isize).wrapping_add(offset) as usize,
+    })
+}
+
+/// Decodes a Thumb `B`, `B<cond>`, `BL` or `BLX (immediate)` at the given address.
+///
+/// # Parameters
+///
+/// - `first`: The first halfword of the instruction.
+/// - `second`: The second halfword of the instruction, ignored for 16-bit instructions.
+/// - `address`: Address of the instruction.
+pub fn decode_thumb_branch(first: u16, second: u16, address: usize) -> Option<DecodedBranch> {
+    let pc = InstructionSet::Thumb.read_pc(address);
+
+    if thumb_instruction_length(first) == 2 {
+        if first & 0xF000 == 0xD000 {
+            // B<cond> (T1), condition 0b1110 and 0b1111 are UDF and SVC.
+            let condition = ((first >> 8) & 0xF) as u8;
+            if condition >= CONDITION_AL {
+                return None;
+            }
+
+            let offset = ((first as i8) as isize) << 1;
+            return Some(DecodedBranch {
+                condition,
+                link: false,
+                target: (pc as isize).wrapping_add(offset) as usize | 1,
+            });
+        }
+
+        if first & 0xF800 == 0xE000 {
+            // B (T2)
+            let offset = ((((first as u32) << 21) as i32) >> 20) as isize;
+            return Some(DecodedBranch {
+                condition: CONDITION_AL,
+                link: false,
+                target: (pc as isize).wrapping_add(offset) as usize | 1,
+            });
+        }
+
+        return None;
+    }
+
+    let instruction = (first as u32) << 16 | second as u32;
+    if instruction & 0xF8008000 != 0xF0008000 {
+        return None;
+    }
+
+    match second & 0xD000 {
+        0x9000 => Some(DecodedBranch {
+            condition: CONDITION_AL,
+            link: false,
+            target: (pc as isize).wrapping_add(decode_thumb_wide_offset(instruction)) as usize | 1,
+        }),
+        0xD000 => Some(DecodedBranch {
+            condition: CONDITION_AL,
+            link: true,
+            target: (pc as isize).wrapping_add(decode_thumb_wide_offset(instruction)) as usize | 1,
+        }),
+        0xC000 if second & 1 == 0 => Some(DecodedBranch {
+            condition: CONDITION_AL,
+            link: true,
+            target: ((pc & !3) as isize).wrapping_add(decode_thumb_wide_offset(instruction))
+                as usize,
+        }),
+        0x8000 => {
+            // B<cond> (T3), conditions 0b1110 and 0b1111 are other instructions.
+            let condition = ((instruction >> 22) & 0xF) as u8;
+            if condition >= CONDITION_AL {
+                return None;
+            }
+
+            let s = (instruction >> 26) & 1;
+            let j2 = (instruction >> 11) & 1;
+            let j1 = (instruction >> 13) & 1;
+            let imm6 = (instruction >> 16) & 0x3F;
+            let imm11 = instruction & 0x7FF;
+            let raw = s << 20 | j2 << 19 | j1 << 18 | imm6 << 12 | imm11 << 1;
+            let offset = ((raw << 11) as i32 >> 11) as isize;
+            Some(DecodedBranch {
+                condition,
+                link: false,
+                target: (pc as isize).wrapping_add(offset) as usize | 1,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Decodes a Thumb `CBZ` or `CBNZ` at the given address.
+/// Returns whether it is a `CBNZ`, the tested register and the target (with bit 0 set).
+pub fn decode_cbz(instruction: u16, address: usize) -> Option<(bool, u8, usize)> {
+    if instruction & 0xF500 != 0xB100 {
+        return None;
+    }
+
+    let nonzero = (instruction >> 11) & 1 != 0;
+    let i = ((instruction >> 9) & 1) as usize;
+    let imm5 = ((instruction >> 3) & 0x1F) as usize;
+    let rn = (instruction & 0b111) as u8;
+    let target = InstructionSet::Thumb.read_pc(address) + (i << 6 | imm5 << 1);
+    Some((nonzero, rn, target | 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0, 0x1008, CONDITION_AL, false, "000400ea")] // b #0x1000
+    #[case(Arm, 0x1000, 0x1000, CONDITION_AL, true, "feffffeb")] // bl #-8
+    #[case(Arm, 0, 0x100B, CONDITION_AL, true, "000400fb")] // blx #0x1002
+    #[case(Arm, 0, 20, CONDITION_EQ, false, "0300000a")] // beq #12
+    #[case(Thumb, 0, 0x1005, CONDITION_AL, false, "01f000b8")] // b.w #0x1000
+    #[case(Thumb, 0x1000, 0x0FFD, CONDITION_AL, true, "fff7fcff")] // bl #-8
+    #[case(Thumb, 0, 0x1004, CONDITION_AL, true, "01f000e8")] // blx #0x1000
+    #[case(Thumb, 2, 0x1004, CONDITION_AL, true, "01f000e8")] // blx #0x1000 (PC is aligned down)
+    #[case(Thumb, 0, 0x1005, CONDITION_EQ, false, "01f00080")] // beq.w #0x1000
+    fn can_encode_branch(
+        #[case] isa: InstructionSet,
+        #[case] address: usize,
+        #[case] target: usize,
+        #[case] condition: u8,
+        #[case] link: bool,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        assert!(encode_branch(isa, address, target, condition, link, &mut buf).is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x2000008)] // out of range
+    #[case(Arm, 0x1001)] // jump to thumb
+    #[case(Thumb, 0x1000005)] // out of range
+    #[case(Thumb, 0x1000)] // jump to arm
+    fn branch_errors(#[case] isa: InstructionSet, #[case] target: usize) {
+        let mut buf = Vec::new();
+        assert!(encode_branch(isa, 0, target, CONDITION_AL, false, &mut buf).is_err());
+        assert!(buf.is_empty());
+    }
+
+    #[rstest]
+    #[case(Thumb, 4, 4, CONDITION_AL, "fee7")] // b #-4
+    #[case(Thumb, 0, 16, CONDITION_EQ, "06d0")] // beq #12
+    #[case(Arm, 0, 20, CONDITION_EQ, "0300000a")] // beq #12
+    fn can_encode_branch_short(
+        #[case] isa: InstructionSet,
+        #[case] address: usize,
+        #[case] target: usize,
+        #[case] condition: u8,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        assert!(encode_branch_short(isa, address, target, condition, &mut buf).is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(false, 0, 12, "20b1")] // cbz r0, #8
+    #[case(true, 3, 130, "fbbb")] // cbnz r3, #126
+    fn can_encode_cbz(
+        #[case] nonzero: bool,
+        #[case] rn: u8,
+        #[case] target: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        assert!(encode_cbz(nonzero, rn, 0, target, &mut buf).is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+        assert_eq!(
+            decode_cbz(u16::from_le_bytes([buf[0], buf[1]]), 0),
+            Some((nonzero, rn, target | 1))
+        );
+    }
+
+    #[rstest]
+    #[case(Arm, false, 12, "1cff2fe1")] // bx r12
+    #[case(Arm, true, 3, "33ff2fe1")] // blx r3
+    #[case(Thumb, false, 12, "6047")] // bx r12
+    #[case(Thumb, true, 3, "9847")] // blx r3
+    fn can_encode_branch_register(
+        #[case] isa: InstructionSet,
+        #[case] link: bool,
+        #[case] rm: u8,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        encode_branch_register(isa, link, rm, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case("000400ea", 0, Some((CONDITION_AL, false, 0x1008)))] // b #0x1000
+    #[case("feffffeb", 0x1000, Some((CONDITION_AL, true, 0x1000)))] // bl #-8
+    #[case("000400fb", 0, Some((CONDITION_AL, true, 0x100B)))] // blx #0x1002
+    #[case("0300000a", 0, Some((CONDITION_EQ, false, 20)))] // beq #12
+    #[case("1cff2fe1", 0, None)] // bx r12
+    fn can_decode_arm_branch(
+        #[case] hex: &str,
+        #[case] address: usize,
+        #[case] expected: Option<(u8, bool, usize)>,
+    ) {
+        let instruction = u32::from_str_radix(hex, 16).unwrap().to_be();
+        let result = decode_arm_branch(instruction, address);
+        assert_eq!(result.map(|x| (x.condition, x.link, x.target)), expected);
+    }
+
+    #[rstest]
+    #[case("01f000b8", 0, Some((CONDITION_AL, false, 0x1005)))] // b.w #0x1000
+    #[case("fff7fcff", 0x1000, Some((CONDITION_AL, true, 0x0FFD)))] // bl #-8
+    #[case("01f000e8", 2, Some((CONDITION_AL, true, 0x1004)))] // blx #0x1000
+    #[case("01f00080", 0, Some((CONDITION_EQ, false, 0x1005)))] // beq.w #0x1000
+    #[case("06d0", 0, Some((CONDITION_EQ, false, 0x11)))] // beq #12
+    #[case("fee7", 4, Some((CONDITION_AL, false, 0x5)))] // b #-4
+    #[case("00bf", 0, None)] // nop
+    #[case("fede", 0, None)] // udf #254
+    #[case("dff800f0", 0, None)] // ldr.w pc, [pc]
+    fn can_decode_thumb_branch(
+        #[case] hex: &str,
+        #[case] address: usize,
+        #[case] expected: Option<(u8, bool, usize)>,
+    ) {
+        let bytes = hex::decode(hex).unwrap();
+        let first = u16::from_le_bytes([bytes[0], bytes[1]]);
+        let second = if bytes.len() == 4 {
+            u16::from_le_bytes([bytes[2], bytes[3]])
+        } else {
+            0
+        };
+
+        let result = decode_thumb_branch(first, second, address);
+        assert_eq!(result.map(|x| (x.condition, x.link, x.target)), expected);
+    }
+}
@@ -0,0 +1,310 @@
+extern crate alloc;
+
+use super::errors::exceeds_maximum_range;
+use crate::{
+    all_registers::AllRegisters,
+    helpers::{push_arm, push_thumb16, push_thumb32},
+    instruction_set::InstructionSet,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+// https://developer.arm.com/documentation/ddi0406/latest (ARMv7-A/R Architecture Reference Manual)
+
+/// Replaces the condition code of an ARM (A32) instruction.
+pub fn with_condition(instruction: u32, condition: u8) -> u32 {
+    (instruction & 0x0FFF_FFFF) | ((condition as u32) << 28)
+}
+
+/// Encodes a value as an ARM (A32) 'modified immediate'; an 8-bit value rotated right by
+/// an even number of bits. Returns `None` if the value can't be represented.
+pub fn encode_arm_modified_immediate(value: u32) -> Option<u32> {
+    for rotation in 0..16 {
+        let imm8 = value.rotate_left(rotation * 2);
+        if imm8 <= 0xFF {
+            return Some((rotation << 8) | imm8);
+        }
+    }
+
+    None
+}
+
+/// Splits a 16-bit immediate into the `i:imm4:imm3:imm8` fields used by the Thumb MOVW/MOVT
+/// and ADDW/SUBW instructions. (imm4 is 0 for the 12-bit instructions)
+fn thumb_imm16_fields(value: u32) -> u32 {
+    let imm4 = (value >> 12) & 0xF;
+    let i = (value >> 11) & 1;
+    let imm3 = (value >> 8) & 0b111;
+    let imm8 = value & 0xFF;
+    (i << 26) | (imm4 << 16) | (imm3 << 12) | imm8
+}
+
+/// Encodes `MOV rd, rm`.
+pub fn encode_mov_register(isa: InstructionSet, rd: u8, rm: u8, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => {
+            push_arm(buf, 0xE1A00000 | (rd as u32) << 12 | rm as u32);
+        }
+        InstructionSet::Thumb => {
+            // T1 encoding, can use any register.
+            let d = (rd as u16 >> 3) & 1;
+            push_thumb16(
+                buf,
+                0x4600 | d << 7 | (rm as u16) << 3 | (rd as u16 & 0b111),
+            );
+        }
+    }
+}
+
+/// Encodes `MOVW rd, #imm16` (if `top` is false) or `MOVT rd, #imm16` (if `top` is true).
+pub fn encode_movw_movt(isa: InstructionSet, top: bool, rd: u8, imm16: u16, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => {
+            let opcode = if top { 0xE3400000 } else { 0xE3000000 };
+            let imm16 = imm16 as u32;
+            push_arm(
+                buf,
+                opcode | ((imm16 >> 12) << 16) | (rd as u32) << 12 | (imm16 & 0xFFF),
+            );
+        }
+        InstructionSet::Thumb => {
+            let opcode = if top { 0xF2C00000 } else { 0xF2400000 };
+            push_thumb32(
+                buf,
+                opcode | thumb_imm16_fields(imm16 as u32) | (rd as u32) << 8,
+            );
+        }
+    }
+}
+
+/// Encodes `MOVW rd, #lo16` followed by `MOVT rd, #hi16` (if the upper 16 bits are non-zero).
+pub fn encode_mov_constant(isa: InstructionSet, rd: u8, value: u32, buf: &mut Vec<u8>) {
+    encode_movw_movt(isa, false, rd, value as u16, buf);
+    if value >> 16 != 0 {
+        encode_movw_movt(isa, true, rd, (value >> 16) as u16, buf);
+    }
+}
+
+/// Encodes `ADD rd, rn, #imm` (or `SUB rd, rn, #-imm` for negative values).
+///
+/// # Remarks
+///
+/// In ARM, the immediate must be representable as a modified immediate.
+/// In Thumb, this is encoded as ADDW/SUBW, with a range of -4095..4095.
+pub fn encode_add_immediate(
+    isa: InstructionSet,
+    rd: u8,
+    rn: u8,
+    value: i32,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let magnitude = value.unsigned_abs();
+    match isa {
+        InstructionSet::Arm => {
+            let imm = match encode_arm_modified_immediate(magnitude) {
+                Some(imm) => imm,
+                None => {
+                    return Err(exceeds_maximum_range(
+                        "[ADD/SUB Immediate]",
+                        "8-bit rotated immediate",
+                        value as isize,
+                    ))
+                }
+            };
+
+            let opcode = if value >= 0 { 0xE2800000 } else { 0xE2400000 };
+            push_arm(buf, opcode | (rn as u32) << 16 | (rd as u32) << 12 | imm);
+        }
+        InstructionSet::Thumb => {
+            if magnitude > 4095 {
+                return Err(exceeds_maximum_range(
+                    "[ADDW/SUBW]",
+                    "-4095..4095",
+                    value as isize,
+                ));
+            }
+
+            let opcode = if value >= 0 { 0xF2000000 } else { 0xF2A00000 };
+            push_thumb32(
+                buf,
+                opcode | (rn as u32) << 16 | thumb_imm16_fields(magnitude) | (rd as u32) << 8,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes `CMP rn, #0`.
+pub fn encode_cmp_zero(isa: InstructionSet, rn: u8, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, 0xE3500000 | (rn as u32) << 16),
+        InstructionSet::Thumb => push_thumb32(buf, 0xF1B00F00 | (rn as u32) << 16),
+    }
+}
+
+/// Encodes `CMP rn, rm`.
+pub fn encode_cmp_register(isa: InstructionSet, rn: u8, rm: u8, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, 0xE1500000 | (rn as u32) << 16 | rm as u32),
+        InstructionSet::Thumb => {
+            if rn < 8 && rm < 8 {
+                push_thumb16(buf, 0x4280 | (rm as u16) << 3 | rn as u16);
+            } else {
+                let n = (rn as u16 >> 3) & 1;
+                push_thumb16(
+                    buf,
+                    0x4500 | n << 7 | (rm as u16) << 3 | (rn as u16 & 0b111),
+                );
+            }
+        }
+    }
+}
+
+/// Encodes `ADR rd, <pc + offset>`, where `offset` is relative to the literal base
+/// (see [`InstructionSet::literal_base`]).
+///
+/// # Remarks
+///
+/// In ARM, the offset must be representable as a modified immediate.
+/// In Thumb, the range is -4095..4095.
+pub fn encode_adr(
+    isa: InstructionSet,
+    rd: u8,
+    offset: isize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !(-4095..=4095).contains(&offset) && isa == InstructionSet::Thumb {
+        return Err(exceeds_maximum_range("[ADR]", "-4095..4095", offset));
+    }
+
+    if !(i32::MIN as isize..=i32::MAX as isize).contains(&offset) {
+        return Err(exceeds_maximum_range("[ADR]", "-+2GiB", offset));
+    }
+
+    // ADR is an alias of ADD/SUB rd, pc, #imm.
+    encode_add_immediate(isa, rd, 15, offset as i32, buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0xFF, Some(0xFF))]
+    #[case(0x1000, Some(0xA01))]
+    #[case(0x104, Some(0xF41))]
+    #[case(0xFF000000, Some(0x4FF))]
+    #[case(0x101, None)]
+    #[case(0x12345678, None)]
+    fn can_encode_modified_immediate(#[case] value: u32, #[case] expected: Option<u32>) {
+        assert_eq!(encode_arm_modified_immediate(value), expected);
+    }
+
+    #[rstest]
+    #[case(Arm, 5, 14, "0e50a0e1")] // mov r5, lr
+    #[case(Thumb, 5, 14, "7546")] // mov r5, lr
+    #[case(Thumb, 14, 0, "8646")] // mov lr, r0
+    fn can_encode_mov_register(
+        #[case] isa: InstructionSet,
+        #[case] rd: u8,
+        #[case] rm: u8,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        encode_mov_register(isa, rd, rm, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, 3, 0x1234, "343201e3")] // movw r3, #0x1234
+    #[case(Arm, 3, 0xABCD1234, "343201e3cd3b4ae3")] // movw r3, #0x1234; movt r3, #0xabcd
+    #[case(Thumb, 3, 0x1234, "41f23423")] // movw r3, #0x1234
+    #[case(Thumb, 12, 0xABCD1234, "41f2342ccaf6cd3c")] // movw r12, #0x1234; movt r12, #0xabcd
+    fn can_encode_mov_constant(
+        #[case] isa: InstructionSet,
+        #[case] rd: u8,
+        #[case] value: u32,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        encode_mov_constant(isa, rd, value, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, 13, 13, 0x1000, "01da8de2")] // add sp, sp, #0x1000
+    #[case(Arm, 13, 13, -0x104, "41df4de2")] // sub sp, sp, #0x104
+    #[case(Thumb, 13, 13, 0x123, "0df2231d")] // addw sp, sp, #0x123
+    #[case(Thumb, 13, 13, -4095, "adf6ff7d")] // subw sp, sp, #4095
+    fn can_encode_add_immediate(
+        #[case] isa: InstructionSet,
+        #[case] rd: u8,
+        #[case] rn: u8,
+        #[case] value: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        assert!(encode_add_immediate(isa, rd, rn, value, &mut buf).is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x101)]
+    #[case(Thumb, 4096)]
+    #[case(Thumb, -4096)]
+    fn add_immediate_out_of_range(#[case] isa: InstructionSet, #[case] value: i32) {
+        let mut buf = Vec::new();
+        let result = encode_add_immediate(isa, 13, 13, value, &mut buf);
+        assert!(matches!(result, Err(JitError::OperandOutOfRange(_))));
+        assert!(buf.is_empty());
+    }
+
+    #[rstest]
+    #[case(Arm, 1, -8, "08104fe2")] // adr r1, #-8
+    #[case(Thumb, 1, -8, "aff20801")] // adr.w r1, #-8
+    #[case(Thumb, 14, 4095, "0ff6ff7e")] // adr.w lr, #4095
+    fn can_encode_adr(
+        #[case] isa: InstructionSet,
+        #[case] rd: u8,
+        #[case] offset: isize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        assert!(encode_adr(isa, rd, offset, &mut buf).is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, 1, "000051e3")] // cmp r1, #0
+    #[case(Thumb, 12, "bcf1000f")] // cmp.w r12, #0
+    fn can_encode_cmp_zero(#[case] isa: InstructionSet, #[case] rn: u8, #[case] expected: &str) {
+        let mut buf = Vec::new();
+        encode_cmp_zero(isa, rn, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected);
+    }
+
+    #[rstest]
+    #[case(Arm, 14, 2, "02005ee1")] // cmp lr, r2
+    #[case(Thumb, 14, 2, "9645")] // cmp lr, r2
+    #[case(Thumb, 1, 2, "9142")] // cmp r1, r2
+    fn can_encode_cmp_register(
+        #[case] isa: InstructionSet,
+        #[case] rn: u8,
+        #[case] rm: u8,
+        #[case] expected: &str,
+    ) {
+        let mut buf = Vec::new();
+        encode_cmp_register(isa, rn, rm, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected);
+    }
+
+    #[test]
+    fn can_replace_condition() {
+        // ldreq r0, [pc] from ldr r0, [pc]
+        assert_eq!(with_condition(0xE59F0000, 0), 0x059F0000);
+    }
+}
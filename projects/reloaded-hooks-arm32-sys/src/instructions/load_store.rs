@@ -0,0 +1,285 @@
+extern crate alloc;
+
+use super::errors::exceeds_maximum_range;
+use crate::{
+    all_registers::AllRegisters,
+    helpers::{push_arm, push_thumb16, push_thumb32},
+    instruction_set::InstructionSet,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+/// Width and direction of a single register load/store.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LoadStoreKind {
+    /// `LDR`, loads a word.
+    Ldr,
+    /// `STR`, stores a word.
+    Str,
+    /// `LDRB`, loads a byte (zero extended).
+    Ldrb,
+    /// `STRB`, stores a byte.
+    Strb,
+}
+
+impl LoadStoreKind {
+    /// Opcode for the ARM 'immediate offset' form, with U (add) bit set.
+    fn arm_opcode(&self) -> u32 {
+        match self {
+            LoadStoreKind::Ldr => 0xE5900000,
+            LoadStoreKind::Str => 0xE5800000,
+            LoadStoreKind::Ldrb => 0xE5D00000,
+            LoadStoreKind::Strb => 0xE5C00000,
+        }
+    }
+
+    /// Opcode for the Thumb 'negative 8-bit offset' form (T4), the 12-bit positive offset
+    /// form (T3) is this + 0x00800000.
+    fn thumb_opcode(&self) -> u32 {
+        match self {
+            LoadStoreKind::Ldr => 0xF8500000,
+            LoadStoreKind::Str => 0xF8400000,
+            LoadStoreKind::Ldrb => 0xF8100000,
+            LoadStoreKind::Strb => 0xF8000000,
+        }
+    }
+}
+
+/// Encodes `<LDR|STR|LDRB|STRB> rt, [rn, #offset]`.
+///
+/// # Remarks
+///
+/// In ARM, the offset range is -4095..4095.
+/// In Thumb, the offset range is -255..4095, or -4095..4095 for `LDR (literal)` (rn = pc).
+///
+/// If `rn` is `pc`, the offset is relative to the literal base; see [`InstructionSet::literal_base`].
+pub fn encode_load_store(
+    isa: InstructionSet,
+    kind: LoadStoreKind,
+    rt: u8,
+    rn: u8,
+    offset: i32,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let magnitude = offset.unsigned_abs();
+    let is_literal = rn == 15;
+    match isa {
+        InstructionSet::Arm => {
+            if magnitude > 4095 {
+                return Err(exceeds_maximum_range(
+                    "[LDR/STR Immediate]",
+                    "-4095..4095",
+                    offset as isize,
+                ));
+            }
+
+            let mut opcode = kind.arm_opcode();
+            if offset < 0 {
+                opcode &= !(1 << 23);
+            }
+
+            push_arm(
+                buf,
+                opcode | (rn as u32) << 16 | (rt as u32) << 12 | magnitude,
+            );
+        }
+        InstructionSet::Thumb => {
+            let opcode = kind.thumb_opcode() | (rn as u32) << 16 | (rt as u32) << 12;
+            if offset >= 0 || (is_literal && magnitude <= 4095) {
+                if magnitude > 4095 {
+                    return Err(exceeds_maximum_range(
+                        "[LDR/STR Immediate]",
+                        "-255..4095",
+                        offset as isize,
+                    ));
+                }
+
+                // Literal loads store the sign in the U bit, like the T3 encoding.
+                let u = if offset >= 0 { 1 << 23 } else { 0 };
+                push_thumb32(buf, opcode | u | magnitude);
+            } else {
+                if magnitude > 255 {
+                    return Err(exceeds_maximum_range(
+                        "[LDR/STR Immediate]",
+                        "-255..4095",
+                        offset as isize,
+                    ));
+                }
+
+                push_thumb32(buf, opcode | 0xC00 | magnitude);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes `PUSH {rt}` for a single general purpose register, i.e. `STR rt, [sp, #-4]!`.
+pub fn encode_push_register(isa: InstructionSet, rt: u8, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, 0xE52D0004 | (rt as u32) << 12),
+        InstructionSet::Thumb => {
+            if rt < 8 || rt == 14 {
+                encode_push_list(isa, 1 << rt, buf);
+            } else {
+                push_thumb32(buf, 0xF84D0D04 | (rt as u32) << 12);
+            }
+        }
+    }
+}
+
+/// Encodes `POP {rt}` for a single general purpose register, i.e. `LDR rt, [sp], #4`.
+pub fn encode_pop_register(isa: InstructionSet, rt: u8, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, 0xE49D0004 | (rt as u32) << 12),
+        InstructionSet::Thumb => {
+            if rt < 8 || rt == 15 {
+                encode_pop_list(isa, 1 << rt, buf);
+            } else {
+                push_thumb32(buf, 0xF85D0B04 | (rt as u32) << 12);
+            }
+        }
+    }
+}
+
+/// Encodes `PUSH {list}` (`STMDB sp!, {list}`), where `list` is a bit mask of registers.
+/// The lowest numbered register is stored at the lowest address.
+///
+/// # Remarks
+///
+/// The list must not contain `sp`, or `pc` in Thumb. A single register should be pushed
+/// using [`encode_push_register`] instead.
+pub fn encode_push_list(isa: InstructionSet, list: u16, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, 0xE92D0000 | list as u32),
+        InstructionSet::Thumb => {
+            if list & !0x40FF == 0 {
+                // r0-r7 and lr only, 16-bit encoding.
+                let m = (list >> 14) & 1;
+                push_thumb16(buf, 0xB400 | m << 8 | (list & 0xFF));
+            } else {
+                push_thumb32(buf, 0xE92D0000 | list as u32);
+            }
+        }
+    }
+}
+
+/// Encodes `POP {list}` (`LDMIA sp!, {list}`), where `list` is a bit mask of registers.
+/// The lowest numbered register is loaded from the lowest address.
+///
+/// # Remarks
+///
+/// The list must not contain `sp`, or both `lr` and `pc` in Thumb. A single register should be
+/// popped using [`encode_pop_register`] instead.
+pub fn encode_pop_list(isa: InstructionSet, list: u16, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, 0xE8BD0000 | list as u32),
+        InstructionSet::Thumb => {
+            if list & !0x80FF == 0 {
+                // r0-r7 and pc only, 16-bit encoding.
+                let p = (list >> 15) & 1;
+                push_thumb16(buf, 0xBC00 | p << 8 | (list & 0xFF));
+            } else {
+                push_thumb32(buf, 0xE8BD0000 | list as u32);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoadStoreKind::{self, *};
+    use super::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, Ldr, 0, 13, 4, "04009de5")] // ldr r0, [sp, #4]
+    #[case(Arm, Ldr, 0, 1, -4, "040011e5")] // ldr r0, [r1, #-4]
+    #[case(Arm, Str, 2, 13, 8, "08208de5")] // str r2, [sp, #8]
+    #[case(Arm, Ldrb, 3, 0, -12, "0c3050e5")] // ldrb r3, [r0, #-12]
+    #[case(Arm, Strb, 3, 0, 12, "0c30c0e5")] // strb r3, [r0, #12]
+    #[case(Arm, Ldr, 15, 15, -4, "04f01fe5")] // ldr pc, [pc, #-4]
+    #[case(Arm, Ldr, 1, 15, 16, "10109fe5")] // ldr r1, [pc, #16]
+    #[case(Thumb, Ldr, 0, 13, 4, "ddf80400")] // ldr.w r0, [sp, #4]
+    #[case(Thumb, Ldr, 0, 1, -4, "51f8040c")] // ldr r0, [r1, #-4]
+    #[case(Thumb, Str, 2, 13, 8, "cdf80820")] // str.w r2, [sp, #8]
+    #[case(Thumb, Ldrb, 3, 0, -12, "10f80c3c")] // ldrb r3, [r0, #-12]
+    #[case(Thumb, Strb, 3, 0, 12, "80f80c30")] // strb.w r3, [r0, #12]
+    #[case(Thumb, Ldr, 1, 15, 16, "dff81010")] // ldr.w r1, [pc, #16]
+    #[case(Thumb, Ldr, 1, 15, -16, "5ff81010")] // ldr.w r1, [pc, #-16]
+    #[case(Thumb, Ldr, 15, 15, 0, "dff800f0")] // ldr.w pc, [pc]
+    fn can_encode_load_store(
+        #[case] isa: InstructionSet,
+        #[case] kind: LoadStoreKind,
+        #[case] rt: u8,
+        #[case] rn: u8,
+        #[case] offset: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        assert!(encode_load_store(isa, kind, rt, rn, offset, &mut buf).is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, 13, 4096)]
+    #[case(Arm, 13, -4096)]
+    #[case(Thumb, 13, 4096)]
+    #[case(Thumb, 13, -256)]
+    #[case(Thumb, 15, -4096)]
+    fn load_store_out_of_range(#[case] isa: InstructionSet, #[case] rn: u8, #[case] offset: i32) {
+        let mut buf = Vec::new();
+        let result = encode_load_store(isa, Ldr, 0, rn, offset, &mut buf);
+        assert!(matches!(result, Err(JitError::OperandOutOfRange(_))));
+        assert!(buf.is_empty());
+    }
+
+    #[rstest]
+    #[case(Arm, 4, "04402de5", "04409de4")] // str r4, [sp, #-4]!; ldr r4, [sp], #4
+    #[case(Thumb, 4, "10b4", "10bc")] // push {r4}; pop {r4}
+    #[case(Thumb, 8, "4df8048d", "5df8048b")] // str r8, [sp, #-4]!; ldr r8, [sp], #4
+    fn can_encode_push_pop_register(
+        #[case] isa: InstructionSet,
+        #[case] rt: u8,
+        #[case] expected_push: &str,
+        #[case] expected_pop: &str,
+    ) {
+        let mut buf = Vec::new();
+        encode_push_register(isa, rt, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_push);
+
+        buf.clear();
+        encode_pop_register(isa, rt, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_pop);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x4030, "30402de9")] // push {r4, r5, lr}
+    #[case(Thumb, 0x4030, "30b5")] // push {r4, r5, lr}
+    #[case(Thumb, 0x4110, "2de91041")] // push.w {r4, r8, lr}
+    fn can_encode_push_list(
+        #[case] isa: InstructionSet,
+        #[case] list: u16,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        encode_push_list(isa, list, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x8030, "3080bde8")] // pop {r4, r5, pc}
+    #[case(Thumb, 0x8030, "30bd")] // pop {r4, r5, pc}
+    #[case(Thumb, 0x8110, "bde81081")] // pop.w {r4, r8, pc}
+    fn can_encode_pop_list(
+        #[case] isa: InstructionSet,
+        #[case] list: u16,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        encode_pop_list(isa, list, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+}
@@ -0,0 +1,100 @@
+extern crate alloc;
+
+use crate::{
+    helpers::{push_arm, push_thumb16, push_thumb32},
+    instruction_set::InstructionSet,
+};
+use alloc::vec::Vec;
+
+/// ARM (A32) `NOP` instruction.
+pub const ARM_NOP: u32 = 0xE320F000;
+
+/// 16-bit Thumb `NOP` instruction.
+pub const THUMB_NOP: u16 = 0xBF00;
+
+/// Encodes `MRC p15, 0, rt, c13, c0, 3`, i.e. reads the user read-only thread ID register
+/// (TPIDRURO), which holds the thread pointer on Linux and Android.
+pub fn encode_read_thread_pointer(isa: InstructionSet, rt: u8, buf: &mut Vec<u8>) {
+    let instruction = 0xEE1D0F70 | (rt as u32) << 12;
+    match isa {
+        InstructionSet::Arm => push_arm(buf, instruction),
+        InstructionSet::Thumb => push_thumb32(buf, instruction),
+    }
+}
+
+/// Encodes `LDREX rt, [rn]`.
+pub fn encode_ldrex(isa: InstructionSet, rt: u8, rn: u8, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, 0xE1900F9F | (rn as u32) << 16 | (rt as u32) << 12),
+        InstructionSet::Thumb => {
+            push_thumb32(buf, 0xE8500F00 | (rn as u32) << 16 | (rt as u32) << 12)
+        }
+    }
+}
+
+/// Encodes `STREX rd, rt, [rn]`. `rd` receives 0 on success, 1 if the exclusive access failed.
+pub fn encode_strex(isa: InstructionSet, rd: u8, rt: u8, rn: u8, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(
+            buf,
+            0xE1800F90 | (rn as u32) << 16 | (rd as u32) << 12 | rt as u32,
+        ),
+        InstructionSet::Thumb => push_thumb32(
+            buf,
+            0xE8400000 | (rn as u32) << 16 | (rt as u32) << 12 | (rd as u32) << 8,
+        ),
+    }
+}
+
+/// Encodes `DMB ISH`.
+pub fn encode_dmb_ish(isa: InstructionSet, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, 0xF57FF05B),
+        InstructionSet::Thumb => push_thumb32(buf, 0xF3BF8F5B),
+    }
+}
+
+/// Encodes a `NOP`.
+pub fn encode_nop(isa: InstructionSet, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, ARM_NOP),
+        InstructionSet::Thumb => push_thumb16(buf, THUMB_NOP),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, "700f1dee")] // mrc p15, 0, r0, c13, c0, 3
+    #[case(Thumb, "1dee700f")] // mrc p15, 0, r0, c13, c0, 3
+    fn can_encode_read_thread_pointer(#[case] isa: InstructionSet, #[case] expected_hex: &str) {
+        let mut buf = Vec::new();
+        encode_read_thread_pointer(isa, 0, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, "9f1f90e1912f80e15bf07ff5")] // ldrex r1, [r0]; strex r2, r1, [r0]; dmb ish
+    #[case(Thumb, "50e8001f40e80012bff35b8f")] // ldrex r1, [r0]; strex r2, r1, [r0]; dmb ish
+    fn can_encode_exclusive(#[case] isa: InstructionSet, #[case] expected_hex: &str) {
+        let mut buf = Vec::new();
+        encode_ldrex(isa, 1, 0, &mut buf);
+        encode_strex(isa, 2, 1, 0, &mut buf);
+        encode_dmb_ish(isa, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, "00f020e3")] // nop
+    #[case(Thumb, "00bf")] // nop
+    fn can_encode_nop(#[case] isa: InstructionSet, #[case] expected_hex: &str) {
+        let mut buf = Vec::new();
+        encode_nop(isa, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+}
@@ -0,0 +1,144 @@
+extern crate alloc;
+
+use super::errors::{exceeds_maximum_range, must_be_divisible_by};
+use crate::{
+    all_registers::AllRegisters,
+    helpers::{push_arm, push_thumb32},
+    instruction_set::InstructionSet,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+// VFP instructions are encoded identically in ARM and Thumb; the ARM condition field
+// (always 0b1110 here) matches the fixed upper bits of the Thumb encoding.
+
+fn push_vfp(isa: InstructionSet, instruction: u32, buf: &mut Vec<u8>) {
+    match isa {
+        InstructionSet::Arm => push_arm(buf, instruction),
+        InstructionSet::Thumb => push_thumb32(buf, instruction),
+    }
+}
+
+/// Encodes `VMOV.F64 dd, dm`.
+pub fn encode_vmov(isa: InstructionSet, dd: u8, dm: u8, buf: &mut Vec<u8>) {
+    push_vfp(isa, 0xEEB00B40 | (dd as u32) << 12 | dm as u32, buf);
+}
+
+/// Encodes `VLDR dd, [rn, #offset]` (if `load` is true) or `VSTR dd, [rn, #offset]`.
+/// The offset must be a multiple of 4 in range -1020..1020.
+pub fn encode_vldr_vstr(
+    isa: InstructionSet,
+    load: bool,
+    dd: u8,
+    rn: u8,
+    offset: i32,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    if offset & 0b11 != 0 {
+        return Err(must_be_divisible_by("[VLDR/VSTR]", offset as isize, 4));
+    }
+
+    let magnitude = offset.unsigned_abs();
+    if magnitude > 1020 {
+        return Err(exceeds_maximum_range(
+            "[VLDR/VSTR]",
+            "-1020..1020",
+            offset as isize,
+        ));
+    }
+
+    let opcode = if load { 0xED100B00 } else { 0xED000B00 };
+    let u = if offset >= 0 { 1 << 23 } else { 0 };
+    push_vfp(
+        isa,
+        opcode | u | (rn as u32) << 16 | (dd as u32) << 12 | (magnitude >> 2),
+        buf,
+    );
+    Ok(())
+}
+
+/// Encodes `VPUSH {d<first>-d<first + count - 1>}`.
+/// The lowest numbered register is stored at the lowest address.
+pub fn encode_vpush(isa: InstructionSet, first: u8, count: u8, buf: &mut Vec<u8>) {
+    push_vfp(
+        isa,
+        0xED2D0B00 | (first as u32) << 12 | (count as u32 * 2),
+        buf,
+    );
+}
+
+/// Encodes `VPOP {d<first>-d<first + count - 1>}`.
+/// The lowest numbered register is loaded from the lowest address.
+pub fn encode_vpop(isa: InstructionSet, first: u8, count: u8, buf: &mut Vec<u8>) {
+    push_vfp(
+        isa,
+        0xECBD0B00 | (first as u32) << 12 | (count as u32 * 2),
+        buf,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, "491bb0ee")] // vmov.f64 d1, d9
+    #[case(Thumb, "b0ee491b")] // vmov.f64 d1, d9
+    fn can_encode_vmov(#[case] isa: InstructionSet, #[case] expected_hex: &str) {
+        let mut buf = Vec::new();
+        encode_vmov(isa, 1, 9, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[rstest]
+    #[case(Arm, true, 8, 13, 16, "048b9ded")] // vldr d8, [sp, #16]
+    #[case(Arm, false, 15, 13, 1020, "fffb8ded")] // vstr d15, [sp, #1020]
+    #[case(Arm, true, 2, 1, -8, "022b11ed")] // vldr d2, [r1, #-8]
+    #[case(Thumb, true, 8, 13, 16, "9ded048b")] // vldr d8, [sp, #16]
+    fn can_encode_vldr_vstr(
+        #[case] isa: InstructionSet,
+        #[case] load: bool,
+        #[case] dd: u8,
+        #[case] rn: u8,
+        #[case] offset: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let mut buf = Vec::new();
+        assert!(encode_vldr_vstr(isa, load, dd, rn, offset, &mut buf).is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_hex);
+    }
+
+    #[test]
+    fn vldr_vstr_errors() {
+        let mut buf = Vec::new();
+        let result = encode_vldr_vstr(Arm, true, 0, 13, 1024, &mut buf);
+        assert!(matches!(result, Err(JitError::OperandOutOfRange(_))));
+
+        let result = encode_vldr_vstr(Arm, true, 0, 13, 2, &mut buf);
+        assert!(matches!(result, Err(JitError::InvalidOffset(_))));
+        assert!(buf.is_empty());
+    }
+
+    #[rstest]
+    #[case(Arm, 8, 1, "028b2ded", "028bbdec")] // vpush {d8}; vpop {d8}
+    #[case(Arm, 8, 3, "068b2ded", "068bbdec")] // vpush {d8-d10}; vpop {d8-d10}
+    #[case(Thumb, 8, 1, "2ded028b", "bdec028b")] // vpush {d8}; vpop {d8}
+    fn can_encode_vpush_vpop(
+        #[case] isa: InstructionSet,
+        #[case] first: u8,
+        #[case] count: u8,
+        #[case] expected_push: &str,
+        #[case] expected_pop: &str,
+    ) {
+        let mut buf = Vec::new();
+        encode_vpush(isa, first, count, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_push);
+
+        buf.clear();
+        encode_vpop(isa, first, count, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), expected_pop);
+    }
+}
@@ -0,0 +1,395 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::{
+        branch::{decode_arm_branch, decode_thumb_branch, CONDITION_AL},
+        system::{ARM_NOP, THUMB_NOP},
+    },
+    jit_instructions::{
+        atomic_add::encode_atomic_add,
+        branch_absolute::{encode_call_absolute, encode_jump_absolute},
+        branch_ip_relative::{encode_call_ip_relative, encode_jump_ip_relative},
+        branch_relative::{encode_call_relative, encode_jump_relative},
+        enter_guard::encode_enter_guard,
+        exit_guard::encode_exit_guard,
+        filter_caller::encode_filter_caller,
+        jump_absolute_indirect::encode_jump_absolute_indirect,
+        mov::encode_mov,
+        mov_from_stack::encode_mov_from_stack,
+        mov_to_stack::encode_mov_to_stack,
+        multi_pop::encode_multi_pop,
+        multi_push::encode_multi_push,
+        pop::encode_pop,
+        push::encode_push,
+        push_constant::encode_push_constant,
+        push_stack::encode_push_stack,
+        ret::encode_return,
+        stackalloc::encode_stackalloc,
+        xchg::encode_xchg,
+    },
+};
+use alloc::vec::Vec;
+use core::mem::size_of;
+use reloaded_hooks_portable::api::jit::{
+    call_relative_operation::CallRelativeOperation,
+    compiler::{DecodeCallTargetResult, Jit, JitCapabilities, JitError},
+    jump_absolute_operation::JumpAbsoluteOperation,
+    jump_relative_operation::JumpRelativeOperation,
+    operation::Operation,
+};
+use reloaded_hooks_portable::helpers::read_code::read_code_as;
+
+/// JIT which emits ARM (A32) code.
+///
+/// Branch targets with bit 0 set are treated as Thumb code; calls to them are emitted as BLX,
+/// and jumps to them as absolute jumps.
+pub struct JitArm {}
+
+/// JIT which emits Thumb-2 (T32) code.
+///
+/// All branch targets are treated as Thumb code; i.e. bit 0 is set on targets of absolute branches.
+/// The `address` passed to the compile functions may be either the raw address, or the
+/// interworking address (bit 0 set) of the code.
+pub struct JitThumb {}
+
+impl Jit<AllRegisters> for JitArm {
+    fn compile(
+        address: usize,
+        operations: &[Operation<AllRegisters>],
+    ) -> Result<Vec<u8>, JitError<AllRegisters>> {
+        // Usually most opcodes will correspond to 1-2 instructions, however there may be more
+        // in some cases (e.g. loading constants), so we reserve accordingly.
+        let mut buf = Vec::with_capacity(operations.len() * 2 * size_of::<u32>());
+        Self::compile_with_buf(address, operations, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn compile_with_buf(
+        address: usize,
+        operations: &[Operation<AllRegisters>],
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        compile_with_buf(InstructionSet::Arm, address, operations, buf)
+    }
+
+    fn stack_entry_misalignment() -> u32 {
+        0 // uses LR
+    }
+
+    fn code_alignment() -> u32 {
+        4
+    }
+
+    fn max_relative_jump_distances() -> &'static [usize] {
+        // B reaches -+32MiB from PC, which is 8 bytes ahead of the instruction.
+        &[(1024 * 1024 * 32) - 8]
+    }
+
+    fn get_jit_capabilities() -> JitCapabilities {
+        JitCapabilities::CAN_MULTI_PUSH
+            | JitCapabilities::CAN_ENCODE_IP_RELATIVE_CALL
+            | JitCapabilities::CAN_ENCODE_IP_RELATIVE_JUMP
+    }
+
+    fn max_branch_bytes() -> u32 {
+        12 // MOVW + MOVT + BLX
+    }
+
+    fn fill_nops(arr: &mut [u8]) {
+        fill_nops(InstructionSet::Arm, arr);
+    }
+
+    fn encode_jump(
+        x: &JumpRelativeOperation<AllRegisters>,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_jump_relative(x, InstructionSet::Arm, pc, buf)
+    }
+
+    fn max_relative_jump_bytes() -> usize {
+        8 // LDR PC, [PC, #-4] + address, for jumps to Thumb code.
+    }
+
+    fn encode_call(
+        x: &CallRelativeOperation,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_call_relative(x, InstructionSet::Arm, pc, buf)
+    }
+
+    fn decode_call_target(
+        ins_address: usize,
+        ins_length: usize,
+    ) -> Result<DecodeCallTargetResult, &'static str> {
+        if ins_length != 4 {
+            return Err("[ARM: decode_call_target] Instruction is not 4 bytes long.");
+        }
+
+        let num: u32 = u32::from_le(unsafe { read_code_as::<u32>(ins_address) });
+        match decode_arm_branch(num, ins_address) {
+            Some(branch) if branch.condition == CONDITION_AL => {
+                Ok(DecodeCallTargetResult::new(branch.target, branch.link))
+            }
+            _ => Err("[ARM: decode_call_target] This is not an unconditional branch instruction."),
+        }
+    }
+
+    fn encode_abs_jump(
+        x: &JumpAbsoluteOperation<AllRegisters>,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_jump_absolute(x, InstructionSet::Arm, pc, buf)
+    }
+
+    fn max_standard_relative_call_distance() -> usize {
+        (1024 * 1024 * 32) - 8
+    }
+
+    fn standard_relative_call_bytes() -> usize {
+        4
+    }
+
+    fn standard_register_size() -> usize {
+        4
+    }
+}
+
+impl Jit<AllRegisters> for JitThumb {
+    fn compile(
+        address: usize,
+        operations: &[Operation<AllRegisters>],
+    ) -> Result<Vec<u8>, JitError<AllRegisters>> {
+        // Usually most opcodes will correspond to 1-2 instructions, however there may be more
+        // in some cases (e.g. loading constants), so we reserve accordingly.
+        let mut buf = Vec::with_capacity(operations.len() * 2 * size_of::<u32>());
+        Self::compile_with_buf(address, operations, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn compile_with_buf(
+        address: usize,
+        operations: &[Operation<AllRegisters>],
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        compile_with_buf(InstructionSet::Thumb, address & !1, operations, buf)
+    }
+
+    fn stack_entry_misalignment() -> u32 {
+        0 // uses LR
+    }
+
+    fn code_alignment() -> u32 {
+        4
+    }
+
+    fn max_relative_jump_distances() -> &'static [usize] {
+        // B.W reaches -+16MiB from PC, which is 4 bytes ahead of the instruction.
+        &[(1024 * 1024 * 16) - 4]
+    }
+
+    fn get_jit_capabilities() -> JitCapabilities {
+        JitCapabilities::CAN_MULTI_PUSH
+            | JitCapabilities::CAN_ENCODE_IP_RELATIVE_CALL
+            | JitCapabilities::CAN_ENCODE_IP_RELATIVE_JUMP
+    }
+
+    fn max_branch_bytes() -> u32 {
+        10 // NOP + LDR.W PC, [PC] + address; or MOVW + MOVT + BLX
+    }
+
+    fn fill_nops(arr: &mut [u8]) {
+        fill_nops(InstructionSet::Thumb, arr);
+    }
+
+    fn encode_jump(
+        x: &JumpRelativeOperation<AllRegisters>,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_jump_relative(x, InstructionSet::Thumb, pc, buf)
+    }
+
+    fn max_relative_jump_bytes() -> usize {
+        4 // B.W
+    }
+
+    fn encode_call(
+        x: &CallRelativeOperation,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_call_relative(x, InstructionSet::Thumb, pc, buf)
+    }
+
+    fn decode_call_target(
+        ins_address: usize,
+        ins_length: usize,
+    ) -> Result<DecodeCallTargetResult, &'static str> {
+        let ins_address = ins_address & !1;
+        let (first, second) = match ins_length {
+            2 => (u16::from_le(unsafe { read_code_as::<u16>(ins_address) }), 0),
+            4 => (
+                u16::from_le(unsafe { read_code_as::<u16>(ins_address) }),
+                u16::from_le(unsafe { read_code_as::<u16>(ins_address + 2) }),
+            ),
+            _ => return Err("[Thumb: decode_call_target] Instruction is not 2 or 4 bytes long."),
+        };
+
+        match decode_thumb_branch(first, second, ins_address) {
+            Some(branch) if branch.condition == CONDITION_AL => {
+                Ok(DecodeCallTargetResult::new(branch.target, branch.link))
+            }
+            _ => {
+                Err("[Thumb: decode_call_target] This is not an unconditional branch instruction.")
+            }
+        }
+    }
+
+    fn encode_abs_jump(
+        x: &JumpAbsoluteOperation<AllRegisters>,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_jump_absolute(x, InstructionSet::Thumb, pc, buf)
+    }
+
+    fn max_standard_relative_call_distance() -> usize {
+        (1024 * 1024 * 16) - 4
+    }
+
+    fn standard_relative_call_bytes() -> usize {
+        4
+    }
+
+    fn standard_register_size() -> usize {
+        4
+    }
+}
+
+fn compile_with_buf(
+    isa: InstructionSet,
+    address: usize,
+    operations: &[Operation<AllRegisters>],
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let mut pc = address;
+    for operation in operations {
+        encode_instruction_arm32(operation, isa, &mut pc, buf)?;
+    }
+
+    Ok(())
+}
+
+fn fill_nops(isa: InstructionSet, arr: &mut [u8]) {
+    match isa {
+        InstructionSet::Arm => {
+            for chunk in arr.chunks_mut(4) {
+                let len = chunk.len();
+                chunk.copy_from_slice(&ARM_NOP.to_le_bytes()[..len]);
+            }
+        }
+        InstructionSet::Thumb => {
+            for chunk in arr.chunks_mut(2) {
+                let len = chunk.len();
+                chunk.copy_from_slice(&THUMB_NOP.to_le_bytes()[..len]);
+            }
+        }
+    }
+}
+
+fn encode_instruction_arm32(
+    operation: &Operation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    match operation {
+        Operation::None => Ok(()),
+        Operation::Mov(x) => encode_mov(x, isa, pc, buf),
+        Operation::MovFromStack(x) => encode_mov_from_stack(x, isa, pc, buf),
+        Operation::Push(x) => encode_push(x, isa, pc, buf),
+        Operation::PushStack(x) => encode_push_stack(x, isa, pc, buf),
+        Operation::PushConst(x) => encode_push_constant(x, isa, pc, buf),
+        Operation::StackAlloc(x) => encode_stackalloc(x, isa, pc, buf),
+        Operation::Pop(x) => encode_pop(x, isa, pc, buf),
+        Operation::Xchg(x) => encode_xchg(x, isa, pc, buf),
+        Operation::CallAbsolute(x) => encode_call_absolute(x, isa, pc, buf),
+        Operation::CallRelative(x) => encode_call_relative(x, isa, pc, buf),
+        Operation::JumpRelative(x) => encode_jump_relative(x, isa, pc, buf),
+        Operation::JumpAbsolute(x) => encode_jump_absolute(x, isa, pc, buf),
+        Operation::JumpAbsoluteIndirect(x) => encode_jump_absolute_indirect(x, isa, pc, buf),
+        Operation::Return(x) => encode_return(x, isa, pc, buf),
+        Operation::CallIpRelative(x) => encode_call_ip_relative(x, isa, pc, buf),
+        Operation::JumpIpRelative(x) => encode_jump_ip_relative(x, isa, pc, buf),
+        Operation::MultiPush(x) => encode_multi_push(x, isa, pc, buf),
+        Operation::MultiPop(x) => encode_multi_pop(x, isa, pc, buf),
+        Operation::MovToStack(x) => encode_mov_to_stack(x, isa, pc, buf),
+        Operation::AtomicAdd(x) => encode_atomic_add(x, isa, pc, buf),
+        Operation::EnterGuard(x) => encode_enter_guard(x, isa, pc, buf),
+        Operation::ExitGuard(x) => encode_exit_guard(x, isa, pc, buf),
+        Operation::FilterCaller(x) => encode_filter_caller(x, isa, pc, buf),
+        // Only needed for moving between register kinds with no direct move, e.g. x87 to SSE.
+        Operation::MovFloatFromStack(x) => Err(JitError::InvalidRegister(x.target)),
+        Operation::MovFloatToStack(x) => Err(JitError::InvalidRegister(x.register)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jit::{JitArm, JitThumb};
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use reloaded_hooks_portable::api::jit::compiler::Jit;
+    use rstest::rstest;
+
+    #[test]
+    fn can_fill_nops() {
+        let mut arm = [0u8; 8];
+        JitArm::fill_nops(&mut arm);
+        assert_eq!(instruction_buffer_as_hex_u8(&arm), "00f020e300f020e3");
+
+        let mut thumb = [0u8; 6];
+        JitThumb::fill_nops(&mut thumb);
+        assert_eq!(instruction_buffer_as_hex_u8(&thumb), "00bf00bf00bf");
+    }
+
+    #[rstest]
+    #[case("feffffeb", Some((0x1000, true)))] // bl #-8
+    #[case("feffffea", Some((0x1000, false)))] // b #-8
+    #[case("fdfffffa", Some((0xffd, true)))] // blx #-12
+    #[case("fcffff0a", None)] // beq #-8
+    #[case("1eff2fe1", None)] // bx lr
+    fn decode_call_target_arm(#[case] hex: &str, #[case] expected: Option<(usize, bool)>) {
+        let code = hex::decode(hex).unwrap();
+        let address = code.as_ptr() as usize;
+        let result = JitArm::decode_call_target(address, code.len())
+            .ok()
+            .map(|x| (x.target_address.wrapping_sub(address), x.is_call));
+
+        let expected = expected.map(|(target, is_call)| (target.wrapping_sub(0x1000), is_call));
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case("fff7feff", Some((0x1001, true)))] // bl #-4
+    #[case("fff7febf", Some((0x1001, false)))] // b.w #-4
+    #[case("fee7", Some((0x1001, false)))] // b #-4
+    #[case("fed0", None)] // beq #-4
+    #[case("7047", None)] // bx lr
+    fn decode_call_target_thumb(#[case] hex: &str, #[case] expected: Option<(usize, bool)>) {
+        let code = hex::decode(hex).unwrap();
+        let address = code.as_ptr() as usize;
+
+        // Passing the interworking address is fine.
+        let result = JitThumb::decode_call_target(address | 1, code.len())
+            .ok()
+            .map(|x| (x.target_address.wrapping_sub(address), x.is_call));
+
+        let expected = expected.map(|(target, is_call)| (target.wrapping_sub(0x1000), is_call));
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,127 @@
+extern crate alloc;
+
+use super::{gpr_scratch::GprScratch, push_constant::encode_mov_constant_to_reg};
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::{
+        branch::{encode_branch_short, CONDITION_NE},
+        data_processing::{encode_add_immediate, encode_cmp_zero},
+        system::{encode_dmb_ish, encode_ldrex, encode_strex},
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::AtomicAdd};
+
+/// Encoded as MOVW + MOVT + DMB, followed by an LDREX + ADD + STREX + CMP + BNE loop and a final DMB.
+///
+/// Uses 3 general purpose registers; for the address, the value and the store result.
+/// Registers missing from the scratch list are borrowed (and saved on the stack), see [`GprScratch`].
+pub fn encode_atomic_add(
+    x: &AtomicAdd<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let registers = GprScratch::new(&x.scratch.borrow(), 3);
+    let (address, value, status) = (
+        registers.registers[0],
+        registers.registers[1],
+        registers.registers[2],
+    );
+
+    // Encode the add first, so an unencodable value doesn't leave partial code behind.
+    let mut add = Vec::new();
+    encode_add_immediate(isa, value, value, x.value, &mut add)?;
+
+    let mut code_pc = *pc;
+    let mut code = Vec::new();
+    registers.encode_save(isa, &mut code);
+    code_pc += code.len();
+    encode_mov_constant_to_reg(x.address, address, isa, &mut code_pc, &mut code);
+    encode_dmb_ish(isa, &mut code);
+
+    let loop_address = *pc + code.len();
+    encode_ldrex(isa, value, address, &mut code);
+    code.extend_from_slice(&add);
+    encode_strex(isa, status, value, address, &mut code);
+    encode_cmp_zero(isa, status, &mut code);
+    encode_branch_short(isa, *pc + code.len(), loop_address, CONDITION_NE, &mut code)?;
+    encode_dmb_ish(isa, &mut code);
+    registers.encode_restore(isa, &mut code);
+
+    *pc += code.len();
+    buf.extend_from_slice(&code);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::atomic_add::encode_atomic_add;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // movw r0, #0x1234; dmb ish; 1: ldrex r1, [r0]; add r1, r1, #1; strex r2, r1, [r0]; cmp r2, #0; bne 1b; dmb ish
+    #[case(
+        Arm,
+        0x1234,
+        1,
+        vec![r0, r1, r2],
+        "340201e35bf07ff59f1f90e1011081e2912f80e1000052e3faffff1a5bf07ff5"
+    )]
+    // push {r4, r5}; movw r12, #0x1234; dmb ish; 1: ldrex r4, [r12]; sub r4, r4, #1; strex r5, r4, [r12];
+    // cmp r5, #0; bne 1b; dmb ish; pop {r4, r5}
+    #[case(
+        Arm,
+        0x1234,
+        -1,
+        vec![d0, r12],
+        "30002de934c201e35bf07ff59f4f9ce1014044e2945f8ce1000055e3faffff1a5bf07ff53000bde8"
+    )]
+    // push {r4, r5}; movw r12, #0x1234; dmb ish; 1: ldrex r4, [r12]; addw r4, r4, #1; strex r5, r4, [r12];
+    // cmp.w r5, #0; bne 1b; dmb ish; pop {r4, r5}
+    #[case(
+        Thumb,
+        0x1234,
+        1,
+        vec![r12],
+        "30b441f2342cbff35b8f5ce8004f04f201044ce80045b5f1000ff6d1bff35b8f30bc"
+    )]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] address: usize,
+        #[case] value: i32,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = AtomicAdd::new(address, value, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_atomic_add(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x101)]
+    #[case(Thumb, 4096)]
+    fn error_on_out_of_range_value(#[case] isa: InstructionSet, #[case] value: i32) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = AtomicAdd::new(0x1234, value, Rc::new(RefCell::new(vec![r12])));
+
+        let result = encode_atomic_add(&operation, isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
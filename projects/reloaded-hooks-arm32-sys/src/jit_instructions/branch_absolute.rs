@@ -0,0 +1,152 @@
+extern crate alloc;
+
+use super::push_constant::encode_mov_constant_to_reg;
+use crate::{
+    all_registers::AllRegisters,
+    helpers::push_arm,
+    instruction_set::InstructionSet,
+    instructions::{
+        branch::encode_branch_register,
+        load_store::{encode_load_store, LoadStoreKind},
+        system::encode_nop,
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    call_absolute_operation::CallAbsoluteOperation, compiler::JitError,
+    jump_absolute_operation::JumpAbsoluteOperation,
+};
+
+/// Encoded as LDR PC, [PC, #-4] followed by the target address.
+/// In Thumb, this is LDR.W PC, [PC], preceded by a NOP if needed to align the target address.
+///
+/// The scratch register is not used.
+pub fn encode_jump_absolute(
+    x: &JumpAbsoluteOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    encode_jump_absolute_literal(x.target_address, isa, pc, buf)
+}
+
+/// Encoded as MOVW + MOVT + BLX
+pub fn encode_call_absolute(
+    x: &CallAbsoluteOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !x.scratch_register.is_gpr() {
+        return Err(JitError::InvalidRegister(x.scratch_register));
+    }
+
+    let register_number = x.scratch_register.register_number() as u8;
+    let target = isa.jit_branch_target(x.target_address);
+    encode_mov_constant_to_reg(target, register_number, isa, pc, buf);
+
+    let start = buf.len();
+    encode_branch_register(isa, true, register_number, buf);
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+/// Encodes a jump to an absolute address, which is stored right after the jump instruction.
+/// Reaches any address, and can switch instruction set, without needing a scratch register.
+pub(crate) fn encode_jump_absolute_literal(
+    target_address: usize,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let start = buf.len();
+    match isa {
+        InstructionSet::Arm => {
+            encode_load_store(isa, LoadStoreKind::Ldr, 15, 15, -4, buf)?;
+        }
+        InstructionSet::Thumb => {
+            // Loads to PC must be word aligned, and the literal directly follows the LDR.
+            if *pc % 4 == 2 {
+                encode_nop(isa, buf);
+            }
+
+            encode_load_store(isa, LoadStoreKind::Ldr, 15, 15, 0, buf)?;
+        }
+    }
+
+    push_arm(buf, isa.jit_branch_target(target_address) as u32);
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::branch_absolute::{encode_call_absolute, encode_jump_absolute};
+    use crate::test_helpers::assert_encode_with_initial_pc;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0, 0x12345678, "04f01fe578563412")] // ldr pc, [pc, #-4]; .word 0x12345678
+    #[case(Arm, 0, 0x12345679, "04f01fe579563412")] // ldr pc, [pc, #-4]; .word 0x12345679
+    #[case(Thumb, 0, 0x12345678, "dff800f079563412")] // ldr.w pc, [pc]; .word 0x12345679
+    #[case(Thumb, 2, 0x12345678, "00bfdff800f079563412")] // nop; ldr.w pc, [pc]; .word 0x12345679
+    fn jump_absolute(
+        #[case] isa: InstructionSet,
+        #[case] initial_pc: usize,
+        #[case] target_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        let operation = JumpAbs {
+            scratch_register: r12,
+            target_address,
+        };
+
+        assert!(encode_jump_absolute(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    #[rstest]
+    // movw r12, #0x5678; movt r12, #0x1234; blx r12
+    #[case(Arm, 0x12345678, r12, "78c605e334c241e33cff2fe1")]
+    // movw r3, #0x1000; blx r3
+    #[case(Arm, 0x1000, r3, "003001e333ff2fe1")]
+    // movw r12, #0x5679; movt r12, #0x1234; blx r12
+    #[case(Thumb, 0x12345678, r12, "45f2796cc1f2342ce047")]
+    fn call_absolute(
+        #[case] isa: InstructionSet,
+        #[case] target_address: usize,
+        #[case] scratch_register: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = CallAbs {
+            scratch_register,
+            target_address,
+        };
+
+        assert!(encode_call_absolute(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, 0, pc);
+    }
+
+    #[test]
+    fn call_with_float_scratch() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = CallAbs {
+            scratch_register: d0,
+            target_address: 0x1000,
+        };
+
+        let result = encode_call_absolute(&operation, Arm, &mut pc, &mut buf);
+        assert_error!(result, JitError::InvalidRegister(_), pc, buf);
+    }
+}
@@ -0,0 +1,126 @@
+extern crate alloc;
+
+use super::load_pc_relative_value::load_pc_rel_value;
+use crate::{
+    all_registers::AllRegisters, instruction_set::InstructionSet,
+    instructions::branch::encode_branch_register,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError,
+    operation_aliases::{CallIpRel, JumpIpRel},
+};
+
+/// Encoded as LDR (literal) + BLX
+pub fn encode_call_ip_relative(
+    x: &CallIpRel<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !x.scratch.is_gpr() {
+        return Err(JitError::InvalidRegister(x.scratch));
+    }
+
+    let scratch = x.scratch.register_number() as u8;
+    load_pc_rel_value(scratch, Some(scratch), isa, pc, buf, x.target_address)?;
+
+    let start = buf.len();
+    encode_branch_register(isa, true, scratch, buf);
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+/// Encoded as LDR PC, [PC, #offset]
+pub fn encode_jump_ip_relative(
+    x: &JumpIpRel<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !x.scratch.is_gpr() {
+        return Err(JitError::InvalidRegister(x.scratch));
+    }
+
+    let scratch = x.scratch.register_number() as u8;
+    load_pc_rel_value(15, Some(scratch), isa, pc, buf, x.target_address)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::branch_ip_relative::encode_call_ip_relative;
+    use crate::jit_instructions::branch_ip_relative::encode_jump_ip_relative;
+    use crate::test_helpers::assert_encode_with_initial_pc;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0, 8, "00f09fe5")] // ldr pc, [pc]
+    #[case(Arm, 4, 0, "0cf01fe5")] // ldr pc, [pc, #-12]
+    #[case(Thumb, 0, 4, "dff800f0")] // ldr.w pc, [pc]
+    #[case(Thumb, 2, 0, "5ff804f0")] // ldr.w pc, [pc, #-4]
+    fn can_encode_jump_ip_relative(
+        #[case] isa: InstructionSet,
+        #[case] initial_pc: usize,
+        #[case] target_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        assert!(encode_jump_ip_relative(
+            &JumpIpRel {
+                scratch: r12,
+                target_address
+            },
+            isa,
+            &mut pc,
+            &mut buf
+        )
+        .is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 0, 12, "04c09fe53cff2fe1")] // ldr r12, [pc, #4]; blx r12
+    #[case(Thumb, 0, 8, "dff804c0e047")] // ldr.w r12, [pc, #4]; blx r12
+    fn can_encode_call_ip_relative(
+        #[case] isa: InstructionSet,
+        #[case] initial_pc: usize,
+        #[case] target_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        assert!(encode_call_ip_relative(
+            &CallIpRel {
+                scratch: r12,
+                target_address
+            },
+            isa,
+            &mut pc,
+            &mut buf
+        )
+        .is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    #[test]
+    fn error_on_float_scratch() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let result = encode_call_ip_relative(
+            &CallIpRel {
+                scratch: d0,
+                target_address: 0,
+            },
+            Arm,
+            &mut pc,
+            &mut buf,
+        );
+        assert_error!(result, JitError::InvalidRegister(_), pc, buf);
+    }
+}
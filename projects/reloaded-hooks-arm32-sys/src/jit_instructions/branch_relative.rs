@@ -0,0 +1,129 @@
+extern crate alloc;
+
+use super::branch_absolute::encode_jump_absolute_literal;
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::branch::{encode_branch, CONDITION_AL},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    call_relative_operation::CallRelativeOperation, compiler::JitError,
+    jump_relative_operation::JumpRelativeOperation,
+};
+
+/// Encoded as BL, or BLX if the target is in the other instruction set.
+pub fn encode_call_relative(
+    x: &CallRelativeOperation,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let start = buf.len();
+    let target = isa.jit_branch_target(x.target_address);
+    encode_branch(isa, *pc, target, CONDITION_AL, true, buf)?;
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+/// Encoded as B (B.W in Thumb).
+///
+/// B can't switch instruction set, so jumps from ARM to Thumb code are encoded as an
+/// LDR PC, [PC, #-4] followed by the target address instead.
+pub fn encode_jump_relative(
+    x: &JumpRelativeOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let target = isa.jit_branch_target(x.target_address);
+    if InstructionSet::from_address(target) != isa {
+        return encode_jump_absolute_literal(target, isa, pc, buf);
+    }
+
+    let start = buf.len();
+    encode_branch(isa, *pc, target, CONDITION_AL, false, buf)?;
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+/// Encodes a jump to the target; B if it is in range, else an absolute jump.
+/// Used for the bypass branches of the guard and caller filter operations.
+pub(crate) fn encode_jump_relative_or_absolute(
+    target_address: usize,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let jump = JumpRelativeOperation::new(target_address);
+    if encode_jump_relative(&jump, isa, pc, buf).is_ok() {
+        return Ok(());
+    }
+
+    encode_jump_absolute_literal(target_address, isa, pc, buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::branch_relative::{encode_call_relative, encode_jump_relative};
+    use crate::test_helpers::assert_encode_with_initial_pc;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0, 0x1000, "fe0300eb")] // bl #0xff8
+    #[case(Arm, 0x1000, 0, "fefbffeb")] // bl #-0x1008
+    #[case(Arm, 0, 0x1001, "fe0300fa")] // blx #0xff8
+    #[case(Thumb, 0, 0x1000, "00f0feff")] // bl #0xffc
+    #[case(Thumb, 0, 0x1001, "00f0feff")] // bl #0xffc
+    #[case(Thumb, 0x1000, 0, "fef7feff")] // bl #-0x1004
+    fn call_relative(
+        #[case] isa: InstructionSet,
+        #[case] initial_pc: usize,
+        #[case] target: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        let operation = CallRel::new(target);
+
+        assert!(encode_call_relative(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 0, 0x1000, "fe0300ea")] // b #0xff8
+    #[case(Arm, 0x1000, 0, "fefbffea")] // b #-0x1008
+    #[case(Arm, 0, 0x1001, "04f01fe501100000")] // ldr pc, [pc, #-4]; .word 0x1001
+    #[case(Thumb, 0, 0x1000, "00f0febf")] // b.w #0xffc
+    #[case(Thumb, 0x1000, 0, "fef7febf")] // b.w #-0x1004
+    fn jump_relative(
+        #[case] isa: InstructionSet,
+        #[case] initial_pc: usize,
+        #[case] target: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        let operation = JumpRel::new(target);
+
+        assert!(encode_jump_relative(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x2000008)]
+    #[case(Thumb, 0x1000004)]
+    fn out_of_range(#[case] isa: InstructionSet, #[case] target: usize) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let result = encode_call_relative(&CallRel::new(target), isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+
+        let result = encode_jump_relative(&JumpRel::new(target), isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
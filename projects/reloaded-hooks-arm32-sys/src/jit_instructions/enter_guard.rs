@@ -0,0 +1,135 @@
+extern crate alloc;
+
+use super::{branch_relative::encode_jump_relative_or_absolute, gpr_scratch::GprScratch};
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::{
+        branch::{encode_branch_short, CONDITION_EQ},
+        data_processing::{encode_cmp_zero, encode_movw_movt},
+        load_store::{encode_load_store, LoadStoreKind},
+        system::encode_read_thread_pointer,
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::EnterGuard};
+
+/// Encoded as MRC (read thread pointer) + LDRB + CMP + BEQ over a branch to the bypass address,
+/// followed by MOVW + STRB.
+///
+/// Uses 2 general purpose registers; for the thread pointer and the flag.
+/// Registers missing from the scratch list are borrowed (and saved on the stack), see [`GprScratch`];
+/// these are restored before branching to the bypass address.
+pub fn encode_enter_guard(
+    x: &EnterGuard<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let registers = GprScratch::new(&x.scratch.borrow(), 2);
+    let (base, flag) = (registers.registers[0], registers.registers[1]);
+    let offset = x.tls_offset as i32;
+
+    let mut code = Vec::new();
+    registers.encode_save(isa, &mut code);
+    encode_read_thread_pointer(isa, base, &mut code);
+    encode_load_store(isa, LoadStoreKind::Ldrb, flag, base, offset, &mut code)?;
+    encode_cmp_zero(isa, flag, &mut code);
+
+    // Branch to bypass, placed after the BEQ.
+    let branch_address = *pc + code.len();
+    let branch_size = match isa {
+        InstructionSet::Arm => 4,
+        InstructionSet::Thumb => 2,
+    };
+
+    let mut bypass = Vec::new();
+    registers.encode_restore(isa, &mut bypass);
+    let mut bypass_pc = branch_address + branch_size + bypass.len();
+    encode_jump_relative_or_absolute(x.bypass_address, isa, &mut bypass_pc, &mut bypass)?;
+
+    let skip_address = branch_address + branch_size + bypass.len();
+    encode_branch_short(isa, branch_address, skip_address, CONDITION_EQ, &mut code)?;
+    code.extend_from_slice(&bypass);
+
+    encode_movw_movt(isa, false, flag, 1, &mut code);
+    encode_load_store(isa, LoadStoreKind::Strb, flag, base, offset, &mut code)?;
+    registers.encode_restore(isa, &mut code);
+
+    *pc += code.len();
+    buf.extend_from_slice(&code);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::enter_guard::encode_enter_guard;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // mrc p15, 0, r12, c13, c0, 3; ldrb r3, [r12, #16]; cmp r3, #0; beq 1f; b 0x1000; 1: movw r3, #1; strb r3, [r12, #16]
+    #[case(
+        Arm,
+        16,
+        0x1000,
+        vec![r12, r3],
+        "70cf1dee1030dce5000053e30000000afa0300ea013000e31030cce5"
+    )]
+    // str r4, [sp, #-4]!; mrc p15, 0, r12, c13, c0, 3; ldrb r4, [r12, #16]; cmp r4, #0; beq 1f; ldr r4, [sp], #4;
+    // ldr pc, [pc, #-4]; .word 0x12345678; 1: movw r4, #1; strb r4, [r12, #16]; ldr r4, [sp], #4
+    #[case(
+        Arm,
+        16,
+        0x12345678,
+        vec![r12],
+        "04402de570cf1dee1040dce5000054e30200000a04409de404f01fe578563412014000e31040cce504409de4"
+    )]
+    // push {r4}; mrc p15, 0, r12, c13, c0, 3; ldrb.w r4, [r12, #16]; cmp.w r4, #0; beq 1f; pop {r4};
+    // nop; ldr.w pc, [pc]; .word 0x12345679; 1: movw r4, #1; strb.w r4, [r12, #16]; pop {r4}
+    #[case(
+        Thumb,
+        16,
+        0x12345678,
+        vec![r12],
+        "10b41dee70cf9cf81040b4f1000f05d010bc00bfdff800f07956341240f201048cf8104010bc"
+    )]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] tls_offset: isize,
+        #[case] bypass_address: usize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = EnterGuard::new(tls_offset, bypass_address, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_enter_guard(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 4096)]
+    #[case(Arm, -4096)]
+    #[case(Thumb, -256)]
+    fn error_on_out_of_range_offset(#[case] isa: InstructionSet, #[case] tls_offset: isize) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let scratch = vec![r12, r3];
+        let operation = EnterGuard::new(tls_offset, 0x1000, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_enter_guard(&operation, isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
@@ -0,0 +1,96 @@
+extern crate alloc;
+
+use super::gpr_scratch::GprScratch;
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::{
+        data_processing::encode_movw_movt,
+        load_store::{encode_load_store, LoadStoreKind},
+        system::encode_read_thread_pointer,
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::ExitGuard};
+
+/// Encoded as MRC (read thread pointer) + MOVW + STRB.
+///
+/// Uses 2 general purpose registers; for the thread pointer and the zero value.
+/// Registers missing from the scratch list are borrowed (and saved on the stack), see [`GprScratch`].
+pub fn encode_exit_guard(
+    x: &ExitGuard<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let registers = GprScratch::new(&x.scratch.borrow(), 2);
+    let (base, zero) = (registers.registers[0], registers.registers[1]);
+
+    let mut code = Vec::new();
+    registers.encode_save(isa, &mut code);
+    encode_read_thread_pointer(isa, base, &mut code);
+    encode_movw_movt(isa, false, zero, 0, &mut code);
+    encode_load_store(
+        isa,
+        LoadStoreKind::Strb,
+        zero,
+        base,
+        x.tls_offset as i32,
+        &mut code,
+    )?;
+    registers.encode_restore(isa, &mut code);
+
+    *pc += code.len();
+    buf.extend_from_slice(&code);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::exit_guard::encode_exit_guard;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // mrc p15, 0, r12, c13, c0, 3; movw r3, #0; strb r3, [r12, #16]
+    #[case(Arm, 16, vec![r12, r3], "70cf1dee003000e31030cce5")]
+    // str r4, [sp, #-4]!; mrc p15, 0, r12, c13, c0, 3; movw r4, #0; strb r4, [r12, #-16]; ldr r4, [sp], #4
+    #[case(Arm, -16, vec![r12], "04402de570cf1dee004000e310404ce504409de4")]
+    // push {r4}; mrc p15, 0, r12, c13, c0, 3; movw r4, #0; strb.w r4, [r12, #16]; pop {r4}
+    #[case(Thumb, 16, vec![r12], "10b41dee70cf40f200048cf8104010bc")]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] tls_offset: isize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = ExitGuard::new(tls_offset, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_exit_guard(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 4096)]
+    #[case(Thumb, -256)]
+    fn error_on_out_of_range_offset(#[case] isa: InstructionSet, #[case] tls_offset: isize) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = ExitGuard::new(tls_offset, Rc::new(RefCell::new(vec![r12, r3])));
+
+        let result = encode_exit_guard(&operation, isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
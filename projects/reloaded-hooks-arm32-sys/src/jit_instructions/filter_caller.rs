@@ -0,0 +1,158 @@
+extern crate alloc;
+
+use super::{
+    branch_relative::encode_jump_relative_or_absolute, gpr_scratch::GprScratch,
+    push_constant::encode_mov_constant_to_reg,
+};
+use crate::{
+    all_registers::AllRegisters::{self, lr},
+    instruction_set::InstructionSet,
+    instructions::{
+        branch::{encode_branch_short, CONDITION_AL, CONDITION_EQ, CONDITION_HS, CONDITION_LO},
+        data_processing::{encode_add_immediate, encode_cmp_register, encode_cmp_zero},
+        load_store::{encode_load_store, LoadStoreKind},
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::FilterCaller};
+
+/// Encoded as MOVW + MOVT + LDR + B over a branch to the bypass address, followed by a
+/// LDR + LDR + ADD + CMP + BEQ + CMP + BLO + CMP + BHS loop over the table of ranges.
+///
+/// The return address is read from LR. For callers in Thumb code it has bit 0 set; which
+/// doesn't affect the outcome, as both ends of each range are instruction boundaries.
+///
+/// Uses 3 general purpose registers; for the table entry, and the start and end of each range.
+/// Registers missing from the scratch list are borrowed (and saved on the stack), see [`GprScratch`];
+/// these are restored before branching to the bypass address.
+pub fn encode_filter_caller(
+    x: &FilterCaller<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let scratch: Vec<AllRegisters> = x
+        .scratch
+        .borrow()
+        .iter()
+        .filter(|reg| **reg != lr)
+        .copied()
+        .collect();
+
+    let registers = GprScratch::new(&scratch, 3);
+    let (entry, start, end) = (
+        registers.registers[0],
+        registers.registers[1],
+        registers.registers[2],
+    );
+
+    let mut code_pc = *pc;
+    let mut code = Vec::new();
+    registers.encode_save(isa, &mut code);
+    code_pc += code.len();
+    encode_mov_constant_to_reg(x.table_pointer, entry, isa, &mut code_pc, &mut code);
+    encode_load_store(isa, LoadStoreKind::Ldr, entry, entry, 0, &mut code)?;
+
+    // Branch to bypass, placed before the loop.
+    let branch_address = *pc + code.len();
+    let branch_size = match isa {
+        InstructionSet::Arm => 4,
+        InstructionSet::Thumb => 2,
+    };
+
+    let bypass_address = branch_address + branch_size;
+    let mut bypass = Vec::new();
+    registers.encode_restore(isa, &mut bypass);
+    let mut bypass_pc = bypass_address + bypass.len();
+    encode_jump_relative_or_absolute(x.bypass_address, isa, &mut bypass_pc, &mut bypass)?;
+
+    let loop_address = bypass_address + bypass.len();
+    encode_branch_short(isa, branch_address, loop_address, CONDITION_AL, &mut code)?;
+    code.extend_from_slice(&bypass);
+
+    let word = 4;
+    encode_load_store(isa, LoadStoreKind::Ldr, start, entry, 0, &mut code)?;
+    encode_load_store(isa, LoadStoreKind::Ldr, end, entry, word, &mut code)?;
+    encode_add_immediate(isa, entry, entry, word * 2, &mut code)?;
+    encode_cmp_zero(isa, end, &mut code);
+    encode_branch_short(
+        isa,
+        *pc + code.len(),
+        bypass_address,
+        CONDITION_EQ,
+        &mut code,
+    )?;
+    encode_cmp_register(isa, lr as u8, start, &mut code);
+    encode_branch_short(isa, *pc + code.len(), loop_address, CONDITION_LO, &mut code)?;
+    encode_cmp_register(isa, lr as u8, end, &mut code);
+    encode_branch_short(isa, *pc + code.len(), loop_address, CONDITION_HS, &mut code)?;
+    registers.encode_restore(isa, &mut code);
+
+    *pc += code.len();
+    buf.extend_from_slice(&code);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::filter_caller::encode_filter_caller;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // movw r0, #0x2000; ldr r0, [r0]; b 2f; 1: b 0x1000; 2: ldr r1, [r0]; ldr r2, [r0, #4]; add r0, r0, #8;
+    // cmp r2, #0; beq 1b; cmp lr, r1; blo 2b; cmp lr, r2; bhs 2b
+    #[case(
+        Arm,
+        0x2000,
+        0x1000,
+        vec![r0, r1, r2],
+        "000002e3000090e5000000eafb0300ea001090e5042090e5080080e2000052e3f9ffff0a01005ee1f8ffff3a02005ee1f6ffff2a"
+    )]
+    // push {r4, r5}; movw r12, #0x2000; ldr r12, [r12]; b 2f; 1: pop {r4, r5}; ldr pc, [pc, #-4]; .word 0x12345678;
+    // 2: ldr r4, [r12]; ldr r5, [r12, #4]; add r12, r12, #8; cmp r5, #0; beq 1b; cmp lr, r4; blo 2b;
+    // cmp lr, r5; bhs 2b; pop {r4, r5}
+    #[case(
+        Arm,
+        0x2000,
+        0x12345678,
+        vec![lr, r12],
+        "30002de900c002e300c09ce5020000ea3000bde804f01fe57856341200409ce504509ce508c08ce2000055e3f7ffff0a04005ee1f8ffff3a05005ee1f6ffff2a3000bde8"
+    )]
+    // push {r4, r5}; movw r12, #0x2000; ldr.w r12, [r12]; b 2f; 1: pop {r4, r5}; nop; ldr.w pc, [pc]; .word 0x12345679;
+    // 2: ldr.w r4, [r12]; ldr.w r5, [r12, #4]; addw r12, r12, #8; cmp.w r5, #0; beq 1b; cmp lr, r4; blo 2b;
+    // cmp lr, r5; bhs 2b; pop {r4, r5}
+    #[case(
+        Thumb,
+        0x2000,
+        0x12345678,
+        vec![r12],
+        "30b442f2000cdcf800c005e030bc00bfdff800f079563412dcf80040dcf804500cf2080cb5f1000ff0d0a645f4d3ae45f2d230bc"
+    )]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] table_pointer: usize,
+        #[case] bypass_address: usize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = FilterCaller::new(
+            table_pointer,
+            bypass_address,
+            Rc::new(RefCell::new(scratch)),
+        );
+
+        assert!(encode_filter_caller(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
@@ -0,0 +1,114 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::load_store::{
+        encode_pop_list, encode_pop_register, encode_push_list, encode_push_register,
+    },
+};
+use alloc::vec::Vec;
+use smallvec::SmallVec;
+
+/// Callee saved registers which may be borrowed when there are not enough scratch registers.
+/// Low registers come first, as they have shorter encodings in Thumb.
+const BORROWABLE_REGISTERS: [u8; 8] = [4, 5, 6, 7, 8, 9, 10, 11];
+
+/// General purpose registers for an operation which needs more than one of them.
+///
+/// The wrapper generator can usually only spare `r12` on 32-bit ARM, as the remaining caller saved
+/// registers hold parameters. Registers missing from the scratch list are therefore borrowed from
+/// the callee saved registers (r4-r11); these are saved to the stack before they are used,
+/// and restored afterwards.
+pub(crate) struct GprScratch {
+    /// Register numbers, in the order they were requested.
+    pub registers: SmallVec<[u8; 4]>,
+
+    /// Bit mask of the borrowed registers, which need saving.
+    pub borrowed: u16,
+}
+
+impl GprScratch {
+    /// Picks `count` registers, preferring the general purpose registers in `scratch`.
+    pub fn new(scratch: &[AllRegisters], count: usize) -> Self {
+        let mut registers: SmallVec<[u8; 4]> = scratch
+            .iter()
+            .filter(|reg| reg.is_gpr() && **reg != AllRegisters::sp)
+            .map(|reg| reg.register_number() as u8)
+            .take(count)
+            .collect();
+
+        let mut borrowed = 0;
+        for register in BORROWABLE_REGISTERS {
+            if registers.len() >= count {
+                break;
+            }
+
+            if !registers.contains(&register) {
+                registers.push(register);
+                borrowed |= 1 << register;
+            }
+        }
+
+        Self {
+            registers,
+            borrowed,
+        }
+    }
+
+    /// Encodes a PUSH of the borrowed registers, if any.
+    pub fn encode_save(&self, isa: InstructionSet, buf: &mut Vec<u8>) {
+        match self.borrowed.count_ones() {
+            0 => {}
+            1 => encode_push_register(isa, self.borrowed.trailing_zeros() as u8, buf),
+            _ => encode_push_list(isa, self.borrowed, buf),
+        }
+    }
+
+    /// Encodes a POP of the borrowed registers, if any.
+    pub fn encode_restore(&self, isa: InstructionSet, buf: &mut Vec<u8>) {
+        match self.borrowed.count_ones() {
+            0 => {}
+            1 => encode_pop_register(isa, self.borrowed.trailing_zeros() as u8, buf),
+            _ => encode_pop_list(isa, self.borrowed, buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GprScratch;
+    use crate::all_registers::AllRegisters::*;
+    use crate::instruction_set::InstructionSet::*;
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+
+    #[test]
+    fn uses_scratch_registers_first() {
+        let scratch = GprScratch::new(&[d0, sp, r12, r3], 2);
+        assert_eq!(scratch.registers.as_slice(), &[12, 3]);
+        assert_eq!(scratch.borrowed, 0);
+    }
+
+    #[test]
+    fn borrows_missing_registers() {
+        let scratch = GprScratch::new(&[r4, r12], 3);
+        assert_eq!(scratch.registers.as_slice(), &[4, 12, 5]);
+        assert_eq!(scratch.borrowed, 1 << 5);
+
+        let mut buf = Vec::new();
+        scratch.encode_save(Thumb, &mut buf);
+        scratch.encode_restore(Thumb, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), "20b420bc"); // push {r5}; pop {r5}
+    }
+
+    #[test]
+    fn saves_multiple_borrowed_registers() {
+        let scratch = GprScratch::new(&[], 2);
+        assert_eq!(scratch.registers.as_slice(), &[4, 5]);
+
+        let mut buf = Vec::new();
+        scratch.encode_save(Arm, &mut buf);
+        scratch.encode_restore(Arm, &mut buf);
+        assert_eq!(instruction_buffer_as_hex_u8(&buf), "30002de93000bde8"); // push {r4, r5}; pop {r4, r5}
+    }
+}
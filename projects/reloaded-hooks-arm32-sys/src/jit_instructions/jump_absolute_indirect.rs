@@ -0,0 +1,76 @@
+extern crate alloc;
+
+use super::load_pc_relative_value::load_pc_rel_value;
+use crate::{all_registers::AllRegisters, instruction_set::InstructionSet};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::JumpAbsInd};
+
+/// Encoded as LDR PC, [PC, #offset], or MOVW + MOVT + LDR PC if the pointer is further
+/// than +-4095 bytes. The scratch register is only needed in the latter case.
+///
+/// The value at the pointer must be an interworking address; i.e. bit 0 set for Thumb code.
+pub fn encode_jump_absolute_indirect(
+    x: &JumpAbsInd<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let scratch = x
+        .scratch_register
+        .filter(|reg| reg.is_gpr())
+        .map(|reg| reg.register_number() as u8);
+
+    load_pc_rel_value(15, scratch, isa, pc, buf, x.pointer_address)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::jump_absolute_indirect::encode_jump_absolute_indirect;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0x100, None, "f8f09fe5")] // ldr pc, [pc, #0xf8]
+    // movw r12, #0x5678; movt r12, #0x1234; ldr pc, [r12]
+    #[case(Arm, 0x12345678, Some(r12), "78c605e334c241e300f09ce5")]
+    #[case(Thumb, 0x100, None, "dff8fcf0")] // ldr.w pc, [pc, #0xfc]
+    // movw r12, #0x5678; movt r12, #0x1234; ldr.w pc, [r12]
+    #[case(Thumb, 0x12345678, Some(r12), "45f2786cc1f2342cdcf800f0")]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] pointer_address: usize,
+        #[case] scratch_register: Option<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = JumpAbsInd {
+            scratch_register,
+            pointer_address,
+        };
+
+        assert!(encode_jump_absolute_indirect(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(d0))]
+    fn error_on_missing_scratch(#[case] scratch_register: Option<AllRegisters>) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = JumpAbsInd {
+            scratch_register,
+            pointer_address: 0x12345678,
+        };
+
+        let result = encode_jump_absolute_indirect(&operation, Arm, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
@@ -0,0 +1,116 @@
+extern crate alloc;
+
+use super::push_constant::encode_mov_constant_to_reg;
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::load_store::{encode_load_store, LoadStoreKind},
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+/// Loads a 32-bit value at a given address into a register.
+///
+/// # Arguments
+///
+/// * `destination` - The register to load the value into. May be `pc`, making this a jump.
+/// * `address_register` - Register used to hold the address, if it is out of range of the PC.
+/// * `isa` - The instruction set to emit code in.
+/// * `pc` - A mutable reference to the program counter.
+/// * `buf` - A mutable reference to the buffer that will hold the assembled instructions.
+/// * `target_address` - The address of the value that we want to load into the register.
+///
+/// # Returns
+///
+/// Returns a `Result` with an empty `Ok` value if the assembly is successful, or a `JitError` if
+/// there is an error assembling the instructions.
+///
+/// # Remarks
+///
+/// Assembled as LDR (literal) if the address is within +-4095 bytes of the PC, else
+/// MOVW + MOVT + LDR.
+pub fn load_pc_rel_value(
+    destination: u8,
+    address_register: Option<u8>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+    target_address: usize,
+) -> Result<(), JitError<AllRegisters>> {
+    let start = buf.len();
+    let offset = (target_address as isize).wrapping_sub(isa.literal_base(*pc) as isize);
+
+    if (-4095..=4095).contains(&offset) {
+        encode_load_store(isa, LoadStoreKind::Ldr, destination, 15, offset as i32, buf)?;
+        *pc += buf.len() - start;
+        return Ok(());
+    }
+
+    // Out of range, materialize the full address instead.
+    let address_register = match address_register {
+        Some(register) => register,
+        None => {
+            return Err(JitError::NoScratchRegister(
+                "for loading a value out of range of the PC.".to_string(),
+            ))
+        }
+    };
+
+    encode_mov_constant_to_reg(target_address, address_register, isa, pc, buf);
+    let start = buf.len();
+    encode_load_store(
+        isa,
+        LoadStoreKind::Ldr,
+        destination,
+        address_register,
+        0,
+        buf,
+    )?;
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::load_pc_relative_value::load_pc_rel_value;
+    use crate::test_helpers::assert_encode_with_initial_pc;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0, 0x1000, 12, "f8cf9fe5")] // ldr r12, [pc, #0xff8]
+    #[case(Arm, 0x1000, 0x1000, 15, "08f01fe5")] // ldr pc, [pc, #-8]
+    #[case(Arm, 0, 0x12345678, 12, "78c605e334c241e300c09ce5")] // movw r12, #0x5678; movt r12, #0x1234; ldr r12, [r12]
+    #[case(Thumb, 2, 0x1000, 12, "dff8fccf")] // ldr.w r12, [pc, #0xffc]
+    #[case(Thumb, 0, 0x12345678, 15, "45f2786cc1f2342cdcf800f0")] // movw r12, #0x5678; movt r12, #0x1234; ldr.w pc, [r12]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] initial_pc: usize,
+        #[case] target_address: usize,
+        #[case] destination: u8,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        assert!(load_pc_rel_value(
+            destination,
+            Some(12),
+            isa,
+            &mut pc,
+            &mut buf,
+            target_address
+        )
+        .is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    #[test]
+    fn error_on_missing_address_register() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let result = load_pc_rel_value(15, None, Arm, &mut pc, &mut buf, 0x12345678);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
@@ -0,0 +1,82 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::{data_processing::encode_mov_register, vfp::encode_vmov},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, mov_operation::MovOperation};
+
+/// Encoded as MOV for general purpose registers, VMOV.F64 for VFP registers.
+pub fn encode_mov(
+    x: &MovOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let source = x.source.register_number() as u8;
+    let target = x.target.register_number() as u8;
+    let start = buf.len();
+
+    if x.source.is_gpr() && x.target.is_gpr() {
+        encode_mov_register(isa, target, source, buf);
+    } else if x.source.is_vfp() && x.target.is_vfp() {
+        encode_vmov(isa, target, source, buf);
+    } else {
+        return Err(JitError::InvalidRegisterCombination(x.source, x.target));
+    }
+
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::mov::encode_mov;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, r1, r0, "0100a0e1")] // mov r0, r1
+    #[case(Arm, lr, r5, "0e50a0e1")] // mov r5, lr
+    #[case(Arm, d9, d1, "491bb0ee")] // vmov.f64 d1, d9
+    #[case(Thumb, r1, r0, "0846")] // mov r0, r1
+    #[case(Thumb, lr, r5, "7546")] // mov r5, lr
+    #[case(Thumb, d9, d1, "b0ee491b")] // vmov.f64 d1, d9
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] source: AllRegisters,
+        #[case] target: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Mov { source, target };
+
+        assert!(encode_mov(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, r0, d0)]
+    #[case(Thumb, d0, r0)]
+    fn error_on_mixed_registers(
+        #[case] isa: InstructionSet,
+        #[case] source: AllRegisters,
+        #[case] target: AllRegisters,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Mov { source, target };
+
+        let result = encode_mov(&operation, isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::InvalidRegisterCombination(_, _), pc, buf);
+    }
+}
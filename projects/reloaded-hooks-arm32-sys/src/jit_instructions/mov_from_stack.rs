@@ -0,0 +1,90 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instruction_set::InstructionSet,
+    instructions::{
+        load_store::{encode_load_store, LoadStoreKind},
+        vfp::encode_vldr_vstr,
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, mov_from_stack_operation::MovFromStackOperation,
+};
+
+/// Encoded as LDR for general purpose registers, VLDR for VFP registers.
+pub fn encode_mov_from_stack(
+    x: &MovFromStackOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let target = x.target.register_number() as u8;
+    let start = buf.len();
+
+    if x.target.is_vfp() {
+        encode_vldr_vstr(isa, true, target, sp as u8, x.stack_offset, buf)?;
+    } else {
+        encode_load_store(
+            isa,
+            LoadStoreKind::Ldr,
+            target,
+            sp as u8,
+            x.stack_offset,
+            buf,
+        )?;
+    }
+
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::mov_from_stack::encode_mov_from_stack;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 8, r0, "08009de5")] // ldr r0, [sp, #8]
+    #[case(Arm, 4095, r1, "ff1f9de5")] // ldr r1, [sp, #4095]
+    #[case(Arm, 16, d8, "048b9ded")] // vldr d8, [sp, #16]
+    #[case(Thumb, 8, r0, "ddf80800")] // ldr.w r0, [sp, #8]
+    #[case(Thumb, 16, d8, "9ded048b")] // vldr d8, [sp, #16]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] stack_offset: i32,
+        #[case] target: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = MovFromStack::new(stack_offset, target);
+
+        assert!(encode_mov_from_stack(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 4096, r0)]
+    #[case(Thumb, 1024, d0)]
+    fn error_on_out_of_range_offset(
+        #[case] isa: InstructionSet,
+        #[case] stack_offset: i32,
+        #[case] target: AllRegisters,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = MovFromStack::new(stack_offset, target);
+
+        let result = encode_mov_from_stack(&operation, isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
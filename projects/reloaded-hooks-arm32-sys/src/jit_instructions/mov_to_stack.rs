@@ -0,0 +1,89 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instruction_set::InstructionSet,
+    instructions::{
+        load_store::{encode_load_store, LoadStoreKind},
+        vfp::encode_vldr_vstr,
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, mov_to_stack_operation::MovToStackOperation,
+};
+
+/// Encoded as STR for general purpose registers, VSTR for VFP registers.
+pub fn encode_mov_to_stack(
+    x: &MovToStackOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let register = x.register.register_number() as u8;
+    let start = buf.len();
+
+    if x.register.is_vfp() {
+        encode_vldr_vstr(isa, false, register, sp as u8, x.stack_offset, buf)?;
+    } else {
+        encode_load_store(
+            isa,
+            LoadStoreKind::Str,
+            register,
+            sp as u8,
+            x.stack_offset,
+            buf,
+        )?;
+    }
+
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::mov_to_stack::encode_mov_to_stack;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 8, r0, "08008de5")] // str r0, [sp, #8]
+    #[case(Arm, 16, d8, "048b8ded")] // vstr d8, [sp, #16]
+    #[case(Thumb, 8, r0, "cdf80800")] // str.w r0, [sp, #8]
+    #[case(Thumb, 16, d8, "8ded048b")] // vstr d8, [sp, #16]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] stack_offset: i32,
+        #[case] register: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = MovToStack::new(stack_offset, register);
+
+        assert!(encode_mov_to_stack(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 4096, r0)]
+    #[case(Thumb, 1024, d0)]
+    fn error_on_out_of_range_offset(
+        #[case] isa: InstructionSet,
+        #[case] stack_offset: i32,
+        #[case] register: AllRegisters,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = MovToStack::new(stack_offset, register);
+
+        let result = encode_mov_to_stack(&operation, isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
@@ -0,0 +1,110 @@
+extern crate alloc;
+
+use super::pop::encode_pop;
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instruction_set::InstructionSet,
+    instructions::{load_store::encode_pop_list, vfp::encode_vpop},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, operation::MultiPopVec, pop_operation::PopOperation,
+};
+use smallvec::SmallVec;
+
+/// Encoded as POP {list} and VPOP {list}, falling back to individual pops where needed.
+///
+/// The stack layout consumed is identical to popping each register one by one, i.e. the
+/// first register in the list is loaded from the top of the stack. As POP loads the lowest numbered
+/// register from the lowest address, a run of registers can only be popped together if their
+/// numbers are ascending (and consecutive, for VFP registers).
+pub fn encode_multi_pop(
+    x: &SmallVec<MultiPopVec<AllRegisters>>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let mut index = 0;
+    while index < x.len() {
+        let mut end = index + 1;
+        while end < x.len() && can_pop_together(x[end - 1].register, x[end].register) {
+            end += 1;
+        }
+
+        encode_pop_run(&x[index..end], isa, pc, buf)?;
+        index = end;
+    }
+
+    Ok(())
+}
+
+fn can_pop_together(previous: AllRegisters, next: AllRegisters) -> bool {
+    let previous_number = previous.register_number();
+    let next_number = next.register_number();
+    if previous.is_vfp() && next.is_vfp() {
+        return previous_number + 1 == next_number;
+    }
+
+    previous.is_gpr()
+        && next.is_gpr()
+        && previous != sp
+        && next != sp
+        && previous_number < next_number
+}
+
+fn encode_pop_run(
+    run: &[PopOperation<AllRegisters>],
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    if run.len() == 1 {
+        return encode_pop(&run[0], isa, pc, buf);
+    }
+
+    let start = buf.len();
+    let lowest = run[0].register;
+    if lowest.is_vfp() {
+        encode_vpop(isa, lowest.register_number() as u8, run.len() as u8, buf);
+    } else {
+        let list = run.iter().fold(0u16, |list, item| {
+            list | 1 << item.register.register_number()
+        });
+        encode_pop_list(isa, list, buf);
+    }
+
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::multi_pop::encode_multi_pop;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+    use smallvec::SmallVec;
+
+    #[rstest]
+    #[case(Arm, &[r0], "04009de4")] // ldr r0, [sp], #4
+    #[case(Arm, &[r4, r5, lr], "3040bde8")] // pop {r4, r5, lr}
+    #[case(Arm, &[r1, r0], "04109de404009de4")] // ldr r1, [sp], #4; ldr r0, [sp], #4
+    #[case(Arm, &[d8, d9, d10], "068bbdec")] // vpop {d8-d10}
+    #[case(Thumb, &[d8, d9, r4], "bdec048b10bc")] // vpop {d8, d9}; pop {r4}
+    #[case(Thumb, &[r4, r11], "bde81008")] // pop.w {r4, r11}
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] registers: &[AllRegisters],
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operations: SmallVec<_> = registers.iter().map(|&register| Pop { register }).collect();
+
+        assert!(encode_multi_pop(&operations, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
@@ -0,0 +1,114 @@
+extern crate alloc;
+
+use super::push::encode_push;
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instruction_set::InstructionSet,
+    instructions::{load_store::encode_push_list, vfp::encode_vpush},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, operation::MultiPushVec, push_operation::PushOperation,
+};
+use smallvec::SmallVec;
+
+/// Encoded as PUSH {list} and VPUSH {list}, falling back to individual pushes where needed.
+///
+/// The resulting stack layout is identical to pushing each register one by one, i.e. the
+/// last register in the list ends up at the top of the stack. As PUSH stores the lowest numbered
+/// register at the lowest address, a run of registers can only be pushed together if their
+/// numbers are descending (and consecutive, for VFP registers).
+pub fn encode_multi_push(
+    x: &SmallVec<MultiPushVec<AllRegisters>>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let mut index = 0;
+    while index < x.len() {
+        let mut end = index + 1;
+        while end < x.len() && can_push_together(x[end - 1].register, x[end].register) {
+            end += 1;
+        }
+
+        encode_push_run(&x[index..end], isa, pc, buf)?;
+        index = end;
+    }
+
+    Ok(())
+}
+
+fn can_push_together(previous: AllRegisters, next: AllRegisters) -> bool {
+    let previous_number = previous.register_number();
+    let next_number = next.register_number();
+    if previous.is_vfp() && next.is_vfp() {
+        return next_number + 1 == previous_number;
+    }
+
+    previous.is_gpr()
+        && next.is_gpr()
+        && previous != sp
+        && next != sp
+        && next_number < previous_number
+}
+
+fn encode_push_run(
+    run: &[PushOperation<AllRegisters>],
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    if run.len() == 1 {
+        return encode_push(&run[0], isa, pc, buf);
+    }
+
+    let start = buf.len();
+    let lowest = run[run.len() - 1].register;
+    if lowest.is_vfp() {
+        encode_vpush(isa, lowest.register_number() as u8, run.len() as u8, buf);
+    } else {
+        let list = run.iter().fold(0u16, |list, item| {
+            list | 1 << item.register.register_number()
+        });
+        encode_push_list(isa, list, buf);
+    }
+
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::multi_push::encode_multi_push;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+    use smallvec::SmallVec;
+
+    #[rstest]
+    #[case(Arm, &[r0], "04002de5")] // str r0, [sp, #-4]!
+    #[case(Arm, &[lr, r5, r4], "30402de9")] // push {r4, r5, lr}
+    #[case(Arm, &[r0, r1], "04002de504102de5")] // str r0, [sp, #-4]!; str r1, [sp, #-4]!
+    #[case(Arm, &[d10, d9, d8], "068b2ded")] // vpush {d8-d10}
+    #[case(Arm, &[d8, d10], "028b2ded02ab2ded")] // vpush {d8}; vpush {d10}
+    #[case(Thumb, &[r11, r4, d8], "2de910082ded028b")] // push.w {r4, r11}; vpush {d8}
+    #[case(Thumb, &[r5, r4, lr], "30b400b5")] // push {r4, r5}; push {lr}
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] registers: &[AllRegisters],
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operations: SmallVec<_> = registers
+            .iter()
+            .map(|&register| Push { register })
+            .collect();
+
+        assert!(encode_multi_push(&operations, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
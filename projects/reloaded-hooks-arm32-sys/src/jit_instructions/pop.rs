@@ -0,0 +1,74 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::{load_store::encode_pop_register, vfp::encode_vpop},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, pop_operation::PopOperation};
+
+/// Encoded as POP (LDR with post-increment) for general purpose registers, VPOP for VFP registers.
+pub fn encode_pop(
+    x: &PopOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let register = x.register.register_number() as u8;
+    let start = buf.len();
+
+    if x.register.is_vfp() {
+        encode_vpop(isa, register, 1, buf);
+    } else if x.register == AllRegisters::sp {
+        return Err(JitError::InvalidRegister(x.register));
+    } else {
+        encode_pop_register(isa, register, buf);
+    }
+
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::pop::encode_pop;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, r0, "04009de4")] // ldr r0, [sp], #4
+    #[case(Arm, d8, "028bbdec")] // vpop {d8}
+    #[case(Thumb, r0, "01bc")] // pop {r0}
+    #[case(Thumb, lr, "5df804eb")] // ldr lr, [sp], #4
+    #[case(Thumb, r12, "5df804cb")] // ldr r12, [sp], #4
+    #[case(Thumb, d8, "bdec028b")] // vpop {d8}
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] register: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Pop::new(register);
+
+        assert!(encode_pop(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[test]
+    fn error_on_sp() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Pop::new(sp);
+
+        let result = encode_pop(&operation, Arm, &mut pc, &mut buf);
+        assert_error!(result, JitError::InvalidRegister(_), pc, buf);
+    }
+}
@@ -0,0 +1,77 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters,
+    instruction_set::InstructionSet,
+    instructions::{load_store::encode_push_register, vfp::encode_vpush},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, push_operation::PushOperation};
+
+/// Encoded as PUSH (STR with pre-decrement) for general purpose registers, VPUSH for VFP registers.
+pub fn encode_push(
+    x: &PushOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let register = x.register.register_number() as u8;
+    let start = buf.len();
+
+    if x.register.is_vfp() {
+        encode_vpush(isa, register, 1, buf);
+    } else if x.register == AllRegisters::sp {
+        return Err(JitError::InvalidRegister(x.register));
+    } else {
+        encode_push_register(isa, register, buf);
+    }
+
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::push::encode_push;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, r0, "04002de5")] // str r0, [sp, #-4]!
+    #[case(Arm, lr, "04e02de5")] // str lr, [sp, #-4]!
+    #[case(Arm, d8, "028b2ded")] // vpush {d8}
+    #[case(Thumb, r0, "01b4")] // push {r0}
+    #[case(Thumb, lr, "00b5")] // push {lr}
+    #[case(Thumb, r12, "4df804cd")] // str r12, [sp, #-4]!
+    #[case(Thumb, d8, "2ded028b")] // vpush {d8}
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] register: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Push::new(register);
+
+        assert!(encode_push(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(Arm)]
+    #[case(Thumb)]
+    fn error_on_sp(#[case] isa: InstructionSet) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Push::new(sp);
+
+        let result = encode_push(&operation, isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::InvalidRegister(_), pc, buf);
+    }
+}
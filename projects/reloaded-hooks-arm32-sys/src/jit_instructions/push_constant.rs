@@ -0,0 +1,87 @@
+extern crate alloc;
+
+use super::push::encode_push;
+use crate::{
+    all_registers::AllRegisters, instruction_set::InstructionSet,
+    instructions::data_processing::encode_mov_constant,
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, operation_aliases::PushConst, push_operation::PushOperation,
+};
+
+/// Encoded as MOVW (+ MOVT) + PUSH
+pub fn encode_push_constant(
+    x: &PushConst<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let scratch = match x.scratch {
+        Some(scratch) if scratch.is_gpr() => scratch,
+        _ => return Err(JitError::NoScratchRegister("for PushConstant.".to_string())),
+    };
+
+    encode_mov_constant_to_reg(x.value, scratch.register_number() as u8, isa, pc, buf);
+    encode_push(&PushOperation::new(scratch), isa, pc, buf)
+}
+
+/// Encoded as MOVW, followed by MOVT if the upper 16 bits are non-zero.
+pub fn encode_mov_constant_to_reg(
+    value: usize,
+    destination: u8,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) {
+    let start = buf.len();
+    encode_mov_constant(isa, destination, value as u32, buf);
+    *pc += buf.len() - start;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::push_constant::encode_push_constant;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0x1234, r12, "34c201e304c02de5")]
+    // movw r12, #0x1234; str r12, [sp, #-4]!
+    // movw r12, #0x5678; movt r12, #0x1234; str r12, [sp, #-4]!
+    #[case(Arm, 0x12345678, r12, "78c605e334c241e304c02de5")]
+    #[case(Thumb, 0x1234, r0, "41f2342001b4")] // movw r0, #0x1234; push {r0}
+    // movw r12, #0x5678; movt r12, #0x1234; str r12, [sp, #-4]!
+    #[case(Thumb, 0x12345678, r12, "45f2786cc1f2342c4df804cd")]
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] value: usize,
+        #[case] scratch: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushConst::new(value, Some(scratch));
+
+        assert!(encode_push_constant(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(d0))]
+    fn error_on_missing_scratch(#[case] scratch: Option<AllRegisters>) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushConst::new(0x1234, scratch);
+
+        let result = encode_push_constant(&operation, Arm, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
@@ -0,0 +1,106 @@
+extern crate alloc;
+
+use super::{mov_from_stack::encode_mov_from_stack, push::encode_push};
+use crate::{all_registers::AllRegisters, instruction_set::InstructionSet};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, operation_aliases::MovFromStack, push_operation::PushOperation,
+    push_stack_operation::PushStackOperation,
+};
+
+/// Encoded as a LDR + PUSH sequence for every 4 bytes.
+pub fn encode_push_stack(
+    x: &PushStackOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    // Validate remaining size is usable.
+    if !x.item_size.is_multiple_of(4) {
+        return Err(JitError::ThirdPartyAssemblerError(
+            "ARM PushStack Must use Multiple of 4 Sizes".to_string(),
+        ));
+    }
+
+    let scratch = match x
+        .scratch
+        .borrow()
+        .iter()
+        .find(|reg| reg.is_gpr() && **reg != AllRegisters::sp)
+    {
+        Some(reg) => *reg,
+        None => {
+            return Err(JitError::NoScratchRegister(
+                "for PushStack. 1 general purpose register is required.".to_string(),
+            ))
+        }
+    };
+
+    // Each push moves the stack pointer down by 4, so the same offset reaches the next item.
+    for _ in 0..x.item_size / 4 {
+        encode_mov_from_stack(&MovFromStack::new(x.offset, scratch), isa, pc, buf)?;
+        encode_push(&PushOperation::new(scratch), isa, pc, buf)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::push_stack::encode_push_stack;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 8, 4, vec![r12], "08c09de504c02de5")]
+    // ldr r12, [sp, #8]; str r12, [sp, #-4]!
+    // ldr r12, [sp, #8]; str r12, [sp, #-4]!; ldr r12, [sp, #8]; str r12, [sp, #-4]!
+    #[case(Arm, 8, 8, vec![d0, r12], "08c09de504c02de508c09de504c02de5")]
+    #[case(Thumb, 8, 4, vec![r0], "ddf8080001b4")] // ldr.w r0, [sp, #8]; push {r0}
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] offset: i32,
+        #[case] item_size: u32,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushStack::new(offset, item_size, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_push_stack(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![d0, sp])]
+    fn error_on_missing_scratch(#[case] scratch: Vec<AllRegisters>) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushStack::new(8, 4, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_push_stack(&operation, Arm, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+
+    #[test]
+    fn error_on_unaligned_size() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushStack::new(8, 6, Rc::new(RefCell::new(vec![r12])));
+
+        let result = encode_push_stack(&operation, Arm, &mut pc, &mut buf);
+        assert_error!(result, JitError::ThirdPartyAssemblerError(_), pc, buf);
+    }
+}
@@ -0,0 +1,57 @@
+extern crate alloc;
+
+use super::stackalloc::encode_stackalloc;
+use crate::{
+    all_registers::AllRegisters::{self, lr},
+    instruction_set::InstructionSet,
+    instructions::branch::encode_branch_register,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, return_operation::ReturnOperation,
+    stack_alloc_operation::StackAllocOperation,
+};
+
+/// Encoded as ADD SP (if needed) + BX LR
+pub fn encode_return(
+    x: &ReturnOperation,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    if x.offset > 0 {
+        encode_stackalloc(&StackAllocOperation::new(-(x.offset as i32)), isa, pc, buf)?;
+    }
+
+    let start = buf.len();
+    encode_branch_register(isa, false, lr as u8, buf);
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::ret::encode_return;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 0, "1eff2fe1")] // bx lr
+    #[case(Arm, 16, "10d08de21eff2fe1")] // add sp, sp, #16; bx lr
+    #[case(Thumb, 0, "7047")] // bx lr
+    #[case(Thumb, 16, "0df2100d7047")] // addw sp, sp, #16; bx lr
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] offset: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Return::new(offset);
+
+        assert!(encode_return(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
@@ -0,0 +1,69 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instruction_set::InstructionSet,
+    instructions::data_processing::encode_add_immediate,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, stack_alloc_operation::StackAllocOperation,
+};
+
+/// Encoded as SUB SP, SP, #operand (SUBW in Thumb).
+///
+/// In ARM, the operand must be representable as a rotated 8-bit immediate; which is the case for
+/// all multiples of 4 up to 1020, and most stack frame sizes above that.
+pub fn encode_stackalloc(
+    x: &StackAllocOperation,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    let start = buf.len();
+    encode_add_immediate(isa, sp as u8, sp as u8, -x.operand, buf)?;
+    *pc += buf.len() - start;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::stackalloc::encode_stackalloc;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, 16, "10d04de2")] // sub sp, sp, #16
+    #[case(Arm, -16, "10d08de2")] // add sp, sp, #16
+    #[case(Arm, 4096, "01da4de2")] // sub sp, sp, #4096
+    #[case(Thumb, 16, "adf2100d")] // subw sp, sp, #16
+    #[case(Thumb, -4095, "0df6ff7d")] // addw sp, sp, #4095
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] operand: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = StackAlloc::new(operand);
+
+        assert!(encode_stackalloc(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(Arm, 0x101)]
+    #[case(Thumb, 4096)]
+    fn error_on_out_of_range(#[case] isa: InstructionSet, #[case] operand: i32) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = StackAlloc::new(operand);
+
+        let result = encode_stackalloc(&operation, isa, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
@@ -0,0 +1,109 @@
+extern crate alloc;
+
+use super::mov::encode_mov;
+use crate::{all_registers::AllRegisters, instruction_set::InstructionSet};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, mov_operation::MovOperation, xchg_operation::XChgOperation,
+};
+
+/// Encoded as 3 moves through the scratch register.
+pub fn encode_xchg(
+    x: &XChgOperation<AllRegisters>,
+    isa: InstructionSet,
+    pc: &mut usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), JitError<AllRegisters>> {
+    // Try get scratch register.
+    let scratch = match x.scratch {
+        Some(s) => s,
+        None => {
+            return Err(JitError::NoScratchRegister("for XChg".to_string()));
+        }
+    };
+
+    // Check if any two registers are the same, or if the types differ.
+    // (moving between a general purpose and a VFP register is not supported)
+    if x.register1 == x.register2
+        || x.register1 == scratch
+        || x.register2 == scratch
+        || x.register1.is_vfp() != x.register2.is_vfp()
+        || x.register1.is_vfp() != scratch.is_vfp()
+    {
+        return Err(JitError::InvalidRegisterCombination3(
+            x.register1,
+            x.register2,
+            scratch,
+        ));
+    }
+
+    encode_mov(&MovOperation::new(x.register1, scratch), isa, pc, buf)?;
+    encode_mov(&MovOperation::new(x.register2, x.register1), isa, pc, buf)?;
+    encode_mov(&MovOperation::new(scratch, x.register2), isa, pc, buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::instruction_set::InstructionSet::{self, *};
+    use crate::jit_instructions::xchg::encode_xchg;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Arm, r0, r1, r12, "00c0a0e10100a0e10c10a0e1")] // mov r12, r0; mov r0, r1; mov r1, r12
+    #[case(Arm, d0, d1, d2, "402bb0ee410bb0ee421bb0ee")] // vmov.f64 d2, d0; vmov.f64 d0, d1; vmov.f64 d1, d2
+    #[case(Thumb, r0, r1, r12, "844608466146")] // mov r12, r0; mov r0, r1; mov r1, r12
+    fn standard_cases(
+        #[case] isa: InstructionSet,
+        #[case] register1: AllRegisters,
+        #[case] register2: AllRegisters,
+        #[case] scratch: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = XChg::new(register1, register2, Some(scratch));
+
+        assert!(encode_xchg(&operation, isa, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[test]
+    fn error_on_missing_scratch() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = XChg::new(r0, r1, None);
+
+        let result = encode_xchg(&operation, Arm, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+
+    #[rstest]
+    #[case(r0, r0, r12)]
+    #[case(r0, r1, r0)]
+    #[case(r0, d1, r12)]
+    #[case(d0, d1, r12)]
+    fn error_on_invalid_combination(
+        #[case] register1: AllRegisters,
+        #[case] register2: AllRegisters,
+        #[case] scratch: AllRegisters,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = XChg::new(register1, register2, Some(scratch));
+
+        let result = encode_xchg(&operation, Arm, &mut pc, &mut buf);
+        assert_error!(
+            result,
+            JitError::InvalidRegisterCombination3(_, _, _),
+            pc,
+            buf
+        );
+    }
+}
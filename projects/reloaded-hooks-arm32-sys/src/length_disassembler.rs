@@ -0,0 +1,200 @@
+use crate::{
+    helpers::thumb_instruction_length,
+    instructions::branch::{decode_arm_branch, decode_cbz, decode_thumb_branch},
+};
+use reloaded_hooks_portable::{
+    api::length_disassembler::LengthDisassembler, helpers::read_code::read_code_as,
+};
+
+/// Length disassembler for ARM (A32) code.
+pub struct LengthDisassemblerArm;
+
+/// Length disassembler for Thumb-2 (T32) code.
+/// Bit 0 of the code address is ignored, so interworking addresses can be passed.
+pub struct LengthDisassemblerThumb;
+
+impl LengthDisassembler for LengthDisassemblerArm {
+    fn disassemble_length(_code_address: usize, min_length: usize) -> (usize, usize) {
+        // All instructions are 4 bytes long, so we round up to the nearest multiple of 4.
+        let length = (min_length + 3) & !3;
+        (length, length / 4)
+    }
+
+    fn find_branch_into_stolen_region(
+        code_address: usize,
+        stolen_length: usize,
+        scan_length: usize,
+    ) -> Option<(usize, usize)> {
+        let stolen_end = code_address + stolen_length;
+        let scan_end = stolen_end + scan_length;
+        let mut address = code_address;
+
+        while address < scan_end {
+            let instruction = unsafe { read_code_as::<u32>(address) }.to_le();
+            if let Some(branch) = decode_arm_branch(instruction, address) {
+                let target = branch.target & !1;
+                if target > code_address && target < stolen_end {
+                    return Some((address, target));
+                }
+            }
+
+            address += 4;
+        }
+
+        None
+    }
+}
+
+impl LengthDisassembler for LengthDisassemblerThumb {
+    fn disassemble_length(code_address: usize, min_length: usize) -> (usize, usize) {
+        // Instructions are either 2 or 4 bytes long, so we walk them using the length
+        // encoded in the first halfword of each instruction.
+        let code_address = code_address & !1;
+        let mut length = 0;
+        let mut count = 0;
+
+        while length < min_length {
+            let first = unsafe { read_code_as::<u16>(code_address + length) }.to_le();
+            length += thumb_instruction_length(first);
+            count += 1;
+        }
+
+        (length, count)
+    }
+
+    fn find_branch_into_stolen_region(
+        code_address: usize,
+        stolen_length: usize,
+        scan_length: usize,
+    ) -> Option<(usize, usize)> {
+        let code_address = code_address & !1;
+        let stolen_end = code_address + stolen_length;
+        let scan_end = stolen_end + scan_length;
+        let mut address = code_address;
+
+        while address < scan_end {
+            let first = unsafe { read_code_as::<u16>(address) }.to_le();
+            let length = thumb_instruction_length(first);
+
+            if let Some(target) = branch_target_thumb(address, first, length) {
+                let target = target & !1;
+                if target > code_address && target < stolen_end {
+                    return Some((address, target));
+                }
+            }
+
+            address += length;
+        }
+
+        None
+    }
+}
+
+/// Returns the target of a direct branch (B, B<cond>, BL, BLX, CBZ, CBNZ), if the instruction is one.
+fn branch_target_thumb(address: usize, first: u16, length: usize) -> Option<usize> {
+    if length == 2 {
+        if let Some((_, _, target)) = decode_cbz(first, address) {
+            return Some(target);
+        }
+
+        return decode_thumb_branch(first, 0, address).map(|branch| branch.target);
+    }
+
+    let second = unsafe { read_code_as::<u16>(address + 2) }.to_le();
+    decode_thumb_branch(first, second, address).map(|branch| branch.target)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::length_disassembler::{LengthDisassemblerArm, LengthDisassemblerThumb};
+    use reloaded_hooks_portable::api::length_disassembler::LengthDisassembler;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::single_ins("0100a0e1", 4, 4, 1)] // mov r0, r1
+    #[case::two_instructions("0100a0e10100a0e1", 8, 8, 2)] // mov r0, r1 (x2)
+    #[case::non_aligned_min_length("0100a0e10100a0e1", 5, 8, 2)]
+    fn can_disassemble_length_arm(
+        #[case] instructions: &str,
+        #[case] min_length: usize,
+        #[case] expected_length: usize,
+        #[case] expected_num_ins: usize,
+    ) {
+        let ins_vec = hex::decode(instructions).unwrap();
+        let code_address = ins_vec.as_ptr() as usize;
+        let result = LengthDisassemblerArm::disassemble_length(code_address, min_length);
+        assert_eq!(result.0, expected_length);
+        assert_eq!(result.1, expected_num_ins);
+    }
+
+    #[rstest]
+    #[case::narrow("0846084608460846", 4, 4, 2)] // mov r0, r1 (x4)
+    #[case::wide("00f1010000f10100", 4, 4, 1)] // add.w r0, r0, #1 (x2)
+    #[case::mixed("084600f10100", 4, 6, 2)] // mov r0, r1 + add.w r0, r0, #1
+    #[case::mixed_odd("084600f101000846", 7, 8, 3)] // mov + add.w + mov
+    fn can_disassemble_length_thumb(
+        #[case] instructions: &str,
+        #[case] min_length: usize,
+        #[case] expected_length: usize,
+        #[case] expected_num_ins: usize,
+    ) {
+        let ins_vec = hex::decode(instructions).unwrap();
+
+        // Passing the interworking address is fine.
+        let code_address = ins_vec.as_ptr() as usize | 1;
+        let result = LengthDisassemblerThumb::disassemble_length(code_address, min_length);
+        assert_eq!(result.0, expected_length);
+        assert_eq!(result.1, expected_num_ins);
+    }
+
+    #[rstest]
+    #[case::no_branch("0100a0e10100a0e10100a0e1", 8, 4, None)] // mov r0, r1 (x3)
+    #[case::b_into_stolen("0100a0e10100a0e1fdffffea", 8, 4, Some((8, 4)))] // mov (x2) + b #-12
+    #[case::b_to_start("0100a0e10100a0e1fcffffea", 8, 4, None)] // mov (x2) + b #-16
+    #[case::bleq_into_stolen("0100a0e10100a0e1fdffff0b", 8, 4, Some((8, 4)))] // mov (x2) + bleq #-12
+    #[case::outside_window("0100a0e10100a0e1fdffffea", 8, 0, None)] // b #-12 is not scanned
+    fn can_find_branch_into_stolen_region_arm(
+        #[case] instructions: &str,
+        #[case] stolen_length: usize,
+        #[case] scan_length: usize,
+        #[case] expected_offsets: Option<(usize, usize)>,
+    ) {
+        let ins_vec = hex::decode(instructions).unwrap();
+        let code_address = ins_vec.as_ptr() as usize;
+        let result = LengthDisassemblerArm::find_branch_into_stolen_region(
+            code_address,
+            stolen_length,
+            scan_length,
+        );
+        assert_eq!(
+            result.map(|(source, target)| (source - code_address, target - code_address)),
+            expected_offsets
+        );
+    }
+
+    #[rstest]
+    #[case::no_branch("00f1010000f1010000bf", 8, 2, None)] // add.w (x2) + nop
+    #[case::b_into_stolen("00f1010000f10100fce7", 8, 2, Some((8, 4)))] // add.w (x2) + b #-8
+    #[case::b_to_start("00f1010000f10100fae7", 8, 2, None)] // add.w (x2) + b #-12
+    #[case::bne_w_into_stolen("00f1010000f101007ff4fcaf", 8, 4, Some((8, 4)))] // add.w (x2) + bne.w #-8
+    #[case::cbz_in_stolen_region("00b1084600bf", 6, 0, Some((0, 4)))] // cbz r0, #0 + mov + nop
+    #[case::outside_window("00f1010000f10100fce7", 8, 0, None)] // b #-8 is not scanned
+    fn can_find_branch_into_stolen_region_thumb(
+        #[case] instructions: &str,
+        #[case] stolen_length: usize,
+        #[case] scan_length: usize,
+        #[case] expected_offsets: Option<(usize, usize)>,
+    ) {
+        let ins_vec = hex::decode(instructions).unwrap();
+        let code_address = ins_vec.as_ptr() as usize;
+        let result = LengthDisassemblerThumb::find_branch_into_stolen_region(
+            code_address,
+            stolen_length,
+            scan_length,
+        );
+        assert_eq!(
+            result.map(|(source, target)| (source - code_address, target - code_address)),
+            expected_offsets
+        );
+    }
+}
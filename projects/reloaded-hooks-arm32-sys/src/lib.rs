@@ -0,0 +1,81 @@
+//! # Some Cool Reloaded Library
+//! Here's the crate documentation.
+#![cfg_attr(not(test), no_std)]
+
+/// Contains all of the functional registers for the 32-bit ARM architecture.
+#[cfg(not(tarpaulin_include))]
+pub mod all_registers;
+
+/// Contains the Just in Time Assembler that integrates with reloaded-hooks-rs.
+pub mod jit;
+
+/// Contains Code Rewriter which translates code from one address to another.
+pub mod rewriter;
+
+/// Contains the length disassembler, returning length of instructions requires for hooking.
+pub mod length_disassembler;
+
+/// Contains the calling convention related info.
+pub mod calling_convention;
+
+/// Contains the [`instruction_set::InstructionSet`] enum, distinguishing ARM and Thumb code.
+pub mod instruction_set;
+
+/// Rewriting the code from one address to another!
+pub(crate) mod code_rewriter {
+    pub mod arm_rewriter;
+    pub mod foreign_hook;
+    pub mod helpers;
+    pub mod thumb_rewriter;
+
+    pub(crate) mod instructions {
+        pub mod adr;
+        pub mod branch;
+        pub mod cbz;
+        pub mod ldr_literal;
+    }
+}
+
+/// This namespace contains the raw instruction encodings for various
+/// ARM and Thumb instructions.
+pub(crate) mod instructions {
+    pub mod branch;
+    pub mod data_processing;
+    pub mod errors;
+    pub mod load_store;
+    pub mod system;
+    pub mod vfp;
+}
+
+/// This namespace contains the code for encoding the JIT instructions
+/// using the raw instructions in the [`crate::instructions`] namespace.
+pub(crate) mod jit_instructions {
+    pub mod atomic_add;
+    pub mod branch_absolute;
+    pub mod branch_ip_relative;
+    pub mod branch_relative;
+    pub mod enter_guard;
+    pub mod exit_guard;
+    pub mod filter_caller;
+    pub mod gpr_scratch;
+    pub mod jump_absolute_indirect;
+    pub mod load_pc_relative_value;
+    pub mod mov;
+    pub mod mov_from_stack;
+    pub mod mov_to_stack;
+    pub mod multi_pop;
+    pub mod multi_push;
+    pub mod pop;
+    pub mod push;
+    pub mod push_constant;
+    pub mod push_stack;
+    pub mod ret;
+    pub mod stackalloc;
+    pub mod xchg;
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers;
+
+/// Utility methods
+pub(crate) mod helpers;
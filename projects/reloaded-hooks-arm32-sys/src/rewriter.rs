@@ -0,0 +1,77 @@
+extern crate alloc;
+
+use crate::all_registers::AllRegisters;
+use crate::code_rewriter::foreign_hook::{find_foreign_hook_arm, find_foreign_hook_thumb};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::rewriter::{
+    code_rewriter::{CodeRewriter, CodeRewriterError},
+    foreign_hook::ForeignHook,
+};
+
+/// Code rewriter for ARM (A32) code.
+pub struct CodeRewriterArm;
+
+/// Code rewriter for Thumb-2 (T32) code.
+pub struct CodeRewriterThumb;
+
+impl CodeRewriter<AllRegisters> for CodeRewriterArm {
+    fn max_ins_size_increase() -> usize {
+        12 // BL<cond> to B<!cond> + MOVW + MOVT + BLX.
+    }
+
+    unsafe fn rewrite_code_with_buffer(
+        old_code: *const u8,
+        old_code_size: usize,
+        old_address: usize,
+        new_address: usize,
+        scratch_register: Option<AllRegisters>,
+        existing_buffer: &mut Vec<u8>,
+    ) -> Result<(), CodeRewriterError> {
+        crate::code_rewriter::arm_rewriter::rewrite_code_arm(
+            old_code,
+            old_code_size,
+            old_address,
+            new_address,
+            scratch_register_number(scratch_register),
+            existing_buffer,
+        )
+    }
+
+    unsafe fn find_foreign_hook(address: usize) -> Option<ForeignHook> {
+        find_foreign_hook_arm(address)
+    }
+}
+
+impl CodeRewriter<AllRegisters> for CodeRewriterThumb {
+    fn max_ins_size_increase() -> usize {
+        10 // 16-bit B<cond> to B<!cond> + NOP + LDR.W PC, [PC] + address.
+    }
+
+    unsafe fn rewrite_code_with_buffer(
+        old_code: *const u8,
+        old_code_size: usize,
+        old_address: usize,
+        new_address: usize,
+        scratch_register: Option<AllRegisters>,
+        existing_buffer: &mut Vec<u8>,
+    ) -> Result<(), CodeRewriterError> {
+        crate::code_rewriter::thumb_rewriter::rewrite_code_thumb(
+            old_code,
+            old_code_size,
+            old_address,
+            new_address,
+            scratch_register_number(scratch_register),
+            existing_buffer,
+        )
+    }
+
+    unsafe fn find_foreign_hook(address: usize) -> Option<ForeignHook> {
+        find_foreign_hook_thumb(address)
+    }
+}
+
+fn scratch_register_number(scratch_register: Option<AllRegisters>) -> Option<u8> {
+    scratch_register
+        .filter(|reg| reg.is_gpr() && *reg != AllRegisters::sp)
+        .map(|reg| reg.register_number() as u8)
+}
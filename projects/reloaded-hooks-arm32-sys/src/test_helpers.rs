@@ -0,0 +1,38 @@
+pub fn instruction_buffer_as_hex_u8(buf: &[u8]) -> String {
+    hex::encode(buf)
+}
+
+pub fn assert_encode(expected_hex: &str, buf: &[u8], pc: usize) {
+    assert_eq!(expected_hex, instruction_buffer_as_hex_u8(buf));
+    assert_eq!(buf.len(), pc);
+}
+
+pub fn assert_encode_with_initial_pc(expected_hex: &str, buf: &[u8], initial_pc: usize, pc: usize) {
+    assert_encode(expected_hex, buf, pc - initial_pc);
+}
+
+/// Macro to assert a specific type of error result from a function call.
+///
+/// This macro helps in reducing boilerplate code in tests when checking for specific error types.
+///
+/// # Parameters
+///
+/// - `$result`: The `Result` object returned from a function call.
+/// - `$expected_error`: The expected error pattern. This should match the error variant you are expecting.
+/// - `$pc`: The program counter value to assert against. Typically used to check if the program counter remains unchanged in the case of an error.
+/// - `$buf`: The buffer to check the length against. Typically used to check if the buffer remains unchanged in the case of an error.
+#[macro_export]
+macro_rules! assert_error {
+    ($result:expr, $expected_error:pat, $pc:expr, $buf:expr) => {
+        assert!($result.is_err());
+        assert!(matches!($result.unwrap_err(), $expected_error));
+        assert_eq!(0, $pc);
+        assert_eq!(0, $buf.len());
+    };
+    ($result:expr, $expected_error:pat, $expected_pc:expr, $expected_buf_len:expr, $pc:expr, $buf:expr) => {
+        assert!($result.is_err());
+        assert!(matches!($result.unwrap_err(), $expected_error));
+        assert_eq!($expected_pc, $pc);
+        assert_eq!($expected_buf_len, $buf.len());
+    };
+}
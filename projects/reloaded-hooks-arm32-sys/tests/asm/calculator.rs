@@ -0,0 +1,59 @@
+pub const CALCULATOR_ADD_ARM: [u8; 24] = [
+    0x01, 0x00, 0x80, 0xe0, // add r0, r0, r1
+    0x00, 0xf0, 0x20, 0xe3, // nop
+    0x00, 0xf0, 0x20, 0xe3, // nop
+    0x00, 0xf0, 0x20, 0xe3, // nop
+    0x00, 0xf0, 0x20, 0xe3, // nop
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+pub const CALL_CALCULATOR_ADD_ARM: [u8; 36] = [
+    // add_fn:
+    0x01, 0x00, 0x80, 0xe0, // add r0, r0, r1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+    // add_wrapper:
+    0x00, 0x48, 0x2d, 0xe9, // push {r11, lr}
+    0x0d, 0xb0, 0xa0, 0xe1, // mov r11, sp
+    0xfa, 0xff, 0xff, 0xeb, // bl add_fn
+    0x00, 0x88, 0xbd, 0xe8, // pop {r11, pc}
+    // target_function:
+    0x01, 0x00, 0x80, 0xe0, // add r0, r0, r1
+    0x01, 0x00, 0x80, 0xe2, // add r0, r0, #1
+    0x1e, 0xff, 0x2f, 0xe1, // bx lr
+];
+
+pub const CALL_CALCULATOR_ADD_ARM_FUN_OFFSET: usize = 8; // Start of add_wrapper
+pub const CALL_CALCULATOR_ADD_ARM_CALL_OFFSET: usize = 16; // Offset of 'bl add_fn' in add_wrapper
+pub const CALL_CALCULATOR_ADD_ARM_TARGET_FUNCTION_OFFSET: usize = 24; // Start of target_function
+
+pub const CALCULATOR_ADD_THUMB: [u8; 20] = [
+    0x08, 0x44, // add r0, r1
+    0x00, 0xbf, // nop
+    0x00, 0xbf, // nop
+    0x00, 0xbf, // nop
+    0x00, 0xbf, // nop
+    0x00, 0xbf, // nop
+    0x00, 0xbf, // nop
+    0x00, 0xbf, // nop
+    0x00, 0xbf, // nop
+    0x70, 0x47, // bx lr
+];
+
+pub const CALL_CALCULATOR_ADD_THUMB: [u8; 20] = [
+    // add_fn:
+    0x08, 0x44, // add r0, r1
+    0x70, 0x47, // bx lr
+    // add_wrapper:
+    0x80, 0xb5, // push {r7, lr}
+    0x6f, 0x46, // mov r7, sp
+    0xff, 0xf7, 0xfa, 0xff, // bl add_fn
+    0x80, 0xbd, // pop {r7, pc}
+    // target_function:
+    0x08, 0x44, // add r0, r1
+    0x40, 0x1c, // adds r0, r0, #1
+    0x70, 0x47, // bx lr
+];
+
+pub const CALL_CALCULATOR_ADD_THUMB_FUN_OFFSET: usize = 4; // Start of add_wrapper
+pub const CALL_CALCULATOR_ADD_THUMB_CALL_OFFSET: usize = 8; // Offset of 'bl add_fn' in add_wrapper
+pub const CALL_CALCULATOR_ADD_THUMB_TARGET_FUNCTION_OFFSET: usize = 14; // Start of target_function
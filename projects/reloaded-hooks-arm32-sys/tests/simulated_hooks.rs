@@ -0,0 +1,413 @@
+//! Creates hooks in simulated memory, such that hook creation can be tested on any host.
+//! The produced code is never executed, its bytes are compared against the expected encoding instead.
+
+mod asm;
+
+mod tests {
+    use crate::asm::calculator::*;
+    use reloaded_hooks_arm32_sys::all_registers::AllRegisters::{self, *};
+    use reloaded_hooks_arm32_sys::calling_convention::CallingConvention;
+    use reloaded_hooks_arm32_sys::jit::{JitArm, JitThumb};
+    use reloaded_hooks_arm32_sys::length_disassembler::{
+        LengthDisassemblerArm, LengthDisassemblerThumb,
+    };
+    use reloaded_hooks_arm32_sys::rewriter::{CodeRewriterArm, CodeRewriterThumb};
+    use reloaded_hooks_portable::api::buffers::simulated_buffer::SimulatedBuffer;
+    use reloaded_hooks_portable::api::buffers::simulated_buffer_factory::SimulatedBufferFactory;
+    use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+    use reloaded_hooks_portable::api::function_info::{BasicFunctionInfo, ParameterType};
+    use reloaded_hooks_portable::api::hooks::assembly::assembly_hook::create_assembly_hook;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook::create_branch_hook_with_callback;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook_fast::create_branch_hook_fast_with_callback;
+    use reloaded_hooks_portable::api::hooks::common_hook::CommonHook;
+    use reloaded_hooks_portable::api::jit::compiler::Jit;
+    use reloaded_hooks_portable::api::length_disassembler::LengthDisassembler;
+    use reloaded_hooks_portable::api::platforms::simulated_memory::{
+        get_simulated_memory, register_simulated_memory,
+    };
+    use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriter;
+    use reloaded_hooks_portable::api::settings::assembly_hook_settings::AssemblyHookSettings;
+    use reloaded_hooks_portable::api::settings::basic_hook_settings::BasicHookSettings;
+    use reloaded_hooks_portable::api::settings::function_hook_settings::FunctionHookSettings;
+
+    static ADD_INFO: BasicFunctionInfo =
+        BasicFunctionInfo::new(&[ParameterType::i32, ParameterType::i32]);
+
+    /// Reads `len` bytes of simulated memory, as hex.
+    fn read_hex(address: usize, len: usize) -> String {
+        hex::encode(get_simulated_memory().read(address, len))
+    }
+
+    fn assembly_hook<TJit, TDisassembler, TRewriter>(
+        address: usize,
+        code: &[u8],
+        max_permitted_bytes: usize,
+    ) -> CommonHook<SimulatedBuffer, TJit, AllRegisters, SimulatedBufferFactory>
+    where
+        TJit: Jit<AllRegisters>,
+        TDisassembler: LengthDisassembler,
+        TRewriter: CodeRewriter<AllRegisters>,
+    {
+        let settings = AssemblyHookSettings::new_minimal(
+            address,
+            code.as_ptr() as usize,
+            code.len(),
+            max_permitted_bytes,
+        )
+        .with_scratch_register(r12);
+
+        unsafe {
+            create_assembly_hook::<
+                TJit,
+                AllRegisters,
+                TDisassembler,
+                TRewriter,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        }
+    }
+
+    fn branch_hook_fast<TJit, TDisassembler, TRewriter>(address: usize, target: usize) -> usize
+    where
+        TJit: Jit<AllRegisters>,
+        TDisassembler: LengthDisassembler,
+        TRewriter: CodeRewriter<AllRegisters>,
+    {
+        let settings = BasicHookSettings::new_with_scratch_register(address, target, Some(r12));
+        let mut original = 0;
+        unsafe {
+            create_branch_hook_fast_with_callback::<
+                TJit,
+                AllRegisters,
+                TDisassembler,
+                TRewriter,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings, |x| original = x)
+            .unwrap();
+        }
+
+        original
+    }
+
+    /// Creates a hook with a wrapper; the hook function takes its parameters in reverse.
+    fn branch_hook_with_wrapper<TJit, TDisassembler, TRewriter>(
+        address: usize,
+        target: usize,
+    ) -> (
+        CommonHook<SimulatedBuffer, TJit, AllRegisters, SimulatedBufferFactory>,
+        usize,
+    )
+    where
+        TJit: Jit<AllRegisters>,
+        TDisassembler: LengthDisassembler,
+        TRewriter: CodeRewriter<AllRegisters>,
+    {
+        let basic_settings =
+            BasicHookSettings::new_with_scratch_register(address, target, Some(r12));
+
+        let mut reversed: GenericCallingConvention<AllRegisters> =
+            (**CallingConvention::aapcs()).clone();
+        reversed.int_parameters = &[r1, r0];
+
+        let settings = FunctionHookSettings::<
+            AllRegisters,
+            BasicFunctionInfo,
+            GenericCallingConvention<AllRegisters>,
+        >::new(
+            basic_settings,
+            true,
+            ADD_INFO,
+            CallingConvention::aapcs(),
+            &reversed,
+            None,
+        );
+
+        let mut original = 0;
+        let hook = unsafe {
+            create_branch_hook_with_callback::<
+                TJit,
+                AllRegisters,
+                TDisassembler,
+                TRewriter,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+                BasicFunctionInfo,
+                GenericCallingConvention<AllRegisters>,
+            >(&settings, |x| original = x)
+            .unwrap()
+        };
+
+        (hook, original)
+    }
+
+    #[test]
+    fn arm_assembly_hook_relocates_branch() {
+        register_simulated_memory();
+        let base = 0x4000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_ARM);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_ARM_CALL_OFFSET;
+        let code = &[0x01u8, 0x10, 0x81, 0xe2]; // add r1, r1, #1
+        let _hook =
+            assembly_hook::<JitArm, LengthDisassemblerArm, CodeRewriterArm>(hook_addr, code, 4);
+
+        assert_eq!(read_hex(hook_addr, 4), "fa0700ea"); // b 0x40002000
+        assert_eq!(
+            read_hex(base + 0x2000, 12),
+            concat!(
+                "011081e2", // add r1, r1, #1
+                "fdf7ffeb", // bl 0x40000000 (add_fn)
+                "01f8ffea", // b 0x40000014
+            )
+        );
+    }
+
+    #[test]
+    fn arm_assembly_hook_with_enable_disable() {
+        register_simulated_memory();
+        let memory = get_simulated_memory();
+        let add_addr = 0x1000_0000;
+        memory.map_code(add_addr, &CALCULATOR_ADD_ARM);
+
+        let code = &[0x01u8, 0x10, 0x81, 0xe2]; // add r1, r1, #1
+        let hook =
+            assembly_hook::<JitArm, LengthDisassemblerArm, CodeRewriterArm>(add_addr, code, 20)
+                .with_uninstall_on_drop(true);
+
+        // Stolen bytes not needed for the branch are filled with nops.
+        let hooked = concat!(
+            "fe0700ea", // b 0x10002000
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "1eff2fe1", // bx lr
+        );
+        let enabled = concat!(
+            "011081e2", // add r1, r1, #1
+            "010080e0", // add r0, r0, r1
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "fdf7ffea", // b 0x10000014
+        );
+        let disabled = concat!(
+            "010080e0", // add r0, r0, r1
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "00f020e3", // nop
+            "fef7ffea", // b 0x10000014
+        );
+
+        let stub = add_addr + 0x2000;
+        assert_eq!(read_hex(add_addr, 24), hooked);
+        assert_eq!(read_hex(stub, 28), enabled);
+
+        hook.disable();
+        assert_eq!(read_hex(add_addr, 24), hooked);
+        assert_eq!(read_hex(stub, 24), disabled);
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 28), enabled);
+
+        drop(hook);
+        assert_eq!(memory.read(add_addr, 24), CALCULATOR_ADD_ARM);
+        assert_eq!(memory.num_unprotected_regions(), 0);
+    }
+
+    #[test]
+    fn arm_branch_hook_fast() {
+        register_simulated_memory();
+        let base = 0x2000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_ARM);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_ARM_CALL_OFFSET;
+        let original = branch_hook_fast::<JitArm, LengthDisassemblerArm, CodeRewriterArm>(
+            hook_addr,
+            base + CALL_CALCULATOR_ADD_ARM_TARGET_FUNCTION_OFFSET,
+        );
+
+        assert_eq!(original, base);
+        assert_eq!(read_hex(hook_addr, 4), "000000eb"); // bl 0x20000018 (target_function)
+    }
+
+    #[test]
+    fn arm_branch_hook_with_wrapper() {
+        register_simulated_memory();
+        let base = 0x3000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_ARM);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_ARM_CALL_OFFSET;
+        let (hook, original) = branch_hook_with_wrapper::<
+            JitArm,
+            LengthDisassemblerArm,
+            CodeRewriterArm,
+        >(hook_addr, 0x1234_5678);
+
+        // The parameters are swapped via a scratch register, and the out of range target is
+        // called via a register which isn't a parameter.
+        let wrapper = concat!(
+            "04e02de5", // str lr, [sp, #-4]!
+            "04d04de2", // sub sp, sp, #4
+            "0120a0e1", // mov r2, r1
+            "0010a0e1", // mov r1, r0
+            "0200a0e1", // mov r0, r2
+            "782605e3", // movw r2, #0x5678
+            "342241e3", // movt r2, #0x1234
+            "32ff2fe1", // blx r2
+            "04d08de2", // add sp, sp, #4
+            "04e09de4", // ldr lr, [sp], #4
+            "1eff2fe1", // bx lr
+        );
+
+        let stub = base + 0x2000;
+        assert_eq!(original, base);
+        assert_eq!(read_hex(hook_addr, 4), "fa0700eb"); // bl 0x30002000
+        assert_eq!(read_hex(stub, 44), wrapper);
+
+        hook.disable();
+        assert_eq!(read_hex(stub, 4), "fef7ffea"); // b 0x30000000 (add_fn)
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 44), wrapper);
+    }
+
+    #[test]
+    fn thumb_assembly_hook_with_enable_disable() {
+        register_simulated_memory();
+        let memory = get_simulated_memory();
+        let add_addr = 0x5000_0000;
+        memory.map_code(add_addr, &CALCULATOR_ADD_THUMB);
+
+        let code = &[0x01u8, 0x31]; // adds r1, #1
+        let hook = assembly_hook::<JitThumb, LengthDisassemblerThumb, CodeRewriterThumb>(
+            add_addr, code, 18,
+        )
+        .with_uninstall_on_drop(true);
+
+        let hooked = concat!(
+            "01f0febf",                     // b.w 0x50002000
+            "00bf00bf00bf00bf00bf00bf00bf", // nop (x7)
+            "7047",                         // bx lr
+        );
+        let enabled = concat!(
+            "0131",                             // adds r1, #1
+            "0844",                             // add r0, r1
+            "00bf00bf00bf00bf00bf00bf00bf00bf", // nop (x8)
+            "fdf7fdbf",                         // b.w 0x50000012
+        );
+        let disabled = concat!(
+            "0844",                             // add r0, r1
+            "00bf00bf00bf00bf00bf00bf00bf00bf", // nop (x8)
+            "fdf7febf",                         // b.w 0x50000012
+        );
+
+        let stub = add_addr + 0x2000;
+        assert_eq!(read_hex(add_addr, 20), hooked);
+        assert_eq!(read_hex(stub, 24), enabled);
+
+        hook.disable();
+        assert_eq!(read_hex(add_addr, 20), hooked);
+        assert_eq!(read_hex(stub, 22), disabled);
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 24), enabled);
+
+        drop(hook);
+        assert_eq!(memory.read(add_addr, 20), CALCULATOR_ADD_THUMB);
+        assert_eq!(memory.num_unprotected_regions(), 0);
+    }
+
+    #[test]
+    fn thumb_branch_hook_fast() {
+        register_simulated_memory();
+        let base = 0x6000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_THUMB);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_THUMB_CALL_OFFSET;
+        let original = branch_hook_fast::<JitThumb, LengthDisassemblerThumb, CodeRewriterThumb>(
+            hook_addr,
+            base + CALL_CALCULATOR_ADD_THUMB_TARGET_FUNCTION_OFFSET,
+        );
+
+        // The original function is Thumb code.
+        assert_eq!(original, base | 1);
+        assert_eq!(read_hex(hook_addr, 4), "00f001f8"); // bl 0x6000000E (target_function)
+    }
+
+    #[test]
+    fn thumb_branch_hook_with_wrapper() {
+        register_simulated_memory();
+        let base = 0x7000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_THUMB);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_THUMB_CALL_OFFSET;
+        let (hook, original) = branch_hook_with_wrapper::<
+            JitThumb,
+            LengthDisassemblerThumb,
+            CodeRewriterThumb,
+        >(hook_addr, 0x1234_5678);
+
+        // The target is called as Thumb code.
+        let wrapper = concat!(
+            "00b5",     // push {lr}
+            "adf2040d", // subw sp, sp, #4
+            "0a46",     // mov r2, r1
+            "0146",     // mov r1, r0
+            "1046",     // mov r0, r2
+            "45f27962", // movw r2, #0x5679
+            "c1f23422", // movt r2, #0x1234
+            "9047",     // blx r2
+            "0df2040d", // addw sp, sp, #4
+            "5df804eb", // ldr lr, [sp], #4
+            "7047",     // bx lr
+        );
+
+        let stub = base + 0x2000;
+        assert_eq!(original, base | 1);
+        assert_eq!(read_hex(hook_addr, 4), "01f0faff"); // bl 0x70002000
+        assert_eq!(read_hex(stub, 32), wrapper);
+
+        hook.disable();
+        assert_eq!(read_hex(stub, 4), "fdf7febf"); // b.w 0x70000000 (add_fn)
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 32), wrapper);
+    }
+
+    #[test]
+    fn thumb_assembly_hook_chains_in_front_of_foreign_hook() {
+        register_simulated_memory();
+        let memory = get_simulated_memory();
+        let base = 0x8000_0000;
+
+        // A foreign hook at the start of the function, jumping to the hook's stub at base + 0x100.
+        let mut code = vec![0u8; 0x100];
+        code[..4].copy_from_slice(&hex::decode("00f07eb8").unwrap()); // b.w 0x80000100
+        code.extend_from_slice(&CALCULATOR_ADD_THUMB);
+        memory.map_code(base, &code);
+
+        let asm = &[0x01u8, 0x31]; // adds r1, #1
+        let hook =
+            assembly_hook::<JitThumb, LengthDisassemblerThumb, CodeRewriterThumb>(base, asm, 18)
+                .with_uninstall_on_drop(true);
+
+        // Only the foreign hook is overwritten, the stub continues to the foreign hook's target.
+        let stub = base + 0x2000;
+        assert_eq!(read_hex(base, 4), "01f0febf"); // b.w 0x80002000
+        let enabled = concat!(
+            "0131",     // adds r1, #1
+            "fef77db8", // b.w 0x80000100 (foreign hook target)
+            "fdf7fdbf", // b.w 0x80000004
+        );
+        assert_eq!(read_hex(stub, 10), enabled);
+
+        drop(hook);
+        assert_eq!(memory.read(base, 4), code[..4]);
+    }
+}
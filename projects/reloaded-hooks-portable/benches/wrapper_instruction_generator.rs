@@ -166,5 +166,8 @@ fn get_common_options(
         can_generate_relative_jumps: true,
         enable_optimizations: optimized,
         standard_register_size: size_of::<isize>(),
+        in_flight_counter: None,
+        reentrancy_guard: None,
+        caller_filter: None,
     }
 }
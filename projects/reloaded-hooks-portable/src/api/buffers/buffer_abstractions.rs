@@ -55,6 +55,23 @@ pub trait BufferFactory<TBuffer: Buffer>: Sync + Send {
     ///
     /// Returned buffers must be locked and not returned to the pool until they are dropped.
     fn get_any_buffer(size: u32, alignment: u32) -> Result<Box<TBuffer>, String>;
+
+    /// Returns a region of memory previously written to via a buffer from this factory,
+    /// such that it can be reused by future buffers.
+    ///
+    /// # Parameters
+    ///
+    /// - `address`: Start of the region to release.
+    /// - `len`: Length of the region to release.
+    ///
+    /// # Remarks
+    ///
+    /// The region may contain code which other threads are still executing, or returning into.
+    /// Implementations must therefore quarantine released regions for a while before handing
+    /// them out again.
+    ///
+    /// The default implementation does nothing, i.e. the memory is never reused.
+    fn release(_address: usize, _len: usize) {}
 }
 
 pub trait Buffer {
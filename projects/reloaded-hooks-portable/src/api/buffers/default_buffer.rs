@@ -1,10 +1,7 @@
 extern crate alloc;
 
-use crate::api::platforms::platform_functions::{
-    disable_write_xor_execute, restore_write_xor_execute,
-};
+use crate::api::platforms::platform_functions::get_platform_functions;
 use crate::helpers::atomic_write::atomic_write;
-use crate::helpers::icache_clear::clear_instruction_cache;
 use alloc::rc::Rc;
 use core::cell::RefCell;
 use core::mem::size_of;
@@ -12,12 +9,24 @@ use core::ptr::{copy_nonoverlapping, NonNull};
 use core::sync::atomic::{AtomicBool, Ordering};
 
 use super::buffer_abstractions::Buffer;
+use super::default_buffer_factory::{get_write_address, release_unused};
 
 pub struct AllocatedBuffer {
     pub(crate) ptr: NonNull<u8>,
+
+    /// Writable view of the memory at `ptr`. Same as `ptr`, unless the memory is dual mapped
+    /// because the OS forbids memory which is both writable and executable.
+    pub(crate) write_ptr: NonNull<u8>,
+
     pub(crate) write_offset: RefCell<u32>,
     pub(crate) size: u32,
     pub(crate) locked: AtomicBool,
+
+    /// True if this buffer was carved out of memory returned via [`DefaultBufferFactory::release`].
+    /// Space left unused in such a buffer is returned to the free list once it is dropped.
+    ///
+    /// [`DefaultBufferFactory::release`]: super::default_buffer_factory::DefaultBufferFactory
+    pub(crate) is_recycled: bool,
 }
 
 impl AllocatedBuffer {
@@ -31,9 +40,11 @@ impl Clone for AllocatedBuffer {
     fn clone(&self) -> Self {
         Self {
             ptr: self.ptr,
+            write_ptr: self.write_ptr,
             write_offset: self.write_offset.clone(),
             size: self.size,
             locked: AtomicBool::new(self.locked.load(Ordering::Relaxed)),
+            is_recycled: self.is_recycled,
         }
     }
 }
@@ -58,13 +69,15 @@ impl Buffer for LockedBuffer {
         debug_assert!(end <= self.buffer.size, "Buffer overflow");
 
         let buffer_ptr = self.buffer.ptr.as_ptr();
+        let write_ptr = self.buffer.write_ptr.as_ptr();
 
         // Make buffer RW for W^X
-        let orig = disable_write_xor_execute(buffer_ptr as *const u8, data.len());
+        let platform = get_platform_functions();
+        let orig = platform.disable_write_xor_execute(buffer_ptr as usize, data.len());
         unsafe {
             copy_nonoverlapping(
                 data.as_ptr(),
-                buffer_ptr.add(current_offset as usize),
+                write_ptr.add(current_offset as usize),
                 data.len(),
             );
         }
@@ -74,27 +87,27 @@ impl Buffer for LockedBuffer {
 
         // Make code executable again for W^X
         if let Some(orig_val) = orig {
-            restore_write_xor_execute(buffer_ptr as *const u8, data.len(), orig_val);
+            platform.restore_write_xor_execute(buffer_ptr as usize, data.len(), orig_val);
         }
 
-        clear_instruction_cache(
-            buffer_ptr as *const u8,
-            (buffer_ptr as usize + data.len()) as *const u8,
-        );
+        let written_ptr = buffer_ptr as usize + current_offset as usize;
+        platform.clear_instruction_cache(written_ptr, written_ptr + data.len());
         result
     }
 
     fn overwrite(address: usize, buffer: &[u8]) {
-        let orig = disable_write_xor_execute(address as *const u8, buffer.len());
+        let write_address = get_write_address(address);
+        let platform = get_platform_functions();
+        let orig = platform.disable_write_xor_execute(address, buffer.len());
         unsafe {
-            copy_nonoverlapping(buffer.as_ptr(), address as *mut u8, buffer.len());
+            copy_nonoverlapping(buffer.as_ptr(), write_address as *mut u8, buffer.len());
         }
 
         if let Some(orig_val) = orig {
-            restore_write_xor_execute(address as *const u8, buffer.len(), orig_val);
+            platform.restore_write_xor_execute(address, buffer.len(), orig_val);
         }
 
-        clear_instruction_cache(address as *const u8, (address + buffer.len()) as *const u8);
+        platform.clear_instruction_cache(address, address + buffer.len());
     }
 
     fn advance(&mut self, num_bytes: usize) -> *const u8 {
@@ -108,29 +121,39 @@ impl Buffer for LockedBuffer {
     where
         Self: Sized,
     {
-        let orig = disable_write_xor_execute(address as *const u8, size_of::<TInteger>());
+        let write_address = get_write_address(address);
+        let platform = get_platform_functions();
+        let orig = platform.disable_write_xor_execute(address, size_of::<TInteger>());
 
         unsafe {
             atomic_write(
                 &buffer as *const TInteger as *const u8,
-                address as *mut u8,
+                write_address as *mut u8,
                 size_of::<TInteger>(),
             );
         }
 
         if let Some(orig_val) = orig {
-            restore_write_xor_execute(address as *const u8, size_of::<TInteger>(), orig_val);
+            platform.restore_write_xor_execute(address, size_of::<TInteger>(), orig_val);
         }
 
-        clear_instruction_cache(
-            address as *const u8,
-            (address + size_of::<TInteger>()) as *const u8,
-        );
+        platform.clear_instruction_cache(address, address + size_of::<TInteger>());
     }
 }
 
 impl Drop for LockedBuffer {
     fn drop(&mut self) {
+        // Recycled buffers aren't in the buffer list, so hand back the unused space instead.
+        if self.buffer.is_recycled {
+            let write_offset = *self.buffer.write_offset.borrow();
+            if write_offset < self.buffer.size {
+                release_unused(
+                    self.buffer.ptr.as_ptr() as usize + write_offset as usize,
+                    (self.buffer.size - write_offset) as usize,
+                );
+            }
+        }
+
         self.buffer.locked.store(false, Ordering::Release); // unlock buffer when done
     }
 }
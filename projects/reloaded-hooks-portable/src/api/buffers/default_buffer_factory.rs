@@ -1,22 +1,37 @@
 extern crate alloc;
 use super::buffer_abstractions::BufferFactory;
 use super::default_buffer::{AllocatedBuffer, LockedBuffer};
+use crate::api::platforms::platform_functions::get_monotonic_time_ms;
 use alloc::boxed::Box;
 use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
 use core::cell::RefCell;
+#[cfg(not(target_os = "linux"))]
 use core::mem;
 use core::ptr::NonNull;
 use core::sync::atomic::{AtomicBool, Ordering};
+use derive_new::new;
+#[cfg(not(target_os = "linux"))]
 use mmap_rs_with_map_from_existing::UnsafeMmapFlags;
-use spin::RwLock;
+use spin::{Mutex, RwLock};
 
 pub(crate) static BUFFERS: BuffersWrapper = BuffersWrapper {
     buffers: RwLock::new(Vec::new()),
 };
 
+/// Set once any buffer is dual mapped, i.e. has separate addresses for writing and executing.
+static HAS_DUAL_MAPPINGS: AtomicBool = AtomicBool::new(false);
+
+/// Memory returned via [`DefaultBufferFactory::release`], sorted by address.
+pub(crate) static FREE_REGIONS: Mutex<Vec<FreeRegion>> = Mutex::new(Vec::new());
+
+/// Amount of time memory returned via [`DefaultBufferFactory::release`] is quarantined for before
+/// it may be reused. This gives threads still executing (or returning through) the released code
+/// time to leave it.
+pub const RELEASE_QUARANTINE_MS: u64 = 1000;
+
 pub struct BuffersWrapper {
     pub buffers: RwLock<Vec<Rc<AllocatedBuffer>>>,
 }
@@ -39,56 +54,229 @@ unsafe impl Sync for DefaultBufferFactory {}
 
 impl BufferFactory<LockedBuffer> for DefaultBufferFactory {
     fn get_buffer(
-        _size: u32,
-        _target: usize,
-        _proximity: usize,
-        _alignment: u32,
+        size: u32,
+        target: usize,
+        proximity: usize,
+        alignment: u32,
     ) -> Result<Box<LockedBuffer>, String> {
-        Err("Not Supported".to_string())
+        #[cfg(target_os = "linux")]
+        {
+            use super::linux_proximity::{allocate_near, is_in_proximity};
+
+            let in_proximity =
+                |start: usize, end: usize| is_in_proximity(target, proximity, start, end);
+
+            if let Some(buffer) = try_take_free_region(size, alignment, in_proximity) {
+                return Ok(buffer);
+            }
+
+            if let Some(buffer) = try_lock_existing_buffer(size, alignment, in_proximity) {
+                return Ok(buffer);
+            }
+
+            // Allocate more than requested, so later requests near the same module can reuse the buffer.
+            let alloc_size = size.max(PROXIMITY_BUFFER_SIZE);
+            let mut write_lock = BUFFERS.buffers.write();
+            let mapping = allocate_near(target, proximity, alloc_size as usize)
+                .ok_or_else(|| "No free memory found in proximity of target".to_string())?;
+
+            if mapping.exec != mapping.write {
+                HAS_DUAL_MAPPINGS.store(true, Ordering::Relaxed);
+            }
+
+            let buffer = Rc::new(AllocatedBuffer {
+                ptr: mapping.exec,
+                write_ptr: mapping.write,
+                write_offset: RefCell::new(0),
+                size: alloc_size,
+                locked: AtomicBool::new(true),
+                is_recycled: false,
+            });
+
+            write_lock.push(buffer.clone());
+            Ok(Box::new(LockedBuffer { buffer }))
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (size, target, proximity, alignment);
+            Err("Not Supported".to_string())
+        }
     }
 
     fn get_any_buffer(size: u32, alignment: u32) -> Result<Box<LockedBuffer>, String> {
-        let read_lock = BUFFERS.buffers.read();
-        for buffer in read_lock.iter() {
-            // Try to lock the buffer temporarily, to ensure thread safety.
-            if buffer
-                .locked
-                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
-                == Ok(false)
-            {
-                let current_address =
-                    (buffer.ptr.as_ptr() as usize) + *buffer.write_offset.borrow() as usize;
-                let adjustment = align_offset(current_address, alignment as usize);
-                let aligned_offset = *buffer.write_offset.borrow() + adjustment as u32;
-                let new_bytes_remaining = buffer.size - aligned_offset;
-
-                if new_bytes_remaining >= size {
-                    // Adjust the write_offset of buffer to ensure alignment
-                    *buffer.write_offset.borrow_mut() += adjustment as u32;
-
-                    return Ok(Box::new(LockedBuffer {
-                        buffer: buffer.clone(),
-                    }));
-                } else {
-                    // Buffer is not eligible, unlock it
-                    buffer.locked.store(false, Ordering::Release);
-                }
-            }
+        if let Some(buffer) = try_take_free_region(size, alignment, |_, _| true) {
+            return Ok(buffer);
         }
 
-        drop(read_lock);
+        if let Some(buffer) = try_lock_existing_buffer(size, alignment, |_, _| true) {
+            return Ok(buffer);
+        }
 
         // If no buffer was found, create a new one
         let mut write_lock = BUFFERS.buffers.write();
 
+        let (ptr, write_ptr) = map_any_code(size as usize)?;
+        let buffer = Rc::new(AllocatedBuffer {
+            ptr,
+            write_ptr,
+            write_offset: RefCell::new(0),
+            size,
+            locked: AtomicBool::new(true),
+            is_recycled: false,
+        });
+
+        write_lock.push(buffer.clone());
+        Ok(Box::new(LockedBuffer {
+            buffer: buffer.clone(),
+        }))
+    }
+
+    fn release(address: usize, len: usize) {
+        // Without a clock we can't tell when quarantine ends, so the memory is never reused.
+        if let Some(now) = get_monotonic_time_ms() {
+            add_free_region(
+                &mut FREE_REGIONS.lock(),
+                FreeRegion::new(address, len, now.max(1)),
+            );
+        }
+    }
+}
+
+/// A region of memory which can be reused by future buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub(crate) struct FreeRegion {
+    pub address: usize,
+    pub len: usize,
+
+    /// Time (from [`get_monotonic_time_ms`]) the region was released at.
+    /// 0 if the region was never written to, and can thus be reused immediately.
+    pub released_at: u64,
+}
+
+/// Returns space at the end of a recycled buffer, which was never written to, to the free list.
+pub(crate) fn release_unused(address: usize, len: usize) {
+    add_free_region(&mut FREE_REGIONS.lock(), FreeRegion::new(address, len, 0));
+}
+
+/// Inserts a region into the (address sorted) free list, merging it with adjacent regions.
+/// A merged region is quarantined until the most recently released part of it is out of quarantine.
+pub(crate) fn add_free_region(regions: &mut Vec<FreeRegion>, mut region: FreeRegion) {
+    let index = regions.partition_point(|x| x.address < region.address);
+
+    // Merge with next
+    if let Some(next) = regions.get(index) {
+        if region.address + region.len == next.address {
+            region.len += next.len;
+            region.released_at = region.released_at.max(next.released_at);
+            regions.remove(index);
+        }
+    }
+
+    // Merge with previous
+    if index > 0 {
+        let prev = &mut regions[index - 1];
+        if prev.address + prev.len == region.address {
+            prev.len += region.len;
+            prev.released_at = prev.released_at.max(region.released_at);
+            return;
+        }
+    }
+
+    regions.insert(index, region);
+}
+
+/// Removes the first region out of quarantine from the free list, which has `size` bytes free after
+/// aligning to `alignment`, and for which `predicate(start, end)` of the aligned space returns true.
+///
+/// # Returns
+///
+/// `(address, len)` of the aligned space taken. The part of the region before the aligned address
+/// stays in the free list.
+pub(crate) fn take_free_region(
+    regions: &mut Vec<FreeRegion>,
+    size: usize,
+    alignment: usize,
+    now: u64,
+    predicate: impl Fn(usize, usize) -> bool,
+) -> Option<(usize, usize)> {
+    let index = regions.iter().position(|region| {
+        let aligned = region.address + align_offset(region.address, alignment);
+        let end = region.address + region.len;
+        let quarantined = now.saturating_sub(region.released_at) < RELEASE_QUARANTINE_MS;
+        (region.released_at == 0 || !quarantined)
+            && aligned + size <= end
+            && predicate(aligned, aligned + size)
+    })?;
+
+    let region = regions.remove(index);
+    let aligned = region.address + align_offset(region.address, alignment);
+    if aligned > region.address {
+        regions.insert(
+            index,
+            FreeRegion::new(region.address, aligned - region.address, region.released_at),
+        );
+    }
+
+    Some((aligned, region.address + region.len - aligned))
+}
+
+/// Creates a locked buffer out of memory in the free list, see [`take_free_region`].
+fn try_take_free_region(
+    size: u32,
+    alignment: u32,
+    predicate: impl Fn(usize, usize) -> bool,
+) -> Option<Box<LockedBuffer>> {
+    let now = get_monotonic_time_ms()?;
+    let (address, len) = take_free_region(
+        &mut FREE_REGIONS.lock(),
+        size as usize,
+        alignment as usize,
+        now,
+        predicate,
+    )?;
+
+    Some(Box::new(LockedBuffer {
+        buffer: Rc::new(AllocatedBuffer {
+            ptr: NonNull::new(address as *mut u8).unwrap(),
+            write_ptr: NonNull::new(get_write_address(address) as *mut u8).unwrap(),
+            write_offset: RefCell::new(0),
+            size: len as u32,
+            locked: AtomicBool::new(true),
+            is_recycled: true,
+        }),
+    }))
+}
+
+/// Maps `size` bytes of memory for code, anywhere in the address space.
+///
+/// # Returns
+///
+/// The (executable, writable) addresses of the memory. These differ if the memory is dual mapped.
+fn map_any_code(size: usize) -> Result<(NonNull<u8>, NonNull<u8>), String> {
+    // Linux kernels may be hardened to forbid RWX memory, so fall back to dual mapping there.
+    #[cfg(target_os = "linux")]
+    {
+        let mapping = super::linux_memfd::map_code(size, None)
+            .ok_or_else(|| "Failed to map memory for code".to_string())?;
+
+        if mapping.exec != mapping.write {
+            HAS_DUAL_MAPPINGS.store(true, Ordering::Relaxed);
+        }
+
+        Ok((mapping.exec, mapping.write))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
         let mut map = if create_page_as_rx() {
-            mmap_rs_with_map_from_existing::MmapOptions::new(size as usize)
+            mmap_rs_with_map_from_existing::MmapOptions::new(size)
                 .unwrap()
                 .map_mut()
                 .unwrap()
         } else {
             unsafe {
-                mmap_rs_with_map_from_existing::MmapOptions::new(size as usize)
+                mmap_rs_with_map_from_existing::MmapOptions::new(size)
                     .unwrap()
                     .with_unsafe_flags(UnsafeMmapFlags::JIT)
                     .map_exec_mut()
@@ -97,21 +285,74 @@ impl BufferFactory<LockedBuffer> for DefaultBufferFactory {
         };
 
         // Don't drop the map!
-        let ptr = map.as_mut_ptr();
+        let ptr = NonNull::new(map.as_mut_ptr()).unwrap();
         mem::forget(map);
+        Ok((ptr, ptr))
+    }
+}
 
-        let buffer = Rc::new(AllocatedBuffer {
-            ptr: NonNull::new(ptr).unwrap(),
-            write_offset: RefCell::new(0),
-            size,
-            locked: AtomicBool::new(true),
-        });
+/// Translates an address inside a buffer to the address at which it can be written to.
+///
+/// This is the address itself, unless the buffer is dual mapped (see [`AllocatedBuffer::write_ptr`]).
+pub(crate) fn get_write_address(address: usize) -> usize {
+    if !HAS_DUAL_MAPPINGS.load(Ordering::Relaxed) {
+        return address;
+    }
 
-        write_lock.push(buffer.clone());
-        Ok(Box::new(LockedBuffer {
-            buffer: buffer.clone(),
-        }))
+    let read_lock = BUFFERS.buffers.read();
+    for buffer in read_lock.iter() {
+        let start = buffer.ptr.as_ptr() as usize;
+        if address >= start && address < start + buffer.size as usize {
+            return buffer.write_ptr.as_ptr() as usize + (address - start);
+        }
+    }
+
+    address
+}
+
+/// Size of buffers allocated by [`DefaultBufferFactory::get_buffer`].
+#[cfg(target_os = "linux")]
+const PROXIMITY_BUFFER_SIZE: u32 = 65536;
+
+/// Locks the first existing buffer with `size` bytes free after aligning to `alignment`,
+/// and for which `predicate(start, end)` of the aligned free region returns true.
+fn try_lock_existing_buffer(
+    size: u32,
+    alignment: u32,
+    predicate: impl Fn(usize, usize) -> bool,
+) -> Option<Box<LockedBuffer>> {
+    let read_lock = BUFFERS.buffers.read();
+    for buffer in read_lock.iter() {
+        // Try to lock the buffer temporarily, to ensure thread safety.
+        if buffer
+            .locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            == Ok(false)
+        {
+            let current_address =
+                (buffer.ptr.as_ptr() as usize) + *buffer.write_offset.borrow() as usize;
+            let adjustment = align_offset(current_address, alignment as usize);
+            let aligned_offset = *buffer.write_offset.borrow() + adjustment as u32;
+            let aligned_address = current_address + adjustment;
+
+            if aligned_offset <= buffer.size
+                && buffer.size - aligned_offset >= size
+                && predicate(aligned_address, aligned_address + size as usize)
+            {
+                // Adjust the write_offset of buffer to ensure alignment
+                *buffer.write_offset.borrow_mut() += adjustment as u32;
+
+                return Some(Box::new(LockedBuffer {
+                    buffer: buffer.clone(),
+                }));
+            } else {
+                // Buffer is not eligible, unlock it
+                buffer.locked.store(false, Ordering::Release);
+            }
+        }
     }
+
+    None
 }
 
 /// Returns the required number of bytes to align 'address' to 'alignment'.
@@ -121,6 +362,7 @@ fn align_offset(address: usize, alignment: usize) -> usize {
 
 /// Returns true if the platform should crate memory pages as R^X instead of RWX.
 /// Use this when platform enforces strict W^X policy. This is intended to be used when testing new platforms.
+#[cfg(not(target_os = "linux"))]
 fn create_page_as_rx() -> bool {
     #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
     {
@@ -153,6 +395,118 @@ mod tests {
         // assert!(!BUFFERS.buffers.read()[0].locked.load(Ordering::Acquire));
     }
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn get_buffer_in_proximity() {
+        let target = get_buffer_in_proximity as *const () as usize;
+        let proximity = 0x7FFFFFFF;
+
+        for _ in 0..2 {
+            let buffer = DefaultBufferFactory::get_buffer(64, target, proximity, 4).unwrap();
+            let address = buffer.get_address() as usize;
+            assert!(address.abs_diff(target) <= proximity);
+            assert!((address + 64).abs_diff(target) <= proximity);
+        }
+    }
+
+    #[test]
+    fn add_free_region_merges_adjacent() {
+        let mut regions = Vec::new();
+        add_free_region(&mut regions, FreeRegion::new(0x1000, 0x100, 5));
+        add_free_region(&mut regions, FreeRegion::new(0x1200, 0x100, 0));
+        assert_eq!(regions.len(), 2);
+
+        // Fills the gap, so all three are merged, with the latest release time.
+        add_free_region(&mut regions, FreeRegion::new(0x1100, 0x100, 7));
+        assert_eq!(regions, vec![FreeRegion::new(0x1000, 0x300, 7)]);
+    }
+
+    #[test]
+    fn take_free_region_respects_quarantine() {
+        let mut regions = vec![FreeRegion::new(0x1000, 0x100, 500)];
+        let quarantine_end = 500 + RELEASE_QUARANTINE_MS;
+
+        assert_eq!(
+            take_free_region(&mut regions, 0x10, 4, quarantine_end - 1, |_, _| true),
+            None
+        );
+        assert_eq!(
+            take_free_region(&mut regions, 0x10, 4, quarantine_end, |_, _| true),
+            Some((0x1000, 0x100))
+        );
+        assert!(regions.is_empty());
+    }
+
+    #[test]
+    fn take_free_region_never_written_is_not_quarantined() {
+        let mut regions = vec![FreeRegion::new(0x1000, 0x100, 0)];
+        assert_eq!(
+            take_free_region(&mut regions, 0x10, 4, 0, |_, _| true),
+            Some((0x1000, 0x100))
+        );
+    }
+
+    #[test]
+    fn take_free_region_aligns_and_keeps_head() {
+        let mut regions = vec![
+            FreeRegion::new(0x1002, 0x8, 0), // too small once aligned
+            FreeRegion::new(0x2002, 0x100, 0),
+        ];
+
+        assert_eq!(
+            take_free_region(&mut regions, 0x10, 16, 0, |_, _| true),
+            Some((0x2010, 0xF2))
+        );
+        assert_eq!(
+            regions,
+            vec![
+                FreeRegion::new(0x1002, 0x8, 0),
+                FreeRegion::new(0x2002, 0xE, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn take_free_region_respects_predicate() {
+        let mut regions = vec![
+            FreeRegion::new(0x1000, 0x100, 0),
+            FreeRegion::new(0x9000, 0x100, 0),
+        ];
+
+        assert_eq!(
+            take_free_region(&mut regions, 0x10, 4, 0, |start, _| start >= 0x9000),
+            Some((0x9000, 0x100))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn write_to_dual_mapped_buffer() {
+        let mapping = super::super::linux_memfd::map_dual(4096, None).unwrap();
+        HAS_DUAL_MAPPINGS.store(true, Ordering::Relaxed);
+        BUFFERS.buffers.write().push(Rc::new(AllocatedBuffer {
+            ptr: mapping.exec,
+            write_ptr: mapping.write,
+            write_offset: RefCell::new(0),
+            size: 4096,
+            locked: AtomicBool::new(true),
+            is_recycled: false,
+        }));
+
+        // Writing to the executable view directly would segfault, so these must go through the RW view.
+        let exec = mapping.exec.as_ptr() as usize;
+        let mut buffer = LockedBuffer {
+            buffer: BUFFERS.buffers.read().last().unwrap().clone(),
+        };
+        buffer.write(&[1u8, 2u8, 3u8]);
+        LockedBuffer::overwrite(exec + 1, &[4u8]);
+        LockedBuffer::overwrite_atomic(exec + 2, 5u8);
+
+        unsafe {
+            assert_eq!(*(exec as *const [u8; 3]), [1u8, 4u8, 5u8]);
+        }
+    }
+
     #[test]
     fn write_to_buffer() {
         let mut buffer = DefaultBufferFactory::get_any_buffer(10, 4).unwrap();
@@ -0,0 +1,181 @@
+use core::ptr::{null_mut, NonNull};
+use core::sync::atomic::{AtomicBool, Ordering};
+use derive_new::new;
+use libc::{c_void, MAP_ANONYMOUS, MAP_FAILED, MAP_FIXED_NOREPLACE, MAP_PRIVATE, MAP_SHARED};
+use libc::{MFD_CLOEXEC, PROT_EXEC, PROT_READ, PROT_WRITE};
+
+/// Set once the kernel refuses to create RWX memory, after which only dual mappings are created.
+static RWX_DENIED: AtomicBool = AtomicBool::new(false);
+
+/// Memory mapped twice; once for executing and once for writing.
+///
+/// Hardened kernels (PaX, grsecurity, SELinux with `execmem` denied) refuse to create memory that
+/// is writable and executable at the same time. Mapping the same `memfd` twice works around this,
+/// code is written through the [`DualMapping::write`] view, and executed from [`DualMapping::exec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub(crate) struct DualMapping {
+    /// Read + Execute view of the memory.
+    pub exec: NonNull<u8>,
+
+    /// Read + Write view of the memory.
+    pub write: NonNull<u8>,
+}
+
+/// Maps `size` bytes of memory suitable for writing code to.
+///
+/// A single RWX mapping is preferred, if the kernel refuses to create one, this (and all future
+/// calls) fall back to [`map_dual`].
+///
+/// # Parameters
+///
+/// - `size`: The number of bytes to map.
+/// - `exec_address`: If specified, the memory must be executable at exactly this address.
+///
+/// # Returns
+///
+/// The mapped memory, where [`DualMapping::exec`] and [`DualMapping::write`] are the same for
+/// RWX memory; or `None` if the memory could not be mapped.
+pub(crate) fn map_code(size: usize, exec_address: Option<usize>) -> Option<DualMapping> {
+    if !RWX_DENIED.load(Ordering::Relaxed) {
+        match map_rwx(size, exec_address) {
+            Ok(ptr) => return Some(DualMapping::new(ptr, ptr)),
+            Err(true) => RWX_DENIED.store(true, Ordering::Relaxed),
+            Err(false) => return None,
+        }
+    }
+
+    map_dual(size, exec_address)
+}
+
+/// Maps `size` bytes of RWX memory, at exactly `exec_address` if specified.
+///
+/// # Returns
+///
+/// The mapped memory, or an error which is `true` if the mapping was refused due to a policy
+/// forbidding RWX memory.
+fn map_rwx(size: usize, exec_address: Option<usize>) -> Result<NonNull<u8>, bool> {
+    let (hint, fixed_flag) = match exec_address {
+        Some(address) => (address as *mut c_void, MAP_FIXED_NOREPLACE),
+        None => (null_mut(), 0),
+    };
+
+    unsafe {
+        let result = libc::mmap(
+            hint,
+            size,
+            PROT_READ | PROT_WRITE | PROT_EXEC,
+            MAP_PRIVATE | MAP_ANONYMOUS | fixed_flag,
+            -1,
+            0,
+        );
+
+        if result == MAP_FAILED {
+            return Err(is_rwx_denied_error());
+        }
+
+        // Kernels older than 4.17 don't know `MAP_FIXED_NOREPLACE`, and treat the address as a hint.
+        if exec_address.is_some_and(|address| result as usize != address) {
+            libc::munmap(result, size);
+            return Err(false);
+        }
+
+        NonNull::new(result as *mut u8).ok_or(false)
+    }
+}
+
+/// Creates a `memfd` of `size` bytes, and maps it as both RX and RW.
+///
+/// # Parameters
+///
+/// - `size`: The number of bytes to map.
+/// - `exec_address`: If specified, the RX view must be mapped at exactly this address.
+///
+/// # Returns
+///
+/// The two views of the memory, or `None` if the memory could not be mapped.
+pub(crate) fn map_dual(size: usize, exec_address: Option<usize>) -> Option<DualMapping> {
+    unsafe {
+        let fd = libc::memfd_create(c"reloaded-hooks".as_ptr(), MFD_CLOEXEC);
+        if fd < 0 {
+            return None;
+        }
+
+        let result = map_dual_fd(fd, size, exec_address);
+
+        // Mappings hold a reference to the file, so it stays alive after closing.
+        libc::close(fd);
+        result
+    }
+}
+
+unsafe fn map_dual_fd(fd: i32, size: usize, exec_address: Option<usize>) -> Option<DualMapping> {
+    if libc::ftruncate(fd, size as libc::off_t) != 0 {
+        return None;
+    }
+
+    let (hint, fixed_flag) = match exec_address {
+        Some(address) => (address as *mut c_void, MAP_FIXED_NOREPLACE),
+        None => (null_mut(), 0),
+    };
+
+    let exec = libc::mmap(
+        hint,
+        size,
+        PROT_READ | PROT_EXEC,
+        MAP_SHARED | fixed_flag,
+        fd,
+        0,
+    );
+    if exec == MAP_FAILED {
+        return None;
+    }
+
+    // Kernels older than 4.17 don't know `MAP_FIXED_NOREPLACE`, and treat the address as a hint.
+    if exec_address.is_some_and(|address| exec as usize != address) {
+        libc::munmap(exec, size);
+        return None;
+    }
+
+    let write = libc::mmap(null_mut(), size, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+    if write == MAP_FAILED {
+        libc::munmap(exec, size);
+        return None;
+    }
+
+    Some(DualMapping::new(
+        NonNull::new(exec as *mut u8)?,
+        NonNull::new(write as *mut u8)?,
+    ))
+}
+
+/// Returns true if the last failed `mmap` was refused due to a policy forbidding RWX memory.
+fn is_rwx_denied_error() -> bool {
+    let errno = unsafe { *libc::__errno_location() };
+    errno == libc::EACCES || errno == libc::EPERM
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::{read_volatile, write_volatile};
+
+    #[test]
+    fn map_code_is_writable() {
+        let mapping = map_code(4096, None).unwrap();
+        unsafe {
+            write_volatile(mapping.write.as_ptr(), 0xCC);
+            assert_eq!(read_volatile(mapping.exec.as_ptr()), 0xCC);
+        }
+    }
+
+    #[test]
+    fn writes_are_visible_in_exec_view() {
+        let mapping = map_dual(4096, None).unwrap();
+        assert_ne!(mapping.exec, mapping.write);
+
+        unsafe {
+            write_volatile(mapping.write.as_ptr().add(16), 0xCC);
+            assert_eq!(read_volatile(mapping.exec.as_ptr().add(16)), 0xCC);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+extern crate alloc;
+
+use super::linux_memfd::{map_code, DualMapping};
+use crate::api::platforms::linux_proc_maps::{parse_proc_maps, read_proc_maps, MappedRegion};
+use alloc::vec::Vec;
+
+/// Lowest address we will attempt to allocate at.
+/// Matches the default `vm.mmap_min_addr` on most distributions.
+const MIN_ADDRESS: usize = 0x10000;
+
+/// Allocates a block of code memory (see [`map_code`]) of `size` bytes, such that the whole block lies within
+/// `proximity` bytes of `target`.
+///
+/// Free gaps are found by parsing `/proc/self/maps`, then mapped with `MAP_FIXED_NOREPLACE`;
+/// the flag ensures we never clobber a mapping made by another thread after the maps were read.
+///
+/// # Parameters
+///
+/// - `target`: The address near which the memory should be allocated.
+/// - `proximity`: The maximum distance between the target address and any byte of the allocation.
+/// - `size`: The number of bytes to allocate. Rounded up to page size.
+///
+/// # Returns
+///
+/// The allocated memory, or `None` if no suitable free memory was found.
+pub(crate) fn allocate_near(target: usize, proximity: usize, size: usize) -> Option<DualMapping> {
+    let page_size = page_size();
+    let size = align_up(size, page_size);
+    let maps = read_proc_maps()?;
+    let regions = parse_proc_maps(&maps);
+    let gaps = find_free_gaps(&regions);
+
+    for address in get_candidate_addresses(&gaps, target, proximity, size, page_size) {
+        if let Some(result) = map_code(size, Some(address)) {
+            return Some(result);
+        }
+    }
+
+    None
+}
+
+/// Returns the free gaps between the given mapped regions as `(start, end)` pairs.
+///
+/// The regions must be sorted by address. Gaps below [`MIN_ADDRESS`] are excluded, as are gaps
+/// past the last mapped region (which is usually kernel space).
+pub(crate) fn find_free_gaps(regions: &[MappedRegion]) -> Vec<(usize, usize)> {
+    let mut gaps = Vec::new();
+    let mut last_end = MIN_ADDRESS;
+    for region in regions {
+        if region.start > last_end {
+            gaps.push((last_end, region.start));
+        }
+
+        last_end = last_end.max(region.end);
+    }
+
+    gaps
+}
+
+/// Returns the page aligned addresses where an allocation of `size` bytes would fit within the given
+/// gaps, and be within `proximity` of `target`. One candidate per gap, ordered by distance to `target`.
+pub(crate) fn get_candidate_addresses(
+    gaps: &[(usize, usize)],
+    target: usize,
+    proximity: usize,
+    size: usize,
+    page_size: usize,
+) -> Vec<usize> {
+    let min_address = target.saturating_sub(proximity);
+    let max_address = target.saturating_add(proximity);
+
+    let mut candidates = Vec::new();
+    for &(gap_start, gap_end) in gaps {
+        let lowest = align_up(gap_start.max(min_address), page_size);
+        let end = gap_end.min(max_address);
+        if end < size {
+            continue;
+        }
+
+        let highest = align_down(end - size, page_size);
+        if lowest > highest {
+            continue;
+        }
+
+        candidates.push(align_down(target, page_size).clamp(lowest, highest));
+    }
+
+    candidates.sort_unstable_by_key(|x| x.abs_diff(target));
+    candidates
+}
+
+/// Returns true if the region `[start, end)` lies entirely within `proximity` bytes of `target`.
+pub(crate) fn is_in_proximity(target: usize, proximity: usize, start: usize, end: usize) -> bool {
+    start.abs_diff(target) <= proximity && end.abs_diff(target) <= proximity
+}
+
+fn page_size() -> usize {
+    let result = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if result <= 0 {
+        4096
+    } else {
+        result as usize
+    }
+}
+
+pub(crate) fn align_up(value: usize, alignment: usize) -> usize {
+    align_down(value.saturating_add(alignment - 1), alignment)
+}
+
+fn align_down(value: usize, alignment: usize) -> usize {
+    value & !(alignment - 1)
+}
+
+#[cfg(test)]
+#[cfg(target_pointer_width = "64")]
+mod tests {
+    use super::*;
+
+    const MAPS: &[u8] = b"\
+55d0a0000000-55d0a0002000 r--p 00000000 00:1f 123  /usr/bin/game
+55d0a0002000-55d0a0010000 r-xp 00002000 00:1f 123  /usr/bin/game
+55d0a0020000-55d0a0021000 rw-p 00000000 00:00 0    [heap]
+7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0    [stack]
+";
+
+    #[test]
+    fn finds_free_gaps() {
+        let gaps = find_free_gaps(&parse_proc_maps(MAPS));
+        assert_eq!(
+            gaps,
+            [
+                (MIN_ADDRESS, 0x55d0a0000000),
+                (0x55d0a0010000, 0x55d0a0020000),
+                (0x55d0a0021000, 0x7ffd00000000),
+            ]
+        );
+    }
+
+    #[test]
+    fn candidates_ordered_by_distance() {
+        let gaps = find_free_gaps(&parse_proc_maps(MAPS));
+        let candidates = get_candidate_addresses(&gaps, 0x55d0a0008000, 0x80000000, 0x1000, 0x1000);
+
+        // Gap between code and heap, then directly before the module, then directly after heap.
+        assert_eq!(candidates, [0x55d0a0010000, 0x55d09ffff000, 0x55d0a0021000]);
+    }
+
+    #[test]
+    fn candidates_respect_proximity() {
+        let gaps = find_free_gaps(&parse_proc_maps(MAPS));
+        let candidates = get_candidate_addresses(&gaps, 0x55d0a0008000, 0x10000, 0x1000, 0x1000);
+
+        // The gap after the heap is out of range.
+        assert_eq!(candidates, [0x55d0a0010000, 0x55d09ffff000]);
+    }
+
+    #[test]
+    fn candidates_skip_small_gaps() {
+        let gaps = find_free_gaps(&parse_proc_maps(MAPS));
+        let candidates =
+            get_candidate_addresses(&gaps, 0x55d0a0008000, 0x80000000, 0x20000, 0x1000);
+        assert_eq!(candidates, [0x55d0a0021000, 0x55d09ffe0000]);
+    }
+
+    #[test]
+    fn allocates_near_target() {
+        let target = allocates_near_target as *const () as usize;
+        let proximity = 0x7FFFFFFF;
+        let result = allocate_near(target, proximity, 0x1000)
+            .unwrap()
+            .exec
+            .as_ptr() as usize;
+        assert!(is_in_proximity(target, proximity, result, result + 0x1000));
+    }
+}
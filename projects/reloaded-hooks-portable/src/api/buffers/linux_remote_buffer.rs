@@ -0,0 +1,73 @@
+extern crate alloc;
+
+use super::buffer_abstractions::Buffer;
+use super::linux_remote_buffer_factory::{RemoteAllocation, REMOTE_ALLOCATIONS};
+use crate::api::platforms::platform_functions::get_platform_functions;
+use core::mem::size_of;
+use core::slice;
+
+/// A buffer in the memory of the process registered with [`register_remote_process`].
+///
+/// All writes go through the registered [`MemoryAccessor`], i.e. into the remote process.
+///
+/// Writes can't fail in the [`Buffer`] API. If the process has exited, writing the hook
+/// at the hook address fails too, and that error is returned when the hook is created.
+///
+/// [`register_remote_process`]: crate::api::platforms::linux_remote::register_remote_process
+/// [`MemoryAccessor`]: crate::api::platforms::platform_abstractions::MemoryAccessor
+pub struct RemoteBuffer {
+    pub(crate) allocation: RemoteAllocation,
+}
+
+impl RemoteBuffer {
+    /// Returns the number of bytes remaining in the buffer.
+    pub fn remaining_bytes(&self) -> usize {
+        self.allocation.size - self.allocation.write_offset
+    }
+}
+
+impl Buffer for RemoteBuffer {
+    fn get_address(&self) -> *const u8 {
+        (self.allocation.address + self.allocation.write_offset) as *const u8
+    }
+
+    fn write(&mut self, data: &[u8]) -> *const u8 {
+        debug_assert!(data.len() <= self.remaining_bytes(), "Buffer overflow");
+        unsafe {
+            _ = get_platform_functions().write_memory(self.get_address() as usize, data);
+        }
+
+        self.advance(data.len())
+    }
+
+    fn advance(&mut self, num_bytes: usize) -> *const u8 {
+        self.allocation.write_offset += num_bytes;
+        self.get_address()
+    }
+
+    fn overwrite(address: usize, buffer: &[u8]) {
+        unsafe {
+            _ = get_platform_functions().write_memory(address, buffer);
+        }
+    }
+
+    fn overwrite_atomic<TInteger>(address: usize, buffer: TInteger)
+    where
+        Self: Sized,
+    {
+        unsafe {
+            let bytes = slice::from_raw_parts(
+                &buffer as *const TInteger as *const u8,
+                size_of::<TInteger>(),
+            );
+            _ = get_platform_functions().write_memory_atomic(address, bytes);
+        }
+    }
+}
+
+impl Drop for RemoteBuffer {
+    fn drop(&mut self) {
+        // Return the allocation to the pool, so the remaining space can be reused.
+        REMOTE_ALLOCATIONS.lock().push(self.allocation);
+    }
+}
@@ -0,0 +1,135 @@
+extern crate alloc;
+
+use super::buffer_abstractions::BufferFactory;
+use super::linux_proximity::{align_up, find_free_gaps, get_candidate_addresses, is_in_proximity};
+use super::linux_remote_buffer::RemoteBuffer;
+use crate::api::platforms::linux_proc_maps::{parse_proc_maps, read_proc_maps_of};
+use crate::api::platforms::linux_remote::{get_remote_process, RemoteProcess};
+use crate::api::platforms::platform_abstractions::PlatformFunctions;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use derive_new::new;
+use spin::Mutex;
+
+/// Memory allocated in the remote process which isn't currently locked by a [`RemoteBuffer`].
+/// Locked allocations are removed from this list, and returned when the buffer is dropped.
+pub(crate) static REMOTE_ALLOCATIONS: Mutex<Vec<RemoteAllocation>> = Mutex::new(Vec::new());
+
+/// Minimum size of memory allocated in the remote process.
+const MIN_ALLOCATION_SIZE: usize = 65536;
+
+/// A block of memory allocated in the remote process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub(crate) struct RemoteAllocation {
+    pub address: usize,
+    pub size: usize,
+    pub write_offset: usize,
+}
+
+impl RemoteAllocation {
+    /// Returns the address the next write goes to after aligning to `alignment`, if `size` bytes
+    /// fit in the allocation from there.
+    fn aligned_free_space(&self, size: usize, alignment: usize) -> Option<usize> {
+        let current = self.address + self.write_offset;
+        let aligned = align_up(current, alignment);
+        (aligned + size <= self.address + self.size).then_some(aligned)
+    }
+}
+
+/// Allocates buffers in the process registered with [`register_remote_process`].
+///
+/// Memory is allocated by making the (stopped) remote process call `mmap`, near the target
+/// based on the contents of `/proc/<pid>/maps`. Only supported on x86_64.
+///
+/// [`register_remote_process`]: crate::api::platforms::linux_remote::register_remote_process
+pub struct RemoteBufferFactory {}
+
+impl BufferFactory<RemoteBuffer> for RemoteBufferFactory {
+    fn get_buffer(
+        size: u32,
+        target: usize,
+        proximity: usize,
+        alignment: u32,
+    ) -> Result<Box<RemoteBuffer>, String> {
+        let process = get_process()?;
+        let in_proximity =
+            |start: usize, end: usize| is_in_proximity(target, proximity, start, end);
+
+        if let Some(buffer) = try_take_allocation(size as usize, alignment as usize, in_proximity) {
+            return Ok(buffer);
+        }
+
+        // Allocate more than requested, so later requests near the same module can reuse the buffer.
+        let page_size = process.page_size();
+        let alloc_size = align_up((size as usize).max(MIN_ALLOCATION_SIZE), page_size);
+        let maps = read_proc_maps_of(process.pid())
+            .ok_or_else(|| "Failed to read memory map of remote process".to_string())?;
+        let gaps = find_free_gaps(&parse_proc_maps(&maps));
+
+        for address in get_candidate_addresses(&gaps, target, proximity, alloc_size, page_size) {
+            if let Some(address) = process.map_memory(Some(address), alloc_size) {
+                return Ok(Box::new(RemoteBuffer {
+                    allocation: RemoteAllocation::new(address, alloc_size, 0),
+                }));
+            }
+        }
+
+        Err("No free memory found in proximity of target".to_string())
+    }
+
+    fn get_any_buffer(size: u32, alignment: u32) -> Result<Box<RemoteBuffer>, String> {
+        let process = get_process()?;
+        if let Some(buffer) = try_take_allocation(size as usize, alignment as usize, |_, _| true) {
+            return Ok(buffer);
+        }
+
+        let alloc_size = align_up(
+            (size as usize).max(MIN_ALLOCATION_SIZE),
+            process.page_size(),
+        );
+        let address = process
+            .map_memory(None, alloc_size)
+            .ok_or_else(|| "Failed to allocate memory in remote process".to_string())?;
+
+        Ok(Box::new(RemoteBuffer {
+            allocation: RemoteAllocation::new(address, alloc_size, 0),
+        }))
+    }
+}
+
+fn get_process() -> Result<&'static RemoteProcess, String> {
+    get_remote_process().ok_or_else(|| "No remote process registered".to_string())
+}
+
+/// Locks the first allocation with `size` bytes free after aligning to `alignment`,
+/// and for which `predicate(start, end)` of the aligned free space returns true.
+fn try_take_allocation(
+    size: usize,
+    alignment: usize,
+    predicate: impl Fn(usize, usize) -> bool,
+) -> Option<Box<RemoteBuffer>> {
+    let mut allocations = REMOTE_ALLOCATIONS.lock();
+    let index = allocations.iter().position(|allocation| {
+        allocation
+            .aligned_free_space(size, alignment)
+            .is_some_and(|aligned| predicate(aligned, aligned + size))
+    })?;
+
+    let mut allocation = allocations.swap_remove(index);
+    allocation.write_offset = allocation.aligned_free_space(size, alignment)? - allocation.address;
+    Some(Box::new(RemoteBuffer { allocation }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned_free_space() {
+        let allocation = RemoteAllocation::new(0x1000, 0x100, 0x11);
+        assert_eq!(allocation.aligned_free_space(0x10, 16), Some(0x1020));
+        assert_eq!(allocation.aligned_free_space(0xE0, 16), Some(0x1020));
+        assert_eq!(allocation.aligned_free_space(0xE1, 16), None);
+    }
+}
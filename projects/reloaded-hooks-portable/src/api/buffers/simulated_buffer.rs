@@ -0,0 +1,63 @@
+use super::buffer_abstractions::Buffer;
+use crate::api::platforms::platform_functions::get_platform_functions;
+use core::mem::size_of;
+use core::slice;
+
+/// A buffer in the [`SimulatedMemory`] registered with [`register_simulated_memory`].
+///
+/// All writes go through the registered [`MemoryAccessor`], i.e. into the simulated memory.
+///
+/// [`SimulatedMemory`]: crate::api::platforms::simulated_memory::SimulatedMemory
+/// [`register_simulated_memory`]: crate::api::platforms::simulated_memory::register_simulated_memory
+/// [`MemoryAccessor`]: crate::api::platforms::platform_abstractions::MemoryAccessor
+pub struct SimulatedBuffer {
+    pub(crate) address: usize,
+    pub(crate) size: usize,
+    pub(crate) write_offset: usize,
+}
+
+impl SimulatedBuffer {
+    /// Returns the number of bytes remaining in the buffer.
+    pub fn remaining_bytes(&self) -> usize {
+        self.size - self.write_offset
+    }
+}
+
+impl Buffer for SimulatedBuffer {
+    fn get_address(&self) -> *const u8 {
+        (self.address + self.write_offset) as *const u8
+    }
+
+    fn write(&mut self, data: &[u8]) -> *const u8 {
+        debug_assert!(data.len() <= self.remaining_bytes(), "Buffer overflow");
+        unsafe {
+            _ = get_platform_functions().write_memory(self.get_address() as usize, data);
+        }
+
+        self.advance(data.len())
+    }
+
+    fn advance(&mut self, num_bytes: usize) -> *const u8 {
+        self.write_offset += num_bytes;
+        self.get_address()
+    }
+
+    fn overwrite(address: usize, buffer: &[u8]) {
+        unsafe {
+            _ = get_platform_functions().write_memory(address, buffer);
+        }
+    }
+
+    fn overwrite_atomic<TInteger>(address: usize, buffer: TInteger)
+    where
+        Self: Sized,
+    {
+        unsafe {
+            let bytes = slice::from_raw_parts(
+                &buffer as *const TInteger as *const u8,
+                size_of::<TInteger>(),
+            );
+            _ = get_platform_functions().write_memory_atomic(address, bytes);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+extern crate alloc;
+
+use super::buffer_abstractions::BufferFactory;
+use super::simulated_buffer::SimulatedBuffer;
+use crate::api::platforms::simulated_memory::get_simulated_memory;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+
+/// Allocates buffers in the [`SimulatedMemory`] registered with [`register_simulated_memory`].
+///
+/// Each buffer gets a new region, mapped at the first free page after the target. Buffers are
+/// therefore always close to the code they were requested for, and at a deterministic offset
+/// from it; provided other code is mapped sufficiently far away.
+///
+/// [`SimulatedMemory`]: crate::api::platforms::simulated_memory::SimulatedMemory
+/// [`register_simulated_memory`]: crate::api::platforms::simulated_memory::register_simulated_memory
+pub struct SimulatedBufferFactory {}
+
+impl BufferFactory<SimulatedBuffer> for SimulatedBufferFactory {
+    fn get_buffer(
+        size: u32,
+        target: usize,
+        proximity: usize,
+        _alignment: u32,
+    ) -> Result<Box<SimulatedBuffer>, String> {
+        // Allocations are page aligned, which satisfies any code alignment.
+        let address = get_simulated_memory()
+            .allocate_near(target, size as usize, proximity)
+            .ok_or_else(|| "No free memory found in proximity of target".to_string())?;
+
+        Ok(new_buffer(address, size as usize))
+    }
+
+    fn get_any_buffer(size: u32, _alignment: u32) -> Result<Box<SimulatedBuffer>, String> {
+        let address = get_simulated_memory().allocate(ANY_BUFFER_ADDRESS, size as usize);
+        Ok(new_buffer(address, size as usize))
+    }
+}
+
+/// Address around which buffers without a target are allocated.
+/// Low enough to be addressable by 32-bit architectures.
+const ANY_BUFFER_ADDRESS: usize = 0x7000_0000;
+
+fn new_buffer(address: usize, size: usize) -> Box<SimulatedBuffer> {
+    Box::new(SimulatedBuffer {
+        address,
+        size,
+        write_offset: 0,
+    })
+}
@@ -26,6 +26,16 @@ pub trait CallingConventionInfo<TRegister: Copy + RegisterInfo + PartialEq + 'st
     /// This is not necessarily the same as the register that the function returns its value in.
     fn return_register(&self) -> TRegister;
 
+    /// The register that the function returns floating point values in.
+    /// In x86 this is typically 'st0', and in x64 'xmm0'.
+    ///
+    /// # Remarks
+    /// When two conventions disagree on this register, wrappers move float return values between them.
+    /// [`None`] if floats aren't returned in a dedicated register, in which case they are left as is.
+    fn float_return_register(&self) -> Option<TRegister> {
+        None
+    }
+
     /// Used for allocating an extra amount of uninitialized (not zero-written) stack space
     /// before calling the function. This is useful for functions that use the stack for temporary storage.
     ///
@@ -71,17 +81,49 @@ pub trait CallingConventionInfo<TRegister: Copy + RegisterInfo + PartialEq + 'st
     /// 0 bytes for x86, etc.
     fn required_stack_alignment(&self) -> u32;
 
+    /// Specifies how the registers in [`register_int_parameters`](#method.register_int_parameters),
+    /// [`register_float_parameters`](#method.register_float_parameters) and
+    /// [`register_vector_parameters`](#method.register_vector_parameters) are assigned to parameters.
+    fn parameter_register_assignment(&self) -> ParameterRegisterAssignment {
+        ParameterRegisterAssignment::Sequential
+    }
+
+    /// True if code using this convention may use registers which require an optional
+    /// instruction set extension (see [`RegisterInfo::requires_extension`]), i.e. the
+    /// code is only ever run on processors supporting that extension.
+    ///
+    /// When false, such registers are never returned from [`caller_saved_registers`](#method.caller_saved_registers).
+    fn uses_extension_registers(&self) -> bool {
+        false
+    }
+
     /// This is automatically determined based on [`callee_saved_registers`](#method.callee_saved_registers)
     /// and [`always_saved_registers`](#method.always_saved_registers). Returns all registers not listed
-    /// there.
+    /// there, except for the stack pointer, which is never free for use, and registers requiring an
+    /// instruction set extension, unless [`uses_extension_registers`](#method.uses_extension_registers).
+    ///
+    /// Only full size registers (see [`RegisterInfo::extend`]) are returned, such that callers can
+    /// remove registers in use (e.g. `x0`) without missing their smaller aliases (e.g. `w0`).
     fn caller_saved_registers(&self) -> Vec<TRegister> {
         let callee_saved = self.callee_saved_registers();
         let always_saved = self.always_saved_registers();
         let all_registers = TRegister::all_registers();
+        let uses_extension_registers = self.uses_extension_registers();
+        let is_saved = |reg: &TRegister| {
+            callee_saved
+                .iter()
+                .chain(always_saved)
+                .any(|saved| saved.extend() == *reg)
+        };
 
         let vec: Vec<TRegister> = all_registers
             .iter()
-            .filter(|reg| !callee_saved.contains(reg) && !always_saved.contains(reg))
+            .filter(|reg| {
+                reg.extend() == **reg
+                    && !reg.is_stack_pointer()
+                    && !is_saved(reg)
+                    && (uses_extension_registers || !reg.requires_extension())
+            })
             .copied()
             .collect();
 
@@ -122,6 +164,35 @@ pub enum StackCleanup {
     Callee,
 }
 
+/// Defines how parameter registers are assigned to the parameters of a function.
+///
+/// # Remarks
+///
+/// For [`SharedFloatVector`] and [`Positional`], a vector parameter is passed in the register of
+/// the same size as the parameter, e.g. `ymm1` for a 256-bit vector in the 2nd slot, so vector
+/// parameter registers of every supported size should be listed in order (e.g. `xmm0`-`xmm7`,
+/// `ymm0`-`ymm7`, `zmm0`-`zmm7`).
+///
+/// [`SharedFloatVector`]: ParameterRegisterAssignment::SharedFloatVector
+/// [`Positional`]: ParameterRegisterAssignment::Positional
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParameterRegisterAssignment {
+    /// Each parameter takes the next unused register of its kind (integer, float or vector).
+    #[default]
+    Sequential,
+
+    /// Same as [`Sequential`](ParameterRegisterAssignment::Sequential), but float and vector
+    /// parameters share the same register slots.
+    ///
+    /// e.g. System V AMD64, where `(float, __m256)` is passed in `xmm0, ymm1`.
+    SharedFloatVector,
+
+    /// Each parameter takes the register of its kind at the same position as the parameter.
+    ///
+    /// e.g. Microsoft x64, where `(int, float)` is passed in `rcx, xmm1`.
+    Positional,
+}
+
 /// Represents the order in which function parameters are pushed onto the stack when making a function call.
 ///
 /// In some calling conventions, parameters are pushed onto the stack from right to left, meaning
@@ -154,24 +225,30 @@ pub enum StackParameterOrder {
 /// - `float_parameters`: A slice of registers used for passing floating-point parameters.
 /// - `vector_parameters`: A slice of registers used for passing vector parameters.
 /// - `return_register`: The register used for the function return value.
+/// - `float_return_register`: The register used for floating point return values, if any.
 /// - `reserved_stack_space`: The amount of stack space reserved for the function.
 /// - `callee_saved_registers`: Registers that the callee is responsible for saving and restoring.
 /// - `always_saved_registers`: Registers that are always saved across function calls.
 /// - `stack_cleanup`: Specifies who cleans up the stack after the function call.
 /// - `stack_parameter_order`: The order in which parameters are pushed onto the stack.
 /// - `required_stack_alignment`: The required alignment of the stack pointer before the function call.
+/// - `uses_extension_registers`: Whether registers requiring an instruction set extension may be used as scratch registers.
+/// - `parameter_register_assignment`: How parameter registers are assigned to parameters.
 #[derive(Debug, Clone, PartialEq)]
 pub struct GenericCallingConvention<'a, TRegister: Copy> {
     pub int_parameters: &'a [TRegister],
     pub float_parameters: &'a [TRegister],
     pub vector_parameters: &'a [TRegister],
     pub return_register: TRegister,
+    pub float_return_register: Option<TRegister>,
     pub reserved_stack_space: u32,
     pub callee_saved_registers: &'a [TRegister],
     pub always_saved_registers: &'a [TRegister],
     pub stack_cleanup: StackCleanup,
     pub stack_parameter_order: StackParameterOrder,
     pub required_stack_alignment: u32,
+    pub uses_extension_registers: bool,
+    pub parameter_register_assignment: ParameterRegisterAssignment,
 }
 
 impl<'a, TRegister: Copy + RegisterInfo + PartialEq + 'static> CallingConventionInfo<TRegister>
@@ -193,6 +270,10 @@ impl<'a, TRegister: Copy + RegisterInfo + PartialEq + 'static> CallingConvention
         self.return_register
     }
 
+    fn float_return_register(&self) -> Option<TRegister> {
+        self.float_return_register
+    }
+
     fn reserved_stack_space(&self) -> u32 {
         self.reserved_stack_space
     }
@@ -216,6 +297,14 @@ impl<'a, TRegister: Copy + RegisterInfo + PartialEq + 'static> CallingConvention
     fn required_stack_alignment(&self) -> u32 {
         self.required_stack_alignment
     }
+
+    fn uses_extension_registers(&self) -> bool {
+        self.uses_extension_registers
+    }
+
+    fn parameter_register_assignment(&self) -> ParameterRegisterAssignment {
+        self.parameter_register_assignment
+    }
 }
 
 #[cfg(test)]
@@ -230,9 +319,9 @@ mod tests {
 
     #[test]
     fn cdecl_like_saved_registers() {
-        // Removed both always saved (LR, SP), and callee saved (R3, R4, F3, F4, V3, V4).
+        // Removed callee saved (R3, R4, F3, F4, V3, V4), and the stack pointer.
         let mut caller_saved = CDECL_LIKE_FUNCTION_ATTRIBUTE.caller_saved_registers();
-        let mut expected: Vec<MockRegister> = vec![R0, R1, R2, F0, F1, F2, V0, V1, V2, SP, LR];
+        let mut expected: Vec<MockRegister> = vec![R0, R1, R2, F0, F1, F2, V0, V1, V2, LR];
         caller_saved.sort();
         expected.sort();
         assert_eq!(caller_saved, expected);
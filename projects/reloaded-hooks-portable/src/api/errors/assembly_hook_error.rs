@@ -1,6 +1,7 @@
 extern crate alloc;
 
 use super::hook_builder_error::HookBuilderError;
+use super::memory_protection_error::MemoryProtectionError;
 use crate::api::jit::compiler::JitError;
 use thiserror_no_std::Error;
 
@@ -21,4 +22,8 @@ pub enum AssemblyHookError<TRegister> {
     /// JIT related error.
     #[error("Error in JIT: {0:?}")]
     JitError(JitError<TRegister>),
+
+    /// Failed to change the protection of the memory being hooked.
+    #[error("Memory Protection Error: {0:?}")]
+    MemoryProtectionError(#[from] MemoryProtectionError),
 }
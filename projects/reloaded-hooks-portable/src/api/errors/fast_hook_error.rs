@@ -1,4 +1,5 @@
 extern crate alloc;
+use super::memory_protection_error::MemoryProtectionError;
 use crate::api::jit::compiler::JitError;
 use thiserror_no_std::Error;
 
@@ -12,4 +13,8 @@ pub enum FastHookError<TRegister> {
     /// JIT related error.
     #[error("JitError: {0:?}")]
     JitError(#[from] JitError<TRegister>),
+
+    /// Failed to change the protection of the memory being hooked.
+    #[error("Memory Protection Error: {0:?}")]
+    MemoryProtectionError(#[from] MemoryProtectionError),
 }
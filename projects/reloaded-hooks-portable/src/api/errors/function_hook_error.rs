@@ -1,6 +1,7 @@
 extern crate alloc;
 use super::{
-    hook_builder_error::HookBuilderError, wrapper_generation_error::WrapperGenerationError,
+    hook_builder_error::HookBuilderError, memory_protection_error::MemoryProtectionError,
+    wrapper_generation_error::WrapperGenerationError,
 };
 use crate::api::jit::compiler::JitError;
 use thiserror_no_std::Error;
@@ -21,4 +22,12 @@ pub enum FunctionHookError<TRegister> {
     /// JIT related error.
     #[error("JitError: {0:?}")]
     JitError(#[from] JitError<TRegister>),
+
+    /// Failed to change the protection of the memory being hooked.
+    #[error("Memory Protection Error: {0:?}")]
+    MemoryProtectionError(#[from] MemoryProtectionError),
+
+    /// The platform could not provide a thread local flag for the re-entrancy guard.
+    #[error("Thread local flag for re-entrancy guard could not be allocated.")]
+    ThreadLocalFlagUnavailable,
 }
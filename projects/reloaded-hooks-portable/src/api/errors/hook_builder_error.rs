@@ -22,6 +22,12 @@ pub enum HookBuilderError<TRegister> {
     #[error("Failed to rewrite code. Source: {0:?}, Error: {1:?}")]
     RewriteError(RewriteErrorDetails, CodeRewriterError),
 
+    /// A branch in the original code lands inside the bytes which would be overwritten by the hook.
+    ///
+    /// Parameters: (branch_address, branch_target)
+    #[error("Branch at {0:#X} targets {1:#X}, which is inside the code overwritten by the hook.")]
+    BranchIntoStolenRegion(usize, usize),
+
     /// JIT related error.
     #[error("Error in JIT: {0:?}")]
     JitError(#[from] JitError<TRegister>),
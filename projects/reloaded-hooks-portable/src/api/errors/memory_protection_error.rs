@@ -0,0 +1,37 @@
+extern crate alloc;
+use thiserror_no_std::Error;
+
+/// Errors that can occur while changing the protection of memory pages, or accessing them.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum MemoryProtectionError {
+    /// The current protection of a page could not be determined.
+    /// Usually because the page is not mapped.
+    ///
+    /// Parameters: (page_address)
+    #[error("Failed to query the protection of page at {0:#X}.")]
+    QueryFailed(usize),
+
+    /// The page could not be made writeable.
+    ///
+    /// Parameters: (page_address)
+    #[error("Failed to unprotect page at {0:#X}.")]
+    UnprotectFailed(usize),
+
+    /// The original protection of a page could not be restored.
+    ///
+    /// Parameters: (page_address)
+    #[error("Failed to restore the protection of page at {0:#X}.")]
+    RestoreFailed(usize),
+
+    /// The memory could not be read, e.g. because the process it belongs to has exited.
+    ///
+    /// Parameters: (address)
+    #[error("Failed to read memory at {0:#X}.")]
+    ReadFailed(usize),
+
+    /// The memory could not be written, e.g. because the process it belongs to has exited.
+    ///
+    /// Parameters: (address)
+    #[error("Failed to write memory at {0:#X}.")]
+    WriteFailed(usize),
+}
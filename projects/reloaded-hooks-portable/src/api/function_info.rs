@@ -1,6 +1,9 @@
 extern crate alloc;
 
-use super::{calling_convention_info::CallingConventionInfo, traits::register_info::RegisterInfo};
+use super::{
+    calling_convention_info::{CallingConventionInfo, ParameterRegisterAssignment},
+    traits::register_info::RegisterInfo,
+};
 use alloc::vec::Vec;
 use core::mem::size_of;
 
@@ -9,6 +12,12 @@ pub trait FunctionInfo {
     /// Types of parameters in left-right order.
     fn parameters(&self) -> &[ParameterType];
 
+    /// Type of the returned value, [`None`] if the function returns nothing, or if the
+    /// return value needs no conversion between calling conventions.
+    fn return_type(&self) -> Option<ParameterType> {
+        None
+    }
+
     /// Returns the number of integer parameters in the function.
     fn num_integer_parameters(&self) -> u32 {
         self.parameters()
@@ -78,28 +87,59 @@ pub trait FunctionInfo {
         let mut stack_idx = 0;
         let mut reg_idx = 0;
 
-        let mut int_registers = convention.register_int_parameters().iter();
-        let mut float_registers = convention.register_float_parameters().iter();
-        let mut vector_registers = convention.register_vector_parameters().iter();
-
-        for &parameter in parameters {
-            if parameter.is_float() {
-                if let Some(reg) = float_registers.next() {
-                    reg_params[reg_idx] = (parameter, *reg);
-                    reg_idx += 1;
-                } else {
-                    stack_params[stack_idx] = parameter;
-                    stack_idx += 1;
+        let int_registers = convention.register_int_parameters();
+        let float_registers = convention.register_float_parameters();
+        let vector_registers = convention.register_vector_parameters();
+        let assignment = convention.parameter_register_assignment();
+
+        // Index of next register of each kind. Float and vector may share one.
+        let mut int_slot = 0;
+        let mut float_slot = 0;
+        let mut vector_slot = 0;
+
+        for (position, &parameter) in parameters.iter().enumerate() {
+            let register = match assignment {
+                ParameterRegisterAssignment::Sequential => {
+                    if parameter.is_float() {
+                        float_slot += 1;
+                        float_registers.get(float_slot - 1)
+                    } else if parameter.is_vector() {
+                        vector_slot += 1;
+                        vector_registers.get(vector_slot - 1)
+                    } else {
+                        int_slot += 1;
+                        int_registers.get(int_slot - 1)
+                    }
                 }
-            } else if parameter.is_vector() {
-                if let Some(reg) = vector_registers.next() {
-                    reg_params[reg_idx] = (parameter, *reg);
-                    reg_idx += 1;
-                } else {
-                    stack_params[stack_idx] = parameter;
-                    stack_idx += 1;
+                ParameterRegisterAssignment::SharedFloatVector => {
+                    if parameter.is_float() {
+                        float_slot += 1;
+                        get_float_register(
+                            float_registers,
+                            vector_registers,
+                            float_slot - 1,
+                            parameter,
+                        )
+                    } else if parameter.is_vector() {
+                        float_slot += 1;
+                        get_vector_register(vector_registers, float_slot - 1, parameter)
+                    } else {
+                        int_slot += 1;
+                        int_registers.get(int_slot - 1)
+                    }
                 }
-            } else if let Some(reg) = int_registers.next() {
+                ParameterRegisterAssignment::Positional => {
+                    if parameter.is_float() {
+                        get_float_register(float_registers, vector_registers, position, parameter)
+                    } else if parameter.is_vector() {
+                        get_vector_register(vector_registers, position, parameter)
+                    } else {
+                        int_registers.get(position)
+                    }
+                }
+            };
+
+            if let Some(reg) = register {
                 reg_params[reg_idx] = (parameter, *reg);
                 reg_idx += 1;
             } else {
@@ -155,15 +195,59 @@ pub trait FunctionInfo {
     }
 }
 
+/// Returns the `index`-th float register, or the `index`-th vector register if the parameter
+/// does not fit in a float register (e.g. 256-bit float in `ymm` rather than `xmm`).
+fn get_float_register<'a, TRegister: RegisterInfo>(
+    float_registers: &'a [TRegister],
+    vector_registers: &'a [TRegister],
+    index: usize,
+    parameter: ParameterType,
+) -> Option<&'a TRegister> {
+    let register = float_registers.get(index)?;
+    if register.size_in_bytes() >= parameter.size_in_bytes() {
+        Some(register)
+    } else {
+        get_vector_register(vector_registers, index, parameter)
+    }
+}
+
+/// Returns the `index`-th vector register out of the smallest registers which fit the parameter.
+fn get_vector_register<TRegister: RegisterInfo>(
+    registers: &[TRegister],
+    index: usize,
+    parameter: ParameterType,
+) -> Option<&TRegister> {
+    let size = registers
+        .iter()
+        .map(|reg| reg.size_in_bytes())
+        .filter(|&size| size >= parameter.size_in_bytes())
+        .min()?;
+
+    registers
+        .iter()
+        .filter(|reg| reg.size_in_bytes() == size)
+        .nth(index)
+}
+
 /// Basic reference implementation of [`FunctionInfo`]
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct BasicFunctionInfo<'a> {
     params: &'a [ParameterType],
+    return_type: Option<ParameterType>,
 }
 
 impl<'a> BasicFunctionInfo<'a> {
     pub const fn new(params: &'a [ParameterType]) -> Self {
-        Self { params }
+        Self {
+            params,
+            return_type: None,
+        }
+    }
+
+    /// Sets the type of the returned value, see [`FunctionInfo::return_type`].
+    pub const fn with_return_type(mut self, return_type: ParameterType) -> Self {
+        self.return_type = Some(return_type);
+        self
     }
 }
 
@@ -171,6 +255,10 @@ impl<'a> FunctionInfo for BasicFunctionInfo<'a> {
     fn parameters(&self) -> &[ParameterType] {
         self.params
     }
+
+    fn return_type(&self) -> Option<ParameterType> {
+        self.return_type
+    }
 }
 
 /// Defines the kind of parameter used in the function.
@@ -296,11 +384,12 @@ impl ParameterType {
     pub fn size_in_bytes(&self) -> usize {
         match *self {
             ParameterType::nint => size_of::<isize>(),
-            ParameterType::i8 | ParameterType::v16 | ParameterType::f16 => 1,
-            ParameterType::i16 | ParameterType::v32 | ParameterType::f32 => 2,
-            ParameterType::i32 | ParameterType::v64 | ParameterType::f64 => 4,
-            ParameterType::i64 | ParameterType::v128 | ParameterType::f128 => 8,
-            ParameterType::i128 | ParameterType::v256 | ParameterType::f256 => 16,
+            ParameterType::i8 => 1,
+            ParameterType::i16 | ParameterType::v16 | ParameterType::f16 => 2,
+            ParameterType::i32 | ParameterType::v32 | ParameterType::f32 => 4,
+            ParameterType::i64 | ParameterType::v64 | ParameterType::f64 => 8,
+            ParameterType::i128 | ParameterType::v128 | ParameterType::f128 => 16,
+            ParameterType::v256 | ParameterType::f256 => 32,
             ParameterType::v512 | ParameterType::f512 => 64,
         }
     }
@@ -447,4 +536,82 @@ mod tests {
             vec![ParameterType::f32]
         );
     }
+
+    fn get_register_parameters(
+        function: &MockFunction,
+        attribute: &MockFunctionAttribute,
+    ) -> Vec<MockRegister> {
+        let params = function.get_parameters_as_vec(attribute).1;
+        params.iter().map(|x| x.1).collect()
+    }
+
+    #[test]
+    fn shared_float_vector_registers() {
+        let function = create_mock_function(vec![
+            ParameterType::f32,
+            ParameterType::v128,
+            ParameterType::i32,
+            ParameterType::f32,
+            ParameterType::v256,
+        ]);
+        let attribute = MockFunctionAttribute {
+            int_params: vec![MockRegister::R1],
+            float_params: vec![MockRegister::F0, MockRegister::F1, MockRegister::F2],
+            vector_params: vec![MockRegister::V0, MockRegister::V1, MockRegister::V2],
+            parameter_register_assignment: ParameterRegisterAssignment::SharedFloatVector,
+            ..Default::default()
+        };
+
+        // v256 doesn't fit any vector register.
+        assert_eq!(
+            get_register_parameters(&function, &attribute),
+            vec![
+                MockRegister::F0,
+                MockRegister::V1,
+                MockRegister::R1,
+                MockRegister::F2
+            ]
+        );
+        assert_eq!(
+            get_spilled_parameters(&function, &attribute),
+            vec![ParameterType::v256]
+        );
+    }
+
+    #[test]
+    fn positional_registers() {
+        let function = create_mock_function(vec![
+            ParameterType::i32,
+            ParameterType::f32,
+            ParameterType::v64,
+            ParameterType::i32,
+            ParameterType::i32,
+        ]);
+        let attribute = MockFunctionAttribute {
+            int_params: vec![
+                MockRegister::R0,
+                MockRegister::R1,
+                MockRegister::R2,
+                MockRegister::R3,
+            ],
+            float_params: vec![MockRegister::F0, MockRegister::F1],
+            vector_params: vec![MockRegister::V0, MockRegister::V1, MockRegister::V2],
+            parameter_register_assignment: ParameterRegisterAssignment::Positional,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_register_parameters(&function, &attribute),
+            vec![
+                MockRegister::R0,
+                MockRegister::F1,
+                MockRegister::V2,
+                MockRegister::R3
+            ]
+        );
+        assert_eq!(
+            get_spilled_parameters(&function, &attribute),
+            vec![ParameterType::i32]
+        );
+    }
 }
@@ -9,21 +9,26 @@ use crate::{
                 RewriteErrorSource::{CustomCode, OriginalCode},
             },
         },
-        hooks::common_hook::CommonHook,
+        hooks::common_hook::{CommonHook, HookPatch},
         jit::compiler::Jit,
         length_disassembler::LengthDisassembler,
         platforms::platform_functions::MUTUAL_EXCLUSOR,
-        rewriter::code_rewriter::CodeRewriter,
-        settings::assembly_hook_settings::{AsmHookBehaviour, AssemblyHookSettings},
+        rewriter::{code_rewriter::CodeRewriter, foreign_hook::ForeignHook},
+        settings::assembly_hook_settings::{
+            AsmHookBehaviour, AssemblyHookSettings, BranchIntoStolenRegionBehaviour,
+            ForeignHookBehaviour,
+        },
         traits::register_info::RegisterInfo,
     },
     helpers::{
         atomic_write_masked::MAX_ATOMIC_WRITE_BYTES, jit_jump_operation::create_jump_operation,
-        overwrite_code::overwrite_code,
+        overwrite_code::overwrite_code, read_code::try_read_code,
+        relative_branch_range_check::can_direct_branch,
     },
     internal::{
         stub_builder::{
-            create_hook_stub_buffer, create_stub, get_relocated_code_length, new_rewrite_error,
+            create_hook_stub_buffer, create_stub, get_landing_pad, get_relocated_code_length,
+            new_rewrite_error,
         },
         stub_builder_settings::{HookBuilderSettings, HookBuilderSettingsMixin},
     },
@@ -31,7 +36,7 @@ use crate::{
 use alloc::vec::Vec;
 use alloca::with_alloca;
 use core::{
-    cmp::max,
+    cmp::{max, min},
     marker::PhantomData,
     mem::{transmute, MaybeUninit},
 };
@@ -105,28 +110,95 @@ pub unsafe fn create_assembly_hook<
     // library instances, which is a-ok for Reloaded3.
     let _guard = MUTUAL_EXCLUSOR.lock();
 
+    let (hook_address, landing_pad_length, foreign_hook) =
+        find_hook_address::<TRegister, TRewriter>(settings);
+    let settings = AssemblyHookSettings {
+        hook_address,
+        max_permitted_bytes: settings
+            .max_permitted_bytes
+            .saturating_sub(landing_pad_length),
+        ..*settings
+    };
+
+    create_assembly_hook_at::<TJit, TRegister, TDisassembler, TRewriter, TBuffer, TBufferFactory>(
+        settings,
+        foreign_hook,
+    )
+}
+
+/// Creates an assembly hook at [`AssemblyHookSettings::hook_address`], after any landing pad and
+/// foreign hook handling has been resolved by [`create_assembly_hook`].
+///
+/// # Parameters
+/// - `settings`: Settings of the hook, with the final hook address.
+/// - `foreign_hook`: Foreign hook at the hook address to chain in front of, if any.
+#[allow(clippy::type_complexity)]
+unsafe fn create_assembly_hook_at<
+    TJit: Jit<TRegister>,
+    TRegister: RegisterInfo + Clone + Default + Copy,
+    TDisassembler: LengthDisassembler,
+    TRewriter: CodeRewriter<TRegister>,
+    TBuffer: Buffer,
+    TBufferFactory: BufferFactory<TBuffer>,
+>(
+    mut settings: AssemblyHookSettings<TRegister>,
+    foreign_hook: Option<ForeignHook>,
+) -> Result<CommonHook<TBuffer, TJit, TRegister, TBufferFactory>, AssemblyHookError<TRegister>> {
     // Length of the original code to be hooked.
-    let orig_code_lengths = get_relocated_code_length::<TDisassembler, TRewriter, TRegister>(
-        settings.hook_address,
-        settings.max_permitted_bytes,
-    );
+    // A foreign hook is replaced by a jump to its target rather than relocated, and nothing after
+    // it is overwritten, as the foreign library may jump back to the code right after it.
+    let (mut max_orig_code_length, mut orig_code_length) = match foreign_hook {
+        Some(hook) => (
+            TJit::max_branch_bytes() as usize,
+            min(hook.length, settings.max_permitted_bytes),
+        ),
+        None => get_relocated_code_length::<TDisassembler, TRewriter, TRegister>(
+            settings.hook_address,
+            settings.max_permitted_bytes,
+        ),
+    };
 
-    let orig_code_length = orig_code_lengths.1;
-    let max_orig_code_length = orig_code_lengths.0;
+    // Handle nearby code which branches into the middle of the code we're about to overwrite.
+    // Not needed for foreign hooks, as we don't overwrite more than the foreign library did.
+    if settings.branch_scan_length > 0 && foreign_hook.is_none() {
+        while let Some((source, target)) = TDisassembler::find_branch_into_stolen_region(
+            settings.hook_address,
+            orig_code_length,
+            settings.branch_scan_length,
+        ) {
+            let skipped = target - settings.hook_address;
+            if settings.branch_into_stolen_region_behaviour == BranchIntoStolenRegionBehaviour::Fail
+                || skipped >= settings.max_permitted_bytes
+            {
+                return Err(HookBuilderError::BranchIntoStolenRegion(source, target).into());
+            }
+
+            // A branch target is an instruction boundary, and branching to the start of the
+            // overwritten code is fine.
+            settings.hook_address = target;
+            settings.max_permitted_bytes -= skipped;
+            (max_orig_code_length, orig_code_length) =
+                get_relocated_code_length::<TDisassembler, TRewriter, TRegister>(
+                    settings.hook_address,
+                    settings.max_permitted_bytes,
+                );
+        }
+    }
+    let settings = &settings;
 
     // Max possible lengths of custom (hook) code and original code
     // When placed inside the stub.
     let stub_orig_max_len = max_orig_code_length + TJit::max_branch_bytes() as usize;
-    let stub_hook_max_len = hookfunction_max_len::<TDisassembler, TRewriter, TRegister, TJit>(
-        settings,
-        max_orig_code_length,
-    );
+    let stub_hook_max_len =
+        hookfunction_max_len::<TRewriter, TRegister, TJit>(settings, max_orig_code_length)?;
 
     // Setup the stub builder.
+    let landing_pad_length = get_landing_pad::<TJit, TRegister>().len();
     let max_swap_length = max(stub_hook_max_len, stub_orig_max_len);
     let max_buf_length = max_swap_length
         + stub_hook_max_len
         + stub_orig_max_len
+        + (landing_pad_length * 3)
         + (MAX_ATOMIC_WRITE_BYTES as usize - 1);
 
     // Get stub buffer we will be using.
@@ -137,10 +209,38 @@ pub unsafe fn create_assembly_hook<
 
     let buf_addr = alloc.buf.get_address() as usize;
 
+    // Make jump to new buffer, the stub is placed at its start.
+    let mut code = Vec::<u8>::with_capacity(orig_code_length);
+    create_jump_operation::<TRegister, TJit, TBufferFactory, TBuffer>(
+        settings.hook_address,
+        alloc.can_relative_jump,
+        buf_addr,
+        settings.scratch_register,
+        &mut code,
+    )
+    .map_err(|e| AssemblyHookError::JitError(e))?;
+
+    // Our jump doesn't fit in place of the foreign hook (e.g. a 2 byte `jmp rel8`, or stub out of
+    // relative jump range), so relocate the foreign hook like any other code instead.
+    // Checked before building the stub, such that nothing is written to the buffer.
+    if foreign_hook.is_some() && code.len() > orig_code_length {
+        return create_assembly_hook_at::<
+            TJit,
+            TRegister,
+            TDisassembler,
+            TRewriter,
+            TBuffer,
+            TBufferFactory,
+        >(*settings, None);
+    }
+
+    // Copy of the original code, which may live in another process.
+    let orig_code = try_read_code(settings.hook_address, orig_code_length)?;
     let mixin: &mut dyn HookBuilderSettingsMixin<TRegister> =
         &mut AssemblyHookMixin::<TRegister, TJit, TBuffer, TRewriter, TBufferFactory>::new(
-            orig_code_length,
-            settings.hook_address + orig_code_length,
+            &orig_code,
+            foreign_hook.map(|hook| hook.target),
+            settings.hook_address + foreign_hook.map_or(orig_code_length, |hook| hook.length),
             alloc.can_relative_jump,
             settings,
         );
@@ -151,18 +251,8 @@ pub unsafe fn create_assembly_hook<
         settings.auto_activate,
     );
 
-    let stub = create_stub::<TRegister, TBuffer>(&mut builder_settings, &mut alloc, mixin)?;
-
-    // Make jump to new buffer
-    let mut code = Vec::<u8>::with_capacity(orig_code_length);
-    create_jump_operation::<TRegister, TJit, TBufferFactory, TBuffer>(
-        settings.hook_address,
-        alloc.can_relative_jump,
-        stub.stub,
-        settings.scratch_register,
-        &mut code,
-    )
-    .map_err(|e| AssemblyHookError::JitError(e))?;
+    let stub = create_stub::<TJit, TRegister, TBuffer>(&mut builder_settings, &mut alloc, mixin)?;
+    let stub_len = alloc.buf.get_address() as usize - buf_addr;
 
     // Bail out if the jump to buffer is greater than expected.
     // This path is considered 'rare' and should never be thrown, if it is thrown, mod author should
@@ -176,7 +266,7 @@ pub unsafe fn create_assembly_hook<
     }
 
     // Write jump to custom code.
-    overwrite_code(settings.hook_address, &code);
+    overwrite_code(settings.hook_address, &code)?;
 
     // Now be a good citizen and add nops to the end of our jump.
     // This will ensure we don't leave invalid instructions.
@@ -187,12 +277,14 @@ pub unsafe fn create_assembly_hook<
             |nops: &mut [MaybeUninit<u8>]| {
                 let slice = unsafe { transmute::<&mut [MaybeUninit<u8>], &mut [u8]>(nops) };
                 TJit::fill_nops(slice);
-                overwrite_code(settings.hook_address + code.len(), slice);
+                overwrite_code(settings.hook_address + code.len(), slice)
             },
-        );
+        )?;
     }
 
-    Ok(CommonHook::new(stub.props, buf_addr))
+    let hook_code = try_read_code(settings.hook_address, orig_code_length)?;
+    let patch = HookPatch::new(settings.hook_address, orig_code, hook_code, stub_len);
+    Ok(CommonHook::new(stub.props, buf_addr, patch))
 }
 
 /// Mixin that provides the 'Assembly Hook' specific functionality for [`HookBuilderSettings`].
@@ -205,8 +297,12 @@ pub struct AssemblyHookMixin<
     TRewriter: CodeRewriter<TRegister>,
     TBufferFactory: BufferFactory<TBuffer>,
 > {
-    /// Size of the original code.
-    orig_code_length: usize,
+    /// Copy of the original code.
+    orig_code: &'a [u8],
+
+    /// Target of the foreign hook at the hook address, if any.
+    /// If set, a jump to this address is emitted in place of the original code.
+    foreign_hook_target: Option<usize>,
 
     /// Address of where the generated code should 'jump back' to.
     jump_back_address: usize,
@@ -224,6 +320,53 @@ pub struct AssemblyHookMixin<
     _tb: PhantomData<TBuffer>,
 }
 
+impl<
+        'a,
+        TRegister: Clone + RegisterInfo + Default + Copy,
+        TBuffer: Buffer,
+        TRewriter: CodeRewriter<TRegister>,
+        TJit: Jit<TRegister>,
+        TBufferFactory: BufferFactory<TBuffer>,
+    > AssemblyHookMixin<'a, TRegister, TJit, TBuffer, TRewriter, TBufferFactory>
+{
+    /// Appends the original code, relocated to the end of `code`, which starts at `address`.
+    /// A foreign hook is replaced with a jump to its target.
+    unsafe fn append_orig_code(
+        &self,
+        address: usize,
+        code: &mut Vec<u8>,
+    ) -> Result<(), HookBuilderError<TRegister>> {
+        let pc = address + code.len();
+        if let Some(target) = self.foreign_hook_target {
+            let can_relative_jump = can_direct_branch(
+                pc,
+                target,
+                TJit::max_standard_relative_call_distance(),
+                TJit::standard_relative_call_bytes(),
+            );
+
+            return create_jump_operation::<TRegister, TJit, TBufferFactory, TBuffer>(
+                pc,
+                can_relative_jump,
+                target,
+                self.settings.scratch_register,
+                code,
+            )
+            .map_err(|e| HookBuilderError::JitError(e));
+        }
+
+        TRewriter::rewrite_code_with_buffer(
+            self.orig_code.as_ptr(),
+            self.orig_code.len(),
+            self.settings.hook_address,
+            pc,
+            self.settings.scratch_register,
+            code,
+        )
+        .map_err(|e| new_rewrite_error(OriginalCode, self.settings.hook_address, pc, e))
+    }
+}
+
 impl<
         'a,
         TRegister: Clone + RegisterInfo + Default + Copy,
@@ -240,15 +383,7 @@ impl<
         code: &mut Vec<u8>,
     ) -> Result<(), HookBuilderError<TRegister>> {
         unsafe {
-            TRewriter::rewrite_code_with_buffer(
-                self.settings.hook_address as *const u8,
-                self.orig_code_length,
-                self.settings.hook_address,
-                address,
-                self.settings.scratch_register,
-                code,
-            )
-            .map_err(|e| new_rewrite_error(OriginalCode, self.settings.hook_address, address, e))?;
+            self.append_orig_code(address, code)?;
 
             create_jump_operation::<TRegister, TJit, TBufferFactory, TBuffer>(
                 address.wrapping_add(code.len()),
@@ -273,17 +408,7 @@ impl<
             // hook is 'second'
             if self.settings.behaviour == AsmHookBehaviour::ExecuteAfter {
                 // Include original code first
-                TRewriter::rewrite_code_with_buffer(
-                    self.settings.hook_address as *const u8,
-                    self.orig_code_length,
-                    self.settings.hook_address,
-                    address,
-                    self.settings.scratch_register,
-                    code,
-                )
-                .map_err(|e| {
-                    new_rewrite_error(OriginalCode, self.settings.hook_address, address, e)
-                })?;
+                self.append_orig_code(address, code)?;
             }
 
             // Include hook code
@@ -302,17 +427,7 @@ impl<
             // Include original code after if required
             // hook is 'first'
             if self.settings.behaviour == AsmHookBehaviour::ExecuteFirst {
-                TRewriter::rewrite_code_with_buffer(
-                    self.settings.hook_address as *const u8,
-                    self.orig_code_length,
-                    self.settings.hook_address,
-                    address + code.len(),
-                    self.settings.scratch_register,
-                    code,
-                )
-                .map_err(|e| {
-                    new_rewrite_error(OriginalCode, self.settings.hook_address, address, e)
-                })?;
+                self.append_orig_code(address, code)?;
             }
 
             // Add jump back to the end of the sequence
@@ -330,6 +445,51 @@ impl<
     }
 }
 
+/// Maximum number of foreign hooks followed with [`ForeignHookBehaviour::HookContinuation`],
+/// guards against foreign hooks which jump to each other.
+const MAX_FOREIGN_HOOK_CHAIN_LENGTH: usize = 8;
+
+/// Finds the address the hook should be placed at.
+///
+/// Any landing pad at the hook address is left in place, and the code after it hooked instead.
+/// Otherwise the code could no longer be reached via indirect branches, if branch target
+/// enforcement (e.g. BTI on ARM64) is active.
+///
+/// With [`ForeignHookBehaviour::HookContinuation`], hooks placed by other libraries are followed.
+///
+/// # Returns
+///
+/// The address to hook, length of the landing pad before it, and the foreign hook at the address (if any).
+unsafe fn find_hook_address<TRegister, TRewriter: CodeRewriter<TRegister>>(
+    settings: &AssemblyHookSettings<TRegister>,
+) -> (usize, usize, Option<ForeignHook>)
+where
+    TRegister: Clone + Copy,
+{
+    let mut address = settings.hook_address;
+    let mut chain_length = 0;
+    loop {
+        let landing_pad_length = TRewriter::landing_pad_length(address);
+        let foreign_hook = TRewriter::find_foreign_hook(address + landing_pad_length);
+        match foreign_hook {
+            Some(hook)
+                if settings.foreign_hook_behaviour == ForeignHookBehaviour::HookContinuation
+                    && chain_length < MAX_FOREIGN_HOOK_CHAIN_LENGTH =>
+            {
+                address = hook.target;
+                chain_length += 1;
+            }
+            _ => {
+                return (
+                    address + landing_pad_length,
+                    landing_pad_length,
+                    foreign_hook,
+                )
+            }
+        }
+    }
+}
+
 /// Retrieves the max possible ASM length for the hook code (i.e. 'hook enabled')
 /// once emplaced in the 'Hook Function'.
 ///
@@ -338,27 +498,67 @@ impl<
 /// # Parameters
 /// - `settings`: The settings for the assembly hook.
 /// - `max_orig_code_length`: The maximum possible length of the original code.
-fn hookfunction_max_len<TDisassembler, TRewriter, TRegister: Clone, TJit>(
+fn hookfunction_max_len<TRewriter, TRegister: Clone, TJit>(
     settings: &AssemblyHookSettings<TRegister>,
     max_orig_code_length: usize,
-) -> usize
+) -> Result<usize, HookBuilderError<TRegister>>
 where
     TRegister: RegisterInfo + Copy,
-    TDisassembler: LengthDisassembler,
     TRewriter: CodeRewriter<TRegister>,
     TJit: Jit<TRegister>,
 {
     // New code length + extra max possible length + jmp back to original code
-    let hook_code_max_length = get_relocated_code_length::<TDisassembler, TRewriter, TRegister>(
-        settings.asm_code_ptr,
-        settings.asm_code_len,
-    )
-    .0;
+    let result = relocated_custom_code_length::<TRewriter, TRegister>(settings)?
+        + TJit::max_branch_bytes() as usize;
 
-    let result = hook_code_max_length + TJit::max_branch_bytes() as usize;
     if settings.behaviour == AsmHookBehaviour::DoNotExecuteOriginal {
-        result
+        Ok(result)
     } else {
-        result + max_orig_code_length
+        Ok(result + max_orig_code_length)
     }
 }
+
+/// Retrieves the length of the custom code once relocated to the 'Hook Function'.
+///
+/// The custom code lives in the current process, so it is relocated from `asm_code_ptr` rather than
+/// disassembled through the registered [`MemoryAccessor`], which may read another process.
+///
+/// The stub may end up anywhere, so the code is relocated half the address space away, where no
+/// relative branch can be kept as is. If that fails (e.g. due to a missing scratch register), the
+/// stub can only work near the hook, so the code is relocated to the hook address instead.
+///
+/// [`MemoryAccessor`]: crate::api::platforms::platform_abstractions::MemoryAccessor
+fn relocated_custom_code_length<TRewriter, TRegister>(
+    settings: &AssemblyHookSettings<TRegister>,
+) -> Result<usize, HookBuilderError<TRegister>>
+where
+    TRegister: RegisterInfo + Clone + Copy,
+    TRewriter: CodeRewriter<TRegister>,
+{
+    let far_address = settings.asm_code_address ^ (1 << (usize::BITS - 1));
+    let mut code = Vec::with_capacity(settings.asm_code_len);
+    let rewrite = |new_address: usize, code: &mut Vec<u8>| unsafe {
+        code.clear();
+        TRewriter::rewrite_code_with_buffer(
+            settings.asm_code_ptr as *const u8,
+            settings.asm_code_len,
+            settings.asm_code_address,
+            new_address,
+            settings.scratch_register,
+            code,
+        )
+    };
+
+    rewrite(far_address, &mut code)
+        .or_else(|_| rewrite(settings.hook_address, &mut code))
+        .map_err(|e| {
+            new_rewrite_error(
+                CustomCode,
+                settings.asm_code_address,
+                settings.hook_address,
+                e,
+            )
+        })?;
+
+    Ok(code.len())
+}
@@ -7,7 +7,7 @@ use crate::{
         errors::function_hook_error::FunctionHookError,
         function_info::FunctionInfo,
         hooks::{
-            common_hook::CommonHook,
+            common_hook::{CommonHook, HookPatch},
             stub::mixins::{assembly_mixin::AssemblyMixin, stub_wrapper_mixin::StubWrapperMixin},
         },
         jit::{
@@ -16,26 +16,28 @@ use crate::{
             operation_aliases::{CallRel, JumpAbs, JumpRel},
         },
         length_disassembler::LengthDisassembler,
-        platforms::platform_functions::MUTUAL_EXCLUSOR,
+        platforms::platform_functions::{get_platform_functions, MUTUAL_EXCLUSOR},
         rewriter::code_rewriter::CodeRewriter,
         settings::function_hook_settings::FunctionHookSettings,
         traits::register_info::RegisterInfo,
         wrapper_instruction_generator::{
             generate_wrapper_instructions, new_wrapper_instruction_generator_options,
-            MAX_WRAPPER_LENGTH,
+            CallerFilterOptions, ReentrancyGuardOptions, MAX_WRAPPER_LENGTH,
         },
     },
     helpers::{
         atomic_write_masked::MAX_ATOMIC_WRITE_BYTES, overwrite_code::overwrite_code,
-        relative_branch_range_check::can_direct_branch,
+        read_code::try_read_code, relative_branch_range_check::can_direct_branch,
     },
     internal::{
-        stub_builder::{create_hook_stub_buffer, create_stub},
+        stub_builder::{create_hook_stub_buffer, create_stub, get_landing_pad},
         stub_builder_settings::{HookBuilderSettings, HookBuilderSettingsMixin},
     },
 };
+use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::hash::Hash;
+use core::sync::atomic::AtomicUsize;
 use core::{fmt::Debug, slice::from_raw_parts};
 
 /// Creates a 'fast branch hook'
@@ -146,17 +148,41 @@ pub unsafe fn create_branch_hook_with_callback<
         // Get stub buffer we will be using
         let mut alloc = create_hook_stub_buffer::<TJit, TRegister, TBuffer, TBufferFactory>(
             core_settings.hook_address,
-            (MAX_WRAPPER_LENGTH * 2) + MAX_BRANCH_LENGTH + (MAX_ATOMIC_WRITE_BYTES as usize - 1),
+            (MAX_WRAPPER_LENGTH * 2)
+                + MAX_BRANCH_LENGTH
+                + (get_landing_pad::<TJit, TRegister>().len() * 3)
+                + (MAX_ATOMIC_WRITE_BYTES as usize - 1),
         );
         debug_assert!(alloc.can_relative_jump);
 
+        // Never freed, as threads may still be leaving the wrapper after the hook is dropped.
+        let in_flight_counter: Option<&'static AtomicUsize> = settings
+            .track_in_flight_calls
+            .then(|| &*Box::leak(Box::new(AtomicUsize::new(0))));
+
         // Setup the mixin.
-        let options = new_wrapper_instruction_generator_options::<TFunctionInfo, TRegister, TJit>(
+        let mut options = new_wrapper_instruction_generator_options::<TFunctionInfo, TRegister, TJit>(
             false,
             core_settings.new_target,
             &settings.function_info,
             settings.injected_parameter,
         );
+        options.in_flight_counter = in_flight_counter.map(|x| x as *const AtomicUsize as usize);
+        let thread_local_flag = settings
+            .reentrancy_guard
+            .then(|| {
+                get_platform_functions()
+                    .allocate_thread_local_flag()
+                    .ok_or(FunctionHookError::ThreadLocalFlagUnavailable)
+            })
+            .transpose()?;
+
+        options.reentrancy_guard = thread_local_flag
+            .map(|tls_offset| ReentrancyGuardOptions::new(tls_offset, target.target_address));
+
+        options.caller_filter = settings
+            .caller_filter
+            .map(|filter| CallerFilterOptions::new(filter.table_pointer(), target.target_address));
 
         let wrap_instructions =
             generate_wrapper_instructions(settings.conv_target, settings.conv_source, &options)?;
@@ -176,7 +202,9 @@ pub unsafe fn create_branch_hook_with_callback<
         );
 
         // Create the stub
-        let stub = create_stub::<TRegister, TBuffer>(&mut builder_settings, &mut alloc, mixin)?;
+        let stub =
+            create_stub::<TJit, TRegister, TBuffer>(&mut builder_settings, &mut alloc, mixin)?;
+        let stub_len = alloc.buf.get_address() as usize - stub.stub;
 
         // Lastly, write the branch to the buffer.
         let mut pc = core_settings.hook_address;
@@ -186,15 +214,21 @@ pub unsafe fn create_branch_hook_with_callback<
             TJit::encode_jump(&JumpRel::new(stub.stub), &mut pc, &mut code)?;
         }
 
-        overwrite_code(core_settings.hook_address, &code);
+        let orig_code = try_read_code(core_settings.hook_address, code.len())?;
+        overwrite_code(core_settings.hook_address, &code)?;
+        let patch = HookPatch::new(core_settings.hook_address, orig_code, code, stub_len);
 
         // And return the good stuff.
-        Ok(CommonHook::new(stub.props, stub.stub))
+        Ok(CommonHook::new(stub.props, stub.stub, patch)
+            .with_in_flight_counter(in_flight_counter)
+            .with_thread_local_flag(thread_local_flag))
     } else {
         // Get stub buffer we will be using
         let mut alloc = create_hook_stub_buffer::<TJit, TRegister, TBuffer, TBufferFactory>(
             core_settings.hook_address,
-            (MAX_BRANCH_LENGTH * 3) + (MAX_ATOMIC_WRITE_BYTES as usize - 1),
+            (MAX_BRANCH_LENGTH * 3)
+                + (get_landing_pad::<TJit, TRegister>().len() * 3)
+                + (MAX_ATOMIC_WRITE_BYTES as usize - 1),
         );
         debug_assert!(alloc.can_relative_jump);
 
@@ -239,7 +273,9 @@ pub unsafe fn create_branch_hook_with_callback<
         );
 
         // Create the stub
-        let stub = create_stub::<TRegister, TBuffer>(&mut builder_settings, &mut alloc, mixin)?;
+        let stub =
+            create_stub::<TJit, TRegister, TBuffer>(&mut builder_settings, &mut alloc, mixin)?;
+        let stub_len = alloc.buf.get_address() as usize - stub.stub;
 
         // Lastly, write the branch to the buffer.
         code.clear();
@@ -250,9 +286,11 @@ pub unsafe fn create_branch_hook_with_callback<
             TJit::encode_jump(&JumpRel::new(stub.stub), &mut pc, &mut code)?;
         }
 
-        overwrite_code(core_settings.hook_address, &code);
+        let orig_code = try_read_code(core_settings.hook_address, code.len())?;
+        overwrite_code(core_settings.hook_address, &code)?;
+        let patch = HookPatch::new(core_settings.hook_address, orig_code, code, stub_len);
 
         // And return the good stuff.
-        Ok(CommonHook::new(stub.props, buf_ptr))
+        Ok(CommonHook::new(stub.props, buf_ptr, patch))
     }
 }
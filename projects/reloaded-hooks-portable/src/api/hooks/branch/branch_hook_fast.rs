@@ -15,7 +15,7 @@ use crate::{
         traits::register_info::RegisterInfo,
     },
     helpers::{overwrite_code::overwrite_code, relative_branch_range_check::can_direct_branch},
-    internal::stub_builder::create_hook_stub_buffer,
+    internal::stub_builder::{create_hook_stub_buffer, get_landing_pad},
 };
 use alloc::vec::Vec;
 use core::fmt::Debug;
@@ -124,28 +124,30 @@ pub unsafe fn create_branch_hook_fast_with_callback<
             TJit::encode_jump(&JumpRel::new(settings.new_target), &mut pc, &mut code)?;
         }
 
-        overwrite_code(settings.hook_address, &code);
+        overwrite_code(settings.hook_address, &code)?;
         return Ok(());
     }
 
     // We cannot branch directly to the target. We need to use a stub.
 
     // Get intermediary buffer we will be using
+    let landing_pad = get_landing_pad::<TJit, TRegister>();
     let mut alloc = create_hook_stub_buffer::<TJit, TRegister, TBuffer, TBufferFactory>(
         settings.hook_address,
-        MAX_BRANCH_LENGTH,
+        MAX_BRANCH_LENGTH + landing_pad.len(),
     );
 
     debug_assert!(alloc.can_relative_jump);
     let buf_ptr = alloc.buf.get_address() as usize;
     let is_direct_branch = can_direct_branch(
-        buf_ptr,
+        buf_ptr + landing_pad.len(),
         settings.new_target,
         TJit::max_standard_relative_call_distance(),
         TJit::standard_relative_call_bytes(),
     );
 
-    let mut pc = buf_ptr;
+    code.extend_from_slice(landing_pad);
+    let mut pc = buf_ptr + landing_pad.len();
     if is_direct_branch {
         TJit::encode_jump(&JumpRel::new(settings.new_target), &mut pc, &mut code)?;
         TBuffer::overwrite(buf_ptr, &code);
@@ -159,7 +161,7 @@ pub unsafe fn create_branch_hook_fast_with_callback<
         } else {
             TJit::encode_jump(&JumpRel::new(buf_ptr), &mut pc, &mut code)?;
         }
-        overwrite_code(settings.hook_address, &code);
+        overwrite_code(settings.hook_address, &code)?;
 
         return Ok(());
     }
@@ -175,7 +177,7 @@ pub unsafe fn create_branch_hook_fast_with_callback<
         &mut code,
     )?;
 
-    debug_assert!(code.len() <= MAX_BRANCH_LENGTH);
+    debug_assert!(code.len() <= MAX_BRANCH_LENGTH + landing_pad.len());
     TBuffer::overwrite(buf_ptr, &code);
     alloc.buf.advance(code.len());
 
@@ -187,6 +189,6 @@ pub unsafe fn create_branch_hook_fast_with_callback<
     } else {
         TJit::encode_jump(&JumpRel::new(buf_ptr), &mut pc, &mut code)?;
     }
-    overwrite_code(settings.hook_address, &code);
+    overwrite_code(settings.hook_address, &code)?;
     Ok(())
 }
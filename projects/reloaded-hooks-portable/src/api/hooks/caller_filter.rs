@@ -0,0 +1,150 @@
+extern crate alloc;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::ops::Range;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// A set of address ranges, used to only run a hook function for calls made from code
+/// inside of them.
+///
+/// The generated wrapper compares the return address of the caller against each range; calls from
+/// anywhere else go straight to the original function. See
+/// [`FunctionHookSettings::caller_filter`].
+///
+/// # Remarks
+///
+/// The ranges are stored in a table, which the wrapper reads through a pointer. Changing the ranges
+/// with [`CallerFilter::set_ranges`] atomically swaps that pointer, so it is safe to do while the
+/// hook is being called from other threads.
+///
+/// Because other threads may still be reading a table after it is replaced, old tables are never
+/// freed. Avoid changing the ranges in a hot loop.
+///
+/// [`FunctionHookSettings::caller_filter`]: crate::api::settings::function_hook_settings::FunctionHookSettings::caller_filter
+pub struct CallerFilter {
+    /// Pointer to pairs of `start, end` addresses, ending with a pair whose `end` is 0.
+    table: AtomicPtr<usize>,
+}
+
+impl CallerFilter {
+    /// Creates a new filter with the given address ranges.
+    ///
+    /// # Parameters
+    ///
+    /// - `ranges`: Address ranges of the code whose calls should be hooked. Empty ranges are ignored.
+    ///
+    /// # Returns
+    ///
+    /// A filter which lives for the rest of the program, since generated code refers to it.
+    pub fn new(ranges: &[Range<usize>]) -> &'static CallerFilter {
+        Box::leak(Box::new(CallerFilter {
+            table: AtomicPtr::new(create_table(ranges)),
+        }))
+    }
+
+    /// Replaces the address ranges of the filter.
+    /// Calls made after this returns use the new ranges.
+    ///
+    /// # Parameters
+    ///
+    /// - `ranges`: Address ranges of the code whose calls should be hooked. Empty ranges are ignored.
+    pub fn set_ranges(&self, ranges: &[Range<usize>]) {
+        // The old table is leaked, as it may still be in use by another thread.
+        self.table.swap(create_table(ranges), Ordering::AcqRel);
+    }
+
+    /// Returns the current address ranges of the filter.
+    pub fn get_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut entry = self.table.load(Ordering::Acquire) as *const usize;
+        unsafe {
+            while *entry.add(1) != 0 {
+                ranges.push(*entry..*entry.add(1));
+                entry = entry.add(2);
+            }
+        }
+
+        ranges
+    }
+
+    /// Returns `true` if calls returning to `return_address` pass the filter.
+    pub fn contains(&self, return_address: usize) -> bool {
+        self.get_ranges()
+            .iter()
+            .any(|range| range.contains(&return_address))
+    }
+
+    /// Returns the address of the pointer to the table of ranges, which is read by generated code.
+    /// See [`FilterCallerOperation`] for the table layout.
+    ///
+    /// [`FilterCallerOperation`]: crate::api::jit::filter_caller_operation::FilterCallerOperation
+    pub fn table_pointer(&self) -> usize {
+        &self.table as *const AtomicPtr<usize> as usize
+    }
+}
+
+impl Debug for CallerFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CallerFilter")
+            .field("ranges", &self.get_ranges())
+            .finish()
+    }
+}
+
+// Filters are compared by identity, since their ranges can change at any time.
+impl PartialEq for CallerFilter {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self, other)
+    }
+}
+
+impl Eq for CallerFilter {}
+
+/// Creates a table of ranges in the format read by generated code. Never freed.
+fn create_table(ranges: &[Range<usize>]) -> *mut usize {
+    let mut table = Vec::with_capacity((ranges.len() + 1) * 2);
+    for range in ranges.iter().filter(|range| !range.is_empty()) {
+        table.push(range.start);
+        table.push(range.end);
+    }
+
+    table.push(0);
+    table.push(0);
+    Box::leak(table.into_boxed_slice()).as_mut_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_has_terminated_range_pairs() {
+        let filter = CallerFilter::new(&[0x1000..0x2000, 0x5000..0x5000, 0x8000..0x8100]);
+        let table = unsafe { *(filter.table_pointer() as *const *const usize) };
+        let entries = unsafe { core::slice::from_raw_parts(table, 6) };
+
+        // Empty range is skipped.
+        assert_eq!(entries, &[0x1000, 0x2000, 0x8000, 0x8100, 0, 0]);
+        assert_eq!(filter.get_ranges(), vec![0x1000..0x2000, 0x8000..0x8100]);
+    }
+
+    #[test]
+    fn set_ranges_swaps_table() {
+        let filter = CallerFilter::new(&[0x1000..0x2000, 0x2800..0x2900]);
+        let old_table = unsafe { *(filter.table_pointer() as *const usize) };
+        assert!(filter.contains(0x1000));
+        assert!(!filter.contains(0x2000));
+        assert!(filter.contains(0x2800));
+
+        filter.set_ranges(&[0x3000..0x4000, 0x5000..0x5000]);
+        assert_ne!(old_table, unsafe {
+            *(filter.table_pointer() as *const usize)
+        });
+        assert!(!filter.contains(0x1000));
+        assert!(filter.contains(0x3FFF));
+
+        filter.set_ranges(&[]);
+        assert!(filter.get_ranges().is_empty());
+    }
+}
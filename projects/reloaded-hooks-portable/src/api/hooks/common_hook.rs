@@ -2,29 +2,24 @@ extern crate alloc;
 use crate::api::{
     buffers::buffer_abstractions::{Buffer, BufferFactory},
     jit::compiler::Jit,
+    platforms::platform_functions::{
+        get_monotonic_time_ms, get_platform_functions, MUTUAL_EXCLUSOR,
+    },
     traits::register_info::RegisterInfo,
 };
+use crate::helpers::{overwrite_code::overwrite_code, read_code::try_read_code};
+use alloc::vec::Vec;
+use core::hint::spin_loop;
 use core::marker::PhantomData;
 use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use derive_new::new;
 
-#[cfg(any(
-    target_arch = "aarch64",
-    target_arch = "arm",
-    target_arch = "mips",
-    target_arch = "powerpc",
-    target_arch = "riscv32",
-    target_arch = "riscv64"
-))]
+#[cfg(any(target_arch = "aarch64", target_arch = "mips", target_arch = "powerpc"))]
 use super::stub::stub_props_4byteins::*;
 
-#[cfg(not(any(
-    target_arch = "aarch64",
-    target_arch = "arm",
-    target_arch = "mips",
-    target_arch = "powerpc",
-    target_arch = "riscv32",
-    target_arch = "riscv64"
-)))]
+#[cfg(not(any(target_arch = "aarch64", target_arch = "mips", target_arch = "powerpc")))]
 use super::stub::stub_props_other::*;
 
 /// Represents an assembly hook.
@@ -43,7 +38,17 @@ where
     /// Address of 'props' structure
     props: NonNull<StubPackedProps>, // 4/8
 
-    // Struct size: 8/16 bytes.
+    /// Code written at the hook address, used to uninstall the hook on drop.
+    patch: HookPatch,
+
+    /// Whether the hook is uninstalled when dropped.
+    uninstall_on_drop: bool,
+
+    /// Number of threads executing the hook function, if tracked by the wrapper.
+    in_flight: Option<&'static AtomicUsize>,
+
+    /// Thread local flag used by the wrapper's re-entrancy guard, freed once the hook is uninstalled.
+    thread_local_flag: Option<isize>,
 
     // Dummy type parameters for Rust compiler to comply.
     _unused_buf: PhantomData<TBuffer>,
@@ -59,10 +64,14 @@ where
     TRegister: RegisterInfo + Clone + Default + Copy,
     TBufferFactory: BufferFactory<TBuffer>,
 {
-    pub fn new(props: NonNull<StubPackedProps>, stub_address: usize) -> Self {
+    pub fn new(props: NonNull<StubPackedProps>, stub_address: usize, patch: HookPatch) -> Self {
         Self {
             props,
             stub_address,
+            patch,
+            uninstall_on_drop: false,
+            in_flight: None,
+            thread_local_flag: None,
             _unused_buf: PhantomData,
             _unused_tj: PhantomData,
             _unused_tr: PhantomData,
@@ -96,6 +105,88 @@ where
     pub fn get_is_enabled(&self) -> bool {
         unsafe { self.props.as_ref().is_enabled() }
     }
+
+    /// Sets whether the hook is uninstalled when dropped.
+    ///
+    /// By default, dropping a hook leaves it installed, and its stub is never freed.
+    /// When enabled, dropping the hook restores the original code and returns the stub's memory to
+    /// the buffer factory (see [`BufferFactory::release`]).
+    ///
+    /// # Parameters
+    ///
+    /// - `uninstall`: Whether to uninstall the hook on drop.
+    pub fn with_uninstall_on_drop(mut self, uninstall: bool) -> Self {
+        self.uninstall_on_drop = uninstall;
+        self
+    }
+
+    /// Sets the counter of threads executing the hook function, which is updated by the wrapper.
+    pub(crate) fn with_in_flight_counter(mut self, counter: Option<&'static AtomicUsize>) -> Self {
+        self.in_flight = counter;
+        self
+    }
+
+    /// Sets the thread local flag used by the wrapper's re-entrancy guard.
+    pub(crate) fn with_thread_local_flag(mut self, flag: Option<isize>) -> Self {
+        self.thread_local_flag = flag;
+        self
+    }
+
+    /// Returns the number of threads currently executing the hook function, if the hook was created
+    /// with [`FunctionHookSettings::track_in_flight_calls`].
+    ///
+    /// Only branch hooks with a wrapper count calls; for all other hooks this returns `None`.
+    ///
+    /// [`FunctionHookSettings::track_in_flight_calls`]: crate::api::settings::function_hook_settings::FunctionHookSettings::track_in_flight_calls
+    pub fn get_in_flight_calls(&self) -> Option<usize> {
+        self.in_flight.map(|x| x.load(Ordering::Acquire))
+    }
+
+    /// Disables the hook, then waits for all threads executing the hook function to return.
+    ///
+    /// Only supported for branch hooks created with [`FunctionHookSettings::track_in_flight_calls`],
+    /// whose wrapper counts the calls. Other hooks (assembly hooks, fast branch hooks, and branch
+    /// hooks without the setting) are only disabled, as there is nothing to wait on.
+    ///
+    /// # Parameters
+    ///
+    /// - `timeout`: The maximum amount of time to wait for.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(true)` if no threads are executing the hook function.
+    /// - `Some(false)` if the timeout elapsed first.
+    /// - `None` if the hook does not count calls.
+    ///
+    /// # Remarks
+    ///
+    /// The counter is incremented by the wrapper, after the caller filter and re-entrancy guard.
+    /// A thread which entered the stub before the hook was disabled, but has not reached the
+    /// increment yet, is not waited for. Allow a short grace period before unloading the code the
+    /// hook pointed to (e.g. via `dlclose`).
+    ///
+    /// Calls which never return to the wrapper (e.g. because the hook function unwinds past it,
+    /// or calls `longjmp`) are counted forever.
+    ///
+    /// On platforms without a monotonic clock, this returns `Some(false)` immediately if any calls are in flight.
+    ///
+    /// [`FunctionHookSettings::track_in_flight_calls`]: crate::api::settings::function_hook_settings::FunctionHookSettings::track_in_flight_calls
+    pub fn disable_and_wait(&self, timeout: Duration) -> Option<bool> {
+        self.disable();
+        let counter = self.in_flight?;
+
+        let start = get_monotonic_time_ms();
+        let timeout_ms = timeout.as_millis() as u64;
+        while counter.load(Ordering::Acquire) != 0 {
+            let now = get_monotonic_time_ms();
+            match (start, now) {
+                (Some(start), Some(now)) if now.saturating_sub(start) < timeout_ms => spin_loop(),
+                _ => return Some(false),
+            }
+        }
+
+        Some(true)
+    }
 }
 
 impl<TBuffer, TJit, TRegister, TBufferFactory> Drop
@@ -106,9 +197,48 @@ where
     TRegister: RegisterInfo + Clone + Default + Copy,
     TBufferFactory: BufferFactory<TBuffer>,
 {
+    /// Frees the hook's state; the hook itself stays installed.
+    ///
+    /// If the hook was created with [`CommonHook::with_uninstall_on_drop`], the hook is instead
+    /// uninstalled, restoring the original code, and the stub's memory is returned to the buffer
+    /// factory, along with the thread local flag of its re-entrancy guard. If another hook has since
+    /// been installed at the same address, that hook still branches to our stub; in which case the
+    /// hook is left in place and neither is reclaimed.
     fn drop(&mut self) {
+        if self.uninstall_on_drop {
+            let _guard = MUTUAL_EXCLUSOR.lock();
+            let patch = &self.patch;
+            let current_code = unsafe { try_read_code(patch.hook_address, patch.hook_code.len()) };
+
+            // If the original code can't be written back, the stub is still in use.
+            if current_code.is_ok_and(|code| code == patch.hook_code)
+                && overwrite_code(patch.hook_address, &patch.orig_code).is_ok()
+            {
+                TBufferFactory::release(self.stub_address, patch.stub_len);
+                if let Some(flag) = self.thread_local_flag {
+                    get_platform_functions().free_thread_local_flag(flag);
+                }
+            }
+        }
+
         unsafe {
             self.props.as_mut().free();
         }
     }
 }
+
+/// Describes the code overwritten at the hook address when a hook was installed.
+#[derive(new)]
+pub struct HookPatch {
+    /// Address of the code that was hooked.
+    pub hook_address: usize,
+
+    /// Code at [`HookPatch::hook_address`] before the hook was installed.
+    pub orig_code: Vec<u8>,
+
+    /// Code at [`HookPatch::hook_address`] after the hook was installed.
+    pub hook_code: Vec<u8>,
+
+    /// Number of bytes written to the buffer for the stub.
+    pub stub_len: usize,
+}
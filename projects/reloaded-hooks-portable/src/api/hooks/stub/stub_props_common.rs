@@ -3,10 +3,11 @@ use crate::{
     api::{
         buffers::buffer_abstractions::{Buffer, BufferFactory},
         jit::compiler::Jit,
+        platforms::platform_functions::get_platform_functions,
     },
     helpers::{
         atomic_write::atomic_swap, atomic_write_masked::atomic_write_masked,
-        jit_jump_operation::create_jump_operation,
+        jit_jump_operation::create_jump_operation, read_code::read_code,
     },
 };
 use alloc::vec::Vec;
@@ -16,24 +17,10 @@ use core::{
     slice::from_raw_parts_mut,
 };
 
-#[cfg(any(
-    target_arch = "aarch64",
-    target_arch = "arm",
-    target_arch = "mips",
-    target_arch = "powerpc",
-    target_arch = "riscv32",
-    target_arch = "riscv64"
-))]
+#[cfg(any(target_arch = "aarch64", target_arch = "mips", target_arch = "powerpc"))]
 use super::stub_props_4byteins::StubPackedProps;
 
-#[cfg(not(any(
-    target_arch = "aarch64",
-    target_arch = "arm",
-    target_arch = "mips",
-    target_arch = "powerpc",
-    target_arch = "riscv32",
-    target_arch = "riscv64"
-)))]
+#[cfg(not(any(target_arch = "aarch64", target_arch = "mips", target_arch = "powerpc")))]
 use super::stub_props_other::StubPackedProps;
 
 /*
@@ -136,8 +123,8 @@ impl StubPackedProps {
             let swap_buffer_copy = swap_buffer_real.to_vec();
 
             // Copy current code into swap buffer
-            let buf_buffer_real = from_raw_parts_mut(stub_address as *mut u8, self.get_swap_size());
-            swap_buffer_real.copy_from_slice(buf_buffer_real);
+            let buf_buffer_real = read_code(stub_address, self.get_swap_size());
+            swap_buffer_real.copy_from_slice(&buf_buffer_real);
 
             // JIT temp branch to hook/orig code.
             let mut vec = Vec::<u8>::with_capacity(8);
@@ -154,7 +141,7 @@ impl StubPackedProps {
             // Write the temp branch first, as per docs
             // This also overwrites some extra code afterwards, but that's a-ok for now.
             unsafe {
-                atomic_write_masked::<TBuffer>(stub_address, branch_opcode, branch_bytes);
+                _ = atomic_write_masked::<TBuffer>(stub_address, branch_opcode, branch_bytes);
             }
 
             // Now write the remaining code
@@ -165,9 +152,12 @@ impl StubPackedProps {
 
             // And now re-insert the code we temp overwrote with the branch
             unsafe {
-                atomic_write_masked::<TBuffer>(stub_address, &swap_buffer_copy, branch_bytes);
+                _ = atomic_write_masked::<TBuffer>(stub_address, &swap_buffer_copy, branch_bytes);
             }
         }
+
+        // Make sure threads on other cores don't keep running the old code.
+        get_platform_functions().synchronize_cores();
     }
 
     /// Frees the memory allocated for this instance using libc's free.
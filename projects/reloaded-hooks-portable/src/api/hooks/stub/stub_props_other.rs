@@ -2,7 +2,8 @@ use bitfield::bitfield;
 
 bitfield! {
     /// Defines the data layout of the Assembly Hook data for architectures
-    /// with variable length instructions.
+    /// with variable length instructions (e.g. x86, or 32-bit ARM and RISC-V where Thumb-2 and
+    /// the compressed extension mix 16-bit and 32-bit instructions).
     pub struct StubPackedProps(u32);
     impl Debug;
 
@@ -0,0 +1,60 @@
+extern crate alloc;
+use core::cell::RefCell;
+
+use alloc::{rc::Rc, vec::Vec};
+use derive_new::new;
+
+/// Represents an operation which atomically adds a value to a pointer sized integer in memory.
+///
+/// # Fields
+///
+/// - `address`: Address of the integer to add to.
+/// - `value`: The value to add. Use a negative value to subtract.
+///
+/// # Example
+///
+/// The generated wrappers use this operation to count the threads currently executing them,
+/// which is done with `lock inc qword ptr [address]` on x64, or with an exclusive load/store loop
+/// on ARM64.
+///
+/// ```
+/// use reloaded_hooks_portable::api::jit::atomic_add_operation::AtomicAddOperation;
+/// let increment = AtomicAddOperation::<i32>::with_address_and_value(0x12345678, 1);
+/// ```
+///
+/// # Remarks
+///
+/// May clobber the flags register. Architectures without an atomic add to an absolute address
+/// use the scratch registers to hold the address and intermediate values.
+#[derive(Debug, Clone, PartialEq, Eq, new)]
+pub struct AtomicAddOperation<T> {
+    /// Address of the pointer sized integer to add to.
+    pub address: usize,
+
+    /// Value to add to the integer at `address`.
+    pub value: i32,
+
+    /// Scratch registers to use for the operation. (Needed for some architectures)
+    pub scratch: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T> AtomicAddOperation<T> {
+    /// Creates a new `AtomicAddOperation` with the given address and value, and no scratch registers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reloaded_hooks_portable::api::jit::atomic_add_operation::AtomicAddOperation;
+    ///
+    /// let decrement = AtomicAddOperation::<i32>::with_address_and_value(0x1000, -1);
+    /// assert_eq!(decrement.address, 0x1000);
+    /// assert_eq!(decrement.value, -1);
+    /// ```
+    pub fn with_address_and_value(address: usize, value: i32) -> Self {
+        Self {
+            address,
+            value,
+            scratch: Default::default(),
+        }
+    }
+}
@@ -4,7 +4,7 @@ use super::{
     call_relative_operation::CallRelativeOperation, jump_absolute_operation::JumpAbsoluteOperation,
     jump_relative_operation::JumpRelativeOperation, operation::Operation,
 };
-use alloc::{string::String, vec::Vec};
+use alloc::{format, string::String, vec::Vec};
 use bitflags::bitflags;
 use core::fmt::Debug;
 use derive_new::new;
@@ -37,6 +37,11 @@ bitflags! {
 
         /// This JIT can perform the 'Mov To Stack' operation.
         const CAN_MOV_TO_STACK = 1 << 5;
+
+        /// Every stub and wrapper starts with a branch target landing pad, [`Jit::landing_pad`].
+        /// This allows them to be reached via indirect branches when branch target enforcement
+        /// (e.g. BTI on ARM64, IBT on x86) is active.
+        const EMITS_LANDING_PADS = 1 << 6;
     }
 }
 
@@ -87,6 +92,13 @@ pub trait Jit<TRegister: Copy + Clone> {
         &[]
     }
 
+    /// Branch target landing pad placed at the start of every stub and wrapper, e.g. `bti jc` on ARM64.
+    ///
+    /// Override this if you set [`JitCapabilities::EMITS_LANDING_PADS`] in [`Self::get_jit_capabilities`]
+    fn landing_pad() -> &'static [u8] {
+        &[]
+    }
+
     /// Returns the size of a regular register in bytes.
     fn standard_register_size() -> usize;
 
@@ -220,6 +232,36 @@ where
     }
 }
 
+/// Variant of [`transform_err`] for conversions where the old register may not have
+/// an equivalent in the new register type.
+///
+/// Registers without an equivalent are reported as [`JitError::ThirdPartyAssemblerError`].
+pub fn try_transform_err<TOldRegister: Clone + Copy + Debug, TNewRegister, TConvertRegister>(
+    err: JitError<TOldRegister>,
+    f: TConvertRegister,
+) -> JitError<TNewRegister>
+where
+    TConvertRegister: Fn(TOldRegister) -> Option<TNewRegister>,
+{
+    let map = |x: TOldRegister| f(x).ok_or_else(|| format!("Register {:?} has no equivalent", x));
+    let result = match err {
+        JitError::CannotInitializeAssembler(x) => Ok(JitError::CannotInitializeAssembler(x)),
+        JitError::ThirdPartyAssemblerError(x) => Ok(JitError::ThirdPartyAssemblerError(x)),
+        JitError::InvalidRegister(x) => map(x).map(JitError::InvalidRegister),
+        JitError::InvalidRegisterCombination(a, b) => {
+            map(a).and_then(|a| Ok(JitError::InvalidRegisterCombination(a, map(b)?)))
+        }
+        JitError::OperandOutOfRange(a) => Ok(JitError::OperandOutOfRange(a)),
+        JitError::InvalidOffset(x) => Ok(JitError::InvalidOffset(x)),
+        JitError::NoScratchRegister(x) => Ok(JitError::NoScratchRegister(x)),
+        JitError::InvalidRegisterCombination3(a, b, c) => {
+            map(a).and_then(|a| Ok(JitError::InvalidRegisterCombination3(a, map(b)?, map(c)?)))
+        }
+    };
+
+    result.unwrap_or_else(JitError::ThirdPartyAssemblerError)
+}
+
 /// Contains the result of decoding a 'call' instruction.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
 pub struct DecodeCallTargetResult {
@@ -0,0 +1,65 @@
+extern crate alloc;
+use core::cell::RefCell;
+
+use alloc::{rc::Rc, vec::Vec};
+use derive_new::new;
+
+/// Represents an operation which enters a per-thread re-entrancy guard.
+///
+/// If the thread local flag is already set, execution jumps to `bypass_address`;
+/// otherwise the flag is set and execution continues.
+///
+/// # Fields
+///
+/// - `tls_offset`: Offset of the flag from the thread pointer.
+/// - `bypass_address`: Address to jump to when the flag is already set.
+///
+/// # Example
+///
+/// The generated wrappers use this operation to skip the hook function when it (indirectly) calls
+/// itself. On x64 Linux this is encoded as `cmp byte ptr fs:[tls_offset], 0`, followed by a
+/// `jne bypass_address` and a `mov byte ptr fs:[tls_offset], 1`.
+///
+/// ```
+/// use reloaded_hooks_portable::api::jit::enter_guard_operation::EnterGuardOperation;
+/// let enter = EnterGuardOperation::<i32>::with_offset_and_bypass(-64, 0x12345678);
+/// ```
+///
+/// # Remarks
+///
+/// Must be emitted before anything else in the wrapper, such that the stack and parameter registers
+/// are unchanged when jumping to `bypass_address`. May clobber the flags register.
+///
+/// The thread pointer is `fs`/`gs` (depending on OS) on x86, and `tpidr_el0` on ARM64.
+#[derive(Debug, Clone, PartialEq, Eq, new)]
+pub struct EnterGuardOperation<T> {
+    /// Offset of the pointer sized thread local flag, relative to the thread pointer.
+    pub tls_offset: isize,
+
+    /// Address to jump to if the current thread is already inside the guard.
+    pub bypass_address: usize,
+
+    /// Scratch registers to use for the operation. (Needed for some architectures)
+    pub scratch: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T> EnterGuardOperation<T> {
+    /// Creates a new `EnterGuardOperation` with the given offset and bypass address, and no scratch registers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reloaded_hooks_portable::api::jit::enter_guard_operation::EnterGuardOperation;
+    ///
+    /// let enter = EnterGuardOperation::<i32>::with_offset_and_bypass(0x1480, 0x1000);
+    /// assert_eq!(enter.tls_offset, 0x1480);
+    /// assert_eq!(enter.bypass_address, 0x1000);
+    /// ```
+    pub fn with_offset_and_bypass(tls_offset: isize, bypass_address: usize) -> Self {
+        Self {
+            tls_offset,
+            bypass_address,
+            scratch: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+extern crate alloc;
+use core::cell::RefCell;
+
+use alloc::{rc::Rc, vec::Vec};
+use derive_new::new;
+
+/// Represents an operation which leaves a per-thread re-entrancy guard entered with
+/// [`EnterGuardOperation`], by clearing the thread local flag.
+///
+/// # Fields
+///
+/// - `tls_offset`: Offset of the flag from the thread pointer.
+///
+/// # Example
+///
+/// On x64 Linux this is encoded as `mov byte ptr fs:[tls_offset], 0`.
+///
+/// ```
+/// use reloaded_hooks_portable::api::jit::exit_guard_operation::ExitGuardOperation;
+/// let exit = ExitGuardOperation::<i32>::with_offset(-64);
+/// ```
+///
+/// [`EnterGuardOperation`]: super::enter_guard_operation::EnterGuardOperation
+#[derive(Debug, Clone, PartialEq, Eq, new)]
+pub struct ExitGuardOperation<T> {
+    /// Offset of the pointer sized thread local flag, relative to the thread pointer.
+    pub tls_offset: isize,
+
+    /// Scratch registers to use for the operation. (Needed for some architectures)
+    pub scratch: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T> ExitGuardOperation<T> {
+    /// Creates a new `ExitGuardOperation` with the given offset, and no scratch registers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reloaded_hooks_portable::api::jit::exit_guard_operation::ExitGuardOperation;
+    ///
+    /// let exit = ExitGuardOperation::<i32>::with_offset(0x1480);
+    /// assert_eq!(exit.tls_offset, 0x1480);
+    /// ```
+    pub fn with_offset(tls_offset: isize) -> Self {
+        Self {
+            tls_offset,
+            scratch: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+extern crate alloc;
+use core::cell::RefCell;
+
+use alloc::{rc::Rc, vec::Vec};
+use derive_new::new;
+
+/// Represents an operation which checks the return address of the caller against a table of
+/// address ranges, jumping to `bypass_address` if it is not inside any of them.
+///
+/// # Fields
+///
+/// - `table_pointer`: Address of a pointer to the table of address ranges.
+/// - `bypass_address`: Address to jump to when the caller is not in any of the ranges.
+///
+/// # Table Layout
+///
+/// The pointer at `table_pointer` points to pairs of pointer sized integers `start, end`,
+/// where `start` is inclusive and `end` is exclusive. The table ends with a pair whose `end` is 0.
+/// See [`CallerFilter`].
+///
+/// # Example
+///
+/// The generated wrappers use this operation to only run the hook function for some callers.
+/// On x64 this is roughly encoded as:
+///
+/// ```asm
+/// mov rax, table_pointer
+/// mov rax, [rax]
+/// mov rcx, [rsp]        ; return address
+/// jmp loop
+/// bypass:
+/// jmp bypass_address
+/// loop:
+/// cmp qword [rax + 8], 0
+/// je bypass
+/// add rax, 16
+/// cmp rcx, [rax - 16]
+/// jb loop
+/// cmp rcx, [rax - 8]
+/// jae loop
+/// ; caller matched, continue
+/// ```
+///
+/// ```
+/// use reloaded_hooks_portable::api::jit::filter_caller_operation::FilterCallerOperation;
+/// let filter = FilterCallerOperation::<i32>::with_table_and_bypass(0x2000, 0x12345678);
+/// ```
+///
+/// # Remarks
+///
+/// Must be emitted before anything else in the wrapper, such that the stack and parameter registers
+/// are unchanged when jumping to `bypass_address`. May clobber the flags register.
+///
+/// The return address is at the top of the stack on x86, and in the link register (`x30`) on ARM64.
+///
+/// [`CallerFilter`]: crate::api::hooks::caller_filter::CallerFilter
+#[derive(Debug, Clone, PartialEq, Eq, new)]
+pub struct FilterCallerOperation<T> {
+    /// Address of the pointer to the table of address ranges.
+    pub table_pointer: usize,
+
+    /// Address to jump to if the caller is not in any of the ranges.
+    pub bypass_address: usize,
+
+    /// Scratch registers to use for the operation.
+    pub scratch: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T> FilterCallerOperation<T> {
+    /// Creates a new `FilterCallerOperation` with the given table pointer and bypass address,
+    /// and no scratch registers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reloaded_hooks_portable::api::jit::filter_caller_operation::FilterCallerOperation;
+    ///
+    /// let filter = FilterCallerOperation::<i32>::with_table_and_bypass(0x2000, 0x1000);
+    /// assert_eq!(filter.table_pointer, 0x2000);
+    /// assert_eq!(filter.bypass_address, 0x1000);
+    /// ```
+    pub fn with_table_and_bypass(table_pointer: usize, bypass_address: usize) -> Self {
+        Self {
+            table_pointer,
+            bypass_address,
+            scratch: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use derive_new::new;
+
+/// Represents a move operation that loads a floating point value from the stack into a register.
+///
+/// Unlike [`MovFromStackOperation`](super::mov_from_stack_operation::MovFromStackOperation),
+/// only `size` bytes are read, so the value can be placed in registers which hold floats in a
+/// different format, such as x87's `st0`.
+///
+/// # Example
+///
+/// ```
+/// use reloaded_hooks_portable::api::jit::mov_float_from_stack_operation::MovFloatFromStackOperation;
+///
+/// let move_op = MovFloatFromStackOperation {
+///     stack_offset: 8,
+///     size: 8,
+///     target: "st0"
+/// };
+///
+/// // This represents the Intel assembly instruction: FLD QWORD PTR [ESP + 8]
+/// println!("FLD QWORD PTR [ESP + {}]", move_op.stack_offset);
+/// ```
+///
+/// For x87, this pushes onto the x87 register stack; so the value ends up in `st0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub struct MovFloatFromStackOperation<T> {
+    /// The offset from the current stack pointer in the direction opposite to the stack's growth.
+    pub stack_offset: i32,
+
+    /// Size of the float in bytes, e.g. 4 for `float` and 8 for `double`.
+    pub size: u32,
+
+    /// The target (destination) register for the move operation.
+    pub target: T,
+}
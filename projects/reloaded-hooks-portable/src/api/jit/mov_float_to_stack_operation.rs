@@ -0,0 +1,35 @@
+use derive_new::new;
+
+/// Represents a move operation that stores a floating point value from a register onto the stack.
+///
+/// Unlike [`MovToStackOperation`](super::mov_to_stack_operation::MovToStackOperation),
+/// only `size` bytes are written, converting from the register's format if needed, such as
+/// for x87's `st0`.
+///
+/// # Example
+///
+/// ```
+/// use reloaded_hooks_portable::api::jit::mov_float_to_stack_operation::MovFloatToStackOperation;
+///
+/// let move_op = MovFloatToStackOperation {
+///     stack_offset: 0,
+///     size: 4,
+///     register: "st0"
+/// };
+///
+/// // This represents the Intel assembly instruction: FSTP DWORD PTR [ESP + 0]
+/// println!("FSTP DWORD PTR [ESP + {}]", move_op.stack_offset);
+/// ```
+///
+/// For x87, this pops the x87 register stack; so `st0` is no longer valid afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub struct MovFloatToStackOperation<T> {
+    /// The offset from the current stack pointer in the direction opposite to the stack's growth.
+    pub stack_offset: i32,
+
+    /// Size of the float in bytes, e.g. 4 for `float` and 8 for `double`.
+    pub size: u32,
+
+    /// The source register to move to the stack.
+    pub register: T,
+}
@@ -1,10 +1,15 @@
 extern crate alloc;
 use super::{
-    call_absolute_operation::CallAbsoluteOperation, call_relative_operation::CallRelativeOperation,
+    atomic_add_operation::AtomicAddOperation, call_absolute_operation::CallAbsoluteOperation,
+    call_relative_operation::CallRelativeOperation,
     call_rip_relative_operation::CallIpRelativeOperation,
+    enter_guard_operation::EnterGuardOperation, exit_guard_operation::ExitGuardOperation,
+    filter_caller_operation::FilterCallerOperation,
     jump_absolute_indirect_operation::JumpAbsoluteIndirectOperation,
     jump_absolute_operation::JumpAbsoluteOperation, jump_relative_operation::JumpRelativeOperation,
     jump_rip_relative_operation::JumpIpRelativeOperation,
+    mov_float_from_stack_operation::MovFloatFromStackOperation,
+    mov_float_to_stack_operation::MovFloatToStackOperation,
     mov_from_stack_operation::MovFromStackOperation, mov_operation::MovOperation,
     mov_to_stack_operation::MovToStackOperation, pop_operation::PopOperation,
     push_constant_operation::PushConstantOperation, push_operation::PushOperation,
@@ -37,6 +42,12 @@ pub enum Operation<T: Copy + Clone> {
     JumpAbsolute(JumpAbsoluteOperation<T>),
     JumpAbsoluteIndirect(JumpAbsoluteIndirectOperation<T>),
     Return(ReturnOperation),
+    AtomicAdd(AtomicAddOperation<T>), // Required for counting threads executing a wrapper
+    EnterGuard(EnterGuardOperation<T>), // Required for re-entrancy guards
+    ExitGuard(ExitGuardOperation<T>),
+    FilterCaller(FilterCallerOperation<T>), // Required for filtering hooks by caller
+    MovFloatFromStack(MovFloatFromStackOperation<T>), // Required for moving float returns between register kinds
+    MovFloatToStack(MovFloatToStackOperation<T>),
 
     // Only possible on some architectures.
     // These are opt-in and controlled by [JitCapabilities](super::compiler::JitCapabilities).
@@ -77,6 +88,45 @@ where
                 scratch: Rc::new(RefCell::new(new_vec)),
             })
         }
+        Operation::AtomicAdd(inner_op) => {
+            let borrowed_scratch = inner_op.scratch.borrow();
+            let mut new_vec = Vec::with_capacity(borrowed_scratch.len());
+            new_vec.extend(borrowed_scratch.iter().map(|x| f(*x)));
+            Operation::AtomicAdd(AtomicAddOperation {
+                address: inner_op.address,
+                value: inner_op.value,
+                scratch: Rc::new(RefCell::new(new_vec)),
+            })
+        }
+        Operation::EnterGuard(inner_op) => {
+            let borrowed_scratch = inner_op.scratch.borrow();
+            let mut new_vec = Vec::with_capacity(borrowed_scratch.len());
+            new_vec.extend(borrowed_scratch.iter().map(|x| f(*x)));
+            Operation::EnterGuard(EnterGuardOperation {
+                tls_offset: inner_op.tls_offset,
+                bypass_address: inner_op.bypass_address,
+                scratch: Rc::new(RefCell::new(new_vec)),
+            })
+        }
+        Operation::ExitGuard(inner_op) => {
+            let borrowed_scratch = inner_op.scratch.borrow();
+            let mut new_vec = Vec::with_capacity(borrowed_scratch.len());
+            new_vec.extend(borrowed_scratch.iter().map(|x| f(*x)));
+            Operation::ExitGuard(ExitGuardOperation {
+                tls_offset: inner_op.tls_offset,
+                scratch: Rc::new(RefCell::new(new_vec)),
+            })
+        }
+        Operation::FilterCaller(inner_op) => {
+            let borrowed_scratch = inner_op.scratch.borrow();
+            let mut new_vec = Vec::with_capacity(borrowed_scratch.len());
+            new_vec.extend(borrowed_scratch.iter().map(|x| f(*x)));
+            Operation::FilterCaller(FilterCallerOperation {
+                table_pointer: inner_op.table_pointer,
+                bypass_address: inner_op.bypass_address,
+                scratch: Rc::new(RefCell::new(new_vec)),
+            })
+        }
         Operation::StackAlloc(inner_op) => Operation::StackAlloc(StackAllocOperation {
             operand: inner_op.operand,
         }),
@@ -146,5 +196,17 @@ where
             register: f(x.register),
             stack_offset: x.stack_offset,
         }),
+        Operation::MovFloatFromStack(x) => {
+            Operation::MovFloatFromStack(MovFloatFromStackOperation {
+                stack_offset: x.stack_offset,
+                size: x.size,
+                target: f(x.target),
+            })
+        }
+        Operation::MovFloatToStack(x) => Operation::MovFloatToStack(MovFloatToStackOperation {
+            stack_offset: x.stack_offset,
+            size: x.size,
+            register: f(x.register),
+        }),
     }
 }
@@ -2,11 +2,16 @@
 // Import as `use crate::api::jit::operation_aliases::*`
 
 use super::{
-    call_absolute_operation::CallAbsoluteOperation, call_relative_operation::CallRelativeOperation,
+    atomic_add_operation::AtomicAddOperation, call_absolute_operation::CallAbsoluteOperation,
+    call_relative_operation::CallRelativeOperation,
     call_rip_relative_operation::CallIpRelativeOperation,
+    enter_guard_operation::EnterGuardOperation, exit_guard_operation::ExitGuardOperation,
+    filter_caller_operation::FilterCallerOperation,
     jump_absolute_indirect_operation::JumpAbsoluteIndirectOperation,
     jump_absolute_operation::JumpAbsoluteOperation, jump_relative_operation::JumpRelativeOperation,
     jump_rip_relative_operation::JumpIpRelativeOperation,
+    mov_float_from_stack_operation::MovFloatFromStackOperation,
+    mov_float_to_stack_operation::MovFloatToStackOperation,
     mov_from_stack_operation::MovFromStackOperation, mov_operation::MovOperation,
     mov_to_stack_operation::MovToStackOperation, operation::Operation, pop_operation::PopOperation,
     push_constant_operation::PushConstantOperation, push_operation::PushOperation,
@@ -31,4 +36,10 @@ pub type JumpAbsInd<T> = JumpAbsoluteIndirectOperation<T>;
 pub type CallIpRel<T> = CallIpRelativeOperation<T>;
 pub type JumpIpRel<T> = JumpIpRelativeOperation<T>;
 pub type MovToStack<T> = MovToStackOperation<T>;
+pub type MovFloatFromStack<T> = MovFloatFromStackOperation<T>;
+pub type MovFloatToStack<T> = MovFloatToStackOperation<T>;
 pub type Return = ReturnOperation;
+pub type AtomicAdd<T> = AtomicAddOperation<T>;
+pub type EnterGuard<T> = EnterGuardOperation<T>;
+pub type ExitGuard<T> = ExitGuardOperation<T>;
+pub type FilterCaller<T> = FilterCallerOperation<T>;
@@ -56,4 +56,32 @@ pub trait LengthDisassembler {
     ///
     /// [`code_address`] better point to a valid address, or you're screwed.
     fn disassemble_length(code_address: usize, min_length: usize) -> (usize, usize);
+
+    /// Searches for a direct branch that lands inside the region that will be overwritten by a hook.
+    ///
+    /// A branch from elsewhere in the function (e.g. the head of a loop) into the middle of the
+    /// stolen instructions would land inside the jump to the hook, and execute garbage.
+    ///
+    /// # Parameters
+    /// - `code_address`: The address of the hook, i.e. start of the stolen instructions.
+    /// - `stolen_length`: Length of the stolen instructions, as returned by [`Self::disassemble_length`].
+    /// - `scan_length`: Amount of bytes after the stolen instructions to scan for branches.
+    ///
+    /// # Returns
+    ///
+    /// `(branch_address, branch_target)` of the first branch found whose target lies within
+    /// `(code_address, code_address + stolen_length)`, else `None`.
+    ///
+    /// The default implementation performs no analysis and always returns `None`.
+    ///
+    /// # Safety
+    ///
+    /// [`code_address`] must point to at least `stolen_length + scan_length` readable bytes.
+    fn find_branch_into_stolen_region(
+        _code_address: usize,
+        _stolen_length: usize,
+        _scan_length: usize,
+    ) -> Option<(usize, usize)> {
+        None
+    }
 }
@@ -0,0 +1,190 @@
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+use libc::{c_void, O_CLOEXEC, O_RDONLY, PROT_EXEC, PROT_NONE, PROT_READ, PROT_WRITE};
+
+/// A region of memory which is mapped into the current process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MappedRegion {
+    pub start: usize,
+    pub end: usize,
+
+    /// Combination of the `PROT_READ`, `PROT_WRITE` and `PROT_EXEC` flags.
+    pub protection: i32,
+}
+
+/// Reads the contents of `/proc/self/maps`.
+pub(crate) fn read_proc_maps() -> Option<Vec<u8>> {
+    read_maps_file(c"/proc/self/maps")
+}
+
+/// Reads the contents of `/proc/<pid>/maps`.
+pub(crate) fn read_proc_maps_of(pid: i32) -> Option<Vec<u8>> {
+    let path = format!("/proc/{}/maps\0", pid);
+    read_maps_file(CStr::from_bytes_with_nul(path.as_bytes()).ok()?)
+}
+
+fn read_maps_file(path: &CStr) -> Option<Vec<u8>> {
+    unsafe {
+        let fd = libc::open(path.as_ptr(), O_RDONLY | O_CLOEXEC);
+        if fd < 0 {
+            return None;
+        }
+
+        let mut result = Vec::<u8>::with_capacity(16384);
+        loop {
+            result.reserve(4096);
+            let spare = result.capacity() - result.len();
+            let num_read = libc::read(
+                fd,
+                result.as_mut_ptr().add(result.len()) as *mut c_void,
+                spare,
+            );
+
+            if num_read <= 0 {
+                break;
+            }
+
+            result.set_len(result.len() + num_read as usize);
+        }
+
+        libc::close(fd);
+        Some(result)
+    }
+}
+
+/// Parses the contents of `/proc/self/maps` into a list of mapped regions.
+///
+/// Each line is in the format `start-end perms offset dev inode pathname`, where `start` and `end`
+/// are hexadecimal. Lines which fail to parse are skipped.
+pub(crate) fn parse_proc_maps(maps: &[u8]) -> Vec<MappedRegion> {
+    let mut regions = Vec::new();
+    for line in maps.split(|x| *x == b'\n') {
+        let mut columns = line.split(|x| *x == b' ');
+        let range = match columns.next() {
+            Some(range) => range,
+            None => continue,
+        };
+
+        let mut parts = range.split(|x| *x == b'-');
+        let start = parts.next().and_then(parse_hex);
+        let end = parts.next().and_then(parse_hex);
+        let protection = columns.next().and_then(parse_protection);
+        if let (Some(start), Some(end), Some(protection)) = (start, end, protection) {
+            regions.push(MappedRegion {
+                start,
+                end,
+                protection,
+            });
+        }
+    }
+
+    regions.sort_unstable_by_key(|x| x.start);
+    regions
+}
+
+/// Returns the region containing `address`, if any.
+///
+/// The regions must be sorted by address.
+pub(crate) fn find_region(regions: &[MappedRegion], address: usize) -> Option<&MappedRegion> {
+    let index = regions.partition_point(|x| x.end <= address);
+    regions.get(index).filter(|x| x.start <= address)
+}
+
+fn parse_hex(text: &[u8]) -> Option<usize> {
+    let text = core::str::from_utf8(text).ok()?;
+    usize::from_str_radix(text, 16).ok()
+}
+
+/// Parses the `rwxp` permissions column into `PROT_` flags.
+fn parse_protection(text: &[u8]) -> Option<i32> {
+    if text.len() < 3 {
+        return None;
+    }
+
+    let mut result = PROT_NONE;
+    for (char, flag) in text
+        .iter()
+        .zip([(b'r', PROT_READ), (b'w', PROT_WRITE), (b'x', PROT_EXEC)])
+    {
+        match *char {
+            b'-' => {}
+            x if x == flag.0 => result |= flag.1,
+            _ => return None,
+        }
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+#[cfg(target_pointer_width = "64")]
+mod tests {
+    use super::*;
+
+    const MAPS: &[u8] = b"\
+55d0a0000000-55d0a0002000 r--p 00000000 00:1f 123  /usr/bin/game
+55d0a0002000-55d0a0010000 r-xp 00002000 00:1f 123  /usr/bin/game
+55d0a0020000-55d0a0021000 rw-p 00000000 00:00 0    [heap]
+7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0    [stack]
+";
+
+    #[test]
+    fn parses_maps() {
+        let regions = parse_proc_maps(MAPS);
+        assert_eq!(
+            regions,
+            [
+                MappedRegion {
+                    start: 0x55d0a0000000,
+                    end: 0x55d0a0002000,
+                    protection: PROT_READ
+                },
+                MappedRegion {
+                    start: 0x55d0a0002000,
+                    end: 0x55d0a0010000,
+                    protection: PROT_READ | PROT_EXEC
+                },
+                MappedRegion {
+                    start: 0x55d0a0020000,
+                    end: 0x55d0a0021000,
+                    protection: PROT_READ | PROT_WRITE
+                },
+                MappedRegion {
+                    start: 0x7ffd00000000,
+                    end: 0x7ffd00021000,
+                    protection: PROT_READ | PROT_WRITE
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_region_containing_address() {
+        let regions = parse_proc_maps(MAPS);
+        assert_eq!(
+            find_region(&regions, 0x55d0a0002000).map(|x| x.start),
+            Some(0x55d0a0002000)
+        );
+        assert_eq!(
+            find_region(&regions, 0x55d0a000ffff).map(|x| x.start),
+            Some(0x55d0a0002000)
+        );
+        assert_eq!(find_region(&regions, 0x55d0a0010000), None);
+        assert_eq!(find_region(&regions, 0x7fff00000000), None);
+    }
+
+    #[test]
+    fn reads_own_maps() {
+        let regions = parse_proc_maps(&read_proc_maps().unwrap());
+        let regions_by_pid =
+            parse_proc_maps(&read_proc_maps_of(unsafe { libc::getpid() }).unwrap());
+        assert!(!regions_by_pid.is_empty());
+
+        let address = reads_own_maps as *const () as usize;
+        let region = find_region(&regions, address).unwrap();
+        assert_ne!(region.protection & PROT_EXEC, 0);
+    }
+}
@@ -0,0 +1,411 @@
+extern crate alloc;
+
+use super::platform_abstractions::{MemoryAccessor, PlatformFunctions};
+use super::platform_functions::register_platform_functions;
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
+use alloc::boxed::Box;
+use core::mem::size_of;
+use libc::{c_void, iovec, pid_t, PTRACE_PEEKDATA, PTRACE_POKEDATA};
+use spin::Once;
+
+/// Size of the unit read and written by `PTRACE_PEEKDATA` and `PTRACE_POKEDATA`.
+const WORD_SIZE: usize = size_of::<usize>();
+
+/// The process registered with [`register_remote_process`].
+static REMOTE_PROCESS: Once<&'static RemoteProcess> = Once::new();
+
+/// Registers `pid` as the process in which all hooks are created, instead of the current process.
+///
+/// Use [`RemoteBufferFactory`] as the buffer factory for hooks created in the remote process.
+///
+/// # Parameters
+///
+/// - `pid`: The process to hook. Must be traced by the current thread (see [`RemoteProcess`]).
+///
+/// # Returns
+///
+/// `true` on success; `false` if a platform backend has already been registered or used.
+///
+/// [`RemoteBufferFactory`]: crate::api::buffers::linux_remote_buffer_factory::RemoteBufferFactory
+pub fn register_remote_process(pid: pid_t) -> bool {
+    let process: &'static RemoteProcess = Box::leak(Box::new(RemoteProcess::new(pid)));
+    if !register_platform_functions(process) {
+        return false;
+    }
+
+    REMOTE_PROCESS.call_once(|| process);
+    true
+}
+
+/// Returns the process registered with [`register_remote_process`].
+pub fn get_remote_process() -> Option<&'static RemoteProcess> {
+    REMOTE_PROCESS.get().copied()
+}
+
+/// Accesses the memory of another process on Linux, allowing hooks to be installed into it.
+///
+/// Memory is read with `process_vm_readv`, and written with `PTRACE_POKEDATA`; which, unlike
+/// `process_vm_writev`, ignores page protection. Therefore pages never need to be unprotected.
+///
+/// # Remarks
+///
+/// The process must be attached with `ptrace` (`PTRACE_ATTACH`, `PTRACE_SEIZE` or `PTRACE_TRACEME`)
+/// by the thread using this struct, and be stopped while hooks are created or toggled.
+/// Because it is stopped, no thread can observe a partially written hook.
+///
+/// If the process exits or is detached from, memory accesses fail, and creating a hook returns
+/// [`MemoryProtectionError::ReadFailed`] or [`MemoryProtectionError::WriteFailed`].
+///
+/// Injecting syscalls into the process (used by [`RemoteProcess::map_memory`], and therefore by
+/// [`RemoteBufferFactory`]) is only implemented on x86_64. On other architectures, stubs must be
+/// placed in memory allocated by other means, e.g. via a custom [`BufferFactory`].
+///
+/// [`RemoteBufferFactory`]: crate::api::buffers::linux_remote_buffer_factory::RemoteBufferFactory
+/// [`BufferFactory`]: crate::api::buffers::buffer_abstractions::BufferFactory
+pub struct RemoteProcess {
+    pid: pid_t,
+}
+
+impl RemoteProcess {
+    /// Creates an accessor for the memory of the given process.
+    pub const fn new(pid: pid_t) -> Self {
+        Self { pid }
+    }
+
+    /// Returns the id of the process.
+    pub fn pid(&self) -> pid_t {
+        self.pid
+    }
+
+    /// Maps `size` bytes of read + execute memory in the process, by making it call `mmap`.
+    ///
+    /// The memory can still be written to via [`MemoryAccessor::write_memory`].
+    ///
+    /// # Parameters
+    ///
+    /// - `address`: If specified, the memory must be mapped at exactly this address.
+    /// - `size`: The number of bytes to map.
+    ///
+    /// # Returns
+    ///
+    /// The address of the mapped memory, or `None` on failure.
+    /// Only supported on x86_64, other architectures always return `None`.
+    pub fn map_memory(&self, address: Option<usize>, size: usize) -> Option<usize> {
+        let (hint, fixed_flag) = match address {
+            Some(address) => (address, libc::MAP_FIXED_NOREPLACE),
+            None => (0, 0),
+        };
+
+        let flags = libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | fixed_flag;
+        let result = self.syscall(
+            libc::SYS_mmap,
+            [
+                hint,
+                size,
+                (libc::PROT_READ | libc::PROT_EXEC) as usize,
+                flags as usize,
+                -1_isize as usize,
+                0,
+            ],
+        )?;
+
+        // Kernels older than 4.17 don't know `MAP_FIXED_NOREPLACE`, and treat the address as a hint.
+        if address.is_some_and(|address| result != address) {
+            self.syscall(libc::SYS_munmap, [result, size, 0, 0, 0, 0]);
+            return None;
+        }
+
+        Some(result)
+    }
+
+    /// Makes the stopped process execute a syscall, then restores its state.
+    ///
+    /// # Returns
+    ///
+    /// The result of the syscall, or `None` if it failed or could not be executed.
+    #[cfg(target_arch = "x86_64")]
+    fn syscall(&self, number: i64, args: [usize; 6]) -> Option<usize> {
+        unsafe {
+            let mut saved_regs: libc::user_regs_struct = core::mem::zeroed();
+            if self.ptrace(libc::PTRACE_GETREGS, 0, &mut saved_regs as *mut _ as usize) != 0 {
+                return None;
+            }
+
+            // Temporarily place a `syscall` instruction at the current instruction pointer.
+            let ip = saved_regs.rip as usize;
+            let saved_code = self.peek(ip)?;
+            let syscall_code = (saved_code & !0xFFFF) | usize::from_le(0x050F);
+            self.poke(ip, syscall_code)?;
+
+            let mut regs = saved_regs;
+            regs.rax = number as u64;
+            regs.rdi = args[0] as u64;
+            regs.rsi = args[1] as u64;
+            regs.rdx = args[2] as u64;
+            regs.r10 = args[3] as u64;
+            regs.r8 = args[4] as u64;
+            regs.r9 = args[5] as u64;
+
+            // Prevent the kernel from treating this as a restarted syscall.
+            regs.orig_rax = u64::MAX;
+
+            let executed = self.ptrace(libc::PTRACE_SETREGS, 0, &regs as *const _ as usize) == 0
+                && self.single_step()
+                && self.ptrace(libc::PTRACE_GETREGS, 0, &mut regs as *mut _ as usize) == 0;
+
+            self.poke(ip, saved_code);
+            self.ptrace(libc::PTRACE_SETREGS, 0, &saved_regs as *const _ as usize);
+
+            // Return values between -4095 and -1 are errors.
+            let result = regs.rax as usize;
+            if !executed || result > (-4096_isize) as usize {
+                return None;
+            }
+
+            Some(result)
+        }
+    }
+
+    /// Syscall injection is only implemented on x86_64.
+    #[cfg(not(target_arch = "x86_64"))]
+    fn syscall(&self, _number: i64, _args: [usize; 6]) -> Option<usize> {
+        None
+    }
+
+    /// Executes a single instruction in the stopped process, and waits for it to stop again.
+    #[cfg(target_arch = "x86_64")]
+    fn single_step(&self) -> bool {
+        unsafe {
+            if self.ptrace(libc::PTRACE_SINGLESTEP, 0, 0) != 0 {
+                return false;
+            }
+
+            let mut status = 0;
+            libc::waitpid(self.pid, &mut status, libc::__WALL) == self.pid
+                && libc::WIFSTOPPED(status)
+        }
+    }
+
+    fn peek(&self, address: usize) -> Option<usize> {
+        unsafe {
+            *libc::__errno_location() = 0;
+            let result = self.ptrace(PTRACE_PEEKDATA, address, 0);
+
+            // -1 is a valid value, so we need to check errno.
+            if result == -1 && *libc::__errno_location() != 0 {
+                return None;
+            }
+
+            Some(result as usize)
+        }
+    }
+
+    fn poke(&self, address: usize, value: usize) -> Option<()> {
+        unsafe { (self.ptrace(PTRACE_POKEDATA, address, value) == 0).then_some(()) }
+    }
+
+    unsafe fn ptrace(&self, request: libc::c_uint, address: usize, data: usize) -> libc::c_long {
+        libc::ptrace(
+            request,
+            self.pid,
+            address as *mut c_void,
+            data as *mut c_void,
+        )
+    }
+
+    /// Reads via `process_vm_readv`, returning the number of bytes read.
+    fn read_vm(&self, address: usize, out: &mut [u8]) -> usize {
+        let local = iovec {
+            iov_base: out.as_mut_ptr() as *mut c_void,
+            iov_len: out.len(),
+        };
+        let remote = iovec {
+            iov_base: address as *mut c_void,
+            iov_len: out.len(),
+        };
+
+        let result = unsafe { libc::process_vm_readv(self.pid, &local, 1, &remote, 1, 0) };
+        result.max(0) as usize
+    }
+}
+
+/// Accessing memory fails if the process exits, or is detached from.
+impl MemoryAccessor for RemoteProcess {
+    unsafe fn read_memory(
+        &self,
+        address: usize,
+        out: &mut [u8],
+    ) -> Result<(), MemoryProtectionError> {
+        let num_read = self.read_vm(address, out);
+
+        // process_vm_readv may be blocked (e.g. by seccomp), fall back to ptrace.
+        for offset in (num_read..out.len()).step_by(WORD_SIZE) {
+            let word_address = address + offset;
+            let word = self
+                .peek(word_address)
+                .ok_or(MemoryProtectionError::ReadFailed(word_address))?;
+
+            let num_bytes = (out.len() - offset).min(WORD_SIZE);
+            out[offset..offset + num_bytes].copy_from_slice(&word.to_ne_bytes()[..num_bytes]);
+        }
+
+        Ok(())
+    }
+
+    unsafe fn write_memory(
+        &self,
+        address: usize,
+        data: &[u8],
+    ) -> Result<(), MemoryProtectionError> {
+        let mut offset = 0;
+        while offset < data.len() {
+            // Merge our bytes into the existing (aligned) word.
+            let word_address = (address + offset) & !(WORD_SIZE - 1);
+            let start = address + offset - word_address;
+            let num_bytes = (WORD_SIZE - start).min(data.len() - offset);
+
+            let mut word = self
+                .peek(word_address)
+                .ok_or(MemoryProtectionError::WriteFailed(address + offset))?
+                .to_ne_bytes();
+            word[start..start + num_bytes].copy_from_slice(&data[offset..offset + num_bytes]);
+
+            self.poke(word_address, usize::from_ne_bytes(word))
+                .ok_or(MemoryProtectionError::WriteFailed(address + offset))?;
+            offset += num_bytes;
+        }
+
+        Ok(())
+    }
+
+    unsafe fn write_memory_atomic(
+        &self,
+        address: usize,
+        data: &[u8],
+    ) -> Result<(), MemoryProtectionError> {
+        // The process is stopped, so no thread can observe the write until it completes.
+        self.write_memory(address, data)
+    }
+}
+
+impl PlatformFunctions for RemoteProcess {
+    fn page_size(&self) -> usize {
+        unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+    }
+
+    /// `PTRACE_POKEDATA` ignores page protection, so this is a no-op.
+    fn unprotect_memory(&self, _address: usize, _size: usize) -> Result<(), MemoryProtectionError> {
+        Ok(())
+    }
+
+    fn restore_memory_protection(
+        &self,
+        _address: usize,
+        _size: usize,
+    ) -> Result<(), MemoryProtectionError> {
+        Ok(())
+    }
+
+    /// The kernel flushes the instruction cache after writes made with `PTRACE_POKEDATA`.
+    fn clear_instruction_cache(&self, _start: usize, _end: usize) {}
+}
+
+#[cfg(test)]
+#[cfg(target_arch = "x86_64")]
+mod tests {
+    use super::*;
+    use core::ptr::null_mut;
+
+    static VALUE: [u8; 32] = [0; 32];
+
+    /// Forks a child which stops itself, and waits for it to stop.
+    fn fork_stopped_child() -> pid_t {
+        unsafe {
+            let pid = libc::fork();
+            if pid == 0 {
+                libc::ptrace(
+                    libc::PTRACE_TRACEME,
+                    0,
+                    null_mut::<c_void>(),
+                    null_mut::<c_void>(),
+                );
+                libc::raise(libc::SIGSTOP);
+                libc::_exit(0);
+            }
+
+            let mut status = 0;
+            libc::waitpid(pid, &mut status, 0);
+            assert!(libc::WIFSTOPPED(status));
+            pid
+        }
+    }
+
+    fn kill_child(pid: pid_t) {
+        unsafe {
+            libc::kill(pid, libc::SIGKILL);
+            libc::waitpid(pid, null_mut(), 0);
+        }
+    }
+
+    #[test]
+    fn reads_and_writes_remote_memory() {
+        let pid = fork_stopped_child();
+        let process = RemoteProcess::new(pid);
+        let address = VALUE.as_ptr() as usize;
+
+        unsafe {
+            // Unaligned write spanning multiple words.
+            let data: [u8; 13] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13];
+            process.write_memory(address + 3, &data).unwrap();
+
+            let mut result = [0xFFu8; 19];
+            process.read_memory(address, &mut result).unwrap();
+            assert_eq!(result[..3], [0, 0, 0]);
+            assert_eq!(result[3..16], data);
+            assert_eq!(result[16..], [0, 0, 0]);
+
+            // Our copy is untouched.
+            assert_eq!(VALUE, [0; 32]);
+        }
+
+        kill_child(pid);
+    }
+
+    #[test]
+    fn maps_remote_memory() {
+        let pid = fork_stopped_child();
+        let process = RemoteProcess::new(pid);
+
+        let address = process.map_memory(None, 4096).unwrap();
+        unsafe {
+            process.write_memory(address, &[0xC3]).unwrap();
+            let mut result = [0u8; 1];
+            process.read_memory(address, &mut result).unwrap();
+            assert_eq!(result, [0xC3]);
+        }
+
+        // Fixed address mapping fails, as memory is already mapped there.
+        assert_eq!(process.map_memory(Some(address), 4096), None);
+        kill_child(pid);
+    }
+
+    #[test]
+    fn access_fails_after_process_exits() {
+        let pid = fork_stopped_child();
+        let process = RemoteProcess::new(pid);
+        let address = VALUE.as_ptr() as usize;
+        kill_child(pid);
+
+        unsafe {
+            let mut result = [0u8; 8];
+            assert_eq!(
+                process.read_memory(address, &mut result),
+                Err(MemoryProtectionError::ReadFailed(address))
+            );
+            assert_eq!(
+                process.write_memory(address, &[1]),
+                Err(MemoryProtectionError::WriteFailed(address))
+            );
+        }
+    }
+}
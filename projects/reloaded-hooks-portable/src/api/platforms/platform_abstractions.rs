@@ -0,0 +1,176 @@
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
+use core::ptr::{copy_nonoverlapping, read_unaligned, write_unaligned};
+
+/// Platform specific operations used to modify code in memory.
+///
+/// The library ships with [`DefaultPlatformFunctions`], which supports Windows, Linux and macOS.
+/// For other platforms (consoles, hypervisor guests, emulators patching guest memory, remote processes)
+/// implement this trait and register it with [`register_platform_functions`] at startup.
+///
+/// # Remarks
+///
+/// Addresses are passed as `usize`, as they may not belong to the current address space.
+/// Memory is read and written through the [`MemoryAccessor`] supertrait.
+///
+/// [`DefaultPlatformFunctions`]: super::platform_functions::DefaultPlatformFunctions
+/// [`register_platform_functions`]: super::platform_functions::register_platform_functions
+pub trait PlatformFunctions: MemoryAccessor + Sync + Send {
+    /// Returns the size of a memory page.
+    fn page_size(&self) -> usize;
+
+    /// Removes protection from a memory region.
+    /// This makes it such that existing game code can be safely overwritten.
+    ///
+    /// Each call is followed by a matching call to [`PlatformFunctions::restore_memory_protection`]
+    /// once the code has been written.
+    ///
+    /// # Parameters
+    ///
+    /// - `address`: The address of the memory to remove protection from.
+    /// - `size`: The size of the memory to remove protection from.
+    fn unprotect_memory(&self, address: usize, size: usize) -> Result<(), MemoryProtectionError>;
+
+    /// Restores the protection of a memory region previously unprotected with
+    /// [`PlatformFunctions::unprotect_memory`].
+    ///
+    /// # Parameters
+    ///
+    /// - `address`: The address passed to [`PlatformFunctions::unprotect_memory`].
+    /// - `size`: The size passed to [`PlatformFunctions::unprotect_memory`].
+    fn restore_memory_protection(
+        &self,
+        address: usize,
+        size: usize,
+    ) -> Result<(), MemoryProtectionError>;
+
+    /// Temporarily disables write XOR execute protection, on platforms which enforce it.
+    ///
+    /// # Returns
+    ///
+    /// The old protection, to pass to [`PlatformFunctions::restore_write_xor_execute`]; or `None`
+    /// if the platform does not enforce W^X, in which case [`PlatformFunctions::unprotect_memory`]
+    /// is used instead.
+    fn disable_write_xor_execute(&self, _address: usize, _size: usize) -> Option<usize> {
+        None
+    }
+
+    /// Restores write XOR execute protection.
+    ///
+    /// # Parameters
+    ///
+    /// - `protection`: The protection returned by [`PlatformFunctions::disable_write_xor_execute`].
+    fn restore_write_xor_execute(&self, _address: usize, _size: usize, _protection: usize) {}
+
+    /// Clears the instruction cache for the range `start..end`.
+    fn clear_instruction_cache(&self, start: usize, end: usize);
+
+    /// Ensures all cores executing the target see code modified before this call.
+    fn synchronize_cores(&self) {}
+
+    /// Allocates a pointer sized flag which is unique to each thread, and zero in every thread
+    /// (including threads created later). Used to implement re-entrancy guards in generated code.
+    ///
+    /// # Returns
+    ///
+    /// Offset of the flag relative to the thread pointer; i.e. `fs`/`gs` base on x86, or `tpidr_el0`
+    /// on ARM64. `None` if no more flags can be allocated, or the platform is not supported.
+    ///
+    /// # Remarks
+    ///
+    /// Flags are returned with [`PlatformFunctions::free_thread_local_flag`] once the hook using
+    /// them is uninstalled.
+    fn allocate_thread_local_flag(&self) -> Option<isize> {
+        None
+    }
+
+    /// Returns a flag allocated with [`PlatformFunctions::allocate_thread_local_flag`], such that
+    /// it can be allocated again.
+    ///
+    /// # Remarks
+    ///
+    /// A thread may still be inside the guard using the flag, so implementations must quarantine
+    /// the flag for a while before handing it out again; like [`BufferFactory::release`].
+    ///
+    /// [`BufferFactory::release`]: crate::api::buffers::buffer_abstractions::BufferFactory::release
+    fn free_thread_local_flag(&self, _offset: isize) {}
+}
+
+/// Reads and writes the memory containing the code being hooked, and the stubs generated for it.
+///
+/// The default implementations access memory of the current process. Override all of them to hook
+/// code in another process; see [`RemoteProcess`] for an example.
+///
+/// # Remarks
+///
+/// Failing to access memory which can go away at any time (e.g. because the process it belongs to
+/// exited) should be reported as an error; the error is then returned by the hook being created.
+///
+/// [`RemoteProcess`]: super::linux_remote::RemoteProcess
+pub trait MemoryAccessor {
+    /// Reads `out.len()` bytes from `address` into `out`.
+    ///
+    /// # Returns
+    ///
+    /// [`MemoryProtectionError::ReadFailed`] if the memory could not be read.
+    ///
+    /// # Safety
+    ///
+    /// `address` must point to `out.len()` bytes of readable memory.
+    unsafe fn read_memory(
+        &self,
+        address: usize,
+        out: &mut [u8],
+    ) -> Result<(), MemoryProtectionError> {
+        copy_nonoverlapping(address as *const u8, out.as_mut_ptr(), out.len());
+        Ok(())
+    }
+
+    /// Writes `data` to `address`.
+    ///
+    /// # Returns
+    ///
+    /// [`MemoryProtectionError::WriteFailed`] if the memory could not be written.
+    ///
+    /// # Safety
+    ///
+    /// `address` must point to `data.len()` bytes of writeable memory.
+    unsafe fn write_memory(
+        &self,
+        address: usize,
+        data: &[u8],
+    ) -> Result<(), MemoryProtectionError> {
+        copy_nonoverlapping(data.as_ptr(), address as *mut u8, data.len());
+        Ok(())
+    }
+
+    /// Writes `data` to `address` as a single atomic store.
+    ///
+    /// # Parameters
+    ///
+    /// - `data`: The bytes to write. Length is 1, 2, 4, 8 or 16.
+    ///
+    /// # Returns
+    ///
+    /// [`MemoryProtectionError::WriteFailed`] if the memory could not be written.
+    ///
+    /// # Safety
+    ///
+    /// `address` must point to `data.len()` bytes of writeable memory.
+    unsafe fn write_memory_atomic(
+        &self,
+        address: usize,
+        data: &[u8],
+    ) -> Result<(), MemoryProtectionError> {
+        let src = data.as_ptr();
+        match data.len() {
+            1 => write_unaligned(address as *mut u8, *src),
+            2 => write_unaligned(address as *mut u16, read_unaligned(src as *const u16)),
+            4 => write_unaligned(address as *mut u32, read_unaligned(src as *const u32)),
+            8 => write_unaligned(address as *mut u64, read_unaligned(src as *const u64)),
+            16 => write_unaligned(address as *mut u128, read_unaligned(src as *const u128)),
+            _ => panic!("Unsupported size for atomic write."),
+        }
+
+        Ok(())
+    }
+}
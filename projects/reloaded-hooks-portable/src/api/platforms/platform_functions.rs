@@ -1,9 +1,15 @@
 extern crate alloc;
 
+use super::platform_abstractions::{MemoryAccessor, PlatformFunctions};
+use super::protection_manager::{self, PageProtector};
 use crate::api::buffers::buffer_abstractions::BufferFactory;
+use crate::api::buffers::default_buffer_factory::RELEASE_QUARANTINE_MS;
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
+use crate::helpers::{icache_clear, sync_cores};
 use alloc::boxed::Box;
 use alloc::string::String;
-use spin::Mutex;
+use alloc::vec::Vec;
+use spin::{Mutex, Once};
 
 #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
 use super::platform_functions_unix;
@@ -14,41 +20,197 @@ use super::platform_functions_apple;
 #[cfg(target_os = "windows")]
 use crate::api::platforms::platform_functions_windows;
 
-#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+#[cfg(not(any(unix, target_os = "windows")))]
 use super::platform_functions_mmap_rs::unprotect_memory_mmap_rs;
 
+#[cfg(not(any(unix, target_os = "windows")))]
+use mmap_rs_with_map_from_existing::MmapOptions;
+
 pub(crate) static MUTUAL_EXCLUSOR: Mutex<()> = Mutex::new(());
 
+/// The platform backend in use, set on first use or via [`register_platform_functions`].
+static PLATFORM_FUNCTIONS: Once<&'static dyn PlatformFunctions> = Once::new();
+
+/// Flags returned via [`free_thread_local_flag`], with the time they were released at.
+static RELEASED_THREAD_LOCAL_FLAGS: Mutex<Vec<(isize, u64)>> = Mutex::new(Vec::new());
+
+/// Registers a custom platform backend, replacing [`DefaultPlatformFunctions`].
+///
+/// # Parameters
+///
+/// - `functions`: The backend to use for all future code modifications.
+///
+/// # Returns
+///
+/// `true` on success; `false` if a backend has already been registered, or the default backend
+/// has already been used. Call this at startup, before creating any hooks.
+pub fn register_platform_functions(functions: &'static dyn PlatformFunctions) -> bool {
+    try_register(&PLATFORM_FUNCTIONS, functions)
+}
+
+/// Returns the platform backend in use.
+///
+/// This is [`DefaultPlatformFunctions`], unless another backend was registered with
+/// [`register_platform_functions`].
+#[inline]
+pub fn get_platform_functions() -> &'static dyn PlatformFunctions {
+    *PLATFORM_FUNCTIONS.call_once(|| &DefaultPlatformFunctions)
+}
+
+fn try_register(
+    slot: &Once<&'static dyn PlatformFunctions>,
+    functions: &'static dyn PlatformFunctions,
+) -> bool {
+    let mut registered = false;
+    slot.call_once(|| {
+        registered = true;
+        functions
+    });
+    registered
+}
+
+/// Default implementation of [`PlatformFunctions`], which modifies code in the current process
+/// using the APIs of the current operating system.
+///
+/// Custom backends can delegate to this for operations they don't need to change.
+pub struct DefaultPlatformFunctions;
+
+impl MemoryAccessor for DefaultPlatformFunctions {}
+
+impl PlatformFunctions for DefaultPlatformFunctions {
+    fn page_size(&self) -> usize {
+        NativePageProtector::page_size()
+    }
+
+    fn unprotect_memory(&self, address: usize, size: usize) -> Result<(), MemoryProtectionError> {
+        unprotect_memory(address as *const u8, size)
+    }
+
+    fn restore_memory_protection(
+        &self,
+        address: usize,
+        size: usize,
+    ) -> Result<(), MemoryProtectionError> {
+        restore_memory_protection(address as *const u8, size)
+    }
+
+    fn disable_write_xor_execute(&self, address: usize, size: usize) -> Option<usize> {
+        disable_write_xor_execute(address as *const u8, size)
+    }
+
+    fn restore_write_xor_execute(&self, address: usize, size: usize, protection: usize) {
+        restore_write_xor_execute(address as *const u8, size, protection)
+    }
+
+    fn clear_instruction_cache(&self, start: usize, end: usize) {
+        icache_clear::clear_instruction_cache(start as *const u8, end as *const u8)
+    }
+
+    fn synchronize_cores(&self) {
+        sync_cores::synchronize_cores()
+    }
+
+    fn allocate_thread_local_flag(&self) -> Option<isize> {
+        allocate_thread_local_flag()
+    }
+
+    fn free_thread_local_flag(&self, offset: isize) {
+        free_thread_local_flag(offset)
+    }
+}
+
 /// Removes protection from a memory region.
 /// This makes it such that existing game code can be safely overwritten.
 ///
+/// Every page overlapping the region is made writeable until a matching call to
+/// [`restore_memory_protection`], which restores the exact protection each page had before.
+///
 /// # Parameters
 ///
-/// - `address`: The address of the memory to disable write XOR execute protection for.
-/// - `size`: The size of the memory to disable write XOR execute protection for.
+/// - `address`: The address of the memory to remove protection from.
+/// - `size`: The size of the memory to remove protection from.
 ///
 /// # Returns
 ///
-/// This function is crucial to the operation of the library. On failure, we panic.
+/// Success or error. On error, no protection is changed.
 #[inline]
-pub fn unprotect_memory(address: *const u8, size: usize) {
-    // Implement your logic to unprotect the memory here.
-    // Returning an example Result
+pub fn unprotect_memory(address: *const u8, size: usize) -> Result<(), MemoryProtectionError> {
+    protection_manager::unprotect_memory::<NativePageProtector>(address as usize, size)
+}
 
-    // Windows uses VirtualProtect
-    #[cfg(target_os = "windows")]
-    platform_functions_windows::unprotect_memory(address, size);
+/// Restores the protection of a memory region previously unprotected with [`unprotect_memory`].
+///
+/// Pages are reference counted; a page shared with another region which is still unprotected
+/// stays writeable until that region is restored too.
+///
+/// # Parameters
+///
+/// - `address`: The address passed to [`unprotect_memory`].
+/// - `size`: The size passed to [`unprotect_memory`].
+///
+/// # Returns
+///
+/// Success or error.
+#[inline]
+pub fn restore_memory_protection(
+    address: *const u8,
+    size: usize,
+) -> Result<(), MemoryProtectionError> {
+    protection_manager::restore_memory::<NativePageProtector>(address as usize, size)
+}
 
-    // Non-apple unix platforms use mprotect
-    #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
-    platform_functions_unix::unprotect_memory(address, size);
+/// Changes page protection using the APIs of the current operating system.
+struct NativePageProtector;
 
-    // I don't trust Apple to keep mmap working, so I'm doing manual implementation with mach_ APIs.
-    #[cfg(any(target_os = "macos", target_os = "ios"))]
-    platform_functions_apple::unprotect_memory(address, size);
+impl PageProtector for NativePageProtector {
+    fn page_size() -> usize {
+        #[cfg(target_os = "windows")]
+        return platform_functions_windows::page_size();
+
+        #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+        return platform_functions_unix::page_size();
+
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        return platform_functions_apple::page_size();
+
+        #[cfg(not(any(unix, target_os = "windows")))]
+        MmapOptions::page_size()
+    }
+
+    fn unprotect_page(page: usize) -> Result<Option<usize>, MemoryProtectionError> {
+        // Windows uses VirtualProtect
+        #[cfg(target_os = "windows")]
+        return platform_functions_windows::unprotect_page(page);
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-    unprotect_memory_mmap_rs(address, size);
+        // Non-apple unix platforms use mprotect
+        #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+        return platform_functions_unix::unprotect_page(page);
+
+        // I don't trust Apple to keep mmap working, so I'm doing manual implementation with mach_ APIs.
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        return platform_functions_apple::unprotect_page(page);
+
+        #[cfg(not(any(unix, target_os = "windows")))]
+        {
+            unprotect_memory_mmap_rs(page as *const u8, Self::page_size());
+            Ok(None)
+        }
+    }
+
+    fn restore_page(page: usize, protection: usize) -> Result<(), MemoryProtectionError> {
+        #[cfg(target_os = "windows")]
+        return platform_functions_windows::restore_page(page, protection);
+
+        #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+        return platform_functions_unix::restore_page(page, protection);
+
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        return platform_functions_apple::restore_page(page, protection);
+
+        // mmap_rs can't query protection, so pages are never restored.
+        #[cfg(not(any(unix, target_os = "windows")))]
+        Ok(())
+    }
 }
 
 /// Temporarily disables write XOR execute protection with an OS specialized
@@ -81,6 +243,82 @@ pub fn disable_write_xor_execute(address: *const u8, size: usize) -> Option<usiz
     None
 }
 
+/// Allocates a pointer sized flag which is unique to each thread, for use in generated code.
+///
+/// # Returns
+///
+/// Offset of the flag relative to the thread pointer, or `None` if unsupported.
+/// See [`PlatformFunctions::allocate_thread_local_flag`].
+#[inline]
+pub fn allocate_thread_local_flag() -> Option<isize> {
+    if let Some(offset) = take_released_thread_local_flag() {
+        return Some(offset);
+    }
+
+    #[cfg(target_os = "windows")]
+    return platform_functions_windows::allocate_thread_local_flag();
+
+    #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+    return platform_functions_unix::allocate_thread_local_flag();
+
+    #[cfg(not(any(
+        all(unix, not(any(target_os = "macos", target_os = "ios"))),
+        target_os = "windows"
+    )))]
+    None
+}
+
+/// Returns a flag allocated with [`allocate_thread_local_flag`], such that it can be allocated again.
+///
+/// The flag is quarantined for [`RELEASE_QUARANTINE_MS`] before it is reused, as threads may still
+/// be inside the guard using it. Without a monotonic clock, the flag is never reused.
+/// See [`PlatformFunctions::free_thread_local_flag`].
+pub fn free_thread_local_flag(offset: isize) {
+    if let Some(now) = get_monotonic_time_ms() {
+        RELEASED_THREAD_LOCAL_FLAGS.lock().push((offset, now));
+    }
+}
+
+/// Removes a flag which is out of quarantine from [`RELEASED_THREAD_LOCAL_FLAGS`].
+fn take_released_thread_local_flag() -> Option<isize> {
+    let now = get_monotonic_time_ms()?;
+    let mut released = RELEASED_THREAD_LOCAL_FLAGS.lock();
+    let index = released
+        .iter()
+        .position(|&(_, released_at)| now.saturating_sub(released_at) >= RELEASE_QUARANTINE_MS)?;
+
+    Some(released.swap_remove(index).0)
+}
+
+/// Returns the current value of a monotonic clock, in milliseconds.
+///
+/// # Returns
+///
+/// The time elapsed since an unspecified point in the past, or `None` if the platform has no
+/// supported clock.
+///
+/// # Remarks
+///
+/// Used to time the quarantine of released buffer memory.
+#[inline]
+pub fn get_monotonic_time_ms() -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    return Some(platform_functions_windows::get_monotonic_time_ms());
+
+    #[cfg(unix)]
+    unsafe {
+        let mut time: libc::timespec = core::mem::zeroed();
+        if libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time) != 0 {
+            return None;
+        }
+
+        return Some(time.tv_sec as u64 * 1000 + time.tv_nsec as u64 / 1_000_000);
+    }
+
+    #[cfg(not(any(unix, target_os = "windows")))]
+    None
+}
+
 /// Restores write XOR execute protection.
 ///
 /// # Parameters
@@ -98,3 +336,60 @@ pub fn restore_write_xor_execute(address: *const u8, size: usize, protection: us
     #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
     platform_functions_apple::restore_write_xor_execute(address, size, protection);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CustomPlatformFunctions;
+
+    impl MemoryAccessor for CustomPlatformFunctions {}
+
+    impl PlatformFunctions for CustomPlatformFunctions {
+        fn page_size(&self) -> usize {
+            0x10000
+        }
+
+        fn unprotect_memory(&self, _: usize, _: usize) -> Result<(), MemoryProtectionError> {
+            Ok(())
+        }
+
+        fn restore_memory_protection(
+            &self,
+            _: usize,
+            _: usize,
+        ) -> Result<(), MemoryProtectionError> {
+            Ok(())
+        }
+
+        fn clear_instruction_cache(&self, _: usize, _: usize) {}
+    }
+
+    #[test]
+    fn register_only_succeeds_once() {
+        let slot = Once::new();
+        assert!(try_register(&slot, &CustomPlatformFunctions));
+        assert!(!try_register(&slot, &DefaultPlatformFunctions));
+        assert_eq!(slot.get().unwrap().page_size(), 0x10000);
+    }
+
+    #[test]
+    fn default_page_size_is_power_of_two() {
+        assert!(DefaultPlatformFunctions.page_size().is_power_of_two());
+    }
+
+    #[test]
+    fn default_writes_atomically() {
+        let mut value = 0u64;
+        unsafe {
+            DefaultPlatformFunctions
+                .write_memory_atomic(
+                    &mut value as *mut u64 as usize,
+                    &0x1122334455667788u64.to_ne_bytes(),
+                )
+                .unwrap();
+        }
+
+        assert_eq!(value, 0x1122334455667788);
+    }
+}
@@ -1,5 +1,6 @@
 extern crate alloc;
 
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
 use alloc::string::String;
 use core::mem::size_of;
 use mach::port::mach_port_name_t;
@@ -93,29 +94,79 @@ pub fn restore_write_xor_execute(address: *const u8, size: usize, protection: us
     }
 }
 
-/// Removes protection from a memory region.
+/// Removes protection from a memory page.
 /// This makes it such that existing game code can be safely overwritten.
 ///
 /// # Parameters
 ///
-/// - `address`: The address of the memory to disable write XOR execute protection for.
-/// - `size`: The size of the memory to disable write XOR execute protection for.
+/// - `page`: The page aligned address of the memory to make writeable.
 ///
 /// # Returns
 ///
-/// Success or error.
-pub fn unprotect_memory(address: *const u8, size: usize) {
+/// The previous protection of the page.
+pub fn unprotect_page(page: usize) -> Result<Option<usize>, MemoryProtectionError> {
     unsafe {
-        let result = mach_vm_protect(
+        let mut region_address = page as mach_vm_address_t;
+        let mut region_size = page_size() as mach_vm_size_t;
+        let mut region_info = core::mem::zeroed::<vm_region_basic_info_data_t>();
+        let mut object_name: mach_port_name_t = 0;
+        let mut count = (size_of::<vm_region_basic_info_data_t>() / size_of::<integer_t>()) as u32;
+        let result = mach_vm_region(
             mach_task_self(),
-            address as u64,
-            size as mach_vm_size_t,
+            &mut region_address,
+            &mut region_size,
+            VM_REGION_BASIC_INFO,
+            &mut region_info as *mut _ as vm_region_info_t,
+            &mut count,
+            &mut object_name,
+        );
+
+        // mach_vm_region returns the next region if the page itself is not mapped.
+        if result != KERN_SUCCESS || region_address > page as mach_vm_address_t {
+            return Err(MemoryProtectionError::QueryFailed(page));
+        }
+
+        let protect_result = mach_vm_protect(
+            mach_task_self(),
+            page as u64,
+            page_size() as mach_vm_size_t,
             0,
             VM_PROT_READ | VM_PROT_WRITE | VM_PROT_EXECUTE,
         );
 
+        if protect_result != KERN_SUCCESS {
+            return Err(MemoryProtectionError::UnprotectFailed(page));
+        }
+
+        Ok(Some(region_info.protection as usize))
+    }
+}
+
+/// Restores the protection of a memory page.
+///
+/// # Parameters
+///
+/// - `page`: The page aligned address of the memory to restore protection for.
+/// - `protection`: The protection returned by [`unprotect_page`].
+pub fn restore_page(page: usize, protection: usize) -> Result<(), MemoryProtectionError> {
+    unsafe {
+        let result = mach_vm_protect(
+            mach_task_self(),
+            page as u64,
+            page_size() as mach_vm_size_t,
+            0,
+            protection as i32,
+        );
+
         if result != KERN_SUCCESS {
-            panic!("Failed to unprotect memory");
+            return Err(MemoryProtectionError::RestoreFailed(page));
         }
     }
+
+    Ok(())
+}
+
+/// Returns the size of a memory page.
+pub fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
 }
@@ -13,14 +13,9 @@ use mmap_rs_with_map_from_existing::{MemoryArea, Mmap, MmapOptions};
 /// - `address`: The address of the memory to disable write XOR execute protection for.
 /// - `size`: The size of the memory to disable write XOR execute protection for.
 ///
-/// # Returns
+/// # Remarks
 ///
-/// The old memory protection (if needed for call to [`self::reprotect_memory`]).
-/// If the value returns `None`, then reprotect_memory will not be called.
-///
-/// # Returns
-///
-/// Success or error.
+/// The previous protection is unknown, so the memory is left unprotected.
 pub fn unprotect_memory_mmap_rs(address: *const u8, size: usize) {
     // Make map object from existing memory region
     let map = unsafe {
@@ -1,10 +1,11 @@
 extern crate alloc;
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
 use libc::c_void;
 use mmap_rs_with_map_from_existing::MmapOptions;
 
 static mut PAGE_SIZE: Option<usize> = None;
 
-fn page_size() -> usize {
+pub(crate) fn page_size() -> usize {
     unsafe {
         if PAGE_SIZE.is_none() {
             PAGE_SIZE = Some(MmapOptions::page_size());
@@ -13,28 +14,218 @@ fn page_size() -> usize {
     }
 }
 
-/// Removes protection from a memory region.
+/// Removes protection from a memory page.
 /// This makes it such that existing game code can be safely overwritten.
 ///
 /// # Parameters
 ///
-/// - `address`: The address of the memory to disable write XOR execute protection for.
-/// - `size`: The size of the memory to disable write XOR execute protection for.
+/// - `page`: The page aligned address of the memory to make writeable.
 ///
 /// # Returns
 ///
-/// Success or error.
-pub fn unprotect_memory(address: *const u8, size: usize) {
-    unsafe {
-        let rounded_down = address as usize / page_size() * page_size();
-        let result = libc::mprotect(
-            rounded_down as *mut c_void,
-            size,
+/// The previous protection of the page (as `PROT_` flags), or `None` if it can't be determined
+/// on this platform.
+pub fn unprotect_page(page: usize) -> Result<Option<usize>, MemoryProtectionError> {
+    let protection = get_page_protection(page)?;
+    let result = unsafe {
+        libc::mprotect(
+            page as *mut c_void,
+            page_size(),
             libc::PROT_READ | libc::PROT_WRITE | libc::PROT_EXEC,
+        )
+    };
+
+    if result != 0 {
+        return Err(MemoryProtectionError::UnprotectFailed(page));
+    }
+
+    Ok(protection)
+}
+
+/// Restores the protection of a memory page.
+///
+/// # Parameters
+///
+/// - `page`: The page aligned address of the memory to restore protection for.
+/// - `protection`: The protection returned by [`unprotect_page`].
+pub fn restore_page(page: usize, protection: usize) -> Result<(), MemoryProtectionError> {
+    let result = unsafe { libc::mprotect(page as *mut c_void, page_size(), protection as i32) };
+    if result != 0 {
+        return Err(MemoryProtectionError::RestoreFailed(page));
+    }
+
+    Ok(())
+}
+
+/// Reads the current protection of a page from `/proc/self/maps`.
+#[cfg(target_os = "linux")]
+fn get_page_protection(page: usize) -> Result<Option<usize>, MemoryProtectionError> {
+    use crate::api::platforms::linux_proc_maps::{find_region, parse_proc_maps, read_proc_maps};
+
+    let maps = read_proc_maps().ok_or(MemoryProtectionError::QueryFailed(page))?;
+    let regions = parse_proc_maps(&maps);
+    let region = find_region(&regions, page).ok_or(MemoryProtectionError::QueryFailed(page))?;
+    Ok(Some(region.protection as usize))
+}
+
+/// Other unix platforms have no standard way to query protection; so the page is left writeable.
+#[cfg(not(target_os = "linux"))]
+fn get_page_protection(_page: usize) -> Result<Option<usize>, MemoryProtectionError> {
+    Ok(None)
+}
+
+/// Number of flags which can be returned by [`allocate_thread_local_flag`].
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+const THREAD_LOCAL_FLAG_COUNT: usize = 64;
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+static NEXT_THREAD_LOCAL_FLAG: core::sync::atomic::AtomicUsize =
+    core::sync::atomic::AtomicUsize::new(0);
+
+// Stable Rust can't declare a `#[thread_local]` in `no_std`, so we declare the TLS block by hand.
+// 64 pointer sized flags, zero initialised in every thread.
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+core::arch::global_asm!(
+    ".pushsection .tbss,\"awT\",%nobits",
+    ".p2align 3",
+    "reloaded_hooks_thread_local_flags:",
+    ".zero 512",
+    ".popsection",
+);
+
+/// Allocates a pointer sized flag in the static TLS block of the current module.
+///
+/// # Returns
+///
+/// Offset of the flag relative to the thread pointer, or `None` if all flags are in use.
+///
+/// # Remarks
+///
+/// The flags are accessed with the 'initial exec' TLS model, so the offset is the same in every
+/// thread. Libraries loaded with `dlopen` rely on the C library reserving spare static TLS for this,
+/// which glibc and musl do.
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+pub fn allocate_thread_local_flag() -> Option<isize> {
+    use core::sync::atomic::Ordering;
+
+    let index = NEXT_THREAD_LOCAL_FLAG.fetch_add(1, Ordering::Relaxed);
+    if index >= THREAD_LOCAL_FLAG_COUNT {
+        return None;
+    }
+
+    let base: isize;
+    unsafe {
+        #[cfg(target_arch = "x86_64")]
+        core::arch::asm!(
+            "mov {}, qword ptr [rip + reloaded_hooks_thread_local_flags@GOTTPOFF]",
+            out(reg) base,
+            options(nostack, readonly, pure)
         );
 
-        if result != 0 {
-            panic!("Failed to unprotect memory");
+        #[cfg(target_arch = "aarch64")]
+        core::arch::asm!(
+            "adrp {0}, :gottprel:reloaded_hooks_thread_local_flags",
+            "ldr {0}, [{0}, #:gottprel_lo12:reloaded_hooks_thread_local_flags]",
+            out(reg) base,
+            options(nostack, readonly, pure)
+        );
+    }
+
+    Some(base + (index * core::mem::size_of::<usize>()) as isize)
+}
+
+/// Other platforms don't have a supported way to access TLS from generated code.
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+pub fn allocate_thread_local_flag() -> Option<isize> {
+    None
+}
+
+#[cfg(test)]
+#[cfg(target_os = "linux")]
+mod tests {
+    use super::*;
+    use crate::api::platforms::linux_proc_maps::{find_region, parse_proc_maps, read_proc_maps};
+    use crate::api::platforms::platform_functions::{restore_memory_protection, unprotect_memory};
+    use core::ptr::null_mut;
+    use libc::{MAP_ANONYMOUS, MAP_PRIVATE, PROT_EXEC, PROT_READ, PROT_WRITE};
+
+    fn get_protection(address: usize) -> i32 {
+        let regions = parse_proc_maps(&read_proc_maps().unwrap());
+        find_region(&regions, address).unwrap().protection
+    }
+
+    #[test]
+    fn restores_protection_of_straddled_pages() {
+        let page_size = page_size();
+        unsafe {
+            let map = libc::mmap(
+                null_mut(),
+                page_size * 2,
+                PROT_READ | PROT_EXEC,
+                MAP_PRIVATE | MAP_ANONYMOUS,
+                -1,
+                0,
+            ) as usize;
+            libc::mprotect((map + page_size) as *mut c_void, page_size, PROT_READ);
+
+            let address = map + page_size - 2;
+            unprotect_memory(address as *const u8, 4).unwrap();
+            assert_eq!(get_protection(map), PROT_READ | PROT_WRITE | PROT_EXEC);
+            assert_eq!(
+                get_protection(map + page_size),
+                PROT_READ | PROT_WRITE | PROT_EXEC
+            );
+
+            (address as *mut u32).write_unaligned(0xCCCCCCCC);
+            restore_memory_protection(address as *const u8, 4).unwrap();
+
+            assert_eq!(get_protection(map), PROT_READ | PROT_EXEC);
+            assert_eq!(get_protection(map + page_size), PROT_READ);
+            assert_eq!((address as *const u32).read_unaligned(), 0xCCCCCCCC);
+            libc::munmap(map as *mut c_void, page_size * 2);
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn thread_local_flag_is_per_thread() {
+        fn get_flag(offset: isize) -> *mut usize {
+            let thread_pointer: isize;
+            unsafe {
+                core::arch::asm!("mov {}, qword ptr fs:[0]", out(reg) thread_pointer);
+            }
+            (thread_pointer + offset) as *mut usize
+        }
+
+        let first = allocate_thread_local_flag().unwrap();
+        let second = allocate_thread_local_flag().unwrap();
+        assert_eq!(second - first, size_of::<usize>() as isize);
+
+        unsafe {
+            assert_eq!(0, *get_flag(first));
+            *get_flag(first) = 1;
+
+            let in_other_thread = std::thread::spawn(move || *get_flag(first) as u32)
+                .join()
+                .unwrap();
+
+            assert_eq!(0, in_other_thread);
+            assert_eq!(1, *get_flag(first));
         }
     }
 }
@@ -1,32 +1,106 @@
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
 use core::ffi::c_void;
 
 use windows::Win32::System::Memory::{
     VirtualProtect, PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS,
 };
+use windows::Win32::System::SystemInformation::{GetSystemInfo, GetTickCount64, SYSTEM_INFO};
+use windows::Win32::System::Threading::{TlsAlloc, TlsFree, TLS_OUT_OF_INDEXES};
 
-/// Removes protection from a memory region.
+/// Removes protection from a memory page.
 /// This makes it such that existing game code can be safely overwritten.
 ///
 /// # Parameters
 ///
-/// - `address`: The address of the memory to disable write XOR execute protection for.
-/// - `size`: The size of the memory to disable write XOR execute protection for.
+/// - `page`: The page aligned address of the memory to make writeable.
 ///
 /// # Returns
 ///
-/// Success or error.
-pub fn unprotect_memory(address: *const u8, size: usize) {
+/// The previous protection of the page.
+pub fn unprotect_page(page: usize) -> Result<Option<usize>, MemoryProtectionError> {
     let mut oldprot: PAGE_PROTECTION_FLAGS = PAGE_PROTECTION_FLAGS::default();
     unsafe {
         let result = VirtualProtect(
-            address as *const c_void,
-            size,
+            page as *const c_void,
+            page_size(),
             PAGE_EXECUTE_READWRITE,
             (&mut oldprot) as *mut PAGE_PROTECTION_FLAGS,
         );
 
         if result == false {
-            panic!("Failed to unprotect memory");
+            return Err(MemoryProtectionError::UnprotectFailed(page));
         }
     }
+
+    Ok(Some(oldprot.0 as usize))
+}
+
+/// Restores the protection of a memory page.
+///
+/// # Parameters
+///
+/// - `page`: The page aligned address of the memory to restore protection for.
+/// - `protection`: The protection returned by [`unprotect_page`].
+pub fn restore_page(page: usize, protection: usize) -> Result<(), MemoryProtectionError> {
+    let mut oldprot: PAGE_PROTECTION_FLAGS = PAGE_PROTECTION_FLAGS::default();
+    unsafe {
+        let result = VirtualProtect(
+            page as *const c_void,
+            page_size(),
+            PAGE_PROTECTION_FLAGS(protection as u32),
+            (&mut oldprot) as *mut PAGE_PROTECTION_FLAGS,
+        );
+
+        if result == false {
+            return Err(MemoryProtectionError::RestoreFailed(page));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the size of a memory page.
+pub fn page_size() -> usize {
+    let mut info = SYSTEM_INFO::default();
+    unsafe { GetSystemInfo(&mut info) };
+    info.dwPageSize as usize
+}
+
+/// Number of TLS slots stored directly in the TEB, which can be accessed from generated code.
+const TEB_TLS_SLOT_COUNT: u32 = 64;
+
+/// Allocates a TLS slot with `TlsAlloc`, which is zeroed in every thread.
+///
+/// # Returns
+///
+/// Offset of the slot in the TEB (`TlsSlots`), or `None` if no slot in the TEB is free.
+pub fn allocate_thread_local_flag() -> Option<isize> {
+    // The TEB is not pointed to by tpidr_el0 on ARM64.
+    if cfg!(target_arch = "aarch64") {
+        return None;
+    }
+
+    unsafe {
+        let index = TlsAlloc();
+        if index == TLS_OUT_OF_INDEXES {
+            return None;
+        }
+
+        // Slots past the first 64 live in a separately allocated array.
+        if index >= TEB_TLS_SLOT_COUNT {
+            TlsFree(index);
+            return None;
+        }
+
+        #[cfg(target_pointer_width = "64")]
+        return Some(0x1480 + index as isize * 8);
+
+        #[cfg(target_pointer_width = "32")]
+        return Some(0xE10 + index as isize * 4);
+    }
+}
+
+/// Returns the number of milliseconds elapsed since the system was started.
+pub fn get_monotonic_time_ms() -> u64 {
+    unsafe { GetTickCount64() }
 }
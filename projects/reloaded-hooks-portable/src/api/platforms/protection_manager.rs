@@ -0,0 +1,250 @@
+extern crate alloc;
+
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
+use alloc::vec::Vec;
+use derive_new::new;
+use spin::Mutex;
+
+/// Pages currently unprotected by [`unprotect_pages`], sorted by address.
+static UNPROTECTED_PAGES: Mutex<Vec<UnprotectedPage>> = Mutex::new(Vec::new());
+
+/// Changes the protection of individual memory pages.
+pub(crate) trait PageProtector {
+    /// Returns the size of a memory page.
+    fn page_size() -> usize;
+
+    /// Makes the page starting at `page` readable, writeable and executable.
+    ///
+    /// # Returns
+    ///
+    /// The protection the page had before the call, or `None` if it could not be determined,
+    /// in which case the page is never restored.
+    fn unprotect_page(page: usize) -> Result<Option<usize>, MemoryProtectionError>;
+
+    /// Sets the protection of the page starting at `page` to a value returned by
+    /// [`PageProtector::unprotect_page`].
+    fn restore_page(page: usize, protection: usize) -> Result<(), MemoryProtectionError>;
+}
+
+/// A page made writeable by [`unprotect_pages`].
+#[derive(Debug, Clone, PartialEq, Eq, new)]
+pub(crate) struct UnprotectedPage {
+    /// Address of the start of the page.
+    pub address: usize,
+
+    /// Protection of the page before it was first unprotected.
+    pub protection: Option<usize>,
+
+    /// Number of [`unprotect_pages`] calls which have not yet been matched by [`restore_pages`].
+    pub ref_count: usize,
+}
+
+/// Makes every page overlapping `address..address + size` writeable, until a matching call to
+/// [`restore_memory`].
+///
+/// Pages are reference counted, so patches sharing a page may run concurrently; the original
+/// protection is restored once the last of them finishes.
+pub(crate) fn unprotect_memory<T: PageProtector>(
+    address: usize,
+    size: usize,
+) -> Result<(), MemoryProtectionError> {
+    unprotect_pages::<T>(&mut UNPROTECTED_PAGES.lock(), address, size)
+}
+
+/// Restores the protection of memory previously made writeable with [`unprotect_memory`].
+pub(crate) fn restore_memory<T: PageProtector>(
+    address: usize,
+    size: usize,
+) -> Result<(), MemoryProtectionError> {
+    restore_pages::<T>(&mut UNPROTECTED_PAGES.lock(), address, size)
+}
+
+/// Implementation of [`unprotect_memory`] operating on the given list of pages.
+pub(crate) fn unprotect_pages<T: PageProtector>(
+    pages: &mut Vec<UnprotectedPage>,
+    address: usize,
+    size: usize,
+) -> Result<(), MemoryProtectionError> {
+    let page_size = T::page_size();
+    let first_page = address & !(page_size - 1);
+    let end = address + size.max(1);
+
+    let mut page = first_page;
+    while page < end {
+        if let Err(e) = unprotect_page::<T>(pages, page) {
+            // Undo the pages we've already unprotected, so the caller doesn't have to.
+            let _ = restore_pages::<T>(pages, first_page, page - first_page);
+            return Err(e);
+        }
+
+        page += page_size;
+    }
+
+    Ok(())
+}
+
+/// Implementation of [`restore_memory`] operating on the given list of pages.
+///
+/// All pages are restored, even if one fails; in which case the first error is returned.
+pub(crate) fn restore_pages<T: PageProtector>(
+    pages: &mut Vec<UnprotectedPage>,
+    address: usize,
+    size: usize,
+) -> Result<(), MemoryProtectionError> {
+    let page_size = T::page_size();
+    let end = address + size;
+
+    let mut result = Ok(());
+    let mut page = address & !(page_size - 1);
+    while page < end {
+        if let Ok(index) = pages.binary_search_by_key(&page, |x| x.address) {
+            pages[index].ref_count -= 1;
+            if pages[index].ref_count == 0 {
+                if let Some(protection) = pages.remove(index).protection {
+                    let restored = T::restore_page(page, protection);
+                    result = result.and(restored);
+                }
+            }
+        }
+
+        page += page_size;
+    }
+
+    result
+}
+
+fn unprotect_page<T: PageProtector>(
+    pages: &mut Vec<UnprotectedPage>,
+    page: usize,
+) -> Result<(), MemoryProtectionError> {
+    match pages.binary_search_by_key(&page, |x| x.address) {
+        Ok(index) => pages[index].ref_count += 1,
+        Err(index) => {
+            let protection = T::unprotect_page(page)?;
+            pages.insert(index, UnprotectedPage::new(page, protection, 1));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+    use std::thread_local;
+
+    const PAGE_SIZE: usize = 0x1000;
+    const FAILING_PAGE: usize = 0xDEAD000;
+
+    thread_local! {
+        /// Current protection of each page touched by [`MockProtector`].
+        static PROTECTIONS: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    const RWX: usize = 7;
+    const RX: usize = 5;
+
+    struct MockProtector;
+
+    impl MockProtector {
+        fn get(page: usize) -> usize {
+            PROTECTIONS.with_borrow(|x| {
+                x.iter()
+                    .find(|(address, _)| *address == page)
+                    .map_or(RX, |(_, protection)| *protection)
+            })
+        }
+
+        fn set(page: usize, protection: usize) {
+            PROTECTIONS.with_borrow_mut(|x| {
+                x.retain(|(address, _)| *address != page);
+                x.push((page, protection));
+            })
+        }
+    }
+
+    impl PageProtector for MockProtector {
+        fn page_size() -> usize {
+            PAGE_SIZE
+        }
+
+        fn unprotect_page(page: usize) -> Result<Option<usize>, MemoryProtectionError> {
+            if page == FAILING_PAGE {
+                return Err(MemoryProtectionError::UnprotectFailed(page));
+            }
+
+            let old = Self::get(page);
+            Self::set(page, RWX);
+            Ok(Some(old))
+        }
+
+        fn restore_page(page: usize, protection: usize) -> Result<(), MemoryProtectionError> {
+            Self::set(page, protection);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn restores_original_protection() {
+        let mut pages = Vec::new();
+        MockProtector::set(0x1000, 1);
+
+        unprotect_pages::<MockProtector>(&mut pages, 0x1010, 4).unwrap();
+        assert_eq!(MockProtector::get(0x1000), RWX);
+
+        restore_pages::<MockProtector>(&mut pages, 0x1010, 4).unwrap();
+        assert_eq!(MockProtector::get(0x1000), 1);
+        assert!(pages.is_empty());
+    }
+
+    #[test]
+    fn covers_write_straddling_page_boundary() {
+        let mut pages = Vec::new();
+        MockProtector::set(0x2000, 1);
+
+        unprotect_pages::<MockProtector>(&mut pages, 0x1FFE, 4).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(MockProtector::get(0x1000), RWX);
+        assert_eq!(MockProtector::get(0x2000), RWX);
+        assert_eq!(MockProtector::get(0x3000), RX);
+
+        // Each page gets its own previous protection back.
+        restore_pages::<MockProtector>(&mut pages, 0x1FFE, 4).unwrap();
+        assert_eq!(MockProtector::get(0x1000), RX);
+        assert_eq!(MockProtector::get(0x2000), 1);
+    }
+
+    #[test]
+    fn shared_pages_are_reference_counted() {
+        let mut pages = Vec::new();
+
+        unprotect_pages::<MockProtector>(&mut pages, 0x1000, 4).unwrap();
+        unprotect_pages::<MockProtector>(&mut pages, 0x1FFE, 4).unwrap();
+        assert_eq!(pages[0], UnprotectedPage::new(0x1000, Some(RX), 2));
+        assert_eq!(pages[1], UnprotectedPage::new(0x2000, Some(RX), 1));
+
+        // First patch finishes, page is still in use by second patch.
+        restore_pages::<MockProtector>(&mut pages, 0x1000, 4).unwrap();
+        assert_eq!(MockProtector::get(0x1000), RWX);
+
+        restore_pages::<MockProtector>(&mut pages, 0x1FFE, 4).unwrap();
+        assert_eq!(MockProtector::get(0x1000), RX);
+        assert_eq!(MockProtector::get(0x2000), RX);
+        assert!(pages.is_empty());
+    }
+
+    #[test]
+    fn failure_rolls_back_unprotected_pages() {
+        let mut pages = Vec::new();
+        MockProtector::set(FAILING_PAGE - PAGE_SIZE, 1);
+        let result = unprotect_pages::<MockProtector>(&mut pages, FAILING_PAGE - 2, 4);
+
+        assert_eq!(
+            result,
+            Err(MemoryProtectionError::UnprotectFailed(FAILING_PAGE))
+        );
+        assert_eq!(MockProtector::get(FAILING_PAGE - PAGE_SIZE), 1);
+        assert!(pages.is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+extern crate alloc;
+
+use super::platform_abstractions::{MemoryAccessor, PlatformFunctions};
+use super::platform_functions::register_platform_functions;
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
+use alloc::vec;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+/// Size of a page in [`SimulatedMemory`].
+const SIMULATED_PAGE_SIZE: usize = 4096;
+
+/// The instance registered with [`register_simulated_memory`].
+static SIMULATED_MEMORY: SimulatedMemory = SimulatedMemory::new();
+
+/// Registers the [`SimulatedMemory`] returned by [`get_simulated_memory`] as the memory in which
+/// all hooks are created.
+///
+/// Use [`SimulatedBufferFactory`] as the buffer factory for hooks created in simulated memory.
+///
+/// # Returns
+///
+/// `true` on success; `false` if a platform backend has already been registered or used.
+///
+/// [`SimulatedBufferFactory`]: crate::api::buffers::simulated_buffer_factory::SimulatedBufferFactory
+pub fn register_simulated_memory() -> bool {
+    register_platform_functions(&SIMULATED_MEMORY)
+}
+
+/// Returns the memory registered with [`register_simulated_memory`].
+pub fn get_simulated_memory() -> &'static SimulatedMemory {
+    &SIMULATED_MEMORY
+}
+
+/// A fake address space, which exists only as a list of byte arrays in the current process.
+///
+/// Code for any architecture can be 'hooked' in this memory, as it is never executed.
+/// This allows hook creation for every architecture to be tested end to end on any host;
+/// by placing the code to hook with [`SimulatedMemory::map_code`], creating the hook, then
+/// inspecting the produced code with [`SimulatedMemory::read`].
+///
+/// # Remarks
+///
+/// Like on a real OS, code mapped via [`SimulatedMemory::map_code`] can only be written between
+/// calls to [`PlatformFunctions::unprotect_memory`] and
+/// [`PlatformFunctions::restore_memory_protection`], which affect whole pages.
+/// Accessing unmapped or protected memory panics.
+///
+/// Mapped regions are always followed by an unmapped page, to catch out of bounds accesses.
+/// Memory is never unmapped.
+pub struct SimulatedMemory {
+    state: Mutex<SimulatedMemoryState>,
+}
+
+struct SimulatedMemoryState {
+    regions: Vec<SimulatedRegion>,
+    unprotected: Vec<(usize, usize)>,
+    reserved: Vec<(usize, usize)>,
+}
+
+struct SimulatedRegion {
+    address: usize,
+    data: Vec<u8>,
+    writeable: bool,
+}
+
+impl SimulatedRegion {
+    fn end(&self) -> usize {
+        self.address + self.data.len()
+    }
+
+    fn contains(&self, address: usize, len: usize) -> bool {
+        address >= self.address && address + len <= self.end()
+    }
+
+    /// Returns true if this region, or the guard page after it, overlaps `[start, end)`.
+    fn overlaps(&self, start: usize, end: usize) -> bool {
+        start < self.end() + SIMULATED_PAGE_SIZE && self.address < end
+    }
+}
+
+impl SimulatedMemoryState {
+    /// Returns the first free address at or after `address`, where `size` bytes can be mapped.
+    fn find_free(&self, address: usize, size: usize) -> usize {
+        let mut address = align_up(address, SIMULATED_PAGE_SIZE);
+        loop {
+            let end = address + size + SIMULATED_PAGE_SIZE;
+            if let Some(region) = self
+                .regions
+                .iter()
+                .find(|region| region.overlaps(address, end))
+            {
+                address = region.end() + SIMULATED_PAGE_SIZE;
+            } else if let Some(&(_, reserved_end)) = self
+                .reserved
+                .iter()
+                .find(|&&(start, reserved_end)| start < end && address < reserved_end)
+            {
+                address = align_up(reserved_end, SIMULATED_PAGE_SIZE);
+            } else {
+                return address;
+            }
+        }
+    }
+
+    /// Maps a region of `size` bytes (rounded up to pages) at the first free address at or after
+    /// `address`.
+    fn map(&mut self, address: usize, size: usize, writeable: bool) -> &mut SimulatedRegion {
+        let size = align_up(size.max(1), SIMULATED_PAGE_SIZE);
+        let address = self.find_free(address, size);
+        self.regions.push(SimulatedRegion {
+            address,
+            data: vec![0; size],
+            writeable,
+        });
+
+        self.regions.last_mut().unwrap()
+    }
+
+    fn find(&mut self, address: usize, len: usize) -> &mut SimulatedRegion {
+        self.regions
+            .iter_mut()
+            .find(|region| region.contains(address, len))
+            .unwrap_or_else(|| panic!("Access to unmapped simulated memory at {:#X}", address))
+    }
+
+    fn is_unprotected(&self, address: usize, len: usize) -> bool {
+        self.unprotected
+            .iter()
+            .any(|&(start, end)| address >= start && address + len <= end)
+    }
+}
+
+impl SimulatedMemory {
+    /// Creates an empty address space.
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(SimulatedMemoryState {
+                regions: Vec::new(),
+                unprotected: Vec::new(),
+                reserved: Vec::new(),
+            }),
+        }
+    }
+
+    /// Maps read + execute memory containing `code`, i.e. the code to be hooked.
+    ///
+    /// # Parameters
+    ///
+    /// - `address`: Page aligned address to map the code at.
+    ///
+    /// # Panics
+    ///
+    /// If the memory (or the page after it) is already mapped.
+    pub fn map_code(&self, address: usize, code: &[u8]) {
+        let mut state = self.state.lock();
+        let region = state.map(address, code.len(), false);
+        assert_eq!(
+            region.address, address,
+            "Simulated memory is already mapped"
+        );
+        region.data[..code.len()].copy_from_slice(code);
+    }
+
+    /// Maps `size` bytes of zeroed, read + write + execute memory, e.g. for a buffer.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: The memory is mapped at the first free page at or after this address.
+    ///
+    /// # Returns
+    ///
+    /// The address of the memory.
+    pub fn allocate(&self, target: usize, size: usize) -> usize {
+        self.state.lock().map(target, size, true).address
+    }
+
+    /// Maps `size` bytes of zeroed, read + write + execute memory, if free memory is available
+    /// within `proximity` bytes of `target`.
+    ///
+    /// # Returns
+    ///
+    /// The address of the memory, or [`None`] if nothing was mapped.
+    pub fn allocate_near(&self, target: usize, size: usize, proximity: usize) -> Option<usize> {
+        let mut state = self.state.lock();
+        let size = align_up(size.max(1), SIMULATED_PAGE_SIZE);
+        let address = state.find_free(target, size);
+        if (address + size).abs_diff(target) > proximity {
+            return None;
+        }
+
+        Some(state.map(address, size, true).address)
+    }
+
+    /// Prevents anything from being mapped in `[address, address + size)`, without mapping it.
+    ///
+    /// Use this to simulate memory near the code to hook being in use,
+    /// e.g. to place buffers out of relative jump range.
+    pub fn reserve(&self, address: usize, size: usize) {
+        self.state.lock().reserved.push((address, address + size));
+    }
+
+    /// Reads `len` bytes at `address`.
+    pub fn read(&self, address: usize, len: usize) -> Vec<u8> {
+        let mut state = self.state.lock();
+        let region = state.find(address, len);
+        let offset = address - region.address;
+        region.data[offset..offset + len].to_vec()
+    }
+
+    /// Returns the number of regions currently unprotected via [`PlatformFunctions::unprotect_memory`].
+    pub fn num_unprotected_regions(&self) -> usize {
+        self.state.lock().unprotected.len()
+    }
+}
+
+impl Default for SimulatedMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryAccessor for SimulatedMemory {
+    unsafe fn read_memory(
+        &self,
+        address: usize,
+        out: &mut [u8],
+    ) -> Result<(), MemoryProtectionError> {
+        out.copy_from_slice(&self.read(address, out.len()));
+        Ok(())
+    }
+
+    unsafe fn write_memory(
+        &self,
+        address: usize,
+        data: &[u8],
+    ) -> Result<(), MemoryProtectionError> {
+        let mut state = self.state.lock();
+        let unprotected = state.is_unprotected(address, data.len());
+        let region = state.find(address, data.len());
+        assert!(
+            region.writeable || unprotected,
+            "Write to protected simulated memory at {:#X}",
+            address
+        );
+
+        let offset = address - region.address;
+        region.data[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    unsafe fn write_memory_atomic(
+        &self,
+        address: usize,
+        data: &[u8],
+    ) -> Result<(), MemoryProtectionError> {
+        // Memory is behind a lock, so no reader can observe a partial write.
+        self.write_memory(address, data)
+    }
+}
+
+impl PlatformFunctions for SimulatedMemory {
+    fn page_size(&self) -> usize {
+        SIMULATED_PAGE_SIZE
+    }
+
+    fn unprotect_memory(&self, address: usize, size: usize) -> Result<(), MemoryProtectionError> {
+        let mut state = self.state.lock();
+        state.find(address, size);
+        state.unprotected.push(page_range(address, size));
+        Ok(())
+    }
+
+    fn restore_memory_protection(
+        &self,
+        address: usize,
+        size: usize,
+    ) -> Result<(), MemoryProtectionError> {
+        let mut state = self.state.lock();
+        let index = state
+            .unprotected
+            .iter()
+            .position(|&range| range == page_range(address, size))
+            .expect("Restored protection of memory which was never unprotected");
+
+        state.unprotected.swap_remove(index);
+        Ok(())
+    }
+
+    /// Simulated code is never executed, so there is nothing to flush.
+    fn clear_instruction_cache(&self, _start: usize, _end: usize) {}
+}
+
+/// Returns the `(start, end)` of the pages containing `size` bytes at `address`.
+/// Protection is changed with page granularity, like on a real OS.
+fn page_range(address: usize, size: usize) -> (usize, usize) {
+    (
+        address & !(SIMULATED_PAGE_SIZE - 1),
+        align_up(address + size, SIMULATED_PAGE_SIZE),
+    )
+}
+
+fn align_up(value: usize, alignment: usize) -> usize {
+    (value + alignment - 1) & !(alignment - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_reads_and_writes_memory() {
+        let memory = SimulatedMemory::new();
+        memory.map_code(0x1000, &[0x90, 0xC3]);
+        let buffer = memory.allocate(0x1000, 16);
+
+        assert_eq!(buffer, 0x3000); // after 1 page of code, and 1 guard page
+        assert_eq!(memory.allocate(0x1000, 16), 0x5000);
+        assert_eq!(memory.allocate(0x8000, 16), 0x8000);
+        assert_eq!(memory.allocate_near(0x8000, 16, 0x1000), None);
+        assert_eq!(memory.allocate_near(0x8000, 16, 0x3000), Some(0xA000));
+        assert_eq!(memory.read(0x1000, 3), [0x90, 0xC3, 0x00]);
+
+        unsafe {
+            memory.write_memory(buffer + 1, &[1, 2, 3]).unwrap();
+            let mut result = [0xFFu8; 5];
+            memory.read_memory(buffer, &mut result).unwrap();
+            assert_eq!(result, [0, 1, 2, 3, 0]);
+        }
+    }
+
+    #[test]
+    fn does_not_map_reserved_memory() {
+        let memory = SimulatedMemory::new();
+        memory.reserve(0x2000, 0x3000);
+
+        assert_eq!(memory.allocate(0x1000, 16), 0x5000);
+        assert_eq!(memory.allocate_near(0x1000, 16, 0x2000), None);
+    }
+
+    #[test]
+    fn code_is_writeable_only_when_unprotected() {
+        let memory = SimulatedMemory::new();
+        let code = 0x1000;
+        memory.map_code(code, &[0x90, 0xC3]);
+
+        memory.unprotect_memory(code, 2).unwrap();
+        assert_eq!(memory.num_unprotected_regions(), 1);
+
+        // Whole page is unprotected.
+        unsafe { memory.write_memory_atomic(code + 1, &[0xCC; 8]).unwrap() };
+        memory.restore_memory_protection(code, 2).unwrap();
+
+        assert_eq!(memory.num_unprotected_regions(), 0);
+        assert_eq!(
+            memory.read(code, 10),
+            [0x90, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 extern crate alloc;
+use super::foreign_hook::ForeignHook;
 use alloc::string::String;
 use alloc::vec::Vec;
 use thiserror_no_std::Error;
@@ -107,6 +108,36 @@ pub trait CodeRewriter<TRegister> {
 
     /// Returns the maximum number of bytes that a single instruction can increase in size
     fn max_ins_size_increase() -> usize;
+
+    /// Returns the length of the branch target landing pad at `address` (e.g. `bti c` on ARM64),
+    /// or 0 if the code does not start with one.
+    ///
+    /// Landing pads are left in place when hooking, rather than relocated, such that the hooked
+    /// code can still be reached via indirect branches when branch target enforcement is active.
+    ///
+    /// # Safety
+    ///
+    /// `address` must point to readable code, read via the registered `MemoryAccessor`.
+    unsafe fn landing_pad_length(_address: usize) -> usize {
+        0
+    }
+
+    /// Returns the hook placed at `address` by another hooking library, if the code there
+    /// starts with one of the jumps such libraries commonly emit.
+    ///
+    /// Such jumps are not relocated as regular code when hooking, see
+    /// [`ForeignHookBehaviour`] for the available options.
+    ///
+    /// The default implementation performs no analysis and always returns `None`.
+    ///
+    /// # Safety
+    ///
+    /// `address` must point to readable code, read via the registered `MemoryAccessor`.
+    ///
+    /// [`ForeignHookBehaviour`]: crate::api::settings::assembly_hook_settings::ForeignHookBehaviour
+    unsafe fn find_foreign_hook(_address: usize) -> Option<ForeignHook> {
+        None
+    }
 }
 
 /// Errors that can occur during JIT compilation.
@@ -133,4 +164,8 @@ pub enum CodeRewriterError {
 
     #[error("Third party assembler error: {0:?}")]
     ThirdPartyAssemblerError(String),
+
+    /// Failed to re-encode an instruction at the new address.
+    #[error("Failed to Re-encode Instruction. Instruction offset: {0:?}, Instruction Bytes: {1:?}, Reason: {2:?}")]
+    FailedToReencode(usize, String, String),
 }
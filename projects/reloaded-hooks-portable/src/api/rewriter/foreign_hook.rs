@@ -0,0 +1,17 @@
+use derive_new::new;
+
+/// A jump placed at the start of some code by another hooking library,
+/// e.g. MinHook, Detours, Dobby, frida-gum or older versions of Reloaded.Hooks.
+///
+/// Returned by [`CodeRewriter::find_foreign_hook`].
+///
+/// [`CodeRewriter::find_foreign_hook`]: crate::api::rewriter::code_rewriter::CodeRewriter::find_foreign_hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub struct ForeignHook {
+    /// Length of the foreign hook in bytes.
+    /// This includes any data embedded after the jump, e.g. the address of an absolute jump.
+    pub length: usize,
+
+    /// Address the foreign hook jumps to.
+    pub target: usize,
+}
@@ -45,8 +45,29 @@ where
     /// This is only required if platform does not support 'Targeted Memory Allocation', i.e. more
     /// esoteric platforms.
     pub scratch_register: Option<TRegister>,
+
+    /// Amount of bytes after the overwritten code to scan for branches back into the overwritten code.
+    ///
+    /// If such a branch is found, [`AssemblyHookSettings::branch_into_stolen_region_behaviour`]
+    /// decides what happens. 0 (the default) disables the check.
+    ///
+    /// The scanned bytes are read as-is, so they must be readable; e.g. don't scan past the end of
+    /// the function if it may be the last thing in its memory region.
+    pub branch_scan_length: usize,
+
+    /// What to do if a branch into the overwritten code is found.
+    /// Only used if [`AssemblyHookSettings::branch_scan_length`] is not 0.
+    pub branch_into_stolen_region_behaviour: BranchIntoStolenRegionBehaviour,
+
+    /// What to do if the code at [`AssemblyHookSettings::hook_address`] starts with a hook placed
+    /// by another library, e.g. MinHook or Detours.
+    pub foreign_hook_behaviour: ForeignHookBehaviour,
 }
 
+/// Default value of [`AssemblyHookSettings::branch_scan_length`].
+/// Scanning is opt-in, as the bytes after the hooked code may not be readable.
+pub const DEFAULT_BRANCH_SCAN_LENGTH: usize = 0;
+
 impl<TRegister> AssemblyHookSettings<TRegister>
 where
     TRegister: Clone + Copy,
@@ -73,6 +94,9 @@ where
             behaviour: AsmHookBehaviour::ExecuteFirst,
             auto_activate: true,
             scratch_register: None,
+            branch_scan_length: DEFAULT_BRANCH_SCAN_LENGTH,
+            branch_into_stolen_region_behaviour: BranchIntoStolenRegionBehaviour::Fail,
+            foreign_hook_behaviour: ForeignHookBehaviour::ChainInFront,
         }
     }
 
@@ -99,6 +123,9 @@ where
             behaviour,
             auto_activate: true,
             scratch_register: None,
+            branch_scan_length: DEFAULT_BRANCH_SCAN_LENGTH,
+            branch_into_stolen_region_behaviour: BranchIntoStolenRegionBehaviour::Fail,
+            foreign_hook_behaviour: ForeignHookBehaviour::ChainInFront,
         }
     }
 
@@ -125,6 +152,9 @@ where
             behaviour: AsmHookBehaviour::ExecuteFirst,
             auto_activate: true,
             scratch_register: None,
+            branch_scan_length: DEFAULT_BRANCH_SCAN_LENGTH,
+            branch_into_stolen_region_behaviour: BranchIntoStolenRegionBehaviour::Fail,
+            foreign_hook_behaviour: ForeignHookBehaviour::ChainInFront,
         }
     }
 
@@ -153,6 +183,9 @@ where
             behaviour,
             auto_activate: true,
             scratch_register: None,
+            branch_scan_length: DEFAULT_BRANCH_SCAN_LENGTH,
+            branch_into_stolen_region_behaviour: BranchIntoStolenRegionBehaviour::Fail,
+            foreign_hook_behaviour: ForeignHookBehaviour::ChainInFront,
         }
     }
 
@@ -169,6 +202,51 @@ where
         self.scratch_register = Some(register);
         self
     }
+
+    /// Sets the amount of bytes scanned for branches into the overwritten code and returns the modified instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - Amount of bytes to scan after the overwritten code, 0 to disable the check.
+    ///
+    /// # Returns
+    ///
+    /// Returns the AssemblyHookSettings instance with the scan length set, allowing for method chaining.
+    pub fn with_branch_scan_length(mut self, length: usize) -> Self {
+        self.branch_scan_length = length;
+        self
+    }
+
+    /// Sets what to do if a branch into the overwritten code is found and returns the modified instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `behaviour` - Whether to fail, or hook the code at the branch target instead.
+    ///
+    /// # Returns
+    ///
+    /// Returns the AssemblyHookSettings instance with the behaviour set, allowing for method chaining.
+    pub fn with_branch_into_stolen_region_behaviour(
+        mut self,
+        behaviour: BranchIntoStolenRegionBehaviour,
+    ) -> Self {
+        self.branch_into_stolen_region_behaviour = behaviour;
+        self
+    }
+
+    /// Sets what to do if the hooked code starts with a hook placed by another library and returns the modified instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `behaviour` - Whether to chain in front of the foreign hook, or hook the code it jumps to.
+    ///
+    /// # Returns
+    ///
+    /// Returns the AssemblyHookSettings instance with the behaviour set, allowing for method chaining.
+    pub fn with_foreign_hook_behaviour(mut self, behaviour: ForeignHookBehaviour) -> Self {
+        self.foreign_hook_behaviour = behaviour;
+        self
+    }
 }
 
 /// Defines the behaviour used by the `AssemblyHook`.
@@ -183,3 +261,44 @@ pub enum AsmHookBehaviour {
     /// Do not execute original replaced code. (Dangerous!)
     DoNotExecuteOriginal,
 }
+
+/// Defines how the `AssemblyHook` handles a branch from nearby code into the middle of the code it
+/// would overwrite, e.g. the head of a loop at the start of a function.
+///
+/// See [`AssemblyHookSettings::branch_scan_length`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchIntoStolenRegionBehaviour {
+    /// Fail hook creation with [`HookBuilderError::BranchIntoStolenRegion`].
+    ///
+    /// [`HookBuilderError::BranchIntoStolenRegion`]: crate::api::errors::hook_builder_error::HookBuilderError::BranchIntoStolenRegion
+    Fail,
+
+    /// Place the hook at the branch target instead, which is the next instruction boundary that
+    /// is safe to overwrite. Repeated until no branch lands inside the overwritten code.
+    ///
+    /// The instructions between [`AssemblyHookSettings::hook_address`] and the new hook address
+    /// run before your code, and [`AssemblyHookSettings::max_permitted_bytes`] shrinks by their
+    /// length. If it runs out, hook creation fails as with [`Self::Fail`].
+    HookAtBranchTarget,
+}
+
+/// Defines how the `AssemblyHook` handles code which already starts with a hook placed by another
+/// library (MinHook, Detours, Dobby, frida-gum, older Reloaded.Hooks, ...).
+///
+/// Foreign hooks are recognised by [`CodeRewriter::find_foreign_hook`].
+///
+/// [`CodeRewriter::find_foreign_hook`]: crate::api::rewriter::code_rewriter::CodeRewriter::find_foreign_hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignHookBehaviour {
+    /// Replace the foreign hook's jump with the jump to your code, which then jumps to wherever
+    /// the foreign hook went. i.e. Your code runs first, followed by the foreign hook.
+    ///
+    /// Only the bytes of the foreign hook are overwritten, as the foreign library may jump back
+    /// to the code right after them. If the jump to your code does not fit, the foreign hook is
+    /// instead relocated like any other code.
+    ChainInFront,
+
+    /// Follow the foreign hook's jump(s) and hook the code they lead to instead,
+    /// i.e. Your code runs once the foreign hook continues execution.
+    HookContinuation,
+}
@@ -3,7 +3,7 @@ use derive_new::new;
 use super::basic_hook_settings::BasicHookSettings;
 use crate::api::{
     calling_convention_info::CallingConventionInfo, function_info::FunctionInfo,
-    traits::register_info::RegisterInfo,
+    hooks::caller_filter::CallerFilter, traits::register_info::RegisterInfo,
 };
 
 /// Common hook settings for hooks
@@ -47,6 +47,48 @@ where
     /// This is useful for example when the target function is your own method when hooking
     /// and you want to inject a 'this' pointer.
     pub injected_parameter: Option<usize>,
+
+    /// Whether the wrapper should count the threads currently executing the hook function.
+    ///
+    /// This allows [`CommonHook::disable_and_wait`] to wait for all calls to the hook function to
+    /// return, e.g. before unloading the library containing it. When enabled, a wrapper is always
+    /// generated, even if the calling conventions match.
+    ///
+    /// [`CommonHook::disable_and_wait`]: crate::api::hooks::common_hook::CommonHook::disable_and_wait
+    #[new(default)]
+    pub track_in_flight_calls: bool,
+
+    /// Whether the wrapper should skip the hook function when the current thread is already
+    /// executing it, calling the original function instead.
+    ///
+    /// This prevents infinite recursion when the hook function (indirectly) calls the hooked
+    /// function, e.g. a `malloc` hook which logs. When enabled, a wrapper is always generated,
+    /// even if the calling conventions match.
+    ///
+    /// # Remarks
+    ///
+    /// Uses a thread local flag allocated with [`PlatformFunctions::allocate_thread_local_flag`];
+    /// creating the hook fails on platforms which don't support it, or once all flags are in use.
+    /// The flag is freed when the hook is uninstalled, see [`CommonHook::with_uninstall_on_drop`].
+    ///
+    /// [`PlatformFunctions::allocate_thread_local_flag`]: crate::api::platforms::platform_abstractions::PlatformFunctions::allocate_thread_local_flag
+    /// [`CommonHook::with_uninstall_on_drop`]: crate::api::hooks::common_hook::CommonHook::with_uninstall_on_drop
+    #[new(default)]
+    pub reentrancy_guard: bool,
+
+    /// If this parameter is specified, the hook function is only called when the hooked function
+    /// is called from code in one of the filter's address ranges. Calls from anywhere else go to
+    /// the original function. When set, a wrapper is always generated.
+    ///
+    /// # Remarks
+    ///
+    /// The caller is determined from the return address, i.e. `[esp]`/`[rsp]` on x86 and `lr`
+    /// on ARM64, so calls reached through a tail call (`jmp`) are attributed to the caller of the
+    /// function which made the jump.
+    ///
+    /// The ranges can be changed while the hook is active with [`CallerFilter::set_ranges`].
+    #[new(default)]
+    pub caller_filter: Option<&'static CallerFilter>,
 }
 
 impl<'a, TRegister, TFunctionInfo, TFunctionAttribute>
@@ -60,9 +102,46 @@ where
     ///
     /// # Returns
     ///
-    /// `true` if the calling conventions of the source and target are different,
-    /// indicating that a wrapper is required. Otherwise, `false`.
+    /// `true` if the calling conventions of the source and target are different, or the wrapper
+    /// needs to do extra work, indicating that a wrapper is required. Otherwise, `false`.
     pub fn needs_wrapper(&self) -> bool {
-        self.injected_parameter.is_some() || self.conv_source != self.conv_target
+        self.injected_parameter.is_some()
+            || self.track_in_flight_calls
+            || self.reentrancy_guard
+            || self.caller_filter.is_some()
+            || self.conv_source != self.conv_target
+    }
+
+    /// Enables counting of the threads executing the hook function and returns the modified instance.
+    /// See [`FunctionHookSettings::track_in_flight_calls`].
+    ///
+    /// # Returns
+    ///
+    /// Returns the FunctionHookSettings instance with tracking enabled, allowing for method chaining.
+    pub fn with_in_flight_tracking(mut self) -> Self {
+        self.track_in_flight_calls = true;
+        self
+    }
+
+    /// Enables the re-entrancy guard and returns the modified instance.
+    /// See [`FunctionHookSettings::reentrancy_guard`].
+    ///
+    /// # Returns
+    ///
+    /// Returns the FunctionHookSettings instance with the guard enabled, allowing for method chaining.
+    pub fn with_reentrancy_guard(mut self) -> Self {
+        self.reentrancy_guard = true;
+        self
+    }
+
+    /// Only calls the hook function for callers in the given filter's address ranges, and returns
+    /// the modified instance. See [`FunctionHookSettings::caller_filter`].
+    ///
+    /// # Returns
+    ///
+    /// Returns the FunctionHookSettings instance with the filter set, allowing for method chaining.
+    pub fn with_caller_filter(mut self, filter: &'static CallerFilter) -> Self {
+        self.caller_filter = Some(filter);
+        self
     }
 }
@@ -87,6 +87,17 @@ pub trait RegisterInfo {
     where
         Self: Sized;
 
+    /// True if the register only exists on processors supporting an optional instruction set
+    /// extension, such as `r16`-`r31` on x64, which require Intel APX.
+    ///
+    /// These registers are only used as scratch registers by calling conventions which opt into
+    /// them, see [`CallingConventionInfo::uses_extension_registers`].
+    ///
+    /// [`CallingConventionInfo::uses_extension_registers`]: crate::api::calling_convention_info::CallingConventionInfo::uses_extension_registers
+    fn requires_extension(&self) -> bool {
+        false
+    }
+
     /// Finds a register with the same type as the given register.
     ///
     /// # Arguments
@@ -25,7 +25,12 @@ use crate::{
 use alloc::vec::Vec;
 use alloc::{rc::Rc, string::ToString};
 use core::cell::RefCell;
-use core::{hash::Hash, mem::size_of, slice};
+use core::{
+    hash::Hash,
+    mem::{align_of, size_of},
+    slice,
+};
+use derive_new::new;
 use smallvec::SmallVec;
 
 /// Overkill in practice, but just in case, any leftover memory at end of buffers will
@@ -46,7 +51,10 @@ use smallvec::SmallVec;
 /// not require the generation of a wrapper, they would be inlined or use the default call convention.
 ///
 /// Who would pass 40 parameters to a function anyway !?
-pub const MAX_WRAPPER_LENGTH: usize = 192;
+///
+/// Backing up callee saved registers comes on top of that; e.g. `xmm6` - `xmm15` on Microsoft x64 take
+/// around 100 bytes, and even more if pushed via `sub` + `mov` (see `multipushpop` feature of x86 crate).
+pub const MAX_WRAPPER_LENGTH: usize = 256;
 
 /// Options and additional context necessary for the wrapper generator.
 #[derive(Clone, Copy)]
@@ -90,6 +98,59 @@ where
     /// required for the stack in case the last pushed item during callee save is larger
     /// than the standard register size.
     pub standard_register_size: usize,
+
+    /// If this parameter is specified, the wrapper atomically increments the pointer sized integer
+    /// at this address on entry, and decrements it before returning.
+    ///
+    /// # Remarks
+    ///
+    /// This counts the threads executing the wrapper (and thus the target function), such that
+    /// hooks can wait for calls to finish before the target function is unloaded.
+    pub in_flight_counter: Option<usize>,
+
+    /// If this parameter is specified, the wrapper skips the called function when the current
+    /// thread is already executing it, jumping to [`ReentrancyGuardOptions::bypass_address`] instead.
+    ///
+    /// # Remarks
+    ///
+    /// This prevents infinite recursion when the hook function (indirectly) calls the function
+    /// it is hooking, e.g. when hooking `malloc`.
+    pub reentrancy_guard: Option<ReentrancyGuardOptions>,
+
+    /// If this parameter is specified, the wrapper only calls the called function when the return
+    /// address of its caller is in one of the given address ranges, and jumps to
+    /// [`CallerFilterOptions::bypass_address`] otherwise.
+    ///
+    /// # Remarks
+    ///
+    /// This is checked before anything else, so calls from other code are not counted or guarded.
+    pub caller_filter: Option<CallerFilterOptions>,
+}
+
+/// Settings for the re-entrancy guard emitted by the wrapper generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub struct ReentrancyGuardOptions {
+    /// Offset of the pointer sized thread local flag, relative to the thread pointer.
+    /// See [`PlatformFunctions::allocate_thread_local_flag`].
+    ///
+    /// [`PlatformFunctions::allocate_thread_local_flag`]: crate::api::platforms::platform_abstractions::PlatformFunctions::allocate_thread_local_flag
+    pub tls_offset: isize,
+
+    /// Address jumped to when the thread is already inside the wrapper; usually the original function.
+    pub bypass_address: usize,
+}
+
+/// Settings for the caller filter emitted by the wrapper generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub struct CallerFilterOptions {
+    /// Address of the pointer to the table of address ranges.
+    /// See [`CallerFilter::table_pointer`].
+    ///
+    /// [`CallerFilter::table_pointer`]: crate::api::hooks::caller_filter::CallerFilter::table_pointer
+    pub table_pointer: usize,
+
+    /// Address jumped to when the caller is not in any range; usually the original function.
+    pub bypass_address: usize,
 }
 
 /// Creates a new instance of WrapperInstructionGeneratorOptions with the given parameters.
@@ -130,6 +191,9 @@ where
         target_address,
         function_info,
         injected_parameter,
+        in_flight_counter: None,
+        reentrancy_guard: None,
+        caller_filter: None,
     }
 }
 
@@ -285,7 +349,9 @@ pub fn generate_wrapper_instructions<
 
     // Re-push stack parameters of function returned (right to left)
     let num_params = options.function_info.parameters().len();
-    let returned_stack_params_size = (size_of::<ParameterType>() * num_params);
+    // Rounded up, so the register parameters which follow are aligned.
+    let returned_stack_params_size = (size_of::<ParameterType>() * num_params)
+        .next_multiple_of(align_of::<(ParameterType, TRegister)>());
     let returned_reg_params_size = (size_of::<(ParameterType, TRegister)>() * num_params);
     let mut setup_params_ops = SmallVec::<[Operation<TRegister>; 32]>::new_const();
     let mut callee_cleanup_return_size = 0;
@@ -316,7 +382,7 @@ pub fn generate_wrapper_instructions<
         // To get our 'true' scratch registers.
         (*scratch_registers)
             .borrow_mut()
-            .retain(|&f| !fn_returned_params.1.iter().any(|reg| f == reg.1));
+            .retain(|&f| !fn_returned_params.1.iter().any(|reg| f == reg.1.extend()));
 
         /*
             Context [x64 as example].
@@ -442,6 +508,17 @@ pub fn generate_wrapper_instructions<
         ops.push(Mov::new(fn_called_return_reg, fn_returned_return_reg).into());
     }
 
+    // Move float return value to proper register, e.g. st0 (x87) -> xmm0 (SSE)
+    if let (Some(return_type), Some(called_reg), Some(returned_reg)) = (
+        options.function_info.return_type(),
+        conv_called.float_return_register(),
+        conv_current.float_return_register(),
+    ) {
+        if return_type.is_float() {
+            move_float_return_value(&mut ops, return_type, called_reg, returned_reg);
+        }
+    }
+
     // Fix the stack
     let stack_ofs = if conv_called.stack_cleanup_behaviour() == StackCleanup::Callee {
         stack_misalignment as isize
@@ -512,14 +589,125 @@ pub fn generate_wrapper_instructions<
         }
     }
 
+    // Count the threads executing the wrapper, and/or skip it when re-entered.
+    if options.in_flight_counter.is_some() || options.reentrancy_guard.is_some() {
+        let scratch = Rc::new(RefCell::new(get_entry_and_exit_scratch(conv_current)));
+        if let Some(counter) = options.in_flight_counter {
+            let return_idx = find_return_index(&ops);
+            ops.insert(
+                return_idx,
+                AtomicAdd::new(counter, -1, scratch.clone()).into(),
+            );
+            ops.insert(0, AtomicAdd::new(counter, 1, scratch.clone()).into());
+        }
+
+        // Guard goes outermost, such that a bypassed call is not counted.
+        if let Some(guard) = options.reentrancy_guard {
+            let return_idx = find_return_index(&ops);
+            ops.insert(
+                return_idx,
+                ExitGuard::new(guard.tls_offset, scratch.clone()).into(),
+            );
+            ops.insert(
+                0,
+                EnterGuard::new(guard.tls_offset, guard.bypass_address, scratch).into(),
+            );
+        }
+    }
+
+    // Filter goes before everything else, such that other callers only pay for the check.
+    if let Some(filter) = options.caller_filter {
+        let scratch = Rc::new(RefCell::new(get_entry_scratch(conv_current)));
+        ops.insert(
+            0,
+            FilterCaller::new(filter.table_pointer, filter.bypass_address, scratch).into(),
+        );
+    }
+
     Ok(ops)
 }
 
+/// Moves a float return value between the float return registers of two conventions.
+///
+/// Registers of different kinds (e.g. x87 `st0` and SSE `xmm0`) can't be moved between directly,
+/// so the value goes through a temporary stack slot instead.
+fn move_float_return_value<TRegister: RegisterInfo + Copy + PartialEq>(
+    ops: &mut Vec<Operation<TRegister>>,
+    return_type: ParameterType,
+    called_reg: TRegister,
+    returned_reg: TRegister,
+) {
+    if called_reg == returned_reg {
+        return;
+    }
+
+    if called_reg.register_type() == returned_reg.register_type() {
+        ops.push(Mov::new(called_reg, returned_reg).into());
+        return;
+    }
+
+    let size = return_type.size_in_bytes() as u32;
+    ops.push(StackAlloc::new(size as i32).into());
+    ops.push(MovFloatToStack::new(0, size, called_reg).into());
+    ops.push(MovFloatFromStack::new(0, size, returned_reg).into());
+    ops.push(StackAlloc::new(-(size as i32)).into());
+}
+
+/// Returns the index of the final return in the wrapper, before which cleanup is inserted.
+fn find_return_index<TRegister: Copy>(ops: &[Operation<TRegister>]) -> usize {
+    ops.iter()
+        .rposition(|op| matches!(op, Operation::Return(_)))
+        .unwrap_or(ops.len())
+}
+
+/// Returns the scratch registers which can be used by operations inserted both on entry and
+/// before returning; i.e. the in-flight counter and re-entrancy guard.
+///
+/// These are the caller saved registers, minus any register which may hold a parameter or
+/// (part of) the return value.
+fn get_entry_and_exit_scratch<
+    TRegister: RegisterInfo + Eq + Copy + 'static,
+    TFunctionAttribute: CallingConventionInfo<TRegister>,
+>(
+    conv_current: &TFunctionAttribute,
+) -> Vec<TRegister> {
+    let mut scratch = conv_current.caller_saved_registers();
+    scratch.retain(|reg| {
+        !reg.is_stack_pointer()
+            && *reg != conv_current.return_register()
+            && !conv_current.register_int_parameters().contains(reg)
+            && !conv_current.register_float_parameters().contains(reg)
+            && !conv_current.register_vector_parameters().contains(reg)
+    });
+    scratch
+}
+
+/// Returns the scratch registers which can be used by operations inserted only on entry;
+/// i.e. the caller filter.
+///
+/// These are the caller saved registers, minus any register which may hold a parameter.
+fn get_entry_scratch<
+    TRegister: RegisterInfo + Eq + Copy + 'static,
+    TFunctionAttribute: CallingConventionInfo<TRegister>,
+>(
+    conv_current: &TFunctionAttribute,
+) -> Vec<TRegister> {
+    let mut scratch = conv_current.caller_saved_registers();
+    scratch.retain(|reg| {
+        !reg.is_stack_pointer()
+            && !conv_current.register_int_parameters().contains(reg)
+            && !conv_current.register_float_parameters().contains(reg)
+            && !conv_current.register_vector_parameters().contains(reg)
+    });
+    scratch
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::api::jit::operation::Operation::MultiPush;
     use crate::{
-        api::function_info::ParameterType, helpers::test_helpers::MockRegister::*,
+        api::function_info::{BasicFunctionInfo, ParameterType},
+        helpers::test_helpers::MockRegister::*,
         helpers::test_helpers::*,
     };
 
@@ -554,6 +742,125 @@ pub mod tests {
         assert_eq!(vec[4], Return::new(0).into()); // caller cleanup, so no offset here
     }
 
+    #[test]
+    fn ms_thiscall_to_cdecl_optimized_with_in_flight_counter() {
+        let mock_function = MockFunction {
+            parameters: vec![ParameterType::nint, ParameterType::nint],
+        };
+
+        let mut options =
+            get_common_options(true, 4096, true, &mock_function, get_x86_jit_capabilities());
+        options.in_flight_counter = Some(0x2000);
+
+        let result = generate_wrapper_instructions(
+            &*THISCALL_LIKE_FUNCTION_ATTRIBUTE,
+            &*CDECL_LIKE_FUNCTION_ATTRIBUTE,
+            &options,
+        );
+
+        assert!(result.is_ok());
+        let vec: Vec<Operation<MockRegister>> = result.unwrap();
+        assert_eq!(vec.len(), 6);
+
+        // Increment on entry, decrement right before returning.
+        let Operation::AtomicAdd(increment) = &vec[0] else {
+            panic!("Expected AtomicAdd, got {:?}", vec[0]);
+        };
+        let Operation::AtomicAdd(decrement) = &vec[4] else {
+            panic!("Expected AtomicAdd, got {:?}", vec[4]);
+        };
+
+        assert_eq!((increment.address, increment.value), (0x2000, 1));
+        assert_eq!((decrement.address, decrement.value), (0x2000, -1));
+        assert_eq!(vec[3], CallRel::new(4096).into());
+        assert_eq!(vec[5], Return::new(0).into());
+
+        // Scratch must not clobber callee saved registers or the return value.
+        let scratch = increment.scratch.borrow();
+        assert!(!scratch.is_empty());
+        assert!(!scratch.contains(&R1));
+        assert!(!scratch.contains(&R3));
+        assert!(!scratch.contains(&SP));
+    }
+
+    #[test]
+    fn ms_thiscall_to_cdecl_optimized_with_reentrancy_guard() {
+        let mock_function = MockFunction {
+            parameters: vec![ParameterType::nint, ParameterType::nint],
+        };
+
+        let mut options =
+            get_common_options(true, 4096, true, &mock_function, get_x86_jit_capabilities());
+        options.in_flight_counter = Some(0x2000);
+        options.reentrancy_guard = Some(ReentrancyGuardOptions::new(-64, 0x3000));
+
+        let result = generate_wrapper_instructions(
+            &*THISCALL_LIKE_FUNCTION_ATTRIBUTE,
+            &*CDECL_LIKE_FUNCTION_ATTRIBUTE,
+            &options,
+        );
+
+        assert!(result.is_ok());
+        let vec: Vec<Operation<MockRegister>> = result.unwrap();
+        assert_eq!(vec.len(), 8);
+
+        // Guard is entered first and left last, so bypassed calls aren't counted.
+        let Operation::EnterGuard(enter) = &vec[0] else {
+            panic!("Expected EnterGuard, got {:?}", vec[0]);
+        };
+        let Operation::ExitGuard(exit) = &vec[6] else {
+            panic!("Expected ExitGuard, got {:?}", vec[6]);
+        };
+
+        assert_eq!((enter.tls_offset, enter.bypass_address), (-64, 0x3000));
+        assert_eq!(exit.tls_offset, -64);
+        assert!(matches!(&vec[1], Operation::AtomicAdd(x) if x.value == 1));
+        assert!(matches!(&vec[5], Operation::AtomicAdd(x) if x.value == -1));
+        assert_eq!(vec[7], Return::new(0).into());
+    }
+
+    #[test]
+    fn ms_thiscall_to_cdecl_optimized_with_caller_filter() {
+        let mock_function = MockFunction {
+            parameters: vec![ParameterType::nint, ParameterType::nint],
+        };
+
+        let mut options =
+            get_common_options(true, 4096, true, &mock_function, get_x86_jit_capabilities());
+        options.in_flight_counter = Some(0x2000);
+        options.reentrancy_guard = Some(ReentrancyGuardOptions::new(-64, 0x3000));
+        options.caller_filter = Some(CallerFilterOptions::new(0x4000, 0x3000));
+
+        let result = generate_wrapper_instructions(
+            &*THISCALL_LIKE_FUNCTION_ATTRIBUTE,
+            &*CDECL_LIKE_FUNCTION_ATTRIBUTE,
+            &options,
+        );
+
+        assert!(result.is_ok());
+        let vec: Vec<Operation<MockRegister>> = result.unwrap();
+        assert_eq!(vec.len(), 9);
+
+        // Filter comes before the guard, and has nothing to undo on return.
+        let Operation::FilterCaller(filter) = &vec[0] else {
+            panic!("Expected FilterCaller, got {:?}", vec[0]);
+        };
+
+        assert_eq!(
+            (filter.table_pointer, filter.bypass_address),
+            (0x4000, 0x3000)
+        );
+        assert!(matches!(&vec[1], Operation::EnterGuard(_)));
+        assert!(matches!(&vec[7], Operation::ExitGuard(_)));
+        assert_eq!(vec[8], Return::new(0).into());
+
+        // Return register is free on entry, but parameters and the stack pointer are not.
+        let scratch = filter.scratch.borrow();
+        assert!(scratch.contains(&R1));
+        assert!(!scratch.contains(&R3));
+        assert!(!scratch.contains(&SP));
+    }
+
     // X86-LIKE TESTS //
 
     #[test]
@@ -752,6 +1059,92 @@ pub mod tests {
         generate_wrapper_instructions(conv_called, conv_current, &options)
     }
 
+    #[test]
+    fn float_return_moved_between_register_kinds() {
+        // e.g. x87 st0 -> SSE xmm0
+        let called = MockFunctionAttribute {
+            float_return_reg: Some(F0),
+            ..CDECL_LIKE_FUNCTION_ATTRIBUTE.clone()
+        };
+        let current = MockFunctionAttribute {
+            float_return_reg: Some(V0),
+            ..CDECL_LIKE_FUNCTION_ATTRIBUTE.clone()
+        };
+
+        let vec = float_return(&called, &current, ParameterType::f64);
+        assert_eq!(
+            vec,
+            vec![
+                CallRel::new(4096).into(),
+                StackAlloc::new(8).into(),
+                MovFloatToStack::new(0, 8, F0).into(),
+                MovFloatFromStack::new(0, 8, V0).into(),
+                StackAlloc::new(-8).into(),
+                Return::new(0).into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn float_return_moved_between_same_register_kind() {
+        let called = MockFunctionAttribute {
+            float_return_reg: Some(F0),
+            ..CDECL_LIKE_FUNCTION_ATTRIBUTE.clone()
+        };
+        let current = MockFunctionAttribute {
+            float_return_reg: Some(F1),
+            ..CDECL_LIKE_FUNCTION_ATTRIBUTE.clone()
+        };
+
+        let vec = float_return(&called, &current, ParameterType::f32);
+        assert_eq!(
+            vec,
+            vec![
+                CallRel::new(4096).into(),
+                Mov::new(F0, F1).into(),
+                Return::new(0).into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn float_return_not_moved_for_integer_return() {
+        let called = MockFunctionAttribute {
+            float_return_reg: Some(F0),
+            ..CDECL_LIKE_FUNCTION_ATTRIBUTE.clone()
+        };
+        let current = MockFunctionAttribute {
+            float_return_reg: Some(V0),
+            ..CDECL_LIKE_FUNCTION_ATTRIBUTE.clone()
+        };
+
+        let vec = float_return(&called, &current, ParameterType::i32);
+        assert_eq!(vec, vec![CallRel::new(4096).into(), Return::new(0).into()]);
+    }
+
+    fn float_return(
+        conv_called: &MockFunctionAttribute,
+        conv_current: &MockFunctionAttribute,
+        return_type: ParameterType,
+    ) -> Vec<Operation<MockRegister>> {
+        let function = BasicFunctionInfo::new(&[]).with_return_type(return_type);
+        let options = WrapperInstructionGeneratorOptions {
+            stack_entry_alignment: size_of::<isize>(),
+            target_address: 4096,
+            standard_register_size: size_of::<isize>(),
+            function_info: &function,
+            injected_parameter: None,
+            jit_capabilities: get_x86_jit_capabilities(),
+            can_generate_relative_jumps: true,
+            enable_optimizations: true,
+            in_flight_counter: None,
+            reentrancy_guard: None,
+            caller_filter: None,
+        };
+
+        generate_wrapper_instructions(conv_called, conv_current, &options).unwrap()
+    }
+
     fn get_common_options(
         optimized: bool,
         target_address: usize,
@@ -768,6 +1161,9 @@ pub mod tests {
             jit_capabilities: capabilties,
             can_generate_relative_jumps: can_generate_relative,
             enable_optimizations: optimized,
+            in_flight_counter: None,
+            reentrancy_guard: None,
+            caller_filter: None,
         }
     }
 
@@ -5,6 +5,7 @@ use core::cell::RefCell;
 use core::hash::{BuildHasherDefault, Hash};
 use hashbrown::HashSet;
 use nohash::NoHashHasher;
+use smallvec::SmallVec;
 
 use crate::api::jit::operation::Operation;
 use crate::api::jit::operation_aliases::*;
@@ -30,15 +31,25 @@ pub fn optimize_moves<TRegister>(
     scratch_registers: &[TRegister],
 ) -> Option<Vec<Operation<TRegister>>>
 where
-    TRegister: Eq + PartialEq + Hash + Copy + RegisterInfo,
+    TRegister: Eq + PartialEq + Hash + Copy + RegisterInfo + 'static,
 {
+    // Registers which overlap (e.g. xmm0 & ymm0) are the same node, else writing one could
+    // clobber the other before it is read. Sizes are restored once the order is known.
+    let extended: SmallVec<[Mov<TRegister>; 16]> = moves
+        .iter()
+        .map(|mov| Mov {
+            source: mov.source.extend(),
+            target: mov.target.extend(),
+        })
+        .collect();
+
     // Check if the moves are already valid.
-    if (moves.is_empty()) || validate_moves(moves) {
+    if (moves.is_empty()) || validate_moves(&extended) {
         return None;
     }
 
     let mut results = Vec::<Operation<TRegister>>::with_capacity(moves.len() * 2);
-    let graph = move_graph_builder::build_graph(moves);
+    let graph = move_graph_builder::build_graph(&extended);
     let mut visited: HashSet<TRegister, BuildHasherDefault<NoHashHasher<u32>>> =
         HashSet::with_capacity_and_hasher(graph.len(), BuildHasherDefault::default());
     let mut node_stack: Vec<Rc<RefCell<Node<TRegister>>>> = Vec::with_capacity(graph.len());
@@ -56,9 +67,68 @@ where
         }
     }
 
+    for op in results.iter_mut() {
+        restore_register_sizes(op, moves);
+    }
+
     Some(results)
 }
 
+/// Replaces the extended registers in an operation emitted by [`optimize_moves`] with
+/// the registers used in the original moves.
+///
+/// Registers which are not part of an original move (scratch registers, and the moves to and
+/// from them when breaking a cycle) are sized to the widest original register of their type,
+/// so a cycle is saved and restored as a whole.
+fn restore_register_sizes<TRegister: Eq + Copy + RegisterInfo + 'static>(
+    op: &mut Operation<TRegister>,
+    moves: &[Mov<TRegister>],
+) {
+    match op {
+        Operation::Mov(mov) => {
+            let original = moves
+                .iter()
+                .find(|x| x.source.extend() == mov.source && x.target.extend() == mov.target);
+
+            match original {
+                Some(original) => *mov = *original,
+                None => {
+                    mov.source = widest_original(mov.source, moves);
+                    mov.target = widest_original(mov.target, moves);
+                }
+            }
+        }
+        Operation::Push(push) => push.register = widest_original(push.register, moves),
+        Operation::Pop(pop) => pop.register = widest_original(pop.register, moves),
+        Operation::Xchg(xchg) => {
+            xchg.register1 = widest_original(xchg.register1, moves);
+            xchg.register2 = widest_original(xchg.register2, moves);
+            xchg.scratch = xchg.scratch.map(|x| widest_original(x, moves));
+        }
+        _ => {}
+    }
+}
+
+/// Returns the register which extends to `extended`, and is as large as the largest register
+/// of the same type used in `moves`. Returns `extended` itself if there is none.
+fn widest_original<TRegister: Eq + Copy + RegisterInfo + 'static>(
+    extended: TRegister,
+    moves: &[Mov<TRegister>],
+) -> TRegister {
+    let size = moves
+        .iter()
+        .flat_map(|mov| [mov.source, mov.target])
+        .filter(|reg| reg.extend().register_type() == extended.register_type())
+        .map(|reg| reg.size_in_bytes())
+        .max();
+
+    TRegister::all_registers()
+        .iter()
+        .find(|reg| reg.extend() == extended && Some(reg.size_in_bytes()) == size)
+        .copied()
+        .unwrap_or(extended)
+}
+
 fn dfs<TRegister: Eq + Hash + Copy + RegisterInfo>(
     node: &Rc<RefCell<Node<TRegister>>>,
     visited: &mut HashSet<TRegister, BuildHasherDefault<NoHashHasher<u32>>>,
@@ -134,6 +204,13 @@ fn dfs<TRegister: Eq + Hash + Copy + RegisterInfo>(
                 }));
             }
 
+            return;
+        } else {
+            // The neighbour's moves were already emitted from an earlier node, so it can be
+            // written to now. Treat it as a leaf.
+            rec_stack.push(neighbour.clone());
+            unwind(rec_stack, results);
+            rec_stack.pop();
             return;
         }
     }
@@ -290,4 +367,41 @@ pub mod tests {
             ]
         );
     }
+
+    #[test]
+    fn when_chain_entered_midway_emits_all_moves() {
+        let moves = vec![
+            Mov {
+                source: R2,
+                target: R1,
+            },
+            Mov {
+                source: R1,
+                target: R0,
+            },
+            Mov {
+                source: R3,
+                target: R2,
+            },
+        ];
+
+        let new_operations = optimize_moves(&moves, &[]).unwrap();
+        assert_eq!(
+            new_operations,
+            vec![
+                Operation::Mov(Mov {
+                    source: R1,
+                    target: R0,
+                }),
+                Operation::Mov(Mov {
+                    source: R2,
+                    target: R1,
+                }),
+                Operation::Mov(Mov {
+                    source: R3,
+                    target: R2,
+                }),
+            ]
+        );
+    }
 }
@@ -15,6 +15,7 @@ use core::{
 };
 use portable_atomic::AtomicU128;
 
+use super::read_code::read_code_as;
 use crate::api::buffers::buffer_abstractions::Buffer;
 
 /// Performs an atomic write of value in `src` to `tgt`.
@@ -71,27 +72,30 @@ pub unsafe fn atomic_swap<TBuffer: Buffer>(heap_src: *mut u8, buf_tgt: *mut u8,
     match size {
         1 => {
             let src_val = read_unaligned(heap_src);
-            write_unaligned(heap_src, read_unaligned(buf_tgt));
+            write_unaligned(heap_src, read_code_as::<u8>(buf_tgt as usize));
             TBuffer::overwrite_atomic(buf_tgt as usize, src_val);
         }
         2 => {
             let src_val = read_unaligned(heap_src as *mut u16);
-            write_unaligned(heap_src as *mut u16, read_unaligned(buf_tgt as *mut u16));
+            write_unaligned(heap_src as *mut u16, read_code_as::<u16>(buf_tgt as usize));
             TBuffer::overwrite_atomic(buf_tgt as usize, src_val);
         }
         4 => {
             let src_val = read_unaligned(heap_src as *mut u32);
-            write_unaligned(heap_src as *mut u32, read_unaligned(buf_tgt as *mut u32));
+            write_unaligned(heap_src as *mut u32, read_code_as::<u32>(buf_tgt as usize));
             TBuffer::overwrite_atomic(buf_tgt as usize, src_val);
         }
         8 => {
             let src_val = read_unaligned(heap_src as *mut u64);
-            write_unaligned(heap_src as *mut u64, read_unaligned(buf_tgt as *mut u64));
+            write_unaligned(heap_src as *mut u64, read_code_as::<u64>(buf_tgt as usize));
             TBuffer::overwrite_atomic(buf_tgt as usize, src_val);
         }
         16 => {
             let src_val = read_unaligned(heap_src as *mut u128);
-            write_unaligned(heap_src as *mut u128, read_unaligned(buf_tgt as *mut u128));
+            write_unaligned(
+                heap_src as *mut u128,
+                read_code_as::<u128>(buf_tgt as usize),
+            );
             TBuffer::overwrite_atomic(buf_tgt as usize, src_val);
         }
         _ => panic!("Unsupported size for atomic swap."),
@@ -1,8 +1,9 @@
-use crate::api::buffers::buffer_abstractions::Buffer;
-use core::{
-    hint::unreachable_unchecked,
-    ptr::{read_unaligned, write_unaligned},
+use crate::api::{
+    buffers::buffer_abstractions::Buffer, errors::memory_protection_error::MemoryProtectionError,
+    platforms::platform_functions::get_platform_functions,
 };
+use crate::helpers::read_code::read_code_as;
+use core::{hint::unreachable_unchecked, mem::size_of, ptr::read_unaligned, slice::from_raw_parts};
 
 pub trait AtomicWriter {
     /// Writes a native integer type to a given address atomically.
@@ -11,19 +12,32 @@ pub trait AtomicWriter {
     ///
     /// - `address`: The address to overwrite.
     /// - `value`: The value to write.
-    fn atomic_write<TInteger>(address: usize, value: TInteger)
+    ///
+    /// # Returns
+    ///
+    /// An error if the memory could not be written.
+    fn atomic_write<TInteger>(address: usize, value: TInteger) -> Result<(), MemoryProtectionError>
     where
         Self: Sized;
 }
 
+/// Writes through the registered [`PlatformFunctions`].
+///
+/// [`PlatformFunctions`]: crate::api::platforms::platform_abstractions::PlatformFunctions
 pub struct NativeMemoryAtomicWriter {}
 
 impl AtomicWriter for NativeMemoryAtomicWriter {
-    fn atomic_write<TInteger>(address: usize, value: TInteger)
+    fn atomic_write<TInteger>(address: usize, value: TInteger) -> Result<(), MemoryProtectionError>
     where
         Self: Sized,
     {
-        unsafe { write_unaligned(address as *mut TInteger, value) }
+        unsafe {
+            let bytes = from_raw_parts(
+                &value as *const TInteger as *const u8,
+                size_of::<TInteger>(),
+            );
+            get_platform_functions().write_memory_atomic(address, bytes)
+        }
     }
 }
 
@@ -32,11 +46,12 @@ impl<T> AtomicWriter for T
 where
     T: Buffer,
 {
-    fn atomic_write<TInteger>(address: usize, value: TInteger)
+    fn atomic_write<TInteger>(address: usize, value: TInteger) -> Result<(), MemoryProtectionError>
     where
         Self: Sized,
     {
         T::overwrite_atomic(address, value);
+        Ok(())
     }
 }
 
@@ -50,21 +65,25 @@ pub const MAX_ATOMIC_WRITE_BYTES: u8 = 16;
 ///
 /// Readable memory at 'address' must be at least 'num_bytes' rounded up to next power of 2 long.
 /// I.e. This may not work if at end of virtual address space.
+///
+/// # Returns
+///
+/// An error if the memory could not be written.
 #[inline]
-pub unsafe fn atomic_write_masked<TWriter>(address: usize, code: &[u8], num_bytes: usize)
+pub unsafe fn atomic_write_masked<TWriter>(
+    address: usize,
+    code: &[u8],
+    num_bytes: usize,
+) -> Result<(), MemoryProtectionError>
 where
     TWriter: AtomicWriter,
 {
     unsafe {
         match num_bytes {
-            1 => {
-                TWriter::atomic_write(address, *code.as_ptr());
-            }
-            2 => {
-                TWriter::atomic_write(address, read_unaligned(code.as_ptr() as *const u16));
-            }
+            1 => TWriter::atomic_write(address, *code.as_ptr()),
+            2 => TWriter::atomic_write(address, read_unaligned(code.as_ptr() as *const u16)),
             3..=4 => {
-                let existing_code = read_unaligned(address as *const u32);
+                let existing_code = read_code_as::<u32>(address);
                 let code = read_bytes_as_u32(code.as_ptr(), num_bytes);
                 let mask = match num_bytes {
                     3 => 0x00_FF_FF_FF_u32.to_le(),
@@ -73,10 +92,10 @@ where
                 };
 
                 let combined_code = (existing_code & !mask) | (code & mask);
-                TWriter::atomic_write(address, combined_code);
+                TWriter::atomic_write(address, combined_code)
             }
             5..=8 => {
-                let existing_code = read_unaligned(address as *const u64);
+                let existing_code = read_code_as::<u64>(address);
                 let code: u64 = read_bytes_as_u64(code.as_ptr(), num_bytes);
                 let mask: u64 = match num_bytes {
                     5 => 0x00_00_00_FF_FF_FF_FF_FF_u64.to_le(),
@@ -87,10 +106,10 @@ where
                 };
 
                 let combined_code = (existing_code & !mask) | (code & mask);
-                TWriter::atomic_write(address, combined_code);
+                TWriter::atomic_write(address, combined_code)
             }
             9..=16 => {
-                let existing_code = read_unaligned(address as *const u128);
+                let existing_code = read_code_as::<u128>(address);
                 let code: u128 = read_bytes_as_u128(code.as_ptr(), num_bytes);
                 let mask: u128 = match num_bytes {
                     9 => 0x00_00_00_00_00_00_00_FF_FF_FF_FF_FF_FF_FF_FF_FF_u128.to_le(),
@@ -105,7 +124,7 @@ where
                 };
 
                 let combined_code = (existing_code & !mask) | (code & mask);
-                TWriter::atomic_write(address, combined_code);
+                TWriter::atomic_write(address, combined_code)
             }
 
             _ => panic!("Unsupported num_bytes in atomic_overwrite_with_mask"),
@@ -210,7 +229,8 @@ mod tests {
                 let address = buffer.as_mut_ptr() as usize;
 
                 unsafe {
-                    atomic_write_masked::<NativeMemoryAtomicWriter>(address, &$input, $num_bytes);
+                    atomic_write_masked::<NativeMemoryAtomicWriter>(address, &$input, $num_bytes)
+                        .unwrap();
                 }
                 let result = &buffer[0..$num_bytes];
                 assert_eq!(result, $expected);
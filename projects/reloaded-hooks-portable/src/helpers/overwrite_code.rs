@@ -1,11 +1,9 @@
-use core::ptr::copy_nonoverlapping;
-
-use super::{
-    atomic_write_masked::{atomic_write_masked, NativeMemoryAtomicWriter, MAX_ATOMIC_WRITE_BYTES},
-    icache_clear::clear_instruction_cache,
+use super::atomic_write_masked::{
+    atomic_write_masked, NativeMemoryAtomicWriter, MAX_ATOMIC_WRITE_BYTES,
 };
-use crate::api::platforms::platform_functions::{
-    disable_write_xor_execute, restore_write_xor_execute, unprotect_memory,
+use crate::api::{
+    errors::memory_protection_error::MemoryProtectionError,
+    platforms::platform_functions::get_platform_functions,
 };
 
 /// Overwrites existing code in (.text) or equivalent region of native memory.
@@ -13,31 +11,52 @@ use crate::api::platforms::platform_functions::{
 /// #  Remarks
 ///
 /// - Assumes existing code is in region that may not currently have write permission.
+///   The original protection is restored after the write.
 ///
 /// handling edge cases such as write xor execute.
-/// and instruction cache invalidation.
-pub(crate) fn overwrite_code(address: usize, buffer: &[u8]) {
-    // No-op on non W^X platforms thanks to compiler optimizations.
-    let orig = disable_write_xor_execute(address as *const u8, buffer.len());
+/// and instruction cache invalidation (including on other cores).
+///
+/// All operations go through the registered [`PlatformFunctions`].
+///
+/// # Returns
+///
+/// Error if the memory could not be made writeable, in which case nothing is written; or if it
+/// could not be written.
+///
+/// [`PlatformFunctions`]: crate::api::platforms::platform_abstractions::PlatformFunctions
+pub(crate) fn overwrite_code(address: usize, buffer: &[u8]) -> Result<(), MemoryProtectionError> {
+    let platform = get_platform_functions();
+
+    // No-op on non W^X platforms.
+    let orig = platform.disable_write_xor_execute(address, buffer.len());
 
     // If this is not a W^X platform (none is returned), we unprotect the code region
     if orig.is_none() {
-        unprotect_memory(address as *const u8, buffer.len());
+        platform.unprotect_memory(address, buffer.len())?;
     }
 
-    unsafe {
+    let written = unsafe {
         // If the instructions are short, we can do it atomic! >w< enhancing our reliability.
         if buffer.len() <= MAX_ATOMIC_WRITE_BYTES as usize {
-            atomic_write_masked::<NativeMemoryAtomicWriter>(address, buffer, buffer.len());
+            atomic_write_masked::<NativeMemoryAtomicWriter>(address, buffer, buffer.len())
         } else {
-            copy_nonoverlapping(buffer.as_ptr(), address as *mut u8, buffer.len());
+            platform.write_memory(address, buffer)
         }
-    }
-
-    if let Some(orig_val) = orig {
-        restore_write_xor_execute(address as *const u8, buffer.len(), orig_val);
-    }
+    };
 
     // No-op on x86 platforms
-    clear_instruction_cache(address as *const u8, (address + buffer.len()) as *const u8);
+    platform.clear_instruction_cache(address, address + buffer.len());
+
+    // Make sure threads on other cores don't keep running the old code.
+    platform.synchronize_cores();
+
+    let restored = match orig {
+        Some(orig_val) => {
+            platform.restore_write_xor_execute(address, buffer.len(), orig_val);
+            Ok(())
+        }
+        None => platform.restore_memory_protection(address, buffer.len()),
+    };
+
+    written.and(restored)
 }
@@ -0,0 +1,80 @@
+extern crate alloc;
+
+use crate::api::errors::memory_protection_error::MemoryProtectionError;
+use crate::api::platforms::platform_functions::get_platform_functions;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::{size_of, zeroed};
+use core::slice::from_raw_parts_mut;
+
+/// Reads `len` bytes of code at `address`, through the registered [`MemoryAccessor`].
+///
+/// Use this instead of dereferencing `address`, as the code may live in another process.
+///
+/// # Remarks
+///
+/// If the memory can't be read, zeroes are returned. This is used when analysing code; the failure
+/// is reported when the hook reads the code it overwrites via [`try_read_code`], or writes it.
+///
+/// # Safety
+///
+/// `address` must point to `len` bytes of readable memory.
+///
+/// [`MemoryAccessor`]: crate::api::platforms::platform_abstractions::MemoryAccessor
+pub unsafe fn read_code(address: usize, len: usize) -> Vec<u8> {
+    try_read_code(address, len).unwrap_or_else(|_| vec![0; len])
+}
+
+/// Reads `len` bytes of code at `address`, through the registered [`MemoryAccessor`].
+///
+/// # Returns
+///
+/// The code, or an error if the memory could not be read.
+///
+/// # Safety
+///
+/// `address` must point to `len` bytes of readable memory.
+///
+/// [`MemoryAccessor`]: crate::api::platforms::platform_abstractions::MemoryAccessor
+pub unsafe fn try_read_code(address: usize, len: usize) -> Result<Vec<u8>, MemoryProtectionError> {
+    let mut result = vec![0; len];
+    get_platform_functions().read_memory(address, &mut result)?;
+    Ok(result)
+}
+
+/// Reads a value of type `T` at `address` (unaligned), through the registered [`MemoryAccessor`].
+///
+/// If the memory can't be read, a zeroed `T` is returned; see [`read_code`].
+///
+/// # Safety
+///
+/// `address` must point to `size_of::<T>()` bytes of readable memory, which are a valid `T`.
+/// All zero bytes must also be a valid `T`.
+///
+/// [`MemoryAccessor`]: crate::api::platforms::platform_abstractions::MemoryAccessor
+pub unsafe fn read_code_as<T: Copy>(address: usize) -> T {
+    let mut value = zeroed::<T>();
+    let bytes = from_raw_parts_mut(&mut value as *mut T as *mut u8, size_of::<T>());
+    if get_platform_functions()
+        .read_memory(address, bytes)
+        .is_err()
+    {
+        return zeroed();
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_local_code() {
+        let code = [0x48u8, 0x89, 0xC8, 0xC3];
+        unsafe {
+            assert_eq!(read_code(code.as_ptr() as usize, 4), code);
+            assert_eq!(read_code_as::<u16>(code.as_ptr() as usize + 1), 0xC889);
+        }
+    }
+}
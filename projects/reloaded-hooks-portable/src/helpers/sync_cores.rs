@@ -0,0 +1,156 @@
+//! Forces other cores to discard stale instructions after code has been modified.
+//!
+//! Flushing the instruction cache (see [`clear_instruction_cache`]) only affects the cache; other
+//! cores may still hold the old instructions in their pipelines. The ARM ARM requires that every
+//! core executing cross-modified code performs a context synchronization event (e.g. `ISB`), and
+//! Intel's SDM similarly requires a serializing instruction.
+//!
+//! On Linux, the `membarrier` syscall lets us force this onto every thread of the process.
+//!
+//! [`clear_instruction_cache`]: super::icache_clear::clear_instruction_cache
+
+/// Ensures all cores executing the current process see code modified before this call.
+///
+/// Call after writing code which may be executed by other threads.
+///
+/// # Remarks
+///
+/// This is a no-op on platforms other than Linux, where the OS is expected to serialize
+/// cores on its own (e.g. Windows' `FlushInstructionCache`).
+#[inline]
+pub fn synchronize_cores() {
+    #[cfg(target_os = "linux")]
+    linux::synchronize_cores();
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    const MEMBARRIER_CMD_QUERY: i32 = 0;
+    const MEMBARRIER_CMD_PRIVATE_EXPEDITED: i32 = 1 << 3;
+    const MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED: i32 = 1 << 4;
+    const MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE: i32 = 1 << 5;
+    const MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_SYNC_CORE: i32 = 1 << 6;
+
+    /// How other cores are synchronized, as determined on first use.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub(super) enum SyncStrategy {
+        /// Not yet determined.
+        Unknown = 0,
+
+        /// `MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE`; guaranteed by the kernel to serialize
+        /// every core running one of our threads. Linux 4.16+.
+        SyncCore = 1,
+
+        /// `MEMBARRIER_CMD_PRIVATE_EXPEDITED`; interrupts every core running one of our threads.
+        /// Returning from the interrupt is context synchronizing on aarch64 and x86, so this is
+        /// sufficient in practice. Linux 4.14+.
+        Expedited = 2,
+
+        /// `membarrier` is unavailable (old kernel or seccomp filter), other cores will pick up
+        /// the new code once they serialize on their own.
+        Unsupported = 3,
+    }
+
+    static STRATEGY: AtomicU8 = AtomicU8::new(SyncStrategy::Unknown as u8);
+
+    pub(super) fn synchronize_cores() {
+        let command = match get_strategy() {
+            SyncStrategy::SyncCore => MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE,
+            SyncStrategy::Expedited => MEMBARRIER_CMD_PRIVATE_EXPEDITED,
+            _ => return,
+        };
+
+        membarrier(command);
+    }
+
+    /// Returns the strategy in use, registering with the kernel on first call.
+    pub(super) fn get_strategy() -> SyncStrategy {
+        match STRATEGY.load(Ordering::Acquire) {
+            1 => SyncStrategy::SyncCore,
+            2 => SyncStrategy::Expedited,
+            3 => SyncStrategy::Unsupported,
+            _ => {
+                // Registration is idempotent, so racing threads are harmless.
+                let strategy = register(membarrier(MEMBARRIER_CMD_QUERY));
+                STRATEGY.store(strategy as u8, Ordering::Release);
+                strategy
+            }
+        }
+    }
+
+    /// Registers the process for the best available command, given the result of
+    /// `MEMBARRIER_CMD_QUERY`.
+    fn register(supported: i32) -> SyncStrategy {
+        let strategy = select_strategy(supported);
+        let register_command = match strategy {
+            SyncStrategy::SyncCore => MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_SYNC_CORE,
+            SyncStrategy::Expedited => MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED,
+            _ => return strategy,
+        };
+
+        if membarrier(register_command) == 0 {
+            return strategy;
+        }
+
+        // SYNC_CORE may be advertised and still fail to register, fall back to expedited.
+        if strategy == SyncStrategy::SyncCore {
+            return register(supported & !MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE);
+        }
+
+        SyncStrategy::Unsupported
+    }
+
+    /// Picks the best strategy out of the commands supported by the kernel.
+    pub(super) fn select_strategy(supported: i32) -> SyncStrategy {
+        // Negative means the query itself failed.
+        if supported < 0 {
+            SyncStrategy::Unsupported
+        } else if supported & MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE != 0 {
+            SyncStrategy::SyncCore
+        } else if supported & MEMBARRIER_CMD_PRIVATE_EXPEDITED != 0 {
+            SyncStrategy::Expedited
+        } else {
+            SyncStrategy::Unsupported
+        }
+    }
+
+    fn membarrier(command: i32) -> i32 {
+        unsafe { libc::syscall(libc::SYS_membarrier, command, 0, 0) as i32 }
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_os = "linux")]
+mod tests {
+    use super::linux::{get_strategy, select_strategy, SyncStrategy};
+    use super::synchronize_cores;
+
+    #[test]
+    fn selects_sync_core_when_available() {
+        assert_eq!(select_strategy(0b111_1001), SyncStrategy::SyncCore);
+    }
+
+    #[test]
+    fn falls_back_to_expedited() {
+        assert_eq!(select_strategy(0b001_1001), SyncStrategy::Expedited);
+    }
+
+    #[test]
+    fn unsupported_without_private_expedited() {
+        assert_eq!(select_strategy(0b1), SyncStrategy::Unsupported);
+        assert_eq!(select_strategy(-1), SyncStrategy::Unsupported);
+    }
+
+    #[test]
+    fn registers_and_synchronizes() {
+        synchronize_cores();
+
+        let strategy = get_strategy();
+        assert_ne!(strategy, SyncStrategy::Unknown);
+        assert_eq!(get_strategy(), strategy);
+        synchronize_cores();
+    }
+}
@@ -104,12 +104,14 @@ pub struct MockFunctionAttribute {
     pub float_params: Vec<MockRegister>,
     pub vector_params: Vec<MockRegister>,
     pub return_reg: MockRegister,
+    pub float_return_reg: Option<MockRegister>,
     pub reserved_stack: u32,
     pub callee_saved: Vec<MockRegister>,
     pub always_saved: Vec<MockRegister>,
     pub stack_cleanup: StackCleanup,
     pub stack_param_order: StackParameterOrder,
     pub required_stack_alignment: u32,
+    pub parameter_register_assignment: ParameterRegisterAssignment,
 }
 
 impl Default for MockFunctionAttribute {
@@ -119,12 +121,14 @@ impl Default for MockFunctionAttribute {
             float_params: vec![],
             vector_params: vec![],
             return_reg: MockRegister::R1,
+            float_return_reg: None,
             reserved_stack: 0,
             callee_saved: vec![],
             always_saved: vec![],
             stack_cleanup: StackCleanup::Caller,
             stack_param_order: StackParameterOrder::RightToLeft,
             required_stack_alignment: 0,
+            parameter_register_assignment: ParameterRegisterAssignment::Sequential,
         }
     }
 }
@@ -146,6 +150,10 @@ impl CallingConventionInfo<MockRegister> for MockFunctionAttribute {
         self.return_reg
     }
 
+    fn float_return_register(&self) -> Option<MockRegister> {
+        self.float_return_reg
+    }
+
     fn reserved_stack_space(&self) -> u32 {
         self.reserved_stack
     }
@@ -169,6 +177,10 @@ impl CallingConventionInfo<MockRegister> for MockFunctionAttribute {
     fn required_stack_alignment(&self) -> u32 {
         self.required_stack_alignment
     }
+
+    fn parameter_register_assignment(&self) -> ParameterRegisterAssignment {
+        self.parameter_register_assignment
+    }
 }
 
 pub struct MockFunction {
@@ -190,12 +202,14 @@ lazy_static::lazy_static! {
         float_params: vec![],
         vector_params: vec![],
         return_reg: MockRegister::R1,
+        float_return_reg: None,
         reserved_stack: 0,
         callee_saved: vec![MockRegister::R3, MockRegister::R4, MockRegister::F3, MockRegister::F4, MockRegister::V3, MockRegister::V4],
         always_saved: vec![],
         stack_cleanup: StackCleanup::Caller,
         stack_param_order: StackParameterOrder::RightToLeft,
-        required_stack_alignment: 1
+        required_stack_alignment: 1,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential
     };
 
     /// A calling convention that is similar to x86 'stdcall', but for our pretend architecture.
@@ -204,12 +218,14 @@ lazy_static::lazy_static! {
         float_params: vec![],
         vector_params: vec![],
         return_reg: MockRegister::R1,
+        float_return_reg: None,
         reserved_stack: 0,
         callee_saved: vec![MockRegister::R3, MockRegister::R4, MockRegister::F3, MockRegister::F4, MockRegister::V3, MockRegister::V4],
         always_saved: vec![],
         stack_cleanup: StackCleanup::Callee,  // callee cleanup
         stack_param_order: StackParameterOrder::RightToLeft,
-        required_stack_alignment: 1
+        required_stack_alignment: 1,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential
     };
 
     /// A calling convention that is similar to x86 Microsoft 'thiscall', but for our pretend architecture.
@@ -218,12 +234,14 @@ lazy_static::lazy_static! {
         float_params: vec![],
         vector_params: vec![],
         return_reg: MockRegister::R1,
+        float_return_reg: None,
         reserved_stack: 0,
         callee_saved: vec![MockRegister::R3, MockRegister::R4, MockRegister::F3, MockRegister::F4, MockRegister::V3, MockRegister::V4],
         always_saved: vec![],
         stack_cleanup: StackCleanup::Callee,  // callee cleanup
         stack_param_order: StackParameterOrder::RightToLeft,
-        required_stack_alignment: 1
+        required_stack_alignment: 1,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential
     };
 
     /// A calling convention that is similar to x86 Microsoft 'fastcall', but for our pretend architecture.
@@ -232,12 +250,14 @@ lazy_static::lazy_static! {
         float_params: vec![],
         vector_params: vec![],
         return_reg: MockRegister::R1,
+        float_return_reg: None,
         reserved_stack: 0,
         callee_saved: vec![MockRegister::R3, MockRegister::R4, MockRegister::F3, MockRegister::F4, MockRegister::V3, MockRegister::V4],
         always_saved: vec![],
         stack_cleanup: StackCleanup::Callee,  // callee cleanup
         stack_param_order: StackParameterOrder::RightToLeft,
-        required_stack_alignment: 1
+        required_stack_alignment: 1,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential
     };
 
     /// A calling convention that is similar to Microsoft 'x64', but for our pretend architecture.
@@ -246,11 +266,13 @@ lazy_static::lazy_static! {
         float_params: vec![],
         vector_params: vec![],
         return_reg: MockRegister::R1,
+        float_return_reg: None,
         reserved_stack: 32,
         callee_saved: vec![MockRegister::R3, MockRegister::R4, MockRegister::F3, MockRegister::F4, MockRegister::V3, MockRegister::V4],
         always_saved: vec![],
         stack_cleanup: StackCleanup::Callee,  // callee cleanup
         stack_param_order: StackParameterOrder::RightToLeft,
-        required_stack_alignment: 16
+        required_stack_alignment: 16,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential
     };
 }
@@ -5,7 +5,7 @@ use crate::{
         buffers::buffer_abstractions::{Buffer, BufferFactory},
         errors::hook_builder_error::{HookBuilderError, RewriteErrorDetails, RewriteErrorSource},
         hooks::stub::stub_props_common::*,
-        jit::compiler::Jit,
+        jit::compiler::{Jit, JitCapabilities},
         length_disassembler::LengthDisassembler,
         rewriter::code_rewriter::{CodeRewriter, CodeRewriterError},
         traits::register_info::RegisterInfo,
@@ -26,23 +26,9 @@ use core::{
 };
 use derive_new::new;
 
-#[cfg(any(
-    target_arch = "aarch64",
-    target_arch = "arm",
-    target_arch = "mips",
-    target_arch = "powerpc",
-    target_arch = "riscv32",
-    target_arch = "riscv64"
-))]
+#[cfg(any(target_arch = "aarch64", target_arch = "mips", target_arch = "powerpc"))]
 use crate::api::hooks::stub::stub_props_4byteins::*;
-#[cfg(not(any(
-    target_arch = "aarch64",
-    target_arch = "arm",
-    target_arch = "mips",
-    target_arch = "powerpc",
-    target_arch = "riscv32",
-    target_arch = "riscv64"
-)))]
+#[cfg(not(any(target_arch = "aarch64", target_arch = "mips", target_arch = "powerpc")))]
 use crate::api::hooks::stub::stub_props_other::*;
 
 /// Creates the buffer required to call the [`create_hook_stub`] function.
@@ -92,12 +78,18 @@ where
 /// - Programmers should specify the maximum permissible hook length. If this constraint is not met,
 ///   an error is thrown.
 ///
+/// - Every stub starts with the JIT's landing pad, if it emits one. See [`get_landing_pad`].
+///
 /// # Error Handling
 ///
 /// Errors are propagated via `Result`.
 /// If the hook cannot be created within the constraints specified in `settings`, an error is thrown.
 #[allow(clippy::type_complexity)]
-pub unsafe fn create_stub<TRegister: Clone + Default, TBuffer: Buffer>(
+pub unsafe fn create_stub<
+    TJit: Jit<TRegister>,
+    TRegister: Clone + Copy + Default,
+    TBuffer: Buffer,
+>(
     settings: &mut HookBuilderSettings,
     alloc: &mut HookBuilderStubAllocation<TBuffer>,
     mixin: &mut dyn HookBuilderSettingsMixin<TRegister>,
@@ -119,8 +111,12 @@ where
     let buf_addr = buf.get_address() as usize;
 
     // Preallocate buffers for props, and code
-    let swap_length = settings.max_swap_length;
-    let mut props_buf = Vec::<u8>::with_capacity(swap_length + size_of::<StubPackedProps>());
+    let landing_pad = get_landing_pad::<TJit, TRegister>();
+    let swap_length = settings.max_swap_length + landing_pad.len();
+    // Swap only stubs pad the swap space to a power of two, up to MAX_ATOMIC_WRITE_BYTES.
+    let mut props_buf = Vec::<u8>::with_capacity(
+        max(swap_length, MAX_ATOMIC_WRITE_BYTES as usize) + size_of::<StubPackedProps>(),
+    );
 
     // Reserve space for StubPackedProps, and get a pointer to it.
     #[allow(clippy::uninit_vec)]
@@ -135,18 +131,26 @@ where
     // - entry: [Hook Function / Original Code]
     // - hook: Hook Function
     // - orig: Original Code
+    // Each of which starts with the landing pad (if any).
 
     // 'Original Code' @ entry
-    mixin.get_orig_function(buf_addr, &mut code_buf_1)?;
+    mixin.get_orig_function(buf_addr + landing_pad.len(), &mut code_buf_1)?;
+    code_buf_1.splice(0..0, landing_pad.iter().copied());
 
     // 'Hook Function' @ entry
-    mixin.get_hook_function(buf_addr, &mut code_buf_2)?;
+    mixin.get_hook_function(buf_addr + landing_pad.len(), &mut code_buf_2)?;
+    code_buf_2.splice(0..0, landing_pad.iter().copied());
 
     // Write the default code.
     let enabled_len = code_buf_2.len();
     let disabled_len = code_buf_1.len();
     let swap_space_len = max(enabled_len, disabled_len);
 
+    // The props and stub buffers are only sized for the max swap length.
+    if swap_space_len > swap_length {
+        return Err(HookBuilderError::TooManyBytes(swap_space_len, swap_length));
+    }
+
     let enabled_code = from_raw_parts(code_buf_2.as_ptr(), enabled_len);
     let disabled_code = from_raw_parts(code_buf_1.as_ptr(), disabled_len);
 
@@ -186,7 +190,8 @@ where
         let entry_end_ptr = buf_addr + swap_space_len;
 
         // 'Hook Function' @ hook
-        mixin.get_hook_function(entry_end_ptr, &mut code_buf_1)?;
+        mixin.get_hook_function(entry_end_ptr + landing_pad.len(), &mut code_buf_1)?;
+        code_buf_1.splice(0..0, landing_pad.iter().copied());
 
         TBuffer::overwrite(entry_end_ptr, &code_buf_1);
         props.set_hook_fn_size(code_buf_1.len());
@@ -194,7 +199,8 @@ where
         code_buf_1.clear();
 
         // 'Original Code' @ orig
-        mixin.get_orig_function(hook_at_hook_end, &mut code_buf_1)?;
+        mixin.get_orig_function(hook_at_hook_end + landing_pad.len(), &mut code_buf_1)?;
+        code_buf_1.splice(0..0, landing_pad.iter().copied());
         TBuffer::overwrite(hook_at_hook_end, &code_buf_1);
 
         // Advance the buffer to account for code written.
@@ -206,6 +212,18 @@ where
     Ok(HookBuilderResult::new(props, buf_addr))
 }
 
+/// Returns the landing pad placed at the start of every stub, or an empty slice if the JIT does
+/// not set [`JitCapabilities::EMITS_LANDING_PADS`].
+///
+/// Buffers for stubs should reserve space for one landing pad per piece of code in the stub.
+pub fn get_landing_pad<TJit: Jit<TRegister>, TRegister: Clone + Copy>() -> &'static [u8] {
+    if TJit::get_jit_capabilities().contains(JitCapabilities::EMITS_LANDING_PADS) {
+        TJit::landing_pad()
+    } else {
+        &[]
+    }
+}
+
 #[derive(Clone, Copy, new)]
 pub struct HookBuilderResult {
     pub props: NonNull<StubPackedProps>,
@@ -248,3 +266,180 @@ pub fn new_rewrite_error<TRegister>(
         e,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::buffers::default_buffer_factory::DefaultBufferFactory;
+    use crate::api::jit::call_relative_operation::CallRelativeOperation;
+    use crate::api::jit::compiler::{DecodeCallTargetResult, JitError};
+    use crate::api::jit::jump_absolute_operation::JumpAbsoluteOperation;
+    use crate::api::jit::jump_relative_operation::JumpRelativeOperation;
+    use crate::api::jit::operation::Operation;
+    use crate::helpers::test_helpers::MockRegister;
+    use alloc::vec;
+
+    const LANDING_PAD: [u8; 4] = [0xAA; 4];
+
+    /// Only provides a landing pad, the stub code itself comes from [`FixedCodeMixin`].
+    struct LandingPadJit;
+
+    impl Jit<MockRegister> for LandingPadJit {
+        fn compile(
+            _address: usize,
+            _operations: &[Operation<MockRegister>],
+        ) -> Result<Vec<u8>, JitError<MockRegister>> {
+            unimplemented!()
+        }
+
+        fn compile_with_buf(
+            _address: usize,
+            _operations: &[Operation<MockRegister>],
+            _buf: &mut Vec<u8>,
+        ) -> Result<(), JitError<MockRegister>> {
+            unimplemented!()
+        }
+
+        fn code_alignment() -> u32 {
+            4
+        }
+
+        fn max_branch_bytes() -> u32 {
+            4
+        }
+
+        fn stack_entry_misalignment() -> u32 {
+            0
+        }
+
+        fn max_relative_jump_distances() -> &'static [usize] {
+            &[usize::MAX]
+        }
+
+        fn get_jit_capabilities() -> JitCapabilities {
+            JitCapabilities::EMITS_LANDING_PADS
+        }
+
+        fn landing_pad() -> &'static [u8] {
+            &LANDING_PAD
+        }
+
+        fn standard_register_size() -> usize {
+            4
+        }
+
+        fn max_standard_relative_call_distance() -> usize {
+            usize::MAX
+        }
+
+        fn standard_relative_call_bytes() -> usize {
+            4
+        }
+
+        fn fill_nops(arr: &mut [u8]) {
+            arr.fill(0);
+        }
+
+        fn encode_jump(
+            _x: &JumpRelativeOperation<MockRegister>,
+            _pc: &mut usize,
+            _buf: &mut Vec<u8>,
+        ) -> Result<(), JitError<MockRegister>> {
+            unimplemented!()
+        }
+
+        fn encode_abs_jump(
+            _x: &JumpAbsoluteOperation<MockRegister>,
+            _pc: &mut usize,
+            _buf: &mut Vec<u8>,
+        ) -> Result<(), JitError<MockRegister>> {
+            unimplemented!()
+        }
+
+        fn encode_call(
+            _x: &CallRelativeOperation,
+            _pc: &mut usize,
+            _buf: &mut Vec<u8>,
+        ) -> Result<(), JitError<MockRegister>> {
+            unimplemented!()
+        }
+
+        fn decode_call_target(
+            _ins_address: usize,
+            _ins_length: usize,
+        ) -> Result<DecodeCallTargetResult, &'static str> {
+            unimplemented!()
+        }
+
+        fn max_relative_jump_bytes() -> usize {
+            4
+        }
+    }
+
+    struct FixedCodeMixin {
+        hook: Vec<u8>,
+        orig: Vec<u8>,
+    }
+
+    impl HookBuilderSettingsMixin<MockRegister> for FixedCodeMixin {
+        fn get_orig_function(
+            &mut self,
+            _address: usize,
+            code: &mut Vec<u8>,
+        ) -> Result<(), HookBuilderError<MockRegister>> {
+            code.extend_from_slice(&self.orig);
+            Ok(())
+        }
+
+        fn get_hook_function(
+            &mut self,
+            _address: usize,
+            code: &mut Vec<u8>,
+        ) -> Result<(), HookBuilderError<MockRegister>> {
+            code.extend_from_slice(&self.hook);
+            Ok(())
+        }
+    }
+
+    fn create_fixed_stub(
+        max_swap_length: usize,
+        hook: Vec<u8>,
+    ) -> Result<HookBuilderResult, HookBuilderError<MockRegister>> {
+        let buf = DefaultBufferFactory::get_any_buffer(128, 16).unwrap();
+        let mut alloc = HookBuilderStubAllocation::new(true, buf);
+        let mut settings = HookBuilderSettings::new(0, max_swap_length, true);
+        let mut mixin = FixedCodeMixin {
+            hook,
+            orig: vec![0xCC; 4],
+        };
+
+        unsafe {
+            create_stub::<LandingPadJit, MockRegister, _>(&mut settings, &mut alloc, &mut mixin)
+        }
+    }
+
+    #[test]
+    fn stub_fits_landing_pad_and_max_swap_length() {
+        let max_swap_length = 4 * MAX_ATOMIC_WRITE_BYTES as usize;
+        let hook = vec![0xBB; max_swap_length];
+        let result = create_fixed_stub(max_swap_length, hook.clone()).unwrap();
+
+        let mut expected = LANDING_PAD.to_vec();
+        expected.extend_from_slice(&hook);
+        let stub = unsafe { from_raw_parts(result.stub as *const u8, expected.len()) };
+        assert_eq!(stub, expected.as_slice());
+    }
+
+    #[test]
+    fn stub_longer_than_max_swap_length_errors() {
+        let max_swap_length = 4 * MAX_ATOMIC_WRITE_BYTES as usize;
+        let hook = vec![0xBB; max_swap_length + 1];
+        let result = create_fixed_stub(max_swap_length, hook);
+
+        assert!(matches!(
+            result,
+            Err(HookBuilderError::TooManyBytes(actual, max))
+                if actual == max_swap_length + 5 && max == max_swap_length + 4
+        ));
+    }
+}
@@ -13,8 +13,8 @@ pub struct HookBuilderSettings {
     /// The 'source address' used to allocate the stub buffer within proximity of the original code.
     pub source_address: usize,
 
-    /// The maximum possible length of the 'swap' space in the buffer.
-    /// This is a hint for pre-allocation, not a hard limit.
+    /// The maximum possible length of the 'swap' space in the buffer, excluding the landing pad.
+    /// Stubs which exceed this fail with [`HookBuilderError::TooManyBytes`].
     pub max_swap_length: usize,
 
     /// Whether the hook should be activated automatically when it is created.
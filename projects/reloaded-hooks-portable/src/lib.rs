@@ -12,6 +12,7 @@ pub mod api {
         pub mod function_hook_error;
         pub mod hook_builder_error;
         pub mod inline_branch_error;
+        pub mod memory_protection_error;
         pub mod wrapper_generation_error;
     }
 
@@ -20,6 +21,22 @@ pub mod api {
         pub mod buffer_abstractions;
         pub mod default_buffer;
         pub mod default_buffer_factory;
+        #[cfg(any(test, feature = "simulated"))]
+        pub mod simulated_buffer;
+        #[cfg(any(test, feature = "simulated"))]
+        pub mod simulated_buffer_factory;
+
+        #[cfg(target_os = "linux")]
+        pub(crate) mod linux_memfd;
+
+        #[cfg(target_os = "linux")]
+        pub(crate) mod linux_proximity;
+
+        #[cfg(target_os = "linux")]
+        pub mod linux_remote_buffer;
+
+        #[cfg(target_os = "linux")]
+        pub mod linux_remote_buffer_factory;
     }
 
     /// Settings passed to other methodss
@@ -53,6 +70,7 @@ pub mod api {
             }
         }
 
+        pub mod caller_filter;
         pub mod common_hook;
     }
 
@@ -60,8 +78,19 @@ pub mod api {
     /// Note: Depends on STD crate, but implementation in crate is no-std.
     pub mod platforms {
 
+        pub mod platform_abstractions;
+
         #[allow(warnings)]
         pub mod platform_functions;
+        pub(crate) mod protection_manager;
+        #[cfg(any(test, feature = "simulated"))]
+        pub mod simulated_memory;
+
+        #[cfg(target_os = "linux")]
+        pub(crate) mod linux_proc_maps;
+
+        #[cfg(target_os = "linux")]
+        pub mod linux_remote;
 
         // The easiest OS to work with tbh
         #[cfg(target_os = "windows")]
@@ -75,20 +104,26 @@ pub mod api {
         #[cfg(any(target_os = "macos", target_os = "ios"))]
         pub mod platform_functions_apple;
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+        #[cfg(not(any(unix, target_os = "windows")))]
         pub(crate) mod platform_functions_mmap_rs;
     }
 
     /// Public API related to Just In Time Compilation
     pub mod jit {
+        pub mod atomic_add_operation;
         pub mod call_absolute_operation;
         pub mod call_relative_operation;
         pub mod call_rip_relative_operation;
         pub mod compiler;
+        pub mod enter_guard_operation;
+        pub mod exit_guard_operation;
+        pub mod filter_caller_operation;
         pub mod jump_absolute_indirect_operation;
         pub mod jump_absolute_operation;
         pub mod jump_relative_operation;
         pub mod jump_rip_relative_operation;
+        pub mod mov_float_from_stack_operation;
+        pub mod mov_float_to_stack_operation;
         pub mod mov_from_stack_operation;
         pub mod mov_operation;
         pub mod mov_to_stack_operation;
@@ -111,6 +146,7 @@ pub mod api {
     /// Contains the code rewriter, which is used to rewrite code from one address to another.
     pub mod rewriter {
         pub mod code_rewriter;
+        pub mod foreign_hook;
     }
 
     /// Trait for determining length of disassembled instructions
@@ -147,7 +183,9 @@ pub mod helpers {
     pub mod jit_jump_operation;
     pub mod make_inline_rel_branch;
     pub mod overwrite_code;
+    pub mod read_code;
     pub mod relative_branch_range_check;
+    pub mod sync_cores;
 
     /// For Benchmarks and tests only. Do not use in production code.
     #[doc(hidden)]
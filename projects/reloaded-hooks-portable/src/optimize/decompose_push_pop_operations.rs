@@ -238,7 +238,8 @@ fn update_mov_from_stack_offsets<TRegister: Copy + Clone>(
     if is_stackalloc {
         for op in ops {
             if let Operation::MovFromStack(mov_op) = op {
-                mov_op.stack_offset += last_stackalloc_val; // we add to positive offset
+                // The values sit above the freed space, i.e. at a positive offset.
+                mov_op.stack_offset -= last_stackalloc_val;
             } else {
                 unsafe {
                     unreachable_unchecked(); // we only add MovFromStack operations at this point
@@ -388,20 +389,20 @@ mod tests {
         assert_eq!(operations.len(), 6);
         assert_eq!(
             operations[0],
-            Operation::MovFromStack(MovFromStackOperation::new(-32, V1))
+            Operation::MovFromStack(MovFromStackOperation::new(32, V1))
         );
         assert_eq!(
             operations[1],
-            Operation::MovFromStack(MovFromStackOperation::new(-16, V2))
+            Operation::MovFromStack(MovFromStackOperation::new(48, V2))
         );
         assert_eq!(operations[2], Operation::StackAlloc(StackAlloc::new(-64)));
         assert_eq!(
             operations[3],
-            Operation::MovFromStack(MovFromStackOperation::new(-32, V1))
+            Operation::MovFromStack(MovFromStackOperation::new(32, V1))
         );
         assert_eq!(
             operations[4],
-            Operation::MovFromStack(MovFromStackOperation::new(-16, V2))
+            Operation::MovFromStack(MovFromStackOperation::new(48, V2))
         );
         assert_eq!(operations[5], Operation::StackAlloc(StackAlloc::new(-64)));
     }
@@ -42,6 +42,7 @@ use core::hash::Hash;
 /// # Returns
 ///
 /// A new slice of operations, these operations should replace the input slice that was passed to this structure.
+/// [`None`] if no operations had to be reordered.
 ///
 /// # Remarks
 ///
@@ -52,59 +53,40 @@ pub fn reorder_mov_sequence<TRegister>(
     scratch_registers: &[TRegister],
 ) -> Option<Vec<Operation<TRegister>>>
 where
-    TRegister: RegisterInfo + Eq + PartialEq + Hash + Copy,
+    TRegister: RegisterInfo + Eq + PartialEq + Hash + Copy + 'static,
 {
-    // Find the first block of MOV operations.
-    let mut start_idx = 0;
     let mut new_ops = Vec::<Operation<TRegister>>::with_capacity(operations.len());
+    let mut reordered = false;
+    let mut idx = 0;
 
-    loop {
+    while idx < operations.len() {
         // Copy elements until found a MOV operation.
-        let original_ops = new_ops.len();
-        for (idx, operation) in operations[start_idx..].iter().enumerate() {
-            if let Operation::Mov(_) = operation {
-                start_idx = idx;
-                break;
-            } else {
-                new_ops.push(operation.clone());
-            }
+        if !matches!(operations[idx], Operation::Mov(_)) {
+            new_ops.push(operations[idx].clone());
+            idx += 1;
+            continue;
         }
 
         // Pull values until first non-MOV index.
         let mut as_mov = SmallVec::<[Mov<TRegister>; 16]>::new();
-        for op in operations[start_idx..].iter() {
-            if let Operation::Mov(mov_op) = op {
-                as_mov.push(*mov_op);
-            } else {
-                break;
-            }
+        while let Some(Operation::Mov(mov_op)) = operations.get(idx) {
+            as_mov.push(*mov_op);
+            idx += 1;
         }
 
-        // Get the slice of MOV operations.
-        if as_mov.len() <= 1 {
-            // No more MOV operations to reorder
-            if new_ops.len() > 1 {
-                // Push all remaining items
-                new_ops
-                    .extend_from_slice(&operations[start_idx + (new_ops.len() - original_ops)..]);
-                break;
-            } else {
-                // If no work was done at all, return None
-                return None;
+        // Alter our MOV operations, if they would write invalid data.
+        // Note: A cycle may be replaced with a single operation, e.g. an exchange.
+        match optimize_moves(&as_mov, scratch_registers) {
+            Some(new_moves) => {
+                new_ops.extend(new_moves);
+                reordered = true;
             }
-        }
-
-        // Alter our MOV operations and return
-        start_idx += as_mov.len();
-        let new_mov = optimize_moves(&as_mov, scratch_registers);
-        if let Some(new_moves) = new_mov {
-            new_ops.extend(new_moves);
-        } else {
-            new_ops.extend_from_slice(&operations[start_idx + (new_ops.len() - original_ops)..]);
+            None => new_ops.extend(as_mov.iter().map(|mov| Operation::Mov(*mov))),
         }
     }
 
-    Some(new_ops)
+    // If no work was done at all, return None
+    reordered.then_some(new_ops)
 }
 
 #[cfg(test)]
@@ -189,6 +171,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reorder_mov_sequence_swap() {
+        let mock_op1 = Operation::Mov(Mov {
+            source: R1,
+            target: R2,
+        });
+        let mock_op2 = Operation::Mov(Mov {
+            source: R2,
+            target: R1,
+        });
+
+        let mut operations: Vec<Operation<MockRegister>> = vec![mock_op1, mock_op2];
+        let reordered_ops = reorder_mov_sequence(&mut operations, &[R3]).unwrap();
+
+        assert_eq!(
+            reordered_ops,
+            vec![Operation::Xchg(XChg::new(R2, R1, Some(R3)))]
+        );
+    }
+
     #[test]
     fn reorder_mov_sequence_with_cycle_no_scratch_register() {
         let mock_op1 = Operation::Mov(Mov {
@@ -0,0 +1,201 @@
+use derive_enum_all_values::AllValues;
+use reloaded_hooks_portable::api::traits::register_info::{
+    KnownRegisterType, KnownRegisterType::*, RegisterInfo,
+};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, AllValues, Default)]
+pub enum AllRegisters {
+    // Range 0b00000 - 0b11111 (0-31)
+    // 64 bit general purpose registers, named by their ABI names.
+    zero, // x0
+    ra,   // x1
+    sp,   // x2
+    gp,   // x3
+    tp,   // x4
+    t0,   // x5
+    #[default]
+    t1, // x6
+    t2,   // x7
+    s0,   // x8
+    s1,   // x9
+    a0,   // x10
+    a1,
+    a2,
+    a3,
+    a4,
+    a5,
+    a6,
+    a7, // x17
+    s2, // x18
+    s3,
+    s4,
+    s5,
+    s6,
+    s7,
+    s8,
+    s9,
+    s10,
+    s11, // x27
+    t3,  // x28
+    t4,
+    t5,
+    t6, // x31
+
+    // Range 0b100000 - 0b111111 (32-63)
+    // 64 bit floating point registers (D extension), named by their ABI names.
+    ft0, // f0
+    ft1,
+    ft2,
+    ft3,
+    ft4,
+    ft5,
+    ft6,
+    ft7, // f7
+    fs0, // f8
+    fs1, // f9
+    fa0, // f10
+    fa1,
+    fa2,
+    fa3,
+    fa4,
+    fa5,
+    fa6,
+    fa7, // f17
+    fs2, // f18
+    fs3,
+    fs4,
+    fs5,
+    fs6,
+    fs7,
+    fs8,
+    fs9,
+    fs10,
+    fs11, // f27
+    ft8,  // f28
+    ft9,
+    ft10,
+    ft11, // f31
+}
+
+/// Registers which can be handed out by the wrapper generator.
+///
+/// `zero` always reads as zero, while `gp` and `tp` hold the global and thread pointers,
+/// so they must never be used as scratch registers.
+static USABLE_REGISTERS: &[AllRegisters] = {
+    use AllRegisters::*;
+    &[
+        ra, sp, t0, t1, t2, s0, s1, a0, a1, a2, a3, a4, a5, a6, a7, s2, s3, s4, s5, s6, s7, s8, s9,
+        s10, s11, t3, t4, t5, t6, ft0, ft1, ft2, ft3, ft4, ft5, ft6, ft7, fs0, fs1, fa0, fa1, fa2,
+        fa3, fa4, fa5, fa6, fa7, fs2, fs3, fs4, fs5, fs6, fs7, fs8, fs9, fs10, fs11, ft8, ft9,
+        ft10, ft11,
+    ]
+};
+
+impl AllRegisters {
+    pub fn register_number(&self) -> u32 {
+        // Mask the lower 5 bits to get the register number
+        (*self as u32) & 0b11111
+    }
+
+    pub fn size(&self) -> usize {
+        8
+    }
+
+    /// True if this is one of the integer registers `x0` - `x31`.
+    pub fn is_gpr(&self) -> bool {
+        *self as u32 & 0b100000 == 0
+    }
+
+    /// True if this is one of the floating point registers `f0` - `f31`.
+    pub fn is_fpr(&self) -> bool {
+        *self as u32 & 0b100000 != 0
+    }
+}
+
+impl RegisterInfo for AllRegisters {
+    fn size_in_bytes(&self) -> usize {
+        self.size()
+    }
+
+    fn is_stack_pointer(&self) -> bool {
+        self == &AllRegisters::sp
+    }
+
+    fn register_type(&self) -> KnownRegisterType {
+        if self.is_gpr() {
+            GeneralPurpose64
+        } else {
+            FloatingPoint
+        }
+    }
+
+    fn extend(&self) -> Self {
+        *self
+    }
+
+    fn all_registers() -> &'static [Self]
+    where
+        Self: Sized,
+    {
+        USABLE_REGISTERS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use reloaded_hooks_portable::api::traits::register_info::KnownRegisterType;
+    use reloaded_hooks_portable::api::traits::register_info::KnownRegisterType::*;
+    use reloaded_hooks_portable::api::traits::register_info::RegisterInfo;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(zero, 0)]
+    #[case(ra, 1)]
+    #[case(sp, 2)]
+    #[case(tp, 4)]
+    #[case(t0, 5)]
+    #[case(t2, 7)]
+    #[case(s0, 8)]
+    #[case(s1, 9)]
+    #[case(a0, 10)]
+    #[case(a7, 17)]
+    #[case(s2, 18)]
+    #[case(s11, 27)]
+    #[case(t3, 28)]
+    #[case(t6, 31)]
+    #[case(ft0, 0)]
+    #[case(fs0, 8)]
+    #[case(fa0, 10)]
+    #[case(fa7, 17)]
+    #[case(fs2, 18)]
+    #[case(fs11, 27)]
+    #[case(ft8, 28)]
+    #[case(ft11, 31)]
+    fn register_number(#[case] register: AllRegisters, #[case] expected_number: u32) {
+        assert_eq!(register.register_number(), expected_number);
+    }
+
+    #[rstest]
+    #[case(ra, GeneralPurpose64)]
+    #[case(a0, GeneralPurpose64)]
+    #[case(t6, GeneralPurpose64)]
+    #[case(ft0, FloatingPoint)]
+    #[case(fa0, FloatingPoint)]
+    #[case(ft11, FloatingPoint)]
+    fn register_type(#[case] register: AllRegisters, #[case] expected_type: KnownRegisterType) {
+        assert_eq!(register.register_type(), expected_type);
+        assert_eq!(register.size_in_bytes(), 8);
+    }
+
+    #[test]
+    fn reserved_registers_are_not_usable() {
+        let all = AllRegisters::all_registers();
+        assert!(!all.contains(&zero));
+        assert!(!all.contains(&gp));
+        assert!(!all.contains(&tp));
+        assert_eq!(all.len(), 61);
+    }
+}
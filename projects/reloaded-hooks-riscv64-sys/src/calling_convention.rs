@@ -0,0 +1,81 @@
+use crate::all_registers::AllRegisters;
+use crate::all_registers::AllRegisters::*;
+use derive_more::Deref;
+use derive_more::DerefMut;
+use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+use reloaded_hooks_portable::api::calling_convention_info::ParameterRegisterAssignment;
+use reloaded_hooks_portable::api::calling_convention_info::StackCleanup;
+use reloaded_hooks_portable::api::calling_convention_info::StackParameterOrder;
+
+/// A variant of `GenericCallingConvention` for RISC-V 64.
+///
+/// This struct is specialized for RISC-V 64 and includes the standard LP64D calling convention
+/// used by `riscv64gc` targets.
+///
+/// # Examples
+///
+/// ```rust
+/// use reloaded_hooks_riscv64_sys::calling_convention::CallingConvention;
+/// let lp64d_convention = CallingConvention::lp64d();
+/// ```
+#[derive(Debug, Clone, PartialEq, DerefMut, Deref)]
+pub struct CallingConvention<'a> {
+    convention: GenericCallingConvention<'a, AllRegisters>,
+}
+
+// https://github.com/riscv-non-isa/riscv-elf-psabi-doc/blob/master/riscv-cc.adoc
+static LP64D: CallingConvention = CallingConvention {
+    convention: GenericCallingConvention::<AllRegisters> {
+        int_parameters: &[a0, a1, a2, a3, a4, a5, a6, a7],
+        float_parameters: &[fa0, fa1, fa2, fa3, fa4, fa5, fa6, fa7],
+        vector_parameters: &[],
+        return_register: a0,
+        float_return_register: Some(fa0),
+        reserved_stack_space: 0,
+        callee_saved_registers: &[
+            s0, s1, s2, s3, s4, s5, s6, s7, s8, s9, s10, s11, fs0, fs1, fs2, fs3, fs4, fs5, fs6,
+            fs7, fs8, fs9, fs10, fs11,
+        ],
+        always_saved_registers: &[ra],
+        stack_cleanup: StackCleanup::Caller,
+        stack_parameter_order: StackParameterOrder::RightToLeft,
+        required_stack_alignment: 16,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
+    },
+};
+
+impl<'a> CallingConvention<'a> {
+    /// RISC-V LP64D calling convention.
+    /// - Integer parameters: A0 to A7 for the first eight integer or pointer arguments.
+    /// - Float parameters:   FA0 to FA7 for the first eight floating-point arguments.
+    /// - Additional parameters: Passed on the stack.
+    /// - Return register:    A0 (integer), FA0 (float)
+    /// - Cleanup:            Caller
+    pub fn lp64d() -> &'a Self {
+        &LP64D
+    }
+
+    // Add a method to select RISC-V calling convention based on PresetCallingConvention
+    pub fn from_preset(convention_type: PresetCallingConvention) -> &'a Self {
+        match convention_type {
+            PresetCallingConvention::LP64D => Self::lp64d(),
+        }
+    }
+
+    // Retrieves the default calling convention for the currently running machine.
+    pub fn default_for_current_platform() -> &'a Self {
+        Self::lp64d()
+    }
+}
+
+/// Enum representing various calling conventions with detailed information.
+pub enum PresetCallingConvention {
+    /// RISC-V LP64D calling convention.
+    /// - Integer parameters: A0 to A7 for the first eight integer or pointer arguments.
+    /// - Float parameters:   FA0 to FA7 for the first eight floating-point arguments.
+    /// - Additional parameters: Passed on the stack.
+    /// - Return register:    A0 (integer), FA0 (float)
+    /// - Cleanup:            Caller
+    LP64D,
+}
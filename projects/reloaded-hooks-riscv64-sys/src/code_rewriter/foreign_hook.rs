@@ -0,0 +1,113 @@
+use super::riscv64_rewriter::{is_auipc, is_jal};
+use crate::instructions::{
+    i_type::{IType, OPCODE_JALR, OPCODE_LOAD, WIDTH_DOUBLE},
+    j_type::JType,
+    u_type::UType,
+};
+use reloaded_hooks_portable::{
+    api::rewriter::foreign_hook::ForeignHook, helpers::read_code::read_code_as,
+};
+
+/// Recognises jumps placed at the start of a function by other hooking libraries.
+///
+/// - `j <target>` (older Reloaded.Hooks)
+/// - `auipc rX, <hi>; jr <lo>(rX)` (frida-gum, Reloaded.Hooks)
+/// - `auipc rX, 0; ld rX, N(rX); jr rX` followed by the target address at offset N (Dobby, frida-gum)
+///
+/// # Safety
+///
+/// `address` must point to readable code.
+pub(crate) unsafe fn find_foreign_hook(address: usize) -> Option<ForeignHook> {
+    let instruction = read_code_as::<u32>(address).to_le();
+
+    // j <target>
+    if is_jal(instruction) && !JType(instruction).is_link() {
+        let target = address.wrapping_add_signed(JType(instruction).offset());
+        return Some(ForeignHook::new(4, target));
+    }
+
+    let auipc = UType(instruction);
+    if !is_auipc(instruction) || auipc.rd() == 0 {
+        return None;
+    }
+
+    let register = auipc.rd();
+    let next = IType(read_code_as::<u32>(address + 4).to_le());
+
+    // auipc rX, <hi>; jr <lo>(rX)
+    if is_jump_register(&next, register) {
+        let target = address
+            .wrapping_add_signed(auipc.offset())
+            .wrapping_add_signed(next.imm() as isize);
+        return Some(ForeignHook::new(8, target));
+    }
+
+    // auipc rX, 0; ld rX, N(rX); jr rX
+    // The address is either right after the jump (N = 12), or 8 byte aligned after it (N = 16).
+    let literal_offset = next.imm() as usize;
+    if auipc.offset() == 0
+        && next.opcode() == OPCODE_LOAD
+        && next.funct3() == WIDTH_DOUBLE
+        && next.rd() == register
+        && next.rs1() == register
+        && (literal_offset == 12 || literal_offset == 16)
+    {
+        let jump = IType(read_code_as::<u32>(address + 8).to_le());
+        if is_jump_register(&jump, register) && jump.imm() == 0 {
+            let target = read_code_as::<u64>(address + literal_offset).to_le();
+            return Some(ForeignHook::new(literal_offset + 8, target as usize));
+        }
+    }
+
+    None
+}
+
+/// True if `instruction` is a `jr <imm>(register)`, i.e. `jalr zero, <imm>(register)`.
+fn is_jump_register(instruction: &IType, register: u8) -> bool {
+    instruction.opcode() == OPCODE_JALR
+        && instruction.funct3() == 0
+        && instruction.rd() == 0
+        && instruction.rs1() == register
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Finds the foreign hook in `code`, padded such that reading past the end of shorter code is fine.
+    fn find_in(code: &str) -> (usize, Option<ForeignHook>) {
+        let mut code = hex::decode(code).unwrap();
+        code.resize(24, 0);
+        let address = code.as_ptr() as usize;
+        (address, unsafe { find_foreign_hook(address) })
+    }
+
+    #[rstest]
+    #[case::j("6f000001", Some((4, 0x10)))] // j 16
+    #[case::j_backwards("6ff01fff", Some((4, -0x10)))] // j -16
+    #[case::jal("ef000001", None)] // jal ra, 16
+    #[case::auipc_jr("1703000067000310", Some((8, 0x100)))] // auipc t1, 0; jr 256(t1)
+    #[case::auipc_jr_far("1713000067000380", Some((8, -0x800 + 0x1000)))] // auipc t1, 1; jr -2048(t1)
+    #[case::auipc_jr_other_register("1703000067800210", None)] // auipc t1, 0; jr 256(t0)
+    #[case::auipc_call("17030000e7000310", None)] // auipc t1, 0; jalr ra, 256(t1)
+    #[case::auipc_call_t2("17030000e7030310", None)] // auipc t1, 0; jalr t2, 256(t1)
+    #[case::nop("13000000", None)]
+    fn find_relative(#[case] code: &str, #[case] expected: Option<(usize, isize)>) {
+        let (address, result) = find_in(code);
+        let expected =
+            expected.map(|(len, x)| ForeignHook::new(len, address.wrapping_add_signed(x)));
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case::aligned("97020000 83b20201 67800200 00000000 bc9a785634120000", Some((24, 0x123456789ABC)))] // auipc t0, 0; ld t0, 16(t0); jr t0
+    #[case::unaligned("97020000 83b2c200 67800200 bc9a785634120000", Some((20, 0x123456789ABC)))] // auipc t0, 0; ld t0, 12(t0); jr t0
+    #[case::other_offset("97020000 83b28200 67800200 bc9a785634120000", None)] // auipc t0, 0; ld t0, 8(t0); jr t0
+    #[case::lw("97020000 83a2c200 67800200 bc9a785634120000", None)] // auipc t0, 0; lw t0, 12(t0); jr t0
+    #[case::no_jump("97020000 83b2c200 13000000 bc9a785634120000", None)] // auipc t0, 0; ld t0, 12(t0); nop
+    fn find_absolute(#[case] code: &str, #[case] expected: Option<(usize, usize)>) {
+        let (_, result) = find_in(&code.replace(' ', ""));
+        assert_eq!(result, expected.map(|(len, x)| ForeignHook::new(len, x)));
+    }
+}
@@ -0,0 +1,59 @@
+extern crate alloc;
+
+use super::instruction_rewrite_result::InstructionRewriteResult;
+use crate::instructions::{
+    i_type::IType,
+    j_type::JType,
+    load_immediate::load_immediate,
+    u_type::{split_pc_relative_offset, UType},
+};
+use alloc::string::ToString;
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Emits a branch from `new_address` to `target_address`, storing the return address in `link`.
+///
+/// # Parameters
+///
+/// * `target_address`: The address to branch to.
+/// * `new_address`: The address the first emitted instruction will be placed at.
+/// * `link`: Register receiving the return address, `zero` for a plain jump.
+/// * `scratch_register`: Register used to hold the target when it is further than `jal` can reach.
+/// * `result`: The buffer to which the instructions will be appended.
+///
+/// # Behaviour
+///
+/// The branch is emitted as one of the following:
+/// - JAL
+/// - AUIPC + JALR
+/// - LI + JALR
+pub(crate) fn emit_branch(
+    target_address: usize,
+    new_address: usize,
+    link: u8,
+    scratch_register: Option<u8>,
+    result: &mut InstructionRewriteResult,
+) -> Result<(), CodeRewriterError> {
+    let offset = (target_address as isize).wrapping_sub(new_address as isize);
+
+    if (-0x100000..=0xFFFFE).contains(&offset) {
+        // Offset is already known to be in range and even, so this can't fail.
+        result.push(JType::assemble_jal(link, offset as i32).unwrap().0.to_le());
+        return Ok(());
+    }
+
+    let scratch = scratch_register
+        .ok_or_else(|| CodeRewriterError::NoScratchRegister("emit_branch".to_string()))?;
+
+    match split_pc_relative_offset(offset) {
+        Some((hi, lo)) => {
+            result.push(UType::new_auipc(scratch, hi).0.to_le());
+            result.push(IType::new_jalr(link, scratch, lo).unwrap().0.to_le());
+        }
+        None => {
+            result.extend(load_immediate(scratch, target_address));
+            result.push(IType::new_jalr(link, scratch, 0).unwrap().0.to_le());
+        }
+    }
+
+    Ok(())
+}
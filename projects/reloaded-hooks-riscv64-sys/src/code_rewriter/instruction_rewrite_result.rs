@@ -0,0 +1,9 @@
+use smallvec::SmallVec;
+
+/// Stores the instructions emitted when rewriting a single instruction.
+///
+/// # Remarks
+///
+/// The worst case is a conditional branch out of `auipc` range, which is rewritten as an inverted
+/// branch over an 8 instruction `li` + `jalr`; that is 10 instructions in total.
+pub(crate) type InstructionRewriteResult = SmallVec<[u32; 10]>;
@@ -0,0 +1,88 @@
+use crate::{
+    code_rewriter::instruction_rewrite_result::InstructionRewriteResult,
+    instructions::{
+        i_type::IType,
+        load_immediate::load_immediate,
+        u_type::{split_pc_relative_offset, UType},
+    },
+};
+
+/// Rewrites the `AUIPC` (Add Upper Immediate to PC) instruction for a new address.
+///
+/// # Parameters
+///
+/// * `instruction`: The original `AUIPC` instruction encoded as a 32-bit value.
+/// * `old_address`: The original address associated with the `AUIPC` instruction.
+/// * `new_address`: The new address of the instruction.
+///
+/// # Behaviour
+///
+/// The `AUIPC` instruction is rewritten so that the destination register holds the same value
+/// as before, as one of the following:
+/// - AUIPC
+/// - AUIPC + ADDI
+/// - LI (up to 8 instructions)
+///
+/// Instructions consuming the result (e.g. `ld t0, %pcrel_lo(sym)(t0)`) are copied as is, as they
+/// add their own offset on top of the register.
+///
+/// # Safety
+///
+/// Ensure that the provided `instruction` is a valid `AUIPC` opcode.
+pub(crate) fn rewrite_auipc(
+    instruction: u32,
+    old_address: usize,
+    new_address: usize,
+) -> InstructionRewriteResult {
+    let orig_ins = UType(instruction.to_le());
+    let rd = orig_ins.rd();
+    let mut result = InstructionRewriteResult::new();
+
+    // `auipc zero, imm` has no effect.
+    if rd == 0 {
+        result.push(instruction);
+        return result;
+    }
+
+    let value = (old_address as isize).wrapping_add(orig_ins.offset());
+    let new_offset = value.wrapping_sub(new_address as isize);
+
+    match split_pc_relative_offset(new_offset) {
+        Some((hi, lo)) => {
+            result.push(UType::new_auipc(rd, hi).0.to_le());
+            if lo != 0 {
+                result.push(IType::new_addi(rd, rd, lo).unwrap().0.to_le());
+            }
+        }
+        None => result.extend(load_immediate(rd, value as usize)),
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::instruction_buffer_as_hex_u32;
+    use rstest::rstest;
+
+    #[rstest]
+    // [Within Range] || auipc t0, 1 -> auipc t0, 2 (We move back 4096 bytes)
+    #[case::simple("97120000", 4096, 0, "97220000")]
+    // [Within Range, Unaligned] || auipc t0, 1 -> auipc t0, 1 + addi t0, t0, 4
+    #[case::unaligned("97120000", 4, 0, "9712000093824200")]
+    // [Zero Register] || auipc zero, 1 -> copied as is
+    #[case::zero("17100000", 4096, 0, "17100000")]
+    // [Out of Range] || auipc t0, 0 at 0x100000000 -> li t0, 0x100000000
+    #[case::out_of_range("97020000", 0x100000000, 0, "9302100093920202")]
+    fn standard_cases(
+        #[case] old_instruction: String,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected: String,
+    ) {
+        let instruction = u32::from_str_radix(&old_instruction, 16).unwrap().to_be();
+        let result = rewrite_auipc(instruction, old_address, new_address);
+        assert_eq!(instruction_buffer_as_hex_u32(&result), expected);
+    }
+}
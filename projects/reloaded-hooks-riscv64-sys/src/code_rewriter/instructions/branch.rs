@@ -0,0 +1,117 @@
+use crate::{
+    code_rewriter::{helpers::emit_branch, instruction_rewrite_result::InstructionRewriteResult},
+    instructions::b_type::BType,
+};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Rewrites a conditional branch (`BEQ`, `BNE`, `BLT`, `BGE`, `BLTU`, `BGEU`) for a new address.
+///
+/// # Parameters
+///
+/// * `instruction`: The original branch instruction encoded as a 32-bit value.
+/// * `old_address`: The original address associated with the branch instruction.
+/// * `new_address`: The new address of the instruction.
+/// * `scratch_register`: Specifies the register to use as a scratch when the target is too far for direct branching.
+///
+/// # Behaviour
+///
+/// The branch instruction is rewritten as one of the following:
+/// - B<cond>
+/// - B<!cond> + JAL
+/// - B<!cond> + AUIPC + JALR
+/// - B<!cond> + LI + JALR
+///
+/// # Safety
+///
+/// Ensure that the provided `instruction` is a valid conditional branch opcode.
+pub(crate) fn rewrite_branch(
+    instruction: u32,
+    old_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+) -> Result<InstructionRewriteResult, CodeRewriterError> {
+    let orig_ins = BType(instruction.to_le());
+    let orig_target = (old_address as isize).wrapping_add(orig_ins.offset()) as usize;
+    rewrite_branch_to(
+        orig_ins.condition(),
+        orig_ins.rs1(),
+        orig_ins.rs2(),
+        orig_target,
+        new_address,
+        scratch_register,
+    )
+}
+
+/// Rewrites a conditional branch comparing `rs1` and `rs2`, to `target_address`, for a new address.
+pub(crate) fn rewrite_branch_to(
+    condition: u8,
+    rs1: u8,
+    rs2: u8,
+    target_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+) -> Result<InstructionRewriteResult, CodeRewriterError> {
+    let mut result = InstructionRewriteResult::new();
+    let new_offset = (target_address as isize).wrapping_sub(new_address as isize);
+
+    if (-4096..=4094).contains(&new_offset) {
+        // Offset is already known to be in range and even, so this can't fail.
+        let branch = BType::assemble(condition, rs1, rs2, new_offset as i32).unwrap();
+        result.push(branch.0.to_le());
+        return Ok(result);
+    }
+
+    // Emit the jump first, so we know how far to skip when the condition does not hold.
+    let mut jump = InstructionRewriteResult::new();
+    emit_branch(
+        target_address,
+        new_address.wrapping_add(4),
+        0,
+        scratch_register,
+        &mut jump,
+    )?;
+
+    // Lowest bit of the condition inverts it.
+    let skip = BType::assemble(condition ^ 1, rs1, rs2, 4 + (jump.len() * 4) as i32).unwrap();
+    result.push(skip.0.to_le());
+    result.extend(jump);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::instruction_buffer_as_hex_u32;
+    use rstest::rstest;
+
+    #[rstest]
+    // [Within 4KiB] || beq a0, a1, 8 -> beq a0, a1, 16
+    #[case::simple("6304b500", 8, 0, "6308b500")]
+    // [Within 1MiB] || bne a0, a1, 0 at 0x2000 -> beq a0, a1, 8; j 0x1ffc
+    #[case::jal("6310b500", 0x2000, 0, "6304b5006f10d07f")]
+    // [Within 2GiB] || bltu a0, a1, 0 at 0x12345678 -> bgeu a0, a1, 12; auipc t1, 0x12345; jalr zero, 0x674(t1)
+    #[case::auipc("6360b500", 0x12345678, 0, "6376b5001753341267004367")]
+    // [Over 2GiB] || beq a0, a1, 0 at 0x100000000 -> bne a0, a1, 16; li t1, 0x100000000; jalr zero, 0(t1)
+    #[case::li("6300b500", 0x100000000, 0, "6318b500130310001313030267000300")]
+    fn standard_cases(
+        #[case] old_instruction: String,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected: String,
+    ) {
+        let instruction = u32::from_str_radix(&old_instruction, 16).unwrap().to_be();
+        let result = rewrite_branch(instruction, old_address, new_address, Some(6)).unwrap();
+        assert_eq!(instruction_buffer_as_hex_u32(&result), expected);
+    }
+
+    #[test]
+    fn error_on_missing_scratch() {
+        // beq a0, a1, 0 at 0x12345678 -> requires a scratch register
+        let instruction = u32::from_str_radix("6300b500", 16).unwrap().to_be();
+        let result = rewrite_branch(instruction, 0x12345678, 0, None);
+        assert!(matches!(
+            result,
+            Err(CodeRewriterError::NoScratchRegister(_))
+        ));
+    }
+}
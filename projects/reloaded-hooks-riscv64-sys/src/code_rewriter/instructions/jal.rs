@@ -0,0 +1,90 @@
+use crate::{
+    code_rewriter::{helpers::emit_branch, instruction_rewrite_result::InstructionRewriteResult},
+    instructions::j_type::JType,
+};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Rewrites the `JAL` (Jump and Link) instruction for a new address.
+///
+/// # Parameters
+///
+/// * `instruction`: The original `JAL` instruction encoded as a 32-bit value.
+/// * `old_address`: The original address associated with the `JAL` instruction.
+/// * `new_address`: The new address of the instruction.
+/// * `scratch_register`: Specifies the register to use as a scratch when the target is too far for direct branching.
+///
+/// # Behaviour
+///
+/// The `JAL` instruction is rewritten as one of the following:
+/// - JAL
+/// - AUIPC + JALR
+/// - LI + JALR
+///
+/// For calls, the link register is overwritten anyway, so it is used in place of the scratch register.
+///
+/// # Safety
+///
+/// Ensure that the provided `instruction` is a valid `JAL` opcode.
+pub(crate) fn rewrite_jal(
+    instruction: u32,
+    old_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+) -> Result<InstructionRewriteResult, CodeRewriterError> {
+    let orig_ins = JType(instruction.to_le());
+    let orig_target = (old_address as isize).wrapping_add(orig_ins.offset()) as usize;
+    rewrite_jump_to(orig_target, orig_ins.rd(), new_address, scratch_register)
+}
+
+/// Rewrites a jump (`JAL`, `C.J`) to `target_address`, linking into `rd`, for a new address.
+pub(crate) fn rewrite_jump_to(
+    target_address: usize,
+    rd: u8,
+    new_address: usize,
+    scratch_register: Option<u8>,
+) -> Result<InstructionRewriteResult, CodeRewriterError> {
+    let scratch = if rd != 0 { Some(rd) } else { scratch_register };
+    let mut result = InstructionRewriteResult::new();
+    emit_branch(target_address, new_address, rd, scratch, &mut result)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::instruction_buffer_as_hex_u32;
+    use rstest::rstest;
+
+    #[rstest]
+    // [Within 1MiB] || j 4096 -> j 8192 (We move back 4096 bytes)
+    #[case::simple("6f100000", 4096, 0, "6f200000")]
+    // [Within 1MiB] || jal 4096 -> jal 8192
+    #[case::simple_call("ef100000", 4096, 0, "ef200000")]
+    // [Within 2GiB] || j 0 at 0x12345678 -> auipc t1, 0x12345; jalr zero, 0x678(t1)
+    #[case::auipc("6f000000", 0x12345678, 0, "1753341267008367")]
+    // [Within 2GiB] || jal 0 at 0x12345678 -> auipc ra, 0x12345; jalr ra, 0x678(ra)
+    #[case::auipc_call("ef000000", 0x12345678, 0, "97503412e7808067")]
+    // [Over 2GiB] || j 0 at 0x100000000 -> li t1, 0x100000000; jalr zero, 0(t1)
+    #[case::li("6f000000", 0x100000000, 0, "130310001313030267000300")]
+    fn standard_cases(
+        #[case] old_instruction: String,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected: String,
+    ) {
+        let instruction = u32::from_str_radix(&old_instruction, 16).unwrap().to_be();
+        let result = rewrite_jal(instruction, old_address, new_address, Some(6)).unwrap();
+        assert_eq!(instruction_buffer_as_hex_u32(&result), expected);
+    }
+
+    #[test]
+    fn error_on_missing_scratch() {
+        // j 0 at 0x12345678 -> requires a scratch register
+        let instruction = u32::from_str_radix("6f000000", 16).unwrap().to_be();
+        let result = rewrite_jal(instruction, 0x12345678, 0, None);
+        assert!(matches!(
+            result,
+            Err(CodeRewriterError::NoScratchRegister(_))
+        ));
+    }
+}
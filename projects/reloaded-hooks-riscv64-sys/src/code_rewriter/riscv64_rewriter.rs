@@ -0,0 +1,346 @@
+extern crate alloc;
+
+use super::{
+    instruction_rewrite_result::InstructionRewriteResult,
+    instructions::{
+        auipc::rewrite_auipc,
+        branch::{rewrite_branch, rewrite_branch_to},
+        jal::{rewrite_jal, rewrite_jump_to},
+    },
+};
+use crate::instructions::{
+    b_type::{CONDITION_EQ, CONDITION_NE},
+    compressed::{CompressedBranch, CompressedJump},
+};
+use alloc::{format, string::ToString, vec::Vec};
+use core::ptr::read_unaligned;
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+
+/// Rewrites the code from one address to another.
+///
+/// Given an original block of code starting at `old_address`, this function
+/// will modify any relative addressing instructions to make them compatible
+/// with a new location starting at `new_address`.
+///
+/// This is useful, for example, when code is being moved or injected into a new
+/// location in memory and any relative jumps or calls within the code need to be
+/// adjusted to the new location.
+///
+/// # Parameters
+///
+/// * `old_code`: A pointer to the start of the original block of code.
+/// * `old_code_size`: Amount of bytes to rewrite.
+/// * `old_address`: The address to assume as the source location of the old code.
+/// * `new_address`: The new address for the instructions.
+/// * `scratch_register`
+///     - A scratch general purpose register that can be used for operations.
+///     - This scratch register may or may not be used depending on the code being rewritten.
+///
+/// # Behaviour
+///
+/// The function will iterate over the block of code instruction by instruction, identifying any
+/// instructions that use relative addressing. When such an instruction is identified,
+/// its offset is adjusted to account for the difference between `old_address` and `new_address`.
+///
+/// Compressed (16-bit) instructions are supported; relative ones are expanded to their 32-bit
+/// equivalents, everything else is copied as is.
+///
+/// # Returns
+///
+/// Either a re-encode error, in which case the operation fails, or a vector to consume.
+pub(crate) fn rewrite_code_riscv64(
+    old_code: *const u8,
+    old_code_size: usize,
+    old_address: usize,
+    new_address: usize,
+    scratch_register: Option<u8>,
+    existing_buffer: &mut Vec<u8>,
+) -> Result<(), CodeRewriterError> {
+    let mut offset = 0;
+    let mut current_new_address = new_address;
+
+    while offset < old_code_size {
+        let ins_ptr = old_code.wrapping_add(offset);
+        let low_half = unsafe { read_unaligned(ins_ptr as *const u16) }.to_le();
+        let length = instruction_length(low_half).ok_or_else(|| {
+            CodeRewriterError::FailedToDisasm(offset.to_string(), format!("{:04x}", low_half))
+        })?;
+
+        if offset + length > old_code_size {
+            return Err(CodeRewriterError::InsufficientBytes);
+        }
+
+        let source_address = old_address.wrapping_add(offset);
+        let result = match length {
+            2 => rewrite_compressed_instruction(
+                low_half,
+                source_address,
+                current_new_address,
+                scratch_register,
+            )?,
+            4 => {
+                let instruction = unsafe { read_unaligned(ins_ptr as *const u32) }.to_le();
+                rewrite_instruction(
+                    instruction,
+                    source_address,
+                    current_new_address,
+                    scratch_register,
+                )?
+            }
+            _ => None,
+        };
+
+        match result {
+            Some(x) => {
+                for ins in &x {
+                    existing_buffer.extend_from_slice(&ins.to_le_bytes());
+                }
+                current_new_address = current_new_address.wrapping_add(x.len() * 4);
+            }
+            None => {
+                let bytes = unsafe { core::slice::from_raw_parts(ins_ptr, length) };
+                existing_buffer.extend_from_slice(bytes);
+                current_new_address = current_new_address.wrapping_add(length);
+            }
+        }
+
+        offset += length;
+    }
+
+    Ok(())
+}
+
+/// Rewrites a 32-bit instruction, returning `None` if it should be copied as is.
+fn rewrite_instruction(
+    instruction: u32,
+    source_address: usize,
+    dest_address: usize,
+    scratch_register: Option<u8>,
+) -> Result<Option<InstructionRewriteResult>, CodeRewriterError> {
+    // Note: Converted to little endian inside each of the functions here
+    if is_auipc(instruction) {
+        Ok(Some(rewrite_auipc(
+            instruction,
+            source_address,
+            dest_address,
+        )))
+    } else if is_jal(instruction) {
+        rewrite_jal(instruction, source_address, dest_address, scratch_register).map(Some)
+    } else if is_branch(instruction) {
+        rewrite_branch(instruction, source_address, dest_address, scratch_register).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Rewrites a 16-bit instruction, returning `None` if it should be copied as is.
+fn rewrite_compressed_instruction(
+    instruction: u16,
+    source_address: usize,
+    dest_address: usize,
+    scratch_register: Option<u8>,
+) -> Result<Option<InstructionRewriteResult>, CodeRewriterError> {
+    if is_c_j(instruction) {
+        let target = (source_address as isize).wrapping_add(CompressedJump(instruction).offset());
+        rewrite_jump_to(target as usize, 0, dest_address, scratch_register).map(Some)
+    } else if is_c_beqz_bnez(instruction) {
+        let branch = CompressedBranch(instruction);
+        let target = (source_address as isize).wrapping_add(branch.offset());
+        let condition = if branch.is_bnez() {
+            CONDITION_NE
+        } else {
+            CONDITION_EQ
+        };
+
+        rewrite_branch_to(
+            condition,
+            branch.rs1(),
+            0,
+            target as usize,
+            dest_address,
+            scratch_register,
+        )
+        .map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the length of the instruction starting with the given 16 bits, using the standard
+/// RISC-V length encoding. Returns `None` for lengths of 10 bytes or more, which are reserved.
+pub(crate) fn instruction_length(low_half: u16) -> Option<usize> {
+    if low_half & 0b11 != 0b11 {
+        Some(2)
+    } else if low_half & 0b11100 != 0b11100 {
+        Some(4)
+    } else if low_half & 0b111111 == 0b011111 {
+        Some(6)
+    } else if low_half & 0b1111111 == 0b0111111 {
+        Some(8)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn is_auipc(instruction: u32) -> bool {
+    (instruction & 0x7f) == 0x17
+}
+
+pub(crate) fn is_jal(instruction: u32) -> bool {
+    (instruction & 0x7f) == 0x6f
+}
+
+pub(crate) fn is_branch(instruction: u32) -> bool {
+    (instruction & 0x7f) == 0x63
+}
+
+pub(crate) fn is_c_j(instruction: u16) -> bool {
+    (instruction & 0xe003) == 0xa001
+}
+
+pub(crate) fn is_c_beqz_bnez(instruction: u16) -> bool {
+    (instruction & 0xc003) == 0xc001
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        instruction_length, is_auipc, is_branch, is_c_beqz_bnez, is_c_j, is_jal,
+        rewrite_code_riscv64,
+    };
+    use crate::test_helpers::instruction_buffer_as_hex_u8;
+    use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+    use rstest::rstest;
+
+    #[allow(non_camel_case_types)]
+    #[derive(Debug, PartialEq, Eq)]
+    enum InsType {
+        Auipc,
+        Jal,
+        Branch,
+        Unknown,
+    }
+
+    fn get_ins_type(instruction: u32) -> InsType {
+        if is_auipc(instruction) {
+            InsType::Auipc
+        } else if is_jal(instruction) {
+            InsType::Jal
+        } else if is_branch(instruction) {
+            InsType::Branch
+        } else {
+            InsType::Unknown
+        }
+    }
+
+    #[rstest]
+    #[case::add("3305b500", InsType::Unknown)] // add a0, a0, a1
+    #[case::addi("13051500", InsType::Unknown)] // addi a0, a0, 1
+    #[case::auipc("97020000", InsType::Auipc)] // auipc t0, 0
+    #[case::beq("6300b500", InsType::Branch)] // beq a0, a1, 0
+    #[case::bgeu("6370b500", InsType::Branch)] // bgeu a0, a1, 0
+    #[case::ecall("73000000", InsType::Unknown)] // ecall
+    #[case::jal("ef000000", InsType::Jal)] // jal ra, 0
+    #[case::jalr("e7800000", InsType::Unknown)] // jalr ra, 0(ra)
+    #[case::ld("03350500", InsType::Unknown)] // ld a0, 0(a0)
+    #[case::lui("37050000", InsType::Unknown)] // lui a0, 0
+    #[case::sd("2330b500", InsType::Unknown)] // sd a1, 0(a0)
+    fn can_classify_instruction(#[case] hex: &str, #[case] expected: InsType) {
+        let instruction = u32::from_str_radix(hex, 16).unwrap().to_be();
+        assert_eq!(get_ins_type(instruction), expected);
+    }
+
+    #[rstest]
+    #[case::c_j("01a0", true, false)] // c.j 0
+    #[case::c_beqz("01c1", false, true)] // c.beqz a0, 0
+    #[case::c_bnez("01e1", false, true)] // c.bnez a0, 0
+    #[case::c_jr("8280", false, false)] // c.jr ra
+    #[case::c_addi("0505", false, false)] // c.addi a0, 1
+    #[case::c_addiw("0525", false, false)] // c.addiw a0, 1
+    fn can_classify_compressed_instruction(
+        #[case] hex: &str,
+        #[case] expected_c_j: bool,
+        #[case] expected_c_branch: bool,
+    ) {
+        let instruction = u16::from_str_radix(hex, 16).unwrap().to_be();
+        assert_eq!(is_c_j(instruction), expected_c_j);
+        assert_eq!(is_c_beqz_bnez(instruction), expected_c_branch);
+    }
+
+    #[rstest]
+    #[case(0x0001, Some(2))]
+    #[case(0x0013, Some(4))]
+    #[case(0x001f, Some(6))]
+    #[case(0x003f, Some(8))]
+    #[case(0x007f, None)]
+    fn can_decode_instruction_length(#[case] low_half: u16, #[case] expected: Option<usize>) {
+        assert_eq!(instruction_length(low_half), expected);
+    }
+
+    #[rstest]
+    // addi a0, a0, 1 -> copied
+    #[case::copy("13051500", 0x1000, 0, "13051500", None)]
+    // c.addi a0, 1 + addi a0, a0, 1 -> copied
+    #[case::copy_compressed("050513051500", 0x1000, 0, "050513051500", None)]
+    // auipc t0, 1 -> auipc t0, 2
+    #[case::auipc("97120000", 4096, 0, "97220000", None)]
+    // j 4096 -> j 8192
+    #[case::jal("6f100000", 4096, 0, "6f200000", None)]
+    // c.j 8 -> j 4104
+    #[case::c_j("21a0", 4096, 0, "6f108000", None)]
+    // c.beqz a0, 8 -> beq a0, zero, 4104 -> bne a0, zero, 8 + j 4100
+    #[case::c_beqz("01c5", 4096, 0, "631405006f104000", None)]
+    // c.nop + c.j 0 -> c.nop + j 4096 (The jump is now 2 bytes further into the buffer)
+    #[case::c_j_after_c_nop("010001a0", 4096, 0, "01006f100000", None)]
+    // beq a0, a1, 8 at 0x12345678 -> bne a0, a1, 12 + auipc t1, 0x12345 + jalr zero, 0x67c(t1)
+    #[case::branch_far("6304b500", 0x12345678, 0, "6316b500175334126700c367", Some(6))]
+    fn can_rewrite_code(
+        #[case] old_instruction_hex: &str,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected_hex: &str,
+        #[case] scratch_register: Option<u8>,
+    ) {
+        let old_instruction_bytes = hex::decode(old_instruction_hex).unwrap();
+        let mut new_code = Vec::new();
+        let result = rewrite_code_riscv64(
+            old_instruction_bytes.as_ptr(),
+            old_instruction_bytes.len(),
+            old_address,
+            new_address,
+            scratch_register,
+            &mut new_code,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(instruction_buffer_as_hex_u8(&new_code), expected_hex);
+    }
+
+    #[rstest]
+    // Only half of a 32-bit instruction.
+    #[case::truncated("1305", CodeRewriterError::InsufficientBytes)]
+    // Reserved (>= 80-bit) instruction.
+    #[case::reserved(
+        "7f00",
+        CodeRewriterError::FailedToDisasm(String::new(), String::new())
+    )]
+    // Far jump without a scratch register.
+    #[case::no_scratch("6f000000", CodeRewriterError::NoScratchRegister(String::new()))]
+    fn error_cases(#[case] old_instruction_hex: &str, #[case] expected: CodeRewriterError) {
+        let old_instruction_bytes = hex::decode(old_instruction_hex).unwrap();
+        let mut new_code = Vec::new();
+        let result = rewrite_code_riscv64(
+            old_instruction_bytes.as_ptr(),
+            old_instruction_bytes.len(),
+            0x12345678,
+            0,
+            None,
+            &mut new_code,
+        );
+
+        assert_eq!(
+            core::mem::discriminant(&result.unwrap_err()),
+            core::mem::discriminant(&expected)
+        );
+    }
+}
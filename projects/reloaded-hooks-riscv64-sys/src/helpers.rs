@@ -0,0 +1,14 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+/// Encodes 4 byte instructions via `encode`, and appends them to `buf`.
+///
+/// `buf` may already contain compressed (2 byte) instructions, so it can't be reinterpreted as
+/// a `Vec<i32>` in place.
+pub(crate) fn encode_into<T>(buf: &mut Vec<u8>, encode: impl FnOnce(&mut Vec<i32>) -> T) -> T {
+    let mut buf_i32 = Vec::new();
+    let result = encode(&mut buf_i32);
+    buf.extend(buf_i32.iter().flat_map(|ins| ins.to_le_bytes()));
+    result
+}
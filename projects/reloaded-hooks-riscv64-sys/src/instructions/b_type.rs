@@ -0,0 +1,137 @@
+extern crate alloc;
+
+use super::errors::{exceeds_maximum_range, must_be_divisible_by};
+use crate::all_registers::AllRegisters;
+use bitfield::bitfield;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+/// Opcode for conditional branches.
+pub const OPCODE_BRANCH: u8 = 0b1100011;
+
+/// `funct3` for 'branch if equal'.
+pub const CONDITION_EQ: u8 = 0b000;
+
+/// `funct3` for 'branch if not equal'.
+pub const CONDITION_NE: u8 = 0b001;
+
+/// `funct3` for 'branch if unsigned lower'.
+pub const CONDITION_LTU: u8 = 0b110;
+
+/// `funct3` for 'branch if unsigned higher or same'.
+pub const CONDITION_GEU: u8 = 0b111;
+
+bitfield! {
+    /// `BType` represents the bitfields of the B-type (conditional branch) instruction format
+    /// in the RISC-V architecture. The 13-bit (2 byte aligned) offset is scattered across
+    /// multiple fields.
+    pub struct BType(u32);
+    impl Debug;
+    u8;
+
+    /// Bit 12 (sign) of the offset.
+    imm_12, set_imm_12: 31, 31;
+
+    /// Bits 10 to 5 of the offset.
+    imm_10_5, set_imm_10_5: 30, 25;
+
+    /// Register number for the second compared register.
+    pub rs2, set_rs2: 24, 20;
+
+    /// Register number for the first compared register.
+    pub rs1, set_rs1: 19, 15;
+
+    /// Condition of the branch.
+    /// The lowest bit inverts the condition.
+    pub condition, set_condition: 14, 12;
+
+    /// Bits 4 to 1 of the offset.
+    imm_4_1, set_imm_4_1: 11, 8;
+
+    /// Bit 11 of the offset.
+    imm_11, set_imm_11: 7, 7;
+
+    /// Major opcode.
+    pub opcode, set_opcode: 6, 0;
+}
+
+impl BType {
+    /// Assembles a conditional branch comparing `rs1` with `rs2`, with an offset relative to the
+    /// branch instruction.
+    pub fn assemble(
+        condition: u8,
+        rs1: u8,
+        rs2: u8,
+        offset: i32,
+    ) -> Result<Self, JitError<AllRegisters>> {
+        if !(-4096..=4094).contains(&offset) {
+            return Err(exceeds_maximum_range("[B-Type]", "-+4KiB", offset as isize));
+        }
+
+        if (offset & 1) != 0 {
+            return Err(must_be_divisible_by("[B-Type]", offset as isize, 2));
+        }
+
+        let mut value = BType(0);
+        value.set_opcode(OPCODE_BRANCH);
+        value.set_condition(condition);
+        value.set_rs1(rs1);
+        value.set_rs2(rs2);
+        value.set_imm_12(((offset >> 12) & 1) as u8);
+        value.set_imm_11(((offset >> 11) & 1) as u8);
+        value.set_imm_10_5(((offset >> 5) & 0b111111) as u8);
+        value.set_imm_4_1(((offset >> 1) & 0b1111) as u8);
+        Ok(value)
+    }
+
+    /// Returns the calculated target offset.
+    pub fn offset(&self) -> isize {
+        let imm = ((self.imm_12() as i32) << 12)
+            | ((self.imm_11() as i32) << 11)
+            | ((self.imm_10_5() as i32) << 5)
+            | ((self.imm_4_1() as i32) << 1);
+        ((imm << 19) >> 19) as isize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(CONDITION_EQ, 10, 11, 8, "6304b500")] // beq a0, a1, 8
+    #[case(CONDITION_NE, 5, 0, -4, "e39e02fe")] // bne t0, zero, -4
+    #[case(CONDITION_LTU, 1, 6, -16, "e3e860fe")] // bltu ra, t1, -16
+    #[case(CONDITION_GEU, 1, 7, 4094, "e3ff707e")] // bgeu ra, t2, 4094
+    #[case(0b100, 10, 11, -4096, "6340b580")] // blt a0, a1, -4096
+    fn branch(
+        #[case] condition: u8,
+        #[case] rs1: u8,
+        #[case] rs2: u8,
+        #[case] offset: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let ins = BType::assemble(condition, rs1, rs2, offset).unwrap();
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+        assert_eq!(offset as isize, ins.offset());
+    }
+
+    #[rstest]
+    #[case(4096)]
+    #[case(-4098)]
+    fn out_of_range(#[case] offset: i32) {
+        assert!(matches!(
+            BType::assemble(CONDITION_EQ, 10, 11, offset),
+            Err(JitError::OperandOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn misaligned() {
+        assert!(matches!(
+            BType::assemble(CONDITION_EQ, 10, 11, 3),
+            Err(JitError::InvalidOffset(_))
+        ));
+    }
+}
@@ -0,0 +1,118 @@
+use bitfield::bitfield;
+
+bitfield! {
+    /// `CompressedJump` represents the bitfields of the 16-bit `c.j` instruction
+    /// in the RISC-V 'C' extension. Only decoding is supported.
+    pub struct CompressedJump(u16);
+    impl Debug;
+    u8;
+
+    /// Always `0b101`.
+    pub funct3, _: 15, 13;
+
+    /// Scattered bits of the offset.
+    u16, imm, _: 12, 2;
+
+    /// Quadrant, always `0b01`.
+    pub op, _: 1, 0;
+}
+
+impl CompressedJump {
+    /// Returns the calculated target offset.
+    pub fn offset(&self) -> isize {
+        // offset[11|4|9:8|10|6|7|3:1|5]
+        let imm = self.imm() as i32;
+        let offset = (((imm >> 10) & 1) << 11)
+            | (((imm >> 9) & 1) << 4)
+            | (((imm >> 7) & 0b11) << 8)
+            | (((imm >> 6) & 1) << 10)
+            | (((imm >> 5) & 1) << 6)
+            | (((imm >> 4) & 1) << 7)
+            | (((imm >> 1) & 0b111) << 1)
+            | ((imm & 1) << 5);
+        ((offset << 20) >> 20) as isize
+    }
+}
+
+bitfield! {
+    /// `CompressedBranch` represents the bitfields of the 16-bit `c.beqz` and `c.bnez`
+    /// instructions in the RISC-V 'C' extension. Only decoding is supported.
+    pub struct CompressedBranch(u16);
+    impl Debug;
+    u8;
+
+    /// `0b110` for `c.beqz` and `0b111` for `c.bnez`.
+    pub funct3, _: 15, 13;
+
+    /// Bit 8 (sign) of the offset.
+    imm_8, _: 12, 12;
+
+    /// Bits 4 to 3 of the offset.
+    imm_4_3, _: 11, 10;
+
+    /// Compared register, minus 8.
+    rs1_compressed, _: 9, 7;
+
+    /// Bits 7 to 6 of the offset.
+    imm_7_6, _: 6, 5;
+
+    /// Bits 2 to 1 of the offset.
+    imm_2_1, _: 4, 3;
+
+    /// Bit 5 of the offset.
+    imm_5, _: 2, 2;
+
+    /// Quadrant, always `0b01`.
+    pub op, _: 1, 0;
+}
+
+impl CompressedBranch {
+    /// Returns the calculated target offset.
+    pub fn offset(&self) -> isize {
+        let offset = ((self.imm_8() as i32) << 8)
+            | ((self.imm_7_6() as i32) << 6)
+            | ((self.imm_5() as i32) << 5)
+            | ((self.imm_4_3() as i32) << 3)
+            | ((self.imm_2_1() as i32) << 1);
+        ((offset << 23) >> 23) as isize
+    }
+
+    /// Returns the full register number of the compared register.
+    pub fn rs1(&self) -> u8 {
+        self.rs1_compressed() + 8
+    }
+
+    /// True if this is `c.bnez`, false if it is `c.beqz`.
+    pub fn is_bnez(&self) -> bool {
+        self.funct3() == 0b111
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0xa021, 8)] // c.j 8
+    #[case(0xb001, -2048)] // c.j -2048
+    fn c_j(#[case] instruction: u16, #[case] expected_offset: isize) {
+        assert_eq!(expected_offset, CompressedJump(instruction).offset());
+    }
+
+    #[rstest]
+    #[case(0xc901, 10, false, 16)] // c.beqz a0, 16
+    #[case(0xf081, 9, true, -256)] // c.bnez s1, -256
+    #[case(0xcffd, 15, false, 254)] // c.beqz a5, 254
+    fn c_beqz_bnez(
+        #[case] instruction: u16,
+        #[case] expected_rs1: u8,
+        #[case] expected_bnez: bool,
+        #[case] expected_offset: isize,
+    ) {
+        let ins = CompressedBranch(instruction);
+        assert_eq!(expected_rs1, ins.rs1());
+        assert_eq!(expected_bnez, ins.is_bnez());
+        assert_eq!(expected_offset, ins.offset());
+    }
+}
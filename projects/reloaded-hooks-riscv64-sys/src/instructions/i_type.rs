@@ -0,0 +1,215 @@
+extern crate alloc;
+
+use super::errors::exceeds_maximum_range;
+use crate::all_registers::AllRegisters;
+use bitfield::bitfield;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+/// Opcode for loads into general purpose registers.
+pub const OPCODE_LOAD: u8 = 0b0000011;
+
+/// Opcode for loads into floating point registers.
+pub const OPCODE_LOAD_FP: u8 = 0b0000111;
+
+/// Opcode for register-immediate arithmetic.
+pub const OPCODE_OP_IMM: u8 = 0b0010011;
+
+/// Opcode for register-immediate arithmetic on the lower 32 bits.
+pub const OPCODE_OP_IMM_32: u8 = 0b0011011;
+
+/// Opcode for `jalr`.
+pub const OPCODE_JALR: u8 = 0b1100111;
+
+/// `funct3` for loading an unsigned byte.
+pub const WIDTH_BYTE_UNSIGNED: u8 = 0b100;
+
+/// `funct3` for loading a (sign extended) 32-bit word.
+pub const WIDTH_WORD: u8 = 0b010;
+
+/// `funct3` for loading a 64-bit double word.
+pub const WIDTH_DOUBLE: u8 = 0b011;
+
+bitfield! {
+    /// `IType` represents the bitfields of the I-type instruction format in the RISC-V
+    /// architecture. This format is used by register-immediate arithmetic, loads and `jalr`.
+    pub struct IType(u32);
+    impl Debug;
+    u8;
+
+    /// 12-bit signed immediate.
+    pub i16, imm, set_imm: 31, 20;
+
+    /// Register number for the source/base register.
+    pub rs1, set_rs1: 19, 15;
+
+    /// Selects the operation (or width for loads) within the opcode.
+    pub funct3, set_funct3: 14, 12;
+
+    /// Register number for the destination register.
+    pub rd, set_rd: 11, 7;
+
+    /// Major opcode.
+    pub opcode, set_opcode: 6, 0;
+}
+
+impl IType {
+    /// Assembles an I-type instruction, checking that the immediate fits in 12 bits.
+    pub fn assemble(
+        opcode: u8,
+        funct3: u8,
+        rd: u8,
+        rs1: u8,
+        imm: i32,
+    ) -> Result<Self, JitError<AllRegisters>> {
+        if !(-2048..=2047).contains(&imm) {
+            return Err(exceeds_maximum_range(
+                "[I-Type]",
+                "-2048..2047",
+                imm as isize,
+            ));
+        }
+
+        let mut value = IType(0);
+        value.set_opcode(opcode);
+        value.set_funct3(funct3);
+        value.set_rd(rd);
+        value.set_rs1(rs1);
+        value.set_imm(imm as i16);
+        Ok(value)
+    }
+
+    /// Creates an `addi rd, rs1, imm` instruction.
+    pub fn new_addi(rd: u8, rs1: u8, imm: i32) -> Result<Self, JitError<AllRegisters>> {
+        Self::assemble(OPCODE_OP_IMM, 0b000, rd, rs1, imm)
+    }
+
+    /// Creates an `addiw rd, rs1, imm` instruction.
+    pub fn new_addiw(rd: u8, rs1: u8, imm: i32) -> Result<Self, JitError<AllRegisters>> {
+        Self::assemble(OPCODE_OP_IMM_32, 0b000, rd, rs1, imm)
+    }
+
+    /// Creates a `mv rd, rs1` instruction (alias for `addi rd, rs1, 0`).
+    pub fn new_mov(rd: u8, rs1: u8) -> Self {
+        let mut value = IType(0);
+        value.set_opcode(OPCODE_OP_IMM);
+        value.set_rd(rd);
+        value.set_rs1(rs1);
+        value
+    }
+
+    /// Creates an `slli rd, rs1, shift` instruction. The shift amount is masked to 6 bits.
+    pub fn new_slli(rd: u8, rs1: u8, shift: u8) -> Self {
+        let mut value = IType(0);
+        value.set_opcode(OPCODE_OP_IMM);
+        value.set_funct3(0b001);
+        value.set_rd(rd);
+        value.set_rs1(rs1);
+        value.set_imm((shift & 0b111111) as i16);
+        value
+    }
+
+    /// Creates a `jalr rd, imm(rs1)` instruction.
+    pub fn new_jalr(rd: u8, rs1: u8, imm: i32) -> Result<Self, JitError<AllRegisters>> {
+        Self::assemble(OPCODE_JALR, 0b000, rd, rs1, imm)
+    }
+
+    /// Creates a `ret` instruction (alias for `jalr zero, 0(ra)`).
+    pub fn new_ret() -> Self {
+        let mut value = IType(0);
+        value.set_opcode(OPCODE_JALR);
+        value.set_rs1(1);
+        value
+    }
+
+    /// Creates a load of an integer register, with the width given by one of the `WIDTH_` constants.
+    pub fn new_load(width: u8, rd: u8, rs1: u8, imm: i32) -> Result<Self, JitError<AllRegisters>> {
+        Self::assemble(OPCODE_LOAD, width, rd, rs1, imm)
+    }
+
+    /// Creates an `fld rd, imm(rs1)` instruction.
+    pub fn new_fld(rd: u8, rs1: u8, imm: i32) -> Result<Self, JitError<AllRegisters>> {
+        Self::assemble(OPCODE_LOAD_FP, WIDTH_DOUBLE, rd, rs1, imm)
+    }
+
+    /// Creates a `nop` instruction (alias for `addi zero, zero, 0`).
+    pub fn new_nop() -> Self {
+        Self::new_mov(0, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(10, 11, -1, "1385f5ff")] // addi a0, a1, -1
+    #[case(2, 2, -8, "130181ff")] // addi sp, sp, -8
+    fn addi(#[case] rd: u8, #[case] rs1: u8, #[case] imm: i32, #[case] expected_hex: &str) {
+        let ins = IType::new_addi(rd, rs1, imm).unwrap();
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+        assert_eq!(imm, ins.imm() as i32);
+    }
+
+    #[test]
+    fn addiw() {
+        // addiw t1, t1, 2047
+        let ins = IType::new_addiw(6, 6, 2047).unwrap();
+        assert_eq!("1b03f37f", ins.0.to_hex_string());
+    }
+
+    #[test]
+    fn slli() {
+        // slli t1, t1, 32
+        assert_eq!("13130302", IType::new_slli(6, 6, 32).0.to_hex_string());
+    }
+
+    #[rstest]
+    #[case(0, 1, 0, "67800000")] // jalr zero, 0(ra)
+    #[case(1, 6, -2048, "e7000380")] // jalr ra, -2048(t1)
+    fn jalr(#[case] rd: u8, #[case] rs1: u8, #[case] imm: i32, #[case] expected_hex: &str) {
+        let ins = IType::new_jalr(rd, rs1, imm).unwrap();
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+    }
+
+    #[rstest]
+    #[case(WIDTH_DOUBLE, 10, 2, 16, "03350101")] // ld a0, 16(sp)
+    #[case(WIDTH_BYTE_UNSIGNED, 5, 4, 100, "83424206")] // lbu t0, 100(tp)
+    #[case(WIDTH_WORD, 5, 2, 4, "83224100")] // lw t0, 4(sp)
+    fn load(
+        #[case] width: u8,
+        #[case] rd: u8,
+        #[case] rs1: u8,
+        #[case] imm: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let ins = IType::new_load(width, rd, rs1, imm).unwrap();
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+    }
+
+    #[test]
+    fn fld() {
+        // fld fa0, -8(sp)
+        assert_eq!(
+            "073581ff",
+            IType::new_fld(10, 2, -8).unwrap().0.to_hex_string()
+        );
+    }
+
+    #[test]
+    fn aliases() {
+        assert_eq!("67800000", IType::new_ret().0.to_hex_string());
+        assert_eq!("13000000", IType::new_nop().0.to_hex_string());
+    }
+
+    #[rstest]
+    #[case(2048)]
+    #[case(-2049)]
+    fn out_of_range(#[case] imm: i32) {
+        assert!(matches!(
+            IType::new_addi(10, 10, imm),
+            Err(JitError::OperandOutOfRange(_))
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+extern crate alloc;
+
+use super::errors::{exceeds_maximum_range, must_be_divisible_by};
+use crate::all_registers::AllRegisters;
+use bitfield::bitfield;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+/// Opcode for `jal`.
+pub const OPCODE_JAL: u8 = 0b1101111;
+
+bitfield! {
+    /// `JType` represents the bitfields of the J-type instruction format in the RISC-V
+    /// architecture, used by `jal`. The 21-bit (2 byte aligned) offset is scattered across
+    /// multiple fields.
+    pub struct JType(u32);
+    impl Debug;
+    u8;
+
+    /// Bit 20 (sign) of the offset.
+    imm_20, set_imm_20: 31, 31;
+
+    /// Bits 10 to 1 of the offset.
+    u16, imm_10_1, set_imm_10_1: 30, 21;
+
+    /// Bit 11 of the offset.
+    imm_11, set_imm_11: 20, 20;
+
+    /// Bits 19 to 12 of the offset.
+    imm_19_12, set_imm_19_12: 19, 12;
+
+    /// Register number receiving the return address. `zero` for a plain jump.
+    pub rd, set_rd: 11, 7;
+
+    /// Major opcode.
+    pub opcode, set_opcode: 6, 0;
+}
+
+impl JType {
+    /// Assembles a `jal rd, offset` instruction, with an offset relative to the instruction.
+    pub fn assemble_jal(rd: u8, offset: i32) -> Result<Self, JitError<AllRegisters>> {
+        if !(-0x100000..=0xFFFFE).contains(&offset) {
+            return Err(exceeds_maximum_range("[JAL]", "-+1MiB", offset as isize));
+        }
+
+        if (offset & 1) != 0 {
+            return Err(must_be_divisible_by("[JAL]", offset as isize, 2));
+        }
+
+        let mut value = JType(0);
+        value.set_opcode(OPCODE_JAL);
+        value.set_rd(rd);
+        value.set_imm_20(((offset >> 20) & 1) as u8);
+        value.set_imm_19_12(((offset >> 12) & 0xFF) as u8);
+        value.set_imm_11(((offset >> 11) & 1) as u8);
+        value.set_imm_10_1(((offset >> 1) & 0x3FF) as u16);
+        Ok(value)
+    }
+
+    /// Returns the calculated target offset.
+    pub fn offset(&self) -> isize {
+        let imm = ((self.imm_20() as i32) << 20)
+            | ((self.imm_19_12() as i32) << 12)
+            | ((self.imm_11() as i32) << 11)
+            | ((self.imm_10_1() as i32) << 1);
+        ((imm << 11) >> 11) as isize
+    }
+
+    /// True if this instruction writes the return address, i.e. is a 'call'.
+    pub fn is_link(&self) -> bool {
+        self.rd() != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(1, 0, "ef000000")] // jal ra, 0
+    #[case(1, -8, "eff09fff")] // jal ra, -8
+    #[case(0, 0xFFFFE, "6ff0ff7f")] // jal zero, 1048574
+    #[case(0, -0x100000, "6f000080")] // jal zero, -1048576
+    fn jal(#[case] rd: u8, #[case] offset: i32, #[case] expected_hex: &str) {
+        let ins = JType::assemble_jal(rd, offset).unwrap();
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+        assert_eq!(offset as isize, ins.offset());
+        assert_eq!(rd != 0, ins.is_link());
+    }
+
+    #[rstest]
+    #[case(0x100000)]
+    #[case(-0x100002)]
+    fn out_of_range(#[case] offset: i32) {
+        assert!(matches!(
+            JType::assemble_jal(0, offset),
+            Err(JitError::OperandOutOfRange(_))
+        ));
+    }
+}
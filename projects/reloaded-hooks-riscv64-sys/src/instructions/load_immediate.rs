@@ -0,0 +1,146 @@
+use super::{i_type::IType, u_type::UType};
+use smallvec::SmallVec;
+
+/// Maximum number of instructions emitted by [`load_immediate`].
+pub const MAX_LOAD_IMMEDIATE_INSTRUCTIONS: usize = 8;
+
+/// Instructions emitted by [`load_immediate`].
+pub type LoadImmediateResult = SmallVec<[u32; MAX_LOAD_IMMEDIATE_INSTRUCTIONS]>;
+
+/// Produces the sequence of instructions for the `li` pseudo instruction, loading an arbitrary
+/// 64-bit constant into the `destination` register.
+///
+/// Uses `LUI` + `ADDIW` for values which fit in 32 bits, otherwise recursively builds the upper
+/// bits, followed by `SLLI` + `ADDI` for each remaining chunk of up to 12 bits.
+/// This uses up to 8 instructions.
+///
+/// # Parameters
+///
+/// * `destination`: The destination register.
+/// * `value`: The immediate value to be moved to the destination.
+pub fn load_immediate(destination: u8, value: usize) -> LoadImmediateResult {
+    let mut result = LoadImmediateResult::new();
+    load_immediate_impl(destination, value as i64, &mut result);
+    result
+}
+
+fn load_immediate_impl(destination: u8, value: i64, result: &mut LoadImmediateResult) {
+    let lo12 = (value << 52) >> 52;
+
+    if value == value as i32 as i64 {
+        let hi20 = (value.wrapping_add(0x800) >> 12) & 0xFFFFF;
+        if hi20 != 0 {
+            result.push(UType::new_lui(destination, hi20 as i32).0.to_le());
+        }
+
+        // Note: ADDIW is needed for values just under 2GiB, where LUI produces a negative value.
+        if hi20 != 0 && lo12 != 0 {
+            result.push(
+                IType::new_addiw(destination, destination, lo12 as i32)
+                    .unwrap()
+                    .0
+                    .to_le(),
+            );
+        } else if hi20 == 0 {
+            result.push(
+                IType::new_addi(destination, 0, lo12 as i32)
+                    .unwrap()
+                    .0
+                    .to_le(),
+            );
+        }
+
+        return;
+    }
+
+    // Build the upper bits with the trailing zeroes stripped, then shift them into place.
+    let hi52 = value.wrapping_add(0x800) >> 12;
+    let shift = 12 + hi52.trailing_zeros();
+    let hi_bits = 64 - shift;
+    let hi = ((hi52 >> (shift - 12)) << (64 - hi_bits)) >> (64 - hi_bits);
+
+    load_immediate_impl(destination, hi, result);
+    result.push(
+        IType::new_slli(destination, destination, shift as u8)
+            .0
+            .to_le(),
+    );
+    if lo12 != 0 {
+        result.push(
+            IType::new_addi(destination, destination, lo12 as i32)
+                .unwrap()
+                .0
+                .to_le(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::{
+        i_type::{OPCODE_OP_IMM, OPCODE_OP_IMM_32},
+        u_type::OPCODE_LUI,
+    };
+    use crate::test_helpers::instruction_buffer_as_hex_u32;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, "13030000")] // addi t1, zero, 0
+    #[case(usize::MAX, "1303f0ff")] // addi t1, zero, -1
+    #[case(0x1000, "37130000")] // lui t1, 1
+    #[case(0x12345678, "375334121b038367")]
+    // lui t1, 0x12345; addiw t1, t1, 0x678
+    // lui t1, 0x400; addiw t1, t1, -119; slli t1, t1, 13; addi t1, t1, 837; slli t1, t1, 12; addi t1, t1, 1656
+    #[case(0x7FFF12345678, "370340001b0393f81313d300130353341313c30013038367")]
+    fn encodes_expected(#[case] value: usize, #[case] expected_hex: &str) {
+        assert_eq!(
+            expected_hex,
+            instruction_buffer_as_hex_u32(&load_immediate(6, value))
+        );
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(2047)]
+    #[case(2048)]
+    #[case(0x7FFFF7FF)]
+    #[case(0x7FFFF800)]
+    #[case(0x7FFFFFFF)]
+    #[case(0x80000000)]
+    #[case(0xFFFFFFFF)]
+    #[case(0x1_0000_0000)]
+    #[case(0x7FFF12345678)]
+    #[case(0x8000_0000_0000_0000)]
+    #[case(0x7FFF_FFFF_FFFF_FFFF)]
+    #[case(0xDEAD_BEEF_CAFE_BABE)]
+    #[case(0xFFFF_FFFF_8000_0000)]
+    #[case(0xFFFF_FFFF_7FFF_FFFF)]
+    #[case(usize::MAX)]
+    fn produces_value(#[case] value: usize) {
+        let instructions = load_immediate(6, value);
+        assert!(instructions.len() <= MAX_LOAD_IMMEDIATE_INSTRUCTIONS);
+        assert_eq!(value, emulate(&instructions));
+    }
+
+    /// Runs the instructions emitted by [`load_immediate`] and returns the resulting register value.
+    fn emulate(instructions: &[u32]) -> usize {
+        let mut reg: i64 = 0;
+        for ins in instructions {
+            let ins = u32::from_le(*ins);
+            let opcode = (ins & 0x7F) as u8;
+            let imm12 = (ins as i32 >> 20) as i64;
+            let src = if (ins >> 15) & 0x1F == 0 { 0 } else { reg };
+            reg = match (opcode, (ins >> 12) & 0b111) {
+                (OPCODE_LUI, _) => (ins & 0xFFFFF000) as i32 as i64,
+                (OPCODE_OP_IMM, 0b000) => src.wrapping_add(imm12),
+                (OPCODE_OP_IMM, 0b001) => src << (imm12 & 0x3F),
+                (OPCODE_OP_IMM_32, 0b000) => src.wrapping_add(imm12) as i32 as i64,
+                _ => panic!("Unexpected instruction {:08x}", ins),
+            };
+        }
+
+        reg as usize
+    }
+}
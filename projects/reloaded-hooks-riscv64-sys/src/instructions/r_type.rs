@@ -0,0 +1,100 @@
+use bitfield::bitfield;
+
+/// Opcode for floating point operations.
+pub const OPCODE_OP_FP: u8 = 0b1010011;
+
+/// Opcode for atomic memory operations.
+pub const OPCODE_AMO: u8 = 0b0101111;
+
+bitfield! {
+    /// `RType` represents the bitfields of the R-type (register-register) instruction format
+    /// in the RISC-V architecture.
+    pub struct RType(u32);
+    impl Debug;
+    u8;
+
+    /// Selects the operation within the opcode.
+    pub funct7, set_funct7: 31, 25;
+
+    /// Register number for the second source register.
+    pub rs2, set_rs2: 24, 20;
+
+    /// Register number for the first source register.
+    pub rs1, set_rs1: 19, 15;
+
+    /// Selects the operation within the opcode.
+    pub funct3, set_funct3: 14, 12;
+
+    /// Register number for the destination register.
+    pub rd, set_rd: 11, 7;
+
+    /// Major opcode.
+    pub opcode, set_opcode: 6, 0;
+}
+
+impl RType {
+    fn assemble(opcode: u8, funct3: u8, funct7: u8, rd: u8, rs1: u8, rs2: u8) -> Self {
+        let mut value = RType(0);
+        value.set_opcode(opcode);
+        value.set_funct3(funct3);
+        value.set_funct7(funct7);
+        value.set_rd(rd);
+        value.set_rs1(rs1);
+        value.set_rs2(rs2);
+        value
+    }
+
+    /// Creates a `fmv.d rd, rs` instruction (alias for `fsgnj.d rd, rs, rs`).
+    pub fn new_fmv_d(rd: u8, rs: u8) -> Self {
+        Self::assemble(OPCODE_OP_FP, 0b000, 0b0010001, rd, rs, rs)
+    }
+
+    /// Creates a `fmv.x.d rd, rs` instruction, moving the bits of a floating point register
+    /// to an integer register.
+    pub fn new_fmv_x_d(rd: u8, rs: u8) -> Self {
+        Self::assemble(OPCODE_OP_FP, 0b000, 0b1110001, rd, rs, 0)
+    }
+
+    /// Creates a `fmv.d.x rd, rs` instruction, moving the bits of an integer register
+    /// to a floating point register.
+    pub fn new_fmv_d_x(rd: u8, rs: u8) -> Self {
+        Self::assemble(OPCODE_OP_FP, 0b000, 0b1111001, rd, rs, 0)
+    }
+
+    /// Creates a `amoadd.d.aqrl rd, value, (address)` instruction, which atomically adds `value`
+    /// to the double word at `address`, and places the old value in `rd`.
+    pub fn new_amoadd_d(rd: u8, value: u8, address: u8) -> Self {
+        // funct5 = 0 (amoadd), aq = 1, rl = 1
+        Self::assemble(OPCODE_AMO, 0b011, 0b0000011, rd, address, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+
+    #[test]
+    fn fmv_d() {
+        // fsgnj.d fa0, fa1, fa1
+        assert_eq!("5385b522", RType::new_fmv_d(10, 11).0.to_hex_string());
+    }
+
+    #[test]
+    fn fmv_x_d() {
+        // fmv.x.d a0, fa0
+        assert_eq!("530505e2", RType::new_fmv_x_d(10, 10).0.to_hex_string());
+    }
+
+    #[test]
+    fn fmv_d_x() {
+        // fmv.d.x fa0, a0
+        assert_eq!("530505f2", RType::new_fmv_d_x(10, 10).0.to_hex_string());
+    }
+
+    #[test]
+    fn amoadd_d() {
+        // amoadd.d.aqrl zero, t1, (t0)
+        assert_eq!("2fb06206", RType::new_amoadd_d(0, 6, 5).0.to_hex_string());
+    }
+}
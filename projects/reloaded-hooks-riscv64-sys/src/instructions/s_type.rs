@@ -0,0 +1,131 @@
+extern crate alloc;
+
+use super::errors::exceeds_maximum_range;
+use crate::all_registers::AllRegisters;
+use bitfield::bitfield;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+/// Opcode for stores from general purpose registers.
+pub const OPCODE_STORE: u8 = 0b0100011;
+
+/// Opcode for stores from floating point registers.
+pub const OPCODE_STORE_FP: u8 = 0b0100111;
+
+/// `funct3` for storing a byte.
+pub const WIDTH_BYTE: u8 = 0b000;
+
+/// `funct3` for storing a 32-bit word.
+pub const WIDTH_WORD: u8 = 0b010;
+
+/// `funct3` for storing a 64-bit double word.
+pub const WIDTH_DOUBLE: u8 = 0b011;
+
+bitfield! {
+    /// `SType` represents the bitfields of the S-type (store) instruction format in the RISC-V
+    /// architecture. The 12-bit immediate is split across two fields.
+    pub struct SType(u32);
+    impl Debug;
+    u8;
+
+    /// Upper 7 bits of the immediate.
+    imm_11_5, set_imm_11_5: 31, 25;
+
+    /// Register number for the value to store.
+    pub rs2, set_rs2: 24, 20;
+
+    /// Register number for the base address.
+    pub rs1, set_rs1: 19, 15;
+
+    /// Width of the store.
+    pub funct3, set_funct3: 14, 12;
+
+    /// Lower 5 bits of the immediate.
+    imm_4_0, set_imm_4_0: 11, 7;
+
+    /// Major opcode.
+    pub opcode, set_opcode: 6, 0;
+}
+
+impl SType {
+    /// Assembles a store instruction, checking that the offset fits in 12 bits.
+    pub fn assemble(
+        opcode: u8,
+        width: u8,
+        rs2: u8,
+        rs1: u8,
+        offset: i32,
+    ) -> Result<Self, JitError<AllRegisters>> {
+        if !(-2048..=2047).contains(&offset) {
+            return Err(exceeds_maximum_range(
+                "[S-Type]",
+                "-2048..2047",
+                offset as isize,
+            ));
+        }
+
+        let mut value = SType(0);
+        value.set_opcode(opcode);
+        value.set_funct3(width);
+        value.set_rs2(rs2);
+        value.set_rs1(rs1);
+        value.set_imm_11_5(((offset >> 5) & 0b1111111) as u8);
+        value.set_imm_4_0((offset & 0b11111) as u8);
+        Ok(value)
+    }
+
+    /// Creates a store of an integer register `rs2` to `offset(rs1)`, with the width given by one
+    /// of the `WIDTH_` constants.
+    pub fn new_store(
+        width: u8,
+        rs2: u8,
+        rs1: u8,
+        offset: i32,
+    ) -> Result<Self, JitError<AllRegisters>> {
+        Self::assemble(OPCODE_STORE, width, rs2, rs1, offset)
+    }
+
+    /// Creates an `fsd rs2, offset(rs1)` instruction.
+    pub fn new_fsd(rs2: u8, rs1: u8, offset: i32) -> Result<Self, JitError<AllRegisters>> {
+        Self::assemble(OPCODE_STORE_FP, WIDTH_DOUBLE, rs2, rs1, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(WIDTH_DOUBLE, 10, 2, 0, "2330a100")] // sd a0, 0(sp)
+    #[case(WIDTH_BYTE, 5, 4, 100, "23025206")] // sb t0, 100(tp)
+    #[case(WIDTH_BYTE, 0, 4, 2047, "a30f027e")] // sb zero, 2047(tp)
+    #[case(WIDTH_WORD, 5, 2, -4, "232e51fe")] // sw t0, -4(sp)
+    fn store(
+        #[case] width: u8,
+        #[case] rs2: u8,
+        #[case] rs1: u8,
+        #[case] offset: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let ins = SType::new_store(width, rs2, rs1, offset).unwrap();
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+    }
+
+    #[test]
+    fn fsd() {
+        // fsd fa0, -16(sp)
+        let ins = SType::new_fsd(10, 2, -16).unwrap();
+        assert_eq!("2738a1fe", ins.0.to_hex_string());
+    }
+
+    #[rstest]
+    #[case(2048)]
+    #[case(-2049)]
+    fn out_of_range(#[case] offset: i32) {
+        assert!(matches!(
+            SType::new_store(WIDTH_DOUBLE, 10, 2, offset),
+            Err(JitError::OperandOutOfRange(_))
+        ));
+    }
+}
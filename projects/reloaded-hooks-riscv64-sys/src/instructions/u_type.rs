@@ -0,0 +1,105 @@
+use bitfield::bitfield;
+
+/// Opcode for `lui`.
+pub const OPCODE_LUI: u8 = 0b0110111;
+
+/// Opcode for `auipc`.
+pub const OPCODE_AUIPC: u8 = 0b0010111;
+
+bitfield! {
+    /// `UType` represents the bitfields of the U-type instruction format in the RISC-V
+    /// architecture, used by `lui` and `auipc`.
+    pub struct UType(u32);
+    impl Debug;
+    u8;
+
+    /// Upper 20 bits of the (sign extended) 32-bit immediate.
+    pub i32, imm20, set_imm20: 31, 12;
+
+    /// Register number for the destination register.
+    pub rd, set_rd: 11, 7;
+
+    /// Major opcode.
+    pub opcode, set_opcode: 6, 0;
+}
+
+impl UType {
+    /// Creates a `lui rd, imm20` instruction. Only the lower 20 bits of `imm20` are used.
+    pub fn new_lui(rd: u8, imm20: i32) -> Self {
+        Self::assemble(OPCODE_LUI, rd, imm20)
+    }
+
+    /// Creates an `auipc rd, imm20` instruction. Only the lower 20 bits of `imm20` are used.
+    pub fn new_auipc(rd: u8, imm20: i32) -> Self {
+        Self::assemble(OPCODE_AUIPC, rd, imm20)
+    }
+
+    fn assemble(opcode: u8, rd: u8, imm20: i32) -> Self {
+        let mut value = UType(0);
+        value.set_opcode(opcode);
+        value.set_rd(rd);
+        value.set_imm20(imm20);
+        value
+    }
+
+    /// Returns the value added to the register (or PC), i.e. the immediate shifted left by 12.
+    pub fn offset(&self) -> isize {
+        (self.imm20() as isize) << 12
+    }
+}
+
+/// Splits a PC relative offset into the `hi` part for `auipc`, and the `lo` part for the
+/// instruction which follows it, accounting for the sign extension of the lower 12 bits.
+///
+/// Returns `None` if the offset cannot be reached with an `auipc` pair (roughly +-2GiB).
+pub fn split_pc_relative_offset(offset: isize) -> Option<(i32, i32)> {
+    let hi = (offset as i64).wrapping_add(0x800) >> 12;
+    if !(-0x80000..=0x7FFFF).contains(&hi) {
+        return None;
+    }
+
+    let lo = (offset as i64) - (hi << 12);
+    Some((hi as i32, lo as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ToHexString;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(6, 0x12345, "37533412", 0x12345000)] // lui t1, 0x12345
+    #[case(6, 0xfffff, "37f3ffff", -4096)] // lui t1, 0xfffff
+    fn lui(
+        #[case] rd: u8,
+        #[case] imm20: i32,
+        #[case] expected_hex: &str,
+        #[case] expected_offset: isize,
+    ) {
+        let ins = UType::new_lui(rd, imm20);
+        assert_eq!(expected_hex, ins.0.to_hex_string());
+        assert_eq!(expected_offset, ins.offset());
+    }
+
+    #[rstest]
+    #[case(1, 0x80000, "97000080")] // auipc ra, 0x80000
+    #[case(6, 0, "17030000")] // auipc t1, 0
+    fn auipc(#[case] rd: u8, #[case] imm20: i32, #[case] expected_hex: &str) {
+        assert_eq!(expected_hex, UType::new_auipc(rd, imm20).0.to_hex_string());
+    }
+
+    #[rstest]
+    #[case(0, Some((0, 0)))]
+    #[case(0x7FF, Some((0, 0x7FF)))]
+    #[case(0x800, Some((1, -0x800)))]
+    #[case(-0x800, Some((0, -0x800)))]
+    #[case(0x12345678, Some((0x12345, 0x678)))]
+    #[case(0x7FFFF7FF, Some((0x7FFFF, 0x7FF)))]
+    #[case(0x7FFFF800, None)]
+    #[case(-0x80000800, Some((-0x80000, -0x800)))]
+    #[case(-0x80000801, None)]
+    fn split_offset(#[case] offset: isize, #[case] expected: Option<(i32, i32)>) {
+        assert_eq!(expected, split_pc_relative_offset(offset));
+    }
+}
@@ -0,0 +1,220 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters,
+    code_rewriter::riscv64_rewriter::{is_c_j, is_jal},
+    helpers::encode_into,
+    instructions::{compressed::CompressedJump, i_type::IType, j_type::JType},
+    jit_instructions::{
+        atomic_add::encode_atomic_add,
+        branch_absolute::{encode_call_absolute, encode_jump_absolute},
+        branch_ip_relative::{encode_call_ip_relative, encode_jump_ip_relative},
+        branch_relative::{encode_call_relative, encode_jump_relative},
+        enter_guard::encode_enter_guard,
+        exit_guard::encode_exit_guard,
+        filter_caller::encode_filter_caller,
+        jump_absolute_indirect::encode_jump_absolute_indirect,
+        mov::encode_mov,
+        mov_from_stack::encode_mov_from_stack,
+        mov_to_stack::encode_mov_to_stack,
+        multi_pop::encode_multi_pop,
+        multi_push::encode_multi_push,
+        pop::encode_pop,
+        push::encode_push,
+        push_constant::encode_push_constant,
+        push_stack::encode_push_stack,
+        ret::encode_return,
+        stackalloc::encode_stackalloc,
+        xchg::encode_xchg,
+    },
+};
+use alloc::vec::Vec;
+use core::mem::size_of;
+use reloaded_hooks_portable::api::jit::{
+    call_relative_operation::CallRelativeOperation,
+    compiler::{DecodeCallTargetResult, Jit, JitCapabilities, JitError},
+    jump_absolute_operation::JumpAbsoluteOperation,
+    jump_relative_operation::JumpRelativeOperation,
+    operation::Operation,
+};
+use reloaded_hooks_portable::helpers::read_code::read_code_as;
+
+pub struct JitRiscV64 {}
+
+impl Jit<AllRegisters> for JitRiscV64 {
+    fn compile(
+        address: usize,
+        operations: &[Operation<AllRegisters>],
+    ) -> Result<Vec<u8>, JitError<AllRegisters>> {
+        // Initialize Assembler
+
+        // Usually most opcodes will correspond to 1-2 instructions, however there may be more
+        // in some cases (e.g. loading constants), so we reserve accordingly.
+
+        // The JIT never emits compressed instructions, so each instruction is 4 bytes.
+        let mut buf = Vec::with_capacity(operations.len() * 2 * size_of::<i32>());
+        Self::compile_with_buf(address, operations, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn compile_with_buf(
+        address: usize,
+        operations: &[Operation<AllRegisters>],
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        let mut pc = address;
+        encode_into(buf, |buf_i32| {
+            for operation in operations {
+                encode_instruction_riscv64(operation, &mut pc, buf_i32)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn stack_entry_misalignment() -> u32 {
+        0 // uses RA
+    }
+
+    fn code_alignment() -> u32 {
+        4
+    }
+
+    fn max_relative_jump_distances() -> &'static [usize] {
+        // We remove a -4 value because forward jumps can't go as far.
+        &[
+            (1024 * 4) - 4,              // -+ 4 KiB (Branch Conditional)
+            (1024 * 1024) - 4,           // -+ 1 MiB (JAL)
+            (1024 * 1024 * 2048) - 4096, // -+ 2 GiB (AUIPC + JALR), minus sign extension of the low bits
+        ]
+    }
+
+    fn get_jit_capabilities() -> JitCapabilities {
+        JitCapabilities::CAN_MULTI_PUSH
+            | JitCapabilities::CAN_ENCODE_IP_RELATIVE_CALL
+            | JitCapabilities::CAN_ENCODE_IP_RELATIVE_JUMP
+    }
+
+    fn max_branch_bytes() -> u32 {
+        36 // LI (up to 8 instructions) + JALR
+    }
+
+    fn fill_nops(arr: &mut [u8]) {
+        let nop = IType::new_nop().0.to_le_bytes();
+        const C_NOP: [u8; 2] = [0x01, 0x00]; // c.nop
+
+        // Stolen code may end on a compressed instruction, so a 2 byte remainder is possible.
+        for chunk in arr.chunks_mut(4) {
+            if chunk.len() == 4 {
+                chunk.copy_from_slice(&nop);
+            } else {
+                chunk.copy_from_slice(&C_NOP[..chunk.len()]);
+            }
+        }
+    }
+
+    fn encode_jump(
+        x: &JumpRelativeOperation<AllRegisters>,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_into(buf, |buf_i32| encode_jump_relative(x, pc, buf_i32))
+    }
+
+    fn max_relative_jump_bytes() -> usize {
+        8 // AUIPC + JALR
+    }
+
+    fn encode_call(
+        x: &CallRelativeOperation,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_into(buf, |buf_i32| encode_call_relative(x, pc, buf_i32))
+    }
+
+    fn decode_call_target(
+        ins_address: usize,
+        ins_length: usize,
+    ) -> Result<DecodeCallTargetResult, &'static str> {
+        if ins_length < 2 {
+            return Err("[RISC-V: decode_call_target] Instruction is too short.");
+        }
+
+        let low_half: u16 = u16::from_le(unsafe { read_code_as::<u16>(ins_address) });
+        if ins_length == 2 {
+            if !is_c_j(low_half) {
+                return Err("[RISC-V: decode_call_target] This is not a branch instruction.");
+            }
+
+            let offset = CompressedJump(low_half).offset();
+            let addr = (ins_address as isize).wrapping_add(offset) as usize;
+            return Ok(DecodeCallTargetResult::new(addr, false));
+        }
+
+        let num: u32 = u32::from_le(unsafe { read_code_as::<u32>(ins_address) });
+        if !is_jal(num) {
+            return Err("[RISC-V: decode_call_target] This is not a branch instruction.");
+        }
+
+        let instruction = JType(num);
+        let addr = (ins_address as isize).wrapping_add(instruction.offset()) as usize;
+        Ok(DecodeCallTargetResult::new(addr, instruction.is_link()))
+    }
+
+    fn encode_abs_jump(
+        x: &JumpAbsoluteOperation<AllRegisters>,
+        pc: &mut usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), JitError<AllRegisters>> {
+        encode_into(buf, |buf_i32| encode_jump_absolute(x, pc, buf_i32))
+    }
+
+    fn max_standard_relative_call_distance() -> usize {
+        (1024 * 1024) - 4
+    }
+
+    fn standard_relative_call_bytes() -> usize {
+        4
+    }
+
+    fn standard_register_size() -> usize {
+        8
+    }
+}
+
+fn encode_instruction_riscv64(
+    operation: &Operation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    match operation {
+        Operation::None => Ok(()),
+        Operation::Mov(x) => encode_mov(x, pc, buf),
+        Operation::MovFromStack(x) => encode_mov_from_stack(x, pc, buf),
+        Operation::Push(x) => encode_push(x, pc, buf),
+        Operation::PushStack(x) => encode_push_stack(x, pc, buf),
+        Operation::PushConst(x) => encode_push_constant(x, pc, buf),
+        Operation::StackAlloc(x) => encode_stackalloc(x, pc, buf),
+        Operation::Pop(x) => encode_pop(x, pc, buf),
+        Operation::Xchg(x) => encode_xchg(x, pc, buf),
+        Operation::CallAbsolute(x) => encode_call_absolute(x, pc, buf),
+        Operation::CallRelative(x) => encode_call_relative(x, pc, buf),
+        Operation::JumpRelative(x) => encode_jump_relative(x, pc, buf),
+        Operation::JumpAbsolute(x) => encode_jump_absolute(x, pc, buf),
+        Operation::JumpAbsoluteIndirect(x) => encode_jump_absolute_indirect(x, pc, buf),
+        Operation::Return(x) => encode_return(x, pc, buf),
+        Operation::CallIpRelative(x) => encode_call_ip_relative(x, pc, buf),
+        Operation::JumpIpRelative(x) => encode_jump_ip_relative(x, pc, buf),
+        Operation::MultiPush(x) => encode_multi_push(x, pc, buf),
+        Operation::MultiPop(x) => encode_multi_pop(x, pc, buf),
+        Operation::MovToStack(x) => encode_mov_to_stack(x, pc, buf),
+        Operation::AtomicAdd(x) => encode_atomic_add(x, pc, buf),
+        Operation::EnterGuard(x) => encode_enter_guard(x, pc, buf),
+        Operation::ExitGuard(x) => encode_exit_guard(x, pc, buf),
+        Operation::FilterCaller(x) => encode_filter_caller(x, pc, buf),
+        // Only needed for moving between register kinds with no direct move, e.g. x87 to SSE.
+        Operation::MovFloatFromStack(x) => Err(JitError::InvalidRegister(x.target)),
+        Operation::MovFloatToStack(x) => Err(JitError::InvalidRegister(x.register)),
+    }
+}
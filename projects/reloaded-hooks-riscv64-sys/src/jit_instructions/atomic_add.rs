@@ -0,0 +1,89 @@
+extern crate alloc;
+
+use super::push_constant::encode_mov_constant_to_reg;
+use crate::{
+    all_registers::AllRegisters::{self, zero},
+    instructions::r_type::RType,
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::AtomicAdd};
+
+/// Encoded as LI + LI + AMOADD.D.AQRL.
+/// Uses 2 scratch registers; for the address and the value.
+pub fn encode_atomic_add(
+    x: &AtomicAdd<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let scratch = x.scratch.borrow();
+    let mut regs = scratch.iter().filter(|reg| reg.is_gpr());
+    let (address, value) = match (regs.next(), regs.next()) {
+        (Some(address), Some(value)) => (
+            address.register_number() as u8,
+            value.register_number() as u8,
+        ),
+        _ => {
+            return Err(JitError::NoScratchRegister(
+                "for AtomicAdd. 2 registers are required.".to_string(),
+            ))
+        }
+    };
+
+    encode_mov_constant_to_reg(x.address, address, pc, buf)?;
+    encode_mov_constant_to_reg(x.value as isize as usize, value, pc, buf)?;
+
+    // The previous value is not needed, so it is discarded into the zero register.
+    let add = RType::new_amoadd_d(zero as u8, value, address);
+    buf.push(add.0.to_le() as i32);
+    *pc += 4;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::atomic_add::encode_atomic_add;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // lui t0, 1; addiw t0, t0, 564; addi t1, zero, 1; amoadd.d.aqrl zero, t1, (t0)
+    #[case(0x1234, 1, vec![t0, t1], "b71200009b824223130310002fb06206")]
+    // lui t0, 1; addiw t0, t0, 564; addi t1, zero, -1; amoadd.d.aqrl zero, t1, (t0)
+    #[case(0x1234, -1, vec![t0, t1], "b71200009b8242231303f0ff2fb06206")]
+    // Float registers are skipped.
+    #[case(0x1234, 1, vec![ft0, t0, t1], "b71200009b824223130310002fb06206")]
+    fn standard_cases(
+        #[case] address: usize,
+        #[case] value: i32,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = AtomicAdd::new(address, value, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_atomic_add(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(vec![t0])]
+    #[case(vec![t0, ft0])]
+    fn error_on_insufficient_scratch(#[case] scratch: Vec<AllRegisters>) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = AtomicAdd::new(0x1234, 1, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_atomic_add(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
@@ -0,0 +1,113 @@
+extern crate alloc;
+
+use super::push_constant::encode_mov_constant_to_reg;
+use crate::{
+    all_registers::AllRegisters::{self, ra, zero},
+    instructions::i_type::IType,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    call_absolute_operation::CallAbsoluteOperation, compiler::JitError,
+    jump_absolute_operation::JumpAbsoluteOperation,
+};
+
+/// Encoded as LI + JALR ZERO
+pub fn encode_jump_absolute(
+    x: &JumpAbsoluteOperation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    encode_branch_absolute(x.target_address, x.scratch_register, zero as u8, pc, buf)
+}
+
+/// Encoded as LI + JALR RA
+pub fn encode_call_absolute(
+    x: &CallAbsoluteOperation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    encode_branch_absolute(x.target_address, x.scratch_register, ra as u8, pc, buf)
+}
+
+fn encode_branch_absolute(
+    target_address: usize,
+    scratch: AllRegisters,
+    link: u8,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !scratch.is_gpr() {
+        return Err(JitError::InvalidRegister(scratch));
+    }
+
+    let register_number = scratch.register_number() as u8;
+    encode_mov_constant_to_reg(target_address, register_number, pc, buf)?;
+
+    let op = IType::new_jalr(link, register_number, 0)?;
+    buf.push(op.0.to_le() as i32);
+    *pc += 4;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::branch_absolute::{encode_call_absolute, encode_jump_absolute};
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // lui t1, 0x12345; addiw t1, t1, 0x678; jalr zero, 0(t1)
+    #[case(0x12345678, t1, false, "375334121b03836767000300")]
+    // lui t1, 0x12345; addiw t1, t1, 0x678; jalr ra, 0(t1)
+    #[case(0x12345678, t1, true, "375334121b038367e7000300")]
+    // lui t0, 0x400; addiw t0, t0, -119; slli t0, t0, 13; addi t0, t0, 837; slli t0, t0, 12; addi t0, t0, 1656; jalr zero, 0(t0)
+    #[case(
+        0x7FFF12345678,
+        t0,
+        false,
+        "b70240009b8292f89392d200938252349392c2009382826767800200"
+    )]
+    fn standard_cases(
+        #[case] target_address: usize,
+        #[case] scratch_register: AllRegisters,
+        #[case] is_call: bool,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+
+        if is_call {
+            let operation = CallAbs {
+                scratch_register,
+                target_address,
+            };
+            assert!(encode_call_absolute(&operation, &mut pc, &mut buf).is_ok());
+        } else {
+            let operation = JumpAbs {
+                scratch_register,
+                target_address,
+            };
+            assert!(encode_jump_absolute(&operation, &mut pc, &mut buf).is_ok());
+        }
+
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[test]
+    fn error_on_float_scratch() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = JumpAbs {
+            scratch_register: ft0,
+            target_address: 0x1000,
+        };
+
+        let result = encode_jump_absolute(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::InvalidRegister(_), pc, buf);
+    }
+}
@@ -0,0 +1,96 @@
+extern crate alloc;
+
+use super::load_pc_relative_value::load_pc_rel_value;
+use crate::{
+    all_registers::AllRegisters::{self, ra, zero},
+    instructions::i_type::IType,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError,
+    operation_aliases::{CallIpRel, JumpIpRel},
+};
+
+/// Encoded as AUIPC + LD + JALR RA
+pub fn encode_call_ip_relative(
+    x: &CallIpRel<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    load_pc_rel_value(x.scratch, pc, buf, x.target_address)?;
+
+    let op = IType::new_jalr(ra as u8, x.scratch.register_number() as u8, 0)?;
+    buf.push(op.0.to_le() as i32);
+    *pc += 4;
+    Ok(())
+}
+
+/// Encoded as AUIPC + LD + JALR ZERO
+pub fn encode_jump_ip_relative(
+    x: &JumpIpRel<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    load_pc_rel_value(x.scratch, pc, buf, x.target_address)?;
+
+    let op = IType::new_jalr(zero as u8, x.scratch.register_number() as u8, 0)?;
+    buf.push(op.0.to_le() as i32);
+    *pc += 4;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters::*;
+    use crate::jit_instructions::branch_ip_relative::encode_call_ip_relative;
+    use crate::jit_instructions::branch_ip_relative::encode_jump_ip_relative;
+    use crate::test_helpers::assert_encode_with_initial_pc;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 8, "170300000333830067000300")] // next instruction
+    #[case(4, 0, "170300000333c3ff67000300")] // last instruction
+    fn can_encode_jump_ip_relative(
+        #[case] initial_pc: usize,
+        #[case] target_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        assert!(encode_jump_ip_relative(
+            &JumpIpRel {
+                scratch: t1,
+                target_address
+            },
+            &mut pc,
+            &mut buf
+        )
+        .is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    #[rstest]
+    #[case(0, 8, "1703000003338300e7000300")] // next instruction
+    #[case(4, 0, "170300000333c3ffe7000300")] // last instruction
+    fn can_encode_call_ip_relative(
+        #[case] initial_pc: usize,
+        #[case] target_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        assert!(encode_call_ip_relative(
+            &CallIpRel {
+                scratch: t1,
+                target_address
+            },
+            &mut pc,
+            &mut buf
+        )
+        .is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    // Note: Remaining cases covered by load_pc_relative_value tests.
+}
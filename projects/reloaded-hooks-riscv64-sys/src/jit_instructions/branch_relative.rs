@@ -0,0 +1,149 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters::{self, ra, zero},
+    instructions::{
+        errors::exceeds_maximum_range,
+        i_type::IType,
+        j_type::JType,
+        u_type::{split_pc_relative_offset, UType},
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    call_relative_operation::CallRelativeOperation, compiler::JitError,
+    jump_relative_operation::JumpRelativeOperation,
+};
+
+/// Encoded as JAL RA, or AUIPC RA + JALR RA if further than +-1MiB.
+pub fn encode_call_relative(
+    x: &CallRelativeOperation,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    // The return address register is overwritten by the call anyway, so we can use it for AUIPC.
+    encode_branch_relative(
+        x.target_address,
+        ra as u8,
+        ra as u8,
+        "[Call Relative]",
+        pc,
+        buf,
+    )
+}
+
+/// Encoded as JAL ZERO, or AUIPC + JALR ZERO if further than +-1MiB.
+pub fn encode_jump_relative(
+    x: &JumpRelativeOperation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !x.scratch_register.is_gpr() {
+        return Err(JitError::InvalidRegister(x.scratch_register));
+    }
+
+    encode_branch_relative(
+        x.target_address,
+        zero as u8,
+        x.scratch_register.register_number() as u8,
+        "[Jump Relative]",
+        pc,
+        buf,
+    )
+}
+
+fn encode_branch_relative(
+    target_address: usize,
+    link: u8,
+    scratch: u8,
+    name: &str,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let offset = (target_address as isize).wrapping_sub(*pc as isize);
+
+    if (-0x100000..=0xFFFFE).contains(&offset) {
+        let jal = JType::assemble_jal(link, offset as i32)?;
+        buf.push(jal.0.to_le() as i32);
+        *pc += 4;
+        return Ok(());
+    }
+
+    let (hi, lo) = match split_pc_relative_offset(offset) {
+        Some(parts) => parts,
+        None => return Err(exceeds_maximum_range(name, "-+2GiB", offset)),
+    };
+
+    let auipc = UType::new_auipc(scratch, hi);
+    let jalr = IType::new_jalr(link, scratch, lo)?;
+    buf.push(auipc.0.to_le() as i32);
+    buf.push(jalr.0.to_le() as i32);
+    *pc += 8;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::branch_relative::{encode_call_relative, encode_jump_relative};
+    use crate::test_helpers::assert_encode_with_initial_pc;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 0x1000, "ef100000")] // jal ra, 0x1000
+    #[case(0x1000, 0, "eff00f80")] // jal ra, -0x1000
+    #[case(0, 0xFFFFE, "eff0ff7f")] // jal ra, 0xFFFFE
+    #[case(0, 0x12345678, "97503412e7808067")] // auipc ra, 0x12345; jalr ra, 0x678(ra)
+    #[case(0x80000000, 0x1000, "97100080e7800000")] // auipc ra, 0x80001; jalr ra, 0(ra)
+    fn call_relative(#[case] pc: usize, #[case] target: usize, #[case] expected_hex: &str) {
+        let mut new_pc = pc;
+        let mut buf = Vec::new();
+        let operation = CallRel::new(target);
+
+        assert!(encode_call_relative(&operation, &mut new_pc, &mut buf).is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, pc, new_pc);
+    }
+
+    #[rstest]
+    #[case(0, 0x1000, "6f100000")] // j 0x1000
+    #[case(0x1000, 0, "6ff00f80")] // j -0x1000
+    #[case(0, 0x12345678, "1753341267008367")] // auipc t1, 0x12345; jalr zero, 0x678(t1)
+    #[case(0, 0x7FFFF7FE, "17f3ff7f6700e37f")] // auipc t1, 0x7ffff; jalr zero, 0x7fe(t1)
+    fn jump_relative(#[case] pc: usize, #[case] target: usize, #[case] expected_hex: &str) {
+        let mut new_pc = pc;
+        let mut buf = Vec::new();
+        let operation = JumpRel::new(target);
+
+        assert!(encode_jump_relative(&operation, &mut new_pc, &mut buf).is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, pc, new_pc);
+    }
+
+    #[rstest]
+    #[case(0x7FFFF800)]
+    #[case(0x100000000)]
+    fn out_of_range(#[case] target: usize) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let result = encode_call_relative(&CallRel::new(target), &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+
+        let result = encode_jump_relative(&JumpRel::new(target), &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+
+    #[test]
+    fn jump_with_float_scratch() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = JumpRel {
+            target_address: 0x1000,
+            scratch_register: ft0,
+        };
+
+        let result = encode_jump_relative(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::InvalidRegister(_), pc, buf);
+    }
+}
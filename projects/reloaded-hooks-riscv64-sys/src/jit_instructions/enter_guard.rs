@@ -0,0 +1,149 @@
+extern crate alloc;
+
+use super::{branch_absolute::encode_jump_absolute, branch_relative::encode_jump_relative};
+use crate::{
+    all_registers::AllRegisters::{self, tp, zero},
+    instructions::{
+        b_type::{BType, CONDITION_EQ},
+        errors::exceeds_maximum_range,
+        i_type::{IType, WIDTH_BYTE_UNSIGNED},
+        s_type::{SType, WIDTH_BYTE},
+    },
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError,
+    operation_aliases::{EnterGuard, JumpAbs, JumpRel},
+};
+
+/// Encoded as LBU + BEQ over a branch to the bypass address + ADDI + SB.
+/// The flag is accessed relative to the thread pointer (TP), so only 1 scratch register is used;
+/// for the flag.
+pub fn encode_enter_guard(
+    x: &EnterGuard<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !(-2048..=2047).contains(&x.tls_offset) {
+        return Err(exceeds_maximum_range(
+            "[Enter Guard]",
+            "-2048..2047",
+            x.tls_offset,
+        ));
+    }
+
+    let flag = match x.scratch.borrow().iter().find(|reg| reg.is_gpr()) {
+        Some(flag) => *flag,
+        None => return Err(JitError::NoScratchRegister("for EnterGuard.".to_string())),
+    };
+
+    let flag_num = flag.register_number() as u8;
+    let offset = x.tls_offset as i32;
+    let load = IType::new_load(WIDTH_BYTE_UNSIGNED, flag_num, tp as u8, offset)?;
+
+    // Branch to bypass, placed after the BEQ. The flag register is free to use at that point.
+    let mut bypass_pc = *pc + 8;
+    let mut bypass = Vec::<i32>::new();
+    let jump = JumpRel {
+        target_address: x.bypass_address,
+        scratch_register: flag,
+    };
+
+    if encode_jump_relative(&jump, &mut bypass_pc, &mut bypass).is_err() {
+        bypass_pc = *pc + 8;
+        bypass.clear();
+        encode_jump_absolute(
+            &JumpAbs {
+                scratch_register: flag,
+                target_address: x.bypass_address,
+            },
+            &mut bypass_pc,
+            &mut bypass,
+        )?;
+    }
+
+    let skip_bypass = BType::assemble(
+        CONDITION_EQ,
+        flag_num,
+        zero as u8,
+        4 + (bypass.len() * 4) as i32,
+    )?;
+    let set = IType::new_addi(flag_num, zero as u8, 1)?;
+    let store = SType::new_store(WIDTH_BYTE, flag_num, tp as u8, offset)?;
+
+    buf.push(load.0.to_le() as i32);
+    buf.push(skip_bypass.0.to_le() as i32);
+    buf.extend_from_slice(&bypass);
+    buf.push(set.0.to_le() as i32);
+    buf.push(store.0.to_le() as i32);
+    *pc = bypass_pc + 8;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::enter_guard::encode_enter_guard;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // lbu t0, 16(tp); beq t0, zero, 8; jal zero, 0x1000; addi t0, zero, 1; sb t0, 16(tp)
+    #[case(16, 0x1008, vec![t0], "83420201638402006f1000009302100023085200")]
+    // lbu t0, 16(tp); beq t0, zero, 32; <li t0, 0x7FFF12345678>; jalr zero, 0(t0); addi t0, zero, 1; sb t0, 16(tp)
+    #[case(
+        16,
+        0x7FFF12345678,
+        vec![ft0, t0],
+        "8342020163800202b70240009b8292f89392d200938252349392c20093828267678002009302100023085200"
+    )]
+    fn standard_cases(
+        #[case] tls_offset: isize,
+        #[case] bypass_address: usize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = EnterGuard::new(tls_offset, bypass_address, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_enter_guard(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(16, vec![])]
+    #[case(16, vec![ft0])]
+    fn error_on_insufficient_scratch(
+        #[case] tls_offset: isize,
+        #[case] scratch: Vec<AllRegisters>,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = EnterGuard::new(tls_offset, 0x1000, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_enter_guard(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+
+    #[rstest]
+    #[case(-2049)]
+    #[case(2048)]
+    fn error_on_out_of_range_offset(#[case] tls_offset: isize) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let scratch = vec![t0];
+        let operation = EnterGuard::new(tls_offset, 0x1000, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_enter_guard(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
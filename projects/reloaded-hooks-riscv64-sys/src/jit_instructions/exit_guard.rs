@@ -0,0 +1,76 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters::{self, tp, zero},
+    instructions::{
+        errors::exceeds_maximum_range,
+        s_type::{SType, WIDTH_BYTE},
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::ExitGuard};
+
+/// Encoded as SB of the zero register, relative to the thread pointer (TP).
+/// Does not use any scratch registers.
+pub fn encode_exit_guard(
+    x: &ExitGuard<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !(-2048..=2047).contains(&x.tls_offset) {
+        return Err(exceeds_maximum_range(
+            "[Exit Guard]",
+            "-2048..2047",
+            x.tls_offset,
+        ));
+    }
+
+    let clear = SType::new_store(WIDTH_BYTE, zero as u8, tp as u8, x.tls_offset as i32)?;
+    buf.push(clear.0.to_le() as i32);
+    *pc += 4;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::exit_guard::encode_exit_guard;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(16, vec![t0], "23080200")] // sb zero, 16(tp)
+    #[case(-2048, vec![], "23000280")] // sb zero, -2048(tp)
+    fn standard_cases(
+        #[case] tls_offset: isize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = ExitGuard::new(tls_offset, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_exit_guard(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(2048)]
+    #[case(-2049)]
+    fn error_on_out_of_range_offset(#[case] tls_offset: isize) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = ExitGuard::new(tls_offset, Rc::new(RefCell::new(vec![t0])));
+
+        let result = encode_exit_guard(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
@@ -0,0 +1,151 @@
+extern crate alloc;
+
+use super::{
+    branch_absolute::encode_jump_absolute, branch_relative::encode_jump_relative,
+    push_constant::encode_mov_constant_to_reg,
+};
+use crate::{
+    all_registers::AllRegisters::{self, ra, zero},
+    instructions::{
+        b_type::{BType, CONDITION_EQ, CONDITION_GEU, CONDITION_LTU},
+        i_type::{IType, WIDTH_DOUBLE},
+        j_type::JType,
+    },
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError,
+    operation_aliases::{FilterCaller, JumpAbs, JumpRel},
+};
+
+/// Encoded as LI + LD + JAL over a branch to the bypass address, followed by a
+/// LD + LD + ADDI + BEQ + BLTU + BGEU loop over the table of ranges.
+/// Uses 3 scratch registers; for the table entry, and the start and end of each range.
+pub fn encode_filter_caller(
+    x: &FilterCaller<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let scratch = x.scratch.borrow();
+    let mut regs = scratch.iter().filter(|reg| reg.is_gpr());
+    let (entry, start, end) = match (regs.next(), regs.next(), regs.next()) {
+        (Some(entry), Some(start), Some(end)) => (*entry, *start, *end),
+        _ => {
+            return Err(JitError::NoScratchRegister(
+                "for FilterCaller. 3 registers are required.".to_string(),
+            ))
+        }
+    };
+
+    let entry_num = entry.register_number() as u8;
+    let start_num = start.register_number() as u8;
+    let end_num = end.register_number() as u8;
+
+    encode_mov_constant_to_reg(x.table_pointer, entry_num, pc, buf)?;
+    let load_table = IType::new_load(WIDTH_DOUBLE, entry_num, entry_num, 0)?;
+
+    // Branch to bypass, placed before the loop. The start register is free to use at that point.
+    let mut bypass_pc = *pc + 8;
+    let mut bypass = Vec::<i32>::new();
+    let jump = JumpRel {
+        target_address: x.bypass_address,
+        scratch_register: start,
+    };
+
+    if encode_jump_relative(&jump, &mut bypass_pc, &mut bypass).is_err() {
+        bypass_pc = *pc + 8;
+        bypass.clear();
+        encode_jump_absolute(
+            &JumpAbs {
+                scratch_register: start,
+                target_address: x.bypass_address,
+            },
+            &mut bypass_pc,
+            &mut bypass,
+        )?;
+    }
+
+    let bypass_len = (bypass.len() * 4) as i32;
+    let skip_bypass = JType::assemble_jal(zero as u8, 4 + bypass_len)?;
+    let load_start = IType::new_load(WIDTH_DOUBLE, start_num, entry_num, 0)?;
+    let load_end = IType::new_load(WIDTH_DOUBLE, end_num, entry_num, 8)?;
+    let next_entry = IType::new_addi(entry_num, entry_num, 16)?;
+    let at_end = BType::assemble(CONDITION_EQ, end_num, zero as u8, -(bypass_len + 12))?;
+    let below_start = BType::assemble(CONDITION_LTU, ra as u8, start_num, -16)?;
+    let above_end = BType::assemble(CONDITION_GEU, ra as u8, end_num, -20)?;
+
+    buf.push(load_table.0.to_le() as i32);
+    buf.push(skip_bypass.0.to_le() as i32);
+    buf.extend_from_slice(&bypass);
+    buf.push(load_start.0.to_le() as i32);
+    buf.push(load_end.0.to_le() as i32);
+    buf.push(next_entry.0.to_le() as i32);
+    buf.push(at_end.0.to_le() as i32);
+    buf.push(below_start.0.to_le() as i32);
+    buf.push(above_end.0.to_le() as i32);
+    *pc = bypass_pc + 24;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::filter_caller::encode_filter_caller;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // lui t0, 2; ld t0, 0(t0); jal zero, 8; jal zero, 0x1000; ld t1, 0(t0); ld t2, 8(t0); addi t0, t0, 16;
+    // beq t2, zero, -16; bltu ra, t1, -16; bgeu ra, t2, -20
+    #[case(
+        0x2000,
+        0x100C,
+        vec![t0, t1, t2],
+        "b722000083b202006f0080006f10000003b3020083b3820093820201e38803fee3e860fee3f670fe"
+    )]
+    // lui t0, 2; ld t0, 0(t0); jal zero, 32; <li t1, 0x7FFF12345678>; jalr zero, 0(t1); ld t1, 0(t0); ld t2, 8(t0);
+    // addi t0, t0, 16; beq t2, zero, -40; bltu ra, t1, -16; bgeu ra, t2, -20
+    #[case(
+        0x2000,
+        0x7FFF12345678,
+        vec![ft0, t0, t1, t2],
+        "b722000083b202006f000002370340001b0393f81313d300130353341313c300130383676700030003b3020083b3820093820201e38c03fce3e860fee3f670fe"
+    )]
+    fn standard_cases(
+        #[case] table_pointer: usize,
+        #[case] bypass_address: usize,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = FilterCaller::new(
+            table_pointer,
+            bypass_address,
+            Rc::new(RefCell::new(scratch)),
+        );
+
+        assert!(encode_filter_caller(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(vec![t0, t1])]
+    #[case(vec![t0, t1, ft0])]
+    fn error_on_insufficient_scratch(#[case] scratch: Vec<AllRegisters>) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = FilterCaller::new(0x2000, 0x1000, Rc::new(RefCell::new(scratch)));
+
+        let result = encode_filter_caller(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
@@ -0,0 +1,83 @@
+extern crate alloc;
+
+use super::load_pc_relative_value::load_pc_rel_value;
+use crate::{
+    all_registers::AllRegisters::{self, zero},
+    instructions::i_type::IType,
+};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::JumpAbsInd};
+
+/// AUIPC + LD + JALR ZERO, or LI + LD + JALR ZERO if the pointer is further than +-2GiB.
+pub fn encode_jump_absolute_indirect(
+    x: &JumpAbsInd<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let scratch = match x.scratch_register {
+        Some(scratch) => scratch,
+        None => {
+            return Err(JitError::NoScratchRegister(
+                "Needed for JumpAbsInd".to_string(),
+            ))
+        }
+    };
+
+    load_pc_rel_value(scratch, pc, buf, x.pointer_address)?;
+
+    let op = IType::new_jalr(zero as u8, scratch.register_number() as u8, 0)?;
+    buf.push(op.0.to_le() as i32);
+    *pc += 4;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::jump_absolute_indirect::encode_jump_absolute_indirect;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // auipc t1, 0x12345; ld t1, 0x678(t1); jalr zero, 0(t1)
+    #[case(0x12345678, t1, "175334120333836767000300")]
+    // lui t0, 0x400; addiw t0, t0, -119; slli t0, t0, 13; addi t0, t0, 837; slli t0, t0, 12; addi t0, t0, 1656; ld t0, 0(t0); jalr zero, 0(t0)
+    #[case(
+        0x7FFF12345678,
+        t0,
+        "b70240009b8292f89392d200938252349392c2009382826783b2020067800200"
+    )]
+    fn standard_cases(
+        #[case] pointer_address: usize,
+        #[case] scratch: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = JumpAbsInd {
+            scratch_register: Some(scratch),
+            pointer_address,
+        };
+
+        assert!(encode_jump_absolute_indirect(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[test]
+    fn error_on_missing_scratch() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = JumpAbsInd {
+            scratch_register: None,
+            pointer_address: 0x1000,
+        };
+
+        let result = encode_jump_absolute_indirect(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
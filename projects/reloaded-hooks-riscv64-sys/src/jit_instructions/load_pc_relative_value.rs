@@ -0,0 +1,99 @@
+extern crate alloc;
+
+use super::push_constant::encode_mov_constant_to_reg;
+use crate::{
+    all_registers::AllRegisters,
+    instructions::{
+        i_type::{IType, WIDTH_DOUBLE},
+        u_type::{split_pc_relative_offset, UType},
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+
+/// Loads a 64-bit value at a given address into a register.
+///
+/// # Arguments
+///
+/// * `x` - The register to load the value into. Also used to hold the address.
+/// * `pc` - A mutable reference to the program counter.
+/// * `buf` - A mutable reference to a vector of 32-bit integers that will hold the assembled instructions.
+/// * `target_address` - The address of the value that we want to load into the register.
+///
+/// # Returns
+///
+/// Returns a `Result` with an empty `Ok` value if the assembly is successful, or a `JitError` if
+/// there is an error assembling the instructions.
+///
+/// # Remarks
+///
+/// Assembled as AUIPC + LD if the address is within +-2GiB of the PC, else LI + LD.
+pub fn load_pc_rel_value(
+    x: AllRegisters,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+    target_address: usize,
+) -> Result<(), JitError<AllRegisters>> {
+    if !x.is_gpr() {
+        return Err(JitError::InvalidRegister(x));
+    }
+
+    let reg_num = x.register_number() as u8;
+    let offset = (target_address as isize).wrapping_sub(*pc as isize);
+
+    if let Some((hi, lo)) = split_pc_relative_offset(offset) {
+        let auipc = UType::new_auipc(reg_num, hi);
+        let ld = IType::new_load(WIDTH_DOUBLE, reg_num, reg_num, lo)?;
+        buf.push(auipc.0.to_le() as i32);
+        buf.push(ld.0.to_le() as i32);
+        *pc += 8;
+        return Ok(());
+    }
+
+    // Out of range, materialize the full address instead.
+    encode_mov_constant_to_reg(target_address, reg_num, pc, buf)?;
+    let ld = IType::new_load(WIDTH_DOUBLE, reg_num, reg_num, 0)?;
+    buf.push(ld.0.to_le() as i32);
+    *pc += 4;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::load_pc_relative_value::load_pc_rel_value;
+    use crate::test_helpers::assert_encode_with_initial_pc;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 8, "1703000003338300")] // auipc t1, 0; ld t1, 8(t1)
+    #[case(0x1000, 0, "17f3ffff03330300")] // auipc t1, 0xfffff; ld t1, 0(t1)
+    #[case(0, 0x12345FFF, "176334120333f3ff")]
+    // auipc t1, 0x12346; ld t1, -1(t1)
+    // lui t1, 0x400; addiw t1, t1, -119; slli t1, t1, 13; addi t1, t1, 837; slli t1, t1, 12; addi t1, t1, 1656; ld t1, 0(t1)
+    #[case(
+        0,
+        0x7FFF12345678,
+        "370340001b0393f81313d300130353341313c3001303836703330300"
+    )]
+    fn standard_cases(
+        #[case] initial_pc: usize,
+        #[case] target_address: usize,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = initial_pc;
+        let mut buf = Vec::new();
+        assert!(load_pc_rel_value(t1, &mut pc, &mut buf, target_address).is_ok());
+        assert_encode_with_initial_pc(expected_hex, &buf, initial_pc, pc);
+    }
+
+    #[test]
+    fn error_on_float_register() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let result = load_pc_rel_value(ft0, &mut pc, &mut buf, 0);
+        assert_error!(result, JitError::InvalidRegister(_), pc, buf);
+    }
+}
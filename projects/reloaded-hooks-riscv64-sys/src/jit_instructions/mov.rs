@@ -0,0 +1,58 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters,
+    instructions::{i_type::IType, r_type::RType},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, mov_operation::MovOperation};
+
+/// Encoded as MV (ADDI), FMV.D (FSGNJ.D), FMV.D.X or FMV.X.D depending on the register types.
+pub fn encode_mov(
+    x: &MovOperation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let rs = x.source.register_number() as u8;
+    let rd = x.target.register_number() as u8;
+
+    let ins = match (x.source.is_gpr(), x.target.is_gpr()) {
+        (true, true) => IType::new_mov(rd, rs).0,
+        (false, false) => RType::new_fmv_d(rd, rs).0,
+        (true, false) => RType::new_fmv_d_x(rd, rs).0,
+        (false, true) => RType::new_fmv_x_d(rd, rs).0,
+    };
+
+    *pc += 4;
+    buf.push(ins.to_le() as i32);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::jit_instructions::mov::encode_mov;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(a1, a0, "13850500")] // mv a0, a1
+    #[case(t6, s2, "13890f00")] // mv s2, t6
+    #[case(fa1, fa0, "5385b522")] // fmv.d fa0, fa1
+    #[case(a0, fa0, "530505f2")] // fmv.d.x fa0, a0
+    #[case(fa0, a0, "530505e2")] // fmv.x.d a0, fa0
+    fn standard_cases(
+        #[case] source: AllRegisters,
+        #[case] target: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Mov { source, target };
+
+        assert!(encode_mov(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
@@ -0,0 +1,58 @@
+extern crate alloc;
+
+use super::pop::encode_load_from_stack;
+use crate::all_registers::AllRegisters;
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::MovFromStack};
+
+/// Encoded as LD (or FLD) with SP as base
+pub fn encode_mov_from_stack(
+    x: &MovFromStack<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let load = encode_load_from_stack(x.target, x.stack_offset)?;
+
+    *pc += 4;
+    buf.push(load.0.to_le() as i32);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::mov_from_stack::encode_mov_from_stack;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(a0, 8, "03358100")] // ld a0, 8(sp)
+    #[case(t0, 2040, "8332817f")] // ld t0, 2040(sp)
+    #[case(fa0, 16, "07350101")] // fld fa0, 16(sp)
+    fn standard_cases(
+        #[case] target: AllRegisters,
+        #[case] stack_offset: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = MovFromStack::new(stack_offset, target);
+
+        assert!(encode_mov_from_stack(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[test]
+    fn error_on_out_of_range() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = MovFromStack::new(2048, a0);
+
+        let result = encode_mov_from_stack(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
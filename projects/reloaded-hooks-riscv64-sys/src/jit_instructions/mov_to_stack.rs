@@ -0,0 +1,57 @@
+extern crate alloc;
+
+use super::push::encode_store_to_stack;
+use crate::all_registers::AllRegisters;
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::MovToStack};
+
+/// Encoded as SD (or FSD) with SP as base
+pub fn encode_mov_to_stack(
+    x: &MovToStack<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let store = encode_store_to_stack(x.register, x.stack_offset)?;
+
+    *pc += 4;
+    buf.push(store.0.to_le() as i32);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::mov_to_stack::encode_mov_to_stack;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(a0, 8, "2334a100")] // sd a0, 8(sp)
+    #[case(fa0, 16, "2738a100")] // fsd fa0, 16(sp)
+    fn standard_cases(
+        #[case] register: AllRegisters,
+        #[case] stack_offset: i32,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = MovToStack::new(stack_offset, register);
+
+        assert!(encode_mov_to_stack(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[test]
+    fn error_on_out_of_range() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = MovToStack::new(4096, a0);
+
+        let result = encode_mov_to_stack(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
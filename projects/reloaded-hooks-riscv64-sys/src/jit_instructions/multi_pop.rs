@@ -0,0 +1,58 @@
+extern crate alloc;
+
+use super::pop::encode_load_from_stack;
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instructions::i_type::IType,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation::MultiPopVec};
+use smallvec::SmallVec;
+
+/// Encoded as one LD (or FLD) per register followed by a single ADDI.
+///
+/// The stack layout consumed is identical to popping each register one by one, i.e. the
+/// first register in the list is loaded from the top of the stack.
+pub fn encode_multi_pop(
+    x: &SmallVec<MultiPopVec<AllRegisters>>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    for (index, item) in x.iter().enumerate() {
+        let load = encode_load_from_stack(item.register, index as i32 * 8)?;
+        buf.push(load.0.to_le() as i32);
+    }
+
+    let dealloc = IType::new_addi(sp as u8, sp as u8, (x.len() * 8) as i32)?;
+    buf.push(dealloc.0.to_le() as i32);
+
+    *pc += (x.len() + 1) * 4;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::jit_instructions::multi_pop::encode_multi_pop;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+    use smallvec::SmallVec;
+
+    #[rstest]
+    // ld a0, 0(sp); addi sp, sp, 8
+    #[case(&[a0], "0335010013018100")]
+    // ld a0, 0(sp); ld a1, 8(sp); addi sp, sp, 16
+    #[case(&[a0, a1], "033501008335810013010101")]
+    // ld a0, 0(sp); fld fa0, 8(sp); ld s11, 16(sp); addi sp, sp, 24
+    #[case(&[a0, fa0, s11], "0335010007358100833d010113018101")]
+    fn standard_cases(#[case] registers: &[AllRegisters], #[case] expected_hex: &str) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operations: SmallVec<_> = registers.iter().map(|&register| Pop { register }).collect();
+
+        assert!(encode_multi_pop(&operations, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
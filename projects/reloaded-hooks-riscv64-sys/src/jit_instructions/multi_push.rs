@@ -0,0 +1,63 @@
+extern crate alloc;
+
+use super::push::encode_store_to_stack;
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instructions::i_type::IType,
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation::MultiPushVec};
+use smallvec::SmallVec;
+
+/// Encoded as a single ADDI followed by one SD (or FSD) per register.
+///
+/// The resulting stack layout is identical to pushing each register one by one, i.e. the
+/// last register in the list ends up at the top of the stack.
+pub fn encode_multi_push(
+    x: &SmallVec<MultiPushVec<AllRegisters>>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let total_size = (x.len() * 8) as i32;
+    let alloc = IType::new_addi(sp as u8, sp as u8, -total_size)?;
+    buf.push(alloc.0.to_le() as i32);
+
+    for (index, item) in x.iter().enumerate() {
+        let offset = total_size - ((index as i32 + 1) * 8);
+        let store = encode_store_to_stack(item.register, offset)?;
+        buf.push(store.0.to_le() as i32);
+    }
+
+    *pc += (x.len() + 1) * 4;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::jit_instructions::multi_push::encode_multi_push;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+    use smallvec::SmallVec;
+
+    #[rstest]
+    // addi sp, sp, -8; sd a0, 0(sp)
+    #[case(&[a0], "130181ff2330a100")]
+    // addi sp, sp, -16; sd a0, 8(sp); sd a1, 0(sp)
+    #[case(&[a0, a1], "130101ff2334a1002330b100")]
+    // addi sp, sp, -24; sd a0, 16(sp); fsd fa0, 8(sp); sd s11, 0(sp)
+    #[case(&[a0, fa0, s11], "130181fe2338a1002734a1002330b101")]
+    fn standard_cases(#[case] registers: &[AllRegisters], #[case] expected_hex: &str) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operations: SmallVec<_> = registers
+            .iter()
+            .map(|&register| Push { register })
+            .collect();
+
+        assert!(encode_multi_push(&operations, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
@@ -0,0 +1,59 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instructions::i_type::{IType, WIDTH_DOUBLE},
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, pop_operation::PopOperation};
+
+/// Encoded as LD (or FLD) + ADDI
+pub fn encode_pop(
+    x: &PopOperation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let load = encode_load_from_stack(x.register, 0)?;
+    let free = IType::new_addi(sp as u8, sp as u8, 8)?;
+
+    buf.push(load.0.to_le() as i32);
+    buf.push(free.0.to_le() as i32);
+    *pc += 8;
+    Ok(())
+}
+
+/// Creates a load of `offset(sp)` into `register`, picking LD or FLD based on the register type.
+pub(crate) fn encode_load_from_stack(
+    register: AllRegisters,
+    offset: i32,
+) -> Result<IType, JitError<AllRegisters>> {
+    let number = register.register_number() as u8;
+    if register.is_gpr() {
+        IType::new_load(WIDTH_DOUBLE, number, sp as u8, offset)
+    } else {
+        IType::new_fld(number, sp as u8, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::jit_instructions::pop::encode_pop;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(a0, "0335010013018100")] // ld a0, 0(sp); addi sp, sp, 8
+    #[case(s11, "833d010013018100")] // ld s11, 0(sp); addi sp, sp, 8
+    #[case(fa0, "0735010013018100")] // fld fa0, 0(sp); addi sp, sp, 8
+    fn test_encode_pop(#[case] register: AllRegisters, #[case] expected_hex: &str) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Pop { register };
+
+        assert!(encode_pop(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
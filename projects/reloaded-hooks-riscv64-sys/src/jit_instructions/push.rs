@@ -0,0 +1,62 @@
+extern crate alloc;
+
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instructions::{
+        i_type::IType,
+        s_type::{SType, WIDTH_DOUBLE},
+    },
+};
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{compiler::JitError, push_operation::PushOperation};
+
+/// Encoded as ADDI + SD (or FSD)
+pub fn encode_push(
+    x: &PushOperation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let alloc = IType::new_addi(sp as u8, sp as u8, -8)?;
+    let store = encode_store_to_stack(x.register, 0)?;
+
+    buf.push(alloc.0.to_le() as i32);
+    buf.push(store.0.to_le() as i32);
+    *pc += 8;
+    Ok(())
+}
+
+/// Creates a store of `register` to `offset(sp)`, picking SD or FSD based on the register type.
+pub(crate) fn encode_store_to_stack(
+    register: AllRegisters,
+    offset: i32,
+) -> Result<SType, JitError<AllRegisters>> {
+    let number = register.register_number() as u8;
+    if register.is_gpr() {
+        SType::new_store(WIDTH_DOUBLE, number, sp as u8, offset)
+    } else {
+        SType::new_fsd(number, sp as u8, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::jit_instructions::push::encode_push;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(a0, "130181ff2330a100")] // addi sp, sp, -8; sd a0, 0(sp)
+    #[case(s11, "130181ff2330b101")] // addi sp, sp, -8; sd s11, 0(sp)
+    #[case(fa0, "130181ff2730a100")] // addi sp, sp, -8; fsd fa0, 0(sp)
+    fn test_encode_push(#[case] register: AllRegisters, #[case] expected_hex: &str) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = Push { register };
+
+        assert!(encode_push(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+}
@@ -0,0 +1,80 @@
+extern crate alloc;
+
+use super::push::encode_push;
+use crate::{all_registers::AllRegisters, instructions::load_immediate::load_immediate};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, operation_aliases::PushConst, push_operation::PushOperation,
+};
+
+/// Encoded as LI + ADDI + SD
+pub fn encode_push_constant(
+    x: &PushConst<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    let scratch = match x.scratch {
+        Some(scratch) if scratch.is_gpr() => scratch,
+        _ => return Err(JitError::NoScratchRegister("for PushConstant.".to_string())),
+    };
+
+    encode_mov_constant_to_reg(x.value, scratch.register_number() as u8, pc, buf)?;
+    encode_push(&PushOperation::new(scratch), pc, buf)
+}
+
+/// Encoded as LI (LUI + ADDIW + SLLI + ADDI, 1-8 instructions)
+pub fn encode_mov_constant_to_reg(
+    value: usize,
+    destination: u8,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    for ins in load_immediate(destination, value) {
+        buf.push(ins as i32);
+        *pc += 4;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::push_constant::encode_push_constant;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    // addi t1, zero, 1; addi sp, sp, -8; sd t1, 0(sp)
+    #[case(1, t1, "13031000130181ff23306100")]
+    // lui t1, 0x12345; addiw t1, t1, 0x678; addi sp, sp, -8; sd t1, 0(sp)
+    #[case(0x12345678, t1, "375334121b038367130181ff23306100")]
+    fn standard_cases(
+        #[case] value: usize,
+        #[case] scratch: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushConst::new(value, Some(scratch));
+
+        assert!(encode_push_constant(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(ft0))]
+    fn error_on_missing_scratch(#[case] scratch: Option<AllRegisters>) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushConst::new(1, scratch);
+
+        let result = encode_push_constant(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
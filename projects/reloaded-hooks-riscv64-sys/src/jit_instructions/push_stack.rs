@@ -0,0 +1,118 @@
+extern crate alloc;
+
+use super::{mov_from_stack::encode_mov_from_stack, push::encode_push};
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instructions::{
+        i_type::{self, IType},
+        s_type::{self, SType},
+    },
+};
+use alloc::{string::ToString, vec::Vec};
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, operation_aliases::MovFromStack, push_operation::PushOperation,
+    push_stack_operation::PushStackOperation,
+};
+
+/// Encoded as a LD + ADDI + SD sequence for every 8 bytes, with LW + ADDI + SW for
+/// the remaining 4 bytes (if any).
+pub fn encode_push_stack(
+    x: &PushStackOperation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    // Validate remaining size is usable.
+    if !x.item_size.is_multiple_of(4) {
+        return Err(JitError::ThirdPartyAssemblerError(
+            "RISC-V PushStack Must use Multiple of 4 Sizes".to_string(),
+        ));
+    }
+
+    let scratch = match x.scratch.borrow().iter().find(|reg| reg.is_gpr()) {
+        Some(reg) => *reg,
+        None => {
+            return Err(JitError::NoScratchRegister(
+                "for PushStack. 1 general purpose register is required.".to_string(),
+            ))
+        }
+    };
+
+    let mut remaining_bytes = x.item_size;
+    while remaining_bytes > 0 {
+        if remaining_bytes >= 8 {
+            // Push Single Register
+            encode_mov_from_stack(&MovFromStack::new(x.offset, scratch), pc, buf)?;
+            encode_push(&PushOperation::new(scratch), pc, buf)?;
+            remaining_bytes -= 8;
+        } else {
+            // Push Remaining Multiple of 4
+            let number = scratch.register_number() as u8;
+            let load = IType::new_load(i_type::WIDTH_WORD, number, sp as u8, x.offset)?;
+            let alloc = IType::new_addi(sp as u8, sp as u8, -4)?;
+            let store = SType::new_store(s_type::WIDTH_WORD, number, sp as u8, 0)?;
+            buf.push(load.0.to_le() as i32);
+            buf.push(alloc.0.to_le() as i32);
+            buf.push(store.0.to_le() as i32);
+            *pc += 12;
+            remaining_bytes -= 4;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::push_stack::encode_push_stack;
+    use crate::test_helpers::assert_encode;
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+    extern crate alloc;
+
+    #[rstest]
+    // ld t0, 16(sp); addi sp, sp, -8; sd t0, 0(sp)
+    #[case(16, 8, vec![t0], "83320101130181ff23305100")]
+    // (ld t0, 16(sp); addi sp, sp, -8; sd t0, 0(sp)) x2
+    #[case(16, 16, vec![ft0, t0], "83320101130181ff2330510083320101130181ff23305100")]
+    // ld t0, 16(sp); addi sp, sp, -8; sd t0, 0(sp); lw t0, 16(sp); addi sp, sp, -4; sw t0, 0(sp)
+    #[case(16, 12, vec![t0], "83320101130181ff23305100832201011301c1ff23205100")]
+    fn standard_cases(
+        #[case] offset: i32,
+        #[case] item_size: u32,
+        #[case] scratch: Vec<AllRegisters>,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushStack::new(offset, item_size, Rc::new(RefCell::new(scratch)));
+
+        assert!(encode_push_stack(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[test]
+    fn error_on_invalid_size() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushStack::new(16, 6, Rc::new(RefCell::new(vec![t0])));
+
+        let result = encode_push_stack(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::ThirdPartyAssemblerError(_), pc, buf);
+    }
+
+    #[test]
+    fn error_on_missing_scratch() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = PushStack::new(16, 8, Rc::new(RefCell::new(vec![ft0])));
+
+        let result = encode_push_stack(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+}
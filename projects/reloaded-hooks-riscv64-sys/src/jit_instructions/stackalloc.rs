@@ -0,0 +1,66 @@
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, stack_alloc_operation::StackAllocOperation,
+};
+extern crate alloc;
+use crate::{
+    all_registers::AllRegisters::{self, sp},
+    instructions::{errors::exceeds_maximum_range, i_type::IType},
+};
+use alloc::vec::Vec;
+
+/// Encoded as ADDI SP, SP, -operand
+pub fn encode_stackalloc(
+    x: &StackAllocOperation,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    if !(-2047..=2048).contains(&x.operand) {
+        return Err(exceeds_maximum_range(
+            "[StackAlloc]",
+            "-2047..2048",
+            x.operand as isize,
+        ));
+    }
+
+    let addi = IType::new_addi(sp as u8, sp as u8, -x.operand)?;
+
+    *pc += 4;
+    buf.push(addi.0.to_le() as i32);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_error;
+    use crate::jit_instructions::stackalloc::encode_stackalloc;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(16, "130101ff")] // addi sp, sp, -16
+    #[case(-16, "13010101")] // addi sp, sp, 16
+    #[case(2048, "13010180")] // addi sp, sp, -2048
+    #[case(-2047, "1301f17f")] // addi sp, sp, 2047
+    fn standard_cases(#[case] operand: i32, #[case] expected_hex: &str) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = StackAlloc::new(operand);
+
+        assert!(encode_stackalloc(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[rstest]
+    #[case(2049)]
+    #[case(-2048)]
+    fn error_on_out_of_range(#[case] operand: i32) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = StackAlloc::new(operand);
+
+        let result = encode_stackalloc(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::OperandOutOfRange(_), pc, buf);
+    }
+}
@@ -0,0 +1,99 @@
+extern crate alloc;
+
+use super::mov::encode_mov;
+use crate::all_registers::AllRegisters;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::jit::{
+    compiler::JitError, mov_operation::MovOperation, xchg_operation::XChgOperation,
+};
+
+/// Encoded as 3 moves through the scratch register.
+pub fn encode_xchg(
+    x: &XChgOperation<AllRegisters>,
+    pc: &mut usize,
+    buf: &mut Vec<i32>,
+) -> Result<(), JitError<AllRegisters>> {
+    // Try get scratch register.
+    let scratch = match x.scratch {
+        Some(s) => s,
+        None => {
+            return Err(JitError::NoScratchRegister("for XChg".to_string()));
+        }
+    };
+
+    // Check if any two registers are the same.
+    if x.register1 == x.register2 || x.register1 == scratch || x.register2 == scratch {
+        return Err(JitError::InvalidRegisterCombination3(
+            x.register1,
+            x.register2,
+            scratch,
+        ));
+    }
+
+    encode_mov(&MovOperation::new(x.register1, scratch), pc, buf)?;
+    encode_mov(&MovOperation::new(x.register2, x.register1), pc, buf)?;
+    encode_mov(&MovOperation::new(scratch, x.register2), pc, buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::all_registers::AllRegisters;
+    use crate::all_registers::AllRegisters::*;
+    use crate::assert_error;
+    use crate::jit_instructions::xchg::encode_xchg;
+    use crate::test_helpers::assert_encode;
+    use reloaded_hooks_portable::api::jit::compiler::JitError;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(a0, a1, t0, "930205001385050093850200")] // mv t0, a0; mv a0, a1; mv a1, t0
+    #[case(fa0, fa1, ft0, "5300a5225385b522d3050022")] // fmv.d ft0, fa0; fmv.d fa0, fa1; fmv.d fa1, ft0
+    #[case(a0, fa0, t0, "93020500530505e2538502f2")] // mv t0, a0; fmv.x.d a0, fa0; fmv.d.x fa0, t0
+    fn standard_cases(
+        #[case] register1: AllRegisters,
+        #[case] register2: AllRegisters,
+        #[case] scratch: AllRegisters,
+        #[case] expected_hex: &str,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = XChg::new(register1, register2, Some(scratch));
+
+        assert!(encode_xchg(&operation, &mut pc, &mut buf).is_ok());
+        assert_encode(expected_hex, &buf, pc);
+    }
+
+    #[test]
+    fn error_on_missing_scratch() {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = XChg::new(a0, a1, None);
+
+        let result = encode_xchg(&operation, &mut pc, &mut buf);
+        assert_error!(result, JitError::NoScratchRegister(_), pc, buf);
+    }
+
+    #[rstest]
+    #[case(a0, a0, t0)]
+    #[case(a0, a1, a0)]
+    #[case(a0, a1, a1)]
+    fn error_on_same_registers(
+        #[case] register1: AllRegisters,
+        #[case] register2: AllRegisters,
+        #[case] scratch: AllRegisters,
+    ) {
+        let mut pc = 0;
+        let mut buf = Vec::new();
+        let operation = XChg::new(register1, register2, Some(scratch));
+
+        let result = encode_xchg(&operation, &mut pc, &mut buf);
+        assert_error!(
+            result,
+            JitError::InvalidRegisterCombination3(_, _, _),
+            pc,
+            buf
+        );
+    }
+}
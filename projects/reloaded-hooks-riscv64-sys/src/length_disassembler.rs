@@ -0,0 +1,141 @@
+use crate::{
+    code_rewriter::riscv64_rewriter::{
+        instruction_length, is_branch, is_c_beqz_bnez, is_c_j, is_jal,
+    },
+    instructions::{
+        b_type::BType,
+        compressed::{CompressedBranch, CompressedJump},
+        j_type::JType,
+    },
+};
+use reloaded_hooks_portable::{
+    api::length_disassembler::LengthDisassembler, helpers::read_code::read_code_as,
+};
+
+pub struct LengthDisassemblerRiscV64;
+
+impl LengthDisassembler for LengthDisassemblerRiscV64 {
+    fn disassemble_length(code_address: usize, min_length: usize) -> (usize, usize) {
+        // With the 'C' extension, instructions are either 2 or 4 bytes long, so we walk them
+        // using the length encoded in the lowest bits of each instruction.
+        let mut length = 0;
+        let mut count = 0;
+
+        while length < min_length {
+            let low_half = unsafe { read_code_as::<u16>(code_address + length) }.to_le();
+            length += instruction_length(low_half).unwrap_or(2);
+            count += 1;
+        }
+
+        (length, count)
+    }
+
+    fn find_branch_into_stolen_region(
+        code_address: usize,
+        stolen_length: usize,
+        scan_length: usize,
+    ) -> Option<(usize, usize)> {
+        let stolen_end = code_address + stolen_length;
+        let scan_end = stolen_end + scan_length;
+        let mut address = code_address;
+
+        while address < scan_end {
+            let low_half = unsafe { read_code_as::<u16>(address) }.to_le();
+            let length = instruction_length(low_half).unwrap_or(2);
+
+            if let Some(offset) = branch_offset(address, length) {
+                let target = address.wrapping_add(offset as usize);
+                if target > code_address && target < stolen_end {
+                    return Some((address, target));
+                }
+            }
+
+            address += length;
+        }
+
+        None
+    }
+}
+
+/// Returns the offset of a direct branch (JAL, B<cond>, C.J, C.BEQZ/C.BNEZ), if the instruction is one.
+fn branch_offset(address: usize, length: usize) -> Option<isize> {
+    if length == 2 {
+        let instruction = unsafe { read_code_as::<u16>(address) }.to_le();
+        if is_c_j(instruction) {
+            return Some(CompressedJump(instruction).offset());
+        } else if is_c_beqz_bnez(instruction) {
+            return Some(CompressedBranch(instruction).offset());
+        }
+
+        return None;
+    }
+
+    if length != 4 {
+        return None;
+    }
+
+    let instruction = unsafe { read_code_as::<u32>(address) }.to_le();
+    if is_jal(instruction) {
+        Some(JType(instruction).offset())
+    } else if is_branch(instruction) {
+        Some(BType(instruction).offset())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::length_disassembler::LengthDisassemblerRiscV64;
+    use reloaded_hooks_portable::api::length_disassembler::LengthDisassembler;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::single_ins("13051500", 4, 4, 1)] // addi a0, a0, 1
+    #[case::two_instructions("1305150013051500", 8, 8, 2)] // addi a0, a0, 1 (x2)
+    #[case::non_aligned_min_length("1305150013051500", 5, 8, 2)]
+    #[case::compressed("0505050505050505", 4, 4, 2)] // c.addi a0, 1 (x4)
+    #[case::mixed("050513051500", 4, 6, 2)] // c.addi a0, 1 + addi a0, a0, 1
+    #[case::mixed_odd("0505130515000505", 7, 8, 3)] // c.addi a0, 1 + addi a0, a0, 1 + c.addi a0, 1
+    fn can_disassemble_length(
+        #[case] instructions: &str,
+        #[case] min_length: usize,
+        #[case] expected_length: usize,
+        #[case] expected_num_ins: usize,
+    ) {
+        let ins_vec = hex::decode(instructions).unwrap();
+        let code_address = ins_vec.as_ptr() as usize;
+        let result = LengthDisassemblerRiscV64::disassemble_length(code_address, min_length);
+        assert_eq!(result.0, expected_length);
+        assert_eq!(result.1, expected_num_ins);
+    }
+
+    #[rstest]
+    #[case::no_branch("130515001305150013051500", 8, 4, None)] // addi a0, a0, 1 (x3)
+    #[case::j_into_stolen("13051500130515006ff0dfff", 8, 4, Some((8, 4)))] // addi (x2) + j -4
+    #[case::j_to_start("13051500130515006ff09fff", 8, 4, None)] // addi (x2) + j -8
+    #[case::jal_to_end("1305150013051500ef000000", 8, 4, None)] // addi (x2) + jal 0
+    #[case::beq_into_stolen("1305150013051500e30eb5fe", 8, 4, Some((8, 4)))] // addi (x2) + beq a0, a1, -4
+    #[case::c_j_into_stolen("1305150013051500f5bf", 8, 2, Some((8, 4)))] // addi (x2) + c.j -4
+    #[case::c_beqz_into_stolen("130515001305150075dd", 8, 2, Some((8, 4)))] // addi (x2) + c.beqz a0, -4
+    #[case::in_stolen_region("11c105050505", 6, 0, Some((0, 4)))] // c.beqz a0, 4 + c.addi a0, 1 (x2)
+    #[case::outside_window("13051500130515006ff0dfff", 8, 0, None)] // j -4 is not scanned
+    fn can_find_branch_into_stolen_region(
+        #[case] instructions: &str,
+        #[case] stolen_length: usize,
+        #[case] scan_length: usize,
+        #[case] expected_offsets: Option<(usize, usize)>,
+    ) {
+        let ins_vec = hex::decode(instructions).unwrap();
+        let code_address = ins_vec.as_ptr() as usize;
+        let result = LengthDisassemblerRiscV64::find_branch_into_stolen_region(
+            code_address,
+            stolen_length,
+            scan_length,
+        );
+        assert_eq!(
+            result.map(|(source, target)| (source - code_address, target - code_address)),
+            expected_offsets
+        );
+    }
+}
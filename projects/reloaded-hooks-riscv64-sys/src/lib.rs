@@ -0,0 +1,79 @@
+//! # Some Cool Reloaded Library
+//! Here's the crate documentation.
+#![cfg_attr(not(test), no_std)]
+
+/// Contains all of the functional registers for the RISC-V 64 architecture.
+#[cfg(not(tarpaulin_include))]
+pub mod all_registers;
+
+/// Contains the Just in Time Assembler that integrates with reloaded-hooks-rs.
+pub mod jit;
+
+/// Contains Code Rewriter which translates code from one address to another.
+pub mod rewriter;
+
+/// Contains the length disassembler, returning length of instructions requires for hooking.
+pub mod length_disassembler;
+
+/// Contains the calling convention related info.
+pub mod calling_convention;
+
+/// Rewriting the code from one address to another!
+pub(crate) mod code_rewriter {
+    pub mod foreign_hook;
+    pub mod helpers;
+    pub mod instruction_rewrite_result;
+    pub mod riscv64_rewriter;
+
+    pub(crate) mod instructions {
+        pub mod auipc;
+        pub mod branch;
+        pub mod jal;
+    }
+}
+
+/// This namespace contains the raw instruction encodings for various
+/// RISC-V instructions.
+pub(crate) mod instructions {
+    pub mod b_type;
+    pub mod compressed;
+    pub mod errors;
+    pub mod i_type;
+    pub mod j_type;
+    pub mod load_immediate;
+    pub mod r_type;
+    pub mod s_type;
+    pub mod u_type;
+}
+
+/// This namespace contains the code for encoding the JIT instructions
+/// using the raw instructions in the [`crate::instructions`] namespace.
+pub(crate) mod jit_instructions {
+    pub mod atomic_add;
+    pub mod branch_absolute;
+    pub mod branch_ip_relative;
+    pub mod branch_relative;
+    pub mod enter_guard;
+    pub mod exit_guard;
+    pub mod filter_caller;
+    pub mod jump_absolute_indirect;
+    pub mod load_pc_relative_value;
+    pub mod mov;
+    pub mod mov_from_stack;
+    pub mod mov_to_stack;
+    pub mod multi_pop;
+    pub mod multi_push;
+    pub mod pop;
+    pub mod push;
+    pub mod push_constant;
+    pub mod push_stack;
+    pub mod ret;
+    pub mod stackalloc;
+    pub mod xchg;
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers;
+
+/// Utility methods
+pub(crate) mod helpers;
@@ -0,0 +1,41 @@
+extern crate alloc;
+
+use crate::all_registers::AllRegisters;
+use crate::code_rewriter::foreign_hook::find_foreign_hook;
+use alloc::vec::Vec;
+use reloaded_hooks_portable::api::rewriter::{
+    code_rewriter::{CodeRewriter, CodeRewriterError},
+    foreign_hook::ForeignHook,
+};
+
+pub struct CodeRewriterRiscV64;
+
+impl CodeRewriter<AllRegisters> for CodeRewriterRiscV64 {
+    fn max_ins_size_increase() -> usize {
+        38 // c.beqz to B<!cond> + LUI + ADDIW + SLLI + ADDI + SLLI + ADDI + SLLI + ADDI + JALR.
+    }
+
+    unsafe fn rewrite_code_with_buffer(
+        old_code: *const u8,
+        old_code_size: usize,
+        old_address: usize,
+        new_address: usize,
+        scratch_register: Option<AllRegisters>,
+        existing_buffer: &mut Vec<u8>,
+    ) -> Result<(), CodeRewriterError> {
+        crate::code_rewriter::riscv64_rewriter::rewrite_code_riscv64(
+            old_code,
+            old_code_size,
+            old_address,
+            new_address,
+            scratch_register
+                .filter(|reg| reg.is_gpr())
+                .map(|reg| reg.register_number() as u8),
+            existing_buffer,
+        )
+    }
+
+    unsafe fn find_foreign_hook(address: usize) -> Option<ForeignHook> {
+        find_foreign_hook(address)
+    }
+}
@@ -0,0 +1,29 @@
+pub const CALCULATOR_ADD: [u8; 24] = [
+    0x33, 0x05, 0xb5, 0x00, // add a0, a0, a1
+    0x13, 0x00, 0x00, 0x00, // nop
+    0x13, 0x00, 0x00, 0x00, // nop
+    0x13, 0x00, 0x00, 0x00, // nop
+    0x13, 0x00, 0x00, 0x00, // nop
+    0x67, 0x80, 0x00, 0x00, // ret
+];
+
+pub const CALL_CALCULATOR_ADD: [u8; 44] = [
+    // add_fn:
+    0x33, 0x05, 0xb5, 0x00, // add a0, a0, a1
+    0x67, 0x80, 0x00, 0x00, // ret
+    // add_wrapper:
+    0x13, 0x01, 0x01, 0xff, // addi sp, sp, -16
+    0x23, 0x34, 0x11, 0x00, // sd ra, 8(sp)
+    0xef, 0xf0, 0x1f, 0xff, // jal ra, add_fn
+    0x83, 0x30, 0x81, 0x00, // ld ra, 8(sp)
+    0x13, 0x01, 0x01, 0x01, // addi sp, sp, 16
+    0x67, 0x80, 0x00, 0x00, // ret
+    // target_function:
+    0x33, 0x05, 0xb5, 0x00, // add a0, a0, a1
+    0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+    0x67, 0x80, 0x00, 0x00, // ret
+];
+
+pub const CALL_CALCULATOR_ADD_FUN_OFFSET: usize = 8; // Start of add_wrapper
+pub const CALL_CALCULATOR_ADD_CALL_OFFSET: usize = 16; // Offset of 'jal ra, add_fn' in add_wrapper
+pub const CALL_CALCULATOR_ADD_TARGET_FUNCTION_OFFSET: usize = 32; // Start of target_function
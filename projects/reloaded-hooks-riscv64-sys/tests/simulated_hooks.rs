@@ -0,0 +1,314 @@
+//! Creates hooks in simulated memory, such that hook creation can be tested on any host.
+//! The produced code is never executed, its bytes are compared against the expected encoding instead.
+
+mod asm;
+
+mod tests {
+    use crate::asm::calculator::{
+        CALCULATOR_ADD, CALL_CALCULATOR_ADD, CALL_CALCULATOR_ADD_CALL_OFFSET,
+        CALL_CALCULATOR_ADD_TARGET_FUNCTION_OFFSET,
+    };
+    use reloaded_hooks_portable::api::buffers::simulated_buffer::SimulatedBuffer;
+    use reloaded_hooks_portable::api::buffers::simulated_buffer_factory::SimulatedBufferFactory;
+    use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+    use reloaded_hooks_portable::api::function_info::{BasicFunctionInfo, ParameterType};
+    use reloaded_hooks_portable::api::hooks::assembly::assembly_hook::create_assembly_hook;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook::create_branch_hook_with_callback;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook_fast::create_branch_hook_fast_with_callback;
+    use reloaded_hooks_portable::api::platforms::simulated_memory::{
+        get_simulated_memory, register_simulated_memory,
+    };
+    use reloaded_hooks_portable::api::settings::assembly_hook_settings::AssemblyHookSettings;
+    use reloaded_hooks_portable::api::settings::basic_hook_settings::BasicHookSettings;
+    use reloaded_hooks_portable::api::settings::function_hook_settings::FunctionHookSettings;
+    use reloaded_hooks_riscv64_sys::all_registers::AllRegisters::{self, *};
+    use reloaded_hooks_riscv64_sys::calling_convention::CallingConvention;
+    use reloaded_hooks_riscv64_sys::jit::JitRiscV64;
+    use reloaded_hooks_riscv64_sys::length_disassembler::LengthDisassemblerRiscV64;
+    use reloaded_hooks_riscv64_sys::rewriter::CodeRewriterRiscV64;
+
+    static ADD_INFO: BasicFunctionInfo =
+        BasicFunctionInfo::new(&[ParameterType::i64, ParameterType::i64]);
+
+    /// Reads `len` bytes of simulated memory, as hex.
+    fn read_hex(address: usize, len: usize) -> String {
+        hex::encode(get_simulated_memory().read(address, len))
+    }
+
+    #[test]
+    fn assembly_hook_relocates_jal() {
+        register_simulated_memory();
+        let base = 0x4000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD);
+
+        // Steal `jal ra, add_fn` from `add_wrapper`.
+        let hook_addr = base + CALL_CALCULATOR_ADD_CALL_OFFSET;
+        let code = &[0x93u8, 0x85, 0x15, 0x00]; // addi a1, a1, 1
+        let settings =
+            AssemblyHookSettings::new_minimal(hook_addr, code.as_ptr() as usize, code.len(), 4)
+                .with_scratch_register(t1);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitRiscV64,
+                AllRegisters,
+                LengthDisassemblerRiscV64,
+                CodeRewriterRiscV64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        assert_eq!(read_hex(hook_addr, 4), "6f10107f"); // j 0x40002000
+        assert_eq!(
+            read_hex(base + 0x2000, 12),
+            concat!(
+                "93851500", // addi a1, a1, 1
+                "efd0dfff", // jal ra, 0x40000000 (add_fn)
+                "6fe0cf80", // j 0x40000014
+            )
+        );
+    }
+
+    #[test]
+    fn assembly_hook_with_enable_disable() {
+        register_simulated_memory();
+        let memory = get_simulated_memory();
+        let add_addr = 0x1000_0000;
+        memory.map_code(add_addr, &CALCULATOR_ADD);
+
+        let code = &[0x93u8, 0x85, 0x15, 0x00]; // addi a1, a1, 1
+        let settings =
+            AssemblyHookSettings::new_minimal(add_addr, code.as_ptr() as usize, code.len(), 20)
+                .with_scratch_register(t1);
+
+        let hook = unsafe {
+            create_assembly_hook::<
+                JitRiscV64,
+                AllRegisters,
+                LengthDisassemblerRiscV64,
+                CodeRewriterRiscV64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+            .with_uninstall_on_drop(true)
+        };
+
+        // Stolen bytes not needed for the jump are filled with nops.
+        let hooked = concat!(
+            "6f200000", // j 0x10002000
+            "13000000", // nop
+            "13000000", // nop
+            "13000000", // nop
+            "13000000", // nop
+            "67800000", // ret
+        );
+        let enabled = concat!(
+            "93851500", // addi a1, a1, 1
+            "3305b500", // add a0, a0, a1
+            "13000000", // nop
+            "13000000", // nop
+            "13000000", // nop
+            "13000000", // nop
+            "6fd0dfff", // j 0x10000014
+        );
+        let disabled = concat!(
+            "3305b500", // add a0, a0, a1
+            "13000000", // nop
+            "13000000", // nop
+            "13000000", // nop
+            "13000000", // nop
+            "6fe00f80", // j 0x10000014
+        );
+
+        let stub = add_addr + 0x2000;
+        assert_eq!(read_hex(add_addr, 24), hooked);
+        assert_eq!(read_hex(stub, 28), enabled);
+
+        hook.disable();
+        assert_eq!(read_hex(add_addr, 24), hooked);
+        assert_eq!(read_hex(stub, 24), disabled);
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 28), enabled);
+
+        drop(hook);
+        assert_eq!(memory.read(add_addr, 24), CALCULATOR_ADD);
+        assert_eq!(memory.num_unprotected_regions(), 0);
+    }
+
+    #[test]
+    fn assembly_hook_with_compressed_instructions() {
+        register_simulated_memory();
+        let base = 0x5000_0000;
+        get_simulated_memory().map_code(
+            base,
+            &[
+                0x85, 0x05, // c.addi a1, 1
+                0x33, 0x05, 0xb5, 0x00, // add a0, a0, a1
+                0x01, 0x00, // c.nop
+                0x01, 0x00, // c.nop
+                0x82, 0x80, // ret
+            ],
+        );
+
+        // Stolen code is 10 bytes, i.e. not a whole number of 4 byte instructions.
+        let code = &[0x85u8, 0x05]; // c.addi a1, 1
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 10)
+                .with_scratch_register(t1);
+
+        let hook = unsafe {
+            create_assembly_hook::<
+                JitRiscV64,
+                AllRegisters,
+                LengthDisassemblerRiscV64,
+                CodeRewriterRiscV64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        let hooked = concat!(
+            "6f200000", // j 0x50002000
+            "13000000", // nop
+            "0100",     // c.nop
+            "8280",     // ret
+        );
+        let enabled = concat!(
+            "8505",     // c.addi a1, 1
+            "8505",     // c.addi a1, 1
+            "3305b500", // add a0, a0, a1
+            "0100",     // c.nop
+            "0100",     // c.nop
+            "6fd0ffff", // j 0x5000000a
+        );
+        let disabled = concat!(
+            "8505",     // c.addi a1, 1
+            "3305b500", // add a0, a0, a1
+            "0100",     // c.nop
+            "0100",     // c.nop
+            "6fe00f80", // j 0x5000000a
+        );
+
+        let stub = base + 0x2000;
+        assert_eq!(read_hex(base, 12), hooked);
+        assert_eq!(read_hex(stub, 16), enabled);
+
+        hook.disable();
+        assert_eq!(read_hex(stub, 14), disabled);
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 16), enabled);
+    }
+
+    #[test]
+    fn branch_hook_fast() {
+        register_simulated_memory();
+        let base = 0x2000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_CALL_OFFSET;
+        let settings = BasicHookSettings::new_with_scratch_register(
+            hook_addr,
+            base + CALL_CALCULATOR_ADD_TARGET_FUNCTION_OFFSET,
+            Some(t1),
+        );
+
+        let mut original = 0;
+        unsafe {
+            create_branch_hook_fast_with_callback::<
+                JitRiscV64,
+                AllRegisters,
+                LengthDisassemblerRiscV64,
+                CodeRewriterRiscV64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings, |x| original = x)
+            .unwrap();
+        }
+
+        assert_eq!(original, base);
+        assert_eq!(read_hex(hook_addr, 4), "ef000001"); // jal ra, 0x20000020 (target_function)
+    }
+
+    #[test]
+    fn branch_hook_with_wrapper() {
+        register_simulated_memory();
+        let base = 0x3000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_CALL_OFFSET;
+        let basic_settings =
+            BasicHookSettings::new_with_scratch_register(hook_addr, 0x1234_5678_9ABC, Some(t1));
+
+        // The hook function takes its parameters in reverse, to force a wrapper.
+        let mut reversed: GenericCallingConvention<AllRegisters> =
+            (**CallingConvention::lp64d()).clone();
+        reversed.int_parameters = &[a1, a0];
+
+        let settings = FunctionHookSettings::<
+            AllRegisters,
+            BasicFunctionInfo,
+            GenericCallingConvention<AllRegisters>,
+        >::new(
+            basic_settings,
+            true,
+            ADD_INFO,
+            CallingConvention::lp64d(),
+            &reversed,
+            None,
+        );
+
+        let mut original = 0;
+        let hook = unsafe {
+            create_branch_hook_with_callback::<
+                JitRiscV64,
+                AllRegisters,
+                LengthDisassemblerRiscV64,
+                CodeRewriterRiscV64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+                BasicFunctionInfo,
+                GenericCallingConvention<AllRegisters>,
+            >(&settings, |x| original = x)
+            .unwrap()
+        };
+
+        // The parameters are swapped via a scratch register, and the out of range target is
+        // called via a register which isn't a parameter.
+        let wrapper = concat!(
+            "130181ff", // addi sp, sp, -8
+            "23301100", // sd ra, 0(sp)
+            "130181ff", // addi sp, sp, -8
+            "93820500", // mv t0, a1
+            "93050500", // mv a1, a0
+            "13850200", // mv a0, t0
+            "b7220900", // lui t0, 0x92
+            "9b82b2a2", // addiw t0, t0, -1493
+            "9392c200", // slli t0, t0, 12
+            "9382523c", // addi t0, t0, 965
+            "9392d200", // slli t0, t0, 13
+            "9382c2ab", // addi t0, t0, -1348
+            "e7800200", // jalr t0
+            "13018100", // addi sp, sp, 8
+            "83300100", // ld ra, 0(sp)
+            "13018100", // addi sp, sp, 8
+            "67800000", // ret
+        );
+
+        let stub = base + 0x2000;
+        assert_eq!(original, base);
+        assert_eq!(read_hex(hook_addr, 4), "ef10107f"); // jal ra, 0x30002000
+        assert_eq!(read_hex(stub, 68), wrapper);
+
+        hook.disable();
+        assert_eq!(read_hex(stub, 4), "6fe00f80"); // j 0x30000000 (add_fn)
+
+        hook.enable();
+        assert_eq!(read_hex(stub, 68), wrapper);
+    }
+}
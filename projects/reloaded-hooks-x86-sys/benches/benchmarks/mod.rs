@@ -1,3 +1,4 @@
 pub mod assembler_bench_64;
 pub mod assembly_hook_bench_64;
 pub mod helpers;
+pub mod wrapper_bench_64;
@@ -0,0 +1,72 @@
+use criterion::{black_box, Criterion};
+use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+use reloaded_hooks_portable::api::function_info::{BasicFunctionInfo, ParameterType};
+use reloaded_hooks_portable::api::jit::compiler::Jit;
+use reloaded_hooks_portable::api::wrapper_instruction_generator::{
+    generate_wrapper_instructions, new_wrapper_instruction_generator_options,
+};
+use reloaded_hooks_x86_sys::x64::{self, calling_convention::CallingConvention, jit::JitX64};
+
+// Compare results of `cargo bench` and `cargo bench --features multipushpop`.
+#[cfg(feature = "multipushpop")]
+const SUFFIX: &str = "multipushpop";
+#[cfg(not(feature = "multipushpop"))]
+const SUFFIX: &str = "pushpop";
+
+static ADD_INFO: BasicFunctionInfo =
+    BasicFunctionInfo::new(&[ParameterType::nint, ParameterType::nint]);
+
+extern "sysv64" fn add_system_v(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+/// Creates a Microsoft x64 -> System V wrapper, which backs up `rdi`, `rsi` and `xmm6` - `xmm15`.
+fn create_wrapper_64(target_address: usize) -> Vec<u8> {
+    let options = new_wrapper_instruction_generator_options::<_, x64::Register, JitX64>(
+        false,
+        target_address,
+        &ADD_INFO,
+        None,
+    );
+
+    let ops = generate_wrapper_instructions::<
+        x64::Register,
+        GenericCallingConvention<x64::Register>,
+        BasicFunctionInfo,
+    >(
+        CallingConvention::system_v(),
+        CallingConvention::microsoft_x64(),
+        &options,
+    )
+    .unwrap();
+
+    JitX64::compile(0, &ops).unwrap()
+}
+
+pub(crate) fn benchmark_create_wrapper_64(c: &mut Criterion) {
+    let target = add_system_v as *const () as usize;
+    println!(
+        "wrapper_x64_{SUFFIX} size: {} bytes",
+        create_wrapper_64(target).len()
+    );
+
+    c.bench_function(&format!("create_wrapper_x64_{SUFFIX}"), |b| {
+        b.iter(|| black_box(create_wrapper_64(black_box(target))))
+    });
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn benchmark_call_wrapper_64(c: &mut Criterion) {
+    use super::helpers::alloc_function;
+    use core::mem::transmute;
+
+    // The wrapper is position independent, as it calls the target via absolute address.
+    let code = create_wrapper_64(add_system_v as *const () as usize);
+    let wrapper: extern "win64" fn(i64, i64) -> i64 =
+        unsafe { transmute(alloc_function(&code).unwrap()) };
+    assert_eq!(wrapper(1, 2), 3);
+
+    c.bench_function(&format!("call_wrapper_x64_{SUFFIX}"), |b| {
+        b.iter(|| black_box(wrapper(black_box(1), black_box(2))))
+    });
+}
@@ -2,6 +2,9 @@ mod benchmarks;
 use benchmarks::assembler_bench_64::{benchmark_assemble_x64_total, benchmark_compile_only};
 #[allow(unused_imports)]
 use benchmarks::assembly_hook_bench_64::benchmark_create_assembly_hook; // commented out
+#[cfg(target_arch = "x86_64")]
+use benchmarks::wrapper_bench_64::benchmark_call_wrapper_64;
+use benchmarks::wrapper_bench_64::benchmark_create_wrapper_64;
 use criterion::{criterion_group, criterion_main, Criterion};
 
 #[cfg(not(target_os = "windows"))]
@@ -11,6 +14,11 @@ fn criterion_benchmark(c: &mut Criterion) {
     benchmark_assemble_x64_total(c); // assemble_x64_total
     benchmark_compile_only(c); // assemble_x64_compile_only
 
+    // Compare with and without the 'multipushpop' feature.
+    benchmark_create_wrapper_64(c); // create_wrapper_x64_{pushpop/multipushpop}
+    #[cfg(target_arch = "x86_64")]
+    benchmark_call_wrapper_64(c); // call_wrapper_x64_{pushpop/multipushpop}
+
     // Flawed benchmark, see readme!!
     // benchmark_create_assembly_hook(c); // assembly_hook_creation
 }
@@ -51,6 +51,40 @@ pub enum AllRegisters {
     #[cfg(feature = "x64")]
     r15,
 
+    // Intel APX extended general purpose registers (x64 only):
+    #[cfg(feature = "x64")]
+    r16,
+    #[cfg(feature = "x64")]
+    r17,
+    #[cfg(feature = "x64")]
+    r18,
+    #[cfg(feature = "x64")]
+    r19,
+    #[cfg(feature = "x64")]
+    r20,
+    #[cfg(feature = "x64")]
+    r21,
+    #[cfg(feature = "x64")]
+    r22,
+    #[cfg(feature = "x64")]
+    r23,
+    #[cfg(feature = "x64")]
+    r24,
+    #[cfg(feature = "x64")]
+    r25,
+    #[cfg(feature = "x64")]
+    r26,
+    #[cfg(feature = "x64")]
+    r27,
+    #[cfg(feature = "x64")]
+    r28,
+    #[cfg(feature = "x64")]
+    r29,
+    #[cfg(feature = "x64")]
+    r30,
+    #[cfg(feature = "x64")]
+    r31,
+
     // x87 Floating-point stack registers (common to both x86 and x64):
     st0,
     st1,
@@ -172,6 +206,25 @@ impl AllRegisters {
             | AllRegisters::r14
             | AllRegisters::r15 => 8, // 64 bits
 
+            // Intel APX extended general purpose registers:
+            #[cfg(feature = "x64")]
+            AllRegisters::r16
+            | AllRegisters::r17
+            | AllRegisters::r18
+            | AllRegisters::r19
+            | AllRegisters::r20
+            | AllRegisters::r21
+            | AllRegisters::r22
+            | AllRegisters::r23
+            | AllRegisters::r24
+            | AllRegisters::r25
+            | AllRegisters::r26
+            | AllRegisters::r27
+            | AllRegisters::r28
+            | AllRegisters::r29
+            | AllRegisters::r30
+            | AllRegisters::r31 => 8, // 64 bits
+
             // x87 Floating-point stack registers (common to both x86 and x64):
             AllRegisters::st0
             | AllRegisters::st1
@@ -286,6 +339,36 @@ impl AllRegisters {
         false
     }
 
+    /// True for the Intel APX extended general purpose registers (`r16` - `r31`), which
+    /// the assembler cannot encode; and are instead encoded by hand with a REX2 prefix.
+    pub(crate) fn is_apx(&self) -> bool {
+        #[cfg(feature = "x64")]
+        {
+            matches!(
+                *self,
+                AllRegisters::r16
+                    | AllRegisters::r17
+                    | AllRegisters::r18
+                    | AllRegisters::r19
+                    | AllRegisters::r20
+                    | AllRegisters::r21
+                    | AllRegisters::r22
+                    | AllRegisters::r23
+                    | AllRegisters::r24
+                    | AllRegisters::r25
+                    | AllRegisters::r26
+                    | AllRegisters::r27
+                    | AllRegisters::r28
+                    | AllRegisters::r29
+                    | AllRegisters::r30
+                    | AllRegisters::r31
+            )
+        }
+
+        #[cfg(not(feature = "x64"))]
+        false
+    }
+
     pub(crate) fn is_xmm(&self) -> bool {
         #[cfg(feature = "x64")]
         {
@@ -490,6 +573,25 @@ impl AllRegisters {
             #[cfg(feature = "x64")]
             AllRegisters::r15 => Ok(iced_x86::Register::R15),
 
+            // Intel APX registers are not supported by the assembler.
+            #[cfg(feature = "x64")]
+            AllRegisters::r16
+            | AllRegisters::r17
+            | AllRegisters::r18
+            | AllRegisters::r19
+            | AllRegisters::r20
+            | AllRegisters::r21
+            | AllRegisters::r22
+            | AllRegisters::r23
+            | AllRegisters::r24
+            | AllRegisters::r25
+            | AllRegisters::r26
+            | AllRegisters::r27
+            | AllRegisters::r28
+            | AllRegisters::r29
+            | AllRegisters::r30
+            | AllRegisters::r31 => Err(JitError::InvalidRegister(*self)),
+
             // x87 Floating-point stack registers
             AllRegisters::st0 => Ok(iced_x86::Register::ST0),
             AllRegisters::st1 => Ok(iced_x86::Register::ST1),
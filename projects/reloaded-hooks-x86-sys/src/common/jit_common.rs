@@ -1,19 +1,23 @@
 extern crate alloc;
 use crate::all_registers::AllRegisters;
 use crate::instructions::{
-    call_absolute::encode_call_absolute, call_relative::encode_call_relative,
+    atomic_add::encode_atomic_add, call_absolute::encode_call_absolute,
+    call_relative::encode_call_relative, enter_guard::encode_enter_guard,
+    exit_guard::encode_exit_guard, filter_caller::encode_filter_caller,
     jump_absolute::encode_jump_absolute, jump_absolute_indirect::encode_jump_absolute_indirect,
-    jump_relative::encode_jump_relative, mov::encode_mov, mov_from_stack::encode_mov_from_stack,
+    jump_relative::encode_jump_relative, mov::encode_mov,
+    mov_float_from_stack::encode_mov_float_from_stack,
+    mov_float_to_stack::encode_mov_float_to_stack, mov_from_stack::encode_mov_from_stack,
     mov_to_stack::encode_mov_to_stack, pop::encode_pop, push::encode_push,
     push_const::encode_push_constant, push_stack::encode_push_stack, ret::encode_return,
     stack_alloc::encode_stack_alloc, xchg::encode_xchg,
 };
 use alloc::string::ToString;
 
-#[cfg(target_feature = "multipushpop")]
+#[cfg(feature = "multipushpop")]
 use crate::instructions::multi_pop::encode_multi_pop;
 
-#[cfg(target_feature = "multipushpop")]
+#[cfg(feature = "multipushpop")]
 use crate::instructions::multi_push::encode_multi_push;
 
 #[cfg(feature = "x64")]
@@ -46,6 +50,12 @@ pub(crate) fn encode_instruction(
         Operation::JumpAbsolute(x) => Ok(encode_jump_absolute(assembler, x)?),
         Operation::JumpAbsoluteIndirect(x) => Ok(encode_jump_absolute_indirect(assembler, x)?),
         Operation::MovToStack(x) => Ok(encode_mov_to_stack(assembler, x)?),
+        Operation::MovFloatFromStack(x) => Ok(encode_mov_float_from_stack(assembler, x)?),
+        Operation::MovFloatToStack(x) => Ok(encode_mov_float_to_stack(assembler, x)?),
+        Operation::AtomicAdd(x) => Ok(encode_atomic_add(assembler, x)?),
+        Operation::EnterGuard(x) => Ok(encode_enter_guard(assembler, x, address)?),
+        Operation::ExitGuard(x) => Ok(encode_exit_guard(assembler, x)?),
+        Operation::FilterCaller(x) => Ok(encode_filter_caller(assembler, x, address)?),
 
         // x64 only
         #[cfg(feature = "x64")]
@@ -57,12 +67,12 @@ pub(crate) fn encode_instruction(
         Operation::PushConst(x) => Ok(encode_push_constant(assembler, x)?),
         Operation::Return(x) => Ok(encode_return(assembler, x)?),
 
-        // Deprecated
-        #[cfg(target_feature = "multipushpop")]
-        Operation::MultiPush(x) => encode_multi_push(assembler, x),
+        // Optional, see the 'multipushpop' feature
+        #[cfg(feature = "multipushpop")]
+        Operation::MultiPush(x) => Ok(encode_multi_push(assembler, x)?),
 
-        #[cfg(target_feature = "multipushpop")]
-        Operation::MultiPop(x) => encode_multi_pop(assembler, x),
+        #[cfg(feature = "multipushpop")]
+        Operation::MultiPop(x) => Ok(encode_multi_pop(assembler, x)?),
         _ => unreachable!(),
     }
 }
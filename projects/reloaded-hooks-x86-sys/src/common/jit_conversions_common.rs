@@ -70,6 +70,22 @@ pub(crate) fn map_register_x64_to_allregisters(reg: crate::x64::Register) -> All
         crate::x64::Register::r13 => AllRegisters::r13,
         crate::x64::Register::r14 => AllRegisters::r14,
         crate::x64::Register::r15 => AllRegisters::r15,
+        crate::x64::Register::r16 => AllRegisters::r16,
+        crate::x64::Register::r17 => AllRegisters::r17,
+        crate::x64::Register::r18 => AllRegisters::r18,
+        crate::x64::Register::r19 => AllRegisters::r19,
+        crate::x64::Register::r20 => AllRegisters::r20,
+        crate::x64::Register::r21 => AllRegisters::r21,
+        crate::x64::Register::r22 => AllRegisters::r22,
+        crate::x64::Register::r23 => AllRegisters::r23,
+        crate::x64::Register::r24 => AllRegisters::r24,
+        crate::x64::Register::r25 => AllRegisters::r25,
+        crate::x64::Register::r26 => AllRegisters::r26,
+        crate::x64::Register::r27 => AllRegisters::r27,
+        crate::x64::Register::r28 => AllRegisters::r28,
+        crate::x64::Register::r29 => AllRegisters::r29,
+        crate::x64::Register::r30 => AllRegisters::r30,
+        crate::x64::Register::r31 => AllRegisters::r31,
 
         crate::x64::Register::st0 => AllRegisters::st0,
         crate::x64::Register::st1 => AllRegisters::st1,
@@ -134,49 +150,52 @@ pub(crate) fn map_register_x64_to_allregisters(reg: crate::x64::Register) -> All
 }
 
 #[cfg(feature = "x86")]
-pub(crate) fn map_allregisters_to_x86(reg: AllRegisters) -> crate::x86::Register {
+pub(crate) fn map_allregisters_to_x86(reg: AllRegisters) -> Option<crate::x86::Register> {
     match reg {
-        AllRegisters::eax => crate::x86::Register::eax,
-        AllRegisters::ebx => crate::x86::Register::ebx,
-        AllRegisters::ecx => crate::x86::Register::ecx,
-        AllRegisters::edx => crate::x86::Register::edx,
-        AllRegisters::esi => crate::x86::Register::esi,
-        AllRegisters::edi => crate::x86::Register::edi,
-        AllRegisters::ebp => crate::x86::Register::ebp,
-        AllRegisters::esp => crate::x86::Register::esp,
-        AllRegisters::st0 => crate::x86::Register::st0,
-        AllRegisters::st1 => crate::x86::Register::st1,
-        AllRegisters::st2 => crate::x86::Register::st2,
-        AllRegisters::st3 => crate::x86::Register::st3,
-        AllRegisters::st4 => crate::x86::Register::st4,
-        AllRegisters::st5 => crate::x86::Register::st5,
-        AllRegisters::st6 => crate::x86::Register::st6,
-        AllRegisters::st7 => crate::x86::Register::st7,
-        AllRegisters::xmm0 => crate::x86::Register::xmm0,
-        AllRegisters::xmm1 => crate::x86::Register::xmm1,
-        AllRegisters::xmm2 => crate::x86::Register::xmm2,
-        AllRegisters::xmm3 => crate::x86::Register::xmm3,
-        AllRegisters::xmm4 => crate::x86::Register::xmm4,
-        AllRegisters::xmm5 => crate::x86::Register::xmm5,
-        AllRegisters::xmm6 => crate::x86::Register::xmm6,
-        AllRegisters::xmm7 => crate::x86::Register::xmm7,
-        AllRegisters::ymm0 => crate::x86::Register::ymm0,
-        AllRegisters::ymm1 => crate::x86::Register::ymm1,
-        AllRegisters::ymm2 => crate::x86::Register::ymm2,
-        AllRegisters::ymm3 => crate::x86::Register::ymm3,
-        AllRegisters::ymm4 => crate::x86::Register::ymm4,
-        AllRegisters::ymm5 => crate::x86::Register::ymm5,
-        AllRegisters::ymm6 => crate::x86::Register::ymm6,
-        AllRegisters::ymm7 => crate::x86::Register::ymm7,
-        AllRegisters::zmm0 => crate::x86::Register::zmm0,
-        AllRegisters::zmm1 => crate::x86::Register::zmm1,
-        AllRegisters::zmm2 => crate::x86::Register::zmm2,
-        AllRegisters::zmm3 => crate::x86::Register::zmm3,
-        AllRegisters::zmm4 => crate::x86::Register::zmm4,
-        AllRegisters::zmm5 => crate::x86::Register::zmm5,
-        AllRegisters::zmm6 => crate::x86::Register::zmm6,
-        AllRegisters::zmm7 => crate::x86::Register::zmm7,
-        _ => todo!(),
+        AllRegisters::eax => Some(crate::x86::Register::eax),
+        AllRegisters::ebx => Some(crate::x86::Register::ebx),
+        AllRegisters::ecx => Some(crate::x86::Register::ecx),
+        AllRegisters::edx => Some(crate::x86::Register::edx),
+        AllRegisters::esi => Some(crate::x86::Register::esi),
+        AllRegisters::edi => Some(crate::x86::Register::edi),
+        AllRegisters::ebp => Some(crate::x86::Register::ebp),
+        AllRegisters::esp => Some(crate::x86::Register::esp),
+        AllRegisters::st0 => Some(crate::x86::Register::st0),
+        AllRegisters::st1 => Some(crate::x86::Register::st1),
+        AllRegisters::st2 => Some(crate::x86::Register::st2),
+        AllRegisters::st3 => Some(crate::x86::Register::st3),
+        AllRegisters::st4 => Some(crate::x86::Register::st4),
+        AllRegisters::st5 => Some(crate::x86::Register::st5),
+        AllRegisters::st6 => Some(crate::x86::Register::st6),
+        AllRegisters::st7 => Some(crate::x86::Register::st7),
+        AllRegisters::xmm0 => Some(crate::x86::Register::xmm0),
+        AllRegisters::xmm1 => Some(crate::x86::Register::xmm1),
+        AllRegisters::xmm2 => Some(crate::x86::Register::xmm2),
+        AllRegisters::xmm3 => Some(crate::x86::Register::xmm3),
+        AllRegisters::xmm4 => Some(crate::x86::Register::xmm4),
+        AllRegisters::xmm5 => Some(crate::x86::Register::xmm5),
+        AllRegisters::xmm6 => Some(crate::x86::Register::xmm6),
+        AllRegisters::xmm7 => Some(crate::x86::Register::xmm7),
+        AllRegisters::ymm0 => Some(crate::x86::Register::ymm0),
+        AllRegisters::ymm1 => Some(crate::x86::Register::ymm1),
+        AllRegisters::ymm2 => Some(crate::x86::Register::ymm2),
+        AllRegisters::ymm3 => Some(crate::x86::Register::ymm3),
+        AllRegisters::ymm4 => Some(crate::x86::Register::ymm4),
+        AllRegisters::ymm5 => Some(crate::x86::Register::ymm5),
+        AllRegisters::ymm6 => Some(crate::x86::Register::ymm6),
+        AllRegisters::ymm7 => Some(crate::x86::Register::ymm7),
+        AllRegisters::zmm0 => Some(crate::x86::Register::zmm0),
+        AllRegisters::zmm1 => Some(crate::x86::Register::zmm1),
+        AllRegisters::zmm2 => Some(crate::x86::Register::zmm2),
+        AllRegisters::zmm3 => Some(crate::x86::Register::zmm3),
+        AllRegisters::zmm4 => Some(crate::x86::Register::zmm4),
+        AllRegisters::zmm5 => Some(crate::x86::Register::zmm5),
+        AllRegisters::zmm6 => Some(crate::x86::Register::zmm6),
+        AllRegisters::zmm7 => Some(crate::x86::Register::zmm7),
+        // x64 only registers (rax-r31, xmm8-xmm15, etc.) have no x86 equivalent.
+        // 64-bit registers are not mapped to their lower halves, as that would truncate them.
+        #[allow(unreachable_patterns)]
+        _ => None,
     }
 }
 
@@ -199,6 +218,22 @@ pub(crate) fn map_allregisters_to_x64(reg: AllRegisters) -> crate::x64::Register
         AllRegisters::r13 => crate::x64::Register::r13,
         AllRegisters::r14 => crate::x64::Register::r14,
         AllRegisters::r15 => crate::x64::Register::r15,
+        AllRegisters::r16 => crate::x64::Register::r16,
+        AllRegisters::r17 => crate::x64::Register::r17,
+        AllRegisters::r18 => crate::x64::Register::r18,
+        AllRegisters::r19 => crate::x64::Register::r19,
+        AllRegisters::r20 => crate::x64::Register::r20,
+        AllRegisters::r21 => crate::x64::Register::r21,
+        AllRegisters::r22 => crate::x64::Register::r22,
+        AllRegisters::r23 => crate::x64::Register::r23,
+        AllRegisters::r24 => crate::x64::Register::r24,
+        AllRegisters::r25 => crate::x64::Register::r25,
+        AllRegisters::r26 => crate::x64::Register::r26,
+        AllRegisters::r27 => crate::x64::Register::r27,
+        AllRegisters::r28 => crate::x64::Register::r28,
+        AllRegisters::r29 => crate::x64::Register::r29,
+        AllRegisters::r30 => crate::x64::Register::r30,
+        AllRegisters::r31 => crate::x64::Register::r31,
         AllRegisters::st0 => crate::x64::Register::st0,
         AllRegisters::st1 => crate::x64::Register::st1,
         AllRegisters::st2 => crate::x64::Register::st2,
@@ -255,6 +290,31 @@ pub(crate) fn map_allregisters_to_x64(reg: AllRegisters) -> crate::x64::Register
         AllRegisters::zmm13 => crate::x64::Register::zmm13,
         AllRegisters::zmm14 => crate::x64::Register::zmm14,
         AllRegisters::zmm15 => crate::x64::Register::zmm15,
-        _ => todo!(),
+        // x86 registers are the lower halves of their x64 counterparts.
+        AllRegisters::eax => crate::x64::Register::rax,
+        AllRegisters::ebx => crate::x64::Register::rbx,
+        AllRegisters::ecx => crate::x64::Register::rcx,
+        AllRegisters::edx => crate::x64::Register::rdx,
+        AllRegisters::esi => crate::x64::Register::rsi,
+        AllRegisters::edi => crate::x64::Register::rdi,
+        AllRegisters::ebp => crate::x64::Register::rbp,
+        AllRegisters::esp => crate::x64::Register::rsp,
+    }
+}
+
+#[cfg(all(test, feature = "x86", feature = "x64"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn x64_only_registers_have_no_x86_equivalent() {
+        assert_eq!(
+            map_allregisters_to_x86(AllRegisters::eax),
+            Some(crate::x86::Register::eax)
+        );
+        assert_eq!(map_allregisters_to_x86(AllRegisters::rax), None);
+        assert_eq!(map_allregisters_to_x86(AllRegisters::rsp), None);
+        assert_eq!(map_allregisters_to_x86(AllRegisters::r8), None);
+        assert_eq!(map_allregisters_to_x86(AllRegisters::xmm8), None);
     }
 }
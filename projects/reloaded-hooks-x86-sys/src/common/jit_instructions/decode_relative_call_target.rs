@@ -1,5 +1,6 @@
-use core::ptr::read_unaligned;
-use reloaded_hooks_portable::api::jit::compiler::DecodeCallTargetResult;
+use reloaded_hooks_portable::{
+    api::jit::compiler::DecodeCallTargetResult, helpers::read_code::read_code_as,
+};
 
 pub fn decode_call_target(
     ins_address: usize,
@@ -11,7 +12,7 @@ pub fn decode_call_target(
         );
     }
 
-    let opcode = unsafe { *(ins_address as *const u8) };
+    let opcode = unsafe { read_code_as::<u8>(ins_address) };
     let is_call = match opcode {
         0xE8 => true,
         0xE9 => false,
@@ -19,7 +20,7 @@ pub fn decode_call_target(
     };
 
     // Decode the 32-bit offset
-    let offset = i32::from_le(unsafe { read_unaligned((ins_address + 1) as *const i32) });
+    let offset = i32::from_le(unsafe { read_code_as::<i32>(ins_address + 1) });
 
     // Calculate and return the target address
     let target = (ins_address as isize)
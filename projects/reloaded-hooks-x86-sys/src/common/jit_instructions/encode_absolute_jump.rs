@@ -1,7 +1,7 @@
 // File: encode_absolute_jump.rs
 
 extern crate alloc;
-use super::helpers::{opcode_offset_for_x64_register, opcode_offset_for_x86_register};
+use super::helpers::{encode_rex2, opcode_offset_for_x64_register, opcode_offset_for_x86_register};
 use crate::{x64, x86};
 use alloc::vec::Vec;
 use reloaded_hooks_portable::api::jit::{
@@ -34,12 +34,19 @@ pub fn encode_absolute_jump_x64(
     let opcode_offset = opcode_offset_for_x64_register(x.scratch_register);
 
     // MOV instruction (REX prefix + opcode + immediate)
-    buf.push(0x48 + ((opcode_offset >= 8) as u8)); // REX prefix for 64-bit operand size
+    if opcode_offset >= 16 {
+        buf.extend_from_slice(&encode_rex2(true, 0, opcode_offset)); // REX2 prefix for R16-R31
+    } else {
+        buf.push(0x48 + ((opcode_offset >= 8) as u8)); // REX prefix for 64-bit operand size
+    }
+
     buf.push(0xB8 + (opcode_offset % 8)); // MOV reg, imm64
     buf.extend_from_slice(&(x.target_address as u64).to_le_bytes()); // 64-bit immediate value for the target address
 
     // JMP reg (REX prefix + opcode + ModRM)
-    if opcode_offset >= 8 {
+    if opcode_offset >= 16 {
+        buf.extend_from_slice(&encode_rex2(false, 0, opcode_offset)); // REX2 prefix for R16-R31
+    } else if opcode_offset >= 8 {
         buf.push(0x41); // REX Prefix for R8-R15
     }
 
@@ -75,6 +82,8 @@ mod tests {
     #[case(X64Register::r13, "49bdefbeaddeefbeadde41ffe5")]
     #[case(X64Register::r14, "49beefbeaddeefbeadde41ffe6")]
     #[case(X64Register::r15, "49bfefbeaddeefbeadde41ffe7")]
+    #[case(X64Register::r16, "d518b8efbeaddeefbeadded510ffe0")]
+    #[case(X64Register::r31, "d519bfefbeaddeefbeadded511ffe7")]
     // Add other cases for different x64 registers here
     fn test_encode_absolute_jump_x64(
         #[case] scratch_register: X64Register,
@@ -1,4 +1,9 @@
+extern crate alloc;
+
+#[cfg(feature = "x64")]
+use crate::{all_registers::AllRegisters, common::jit_conversions_common::map_allregisters_to_x64};
 use crate::{x64, x86};
+use alloc::vec::Vec;
 use core::hint::unreachable_unchecked;
 
 // Lookup table for x86 registers to opcode offset
@@ -36,8 +41,108 @@ pub(crate) fn opcode_offset_for_x64_register(register: x64::Register) -> u8 {
         x64::Register::r13 => 13,
         x64::Register::r14 => 14,
         x64::Register::r15 => 15,
-        // Add other registers if needed
-        // TODO: Support for Intel APX registers
+        // Intel APX registers, these need a REX2 prefix (see `encode_rex2`)
+        x64::Register::r16 => 16,
+        x64::Register::r17 => 17,
+        x64::Register::r18 => 18,
+        x64::Register::r19 => 19,
+        x64::Register::r20 => 20,
+        x64::Register::r21 => 21,
+        x64::Register::r22 => 22,
+        x64::Register::r23 => 23,
+        x64::Register::r24 => 24,
+        x64::Register::r25 => 25,
+        x64::Register::r26 => 26,
+        x64::Register::r27 => 27,
+        x64::Register::r28 => 28,
+        x64::Register::r29 => 29,
+        x64::Register::r30 => 30,
+        x64::Register::r31 => 31,
         _ => unsafe { unreachable_unchecked() },
     }
 }
+
+/// Returns the opcode offset of a 64-bit general purpose register, including `r16` - `r31`.
+#[cfg(feature = "x64")]
+pub(crate) fn opcode_offset_for_gpr64(register: AllRegisters) -> u8 {
+    opcode_offset_for_x64_register(map_allregisters_to_x64(register))
+}
+
+/// Encodes a REX2 prefix (Intel APX), which extends the ModRM `reg` and `rm` fields
+/// (or the register in the opcode) to 5 bits, allowing access to `r16` - `r31`.
+///
+/// # Parameters
+/// `w`: Whether the operand size is 64-bit (REX.W).
+/// `reg`: Opcode offset of the register in the ModRM `reg` field.
+/// `rm`: Opcode offset of the register in the ModRM `rm` field, or the opcode itself.
+///
+/// The opcode offsets are returned by [`opcode_offset_for_x64_register`].
+pub(crate) fn encode_rex2(w: bool, reg: u8, rm: u8) -> [u8; 2] {
+    // Payload: [M0 R4 X4 B4 W R3 X3 B3]; M0 = 0 selects the legacy one byte opcode map.
+    let payload = ((reg & 0b10000) << 2)
+        | (rm & 0b10000)
+        | ((w as u8) << 3)
+        | ((reg & 0b1000) >> 1)
+        | ((rm & 0b1000) >> 3);
+    [0xD5, payload]
+}
+
+/// Encodes a 64-bit instruction with a REX2 prefix, and a `[rsp + offset]` memory operand
+/// in its ModRM `rm` field; e.g. `mov r16, [rsp + 8]`.
+///
+/// # Parameters
+/// `opcode`: The opcode, e.g. `0x8B` for `mov r64, r/m64`.
+/// `reg`: Opcode offset of the register in the ModRM `reg` field.
+/// `offset`: Offset from the stack pointer.
+pub(crate) fn encode_rex2_stack_operand(opcode: u8, reg: u8, offset: i32) -> Vec<u8> {
+    const RSP: u8 = 4; // rm = 100 with a SIB byte of 0x24 is [rsp]
+    let mut code = Vec::with_capacity(11);
+    code.extend_from_slice(&encode_rex2(true, reg, RSP));
+    code.push(opcode);
+
+    let modrm = ((reg & 0b111) << 3) | RSP;
+    if offset == 0 {
+        code.extend_from_slice(&[modrm, 0x24]);
+    } else if let Ok(disp) = i8::try_from(offset) {
+        code.extend_from_slice(&[0b01000000 | modrm, 0x24, disp as u8]);
+    } else {
+        code.extend_from_slice(&[0b10000000 | modrm, 0x24]);
+        code.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(false, 0, 16, [0xD5, 0x10])] // push r16
+    #[case(false, 0, 31, [0xD5, 0x11])] // pop r31
+    #[case(true, 16, 0, [0xD5, 0x48])] // mov rax, r16
+    #[case(true, 1, 25, [0xD5, 0x19])] // mov r25, rcx
+    #[case(true, 31, 12, [0xD5, 0x4D])] // mov r12, r31
+    fn rex2(#[case] w: bool, #[case] reg: u8, #[case] rm: u8, #[case] expected: [u8; 2]) {
+        assert_eq!(encode_rex2(w, reg, rm), expected);
+    }
+
+    #[rstest]
+    #[case(0x8B, 16, 0, "d5488b0424")] // mov r16, [rsp]
+    #[case(0x8B, 16, 4, "d5488b442404")] // mov r16, [rsp + 4]
+    #[case(0x8B, 16, -8, "d5488b4424f8")] // mov r16, [rsp - 8]
+    #[case(0x8B, 16, 0x100, "d5488b842400010000")] // mov r16, [rsp + 0x100]
+    #[case(0x89, 31, 16, "d54c897c2410")] // mov [rsp + 16], r31
+    fn rex2_stack_operand(
+        #[case] opcode: u8,
+        #[case] reg: u8,
+        #[case] offset: i32,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            hex::encode(encode_rex2_stack_operand(opcode, reg, offset)),
+            expected
+        );
+    }
+}
@@ -1,19 +1,38 @@
 extern crate alloc;
 
 use crate::all_registers::AllRegisters;
+use alloc::string::ToString;
 use alloc::vec::Vec;
-use iced_x86::Instruction;
-use iced_x86::{BlockEncoder, BlockEncoderOptions, InstructionBlock};
+use iced_x86::{BlockEncoder, BlockEncoderOptions, Encoder, InstructionBlock};
+use iced_x86::{Instruction, OpKind};
 use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
 use smallvec::SmallVec;
 
+#[cfg(feature = "x86")]
+use super::patches_x86::patch_pc_thunk;
+
 // Patches only needed for 64-bit.
 #[cfg(feature = "x64")]
 use super::patches::{
-    patch_jcx, patch_jump_conditional, patch_loop, patch_relative_branch,
-    patch_rip_relative_operand,
+    patch_apx_rip_relative_operand, patch_jcx, patch_jump_conditional, patch_loop,
+    patch_relative_branch, patch_rip_relative_operand,
 };
 
+#[cfg(feature = "x64")]
+use crate::common::util::apx::get_apx_rip_relative_instruction;
+
+/// Length of a GCC/Clang style PC thunk, e.g. `__x86.get_pc_thunk.bx`:
+///
+/// ```text
+/// 8b 1c 24    mov ebx, [esp]
+/// c3          ret
+/// ```
+pub(crate) const PC_THUNK_LEN: usize = 4;
+
+/// Reads [`PC_THUNK_LEN`] bytes of code at the given address.
+/// Returns [`None`] if the memory cannot be read.
+pub(crate) type ReadCodeFn<'a> = &'a dyn Fn(usize) -> Option<[u8; PC_THUNK_LEN]>;
+
 /// Relocates the code to a new location.
 ///
 /// # Parameters
@@ -22,6 +41,7 @@ use super::patches::{
 /// `orig_ins_bytes`: The original instruction bytes.
 /// `new_pc`: The new program counter (RIP/EIP).
 /// `scratch_gpr`: A scratch general purpose register that can be used for operations.
+/// `read_code`: Reads code at the target of a call, used to detect PC thunks in 32-bit code.
 /// `buf`: The buffer to write the relocated code to.
 ///
 /// # Safety (For >= 2GiB relocations)
@@ -39,12 +59,18 @@ pub(crate) fn relocate_code(
     orig_ins_bytes: &[u8],
     new_pc: usize,
     scratch_gpr: Option<AllRegisters>,
+    #[allow(unused_variables)] read_code: Option<ReadCodeFn>,
     buf: &mut Vec<u8>,
 ) -> Result<(), CodeRewriterError> {
     let mut new_isns: SmallVec<[Instruction; 4]> = Default::default();
     let mut current_new_pc = new_pc;
     let mut needs_rewriting = false;
 
+    // Index of the first instruction in `new_isns` emitted for each instruction in `instructions`.
+    // Used to map encoder errors back to the original instruction.
+    let mut new_isns_starts: SmallVec<[usize; 4]> = Default::default();
+    let start_ip = instructions.first().map_or(0, |x| x.ip());
+
     // This code will be eliminated in a x86/x64 only build, because only 1 call will be made here
     // and the compiler will eliminate out the constant branch.
     #[cfg(feature = "x64")]
@@ -52,61 +78,91 @@ pub(crate) fn relocate_code(
         // Note: These translations can only happen in x64, because in x86, branches will always be reachable.
         // Otherwise we need to translate the jmp/call to an absolute address.
         for instruction in instructions {
+            new_isns_starts.push(new_isns.len());
+
             // Note: Check docs for `UnconditionalBranch` and `Call` above for instructions accepted into this branch.
             // If this is not a near call or jump, copy the instruction straight up.
             let is_call_near = instruction.is_call_near();
             let is_jmp_near = instruction.is_jmp_short_or_near();
-            if is_call_near || is_jmp_near {
+            let patch_result = if is_call_near || is_jmp_near {
                 patch_relative_branch(
                     scratch_gpr,
                     &mut new_isns,
                     &mut current_new_pc,
                     instruction,
                     is_call_near,
-                )?;
-                needs_rewriting = true;
-                continue;
-            }
-
-            // Conditional Branch
-            if instruction.is_jcc_short_or_near() {
-                patch_jump_conditional(
-                    scratch_gpr,
-                    &mut new_isns,
-                    &mut current_new_pc,
-                    instruction,
-                )?;
-                needs_rewriting = true;
-                continue;
+                )
+            } else if instruction.is_jcc_short_or_near() {
+                // Conditional Branch
+                patch_jump_conditional(scratch_gpr, &mut new_isns, &mut current_new_pc, instruction)
             } else if instruction.is_loopcc() || instruction.is_loop() {
-                patch_loop(scratch_gpr, &mut new_isns, &mut current_new_pc, instruction)?;
-                needs_rewriting = true;
-                continue;
+                patch_loop(scratch_gpr, &mut new_isns, &mut current_new_pc, instruction)
             } else if instruction.is_jcx_short() {
-                patch_jcx(scratch_gpr, &mut new_isns, &mut current_new_pc, instruction)?;
-                needs_rewriting = true;
-                continue;
+                patch_jcx(scratch_gpr, &mut new_isns, &mut current_new_pc, instruction)
             } else if instruction.memory_base() == iced_x86::Register::RIP {
                 patch_rip_relative_operand(
                     scratch_gpr,
                     &mut new_isns,
                     &mut current_new_pc,
                     instruction,
-                )?;
-                needs_rewriting = true;
+                )
+            } else if let Some((bytes, displacement_offset)) =
+                get_apx_rip_relative_instruction(instruction)
+            {
+                // Intel APX instructions are copied as is, unless they are RIP relative.
+                patch_apx_rip_relative_operand(
+                    &mut new_isns,
+                    &mut current_new_pc,
+                    instruction,
+                    bytes,
+                    displacement_offset,
+                )
+            } else if instruction.is_ip_rel_memory_operand()
+                || instruction.op0_kind() == OpKind::NearBranch64
+            {
+                // Remaining IP relative instructions (e.g. `xbegin`, `[eip + disp]`); iced
+                // re-encodes these, or errors if the target is out of range.
+                append_instruction_with_new_pc(&mut new_isns, &mut current_new_pc, instruction);
+                Ok(())
+            } else {
+                // Everything else is unhandled
+                append_instruction_with_new_pc(&mut new_isns, &mut current_new_pc, instruction);
                 continue;
-            }
+            };
 
-            // Everything else is unhandled
-            append_instruction_with_new_pc(&mut new_isns, &mut current_new_pc, instruction);
+            patch_result
+                .map_err(|x| add_instruction_context(x, instruction, start_ip, orig_ins_bytes))?;
+            needs_rewriting = true;
         }
     }
 
     #[cfg(feature = "x86")]
     if !is_64bit {
-        // Note: For x86, we don't do any custom patching, but it's a great speedup if we can avoid re-encoding
-        // nonetheless.
-        for instruction in instructions {
+        // Note: For x86, branches are always reachable so we only patch the PC thunks used by
+        // position independent code, but it's a great speedup if we can avoid re-encoding nonetheless.
+        let mut x = 0;
+        while x < instructions.len() {
+            let instruction = &instructions[x];
+            let consumed = patch_pc_thunk(
+                &instructions[x..],
+                read_code,
+                &mut new_isns,
+                &mut current_new_pc,
+            )
+            .map_err(|e| add_instruction_context(e, instruction, start_ip, orig_ins_bytes))?;
+
+            if consumed > 0 {
+                for _ in 0..consumed {
+                    new_isns_starts.push(new_isns.len() - 1);
+                }
+
+                needs_rewriting = true;
+                x += consumed;
+                continue;
+            }
+
+            new_isns_starts.push(new_isns.len());
+            x += 1;
             if instruction.is_call_near()
                 || instruction.is_jmp_short_or_near()
                 || instruction.is_jcc_short_or_near()
@@ -127,27 +183,95 @@ pub(crate) fn relocate_code(
         return Ok(());
     }
 
-    let block = InstructionBlock::new(&new_isns, new_pc as u64);
-    let result = match BlockEncoder::encode(
-        if is_64bit & cfg!(feature = "x64") {
-            64
-        } else if cfg!(feature = "x86") {
-            32
-        } else {
-            0
-        },
-        block,
-        BlockEncoderOptions::NONE,
-    ) {
-        Err(err) => panic!("{}", err),
-        Ok(result) => result,
+    let bitness = if is_64bit & cfg!(feature = "x64") {
+        64
+    } else if cfg!(feature = "x86") {
+        32
+    } else {
+        0
     };
 
+    let block = InstructionBlock::new(&new_isns, new_pc as u64);
+    let result =
+        BlockEncoder::encode(bitness, block, BlockEncoderOptions::NONE).map_err(|err| {
+            find_unencodable_instruction(
+                bitness,
+                instructions,
+                &new_isns,
+                &new_isns_starts,
+                orig_ins_bytes,
+            )
+            .unwrap_or_else(|| {
+                CodeRewriterError::FailedToReencode(0, hex::encode(orig_ins_bytes), err.to_string())
+            })
+        })?;
+
     let new_code = result.code_buffer;
     buf.extend(new_code);
     Ok(())
 }
 
+/// Finds the original instruction whose rewritten form cannot be encoded, and
+/// returns an error describing it.
+///
+/// # Parameters
+/// `bitness`: Bitness of the code (32 or 64).
+/// `instructions`: The original instructions.
+/// `new_isns`: The rewritten instructions.
+/// `new_isns_starts`: Index of first instruction in `new_isns` emitted for each instruction in `instructions`.
+/// `orig_ins_bytes`: The original instruction bytes.
+fn find_unencodable_instruction(
+    bitness: u32,
+    instructions: &SmallVec<[Instruction; 4]>,
+    new_isns: &SmallVec<[Instruction; 4]>,
+    new_isns_starts: &SmallVec<[usize; 4]>,
+    orig_ins_bytes: &[u8],
+) -> Option<CodeRewriterError> {
+    let mut encoder = Encoder::try_new(bitness).ok()?;
+    let failed_idx = new_isns
+        .iter()
+        .position(|x| encoder.encode(x, x.ip()).is_err())?;
+
+    let failed = &new_isns[failed_idx];
+    let error = encoder.encode(failed, failed.ip()).err()?.to_string();
+    let orig_idx = new_isns_starts
+        .iter()
+        .rposition(|&start| start <= failed_idx)?;
+
+    Some(add_instruction_context(
+        CodeRewriterError::ThirdPartyAssemblerError(error),
+        &instructions[orig_idx],
+        instructions[0].ip(),
+        orig_ins_bytes,
+    ))
+}
+
+/// Attaches the offset and bytes of the original instruction to errors raised while rewriting it.
+///
+/// # Parameters
+/// `err`: The error raised while rewriting the instruction.
+/// `instruction`: The original instruction being rewritten.
+/// `start_ip`: Address of the first instruction in `orig_ins_bytes`.
+/// `orig_ins_bytes`: The original instruction bytes.
+pub(crate) fn add_instruction_context(
+    err: CodeRewriterError,
+    instruction: &Instruction,
+    start_ip: u64,
+    orig_ins_bytes: &[u8],
+) -> CodeRewriterError {
+    match err {
+        CodeRewriterError::ThirdPartyAssemblerError(reason) => {
+            let offset = instruction.ip().wrapping_sub(start_ip) as usize;
+            let bytes = orig_ins_bytes
+                .get(offset..offset.saturating_add(instruction.len()))
+                .map(hex::encode)
+                .unwrap_or_default();
+            CodeRewriterError::FailedToReencode(offset, bytes, reason)
+        }
+        other => other,
+    }
+}
+
 pub(crate) fn append_if_can_encode_relative(
     new_isns: &mut SmallVec<[Instruction; 4]>,
     current_new_pc: &mut usize,
@@ -195,6 +319,8 @@ mod tests {
     use crate::common::rewriter::code_rewriter::relocate_code;
     use crate::common::util::get_stolen_instructions::get_stolen_instructions;
     use crate::common::util::test_utilities::str_to_vec;
+    use iced_x86::{Decoder, DecoderOptions};
+    use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
     use rstest::rstest;
 
     #[rstest]
@@ -393,6 +519,16 @@ mod tests {
         0,
         "48b8120000000100000062f1ed485808"
     )] // vaddpd zmm1, zmm2, [rip + 8] -> mov rax, 0x100000012 + vaddpd zmm1, zmm2, [rax]
+    #[case::vaddpd_rhs_zmm_masked_bcst(
+        "62f1edd9580d08000000",
+        0x100000000,
+        0,
+        "48b8120000000100000062f1edd95808"
+    )]
+    // vaddpd zmm1 {k1}{z}, zmm2, [rip + 8]{1to8} -> mov rax, 0x100000012 + vaddpd zmm1 {k1}{z}, zmm2, [rax]{1to8}
+    #[case::vmovaps_lhs_ymm("c5fc290508000000", 0x100000000, 0, "48b81000000001000000c5fc2900")] // vmovaps [rip + 8], ymm0 -> mov rax, 0x100000010 + vmovaps [rax], ymm0
+    #[case::push("ff3508000000", 0x100000000, 0, "48b80e00000001000000ff30")] // push qword ptr [rip + 8] -> mov rax, 0x10000000e + push qword ptr [rax]
+    #[case::call("ff1508000000", 0x100000000, 0, "48b80e00000001000000ff10")] // call qword ptr [rip + 8] -> mov rax, 0x10000000e + call qword ptr [rax]
     fn relocate_64b_rip_rel(
         #[case] instructions: String,
         #[case] old_address: usize,
@@ -402,11 +538,224 @@ mod tests {
         relocate_64b(instructions, old_address, new_address, expected);
     }
 
+    // Out of range, spill a temporary register below the red zone.
+    #[rstest]
+    #[cfg(not(target_os = "windows"))]
+    #[case::mov_lhs(
+        "48891d08000000",
+        None,
+        "488d6424805048b80f0000000100000048891858488da42480000000"
+    )]
+    // mov [rip + 8], rbx -> lea rsp, [rsp - 0x80] + push rax + mov rax, 0x10000000f + mov [rax], rbx + pop rax + lea rsp, [rsp + 0x80]
+    #[case::mov_lhs_rax(
+        "48890508000000",
+        None,
+        "488d6424805148b90f0000000100000048890159488da42480000000"
+    )]
+    // mov [rip + 8], rax -> lea rsp, [rsp - 0x80] + push rcx + mov rcx, 0x10000000f + mov [rcx], rax + pop rcx + lea rsp, [rsp + 0x80]
+    #[case::cmpxchg_implicit_eax(
+        "0fb11d08000000",
+        None,
+        "488d6424805148b90f000000010000000fb11959488da42480000000"
+    )]
+    // cmpxchg [rip + 8], ebx -> lea rsp, [rsp - 0x80] + push rcx + mov rcx, 0x10000000f + cmpxchg [rcx], ebx + pop rcx + lea rsp, [rsp + 0x80]
+    #[case::inc(
+        "ff0508000000",
+        None,
+        "488d6424805048b80e00000001000000ff0058488da42480000000"
+    )]
+    // inc dword ptr [rip + 8] -> lea rsp, [rsp - 0x80] + push rax + mov rax, 0x10000000e + inc dword ptr [rax] + pop rax + lea rsp, [rsp + 0x80]
+    #[case::vmovaps_vex(
+        "c5fc280508000000",
+        None,
+        "488d6424805048b81000000001000000c5fc280058488da42480000000"
+    )]
+    // vmovaps ymm0, [rip + 8] -> lea rsp, [rsp - 0x80] + push rax + mov rax, 0x100000010 + vmovaps ymm0, [rax] + pop rax + lea rsp, [rsp + 0x80]
+    #[case::vaddpd_evex_masked_bcst(
+        "62f1edd9580d08000000",
+        None,
+        "488d6424805048b8120000000100000062f1edd9580858488da42480000000"
+    )]
+    // vaddpd zmm1 {k1}{z}, zmm2, [rip + 8]{1to8} -> lea rsp, [rsp - 0x80] + push rax + mov rax, 0x100000012 + vaddpd zmm1 {k1}{z}, zmm2, [rax]{1to8} + pop rax + lea rsp, [rsp + 0x80]
+    #[case::mov_lhs_rax_scratch(
+        "48890508000000",
+        Some(AllRegisters::rax),
+        "488d6424805148b90f0000000100000048890159488da42480000000"
+    )]
+    // mov [rip + 8], rax (scratch rax) -> lea rsp, [rsp - 0x80] + push rcx + mov rcx, 0x10000000f + mov [rcx], rax + pop rcx + lea rsp, [rsp + 0x80]
+    fn relocate_64b_rip_rel_spilled(
+        #[case] instructions: String,
+        #[case] scratch_gpr: Option<AllRegisters>,
+        #[case] expected: String,
+    ) {
+        relocate_64b_with_scratch(instructions, 0x100000000, 0, scratch_gpr, expected);
+    }
+
+    #[rstest]
+    #[case::lea_64("488d1d08000000", "48bb0f00000001000000")]
+    // lea rbx, [rip + 8] -> mov rbx, 0x10000000f
+
+    // Stack operations go through rax and movabs, branches through r11.
+    #[case::push("ff3508000000", "5048a10e0000000100000048870424")]
+    // push qword ptr [rip + 8] -> push rax + movabs rax, [0x10000000e] + xchg [rsp], rax
+    #[case::pop("8f0508000000", "4887042448a30e0000000100000058")]
+    // pop qword ptr [rip + 8] -> xchg [rsp], rax + movabs [0x10000000e], rax + pop rax
+    #[case::jmp("ff2508000000", "49bb0e0000000100000041ff23")]
+    // jmp qword ptr [rip + 8] -> mov r11, 0x10000000e + jmp [r11]
+    #[case::call("ff1508000000", "49bb0e0000000100000041ff13")]
+    // call qword ptr [rip + 8] -> mov r11, 0x10000000e + call [r11]
+    fn relocate_64b_rip_rel_without_scratch(
+        #[case] instructions: String,
+        #[case] expected: String,
+    ) {
+        relocate_64b_with_scratch(instructions, 0x100000000, 0, None, expected);
+    }
+
+    // Instruction classes which iced reports as RIP relative; each must relocate out of range,
+    // with or without a scratch register, and leave no RIP relative operands behind.
+    #[rstest]
+    #[cfg(not(target_os = "windows"))]
+    fn relocate_64b_rip_rel_classes(
+        #[values(
+            "488b1d08000000",       // mov rbx, [rip + 8]
+            "48890508000000",       // mov [rip + 8], rax
+            "c7050800000001000000", // mov dword ptr [rip + 8], 1
+            "f20f38f11d08000000",   // crc32 ebx, dword ptr [rip + 8]
+            "0fa41d0800000005",     // shld [rip + 8], ebx, 5
+            "488d0508000000",       // lea rax, [rip + 8]
+            "f00fb11d08000000",     // lock cmpxchg [rip + 8], ebx
+            "660f580d08000000",     // addpd xmm1, [rip + 8]
+            "c5fc280508000000",     // vmovaps ymm0, [rip + 8]
+            "c4e37d180d0800000001", // vinsertf128 ymm1, ymm0, [rip + 8], 1
+            "c4e3694a0d0800000030", // vblendvps xmm1, xmm2, [rip + 8], xmm3
+            "62f1edd9580d08000000", // vaddpd zmm1 {k1}{z}, zmm2, [rip + 8]{1to8}
+            "62f17c48290508000000", // vmovaps [rip + 8], zmm0
+            "ff3508000000",         // push qword ptr [rip + 8]
+            "8f0508000000",         // pop qword ptr [rip + 8]
+            "ff2508000000",         // jmp qword ptr [rip + 8]
+            "ff1508000000"          // call qword ptr [rip + 8]
+        )]
+        instructions: &str,
+        #[values(None, Some(AllRegisters::rax))] scratch_gpr: Option<AllRegisters>,
+    ) {
+        let hex_bytes = str_to_vec(instructions.to_string());
+        let instructions =
+            get_stolen_instructions(true, hex_bytes.len(), &hex_bytes, 0x100000000).unwrap();
+        let mut result = Vec::new();
+        relocate_code(
+            true,
+            &instructions.0,
+            &hex_bytes,
+            0,
+            scratch_gpr,
+            None,
+            &mut result,
+        )
+        .unwrap();
+
+        let mut decoder = Decoder::new(64, &result, DecoderOptions::NONE);
+        for instruction in &mut decoder {
+            assert!(!instruction.is_invalid(), "{}", hex::encode(&result));
+            assert!(
+                !instruction.is_ip_rel_memory_operand(),
+                "{}",
+                hex::encode(&result)
+            );
+        }
+    }
+
+    #[rstest]
+    #[case::add_rsp("48032508000000")] // add rsp, [rip + 8]
+    #[case::mov_rsp("488b2508000000")] // mov rsp, [rip + 8]
+    #[case::push_word("66ff3508000000")] // push word ptr [rip + 8]
+    fn relocate_64b_rip_rel_uses_stack_pointer(#[case] instructions: &str) {
+        let hex_bytes = str_to_vec(instructions.to_string());
+        let instructions =
+            get_stolen_instructions(true, hex_bytes.len(), &hex_bytes, 0x100000000).unwrap();
+        let mut result = Vec::new();
+        let err = relocate_code(
+            true,
+            &instructions.0,
+            &hex_bytes,
+            0,
+            None,
+            None,
+            &mut result,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, CodeRewriterError::NoScratchRegister(_)));
+    }
+
+    // Intel APX instructions are copied as is; only the displacement of RIP relative ones is patched.
+    #[rstest]
+    #[case::rex2("d51050d54889c0", 0x100000000, 0, "d51050d54889c0")] // push r16 + mov rax, r16
+    #[case::rex2_rip("d5488b0508000000", 0x100000000, 0x100001000, "d5488b0508f0ffff")] // mov r16, [rip + 8] -> mov r16, [rip - 0xff8]
+    #[case::rex2_rip_and_branch(
+        "d5488b0508000000eb02",
+        0x100000000,
+        0x100001000,
+        "d5488b0508f0ffffe9ffefffff"
+    )] // mov r16, [rip + 8] + jmp +2 -> mov r16, [rip - 0xff8] + jmp -0x1001
+    #[case::evex_rip(
+        "62ecfc08030508000000",
+        0x100000000,
+        0x100001000,
+        "62ecfc08030508f0ffff"
+    )] // add r16, [rip + 8] -> add r16, [rip - 0xff8]
+    fn relocate_64b_apx(
+        #[case] instructions: String,
+        #[case] old_address: usize,
+        #[case] new_address: usize,
+        #[case] expected: String,
+    ) {
+        relocate_64b(instructions, old_address, new_address, expected);
+    }
+
+    #[test]
+    fn relocate_64b_apx_rip_rel_out_of_range() {
+        let hex_bytes = str_to_vec("50d5488b0508000000".to_string()); // push rax + mov r16, [rip + 8]
+        let instructions =
+            get_stolen_instructions(true, hex_bytes.len(), &hex_bytes, 0x100000000).unwrap();
+        let mut result = Vec::new();
+        let err = relocate_code(
+            true,
+            &instructions.0,
+            &hex_bytes,
+            0,
+            Some(AllRegisters::rax),
+            None,
+            &mut result,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            CodeRewriterError::FailedToReencode(1, ref bytes, _) if bytes == "d5488b0508000000"
+        ));
+    }
+
     fn relocate_64b(
         instructions: String,
         old_address: usize,
         new_address: usize,
         expected: String,
+    ) {
+        relocate_64b_with_scratch(
+            instructions,
+            old_address,
+            new_address,
+            Some(AllRegisters::rax),
+            expected,
+        );
+    }
+
+    fn relocate_64b_with_scratch(
+        instructions: String,
+        old_address: usize,
+        new_address: usize,
+        scratch_gpr: Option<AllRegisters>,
+        expected: String,
     ) {
         // Remove spaces and convert the string to a vector of bytes
         let hex_bytes: Vec<u8> = str_to_vec(instructions);
@@ -418,7 +767,8 @@ mod tests {
             &instructions.0,
             &hex_bytes,
             new_address,
-            Some(AllRegisters::rax),
+            scratch_gpr,
+            None,
             &mut result,
         )
         .unwrap();
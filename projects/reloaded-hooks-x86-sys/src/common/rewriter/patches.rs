@@ -8,10 +8,41 @@ use crate::common::util::iced_extensions::is_immediate;
 use crate::common::util::invert_branch_condition::invert_branch_condition;
 use crate::{all_registers::AllRegisters, common::util::get_instruction_length};
 use alloc::string::ToString;
-use iced_x86::{Code, Instruction, MemoryOperand, OpKind};
+use iced_x86::{
+    Code, Encoder, EncodingKind, IcedError, Instruction, InstructionInfoFactory, MemoryOperand,
+    OpAccess, OpKind, Register,
+};
 use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
 use smallvec::SmallVec;
 
+/// Size of the area below the stack pointer which may hold data without adjusting the stack pointer.
+/// The System V ABI guarantees 128 bytes, Microsoft x64 has no red zone.
+pub(crate) const RED_ZONE_SIZE: i32 = if cfg!(target_os = "windows") { 0 } else { 128 };
+
+/// Register clobbered by out of range RIP relative `jmp` and `call` when no scratch register is available.
+/// `r11` is volatile and not used for passing arguments in both the System V and Microsoft x64 ABIs,
+/// so it holds nothing the branch target may rely on.
+const BRANCH_REGISTER: Register = Register::R11;
+
+/// Registers which may be borrowed (spilled) when no scratch register is available, in order of preference.
+const SPILL_REGISTERS: [Register; 15] = [
+    Register::RAX,
+    Register::RCX,
+    Register::RDX,
+    Register::RBX,
+    Register::RSI,
+    Register::RDI,
+    Register::R8,
+    Register::R9,
+    Register::R10,
+    Register::R11,
+    Register::R12,
+    Register::R13,
+    Register::R14,
+    Register::R15,
+    Register::RBP,
+];
+
 pub(crate) fn patch_relative_branch(
     scratch_gpr: Option<AllRegisters>,
     new_isns: &mut SmallVec<[Instruction; 4]>,
@@ -27,7 +58,7 @@ pub(crate) fn patch_relative_branch(
     let scratch_reg = scratch_gpr
         .ok_or_else(|| CodeRewriterError::NoScratchRegister(Default::default()))?
         .as_iced_allregister()
-        .unwrap();
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
     let mut mov_ins = Instruction::with2(Code::Mov_r64_imm64, scratch_reg, target)
         .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
     mov_ins.set_len(10);
@@ -85,7 +116,7 @@ pub(crate) fn patch_jump_conditional(
     let scratch_reg = scratch_gpr
         .ok_or_else(|| CodeRewriterError::NoScratchRegister(Default::default()))?
         .as_iced_allregister()
-        .unwrap();
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
     let inverted_condition = invert_branch_condition(instruction.code())?;
 
     // 12 bytes for mov + branch
@@ -141,7 +172,7 @@ pub(crate) fn patch_loop(
     let scratch_reg = scratch_gpr
         .ok_or_else(|| CodeRewriterError::NoScratchRegister(Default::default()))?
         .as_iced_allregister()
-        .unwrap();
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
 
     let mut loop_over = Instruction::with_branch(instruction.code(), (*current_new_pc + 4) as u64)
         .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
@@ -225,7 +256,7 @@ pub(crate) fn patch_jcx(
     let scratch_reg = scratch_gpr
         .ok_or_else(|| CodeRewriterError::NoScratchRegister(Default::default()))?
         .as_iced_allregister()
-        .unwrap();
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
 
     let mut jcx_over = Instruction::with_branch(instruction.code(), (*current_new_pc + 4) as u64)
         .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
@@ -291,7 +322,7 @@ pub(crate) fn append_if_can_encode_relative_rip(
     // because Iced will handle it for us on re-encode.
     let target = instruction.memory_displacement64();
     let end_of_new_inst = *current_new_pc + instruction.len();
-    let delta = (target - end_of_new_inst as u64) as i64;
+    let delta = target.wrapping_sub(end_of_new_inst as u64) as i64;
     if (-0x80000000..=0x7FFFFFFF).contains(&delta) {
         append_instruction_with_new_pc(new_isns, current_new_pc, instruction);
         return true;
@@ -312,9 +343,16 @@ pub(crate) fn patch_rip_relative_operand(
 
     let target = instruction.memory_displacement64();
     let scratch_reg = scratch_gpr
-        .ok_or_else(|| CodeRewriterError::NoScratchRegister(Default::default()))?
-        .as_iced_allregister()
-        .unwrap();
+        .map(|x| x.as_iced_allregister())
+        .transpose()
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?
+        .filter(|x| !reads_register_operand(instruction, *x));
+
+    // No usable scratch register, so we borrow one via the stack.
+    let scratch_reg = match scratch_reg {
+        Some(x) => x,
+        None => return patch_rip_relative_operand_spilled(new_isns, cur_new_pc, instruction),
+    };
     let length = get_instruction_length(instruction.code());
 
     // For encoding, Iced allows us to specify immediates as 32-bit, even if they are imm8 etc. let's abuse this.
@@ -357,125 +395,182 @@ pub(crate) fn patch_rip_relative_operand(
         .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
     mov_address_ins.set_len(10);
 
+    // VEX/EVEX/XOP instructions may carry state outside of their operands (opmask, zeroing,
+    // broadcast, embedded rounding), which would be lost if we rebuilt them from operands.
+    if instruction.encoding() != EncodingKind::Legacy {
+        return append_patched_instruction(
+            new_isns,
+            cur_new_pc,
+            mov_address_ins,
+            with_memory_base(instruction, scratch_reg),
+            None,
+        );
+    }
+
     if instruction.op_count() == 2 {
         if instruction.op0_kind() == OpKind::Memory && instruction.op1_kind() == OpKind::Register {
             // patch_riprel_reg
-            let mut patched_ins = Instruction::with2(
+            let patched_ins = Instruction::with2(
                 instruction.code(),
                 MemoryOperand::with_base(scratch_reg),
                 instruction.op1_register(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
 
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         } else if instruction.op0_kind() == OpKind::Register
             && instruction.op1_kind() == OpKind::Memory
         {
             // patch_reg_riprel
-            let mut patched_ins = Instruction::with2(
+            let patched_ins = Instruction::with2(
                 instruction.code(),
                 instruction.op0_register(),
                 MemoryOperand::with_base(scratch_reg),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
 
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         } else if instruction.op0_kind() == OpKind::Memory && is_immediate(instruction.op1_kind()) {
             // patch_riprel_imm
-            let mut patched_ins = Instruction::with2(
+            let patched_ins = Instruction::with2(
                 instruction.code(),
                 MemoryOperand::with_base(scratch_reg),
                 instruction.immediate32(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
 
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         }
     } else if instruction.op_count() == 1 {
         // patch_riprel
-        let mut patched_ins =
+        let patched_ins =
             Instruction::with1(instruction.code(), MemoryOperand::with_base(scratch_reg))
                 .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-        patched_ins.set_len(length);
 
-        return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+        return append_patched_instruction(
+            new_isns,
+            cur_new_pc,
+            mov_address_ins,
+            patched_ins,
+            length,
+        );
     } else if instruction.op_count() == 3 {
         if instruction.op0_kind() == OpKind::Memory
             && instruction.op1_kind() == OpKind::Register
             && instruction.op2_kind() == OpKind::Register
         {
             // patch_riprel_reg_reg
-            let mut patched_ins = Instruction::with3(
+            let patched_ins = Instruction::with3(
                 instruction.code(),
                 MemoryOperand::with_base(scratch_reg),
                 instruction.op1_register(),
                 instruction.op2_register(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
 
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         } else if instruction.op0_kind() == OpKind::Memory
             && instruction.op1_kind() == OpKind::Register
             && is_immediate(instruction.op2_kind())
         {
             // patch_riprel_reg_imm
-            let mut patched_ins = Instruction::with3(
+            let patched_ins = Instruction::with3(
                 instruction.code(),
                 MemoryOperand::with_base(scratch_reg),
                 instruction.op1_register(),
                 instruction.immediate32(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
 
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         } else if instruction.op0_kind() == OpKind::Register
             && instruction.op1_kind() == OpKind::Register
             && instruction.op2_kind() == OpKind::Memory
         {
             // patch_reg_reg_riprel
-            let mut patched_ins = Instruction::with3(
+            let patched_ins = Instruction::with3(
                 instruction.code(),
                 instruction.op0_register(),
                 instruction.op1_register(),
                 MemoryOperand::with_base(scratch_reg),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
 
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         } else if instruction.op0_kind() == OpKind::Register
             && instruction.op1_kind() == OpKind::Memory
             && is_immediate(instruction.op2_kind())
         {
             // patch_reg_riprel_imm
-            let mut patched_ins = Instruction::with3(
+            let patched_ins = Instruction::with3(
                 instruction.code(),
                 instruction.op0_register(),
                 MemoryOperand::with_base(scratch_reg),
                 instruction.immediate32(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         } else if instruction.op0_kind() == OpKind::Register
             && instruction.op1_kind() == OpKind::Memory
             && instruction.op2_kind() == OpKind::Register
         {
             // patch_reg_riprel_reg
-            let mut patched_ins = Instruction::with3(
+            let patched_ins = Instruction::with3(
                 instruction.code(),
                 instruction.op0_register(),
                 MemoryOperand::with_base(scratch_reg),
                 instruction.op2_register(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         }
     } else if instruction.op_count() == 4 {
         if instruction.op0_kind() == OpKind::Register
@@ -484,7 +579,7 @@ pub(crate) fn patch_rip_relative_operand(
             && is_immediate(instruction.op3_kind())
         {
             // patch_reg_reg_riprel_imm
-            let mut patched_ins = Instruction::with4(
+            let patched_ins = Instruction::with4(
                 instruction.code(),
                 instruction.op0_register(),
                 instruction.op1_register(),
@@ -492,15 +587,20 @@ pub(crate) fn patch_rip_relative_operand(
                 instruction.immediate32(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         } else if instruction.op0_kind() == OpKind::Register
             && instruction.op1_kind() == OpKind::Register
             && instruction.op2_kind() == OpKind::Register
             && instruction.op3_kind() == OpKind::Memory
         {
             // patch_reg_reg_reg_riprel
-            let mut patched_ins = Instruction::with4(
+            let patched_ins = Instruction::with4(
                 instruction.code(),
                 instruction.op0_register(),
                 instruction.op1_register(),
@@ -508,8 +608,13 @@ pub(crate) fn patch_rip_relative_operand(
                 MemoryOperand::with_base(scratch_reg),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         }
     } else if instruction.op_count() == 5 {
         if instruction.op0_kind() == OpKind::Register
@@ -519,7 +624,7 @@ pub(crate) fn patch_rip_relative_operand(
             && is_immediate(instruction.op4_kind())
         {
             // patch_reg_reg_reg_riprel_imm
-            let mut patched_ins = Instruction::with5(
+            let patched_ins = Instruction::with5(
                 instruction.code(),
                 instruction.op0_register(),
                 instruction.op1_register(),
@@ -528,8 +633,13 @@ pub(crate) fn patch_rip_relative_operand(
                 instruction.immediate32(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         } else if instruction.op0_kind() == OpKind::Register
             && instruction.op1_kind() == OpKind::Register
             && instruction.op2_kind() == OpKind::Memory
@@ -537,7 +647,7 @@ pub(crate) fn patch_rip_relative_operand(
             && is_immediate(instruction.op4_kind())
         {
             // patch_reg_reg_riprel_reg_imm
-            let mut patched_ins = Instruction::with5(
+            let patched_ins = Instruction::with5(
                 instruction.code(),
                 instruction.op0_register(),
                 instruction.op1_register(),
@@ -546,12 +656,299 @@ pub(crate) fn patch_rip_relative_operand(
                 instruction.immediate32(),
             )
             .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
-            patched_ins.set_len(length);
-            return append_patched_instruction(new_isns, cur_new_pc, mov_address_ins, patched_ins);
+            return append_patched_instruction(
+                new_isns,
+                cur_new_pc,
+                mov_address_ins,
+                patched_ins,
+                length,
+            );
         }
     }
 
-    append_instruction_with_new_pc(new_isns, cur_new_pc, instruction);
+    // Operand layout not covered above, rebase the original instruction.
+    append_patched_instruction(
+        new_isns,
+        cur_new_pc,
+        mov_address_ins,
+        with_memory_base(instruction, scratch_reg),
+        None,
+    )
+}
+
+/// Patches a RIP relative operand which is out of range when no scratch register is available,
+/// or when the scratch register is read by the instruction itself.
+///
+/// A temporary register not used by the instruction is saved to the stack, loaded with the
+/// target address and restored afterwards. Before saving, the stack pointer is moved past the red
+/// zone ([`RED_ZONE_SIZE`]), as the function may be keeping data there.
+///
+/// ```text
+/// lea rsp, [rsp - RED_ZONE_SIZE]
+/// push temp
+/// mov temp, target
+/// <instruction with [temp]>
+/// pop temp
+/// lea rsp, [rsp + RED_ZONE_SIZE]
+/// ```
+///
+/// Instructions which operate on the stack (`push`, `pop`, `call`, `jmp`) are rewritten
+/// to use `rax` via `movabs` instead, see [`patch_rip_relative_stack_operand`].
+fn patch_rip_relative_operand_spilled(
+    new_isns: &mut SmallVec<[Instruction; 4]>,
+    cur_new_pc: &mut usize,
+    instruction: &Instruction,
+) -> Result<(), CodeRewriterError> {
+    let target = instruction.memory_displacement64();
+    if patch_rip_relative_stack_operand(new_isns, cur_new_pc, instruction)? {
+        return Ok(());
+    }
+
+    // lea reg, [rip + x] -> mov reg, target
+    if instruction.code() == Code::Lea_r64_m {
+        return append_encoded(
+            new_isns,
+            cur_new_pc,
+            Instruction::with2(Code::Mov_r64_imm64, instruction.op0_register(), target),
+        );
+    }
+
+    // Any other use of the stack pointer would observe our spill.
+    let mut info_factory = InstructionInfoFactory::new();
+    let used_registers = info_factory.info(instruction).used_registers();
+    let uses_register = |reg: Register| {
+        used_registers
+            .iter()
+            .any(|x| x.register().full_register() == reg)
+    };
+
+    if instruction.is_stack_instruction() || uses_register(Register::RSP) {
+        return Err(CodeRewriterError::NoScratchRegister(
+            "patch_rip_relative_operand (instruction uses stack pointer)".to_string(),
+        ));
+    }
+
+    let temp_reg = *SPILL_REGISTERS
+        .iter()
+        .find(|x| !uses_register(**x))
+        .ok_or_else(|| {
+            CodeRewriterError::NoScratchRegister(
+                "patch_rip_relative_operand (all registers in use)".to_string(),
+            )
+        })?;
+
+    if RED_ZONE_SIZE != 0 {
+        append_encoded(new_isns, cur_new_pc, lea_rsp(-RED_ZONE_SIZE))?;
+    }
+
+    append_encoded(
+        new_isns,
+        cur_new_pc,
+        Instruction::with1(Code::Push_r64, temp_reg),
+    )?;
+    append_encoded(
+        new_isns,
+        cur_new_pc,
+        Instruction::with2(Code::Mov_r64_imm64, temp_reg, target),
+    )?;
+    append_encoded(
+        new_isns,
+        cur_new_pc,
+        Ok(with_memory_base(instruction, temp_reg)),
+    )?;
+    append_encoded(
+        new_isns,
+        cur_new_pc,
+        Instruction::with1(Code::Pop_r64, temp_reg),
+    )?;
+
+    if RED_ZONE_SIZE != 0 {
+        append_encoded(new_isns, cur_new_pc, lea_rsp(RED_ZONE_SIZE))?;
+    }
+
+    Ok(())
+}
+
+/// Patches `push`, `pop`, `call` and `jmp` with an out of range RIP relative operand,
+/// without a scratch register.
+///
+/// These move the stack pointer, so we cannot spill around them. Instead `rax` is stashed in
+/// the stack slot the original instruction would use, and the target is accessed with `movabs`.
+/// Branches instead go through [`BRANCH_REGISTER`].
+///
+/// ```text
+/// push [rip + x]   ->  push rax; movabs rax, [target]; xchg [rsp], rax
+/// pop [rip + x]    ->  xchg [rsp], rax; movabs [target], rax; pop rax
+/// jmp [rip + x]    ->  mov r11, target; jmp [r11]
+/// call [rip + x]   ->  mov r11, target; call [r11]
+/// ```
+///
+/// Branches are never made via `ret`, as a `ret` without a matching `call` faults when the
+/// shadow stack (Intel CET) is enabled. Nor is the branch target read from below the stack
+/// pointer, as there is no red zone on Microsoft x64.
+///
+/// # Returns
+/// True if the instruction was patched, false if it is not one of the above.
+fn patch_rip_relative_stack_operand(
+    new_isns: &mut SmallVec<[Instruction; 4]>,
+    cur_new_pc: &mut usize,
+    instruction: &Instruction,
+) -> Result<bool, CodeRewriterError> {
+    let target = instruction.memory_displacement64();
+    let target_mem = MemoryOperand::with_displ(target, 8);
+    let stack_top = MemoryOperand::with_base(Register::RSP);
+
+    match instruction.code() {
+        Code::Push_rm64 => {
+            append_encoded(
+                new_isns,
+                cur_new_pc,
+                Instruction::with1(Code::Push_r64, Register::RAX),
+            )?;
+            append_encoded(
+                new_isns,
+                cur_new_pc,
+                Instruction::with2(Code::Mov_RAX_moffs64, Register::RAX, target_mem),
+            )?;
+            append_encoded(
+                new_isns,
+                cur_new_pc,
+                Instruction::with2(Code::Xchg_rm64_r64, stack_top, Register::RAX),
+            )?;
+        }
+        Code::Pop_rm64 => {
+            append_encoded(
+                new_isns,
+                cur_new_pc,
+                Instruction::with2(Code::Xchg_rm64_r64, stack_top, Register::RAX),
+            )?;
+            append_encoded(
+                new_isns,
+                cur_new_pc,
+                Instruction::with2(Code::Mov_moffs64_RAX, target_mem, Register::RAX),
+            )?;
+            append_encoded(
+                new_isns,
+                cur_new_pc,
+                Instruction::with1(Code::Pop_r64, Register::RAX),
+            )?;
+        }
+        Code::Jmp_rm64 | Code::Call_rm64 => {
+            append_encoded(
+                new_isns,
+                cur_new_pc,
+                Instruction::with2(Code::Mov_r64_imm64, BRANCH_REGISTER, target),
+            )?;
+            append_encoded(
+                new_isns,
+                cur_new_pc,
+                Instruction::with1(
+                    instruction.code(),
+                    MemoryOperand::with_base(BRANCH_REGISTER),
+                ),
+            )?;
+        }
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Patches the displacement of a RIP relative Intel APX instruction (see [`decode_apx_instruction`]).
+///
+/// The assembler can't encode these instructions, so the displacement is patched in the original
+/// bytes instead. Unlike [`patch_rip_relative_operand`], this fails if the target is out of range.
+///
+/// # Parameters
+/// `instruction`: The `db` pseudo instruction containing the APX instruction.
+/// `bytes`: The bytes of the instruction.
+/// `displacement_offset`: Offset of the 32-bit displacement in `bytes`.
+///
+/// [`decode_apx_instruction`]: crate::common::util::apx::decode_apx_instruction
+pub(crate) fn patch_apx_rip_relative_operand(
+    new_isns: &mut SmallVec<[Instruction; 4]>,
+    current_new_pc: &mut usize,
+    instruction: &Instruction,
+    mut bytes: [u8; 16],
+    displacement_offset: usize,
+) -> Result<(), CodeRewriterError> {
+    let length = instruction.len();
+    let displacement = &mut bytes[displacement_offset..displacement_offset + 4];
+    let old_displacement = i32::from_le_bytes([
+        displacement[0],
+        displacement[1],
+        displacement[2],
+        displacement[3],
+    ]);
+
+    let target = instruction
+        .next_ip()
+        .wrapping_add(old_displacement as i64 as u64);
+    let delta = target.wrapping_sub((*current_new_pc + length) as u64) as i64;
+    let new_displacement = i32::try_from(delta).map_err(|_| {
+        CodeRewriterError::ThirdPartyAssemblerError(
+            "RIP relative APX instruction cannot reach its target from the new address".to_string(),
+        )
+    })?;
+
+    displacement.copy_from_slice(&new_displacement.to_le_bytes());
+    let mut patched_ins = Instruction::with_declare_byte(&bytes[..length])
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
+    patched_ins.set_len(length);
+    append_instruction_with_new_pc(new_isns, current_new_pc, &patched_ins);
+    Ok(())
+}
+
+/// Checks if the instruction reads the given register through one of its explicit operands.
+///
+/// By contract, the scratch register is not used implicitly by the code being relocated,
+/// but it may still be passed in when the instruction e.g. stores it.
+fn reads_register_operand(instruction: &Instruction, register: Register) -> bool {
+    let mut info_factory = InstructionInfoFactory::new();
+    let info = info_factory.info(instruction);
+    (0..instruction.op_count()).any(|x| {
+        instruction.op_kind(x) == OpKind::Register
+            && instruction.op_register(x).full_register() == register.full_register()
+            && !matches!(
+                info.op_access(x),
+                OpAccess::Write | OpAccess::CondWrite | OpAccess::None
+            )
+    })
+}
+
+/// Copies the instruction, replacing its RIP relative memory operand with `[base]`.
+/// Unlike rebuilding the instruction from its operands, this preserves prefixes and EVEX state.
+fn with_memory_base(instruction: &Instruction, base: Register) -> Instruction {
+    let mut patched_ins = *instruction;
+    patched_ins.set_memory_base(base);
+    patched_ins.set_memory_displacement64(0);
+    patched_ins.set_memory_displ_size(0);
+    patched_ins
+}
+
+fn lea_rsp(offset: i32) -> Result<Instruction, IcedError> {
+    Instruction::with2(
+        Code::Lea_r64_m,
+        Register::RSP,
+        MemoryOperand::with_base_displ(Register::RSP, offset as i64),
+    )
+}
+
+/// Appends an instruction whose length is determined by encoding it at the current PC.
+fn append_encoded(
+    new_isns: &mut SmallVec<[Instruction; 4]>,
+    current_new_pc: &mut usize,
+    instruction: Result<Instruction, IcedError>,
+) -> Result<(), CodeRewriterError> {
+    let mut instruction =
+        instruction.map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
+    instruction.set_ip(*current_new_pc as u64);
+    let length = Encoder::new(64)
+        .encode(&instruction, *current_new_pc as u64)
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
+
+    instruction.set_len(length);
+    append_instruction_with_new_pc(new_isns, current_new_pc, &instruction);
     Ok(())
 }
 
@@ -559,8 +956,18 @@ fn append_patched_instruction(
     new_isns: &mut SmallVec<[Instruction; 4]>,
     current_new_pc: &mut usize,
     mov_address_ins: Instruction,
-    patched_ins: Instruction,
+    mut patched_ins: Instruction,
+    length: Option<usize>,
 ) -> Result<(), CodeRewriterError> {
+    // Instructions missing from the lookup table have their length determined by encoding them.
+    let length = match length {
+        Some(length) => length,
+        None => Encoder::new(64)
+            .encode(&patched_ins, 0)
+            .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?,
+    };
+
+    patched_ins.set_len(length);
     append_instruction_with_new_pc(new_isns, current_new_pc, &mov_address_ins);
     append_instruction_with_new_pc(new_isns, current_new_pc, &patched_ins);
     Ok(())
@@ -0,0 +1,178 @@
+extern crate alloc;
+
+use super::code_rewriter::{append_instruction_with_new_pc, ReadCodeFn};
+use alloc::string::ToString;
+use iced_x86::{Code, Decoder, DecoderOptions, Instruction, OpKind};
+use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
+use smallvec::SmallVec;
+
+/// Patches the 'get program counter' idioms used by 32-bit position independent code.
+///
+/// These idioms obtain the address of the next instruction (usually to calculate the address
+/// of the GOT), and would yield an address inside the relocated code if copied verbatim.
+///
+/// The following are handled:
+///
+/// ```text
+/// call $+5              ->  push <original return address>
+///
+/// call $+5              ->  mov reg, <original return address>
+/// pop reg
+///
+/// call __x86.get_pc_thunk.reg  ->  mov reg, <original return address>
+/// ```
+///
+/// # Parameters
+/// `instructions`: The remaining instructions to relocate, starting with the current instruction.
+/// `read_code`: Reads the code of a call target, used to detect PC thunks. Thunks are only detected if provided.
+/// `new_isns`: The rewritten instructions.
+/// `current_new_pc`: The current program counter of the rewritten code.
+///
+/// # Returns
+/// Number of instructions consumed from `instructions`, 0 if the current instruction is not a PC idiom.
+pub(crate) fn patch_pc_thunk(
+    instructions: &[Instruction],
+    read_code: Option<ReadCodeFn>,
+    new_isns: &mut SmallVec<[Instruction; 4]>,
+    current_new_pc: &mut usize,
+) -> Result<usize, CodeRewriterError> {
+    let instruction = &instructions[0];
+    if instruction.code() != Code::Call_rel32_32 {
+        return Ok(0);
+    }
+
+    let return_address = instruction.next_ip32();
+    let target = instruction.near_branch32();
+
+    // call $+5
+    if target == return_address {
+        if let Some(pop) = instructions.get(1) {
+            if pop.code() == Code::Pop_r32 && pop.op0_register() != iced_x86::Register::ESP {
+                append_mov_imm32(new_isns, current_new_pc, pop.op0_register(), return_address)?;
+                return Ok(2);
+            }
+        }
+
+        let mut push = Instruction::with1(Code::Pushd_imm32, return_address as i32)
+            .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
+        push.set_len(5);
+        append_instruction_with_new_pc(new_isns, current_new_pc, &push);
+        return Ok(1);
+    }
+
+    // call __x86.get_pc_thunk.reg
+    let thunk_reg = read_code
+        .and_then(|read| read(target as usize))
+        .and_then(|code| get_pc_thunk_register(&code, target));
+
+    match thunk_reg {
+        Some(reg) => {
+            append_mov_imm32(new_isns, current_new_pc, reg, return_address)?;
+            Ok(1)
+        }
+        None => Ok(0),
+    }
+}
+
+/// Checks if the given code is a PC thunk, i.e. `mov reg, [esp]` followed by `ret`.
+///
+/// # Returns
+/// The register the thunk loads the return address into, if the code is a PC thunk.
+fn get_pc_thunk_register(code: &[u8], address: u32) -> Option<iced_x86::Register> {
+    let mut decoder = Decoder::with_ip(32, code, address as u64, DecoderOptions::NONE);
+    let mov = decoder.decode();
+    let ret = decoder.decode();
+
+    let is_thunk = mov.code() == Code::Mov_r32_rm32
+        && mov.op1_kind() == OpKind::Memory
+        && mov.memory_base() == iced_x86::Register::ESP
+        && mov.memory_index() == iced_x86::Register::None
+        && mov.memory_displacement32() == 0
+        && mov.op0_register() != iced_x86::Register::ESP
+        && ret.code() == Code::Retnd;
+
+    is_thunk.then(|| mov.op0_register())
+}
+
+fn append_mov_imm32(
+    new_isns: &mut SmallVec<[Instruction; 4]>,
+    current_new_pc: &mut usize,
+    register: iced_x86::Register,
+    value: u32,
+) -> Result<(), CodeRewriterError> {
+    let mut mov = Instruction::with2(Code::Mov_r32_imm32, register, value)
+        .map_err(|x| CodeRewriterError::ThirdPartyAssemblerError(x.to_string()))?;
+    mov.set_len(5);
+    append_instruction_with_new_pc(new_isns, current_new_pc, &mov);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::rewriter::code_rewriter::{relocate_code, PC_THUNK_LEN};
+    use crate::common::util::get_stolen_instructions::get_stolen_instructions;
+    use crate::common::util::test_utilities::str_to_vec;
+    use rstest::rstest;
+
+    // Address of `__x86.get_pc_thunk.*` in our fake binary.
+    const THUNK_ADDRESS: usize = 0x8000;
+
+    fn read_thunk(thunk: &str) -> impl Fn(usize) -> Option<[u8; PC_THUNK_LEN]> + '_ {
+        move |address| {
+            if address != THUNK_ADDRESS {
+                return None;
+            }
+
+            str_to_vec(thunk.to_string()).try_into().ok()
+        }
+    }
+
+    fn relocate_32b(
+        instructions: &str,
+        thunk: &str,
+        old_address: usize,
+        new_address: usize,
+    ) -> String {
+        let hex_bytes = str_to_vec(instructions.to_string());
+        let instructions =
+            get_stolen_instructions(false, hex_bytes.len(), &hex_bytes, old_address).unwrap();
+        let read = read_thunk(thunk);
+        let mut result = Vec::new();
+        relocate_code(
+            false,
+            &instructions.0,
+            &hex_bytes,
+            new_address,
+            None,
+            Some(&read),
+            &mut result,
+        )
+        .unwrap();
+
+        hex::encode(result)
+    }
+
+    #[rstest]
+    // i686 GCC -fPIC prologue: push ebx + call __x86.get_pc_thunk.bx + add ebx, _GLOBAL_OFFSET_TABLE_
+    #[case::get_pc_thunk_bx("53e8fa6f000081c3f42f0000", "8b1c24c3", "53bb0610000081c3f42f0000")] // -> push ebx + mov ebx, 0x1006 + add ebx, 0x2ff4
+    #[case::get_pc_thunk_ax("e8fb6f0000", "8b0424c3", "b805100000")] // call __x86.get_pc_thunk.ax -> mov eax, 0x1005
+    #[case::get_pc_thunk_cx("e8fb6f0000", "8b0c24c3", "b905100000")] // call __x86.get_pc_thunk.cx -> mov ecx, 0x1005
+    #[case::get_pc_thunk_dx("e8fb6f0000", "8b1424c3", "ba05100000")] // call __x86.get_pc_thunk.dx -> mov edx, 0x1005
+    #[case::get_pc_thunk_si("e8fb6f0000", "8b3424c3", "be05100000")] // call __x86.get_pc_thunk.si -> mov esi, 0x1005
+    #[case::get_pc_thunk_di("e8fb6f0000", "8b3c24c3", "bf05100000")] // call __x86.get_pc_thunk.di -> mov edi, 0x1005
+    #[case::get_pc_thunk_bp("e8fb6f0000", "8b2c24c3", "bd05100000")] // call __x86.get_pc_thunk.bp -> mov ebp, 0x1005
+    #[case::not_a_thunk("e8fb6f0000", "8b4424c3", "e8fb7f0100")] // call 0x8000 (mov eax, [esp - 0x3d]) -> call 0x8000
+    #[case::not_a_thunk_no_ret("e8fb6f0000", "8b1c2490", "e8fb7f0100")]
+    // call 0x8000 (mov ebx, [esp] + nop) -> call 0x8000
+    // Older GCC / hand written PIC: call $+5 + pop reg
+    #[case::call_pop_ebx("e8000000005b", "", "bb05100000")] // call $+5 + pop ebx -> mov ebx, 0x1005
+    #[case::call_pop_ecx_pad("55e80000000059", "", "55b906100000")] // push ebp + call $+5 + pop ecx -> push ebp + mov ecx, 0x1006
+    #[case::call_pop_then_add("e8000000005b81c3f42f0000", "", "bb0510000081c3f42f0000")] // call $+5 + pop ebx + add ebx, 0x2ff4 -> mov ebx, 0x1005 + add ebx, 0x2ff4
+    #[case::call_without_pop("e800000000", "", "6805100000")] // call $+5 -> push 0x1005
+    fn relocate_pc_thunk(#[case] instructions: &str, #[case] thunk: &str, #[case] expected: &str) {
+        assert_eq!(
+            relocate_32b(instructions, thunk, 0x1000, 0xFFFF0000),
+            expected
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use iced_x86::{Decoder, DecoderOptions, Instruction};
+
+/// Maximum length of an x86 instruction.
+const MAX_INSTRUCTION_LENGTH: usize = 15;
+
+/// Legacy prefixes, which may precede a REX2 or EVEX prefix.
+const LEGACY_PREFIXES: [u8; 11] = [
+    0x26, 0x2E, 0x36, 0x3E, 0x64, 0x65, 0x66, 0x67, 0xF0, 0xF2, 0xF3,
+];
+
+/// REX2 prefix, followed by a payload byte of `[M0 R4 X4 B4 W R3 X3 B3]`.
+const REX2: u8 = 0xD5;
+const REX2_M0: u8 = 0b10000000;
+const REX2_W: u8 = 0b00001000;
+
+/// EVEX prefix, followed by 3 payload bytes.
+const EVEX: u8 = 0x62;
+
+/// EVEX opcode map of 'promoted' legacy instructions (new data destination, no flags, etc.).
+const EVEX_MAP_4: u8 = 4;
+
+/// An instruction using an Intel APX encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ApxInstruction {
+    /// Length of the instruction in bytes.
+    pub length: usize,
+
+    /// Offset of the 32-bit displacement of a `[rip + disp32]` operand, if the instruction has one.
+    pub rip_displacement_offset: Option<usize>,
+}
+
+/// Decodes an instruction using one of the Intel APX encodings, which the disassembler does not
+/// understand; that is, a legacy instruction with a REX2 prefix, or an EVEX encoded 'promoted'
+/// legacy instruction (map 4).
+///
+/// These instructions are not relative branches, so they only need to be rewritten if they
+/// access memory relative to RIP.
+///
+/// # Parameters
+/// `code`: The code, starting with the instruction to decode.
+///
+/// # Returns
+/// The decoded instruction, or [`None`] if the code does not start with a valid APX instruction.
+pub(crate) fn decode_apx_instruction(code: &[u8]) -> Option<ApxInstruction> {
+    let prefixes = code
+        .iter()
+        .take(MAX_INSTRUCTION_LENGTH)
+        .take_while(|x| LEGACY_PREFIXES.contains(x))
+        .count();
+
+    let result = match *code.get(prefixes)? {
+        REX2 => decode_rex2_instruction(code, prefixes)?,
+        EVEX => decode_evex_map4_instruction(code, prefixes)?,
+        _ => return None,
+    };
+
+    (result.length <= MAX_INSTRUCTION_LENGTH && result.length <= code.len()).then_some(result)
+}
+
+/// Decodes the Intel APX instruction which the decoder failed to decode, replacing it with a
+/// `db` pseudo instruction containing its bytes; then advances the decoder past it.
+///
+/// # Parameters
+/// `decoder`: The decoder which produced the invalid instruction.
+/// `code`: The code being decoded.
+/// `offset`: Offset of the invalid instruction in `code`.
+/// `instr`: The invalid instruction, replaced on success.
+///
+/// # Returns
+/// True if the instruction was an APX instruction.
+pub(crate) fn decode_apx_instruction_with_decoder(
+    decoder: &mut Decoder,
+    code: &[u8],
+    offset: usize,
+    instr: &mut Instruction,
+) -> bool {
+    // APX is only available in 64-bit mode.
+    if decoder.bitness() != 64 {
+        return false;
+    }
+
+    let Some(apx) = code.get(offset..).and_then(decode_apx_instruction) else {
+        return false;
+    };
+
+    let Ok(mut declared) = Instruction::with_declare_byte(&code[offset..offset + apx.length])
+    else {
+        return false;
+    };
+
+    let ip = instr.ip();
+    if decoder.set_position(offset + apx.length).is_err() {
+        return false;
+    }
+
+    // Length first, as the instruction stores its next IP.
+    declared.set_len(apx.length);
+    declared.set_ip(ip);
+    decoder.set_ip(ip + apx.length as u64);
+    *instr = declared;
+    true
+}
+
+/// Returns the bytes of an APX instruction stored in a `db` pseudo instruction by
+/// [`decode_apx_instruction_with_decoder`], and the offset of its RIP relative displacement
+/// (if any).
+pub(crate) fn get_apx_rip_relative_instruction(
+    instruction: &Instruction,
+) -> Option<([u8; 16], usize)> {
+    if instruction.code() != iced_x86::Code::DeclareByte {
+        return None;
+    }
+
+    let mut bytes = [0u8; 16];
+    let length = instruction.declare_data_len();
+    for (x, byte) in bytes[..length].iter_mut().enumerate() {
+        *byte = instruction.get_declare_byte_value(x);
+    }
+
+    let offset = decode_apx_instruction(&bytes[..length])?.rip_displacement_offset?;
+    Some((bytes, offset))
+}
+
+/// Decodes a legacy instruction with a REX2 prefix.
+///
+/// REX2 only extends the register fields of the REX prefix, so the length is that of the
+/// same instruction with an equivalent REX prefix; minus the `0F` escape byte, which REX2
+/// replaces with its `M0` bit.
+fn decode_rex2_instruction(code: &[u8], prefixes: usize) -> Option<ApxInstruction> {
+    let payload = *code.get(prefixes + 1)?;
+    let opcode = *code.get(prefixes + 2)?;
+    if !is_valid_rex2_opcode(payload, opcode) {
+        return None;
+    }
+
+    // Translate to: prefixes + REX + (0F) + remaining bytes.
+    let escape = (payload & REX2_M0 != 0) as usize;
+    let remaining = &code[prefixes + 2..code.len().min(prefixes + MAX_INSTRUCTION_LENGTH)];
+    let mut translated = [0u8; MAX_INSTRUCTION_LENGTH * 2];
+    translated[..prefixes].copy_from_slice(&code[..prefixes]);
+    translated[prefixes] = 0x40 | (payload & 0x0F); // REX.WRXB
+    translated[prefixes + 1] = 0x0F;
+    let start = prefixes + 1 + escape;
+    translated[start..start + remaining.len()].copy_from_slice(remaining);
+    let translated = &translated[..start + remaining.len()];
+
+    let mut decoder = Decoder::new(64, translated, DecoderOptions::NONE);
+    let instruction = decoder.decode();
+    if instruction.is_invalid() {
+        return None;
+    }
+
+    // REX2 is 1 byte longer than REX, but replaces the escape byte.
+    let length = instruction.len() + 1 - escape;
+    let rip_displacement_offset = instruction.is_ip_rel_memory_operand().then(|| {
+        decoder
+            .get_constant_offsets(&instruction)
+            .displacement_offset()
+            + 1
+            - escape
+    });
+
+    Some(ApxInstruction {
+        length,
+        rip_displacement_offset,
+    })
+}
+
+/// Returns false for opcodes which are not allowed after a REX2 prefix; mostly relative
+/// branches, and opcodes which select a different opcode map.
+fn is_valid_rex2_opcode(payload: u8, opcode: u8) -> bool {
+    if payload & REX2_M0 != 0 {
+        // 0F 38 / 0F 3A maps, jcc, etc.
+        return !matches!(opcode, 0x30..=0x3F | 0x77 | 0x80..=0x8F);
+    }
+
+    match opcode {
+        0xA1 => payload & REX2_W == 0, // jmpabs imm64
+        0x0F | 0x40..=0x4F | 0x70..=0x7F | 0xA0 | 0xA2 | 0xA3 | 0xE0..=0xE3 | 0xE8..=0xEB => false,
+        _ => true,
+    }
+}
+
+/// Decodes an EVEX encoded 'promoted' legacy instruction (map 4), e.g. `add r16, r17, r18`.
+///
+/// Unlike other EVEX instructions, these are 'legacy' instructions with a ModRM byte and legacy
+/// sized immediates, so their length is determined by hand.
+fn decode_evex_map4_instruction(code: &[u8], prefixes: usize) -> Option<ApxInstruction> {
+    let evex = code.get(prefixes..prefixes + 4)?;
+    if evex[1] & 0b111 != EVEX_MAP_4 {
+        return None;
+    }
+
+    let opcode = *code.get(prefixes + 4)?;
+    let modrm_offset = prefixes + 5;
+    let modrm = *code.get(modrm_offset)?;
+    let (modrm_length, rip_displacement) = get_modrm_length(&code[modrm_offset..])?;
+    let operand_size_16 = evex[2] & 0b11 == 0b01; // pp = 66
+    let length =
+        modrm_offset + modrm_length + get_map4_immediate_size(opcode, modrm, operand_size_16);
+
+    Some(ApxInstruction {
+        length,
+        rip_displacement_offset: rip_displacement.map(|x| modrm_offset + x),
+    })
+}
+
+/// Returns the length of a ModRM byte, and its SIB byte and displacement (64-bit mode),
+/// alongside the offset of the displacement if the operand is RIP relative.
+fn get_modrm_length(code: &[u8]) -> Option<(usize, Option<usize>)> {
+    let modrm = *code.first()?;
+    let mode = modrm >> 6;
+    let rm = modrm & 0b111;
+
+    let result = match (mode, rm) {
+        (0b11, _) => (1, None),
+        (0b00, 0b101) => (5, Some(1)), // [rip + disp32]
+        (_, 0b100) => {
+            let base = *code.get(1)? & 0b111;
+            match mode {
+                0b00 if base == 0b101 => (6, None), // [index * scale + disp32]
+                0b00 => (2, None),
+                0b01 => (3, None),
+                _ => (6, None),
+            }
+        }
+        (0b00, _) => (1, None),
+        (0b01, _) => (2, None),
+        _ => (5, None),
+    };
+
+    Some(result)
+}
+
+/// Returns the size of the immediate of a 'promoted' legacy instruction in EVEX map 4.
+fn get_map4_immediate_size(opcode: u8, modrm: u8, operand_size_16: bool) -> usize {
+    let imm_z = if operand_size_16 { 2 } else { 4 };
+    let reg = (modrm >> 3) & 0b111;
+    match opcode {
+        // alu r/m, imm8; shifts; imul r, r/m, imm8; shld/shrd r/m, r, imm8
+        0x80 | 0x83 | 0xC0 | 0xC1 | 0x6B | 0x24 | 0x2C => 1,
+        // alu r/m, imm16/32; imul r, r/m, imm16/32
+        0x81 | 0x69 => imm_z,
+        // test / ctest r/m, imm
+        0xF6 if reg <= 1 => 1,
+        0xF7 if reg <= 1 => imm_z,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::util::test_utilities::str_to_vec;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::push_r16("d51050", 3, None)]
+    #[case::mov_rax_r16("d54889c0", 4, None)]
+    #[case::mov_r16_rsp_disp8("d5488b442408", 6, None)]
+    #[case::mov_r16_imm64("d518b88877665544332211", 11, None)]
+    #[case::add_r16_imm32("d51881c0efbeadde", 8, None)]
+    #[case::mov_r16_rip("d5488b0510000000", 8, Some(4))]
+    #[case::cmp_rip_imm8("d508833d1000000005", 9, Some(4))]
+    #[case::map1_imul("d5c8afc0", 4, None)] // imul r16, rax (0F AF)
+    #[case::map1_rip("d5c8af0510000000", 8, Some(4))] // imul r16, [rip + 0x10]
+    #[case::prefixed("66d5108b00", 5, None)] // mov ax, [r16]
+    #[case::jmpabs("d500a18877665544332211", 11, None)]
+    #[case::evex_add_ndd("62ecfc10 01c8", 6, None)] // add r16, r17, r18 (ND)
+    #[case::evex_add_imm8("62fcfc10 83c005", 7, None)]
+    #[case::evex_add_imm32("62fcfc10 81c078563412", 10, None)]
+    #[case::evex_add_imm16("62fcfd10 81c03412", 8, None)]
+    #[case::evex_rip("62ecfc08 030510000000", 10, Some(6))]
+    #[case::evex_sib_disp32("62ecfc08 03042578563412", 11, None)]
+    #[case::evex_shld_imm8("62ecfc08 24c805", 7, None)]
+    fn decodes_apx_instructions(
+        #[case] code: &str,
+        #[case] length: usize,
+        #[case] rip_displacement_offset: Option<usize>,
+    ) {
+        let code = str_to_vec(code.replace(' ', ""));
+        assert_eq!(
+            decode_apx_instruction(&code),
+            Some(ApxInstruction {
+                length,
+                rip_displacement_offset
+            })
+        );
+    }
+
+    #[rstest]
+    #[case::legacy("4889c3")] // mov rbx, rax
+    #[case::rex2_jcc("d51074fe")] // REX2 does not allow relative branches
+    #[case::rex2_call("d510e800000000")]
+    #[case::rex2_map1_jcc("d590840000000000")]
+    #[case::rex2_truncated("d548")]
+    #[case::evex_avx512("62f17c4828c8")] // vmovaps zmm1, zmm0
+    #[case::evex_truncated("62ecfc10")]
+    fn rejects_non_apx_instructions(#[case] code: &str) {
+        assert_eq!(decode_apx_instruction(&str_to_vec(code.to_string())), None);
+    }
+}
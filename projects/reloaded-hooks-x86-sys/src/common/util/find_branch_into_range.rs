@@ -0,0 +1,96 @@
+use super::apx::decode_apx_instruction_with_decoder;
+use iced_x86::{Decoder, DecoderOptions, Instruction, OpKind};
+
+/// Linearly disassembles the provided code, looking for a direct branch (`jmp`, `jcc`, `call`,
+/// `loop`, `jcxz` etc.) whose target lies within the range `(start, end)`.
+///
+/// Disassembly stops at the first invalid (or truncated) instruction.
+/// Intel APX instructions are skipped, as they are never relative branches.
+///
+/// # Parameters
+/// `is_64bit`: Whether the code is 64bit or not.
+/// `code`: The code region to scan.
+/// `ip`: The instruction pointer corresponding to first instruction in 'code'.
+/// `start`: Start of the range, exclusive.
+/// `end`: End of the range, exclusive.
+///
+/// # Returns
+/// The `(branch_address, branch_target)` of the first branch into the range, if any.
+pub(crate) fn find_branch_into_range(
+    is_64bit: bool,
+    code: &[u8],
+    ip: usize,
+    start: usize,
+    end: usize,
+) -> Option<(usize, usize)> {
+    let mut decoder = Decoder::with_ip(
+        if is_64bit & cfg!(feature = "x64") {
+            64
+        } else if cfg!(feature = "x86") {
+            32
+        } else {
+            0
+        },
+        code,
+        ip as u64,
+        DecoderOptions::NONE,
+    );
+
+    let mut instr = Instruction::default();
+    while decoder.can_decode() {
+        let offset = decoder.position();
+        decoder.decode_out(&mut instr);
+        if instr.is_invalid() {
+            if decode_apx_instruction_with_decoder(&mut decoder, code, offset, &mut instr) {
+                continue;
+            }
+
+            break;
+        }
+
+        if !matches!(
+            instr.op0_kind(),
+            OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64
+        ) {
+            continue;
+        }
+
+        let target = instr.near_branch_target() as usize;
+        if target > start && target < end {
+            return Some((instr.ip() as usize, target));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_branch_into_range;
+    use crate::common::util::test_utilities::str_to_vec;
+    use rstest::rstest;
+
+    const ADDRESS: usize = 0x1000;
+
+    #[rstest]
+    #[case::none("4883ec10488b442408", 5, None)] // sub rsp, 16 + mov rax, [rsp+8]
+    #[case::jmp_to_start("4883ec1090ebf9", 4, None)] // sub rsp, 16 + nop + jmp 0x1000 (start is exclusive)
+    #[case::jmp_short_into("4883ec1090ebfb", 4, Some((0x1005, 0x1002)))] // sub rsp, 16 + nop + jmp 0x1002
+    #[case::jcc_near_to_start("4883ec100f85f6ffffff", 4, None)] // sub rsp, 16 + jne 0x1000 (start is exclusive)
+    #[case::jcc_near_inside("4883ec100f85f7ffffff", 4, Some((0x1004, 0x1001)))] // sub rsp, 16 + jne 0x1001
+    #[case::call_into("4883ec10e8f8ffffff", 4, Some((0x1004, 0x1001)))] // sub rsp, 16 + call 0x1001
+    #[case::loop_into("4883ec10e2fc", 4, Some((0x1004, 0x1002)))] // sub rsp, 16 + loop 0x1002
+    #[case::jmp_to_end("4883ec10ebfe", 4, None)] // sub rsp, 16 + jmp 0x1004 (end is exclusive)
+    #[case::jmp_indirect("4883ec10ff20", 4, None)] // sub rsp, 16 + jmp [rax]
+    #[case::stops_at_invalid("4883ec1006ebfb", 4, None)] // sub rsp, 16 + (invalid) + jmp 0x1002
+    #[case::skips_apx("d51050d54889c0ebf8", 4, Some((0x1007, 0x1001)))] // push r16 + mov rax, r16 + jmp 0x1001
+    fn find_branch_64(
+        #[case] instructions: &str,
+        #[case] stolen_length: usize,
+        #[case] expected: Option<(usize, usize)>,
+    ) {
+        let code = str_to_vec(instructions.to_string());
+        let result = find_branch_into_range(true, &code, ADDRESS, ADDRESS, ADDRESS + stolen_length);
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,160 @@
+use reloaded_hooks_portable::{
+    api::rewriter::foreign_hook::ForeignHook, helpers::read_code::read_code_as,
+};
+
+/// Recognises jumps placed at the start of a function by other hooking libraries.
+///
+/// - `jmp rel32` (MinHook, Detours, older Reloaded.Hooks)
+/// - `jmp rel8` to a `jmp rel32` (hot-patch hooks, which place the `jmp rel32` before the function)
+/// - `jmp [rip+0]` followed by the target address (x64; MinHook relay, frida-gum, Detours)
+/// - `push imm32; ret` (x86, older Reloaded.Hooks)
+/// - `push imm32; mov dword [rsp+4], imm32; ret` (x64, older Reloaded.Hooks)
+///
+/// # Parameters
+///
+/// - `is_64bit`: True if the code is x64 code, else x86.
+/// - `address`: Address of the code to check.
+///
+/// # Safety
+///
+/// `address` must point to readable code.
+pub(crate) unsafe fn find_foreign_hook(is_64bit: bool, address: usize) -> Option<ForeignHook> {
+    match read_code_as::<u8>(address) {
+        // jmp rel32
+        0xE9 => {
+            let offset = read_code_as::<i32>(address + 1).to_le() as isize;
+            Some(ForeignHook::new(
+                5,
+                branch_target(is_64bit, address + 5, offset),
+            ))
+        }
+
+        // jmp rel8; any other short jump is likely just the function's own code.
+        0xEB => {
+            let offset = read_code_as::<i8>(address + 1) as isize;
+            let target = branch_target(is_64bit, address + 2, offset);
+            (read_code_as::<u8>(target) == 0xE9).then(|| ForeignHook::new(2, target))
+        }
+
+        // jmp [rip+0]
+        0xFF if is_64bit && read_code_as::<[u8; 5]>(address + 1) == [0x25, 0, 0, 0, 0] => {
+            let target = read_code_as::<u64>(address + 6).to_le();
+            Some(ForeignHook::new(14, target as usize))
+        }
+
+        // push imm32
+        0x68 => {
+            let low = read_code_as::<u32>(address + 1).to_le();
+            match read_code_as::<u8>(address + 5) {
+                // ret; x64 sign extends the pushed value.
+                0xC3 if is_64bit => Some(ForeignHook::new(6, low as i32 as usize)),
+                0xC3 => Some(ForeignHook::new(6, low as usize)),
+
+                // mov dword [rsp+4], imm32; ret
+                0xC7 if is_64bit
+                    && read_code_as::<[u8; 3]>(address + 6) == [0x44, 0x24, 0x04]
+                    && read_code_as::<u8>(address + 13) == 0xC3 =>
+                {
+                    let high = read_code_as::<u32>(address + 9).to_le();
+                    Some(ForeignHook::new(
+                        14,
+                        ((high as u64) << 32 | low as u64) as usize,
+                    ))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Target of a relative branch, wrapping around the 32-bit address space for x86 code.
+fn branch_target(is_64bit: bool, next_instruction: usize, offset: isize) -> usize {
+    let target = next_instruction.wrapping_add_signed(offset);
+    if is_64bit {
+        target
+    } else {
+        target as u32 as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Pads the code, such that reading past the end of shorter code is fine.
+    fn padded(code: &str) -> Vec<u8> {
+        let mut code = hex::decode(code).unwrap();
+        code.resize(16, 0x90);
+        code
+    }
+
+    #[rstest]
+    #[case::jmp_rel32("e9fb0f0000", 5, 0x1000)]
+    #[case::jmp_rel32_backwards("e9fbefffff", 5, -0x1000)]
+    fn find_relative_foreign_hook(
+        #[case] code: &str,
+        #[case] length: usize,
+        #[case] offset: isize,
+    ) {
+        let code = padded(code);
+        let address = code.as_ptr() as usize;
+        assert_eq!(
+            unsafe { find_foreign_hook(true, address) },
+            Some(ForeignHook::new(
+                length,
+                address.wrapping_add_signed(offset)
+            ))
+        );
+    }
+
+    // The function starts at offset 5, after the hot-patch area.
+    #[rstest]
+    #[case::hot_patch("e9fb0f0000ebf9", Some(0))]
+    #[case::jmp_short_forward("9090909090eb0290904889c8", None)]
+    #[case::jmp_short_backward("9090909090ebf9", None)]
+    fn find_short_foreign_hook(#[case] code: &str, #[case] target_offset: Option<usize>) {
+        let code = padded(code);
+        let address = code.as_ptr() as usize;
+        assert_eq!(
+            unsafe { find_foreign_hook(true, address + 5) },
+            target_offset.map(|offset| ForeignHook::new(2, address + offset))
+        );
+    }
+
+    #[rstest]
+    #[case::jmp_rip(true, "ff25000000007856341212000000", Some((14, 0x1212345678)))]
+    #[case::jmp_rip_in_x86(false, "ff25000000007856341212000000", None)]
+    #[case::jmp_rip_with_offset(true, "ff2502000000", None)]
+    #[case::push_ret(false, "6878563412c3", Some((6, 0x12345678)))]
+    #[case::push_ret_sign_extended(true, "68785634f2c3", Some((6, 0xFFFFFFFFF2345678)))]
+    #[case::push_mov_ret(true, "6878563412c744240412000000c3", Some((14, 0x1212345678)))]
+    #[case::push_mov_ret_in_x86(false, "6878563412c744240412000000c3", None)]
+    #[case::push_without_ret(false, "687856341290", None)]
+    #[case::call_rel32(true, "e8fb0f0000", None)]
+    #[case::mov(true, "4889c8", None)]
+    fn find_absolute_foreign_hook(
+        #[case] is_64bit: bool,
+        #[case] code: &str,
+        #[case] expected: Option<(usize, usize)>,
+    ) {
+        let code = padded(code);
+        assert_eq!(
+            unsafe { find_foreign_hook(is_64bit, code.as_ptr() as usize) },
+            expected.map(|(length, target)| ForeignHook::new(length, target))
+        );
+    }
+
+    #[rstest]
+    #[case::x86_wraps(false, 0x10, -0x20, 0xFFFFFFF0)]
+    #[case::x64(true, 0x10, -0x20, usize::MAX - 0xF)]
+    fn branch_target_wraps(
+        #[case] is_64bit: bool,
+        #[case] next_instruction: usize,
+        #[case] offset: isize,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(branch_target(is_64bit, next_instruction, offset), expected);
+    }
+}
@@ -1,7 +1,12 @@
 use iced_x86::Code;
 
-pub(crate) fn get_instruction_length(code: Code) -> usize {
-    match code {
+/// Returns the length of the instruction with the given code after its RIP relative
+/// operand has been replaced with a base register, i.e. `[rip + disp32]` -> `[reg]`.
+///
+/// Returns [`None`] for instructions not in the table, in which case the length must be
+/// obtained by encoding the instruction.
+pub(crate) fn get_instruction_length(code: Code) -> Option<usize> {
+    Some(match code {
         /*
         // Human Verified Instructions //
 
@@ -3085,8 +3090,8 @@ pub(crate) fn get_instruction_length(code: Code) -> usize {
         Code::VEX_Vsm3rnds2_xmm_xmm_xmmm128_imm8 => 5,
         */
         // Alternate instructions (untested)
-        _ => panic!("Unknown instruction"),
-    }
+        _ => return None,
+    })
 }
 
 // Instructions for adding lengths manually
@@ -1,5 +1,6 @@
 extern crate alloc;
 
+use super::apx::decode_apx_instruction_with_decoder;
 use alloc::string::ToString;
 use iced_x86::{Decoder, DecoderOptions, Instruction};
 use reloaded_hooks_portable::api::rewriter::code_rewriter::CodeRewriterError;
@@ -67,7 +68,9 @@ pub(crate) fn get_stolen_instructions_from_decoder(
     while decoder.can_decode() {
         decoder.decode_out(&mut instr);
 
-        if instr.is_invalid() {
+        if instr.is_invalid()
+            && !decode_apx_instruction_with_decoder(decoder, code, total_bytes as usize, &mut instr)
+        {
             return Err(CodeRewriterError::FailedToDisasm(
                 total_bytes.to_string(),
                 hex::encode(&code[total_bytes as usize..]),
@@ -81,11 +84,10 @@ pub(crate) fn get_stolen_instructions_from_decoder(
         }
     }
 
-    if total_bytes < required_bytes {
+    if total_bytes < required_bytes || orig_instructions.is_empty() {
         return Err(CodeRewriterError::InsufficientBytes);
     }
 
-    debug_assert!(!orig_instructions.is_empty());
     Ok((orig_instructions, total_bytes))
 }
 
@@ -147,7 +149,9 @@ pub(crate) fn get_stolen_instructions_length_from_decoder(
     while decoder.can_decode() {
         decoder.decode_out(&mut instr);
 
-        if instr.is_invalid() {
+        if instr.is_invalid()
+            && !decode_apx_instruction_with_decoder(decoder, code, total_bytes as usize, &mut instr)
+        {
             return Err(CodeRewriterError::FailedToDisasm(
                 total_bytes.to_string(),
                 hex::encode(&code[total_bytes as usize..]),
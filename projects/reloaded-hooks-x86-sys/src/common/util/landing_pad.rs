@@ -0,0 +1,39 @@
+use reloaded_hooks_portable::helpers::read_code::read_code_as;
+
+/// `endbr64`; the landing pad for indirect branches in 64-bit code, when Intel CET (IBT) is enabled.
+pub(crate) const ENDBR64: [u8; 4] = [0xF3, 0x0F, 0x1E, 0xFA];
+
+/// `endbr32`; the landing pad for indirect branches in 32-bit code, when Intel CET (IBT) is enabled.
+pub(crate) const ENDBR32: [u8; 4] = [0xF3, 0x0F, 0x1E, 0xFB];
+
+/// Returns the length of `landing_pad` if the code at `address` starts with it, else 0.
+///
+/// # Safety
+///
+/// `address` must point to at least 4 bytes of readable code.
+pub(crate) unsafe fn get_landing_pad_length(address: usize, landing_pad: [u8; 4]) -> usize {
+    if read_code_as::<[u8; 4]>(address) == landing_pad {
+        landing_pad.len()
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::endbr64("f30f1efa", ENDBR64, 4)]
+    #[case::endbr32_in_64bit("f30f1efb", ENDBR64, 0)]
+    #[case::endbr32("f30f1efb", ENDBR32, 4)]
+    #[case::nop_endbr64("90f30f1efa", ENDBR64, 0)]
+    fn landing_pad_length(#[case] code: &str, #[case] pad: [u8; 4], #[case] expected: usize) {
+        let code = hex::decode(code).unwrap();
+        assert_eq!(
+            unsafe { get_landing_pad_length(code.as_ptr() as usize, pad) },
+            expected
+        );
+    }
+}
@@ -7,3 +7,59 @@ pub fn str_to_vec(hex: String) -> Vec<u8> {
         })
         .collect()
 }
+
+/// Hand picked instruction streams which are truncated, malformed or otherwise awkward to relocate.
+/// Used to ensure the code rewriters return errors instead of panicking.
+pub const MALFORMED_CODE_CORPUS: &[&str] = &[
+    "",                                 // empty
+    "48",                               // lone REX prefix
+    "0f",                               // lone escape byte
+    "66666666666666666666666666666690", // prefix spam (> 15 bytes)
+    "488b05080000",                     // mov rax, [rip + 8] (truncated disp32)
+    "e9fb0f00",                         // jmp rel32 (truncated)
+    "0f84fa0f",                         // jz rel32 (truncated)
+    "e2",                               // loop (missing rel8)
+    "c4",                               // VEX prefix with no payload
+    "62f1ed48",                         // EVEX prefix with no opcode
+    "62f1ed48580d080000",               // vaddpd zmm1, zmm2, [rip + 8] (truncated)
+    "ff3508000000",                     // push qword ptr [rip + 8]
+    "ff2508000000",                     // jmp qword ptr [rip + 8]
+    "ff1508000000",                     // call qword ptr [rip + 8]
+    "c5fc280508000000",                 // vmovaps ymm0, [rip + 8]
+    "0f0b",                             // ud2
+    "f0488b05080000",                   // lock mov rax, [rip + 8] (invalid lock, truncated)
+    "f0488b0508000000",                 // lock mov rax, [rip + 8] (invalid lock)
+    "8f",                               // pop r/m (missing modrm)
+    "ea0000000000",                     // jmp far (invalid in 64-bit)
+    "9a000000000000",                   // call far (invalid in 64-bit)
+    "d6",                               // salc (invalid in 64-bit)
+    "67e3fa",                           // jecxz -3 (address size override)
+    "66e8ffff",                         // call rel16
+    "66e9ffff",                         // jmp rel16
+    "c7f8ffffffff",                     // xbegin
+    "678b0508000000",                   // mov eax, [eip + 8]
+    "48a10000000000000080",             // movabs rax, [0x8000000000000000]
+];
+
+/// Generates a deterministic set of pseudo-random byte streams for fuzzing the code rewriters.
+///
+/// # Parameters
+/// `count`: Number of streams to generate.
+/// `max_len`: Maximum length of each stream.
+pub fn random_code_corpus(count: usize, max_len: usize) -> Vec<Vec<u8>> {
+    // xorshift64, fixed seed so failures are reproducible.
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    (0..count)
+        .map(|_| {
+            let len = (next() as usize % max_len) + 1;
+            (0..len).map(|_| next() as u8).collect()
+        })
+        .collect()
+}
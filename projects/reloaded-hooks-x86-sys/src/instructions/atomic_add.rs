@@ -0,0 +1,101 @@
+extern crate alloc;
+
+use crate::all_registers::AllRegisters;
+use crate::common::jit_common::{X86jitError, ARCH_NOT_SUPPORTED};
+use alloc::string::ToString;
+use iced_x86::code_asm::{dword_ptr, qword_ptr, AsmMemoryOperand, CodeAssembler};
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::AtomicAdd};
+
+pub(crate) fn encode_atomic_add(
+    a: &mut CodeAssembler,
+    x: &AtomicAdd<AllRegisters>,
+) -> Result<(), X86jitError<AllRegisters>> {
+    if a.bitness() == 32 && cfg!(feature = "x86") {
+        encode_locked_add(a, dword_ptr(x.address as u32), x.value)
+    } else if a.bitness() == 64 && cfg!(feature = "x64") {
+        #[cfg(feature = "x64")]
+        {
+            // Addresses in the lower 2GiB can be encoded directly.
+            if x.address <= i32::MAX as usize {
+                return encode_locked_add(a, qword_ptr(x.address as u64), x.value);
+            }
+
+            let scratch = x
+                .scratch
+                .borrow()
+                .iter()
+                .find(|reg| reg.is_64())
+                .copied()
+                .ok_or_else(|| JitError::NoScratchRegister("for AtomicAdd.".to_string()))?;
+
+            let scratch = scratch.as_iced_64()?;
+            a.mov(scratch, x.address as u64)?;
+            encode_locked_add(a, qword_ptr(scratch), x.value)?;
+        }
+
+        Ok(())
+    } else {
+        Err(JitError::ThirdPartyAssemblerError(ARCH_NOT_SUPPORTED.to_string()).into())
+    }
+}
+
+fn encode_locked_add(
+    a: &mut CodeAssembler,
+    ptr: AsmMemoryOperand,
+    value: i32,
+) -> Result<(), X86jitError<AllRegisters>> {
+    match value {
+        1 => a.lock().inc(ptr)?,
+        -1 => a.lock().dec(ptr)?,
+        _ => a.lock().add(ptr, value)?,
+    };
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::{
+        x64::{self, jit::JitX64},
+        x86::jit::JitX86,
+    };
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::{compiler::Jit, operation_aliases::*};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0x12345678, 1, "f0ff0578563412")]
+    #[case(0x12345678, -1, "f0ff0d78563412")]
+    #[case(0x12345678, 4, "f083057856341204")]
+    fn atomic_add_x86(#[case] address: usize, #[case] value: i32, #[case] expected_encoded: &str) {
+        let operations = vec![Op::AtomicAdd(AtomicAdd::with_address_and_value(
+            address, value,
+        ))];
+        let result = JitX86::compile(0, &operations);
+        assert!(result.is_ok());
+        assert_eq!(expected_encoded, hex::encode(result.unwrap()));
+    }
+
+    #[rstest]
+    #[case(0x12345678, 1, "f048ff042578563412")]
+    #[case(0x12345678, -1, "f048ff0c2578563412")]
+    #[case(0x7FFF12345678, 1, "49bb78563412ff7f0000f049ff03")]
+    #[case(0x7FFF12345678, -1, "49bb78563412ff7f0000f049ff0b")]
+    fn atomic_add_x64(#[case] address: usize, #[case] value: i32, #[case] expected_encoded: &str) {
+        let scratch = Rc::new(RefCell::new(vec![x64::Register::r11]));
+        let operations = vec![Op::AtomicAdd(AtomicAdd::new(address, value, scratch))];
+        let result = JitX64::compile(0, &operations);
+        assert!(result.is_ok());
+        assert_eq!(expected_encoded, hex::encode(result.unwrap()));
+    }
+
+    #[test]
+    fn atomic_add_x64_without_scratch() {
+        let operations = vec![Op::AtomicAdd(
+            AtomicAdd::<x64::Register>::with_address_and_value(0x7FFF12345678, 1),
+        )];
+        assert!(JitX64::compile(0, &operations).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+extern crate alloc;
+
+use crate::all_registers::AllRegisters;
+use crate::common::jit_common::{X86jitError, ARCH_NOT_SUPPORTED};
+use alloc::string::ToString;
+use iced_x86::code_asm::{byte_ptr, AsmMemoryOperand, CodeAssembler};
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::EnterGuard};
+
+/// Returns the memory operand for a flag at `tls_offset` from the thread pointer.
+///
+/// The thread pointer is in `gs` on x64 Windows and x86 Linux, and in `fs` on x64 Linux
+/// and x86 Windows.
+pub(crate) fn thread_local_flag(a: &CodeAssembler, tls_offset: isize) -> AsmMemoryOperand {
+    let is_windows = cfg!(target_os = "windows");
+    if a.bitness() == 64 {
+        let ptr = byte_ptr(tls_offset as i64 as u64);
+        if is_windows {
+            ptr.gs()
+        } else {
+            ptr.fs()
+        }
+    } else {
+        let ptr = byte_ptr(tls_offset as i32 as u32);
+        if is_windows {
+            ptr.fs()
+        } else {
+            ptr.gs()
+        }
+    }
+}
+
+pub(crate) fn encode_enter_guard(
+    a: &mut CodeAssembler,
+    x: &EnterGuard<AllRegisters>,
+    address: usize,
+) -> Result<(), X86jitError<AllRegisters>> {
+    let flag = thread_local_flag(a, x.tls_offset);
+    a.cmp(flag, 0)?;
+
+    if a.bitness() == 32 && cfg!(feature = "x86") {
+        a.jne(x.bypass_address as u64)?;
+    } else if a.bitness() == 64 && cfg!(feature = "x64") {
+        #[cfg(feature = "x64")]
+        {
+            let isns = a.instructions();
+            let current_ip = if !isns.is_empty() {
+                isns.last().unwrap().next_ip()
+            } else {
+                address as u64
+            };
+
+            // Leave some room for the length of the instructions emitted before the jump.
+            let distance = (x.bypass_address as i64).wrapping_sub(current_ip as i64);
+            if distance.unsigned_abs() < (i32::MAX as u64 - 32) {
+                a.jne(x.bypass_address as u64)?;
+            } else {
+                let scratch = x
+                    .scratch
+                    .borrow()
+                    .iter()
+                    .find(|reg| reg.is_64())
+                    .copied()
+                    .ok_or_else(|| JitError::NoScratchRegister("for EnterGuard.".to_string()))?;
+
+                let scratch = scratch.as_iced_64()?;
+                let mut enter = a.create_label();
+                a.je(enter)?;
+                a.mov(scratch, x.bypass_address as u64)?;
+                a.jmp(scratch)?;
+                a.set_label(&mut enter)?;
+            }
+        }
+    } else {
+        return Err(JitError::ThirdPartyAssemblerError(ARCH_NOT_SUPPORTED.to_string()).into());
+    }
+
+    a.mov(flag, 1)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::{
+        x64::{self, jit::JitX64},
+        x86::jit::JitX86,
+    };
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::{compiler::Jit, operation_aliases::*};
+    use rstest::rstest;
+
+    // Segment override prefix used for the thread pointer.
+    const TLS_X86: &str = if cfg!(target_os = "windows") {
+        "64"
+    } else {
+        "65"
+    };
+    const TLS_X64: &str = if cfg!(target_os = "windows") {
+        "65"
+    } else {
+        "64"
+    };
+
+    #[rstest]
+    // cmp byte ptr [0xE10], 0; jne 0x12345678; mov byte ptr [0xE10], 1
+    #[case(0xE10, 0x12345678, "803d100e0000000f856a563412", "c605100e000001")]
+    fn enter_guard_x86(
+        #[case] tls_offset: isize,
+        #[case] bypass_address: usize,
+        #[case] expected_cmp: &str,
+        #[case] expected_mov: &str,
+    ) {
+        let operations = vec![Op::EnterGuard(EnterGuard::with_offset_and_bypass(
+            tls_offset,
+            bypass_address,
+        ))];
+        let result = JitX86::compile(0, &operations);
+        assert!(result.is_ok());
+
+        let expected = format!("{}{}{}{}", TLS_X86, expected_cmp, TLS_X86, expected_mov);
+        assert_eq!(expected, hex::encode(result.unwrap()));
+    }
+
+    #[rstest]
+    // cmp byte ptr [-64], 0; jne 0x1000; mov byte ptr [-64], 1
+    #[case(-64, 0x1000, "803c25c0ffffff000f85f10f0000", "c60425c0ffffff01")]
+    fn enter_guard_x64(
+        #[case] tls_offset: isize,
+        #[case] bypass_address: usize,
+        #[case] expected_cmp: &str,
+        #[case] expected_mov: &str,
+    ) {
+        let operations = vec![Op::EnterGuard(EnterGuard::with_offset_and_bypass(
+            tls_offset,
+            bypass_address,
+        ))];
+        let result = JitX64::compile(0, &operations);
+        assert!(result.is_ok());
+
+        let expected = format!("{}{}{}{}", TLS_X64, expected_cmp, TLS_X64, expected_mov);
+        assert_eq!(expected, hex::encode(result.unwrap()));
+    }
+
+    #[test]
+    fn enter_guard_x64_far_bypass() {
+        // cmp byte ptr [-64], 0; je enter; mov r11, 0x7FFF12345678; jmp r11; enter: mov byte ptr [-64], 1
+        let scratch = Rc::new(RefCell::new(vec![x64::Register::r11]));
+        let operations = vec![Op::EnterGuard(EnterGuard::new(
+            -64,
+            0x7FFF12345678,
+            scratch,
+        ))];
+        let result = JitX64::compile(0, &operations);
+        assert!(result.is_ok());
+
+        let expected = format!(
+            "{}803c25c0ffffff00740d49bb78563412ff7f000041ffe3{}c60425c0ffffff01",
+            TLS_X64, TLS_X64
+        );
+        assert_eq!(expected, hex::encode(result.unwrap()));
+    }
+
+    #[test]
+    fn enter_guard_x64_far_bypass_without_scratch() {
+        let operations = vec![Op::EnterGuard(
+            EnterGuard::<x64::Register>::with_offset_and_bypass(-64, 0x7FFF12345678),
+        )];
+        assert!(JitX64::compile(0, &operations).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use super::enter_guard::thread_local_flag;
+use crate::{all_registers::AllRegisters, common::jit_common::X86jitError};
+use iced_x86::code_asm::CodeAssembler;
+use reloaded_hooks_portable::api::jit::operation_aliases::ExitGuard;
+
+pub(crate) fn encode_exit_guard(
+    a: &mut CodeAssembler,
+    x: &ExitGuard<AllRegisters>,
+) -> Result<(), X86jitError<AllRegisters>> {
+    let flag = thread_local_flag(a, x.tls_offset);
+    a.mov(flag, 0)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::x64::jit::JitX64;
+    use crate::x86::jit::JitX86;
+    use reloaded_hooks_portable::api::jit::{compiler::Jit, operation_aliases::*};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0xE10, "c605100e000000")] // mov byte ptr [0xE10], 0
+    fn exit_guard_x86(#[case] tls_offset: isize, #[case] expected: &str) {
+        let segment = if cfg!(target_os = "windows") {
+            "64"
+        } else {
+            "65"
+        };
+        let operations = vec![Op::ExitGuard(ExitGuard::with_offset(tls_offset))];
+        let result = JitX86::compile(0, &operations);
+        assert!(result.is_ok());
+        assert_eq!(
+            format!("{}{}", segment, expected),
+            hex::encode(result.unwrap())
+        );
+    }
+
+    #[rstest]
+    #[case(-64, "c60425c0ffffff00")] // mov byte ptr [-64], 0
+    #[case(0x1480, "c604258014000000")] // mov byte ptr [0x1480], 0
+    fn exit_guard_x64(#[case] tls_offset: isize, #[case] expected: &str) {
+        let segment = if cfg!(target_os = "windows") {
+            "65"
+        } else {
+            "64"
+        };
+        let operations = vec![Op::ExitGuard(ExitGuard::with_offset(tls_offset))];
+        let result = JitX64::compile(0, &operations);
+        assert!(result.is_ok());
+        assert_eq!(
+            format!("{}{}", segment, expected),
+            hex::encode(result.unwrap())
+        );
+    }
+}
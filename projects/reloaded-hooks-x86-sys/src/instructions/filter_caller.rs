@@ -0,0 +1,171 @@
+extern crate alloc;
+
+use crate::all_registers::AllRegisters;
+use crate::common::jit_common::{X86jitError, ARCH_NOT_SUPPORTED};
+use alloc::string::ToString;
+use iced_x86::code_asm::{dword_ptr, qword_ptr, CodeAssembler};
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::FilterCaller};
+
+/// Walks the table of `start, end` pairs, jumping to the bypass address if the return address
+/// is not in any of them. Uses 2 scratch registers; for the table entry and the return address.
+pub(crate) fn encode_filter_caller(
+    a: &mut CodeAssembler,
+    x: &FilterCaller<AllRegisters>,
+    address: usize,
+) -> Result<(), X86jitError<AllRegisters>> {
+    let mut bypass = a.create_label();
+    let mut next = a.create_label();
+
+    if a.bitness() == 32 && cfg!(feature = "x86") {
+        let scratch = x.scratch.borrow();
+        let mut regs = scratch.iter().filter(|reg| reg.is_32());
+        let (entry, ret) = match (regs.next(), regs.next()) {
+            (Some(entry), Some(ret)) => (entry.as_iced_32()?, ret.as_iced_32()?),
+            _ => return Err(insufficient_scratch()),
+        };
+
+        a.mov(entry, dword_ptr(x.table_pointer as u32))?;
+        a.mov(ret, dword_ptr(iced_x86::Register::ESP))?;
+        a.jmp(next)?;
+
+        a.set_label(&mut bypass)?;
+        a.jmp(x.bypass_address as u64)?;
+
+        a.set_label(&mut next)?;
+        a.cmp(dword_ptr(entry + 4), 0)?;
+        a.je(bypass)?;
+        a.add(entry, 8)?;
+        a.cmp(ret, dword_ptr(entry - 8))?;
+        a.jb(next)?;
+        a.cmp(ret, dword_ptr(entry - 4))?;
+        a.jae(next)?;
+    } else if a.bitness() == 64 && cfg!(feature = "x64") {
+        #[cfg(feature = "x64")]
+        {
+            let scratch = x.scratch.borrow();
+            let mut regs = scratch.iter().filter(|reg| reg.is_64());
+            let (entry, ret) = match (regs.next(), regs.next()) {
+                (Some(entry), Some(ret)) => (entry.as_iced_64()?, ret.as_iced_64()?),
+                _ => return Err(insufficient_scratch()),
+            };
+
+            // Addresses in the lower 2GiB can be encoded directly.
+            if x.table_pointer <= i32::MAX as usize {
+                a.mov(entry, qword_ptr(x.table_pointer as u64))?;
+            } else {
+                a.mov(entry, x.table_pointer as u64)?;
+                a.mov(entry, qword_ptr(entry))?;
+            }
+
+            a.mov(ret, qword_ptr(iced_x86::Register::RSP))?;
+            a.jmp(next)?;
+
+            // Leave some room for the length of the instructions emitted before the jump.
+            let current_ip = a
+                .instructions()
+                .last()
+                .map(|ins| ins.next_ip())
+                .unwrap_or(address as u64);
+            let distance = (x.bypass_address as i64).wrapping_sub(current_ip as i64);
+
+            a.set_label(&mut bypass)?;
+            if distance.unsigned_abs() < (i32::MAX as u64 - 32) {
+                a.jmp(x.bypass_address as u64)?;
+            } else {
+                a.mov(entry, x.bypass_address as u64)?;
+                a.jmp(entry)?;
+            }
+
+            a.set_label(&mut next)?;
+            a.cmp(qword_ptr(entry + 8), 0)?;
+            a.je(bypass)?;
+            a.add(entry, 16)?;
+            a.cmp(ret, qword_ptr(entry - 16))?;
+            a.jb(next)?;
+            a.cmp(ret, qword_ptr(entry - 8))?;
+            a.jae(next)?;
+        }
+    } else {
+        return Err(JitError::ThirdPartyAssemblerError(ARCH_NOT_SUPPORTED.to_string()).into());
+    }
+
+    Ok(())
+}
+
+fn insufficient_scratch() -> X86jitError<AllRegisters> {
+    JitError::NoScratchRegister("for FilterCaller. 2 registers are required.".to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use crate::{
+        x64::{self, jit::JitX64},
+        x86::{self, jit::JitX86},
+    };
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+    use reloaded_hooks_portable::api::jit::{compiler::Jit, operation_aliases::*};
+    use rstest::rstest;
+
+    #[rstest]
+    // mov ecx, [0x2000]; mov edx, [esp]; jmp next; bypass: jmp 0x1000;
+    // next: cmp dword [ecx+4], 0; je bypass; add ecx, 8; cmp edx, [ecx-8]; jb next; cmp edx, [ecx-4]; jae next
+    #[case(
+        0x2000,
+        0x1000,
+        "8b0d002000008b1424eb05e9f00f00008379040074f583c1083b51f872f23b51fc73ed"
+    )]
+    fn filter_caller_x86(
+        #[case] table_pointer: usize,
+        #[case] bypass_address: usize,
+        #[case] expected: &str,
+    ) {
+        let scratch = Rc::new(RefCell::new(vec![x86::Register::ecx, x86::Register::edx]));
+        let operations = vec![Op::FilterCaller(FilterCaller::new(
+            table_pointer,
+            bypass_address,
+            scratch,
+        ))];
+        let result = JitX86::compile(0, &operations);
+        assert!(result.is_ok());
+        assert_eq!(expected, hex::encode(result.unwrap()));
+    }
+
+    #[rstest]
+    // mov rax, [0x2000]; mov rcx, [rsp]; jmp next; bypass: jmp 0x1000;
+    // next: cmp qword [rax+8], 0; je bypass; add rax, 16; cmp rcx, [rax-16]; jb next; cmp rcx, [rax-8]; jae next
+    #[case(
+        0x2000,
+        0x1000,
+        "48a10020000000000000488b0c24eb05e9eb0f0000488378080074f4480510000000483b48f072ed483b48f873e7"
+    )]
+    // mov rax, 0x7FFF00002000; mov rax, [rax]; mov rcx, [rsp]; jmp next; bypass: mov rax, 0x7FFF12345678; jmp rax; next: ...
+    #[case(
+        0x7FFF00002000,
+        0x7FFF12345678,
+        "48b800200000ff7f0000488b00488b0c24eb0c48b878563412ff7f0000ffe0488378080074ed480510000000483b48f072ed483b48f873e7"
+    )]
+    fn filter_caller_x64(
+        #[case] table_pointer: usize,
+        #[case] bypass_address: usize,
+        #[case] expected: &str,
+    ) {
+        let scratch = Rc::new(RefCell::new(vec![x64::Register::rax, x64::Register::rcx]));
+        let operations = vec![Op::FilterCaller(FilterCaller::new(
+            table_pointer,
+            bypass_address,
+            scratch,
+        ))];
+        let result = JitX64::compile(0, &operations);
+        assert!(result.is_ok());
+        assert_eq!(expected, hex::encode(result.unwrap()));
+    }
+
+    #[test]
+    fn filter_caller_x64_without_scratch() {
+        let scratch = Rc::new(RefCell::new(vec![x64::Register::rax, x64::Register::xmm0]));
+        let operations = vec![Op::FilterCaller(FilterCaller::new(0x2000, 0x1000, scratch))];
+        assert!(JitX64::compile(0, &operations).is_err());
+    }
+}
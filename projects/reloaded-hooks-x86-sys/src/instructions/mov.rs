@@ -2,10 +2,18 @@ use crate::{all_registers::AllRegisters, common::jit_common::X86jitError};
 use iced_x86::code_asm::CodeAssembler;
 use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::Mov};
 
+#[cfg(feature = "x64")]
+use crate::common::jit_instructions::helpers::{encode_rex2, opcode_offset_for_gpr64};
+
 pub(crate) fn encode_mov(
     a: &mut CodeAssembler,
     mov: &Mov<AllRegisters>,
 ) -> Result<(), X86jitError<AllRegisters>> {
+    #[cfg(feature = "x64")]
+    if mov.target.is_apx() || mov.source.is_apx() {
+        return encode_mov_apx(a, mov);
+    }
+
     if mov.target.is_32() && mov.source.is_32() {
         a.mov(mov.target.as_iced_32()?, mov.source.as_iced_32()?)
     } else if mov.target.is_64() && mov.source.is_64() && cfg!(feature = "x64") {
@@ -30,6 +38,26 @@ pub(crate) fn encode_mov(
     Ok(())
 }
 
+/// Encodes `mov r/m64, r64` with a REX2 prefix, for moves the assembler can't encode
+/// because they involve `r16` - `r31`.
+#[cfg(feature = "x64")]
+fn encode_mov_apx(
+    a: &mut CodeAssembler,
+    mov: &Mov<AllRegisters>,
+) -> Result<(), X86jitError<AllRegisters>> {
+    let is_gpr64 = |x: AllRegisters| x.is_64() || x.is_apx();
+    if !is_gpr64(mov.source) || !is_gpr64(mov.target) {
+        return Err(JitError::InvalidRegisterCombination(mov.source, mov.target).into());
+    }
+
+    let source = opcode_offset_for_gpr64(mov.source);
+    let target = opcode_offset_for_gpr64(mov.target);
+    let [rex2, payload] = encode_rex2(true, source, target);
+    let modrm = 0b11000000 | ((source % 8) << 3) | (target % 8);
+    a.db(&[rex2, payload, 0x89, modrm])?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -41,6 +69,10 @@ mod tests {
 
     #[rstest]
     #[case(x64::Register::rax, x64::Register::rbx, "4889c3")]
+    #[case(x64::Register::rax, x64::Register::r16, "d51889c0")]
+    #[case(x64::Register::r17, x64::Register::rbx, "d54889cb")]
+    #[case(x64::Register::r16, x64::Register::r31, "d55989c7")]
+    #[case(x64::Register::r8, x64::Register::r24, "d51d89c0")]
     #[case(x64::Register::xmm0, x64::Register::xmm1, "0f28c8")]
     #[case(x64::Register::ymm0, x64::Register::ymm1, "c5fc28c8")]
     #[case(x64::Register::zmm0, x64::Register::zmm1, "62f17c4828c8")]
@@ -55,6 +87,15 @@ mod tests {
         assert_eq!(expected_encoded, hex::encode(result.unwrap()));
     }
 
+    #[test]
+    fn mov_x64_apx_to_xmm_is_invalid() {
+        let operations = vec![Op::Mov(Mov {
+            source: x64::Register::r16,
+            target: x64::Register::xmm0,
+        })];
+        assert!(JitX64::compile(0, &operations).is_err());
+    }
+
     #[rstest]
     #[case(x86::Register::eax, x86::Register::ebx, "89c3")]
     #[case(x86::Register::xmm0, x86::Register::xmm1, "0f28c8")]
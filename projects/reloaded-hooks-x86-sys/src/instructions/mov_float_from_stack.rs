@@ -0,0 +1,94 @@
+extern crate alloc;
+
+use crate::all_registers::AllRegisters;
+use crate::common::jit_common::X86jitError;
+use alloc::string::ToString;
+use iced_x86::code_asm::{dword_ptr, qword_ptr, CodeAssembler};
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::MovFloatFromStack};
+
+pub(crate) fn encode_mov_float_from_stack(
+    a: &mut CodeAssembler,
+    x: &MovFloatFromStack<AllRegisters>,
+) -> Result<(), X86jitError<AllRegisters>> {
+    let sp = if a.bitness() == 64 {
+        iced_x86::Register::RSP
+    } else {
+        iced_x86::Register::ESP
+    };
+
+    match (x.target, x.size) {
+        // fld pushes onto the x87 stack, so the value always lands in st0.
+        (AllRegisters::st0, 4) => a.fld(dword_ptr(sp) + x.stack_offset),
+        (AllRegisters::st0, 8) => a.fld(qword_ptr(sp) + x.stack_offset),
+        (target, 4) if target.is_xmm() => {
+            a.movss(target.as_iced_xmm()?, dword_ptr(sp) + x.stack_offset)
+        }
+        (target, 8) if target.is_xmm() => {
+            a.movsd_2(target.as_iced_xmm()?, qword_ptr(sp) + x.stack_offset)
+        }
+        (AllRegisters::st0, _) | (_, 4) | (_, 8) => {
+            return Err(JitError::InvalidRegister(x.target).into())
+        }
+        _ => {
+            return Err(JitError::OperandOutOfRange(
+                "Only 4 and 8 byte floats can be moved from the stack.".to_string(),
+            )
+            .into())
+        }
+    }?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        x64::{self, jit::JitX64},
+        x86::{self, jit::JitX86},
+    };
+    use reloaded_hooks_portable::api::jit::{compiler::Jit, operation_aliases::*};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(x86::Register::st0, 4, "d9442404")]
+    #[case(x86::Register::st0, 8, "dd442404")]
+    #[case(x86::Register::xmm0, 4, "f30f10442404")]
+    #[case(x86::Register::xmm1, 8, "f20f104c2404")]
+    fn mov_float_from_stack_x86(
+        #[case] target: x86::Register,
+        #[case] size: u32,
+        #[case] expected_encoded: &str,
+    ) {
+        let operations = vec![Op::MovFloatFromStack(MovFloatFromStack::new(
+            4, size, target,
+        ))];
+        let result = JitX86::compile(0, &operations);
+        assert_eq!(expected_encoded, hex::encode(result.unwrap()));
+    }
+
+    #[rstest]
+    #[case(x64::Register::st0, 8, "dd442404")]
+    #[case(x64::Register::xmm0, 4, "f30f10442404")]
+    fn mov_float_from_stack_x64(
+        #[case] target: x64::Register,
+        #[case] size: u32,
+        #[case] expected_encoded: &str,
+    ) {
+        let operations = vec![Op::MovFloatFromStack(MovFloatFromStack::new(
+            4, size, target,
+        ))];
+        let result = JitX64::compile(0, &operations);
+        assert_eq!(expected_encoded, hex::encode(result.unwrap()));
+    }
+
+    #[rstest]
+    #[case(x86::Register::st1, 8)]
+    #[case(x86::Register::eax, 4)]
+    #[case(x86::Register::st0, 10)]
+    fn mov_float_from_stack_x86_invalid(#[case] target: x86::Register, #[case] size: u32) {
+        let operations = vec![Op::MovFloatFromStack(MovFloatFromStack::new(
+            4, size, target,
+        ))];
+        assert!(JitX86::compile(0, &operations).is_err());
+    }
+}
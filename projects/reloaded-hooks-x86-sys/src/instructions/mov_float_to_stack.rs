@@ -0,0 +1,88 @@
+extern crate alloc;
+
+use crate::all_registers::AllRegisters;
+use crate::common::jit_common::X86jitError;
+use alloc::string::ToString;
+use iced_x86::code_asm::{dword_ptr, qword_ptr, CodeAssembler};
+use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::MovFloatToStack};
+
+pub(crate) fn encode_mov_float_to_stack(
+    a: &mut CodeAssembler,
+    x: &MovFloatToStack<AllRegisters>,
+) -> Result<(), X86jitError<AllRegisters>> {
+    let sp = if a.bitness() == 64 {
+        iced_x86::Register::RSP
+    } else {
+        iced_x86::Register::ESP
+    };
+
+    match (x.register, x.size) {
+        // fstp pops the x87 stack, so st0 is consumed.
+        (AllRegisters::st0, 4) => a.fstp(dword_ptr(sp) + x.stack_offset),
+        (AllRegisters::st0, 8) => a.fstp(qword_ptr(sp) + x.stack_offset),
+        (register, 4) if register.is_xmm() => {
+            a.movss(dword_ptr(sp) + x.stack_offset, register.as_iced_xmm()?)
+        }
+        (register, 8) if register.is_xmm() => {
+            a.movsd_2(qword_ptr(sp) + x.stack_offset, register.as_iced_xmm()?)
+        }
+        (AllRegisters::st0, _) | (_, 4) | (_, 8) => {
+            return Err(JitError::InvalidRegister(x.register).into())
+        }
+        _ => {
+            return Err(JitError::OperandOutOfRange(
+                "Only 4 and 8 byte floats can be moved to the stack.".to_string(),
+            )
+            .into())
+        }
+    }?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        x64::{self, jit::JitX64},
+        x86::{self, jit::JitX86},
+    };
+    use reloaded_hooks_portable::api::jit::{compiler::Jit, operation_aliases::*};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(x86::Register::st0, 4, "d95c2404")]
+    #[case(x86::Register::st0, 8, "dd5c2404")]
+    #[case(x86::Register::xmm0, 4, "f30f11442404")]
+    #[case(x86::Register::xmm1, 8, "f20f114c2404")]
+    fn mov_float_to_stack_x86(
+        #[case] register: x86::Register,
+        #[case] size: u32,
+        #[case] expected_encoded: &str,
+    ) {
+        let operations = vec![Op::MovFloatToStack(MovFloatToStack::new(4, size, register))];
+        let result = JitX86::compile(0, &operations);
+        assert_eq!(expected_encoded, hex::encode(result.unwrap()));
+    }
+
+    #[rstest]
+    #[case(x64::Register::st0, 8, "dd5c2404")]
+    #[case(x64::Register::xmm0, 4, "f30f11442404")]
+    fn mov_float_to_stack_x64(
+        #[case] register: x64::Register,
+        #[case] size: u32,
+        #[case] expected_encoded: &str,
+    ) {
+        let operations = vec![Op::MovFloatToStack(MovFloatToStack::new(4, size, register))];
+        let result = JitX64::compile(0, &operations);
+        assert_eq!(expected_encoded, hex::encode(result.unwrap()));
+    }
+
+    #[rstest]
+    #[case(x86::Register::st1, 8)]
+    #[case(x86::Register::eax, 4)]
+    #[case(x86::Register::xmm0, 16)]
+    fn mov_float_to_stack_x86_invalid(#[case] register: x86::Register, #[case] size: u32) {
+        let operations = vec![Op::MovFloatToStack(MovFloatToStack::new(4, size, register))];
+        assert!(JitX86::compile(0, &operations).is_err());
+    }
+}
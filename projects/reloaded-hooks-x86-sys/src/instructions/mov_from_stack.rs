@@ -6,6 +6,11 @@ use alloc::string::ToString;
 use iced_x86::code_asm::{dword_ptr, qword_ptr, CodeAssembler};
 use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::MovFromStack};
 
+#[cfg(feature = "x64")]
+use crate::common::jit_instructions::helpers::{
+    encode_rex2_stack_operand, opcode_offset_for_gpr64,
+};
+
 pub(crate) fn encode_mov_from_stack(
     a: &mut CodeAssembler,
     x: &MovFromStack<AllRegisters>,
@@ -32,6 +37,17 @@ pub(crate) fn encode_mov_from_stack(
         {
             Ok(())
         }
+    } else if x.target.is_apx() {
+        // mov r16-r31, [rsp + offset], with a REX2 prefix the assembler can't encode.
+        #[cfg(feature = "x64")]
+        {
+            let reg = opcode_offset_for_gpr64(x.target);
+            a.db(&encode_rex2_stack_operand(0x8B, reg, x.stack_offset))
+        }
+        #[cfg(not(feature = "x64"))]
+        {
+            Ok(())
+        }
     } else if x.target.is_xmm() {
         a.movups(x.target.as_iced_xmm()?, base_ptr)
     } else if x.target.is_ymm() {
@@ -73,6 +89,8 @@ mod tests {
 
     #[rstest]
     #[case(x64::Register::rax, "488b442404")]
+    #[case(x64::Register::r16, "d5488b442404")]
+    #[case(x64::Register::r31, "d54c8b7c2404")]
     #[case(x64::Register::xmm0, "0f10442404")]
     #[case(x64::Register::ymm0, "c5fc10442404")]
     #[case(x64::Register::zmm0, "62f17c4810842404000000")]
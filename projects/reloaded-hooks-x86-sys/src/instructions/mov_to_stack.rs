@@ -6,6 +6,11 @@ use iced_x86::code_asm::CodeAssembler;
 use reloaded_hooks_portable::api::jit::compiler::JitError;
 use reloaded_hooks_portable::api::jit::mov_to_stack_operation::MovToStackOperation;
 
+#[cfg(feature = "x64")]
+use crate::common::jit_instructions::helpers::{
+    encode_rex2_stack_operand, opcode_offset_for_gpr64,
+};
+
 pub(crate) fn encode_mov_to_stack(
     a: &mut CodeAssembler,
     x: &MovToStackOperation<AllRegisters>,
@@ -15,6 +20,13 @@ pub(crate) fn encode_mov_to_stack(
     } else if x.register.is_64() && cfg!(feature = "x64") {
         #[cfg(feature = "x64")]
         mov_item_to_stack!(a, x.register, x.stack_offset, as_iced_64, mov);
+    } else if x.register.is_apx() {
+        // mov [rsp + offset], r16-r31, with a REX2 prefix the assembler can't encode.
+        #[cfg(feature = "x64")]
+        {
+            let reg = opcode_offset_for_gpr64(x.register);
+            a.db(&encode_rex2_stack_operand(0x89, reg, x.stack_offset))?;
+        }
     } else if x.register.is_xmm() {
         mov_item_to_stack!(a, x.register, x.stack_offset, as_iced_xmm, movdqu);
     } else if x.register.is_ymm() {
@@ -38,6 +50,8 @@ mod tests {
 
     #[rstest]
     #[case::x64_64bit(x64::Register::rax, 16, "4889442410")]
+    #[case::x64_apx(x64::Register::r16, 16, "d54889442410")]
+    #[case::x64_apx_large_offset(x64::Register::r31, 0x100, "d54c89bc2400010000")]
     #[case::x64_xmm(x64::Register::xmm0, 16, "f30f7f442410")]
     fn test_encode_mov_to_stack_x64(
         #[case] register: x64::Register,
@@ -1,33 +1,20 @@
 extern crate alloc;
 
 use crate::all_registers::AllRegisters;
-use crate::common::jit_common::{convert_error, ARCH_NOT_SUPPORTED};
+use crate::common::jit_common::{X86jitError, ARCH_NOT_SUPPORTED};
+use crate::instructions::mov_from_stack::encode_mov_from_stack;
 use alloc::string::ToString;
-use iced_x86::code_asm::{dword_ptr, qword_ptr, registers as iced_regs, CodeAssembler};
-use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::Pop};
-
-macro_rules! multi_pop_item {
-    ($a:expr, $reg:expr, $offset:expr, $convert_method:ident, $op:ident) => {
-        match $a.bitness() {
-            #[cfg(feature = "x86")]
-            32 => {
-                $a.$op($reg.$convert_method()?, dword_ptr(iced_regs::esp) + $offset)
-                    .map_err(convert_error)?;
-            }
-            #[cfg(feature = "x64")]
-            64 => {
-                $a.$op($reg.$convert_method()?, qword_ptr(iced_regs::rsp) + $offset)
-                    .map_err(convert_error)?;
-            }
-            _ => {
-                return Err(JitError::ThirdPartyAssemblerError(
-                    ARCH_NOT_SUPPORTED.to_string(),
-                ));
-            }
-        }
-    };
-}
+use iced_x86::code_asm::{registers as iced_regs, CodeAssembler};
+use reloaded_hooks_portable::api::jit::compiler::JitError;
+use reloaded_hooks_portable::api::jit::operation_aliases::{MovFromStack, Pop};
 
+/// Pops multiple registers with a `mov` from each register's slot, followed by a single
+/// `add esp/rsp, N`; using unaligned vector moves for vector registers.
+///
+/// This is the inverse of [`encode_multi_push`], i.e. the first register is popped from the
+/// top of the stack.
+///
+/// [`encode_multi_push`]: crate::instructions::multi_push::encode_multi_push
 pub(crate) fn encode_multi_pop(
     a: &mut CodeAssembler,
     ops: &[Pop<AllRegisters>],
@@ -37,31 +24,15 @@ pub(crate) fn encode_multi_pop(
     // Start from the top of the reserved space.
     let mut current_offset = 0;
     for x in ops {
-        if x.register.is_32() {
-            multi_pop_item!(a, x.register, current_offset, as_iced_32, mov);
-        } else if x.register.is_64() && cfg!(feature = "x64") {
-            #[cfg(feature = "x64")]
-            multi_pop_item!(a, x.register, current_offset, as_iced_64, mov);
-        } else if x.register.is_xmm() {
-            multi_pop_item!(a, x.register, current_offset, as_iced_xmm, movdqu);
-        } else if x.register.is_ymm() {
-            multi_pop_item!(a, x.register, current_offset, as_iced_ymm, vmovdqu);
-        } else if x.register.is_zmm() {
-            multi_pop_item!(a, x.register, current_offset, as_iced_zmm, vmovdqu8);
-        } else {
-            return Err(JitError::InvalidRegister(x.register).into());
-        }
-
-        // Move to the next offset.
+        encode_mov_from_stack(a, &MovFromStack::new(current_offset as i32, x.register))?;
         current_offset += x.register.size();
     }
 
     // Release the space.
-    let total_space = ops.iter().map(|x| x.register.size()).sum::<usize>();
     if a.bitness() == 32 && cfg!(feature = "x86") {
-        a.add(iced_regs::esp, total_space as i32)?;
+        a.add(iced_regs::esp, current_offset as i32)?;
     } else if a.bitness() == 64 && cfg!(feature = "x64") {
-        a.add(iced_regs::rsp, total_space as i32)?;
+        a.add(iced_regs::rsp, current_offset as i32)?;
     } else {
         return Err(JitError::ThirdPartyAssemblerError(ARCH_NOT_SUPPORTED.to_string()).into());
     }
@@ -80,74 +51,79 @@ mod tests {
     use smallvec::smallvec;
 
     #[rstest]
-    // Basic register push for x64
-    #[case::compile_multi_push_basic_regs_x64(vec![Op::MultiPush(smallvec![
-    Push::new(x64::Register::rax),
-    Push::new(x64::Register::rbx),
-    Push::new(x64::Register::rcx),
-    ])], "4883ec1848890c2448895c24084889442410")]
-    // XMM register push for x64
-    #[case::compile_multi_push_xmm_x64(vec![Op::MultiPush(smallvec![
-    Push::new(x64::Register::xmm0),
-    Push::new(x64::Register::xmm1),
-    Push::new(x64::Register::xmm2),
-    ])], "4883ec30f30f7f1424f30f7f4c2410f30f7f442420")]
-    // YMM register push for x64
-    #[case::compile_multi_push_ymm_x64(vec![Op::MultiPush(smallvec![
-    Push::new(x64::Register::ymm0),
-    Push::new(x64::Register::ymm1),
-    Push::new(x64::Register::ymm2),
-    ])], "4883ec60c5fe7f1424c5fe7f4c2420c5fe7f442440")]
-    // ZMM register push for x64
-    #[case::compile_multi_push_zmm_x64(vec![Op::MultiPush(smallvec![
-    Push::new(x64::Register::zmm0),
-    Push::new(x64::Register::zmm1),
-    Push::new(x64::Register::zmm2),
-    ])], "4881ecc000000062f17f487f142462f17f487f4c240162f17f487f442402")]
-    // Mixed register push for x64
-    #[case::compile_multi_push_mixed_x64(vec![Op::MultiPush(smallvec![
-    Push::new(x64::Register::rax),
-    Push::new(x64::Register::xmm0),
-    Push::new(x64::Register::ymm1),
-    ])], "4883ec38c5fe7f0c24f30f7f4424204889442430")]
-    fn test_compile_x64(#[case] operations: Vec<Op<x64::Register>>, #[case] expected_hex: &str) {
+    // Basic register pop for x64
+    #[case::compile_multi_pop_basic_regs_x64(vec![Op::MultiPop(smallvec![
+    Pop::new(x64::Register::rax),
+    Pop::new(x64::Register::rbx),
+    Pop::new(x64::Register::rcx),
+    ])], "488b0424488b5c2408488b4c24104883c418")]
+    // XMM register pop for x64
+    #[case::compile_multi_pop_xmm_x64(vec![Op::MultiPop(smallvec![
+    Pop::new(x64::Register::xmm0),
+    Pop::new(x64::Register::xmm1),
+    Pop::new(x64::Register::xmm2),
+    ])], "0f1004240f104c24100f105424204883c430")]
+    // YMM register pop for x64
+    #[case::compile_multi_pop_ymm_x64(vec![Op::MultiPop(smallvec![
+    Pop::new(x64::Register::ymm0),
+    Pop::new(x64::Register::ymm1),
+    Pop::new(x64::Register::ymm2),
+    ])], "c5fc100424c5fc104c2420c5fc105424404883c460")]
+    // ZMM register pop for x64
+    #[case::compile_multi_pop_zmm_x64(vec![Op::MultiPop(smallvec![
+    Pop::new(x64::Register::zmm0),
+    Pop::new(x64::Register::zmm1),
+    Pop::new(x64::Register::zmm2),
+    ])], "62f17c4810042462f17c48104c240162f17c48105424024881c4c0000000")]
+    // Mixed register pop for x64
+    #[case::compile_multi_pop_mixed_x64(vec![Op::MultiPop(smallvec![
+    Pop::new(x64::Register::ymm1),
+    Pop::new(x64::Register::xmm0),
+    Pop::new(x64::Register::rax),
+    ])], "c5fc100c240f10442420488b4424304883c438")]
+    // Intel APX register pop for x64
+    #[case::compile_multi_pop_apx_x64(vec![Op::MultiPop(smallvec![
+    Pop::new(x64::Register::rax),
+    Pop::new(x64::Register::r16),
+    ])], "488b0424d5488b4424084883c410")]
+    fn multi_pop_x64(#[case] operations: Vec<Op<x64::Register>>, #[case] expected_hex: &str) {
         let result = JitX64::compile(0, &operations);
         assert!(result.is_ok());
         assert_eq!(expected_hex, hex::encode(result.as_ref().unwrap()));
     }
 
     #[rstest]
-    // Basic register push for x86
-    #[case::compile_multi_push_basic_regs_x86(vec![Op::MultiPush(smallvec![
-    Push::new(x86::Register::eax),
-    Push::new(x86::Register::ebx),
-    Push::new(x86::Register::ecx),
-    ])], "83ec0c890c24895c240489442408")]
-    // XMM register push for x86
-    #[case::compile_multi_push_xmm_x86(vec![Op::MultiPush(smallvec![
-    Push::new(x86::Register::xmm0),
-    Push::new(x86::Register::xmm1),
-    Push::new(x86::Register::xmm2),
-    ])], "83ec30f30f7f1424f30f7f4c2410f30f7f442420")]
-    // YMM register push for x86
-    #[case::compile_multi_push_ymm_x86(vec![Op::MultiPush(smallvec![
-    Push::new(x86::Register::ymm0),
-    Push::new(x86::Register::ymm1),
-    Push::new(x86::Register::ymm2),
-    ])], "83ec60c5fe7f1424c5fe7f4c2420c5fe7f442440")]
-    // ZMM register push for x86
-    #[case::compile_multi_push_zmm_x86(vec![Op::MultiPush(smallvec![
-    Push::new(x86::Register::zmm0),
-    Push::new(x86::Register::zmm1),
-    Push::new(x86::Register::zmm2),
-    ])], "81ecc000000062f17f487f142462f17f487f4c240162f17f487f442402")]
-    // Mixed register push for x86
-    #[case::compile_multi_push_mixed_x86(vec![Op::MultiPush(smallvec![
-    Push::new(x86::Register::eax),
-    Push::new(x86::Register::xmm0),
-    Push::new(x86::Register::ymm1),
-    ])], "83ec34c5fe7f0c24f30f7f44242089442430")]
-    fn test_compile_x86(#[case] operations: Vec<Op<x86::Register>>, #[case] expected_hex: &str) {
+    // Basic register pop for x86
+    #[case::compile_multi_pop_basic_regs_x86(vec![Op::MultiPop(smallvec![
+    Pop::new(x86::Register::eax),
+    Pop::new(x86::Register::ebx),
+    Pop::new(x86::Register::ecx),
+    ])], "8b04248b5c24048b4c240883c40c")]
+    // XMM register pop for x86
+    #[case::compile_multi_pop_xmm_x86(vec![Op::MultiPop(smallvec![
+    Pop::new(x86::Register::xmm0),
+    Pop::new(x86::Register::xmm1),
+    Pop::new(x86::Register::xmm2),
+    ])], "0f1004240f104c24100f1054242083c430")]
+    // YMM register pop for x86
+    #[case::compile_multi_pop_ymm_x86(vec![Op::MultiPop(smallvec![
+    Pop::new(x86::Register::ymm0),
+    Pop::new(x86::Register::ymm1),
+    Pop::new(x86::Register::ymm2),
+    ])], "c5fc100424c5fc104c2420c5fc1054244083c460")]
+    // ZMM register pop for x86
+    #[case::compile_multi_pop_zmm_x86(vec![Op::MultiPop(smallvec![
+    Pop::new(x86::Register::zmm0),
+    Pop::new(x86::Register::zmm1),
+    Pop::new(x86::Register::zmm2),
+    ])], "62f17c4810042462f17c48104c240162f17c481054240281c4c0000000")]
+    // Mixed register pop for x86
+    #[case::compile_multi_pop_mixed_x86(vec![Op::MultiPop(smallvec![
+    Pop::new(x86::Register::ymm1),
+    Pop::new(x86::Register::xmm0),
+    Pop::new(x86::Register::eax),
+    ])], "c5fc100c240f104424208b44243083c434")]
+    fn multi_pop_x86(#[case] operations: Vec<Op<x86::Register>>, #[case] expected_hex: &str) {
         let result = JitX86::compile(0, &operations);
         assert!(result.is_ok());
         assert_eq!(expected_hex, hex::encode(result.as_ref().unwrap()));
@@ -1,53 +1,26 @@
-extern crate alloc;
-
 use crate::all_registers::AllRegisters;
-use crate::common::jit_common::{convert_error, ARCH_NOT_SUPPORTED};
-use crate::mov_item_to_stack;
-use alloc::string::ToString;
-use iced_x86::code_asm::{registers as iced_regs, CodeAssembler};
-use reloaded_hooks_portable::api::jit::compiler::JitError;
-use reloaded_hooks_portable::api::jit::operation_aliases::Push;
+use crate::common::jit_common::X86jitError;
+use crate::instructions::{mov_to_stack::encode_mov_to_stack, stack_alloc::encode_stack_alloc};
+use iced_x86::code_asm::CodeAssembler;
+use reloaded_hooks_portable::api::jit::operation_aliases::{MovToStack, Push, StackAlloc};
 
+/// Pushes multiple registers with a single `sub esp/rsp, N`, followed by a `mov` into each
+/// register's slot; using `movdqu`/`vmovdqu` for vector registers.
+///
+/// The resulting stack layout is the same as pushing the registers in order, i.e. the first
+/// register is at the highest address.
 pub(crate) fn encode_multi_push(
     a: &mut CodeAssembler,
     ops: &[Push<AllRegisters>],
 ) -> Result<(), X86jitError<AllRegisters>> {
-    // Calculate space to reserve.
-    let mut space_needed = 0;
-
-    for x in ops {
-        space_needed += x.register.size();
-    }
-
     // Reserve the space.
-    if a.bitness() == 64 && cfg!(feature = "x64") {
-        a.sub(iced_regs::rsp, space_needed as i32)?;
-    } else if a.bitness() == 32 && cfg!(feature = "x86") {
-        a.sub(iced_regs::esp, space_needed as i32)?;
-    } else {
-        return Err(JitError::ThirdPartyAssemblerError(ARCH_NOT_SUPPORTED.to_string()).into());
-    }
+    let space_needed = ops.iter().map(|x| x.register.size()).sum::<usize>();
+    encode_stack_alloc(a, &StackAlloc::new(space_needed as i32))?;
 
-    // Push the items.
+    // Push the items, starting with the last one, at the top of the stack.
     let mut current_offset = 0;
     for x in ops.iter().rev() {
-        // Loop through the operations in reverse
-        if x.register.is_32() {
-            mov_item_to_stack!(a, x.register, current_offset, as_iced_32, mov);
-        } else if x.register.is_64() && cfg!(feature = "x64") {
-            #[cfg(feature = "x64")]
-            mov_item_to_stack!(a, x.register, current_offset, as_iced_64, mov);
-        } else if x.register.is_xmm() {
-            mov_item_to_stack!(a, x.register, current_offset, as_iced_xmm, movdqu);
-        } else if x.register.is_ymm() {
-            mov_item_to_stack!(a, x.register, current_offset, as_iced_ymm, vmovdqu);
-        } else if x.register.is_zmm() {
-            mov_item_to_stack!(a, x.register, current_offset, as_iced_zmm, vmovdqu8);
-        } else {
-            return Err(JitError::InvalidRegister(x.register));
-        }
-
-        // Move to the next offset.
+        encode_mov_to_stack(a, &MovToStack::new(current_offset as i32, x.register))?;
         current_offset += x.register.size();
     }
 
@@ -95,6 +68,11 @@ mod tests {
     Push::new(x64::Register::xmm0),
     Push::new(x64::Register::ymm1),
     ])], "4883ec38c5fe7f0c24f30f7f4424204889442430")]
+    // Intel APX register push for x64
+    #[case::compile_multi_push_apx_x64(vec![Op::MultiPush(smallvec![
+    Push::new(x64::Register::r16),
+    Push::new(x64::Register::rax),
+    ])], "4883ec1048890424d54889442408")]
     fn multi_push_x64(#[case] operations: Vec<Op<x64::Register>>, #[case] expected_hex: &str) {
         let result = JitX64::compile(0, &operations);
         assert!(result.is_ok());
@@ -7,6 +7,9 @@ use alloc::string::ToString;
 use iced_x86::code_asm::{dword_ptr, qword_ptr, registers as iced_regs, CodeAssembler};
 use reloaded_hooks_portable::api::jit::{compiler::JitError, operation_aliases::Pop};
 
+#[cfg(feature = "x64")]
+use crate::common::jit_instructions::helpers::{encode_rex2, opcode_offset_for_gpr64};
+
 macro_rules! encode_xmm_pop {
     ($a:expr, $reg:expr, $reg_type:ident, $op:ident) => {
         if $a.bitness() == 32 && cfg!(feature = "x86") {
@@ -30,6 +33,14 @@ pub(crate) fn encode_pop(
     } else if pop.register.is_64() && cfg!(feature = "x64") {
         #[cfg(feature = "x64")]
         a.pop(pop.register.as_iced_64()?)?;
+    } else if pop.register.is_apx() {
+        // pop r16-r31, with a REX2 prefix the assembler can't encode.
+        #[cfg(feature = "x64")]
+        {
+            let offset = opcode_offset_for_gpr64(pop.register);
+            let [rex2, payload] = encode_rex2(false, 0, offset);
+            a.db(&[rex2, payload, 0x58 + (offset % 8)])?;
+        }
     } else if pop.register.is_xmm() {
         encode_xmm_pop!(a, pop.register, as_iced_xmm, movdqu);
     } else if pop.register.is_ymm() {
@@ -64,6 +75,9 @@ mod tests {
     }
 
     #[rstest]
+    #[case(x64::Register::rax, "58")]
+    #[case(x64::Register::r16, "d51058")]
+    #[case(x64::Register::r31, "d5115f")]
     #[case(x64::Register::xmm0, "f30f6f04244883c410")]
     #[case(x64::Register::ymm0, "c5fe6f04244883c420")]
     #[case(x64::Register::zmm0, "62f17f486f04244883c440")]
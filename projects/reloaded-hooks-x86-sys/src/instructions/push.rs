@@ -6,6 +6,9 @@ use iced_x86::code_asm::{dword_ptr, qword_ptr, registers as iced_regs, CodeAssem
 use reloaded_hooks_portable::api::jit::compiler::JitError;
 use reloaded_hooks_portable::api::jit::operation_aliases::Push;
 
+#[cfg(feature = "x64")]
+use crate::common::jit_instructions::helpers::{encode_rex2, opcode_offset_for_gpr64};
+
 macro_rules! encode_xmm_push {
     ($a:expr, $reg:expr, $reg_type:ident, $op:ident) => {
         if $a.bitness() == 32 && cfg!(feature = "x86") {
@@ -29,6 +32,14 @@ pub(crate) fn encode_push(
     } else if push.register.is_64() && cfg!(feature = "x64") {
         #[cfg(feature = "x64")]
         a.push(push.register.as_iced_64()?)?;
+    } else if push.register.is_apx() {
+        // push r16-r31, with a REX2 prefix the assembler can't encode.
+        #[cfg(feature = "x64")]
+        {
+            let offset = opcode_offset_for_gpr64(push.register);
+            let [rex2, payload] = encode_rex2(false, 0, offset);
+            a.db(&[rex2, payload, 0x50 + (offset % 8)])?;
+        }
     } else if push.register.is_xmm() {
         encode_xmm_push!(a, push.register, as_iced_xmm, movdqu);
     } else if push.register.is_ymm() {
@@ -53,6 +64,9 @@ mod tests {
 
     #[rstest]
     #[case(x64::Register::rax, "50")]
+    #[case(x64::Register::r16, "d51050")]
+    #[case(x64::Register::r20, "d51054")]
+    #[case(x64::Register::r31, "d51157")]
     #[case(x64::Register::xmm0, "4883ec10f30f7f0424")]
     #[case(x64::Register::ymm0, "4883ec20c5fe7f0424")]
     #[case(x64::Register::zmm0, "4883ec4062f17f487f0424")]
@@ -13,9 +13,11 @@ macro_rules! encode_push_stack_impl {
             if $push.item_size % $size != 0 {
                 return Err(JitError::ThirdPartyAssemblerError($error_msg.to_string()).into());
             } else {
+                // Push the highest part first so the item keeps its layout. Each push moves the
+                // stack pointer down by one part, so the same displacement reaches the next lower part.
                 let num_operations = $push.item_size / $size;
-                for op_idx in 0..num_operations {
-                    let ptr = $ptr_type($reg) + $push.offset as i32 + (op_idx * $size * 2);
+                let ptr = $ptr_type($reg) + $push.offset as i32 + ($push.item_size - $size) as i32;
+                for _ in 0..num_operations {
                     $a.push(ptr)?;
                 }
             }
@@ -61,7 +63,13 @@ mod tests {
 
     #[rstest]
     #[case(4, 8, "ff742404")]
-    #[case(32, 16, "ff742420ff742430")]
+    #[case(32, 16, "ff742428ff742428")]
+    #[case(0, 32, "ff742418ff742418ff742418ff742418")]
+    #[case(
+        8,
+        64,
+        "ff742440ff742440ff742440ff742440ff742440ff742440ff742440ff742440"
+    )]
     fn push_from_stack_x64(#[case] offset: i32, #[case] size: u32, #[case] expected_encoded: &str) {
         let operations = vec![Op::PushStack(PushStack::with_offset_and_size(offset, size))];
         let result = JitX64::compile(0, &operations);
@@ -71,7 +79,7 @@ mod tests {
 
     #[rstest]
     #[case(4, 4, "ff742404")]
-    #[case(32, 16, "ff742420ff742428ff742430ff742438")]
+    #[case(32, 16, "ff74242cff74242cff74242cff74242c")]
     fn push_from_stack_x86(#[case] offset: i32, #[case] size: u32, #[case] expected_encoded: &str) {
         let operations = vec![Op::PushStack(PushStack::with_offset_and_size(offset, size))];
         let result = JitX86::compile(0, &operations);
@@ -31,6 +31,10 @@ pub(crate) mod common {
 
     pub(crate) mod util {
 
+        pub mod apx;
+        pub mod find_branch_into_range;
+        pub mod foreign_hook;
+
         #[cfg(feature = "x64")]
         pub mod get_instruction_length;
         pub mod get_stolen_instructions;
@@ -41,6 +45,8 @@ pub(crate) mod common {
         #[cfg(feature = "x64")]
         pub mod invert_branch_condition;
 
+        pub mod landing_pad;
+
         #[cfg(test)]
         pub(crate) mod test_utilities;
     }
@@ -50,6 +56,9 @@ pub(crate) mod common {
 
         #[cfg(feature = "x64")]
         pub mod patches;
+
+        #[cfg(feature = "x86")]
+        pub mod patches_x86;
     }
 
     pub mod jit_instructions {
@@ -69,20 +78,26 @@ pub(crate) mod common {
 
 /// This namespace contains the code for encoding the JIT instructions
 pub(crate) mod instructions {
+    pub mod atomic_add;
     pub mod call_absolute;
     pub mod call_ip_relative;
     pub mod call_relative;
+    pub mod enter_guard;
+    pub mod exit_guard;
+    pub mod filter_caller;
     pub mod jump_absolute;
     pub mod jump_absolute_indirect;
     pub mod jump_ip_relative;
     pub mod jump_relative;
     pub mod macros;
     pub mod mov;
+    pub mod mov_float_from_stack;
+    pub mod mov_float_to_stack;
     pub mod mov_from_stack;
     pub mod mov_to_stack;
-    #[cfg(target_feature = "multipushpop")]
+    #[cfg(feature = "multipushpop")]
     pub mod multi_pop;
-    #[cfg(target_feature = "multipushpop")]
+    #[cfg(feature = "multipushpop")]
     pub mod multi_push;
     pub mod pop;
     pub mod push;
@@ -5,7 +5,7 @@ use super::Register::*;
 use derive_more::Deref;
 use derive_more::DerefMut;
 use reloaded_hooks_portable::api::calling_convention_info::{
-    GenericCallingConvention, StackCleanup, StackParameterOrder,
+    GenericCallingConvention, ParameterRegisterAssignment, StackCleanup, StackParameterOrder,
 };
 
 /// A variant of `GenericCallingConvention` for x64.
@@ -30,6 +30,7 @@ static MICROSOFT_X64: CallingConvention = CallingConvention {
         float_parameters: &[xmm0, xmm1, xmm2, xmm3],
         vector_parameters: &[],
         return_register: rax,
+        float_return_register: Some(xmm0),
         reserved_stack_space: 32, // 'shadow space'
         callee_saved_registers: &[
             rbp, rbx, rdi, rsi, r12, r13, r14, r15, xmm6, xmm7, xmm8, xmm9, xmm10, xmm11, xmm12,
@@ -39,23 +40,80 @@ static MICROSOFT_X64: CallingConvention = CallingConvention {
         stack_cleanup: StackCleanup::Caller,
         stack_parameter_order: StackParameterOrder::RightToLeft,
         required_stack_alignment: 16,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Positional,
     },
 };
 
+// https://learn.microsoft.com/en-us/cpp/cpp/vectorcall
+// Integer and vector parameters take the register at their position, e.g. (int, __m256) -> rcx, ymm1.
+static VECTORCALL_X64: CallingConvention = CallingConvention {
+    convention: GenericCallingConvention::<Register> {
+        int_parameters: &[rcx, rdx, r8, r9],
+        float_parameters: &[xmm0, xmm1, xmm2, xmm3, xmm4, xmm5],
+        vector_parameters: &[
+            xmm0, xmm1, xmm2, xmm3, xmm4, xmm5, ymm0, ymm1, ymm2, ymm3, ymm4, ymm5, zmm0, zmm1,
+            zmm2, zmm3, zmm4, zmm5,
+        ],
+        return_register: rax,
+        float_return_register: Some(xmm0),
+        reserved_stack_space: 32, // 'shadow space'
+        callee_saved_registers: &[
+            rbp, rbx, rdi, rsi, r12, r13, r14, r15, xmm6, xmm7, xmm8, xmm9, xmm10, xmm11, xmm12,
+            xmm13, xmm14, xmm15,
+        ],
+        always_saved_registers: &[],
+        stack_cleanup: StackCleanup::Caller,
+        stack_parameter_order: StackParameterOrder::RightToLeft,
+        required_stack_alignment: 16,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Positional,
+    },
+};
+
+// __m128, __m256 and __m512 share the 8 SSE registers with floats, e.g. (float, __m256) -> xmm0, ymm1.
+static SYSTEM_V_VECTOR_PARAMETERS: &[Register] = &[
+    xmm0, xmm1, xmm2, xmm3, xmm4, xmm5, xmm6, xmm7, ymm0, ymm1, ymm2, ymm3, ymm4, ymm5, ymm6, ymm7,
+    zmm0, zmm1, zmm2, zmm3, zmm4, zmm5, zmm6, zmm7,
+];
+
 // https://gitlab.com/x86-psABIs/x86-64-ABI/-/jobs/5301578287/artifacts/raw/x86-64-ABI/abi.pdf
 // Parameter passing section
 static SYSTEM_V_AMD64: CallingConvention = CallingConvention {
     convention: GenericCallingConvention::<Register> {
         int_parameters: &[rdi, rsi, rdx, rcx, r8, r9],
         float_parameters: &[xmm0, xmm1, xmm2, xmm3, xmm4, xmm5, xmm6, xmm7],
-        vector_parameters: &[],
+        vector_parameters: SYSTEM_V_VECTOR_PARAMETERS,
         return_register: rax,
+        float_return_register: Some(xmm0),
         reserved_stack_space: 0, // 'red zone' is on the other side of the stack pointer, as opposed to 'shadow space'.
         callee_saved_registers: &[rbp, rbx, r12, r13, r14, r15],
         always_saved_registers: &[],
         stack_cleanup: StackCleanup::Caller,
         stack_parameter_order: StackParameterOrder::RightToLeft,
         required_stack_alignment: 16,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::SharedFloatVector,
+    },
+};
+
+// System V AMD64 on processors with Intel APX. The APX extension to the psABI makes the extended
+// general purpose registers (r16-r31) caller saved, so they may be used as scratch registers.
+static SYSTEM_V_AMD64_APX: CallingConvention = CallingConvention {
+    convention: GenericCallingConvention::<Register> {
+        int_parameters: &[rdi, rsi, rdx, rcx, r8, r9],
+        float_parameters: &[xmm0, xmm1, xmm2, xmm3, xmm4, xmm5, xmm6, xmm7],
+        vector_parameters: SYSTEM_V_VECTOR_PARAMETERS,
+        return_register: rax,
+        float_return_register: Some(xmm0),
+        reserved_stack_space: 0,
+        callee_saved_registers: &[rbp, rbx, r12, r13, r14, r15],
+        always_saved_registers: &[],
+        stack_cleanup: StackCleanup::Caller,
+        stack_parameter_order: StackParameterOrder::RightToLeft,
+        required_stack_alignment: 16,
+        uses_extension_registers: true,
+        parameter_register_assignment: ParameterRegisterAssignment::SharedFloatVector,
     },
 };
 
@@ -66,17 +124,35 @@ impl<'a> CallingConvention<'a> {
         &MICROSOFT_X64
     }
 
+    /// Returns an instance of the CallingConvention struct configured for the
+    /// Microsoft x64 `__vectorcall` calling convention, which passes vector types
+    /// (`__m128`, `__m256`, `__m512`) in registers.
+    pub fn vectorcall() -> &'a Self {
+        &VECTORCALL_X64
+    }
+
     /// Returns an instance of the CallingConvention struct configured for the
     /// System V AMD64 calling convention, commonly used in Unix-like systems.
     pub fn system_v() -> &'a Self {
         &SYSTEM_V_AMD64
     }
 
+    /// Returns an instance of the CallingConvention struct configured for the
+    /// System V AMD64 calling convention on processors with Intel APX.
+    ///
+    /// Only use this if the target processor supports APX, as the Intel APX registers
+    /// (r16-r31) may be used as scratch registers.
+    pub fn system_v_apx() -> &'a Self {
+        &SYSTEM_V_AMD64_APX
+    }
+
     /// Returns a [`CallingConvention`] based on the provided [`PresetCallingConvention`].
     pub fn from_preset(convention_type: PresetCallingConvention) -> &'a Self {
         match convention_type {
             PresetCallingConvention::MicrosoftX64 => Self::microsoft_x64(),
+            PresetCallingConvention::Vectorcall => Self::vectorcall(),
             PresetCallingConvention::SystemV => Self::system_v(),
+            PresetCallingConvention::SystemVApx => Self::system_v_apx(),
         }
     }
 
@@ -100,19 +176,69 @@ impl<'a> CallingConvention<'a> {
 pub enum PresetCallingConvention {
     /// Microsoft x64 calling convention (used in Windows).
     ///
-    /// - Integer parameters: RCX, RDX, R8, R9 (in order, left to right)
-    /// - Float parameters:   XMM0 to XMM3 (in order, left to right)
+    /// - Integer parameters: RCX, RDX, R8, R9 (by parameter position)
+    /// - Float parameters:   XMM0 to XMM3 (by parameter position)
     /// - Additional parameters: Pushed onto stack right to left
     /// - Return register:    RAX (integer), XMM0 (float)
     /// - Cleanup:            Callee
     MicrosoftX64,
 
+    /// Microsoft x64 `__vectorcall` calling convention (used in Windows).
+    ///
+    /// - Integer parameters: RCX, RDX, R8, R9 (by parameter position)
+    /// - Float parameters:   XMM0 to XMM5 (by parameter position)
+    /// - Vector parameters:  XMM0 to XMM5, YMM0 to YMM5 or ZMM0 to ZMM5 (by parameter position)
+    /// - Additional parameters: Pushed onto stack right to left
+    /// - Return register:    RAX (integer), XMM0 (float)
+    /// - Cleanup:            Caller
+    Vectorcall,
+
     /// System V AMD64 ABI calling convention (used in Unix-like systems).
     ///
     /// - Integer parameters: RDI, RSI, RDX, RCX, R8, R9 (in order, left to right)
     /// - Float parameters:   XMM0 to XMM7 (in order, left to right)
+    /// - Vector parameters:  XMM0 to XMM7, YMM0 to YMM7 or ZMM0 to ZMM7 (shared with float parameters)
     /// - Additional parameters: Pushed onto stack right to left
     /// - Return register:    RAX (integer), XMM0 (float)
     /// - Cleanup:            Caller
     SystemV,
+
+    /// System V AMD64 ABI calling convention, on processors with Intel APX.
+    ///
+    /// - Same as [`PresetCallingConvention::SystemV`]
+    /// - R16 to R31 are caller saved
+    SystemVApx,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reloaded_hooks_portable::api::calling_convention_info::CallingConventionInfo;
+
+    #[test]
+    fn caller_saved_registers_include_apx_only_when_enabled() {
+        let system_v = CallingConvention::system_v().caller_saved_registers();
+        let system_v_apx = CallingConvention::system_v_apx().caller_saved_registers();
+
+        assert!(system_v.contains(&rax));
+        assert!(!system_v.contains(&r16));
+        assert!(!system_v.contains(&r31));
+        assert!(system_v_apx.contains(&rax));
+        assert!(system_v_apx.contains(&r16));
+        assert!(system_v_apx.contains(&r31));
+        assert!(!system_v_apx.contains(&rbx));
+    }
+
+    #[test]
+    fn caller_saved_registers_are_full_size() {
+        let caller_saved = CallingConvention::microsoft_x64().caller_saved_registers();
+        assert!(caller_saved.contains(&rax));
+        assert!(caller_saved.contains(&zmm0));
+
+        // Smaller aliases, aliases of callee saved registers and the stack pointer.
+        assert!(!caller_saved.contains(&xmm0));
+        assert!(!caller_saved.contains(&zmm6));
+        assert!(!caller_saved.contains(&rsp));
+        assert!(!caller_saved.contains(&rbx));
+    }
 }
@@ -9,6 +9,7 @@ use crate::common::jit_instructions::decode_relative_call_target::decode_call_ta
 use crate::common::jit_instructions::encode_absolute_jump::encode_absolute_jump_x64;
 use crate::common::jit_instructions::encode_relative_call::encode_call_relative;
 use crate::common::jit_instructions::encode_relative_jump::encode_jump_relative;
+use crate::common::util::landing_pad::ENDBR64;
 use crate::x64::register::Register;
 use alloc::{string::ToString, vec::Vec};
 use iced_x86::code_asm::CodeAssembler;
@@ -79,14 +80,25 @@ impl Jit<Register> for JitX64 {
     }
 
     fn get_jit_capabilities() -> JitCapabilities {
-        JitCapabilities::CAN_ENCODE_IP_RELATIVE_CALL
+        let capabilities = JitCapabilities::CAN_ENCODE_IP_RELATIVE_CALL
             | JitCapabilities::CAN_ENCODE_IP_RELATIVE_JUMP
             | JitCapabilities::PROFITABLE_ABSOLUTE_INDIRECT_JUMP
             | JitCapabilities::CAN_MOV_TO_STACK
+            | JitCapabilities::EMITS_LANDING_PADS;
+
+        // sub + mov is faster than a push per register, and can push vector registers.
+        #[cfg(feature = "multipushpop")]
+        let capabilities = capabilities | JitCapabilities::CAN_MULTI_PUSH;
+
+        capabilities
+    }
+
+    fn landing_pad() -> &'static [u8] {
+        &ENDBR64 // endbr64; a NOP without CET (IBT)
     }
 
     fn max_branch_bytes() -> u32 {
-        12 // mov <reg>, address + call .
+        15 // mov <reg>, address + call <reg>; with REX2 prefixes for Intel APX registers
     }
 
     fn max_indirect_offsets() -> &'static [u32] {
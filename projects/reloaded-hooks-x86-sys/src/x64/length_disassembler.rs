@@ -1,6 +1,10 @@
-use crate::common::util::get_stolen_instructions::get_stolen_instructions_lengths;
-use core::slice;
-use reloaded_hooks_portable::api::length_disassembler::LengthDisassembler;
+use crate::common::util::{
+    find_branch_into_range::find_branch_into_range,
+    get_stolen_instructions::get_stolen_instructions_lengths,
+};
+use reloaded_hooks_portable::{
+    api::length_disassembler::LengthDisassembler, helpers::read_code::read_code,
+};
 
 // Assume Instruction and CodeRewriterError are defined elsewhere
 pub struct LengthDisassemblerX64;
@@ -8,14 +12,30 @@ pub struct LengthDisassemblerX64;
 impl LengthDisassembler for LengthDisassemblerX64 {
     fn disassemble_length(code_address: usize, min_length: usize) -> (usize, usize) {
         // + 16 for max instruction size.
-        let code = unsafe { slice::from_raw_parts(code_address as *const u8, min_length + 16) };
+        let code = unsafe { read_code(code_address, min_length + 16) };
 
         // Only possible error to return is 'insufficient bytes', however, we add max instruction size
         // (16 bytes) to counteract this, so unwrap is ok.
         let result =
-            get_stolen_instructions_lengths(true, min_length as u8, code, code_address).unwrap();
+            get_stolen_instructions_lengths(true, min_length as u8, &code, code_address).unwrap();
         (result.0 as usize, result.1 as usize)
     }
+
+    fn find_branch_into_stolen_region(
+        code_address: usize,
+        stolen_length: usize,
+        scan_length: usize,
+    ) -> Option<(usize, usize)> {
+        let code = unsafe { read_code(code_address, stolen_length + scan_length) };
+
+        find_branch_into_range(
+            true,
+            &code,
+            code_address,
+            code_address,
+            code_address + stolen_length,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -41,4 +61,26 @@ mod tests {
         assert_eq!(result.0, expected_length);
         assert_eq!(result.1, expected_ins_count);
     }
+
+    #[rstest]
+    #[case::no_branch("4883ec108b4424044889d8", 5, 3, None)]
+    #[case::loop_into_stolen("4883ec108b442404ebf8", 5, 2, Some(2))] // jmp to `sub rsp, 16` + 2
+    #[case::outside_window("4883ec108b44240490ebf7", 5, 2, None)] // jmp is not fully within the scanned bytes
+    fn can_find_branch_into_stolen_region(
+        #[case] instructions: String,
+        #[case] min_length: usize,
+        #[case] scan_length: usize,
+        #[case] expected_target_offset: Option<usize>,
+    ) {
+        let ins_vec = str_to_vec(instructions);
+        let code_address = ins_vec.as_ptr() as usize;
+        let (stolen_length, _) =
+            LengthDisassemblerX64::disassemble_length(code_address, min_length);
+        let result = LengthDisassemblerX64::find_branch_into_stolen_region(
+            code_address,
+            stolen_length,
+            scan_length,
+        );
+        assert_eq!(result.map(|x| x.1 - code_address), expected_target_offset);
+    }
 }
@@ -18,7 +18,7 @@ pub enum Register {
     st7,
 
     // 0b100000 - 0b111111
-    // General purpose 64-bit registers (16 registers, 16 reserved)
+    // General purpose 64-bit registers (16 registers, 16 Intel APX registers)
     #[default]
     rax = 0b100000,
     rbx,
@@ -37,6 +37,25 @@ pub enum Register {
     r14,
     r15,
 
+    // Intel APX extended general purpose registers.
+    // Only available on processors supporting APX, see `requires_extension`.
+    r16 = 0b110000,
+    r17,
+    r18,
+    r19,
+    r20,
+    r21,
+    r22,
+    r23,
+    r24,
+    r25,
+    r26,
+    r27,
+    r28,
+    r29,
+    r30,
+    r31,
+
     // SSE 128-bit registers (16 registers, 48 reserved)
     xmm0 = 0b1000000,
     xmm1,
@@ -96,11 +115,11 @@ impl RegisterInfo for Register {
     fn size_in_bytes(&self) -> usize {
         let value = *self as usize;
         match value {
-            _ if value & 0b10000 != 0 => 10,     // st0 - st7
-            _ if value & 0b100000 != 0 => 8,     // rax - r15
-            _ if value & 0b1000000 != 0 => 16,   // xmm0 - xmm15
-            _ if value & 0b10000000 != 0 => 32,  // ymm0 - ymm15
             _ if value & 0b100000000 != 0 => 64, // zmm0 - zmm15
+            _ if value & 0b10000000 != 0 => 32,  // ymm0 - ymm15
+            _ if value & 0b1000000 != 0 => 16,   // xmm0 - xmm15
+            _ if value & 0b100000 != 0 => 8,     // rax - r31
+            _ if value & 0b10000 != 0 => 10,     // st0 - st7
             _ => unreachable!(), // Should never reach here if the enum is well-defined
         }
     }
@@ -112,11 +131,11 @@ impl RegisterInfo for Register {
     fn register_type(&self) -> KnownRegisterType {
         let value = *self as usize;
         match value {
-            _ if value & 0b10000 != 0 => KnownRegisterType::FloatingPoint, // st0 - st7
-            _ if value & 0b100000 != 0 => KnownRegisterType::GeneralPurpose64, // rax - r15
-            _ if value & 0b1000000 != 0 => KnownRegisterType::Vector128,   // xmm0 - xmm15
-            _ if value & 0b10000000 != 0 => KnownRegisterType::Vector256,  // ymm0 - ymm15
             _ if value & 0b100000000 != 0 => KnownRegisterType::Vector512, // zmm0 - zmm15
+            _ if value & 0b10000000 != 0 => KnownRegisterType::Vector256,  // ymm0 - ymm15
+            _ if value & 0b1000000 != 0 => KnownRegisterType::Vector128,   // xmm0 - xmm15
+            _ if value & 0b100000 != 0 => KnownRegisterType::GeneralPurpose64, // rax - r31
+            _ if value & 0b10000 != 0 => KnownRegisterType::FloatingPoint, // st0 - st7
             _ => unreachable!(), // Should never reach here if the enum is well-defined
         }
     }
@@ -154,6 +173,11 @@ impl RegisterInfo for Register {
     {
         Self::all_values()
     }
+
+    fn requires_extension(&self) -> bool {
+        // r16 - r31
+        matches!(*self as usize, 0b110000..=0b111111)
+    }
 }
 
 #[cfg(test)]
@@ -189,6 +213,8 @@ mod tests {
     #[case(r13, 8)]
     #[case(r14, 8)]
     #[case(r15, 8)]
+    #[case(r16, 8)]
+    #[case(r31, 8)]
     #[case(xmm0, 16)]
     #[case(xmm1, 16)]
     #[case(xmm2, 16)]
@@ -266,6 +292,8 @@ mod tests {
     #[case(r13, false)]
     #[case(r14, false)]
     #[case(r15, false)]
+    #[case(r16, false)]
+    #[case(r31, false)]
     #[case(xmm0, false)]
     #[case(xmm1, false)]
     #[case(xmm2, false)]
@@ -343,6 +371,8 @@ mod tests {
     #[case(r13, GeneralPurpose64)]
     #[case(r14, GeneralPurpose64)]
     #[case(r15, GeneralPurpose64)]
+    #[case(r16, GeneralPurpose64)]
+    #[case(r31, GeneralPurpose64)]
     #[case(xmm0, Vector128)]
     #[case(xmm1, Vector128)]
     #[case(xmm2, Vector128)]
@@ -420,6 +450,8 @@ mod tests {
     #[case(r13, r13)]
     #[case(r14, r14)]
     #[case(r15, r15)]
+    #[case(r16, r16)]
+    #[case(r31, r31)]
     #[case(xmm0, zmm0)]
     #[case(xmm1, zmm1)]
     #[case(xmm2, zmm2)]
@@ -471,4 +503,15 @@ mod tests {
     fn extend_test(#[case] input: Register, #[case] expected: Register) {
         assert_eq!(input.extend(), expected);
     }
+
+    #[rstest]
+    #[case(rax, false)]
+    #[case(r15, false)]
+    #[case(r16, true)]
+    #[case(r31, true)]
+    #[case(xmm0, false)]
+    #[case(zmm15, false)]
+    fn requires_extension_test(#[case] register: Register, #[case] expected: bool) {
+        assert_eq!(register.requires_extension(), expected);
+    }
 }
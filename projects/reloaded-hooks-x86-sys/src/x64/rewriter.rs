@@ -3,11 +3,19 @@ extern crate alloc;
 use super::Register;
 use crate::common::{
     jit_conversions_common::map_register_x64_to_allregisters,
-    rewriter::code_rewriter::relocate_code, util::get_stolen_instructions::get_stolen_instructions,
+    rewriter::code_rewriter::relocate_code,
+    util::{
+        foreign_hook::find_foreign_hook,
+        get_stolen_instructions::get_stolen_instructions,
+        landing_pad::{get_landing_pad_length, ENDBR64},
+    },
 };
 use alloc::vec::Vec;
 use core::slice;
-use reloaded_hooks_portable::api::rewriter::code_rewriter::{CodeRewriter, CodeRewriterError};
+use reloaded_hooks_portable::api::rewriter::{
+    code_rewriter::{CodeRewriter, CodeRewriterError},
+    foreign_hook::ForeignHook,
+};
 
 pub struct CodeRewriterX64;
 
@@ -21,20 +29,112 @@ impl CodeRewriter<Register> for CodeRewriterX64 {
         existing_buffer: &mut Vec<u8>,
     ) -> Result<(), CodeRewriterError> {
         let ins_slice = unsafe { slice::from_raw_parts(old_code, old_code_size) };
-        let instructions =
-            get_stolen_instructions(true, old_code_size, ins_slice, old_address).unwrap();
+        let instructions = get_stolen_instructions(true, old_code_size, ins_slice, old_address)?;
         relocate_code(
             true,
             &instructions.0,
             ins_slice,
             new_address,
             scratch_register.map(map_register_x64_to_allregisters),
+            None,
             existing_buffer,
         )?;
         Ok(())
     }
 
     fn max_ins_size_increase() -> usize {
-        14 // see: patches::patch_jcx
+        25 // see: patches::patch_rip_relative_operand_spilled
+    }
+
+    unsafe fn landing_pad_length(address: usize) -> usize {
+        get_landing_pad_length(address, ENDBR64)
+    }
+
+    unsafe fn find_foreign_hook(address: usize) -> Option<ForeignHook> {
+        find_foreign_hook(true, address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::util::test_utilities::{
+        random_code_corpus, str_to_vec, MALFORMED_CODE_CORPUS,
+    };
+    use crate::x64::{rewriter::CodeRewriterX64, Register};
+    use reloaded_hooks_portable::api::rewriter::code_rewriter::{CodeRewriter, CodeRewriterError};
+    use rstest::rstest;
+
+    // (old_address, new_address) pairs covering in range, out of range and upper half relocations.
+    const ADDRESSES: &[(usize, usize)] = &[
+        (0, 4096),
+        (0x80000000, 0),
+        (0, 0x80000000),
+        (0x8000000080000000, 0x8000000000000000),
+    ];
+
+    fn rewrite(code: &[u8], old_address: usize, new_address: usize, scratch: Option<Register>) {
+        // Every prefix of the input, so truncated instructions are covered too.
+        for len in 0..=code.len() {
+            let _ = unsafe {
+                CodeRewriterX64::rewrite_code(code.as_ptr(), len, old_address, new_address, scratch)
+            };
+        }
+    }
+
+    #[test]
+    fn malformed_corpus_does_not_panic() {
+        for code in MALFORMED_CODE_CORPUS {
+            let code = str_to_vec(code.to_string());
+            for (old_address, new_address) in ADDRESSES {
+                rewrite(&code, *old_address, *new_address, Some(Register::rax));
+                rewrite(&code, *old_address, *new_address, None);
+            }
+        }
+    }
+
+    #[test]
+    fn random_corpus_does_not_panic() {
+        for code in random_code_corpus(2000, 24) {
+            for (old_address, new_address) in ADDRESSES {
+                rewrite(&code, *old_address, *new_address, Some(Register::rax));
+                rewrite(&code, *old_address, *new_address, None);
+            }
+        }
+    }
+
+    #[test]
+    fn reencode_error_has_offset_and_bytes() {
+        // push rax + xbegin -1, target is out of range of rel32 at new address.
+        let code = str_to_vec("50c7f8ffffffff".to_string());
+        let result = unsafe {
+            CodeRewriterX64::rewrite_code(code.as_ptr(), code.len(), 0x100000000, 0, None)
+        };
+
+        match result {
+            Err(CodeRewriterError::FailedToReencode(offset, bytes, _)) => {
+                assert_eq!(offset, 1);
+                assert_eq!(bytes, "c7f8ffffffff");
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[rstest]
+    #[case::empty("", CodeRewriterError::InsufficientBytes)]
+    #[case::truncated_rip_rel(
+        "488b05080000",
+        CodeRewriterError::FailedToDisasm("0".to_string(), "488b05080000".to_string())
+    )]
+    #[case::truncated_after_valid(
+        "50e9fb0f00",
+        CodeRewriterError::FailedToDisasm("1".to_string(), "e9fb0f00".to_string())
+    )]
+    #[case::no_scratch("eb02", CodeRewriterError::NoScratchRegister(String::new()))]
+    fn returns_structured_error(#[case] code: &str, #[case] expected: CodeRewriterError) {
+        let code = str_to_vec(code.to_string());
+        let result = unsafe {
+            CodeRewriterX64::rewrite_code(code.as_ptr(), code.len(), 0x80000000, 0, None)
+        };
+        assert_eq!(result, Err(expected));
     }
 }
@@ -3,7 +3,7 @@ use crate::x86::Register::*;
 use derive_more::Deref;
 use derive_more::DerefMut;
 use reloaded_hooks_portable::api::calling_convention_info::{
-    GenericCallingConvention, StackCleanup, StackParameterOrder,
+    GenericCallingConvention, ParameterRegisterAssignment, StackCleanup, StackParameterOrder,
 };
 
 /// A variant of `GenericCallingConvention` for x86.
@@ -28,12 +28,15 @@ static CDECL: CallingConvention = CallingConvention {
         float_parameters: &[],
         vector_parameters: &[],
         return_register: eax,
+        float_return_register: Some(st0),
         reserved_stack_space: 0,
         callee_saved_registers: &[ebx, esi, edi, ebp],
         always_saved_registers: &[],
         stack_cleanup: StackCleanup::Caller,
         stack_parameter_order: StackParameterOrder::RightToLeft,
         required_stack_alignment: 1,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
     },
 };
 
@@ -43,12 +46,15 @@ static STDCALL: CallingConvention = CallingConvention {
         float_parameters: &[],
         vector_parameters: &[],
         return_register: eax,
+        float_return_register: Some(st0),
         reserved_stack_space: 0,
         callee_saved_registers: &[ebx, esi, edi, ebp],
         always_saved_registers: &[],
         stack_cleanup: StackCleanup::Callee,
         stack_parameter_order: StackParameterOrder::RightToLeft,
         required_stack_alignment: 1,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
     },
 };
 
@@ -58,12 +64,15 @@ static FASTCALL: CallingConvention = CallingConvention {
         float_parameters: &[],
         vector_parameters: &[],
         return_register: eax,
+        float_return_register: Some(st0),
         reserved_stack_space: 0,
         callee_saved_registers: &[ebx, esi, edi, ebp],
         always_saved_registers: &[],
         stack_cleanup: StackCleanup::Caller,
         stack_parameter_order: StackParameterOrder::RightToLeft,
         required_stack_alignment: 1,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
     },
 };
 
@@ -73,12 +82,15 @@ static MICROSOFT_THISCALL: CallingConvention = CallingConvention {
         float_parameters: &[],
         vector_parameters: &[],
         return_register: eax,
+        float_return_register: Some(st0),
         reserved_stack_space: 0,
         callee_saved_registers: &[ebx, esi, edi, ebp],
         always_saved_registers: &[],
         stack_cleanup: StackCleanup::Callee,
         stack_parameter_order: StackParameterOrder::RightToLeft,
         required_stack_alignment: 1,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
     },
 };
 
@@ -88,12 +100,15 @@ static CLRCALL: CallingConvention = CallingConvention {
         float_parameters: &[],
         vector_parameters: &[],
         return_register: eax,
+        float_return_register: Some(st0),
         reserved_stack_space: 0,
         callee_saved_registers: &[ebx, esi, edi, ebp],
         always_saved_registers: &[],
         stack_cleanup: StackCleanup::Callee,
         stack_parameter_order: StackParameterOrder::LeftToRight,
         required_stack_alignment: 1,
+        uses_extension_registers: false,
+        parameter_register_assignment: ParameterRegisterAssignment::Sequential,
     },
 };
 
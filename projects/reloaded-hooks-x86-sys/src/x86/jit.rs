@@ -9,6 +9,7 @@ use crate::common::jit_instructions::decode_relative_call_target::decode_call_ta
 use crate::common::jit_instructions::encode_absolute_jump::encode_absolute_jump_x86;
 use crate::common::jit_instructions::encode_relative_call::encode_call_relative;
 use crate::common::jit_instructions::encode_relative_jump::encode_jump_relative;
+use crate::common::util::landing_pad::ENDBR32;
 use crate::x86::register::Register;
 use alloc::{string::ToString, vec::Vec};
 use iced_x86::code_asm::CodeAssembler;
@@ -17,7 +18,7 @@ use reloaded_hooks_portable::api::jit::compiler::DecodeCallTargetResult;
 use reloaded_hooks_portable::api::jit::jump_absolute_operation::JumpAbsoluteOperation;
 use reloaded_hooks_portable::api::jit::jump_relative_operation::JumpRelativeOperation;
 use reloaded_hooks_portable::api::jit::{
-    compiler::{transform_err, Jit, JitCapabilities, JitError},
+    compiler::{try_transform_err, Jit, JitCapabilities, JitError},
     operation::{transform_op, Operation},
 };
 
@@ -84,9 +85,20 @@ impl Jit<Register> for JitX86 {
     }
 
     fn get_jit_capabilities() -> JitCapabilities {
-        JitCapabilities::CAN_ENCODE_IP_RELATIVE_CALL
+        let capabilities = JitCapabilities::CAN_ENCODE_IP_RELATIVE_CALL
             | JitCapabilities::CAN_ENCODE_IP_RELATIVE_JUMP
             | JitCapabilities::CAN_MOV_TO_STACK
+            | JitCapabilities::EMITS_LANDING_PADS;
+
+        // sub + mov is faster than a push per register, and can push vector registers.
+        #[cfg(feature = "multipushpop")]
+        let capabilities = capabilities | JitCapabilities::CAN_MULTI_PUSH;
+
+        capabilities
+    }
+
+    fn landing_pad() -> &'static [u8] {
+        &ENDBR32 // endbr32; a NOP without CET (IBT)
     }
 
     fn max_branch_bytes() -> u32 {
@@ -157,5 +169,5 @@ fn encode_instruction_x86(
     });
 
     encode_instruction(assembler, &all_register_op, address)
-        .map_err(|x| transform_err(x, map_allregisters_to_x86))
+        .map_err(|x| try_transform_err(x, map_allregisters_to_x86))
 }
@@ -1,6 +1,10 @@
-use crate::common::util::get_stolen_instructions::get_stolen_instructions_lengths;
-use core::slice;
-use reloaded_hooks_portable::api::length_disassembler::LengthDisassembler;
+use crate::common::util::{
+    find_branch_into_range::find_branch_into_range,
+    get_stolen_instructions::get_stolen_instructions_lengths,
+};
+use reloaded_hooks_portable::{
+    api::length_disassembler::LengthDisassembler, helpers::read_code::read_code,
+};
 
 // Assume Instruction and CodeRewriterError are defined elsewhere
 pub struct LengthDisassemblerX86;
@@ -8,14 +12,30 @@ pub struct LengthDisassemblerX86;
 impl LengthDisassembler for LengthDisassemblerX86 {
     fn disassemble_length(code_address: usize, min_length: usize) -> (usize, usize) {
         // + 16 for max instruction size.
-        let code = unsafe { slice::from_raw_parts(code_address as *const u8, min_length + 16) };
+        let code = unsafe { read_code(code_address, min_length + 16) };
 
         // Only possible error to return is 'insufficient bytes', however, we add max instruction size
         // (16 bytes) to counteract this, so unwrap is ok.
         let result =
-            get_stolen_instructions_lengths(false, min_length as u8, code, code_address).unwrap();
+            get_stolen_instructions_lengths(false, min_length as u8, &code, code_address).unwrap();
         (result.0 as usize, result.1 as usize)
     }
+
+    fn find_branch_into_stolen_region(
+        code_address: usize,
+        stolen_length: usize,
+        scan_length: usize,
+    ) -> Option<(usize, usize)> {
+        let code = unsafe { read_code(code_address, stolen_length + scan_length) };
+
+        find_branch_into_range(
+            false,
+            &code,
+            code_address,
+            code_address,
+            code_address + stolen_length,
+        )
+    }
 }
 
 #[cfg(test)]
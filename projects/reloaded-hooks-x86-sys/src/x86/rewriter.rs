@@ -3,11 +3,22 @@ extern crate alloc;
 use super::Register;
 use crate::common::{
     jit_conversions_common::map_register_x86_to_allregisters,
-    rewriter::code_rewriter::relocate_code, util::get_stolen_instructions::get_stolen_instructions,
+    rewriter::code_rewriter::{relocate_code, ReadCodeFn, PC_THUNK_LEN},
+    util::{
+        foreign_hook::find_foreign_hook,
+        get_stolen_instructions::get_stolen_instructions,
+        landing_pad::{get_landing_pad_length, ENDBR32},
+    },
 };
 use alloc::vec::Vec;
 use core::slice;
-use reloaded_hooks_portable::api::rewriter::code_rewriter::{CodeRewriter, CodeRewriterError};
+use reloaded_hooks_portable::{
+    api::rewriter::{
+        code_rewriter::{CodeRewriter, CodeRewriterError},
+        foreign_hook::ForeignHook,
+    },
+    helpers::read_code::try_read_code,
+};
 
 pub struct CodeRewriterX86;
 
@@ -20,21 +31,129 @@ impl CodeRewriter<Register> for CodeRewriterX86 {
         scratch_register: Option<Register>,
         existing_buffer: &mut Vec<u8>,
     ) -> Result<(), CodeRewriterError> {
-        let ins_slice = unsafe { slice::from_raw_parts(old_code, old_code_size) };
-        let instructions =
-            get_stolen_instructions(false, old_code_size, ins_slice, old_address).unwrap();
-        relocate_code(
-            false,
-            &instructions.0,
-            ins_slice,
+        // PC thunks called by the code are read through the registered `MemoryAccessor`, as
+        // `old_code` may be a copy of code which lives at `old_address`, or in another process.
+        let read_code = |address: usize| {
+            try_read_code(address, PC_THUNK_LEN)
+                .ok()
+                .and_then(|code| code.try_into().ok())
+        };
+
+        rewrite_code_x86(
+            old_code,
+            old_code_size,
+            old_address,
             new_address,
-            scratch_register.map(map_register_x86_to_allregisters),
+            scratch_register,
+            (old_address <= u32::MAX as usize).then_some(&read_code as ReadCodeFn),
             existing_buffer,
-        )?;
-        Ok(())
+        )
     }
 
     fn max_ins_size_increase() -> usize {
         4 // jmp imm8 to jmp dword [ptr]
     }
+
+    unsafe fn landing_pad_length(address: usize) -> usize {
+        get_landing_pad_length(address, ENDBR32)
+    }
+
+    unsafe fn find_foreign_hook(address: usize) -> Option<ForeignHook> {
+        find_foreign_hook(false, address)
+    }
+}
+
+/// Relocates x86 code, see [`CodeRewriter::rewrite_code_with_buffer`].
+///
+/// # Parameters
+/// `read_code`: Reads the code of call targets, used to detect PC thunks. Thunks are only detected if provided.
+unsafe fn rewrite_code_x86(
+    old_code: *const u8,
+    old_code_size: usize,
+    old_address: usize,
+    new_address: usize,
+    scratch_register: Option<Register>,
+    read_code: Option<ReadCodeFn>,
+    existing_buffer: &mut Vec<u8>,
+) -> Result<(), CodeRewriterError> {
+    let ins_slice = unsafe { slice::from_raw_parts(old_code, old_code_size) };
+    let instructions = get_stolen_instructions(false, old_code_size, ins_slice, old_address)?;
+    relocate_code(
+        false,
+        &instructions.0,
+        ins_slice,
+        new_address,
+        scratch_register.map(map_register_x86_to_allregisters),
+        read_code,
+        existing_buffer,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::util::test_utilities::{
+        random_code_corpus, str_to_vec, MALFORMED_CODE_CORPUS,
+    };
+    use crate::x86::{
+        rewriter::{rewrite_code_x86, CodeRewriterX86},
+        Register,
+    };
+    use reloaded_hooks_portable::api::rewriter::code_rewriter::{CodeRewriter, CodeRewriterError};
+    use rstest::rstest;
+
+    // (old_address, new_address) pairs, including relocations which wrap around the 4GiB address space.
+    const ADDRESSES: &[(usize, usize)] = &[(0, 4096), (0x80000000, 0), (0xFFFFF000, 0x1000)];
+
+    fn rewrite(code: &[u8], old_address: usize, new_address: usize, scratch: Option<Register>) {
+        // Every prefix of the input, so truncated instructions are covered too.
+        // Calls in the corpus may target any address, so PC thunks are not read.
+        for len in 0..=code.len() {
+            let _ = unsafe {
+                rewrite_code_x86(
+                    code.as_ptr(),
+                    len,
+                    old_address,
+                    new_address,
+                    scratch,
+                    None,
+                    &mut Vec::new(),
+                )
+            };
+        }
+    }
+
+    #[test]
+    fn malformed_corpus_does_not_panic() {
+        for code in MALFORMED_CODE_CORPUS {
+            let code = str_to_vec(code.to_string());
+            for (old_address, new_address) in ADDRESSES {
+                rewrite(&code, *old_address, *new_address, Some(Register::eax));
+                rewrite(&code, *old_address, *new_address, None);
+            }
+        }
+    }
+
+    #[test]
+    fn random_corpus_does_not_panic() {
+        for code in random_code_corpus(2000, 24) {
+            for (old_address, new_address) in ADDRESSES {
+                rewrite(&code, *old_address, *new_address, Some(Register::eax));
+                rewrite(&code, *old_address, *new_address, None);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case::empty("", CodeRewriterError::InsufficientBytes)]
+    #[case::truncated_jmp(
+        "e9fb0f00",
+        CodeRewriterError::FailedToDisasm("0".to_string(), "e9fb0f00".to_string())
+    )]
+    fn returns_structured_error(#[case] code: &str, #[case] expected: CodeRewriterError) {
+        let code = str_to_vec(code.to_string());
+        let result =
+            unsafe { CodeRewriterX86::rewrite_code(code.as_ptr(), code.len(), 0x1000, 0, None) };
+        assert_eq!(result, Err(expected));
+    }
 }
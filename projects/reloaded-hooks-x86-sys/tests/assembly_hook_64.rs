@@ -106,6 +106,66 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn drop_hook_restores_original_x64() {
+        // Allocate the function.
+        let add_addr = alloc_function(&CALCULATOR_ADD_MSFT_X64).unwrap();
+        let add: Add = unsafe { transmute(add_addr) };
+
+        let slice = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(add_addr, slice.as_ptr() as usize, slice.len(), 13)
+                .with_scratch_register(x64::Register::r8);
+
+        let hook = unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                LockedBuffer,
+                DefaultBufferFactory,
+            >(&settings)
+            .unwrap()
+            .with_uninstall_on_drop(true)
+        };
+
+        assert_eq!(3, add(1, 1));
+
+        // Dropping the hook uninstalls it.
+        drop(hook);
+        assert_eq!(2, add(1, 1));
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn drop_hook_keeps_hook_installed_by_default_x64() {
+        // Allocate the function.
+        let add_addr = alloc_function(&CALCULATOR_ADD_MSFT_X64).unwrap();
+        let add: Add = unsafe { transmute(add_addr) };
+
+        let slice = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(add_addr, slice.as_ptr() as usize, slice.len(), 13)
+                .with_scratch_register(x64::Register::r8);
+
+        let hook = unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                LockedBuffer,
+                DefaultBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        drop(hook);
+        assert_eq!(3, add(1, 1));
+    }
+
     #[test]
     #[cfg(target_arch = "x86_64")]
     fn double_hook_calculator_add_asm_x64() {
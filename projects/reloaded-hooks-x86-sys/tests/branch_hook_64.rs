@@ -11,14 +11,19 @@ mod tests {
     use crate::asm::calculator::CALL_CALCULATOR_ADD_MSFT_X64;
     use crate::asm::calculator::CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET;
     use crate::asm::calculator::CALL_CALCULATOR_ADD_MSFT_X64_FUN_OFFSET;
+    use crate::asm::calculator::CALL_CALCULATOR_ADD_MSFT_X64_TARGET_FUNCTION_OFFSET;
     use asm::assemble_function::alloc_function;
     use core::mem::transmute;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::time::Duration;
     use reloaded_hooks_buffers_common::buffer::StaticLinkedBuffer;
     use reloaded_hooks_buffers_common::buffer_factory::BuffersFactory;
     use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
     use reloaded_hooks_portable::api::function_info::BasicFunctionInfo;
     use reloaded_hooks_portable::api::function_info::ParameterType;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook::create_branch_hook_with_callback;
     use reloaded_hooks_portable::api::hooks::branch::branch_hook::create_branch_hook_with_pointer;
+    use reloaded_hooks_portable::api::hooks::caller_filter::CallerFilter;
     use reloaded_hooks_portable::api::settings::basic_hook_settings::BasicHookSettings;
     use reloaded_hooks_portable::api::settings::function_hook_settings::FunctionHookSettings;
     use reloaded_hooks_x86_sys::x64::calling_convention::CallingConvention;
@@ -39,6 +44,14 @@ mod tests {
         MAIN_TEST_ADDR.unwrap_unchecked()(x + 1, y)
     }
 
+    // Function containing the hooked call, for hooks which call back into it.
+    static RECURSIVE_ADD_ADDR: AtomicUsize = AtomicUsize::new(0);
+
+    pub unsafe extern "win64" fn add_hook_impl_recursive(x: i64, y: i64) -> i64 {
+        let add: Add = transmute(RECURSIVE_ADD_ADDR.load(Ordering::Relaxed));
+        add(x, y) + 1
+    }
+
     // Static instance of BasicFunctionInfo
     static ADD_INFO: BasicFunctionInfo =
         BasicFunctionInfo::new(&[ParameterType::i64, ParameterType::i64]);
@@ -157,4 +170,183 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn hook_calculator_branch_x64_with_in_flight_tracking() {
+        unsafe {
+            // Allocate the function.
+            let add_addr = alloc_function(&CALL_CALCULATOR_ADD_MSFT_X64).unwrap();
+            let add: Add = transmute(add_addr + CALL_CALCULATOR_ADD_MSFT_X64_FUN_OFFSET);
+            let hook_addr = add_addr + CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET;
+            let hook_target: usize = add_hook_impl_msft as *const () as usize;
+
+            let basic_settings = BasicHookSettings::new_with_scratch_register(
+                hook_addr,
+                hook_target,
+                Some(x64::Register::r8),
+            );
+
+            let settings = FunctionHookSettings::<
+                Register,
+                BasicFunctionInfo,
+                GenericCallingConvention<Register>,
+            >::new(
+                basic_settings,
+                true,
+                ADD_INFO,
+                CallingConvention::microsoft_x64(),
+                CallingConvention::microsoft_x64(),
+                None,
+            )
+            .with_in_flight_tracking();
+
+            let test_addr_ptr: *mut usize = transmute(&MAIN_TEST_ADDR);
+            let _hook = create_branch_hook_with_pointer::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                StaticLinkedBuffer,
+                BuffersFactory,
+                BasicFunctionInfo,
+                GenericCallingConvention<Register>,
+            >(&settings, test_addr_ptr)
+            .unwrap();
+
+            // Test the hook, the counter must be balanced after the calls.
+            _hook.enable();
+            for x in 0..100 {
+                for y in 0..100 {
+                    assert_eq!(x + y + 1, add(x, y));
+                }
+            }
+
+            assert_eq!(Some(0), _hook.get_in_flight_calls());
+            assert_eq!(
+                Some(true),
+                _hook.disable_and_wait(Duration::from_millis(100))
+            );
+            for x in 0..100 {
+                for y in 0..100 {
+                    assert_eq!(x + y, add(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hook_calculator_branch_x64_with_reentrancy_guard() {
+        unsafe {
+            // Allocate the function.
+            let add_addr = alloc_function(&CALL_CALCULATOR_ADD_MSFT_X64).unwrap();
+            let add: Add = transmute(add_addr + CALL_CALCULATOR_ADD_MSFT_X64_FUN_OFFSET);
+            let hook_addr = add_addr + CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET;
+            let hook_target: usize = add_hook_impl_recursive as *const () as usize;
+            RECURSIVE_ADD_ADDR.store(add as usize, Ordering::Relaxed);
+
+            let basic_settings = BasicHookSettings::new_with_scratch_register(
+                hook_addr,
+                hook_target,
+                Some(x64::Register::r8),
+            );
+
+            let settings = FunctionHookSettings::<
+                Register,
+                BasicFunctionInfo,
+                GenericCallingConvention<Register>,
+            >::new(
+                basic_settings,
+                true,
+                ADD_INFO,
+                CallingConvention::microsoft_x64(),
+                CallingConvention::microsoft_x64(),
+                None,
+            )
+            .with_reentrancy_guard();
+
+            let _hook = create_branch_hook_with_callback::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                StaticLinkedBuffer,
+                BuffersFactory,
+                BasicFunctionInfo,
+                GenericCallingConvention<Register>,
+            >(&settings, |_| {})
+            .unwrap();
+
+            // The hook calls the hooked function again, which must go to the original.
+            for x in 0..100 {
+                for y in 0..100 {
+                    assert_eq!(x + y + 1, add(x, y));
+                }
+            }
+
+            // Other threads have their own flag, so they are not bypassed.
+            let other_thread = std::thread::spawn(move || add(1, 2)).join().unwrap();
+            assert_eq!(4, other_thread);
+        }
+    }
+
+    #[test]
+    fn hook_calculator_branch_x64_with_caller_filter() {
+        unsafe {
+            // Allocate the function.
+            let add_addr = alloc_function(&CALL_CALCULATOR_ADD_MSFT_X64).unwrap();
+            let add: Add = transmute(add_addr + CALL_CALCULATOR_ADD_MSFT_X64_FUN_OFFSET);
+            let hook_addr = add_addr + CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET;
+            let hook_target = add_addr + CALL_CALCULATOR_ADD_MSFT_X64_TARGET_FUNCTION_OFFSET;
+
+            // Return address of the hooked call is right after it, in 'add_wrapper'.
+            let caller = add_addr..add_addr + CALL_CALCULATOR_ADD_MSFT_X64_TARGET_FUNCTION_OFFSET;
+            let elsewhere = 0x1000..0x2000;
+            let filter = CallerFilter::new(&[elsewhere.clone(), caller.clone()]);
+
+            let basic_settings = BasicHookSettings::new_with_scratch_register(
+                hook_addr,
+                hook_target,
+                Some(x64::Register::r8),
+            );
+
+            let settings = FunctionHookSettings::<
+                Register,
+                BasicFunctionInfo,
+                GenericCallingConvention<Register>,
+            >::new(
+                basic_settings,
+                true,
+                ADD_INFO,
+                CallingConvention::microsoft_x64(),
+                CallingConvention::microsoft_x64(),
+                None,
+            )
+            .with_caller_filter(filter);
+
+            let _hook = create_branch_hook_with_callback::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                StaticLinkedBuffer,
+                BuffersFactory,
+                BasicFunctionInfo,
+                GenericCallingConvention<Register>,
+            >(&settings, |_| {})
+            .unwrap();
+
+            // Caller is in the second range, so the hook runs.
+            assert_eq!(3 + 4 + 1, add(3, 4));
+
+            // Caller is in none of the ranges, so the original function runs.
+            filter.set_ranges(&[elsewhere]);
+            assert_eq!(3 + 4, add(3, 4));
+
+            filter.set_ranges(&[]);
+            assert_eq!(3 + 4, add(3, 4));
+
+            filter.set_ranges(&[caller]);
+            assert_eq!(3 + 4 + 1, add(3, 4));
+        }
+    }
 }
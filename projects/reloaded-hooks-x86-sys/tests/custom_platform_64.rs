@@ -0,0 +1,120 @@
+mod asm;
+
+#[cfg(target_arch = "x86_64")]
+mod tests {
+    use crate::asm;
+    use crate::asm::calculator::Add;
+    use asm::assemble_function::alloc_function;
+    use asm::calculator::CALCULATOR_ADD_MSFT_X64;
+    use core::mem::transmute;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use reloaded_hooks_portable::api::buffers::default_buffer::LockedBuffer;
+    use reloaded_hooks_portable::api::errors::memory_protection_error::MemoryProtectionError;
+    use reloaded_hooks_portable::api::hooks::assembly::assembly_hook::create_assembly_hook;
+    use reloaded_hooks_portable::api::platforms::platform_abstractions::{
+        MemoryAccessor, PlatformFunctions,
+    };
+    use reloaded_hooks_portable::api::platforms::platform_functions::{
+        get_platform_functions, register_platform_functions, DefaultPlatformFunctions,
+    };
+    use reloaded_hooks_portable::api::{
+        buffers::default_buffer_factory::DefaultBufferFactory,
+        settings::assembly_hook_settings::AssemblyHookSettings,
+    };
+    use reloaded_hooks_x86_sys::x64::{
+        self, jit::JitX64, length_disassembler::LengthDisassemblerX64, rewriter::CodeRewriterX64,
+    };
+
+    static UNPROTECT_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static RESTORE_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static WRITE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts calls, and forwards them to the default backend.
+    struct CountingPlatformFunctions;
+
+    impl PlatformFunctions for CountingPlatformFunctions {
+        fn page_size(&self) -> usize {
+            DefaultPlatformFunctions.page_size()
+        }
+
+        fn unprotect_memory(
+            &self,
+            address: usize,
+            size: usize,
+        ) -> Result<(), MemoryProtectionError> {
+            UNPROTECT_CALLS.fetch_add(1, Ordering::SeqCst);
+            DefaultPlatformFunctions.unprotect_memory(address, size)
+        }
+
+        fn restore_memory_protection(
+            &self,
+            address: usize,
+            size: usize,
+        ) -> Result<(), MemoryProtectionError> {
+            RESTORE_CALLS.fetch_add(1, Ordering::SeqCst);
+            DefaultPlatformFunctions.restore_memory_protection(address, size)
+        }
+
+        fn clear_instruction_cache(&self, start: usize, end: usize) {
+            DefaultPlatformFunctions.clear_instruction_cache(start, end)
+        }
+    }
+
+    impl MemoryAccessor for CountingPlatformFunctions {
+        unsafe fn write_memory_atomic(
+            &self,
+            address: usize,
+            data: &[u8],
+        ) -> Result<(), MemoryProtectionError> {
+            WRITE_CALLS.fetch_add(1, Ordering::SeqCst);
+            DefaultPlatformFunctions.write_memory_atomic(address, data)
+        }
+    }
+
+    #[test]
+    fn hook_with_custom_platform_x64() {
+        assert!(register_platform_functions(&CountingPlatformFunctions));
+        assert!(!register_platform_functions(&DefaultPlatformFunctions));
+        assert_eq!(
+            get_platform_functions().page_size(),
+            DefaultPlatformFunctions.page_size()
+        );
+
+        // Allocate the function.
+        let add_addr = alloc_function(&CALCULATOR_ADD_MSFT_X64).unwrap();
+        let add: Add = unsafe { transmute(add_addr) };
+
+        let slice = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(add_addr, slice.as_ptr() as usize, slice.len(), 13)
+                .with_scratch_register(x64::Register::r8);
+
+        let hook = unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                LockedBuffer,
+                DefaultBufferFactory,
+            >(&settings)
+            .unwrap()
+            .with_uninstall_on_drop(true)
+        };
+
+        assert_eq!(3, add(1, 1));
+        assert!(UNPROTECT_CALLS.load(Ordering::SeqCst) > 0);
+        assert!(WRITE_CALLS.load(Ordering::SeqCst) > 0);
+        assert_eq!(
+            UNPROTECT_CALLS.load(Ordering::SeqCst),
+            RESTORE_CALLS.load(Ordering::SeqCst)
+        );
+
+        drop(hook);
+        assert_eq!(2, add(1, 1));
+        assert_eq!(
+            UNPROTECT_CALLS.load(Ordering::SeqCst),
+            RESTORE_CALLS.load(Ordering::SeqCst)
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Tests for the thread local flags used by re-entrancy guards.
+//!
+//! Separate from `branch_hook_64.rs`, as the flags are shared by every hook in the process,
+//! and other tests would otherwise take them from under us.
+
+#![allow(clippy::useless_transmute)]
+
+mod asm;
+
+#[cfg(all(
+    target_arch = "x86_64",
+    any(target_os = "linux", target_os = "windows")
+))]
+mod tests {
+    use crate::asm::assemble_function::alloc_function;
+    use crate::asm::calculator::{
+        Add, CALL_CALCULATOR_ADD_MSFT_X64, CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET,
+        CALL_CALCULATOR_ADD_MSFT_X64_FUN_OFFSET,
+    };
+    use core::mem::transmute;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::time::Duration;
+    use reloaded_hooks_buffers_common::buffer::StaticLinkedBuffer;
+    use reloaded_hooks_buffers_common::buffer_factory::BuffersFactory;
+    use reloaded_hooks_portable::api::buffers::default_buffer_factory::RELEASE_QUARANTINE_MS;
+    use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+    use reloaded_hooks_portable::api::errors::function_hook_error::FunctionHookError;
+    use reloaded_hooks_portable::api::function_info::{BasicFunctionInfo, ParameterType};
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook::create_branch_hook_with_callback;
+    use reloaded_hooks_portable::api::hooks::common_hook::CommonHook;
+    use reloaded_hooks_portable::api::settings::basic_hook_settings::BasicHookSettings;
+    use reloaded_hooks_portable::api::settings::function_hook_settings::FunctionHookSettings;
+    use reloaded_hooks_x86_sys::x64::calling_convention::CallingConvention;
+    use reloaded_hooks_x86_sys::x64::{
+        self, jit::JitX64, length_disassembler::LengthDisassemblerX64, rewriter::CodeRewriterX64,
+        Register,
+    };
+
+    /// More than the number of flags available on any platform.
+    const NUM_HOOKS: usize = 130;
+
+    // Function containing the hooked call, for hooks which call back into it.
+    static RECURSIVE_ADD_ADDR: AtomicUsize = AtomicUsize::new(0);
+
+    pub unsafe extern "win64" fn add_hook_impl_recursive(x: i64, y: i64) -> i64 {
+        let add: Add = transmute(RECURSIVE_ADD_ADDR.load(Ordering::Relaxed));
+        add(x, y) + 1
+    }
+
+    static ADD_INFO: BasicFunctionInfo =
+        BasicFunctionInfo::new(&[ParameterType::i64, ParameterType::i64]);
+
+    type GuardedHook = CommonHook<StaticLinkedBuffer, JitX64, Register, BuffersFactory>;
+
+    unsafe fn create_guarded_hook(
+        settings: &FunctionHookSettings<
+            Register,
+            BasicFunctionInfo,
+            GenericCallingConvention<Register>,
+        >,
+    ) -> Result<GuardedHook, FunctionHookError<Register>> {
+        create_branch_hook_with_callback::<
+            JitX64,
+            x64::Register,
+            LengthDisassemblerX64,
+            CodeRewriterX64,
+            StaticLinkedBuffer,
+            BuffersFactory,
+            BasicFunctionInfo,
+            GenericCallingConvention<Register>,
+        >(settings, |_| {})
+    }
+
+    #[test]
+    fn dropped_hooks_free_their_thread_local_flag() {
+        unsafe {
+            let add_addr = alloc_function(&CALL_CALCULATOR_ADD_MSFT_X64).unwrap();
+            let add: Add = transmute(add_addr + CALL_CALCULATOR_ADD_MSFT_X64_FUN_OFFSET);
+            let hook_addr = add_addr + CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET;
+            RECURSIVE_ADD_ADDR.store(add as usize, Ordering::Relaxed);
+
+            let basic_settings = BasicHookSettings::new_with_scratch_register(
+                hook_addr,
+                add_hook_impl_recursive as *const () as usize,
+                Some(x64::Register::r8),
+            );
+
+            let settings = FunctionHookSettings::<
+                Register,
+                BasicFunctionInfo,
+                GenericCallingConvention<Register>,
+            >::new(
+                basic_settings,
+                true,
+                ADD_INFO,
+                CallingConvention::microsoft_x64(),
+                CallingConvention::microsoft_x64(),
+                None,
+            )
+            .with_reentrancy_guard();
+
+            for _ in 0..NUM_HOOKS {
+                // Freed flags are quarantined before they are reused.
+                let hook = match create_guarded_hook(&settings) {
+                    Err(FunctionHookError::ThreadLocalFlagUnavailable) => {
+                        std::thread::sleep(Duration::from_millis(RELEASE_QUARANTINE_MS + 100));
+                        create_guarded_hook(&settings)
+                    }
+                    result => result,
+                }
+                .unwrap()
+                .with_uninstall_on_drop(true);
+
+                assert_eq!(4, add(1, 2));
+                drop(hook);
+                assert_eq!(3, add(1, 2));
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+mod asm;
+
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+mod tests {
+    use crate::asm;
+    use crate::asm::calculator::Add;
+    use asm::assemble_function::alloc_function;
+    use asm::calculator::CALCULATOR_ADD_MSFT_X64;
+    use core::mem::transmute;
+    use core::ptr::null_mut;
+    use reloaded_hooks_portable::api::buffers::linux_remote_buffer::RemoteBuffer;
+    use reloaded_hooks_portable::api::buffers::linux_remote_buffer_factory::RemoteBufferFactory;
+    use reloaded_hooks_portable::api::hooks::assembly::assembly_hook::create_assembly_hook;
+    use reloaded_hooks_portable::api::platforms::linux_remote::register_remote_process;
+    use reloaded_hooks_portable::api::settings::assembly_hook_settings::AssemblyHookSettings;
+    use reloaded_hooks_x86_sys::x64::{
+        self, jit::JitX64, length_disassembler::LengthDisassemblerX64, rewriter::CodeRewriterX64,
+    };
+
+    /// Resumes the child, and waits for it to stop or exit.
+    fn continue_child(pid: libc::pid_t) -> i32 {
+        let mut status = 0;
+        unsafe {
+            libc::ptrace(libc::PTRACE_CONT, pid, null_mut::<libc::c_void>(), 0);
+            libc::waitpid(pid, &mut status, 0);
+        }
+
+        status
+    }
+
+    /// Copies `code` to a newly mapped page of our own process.
+    unsafe fn map_local_code(code: &[u8]) -> usize {
+        let page = libc::mmap(
+            null_mut(),
+            4096,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        assert_ne!(page, libc::MAP_FAILED);
+        core::ptr::copy_nonoverlapping(code.as_ptr(), page as *mut u8, code.len());
+        page as usize
+    }
+
+    #[test]
+    fn hook_in_remote_process_x64() {
+        // Allocate the function before forking, so it exists at the same address in the child.
+        let add_addr = alloc_function(&CALCULATOR_ADD_MSFT_X64).unwrap();
+        let add: Add = unsafe { transmute(add_addr) };
+
+        let pid = unsafe { libc::fork() };
+        if pid == 0 {
+            // Child: stop for the parent to hook us, then stop again for it to unhook us.
+            unsafe {
+                libc::ptrace(libc::PTRACE_TRACEME, 0, null_mut::<libc::c_void>(), 0);
+                libc::raise(libc::SIGSTOP);
+                let hooked = add(1, 1);
+                libc::raise(libc::SIGSTOP);
+                let unhooked = add(1, 1);
+                libc::_exit((hooked * 10 + unhooked) as i32);
+            }
+        }
+
+        let mut status = 0;
+        unsafe { libc::waitpid(pid, &mut status, 0) };
+        assert!(libc::WIFSTOPPED(status));
+        assert!(register_remote_process(pid));
+
+        // The custom code is mapped after forking, so only exists in our process.
+        let slice = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let code = unsafe { map_local_code(slice) };
+        let settings = AssemblyHookSettings::new_minimal(add_addr, code, slice.len(), 13)
+            .with_scratch_register(x64::Register::r8);
+
+        let hook = unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                RemoteBuffer,
+                RemoteBufferFactory,
+            >(&settings)
+            .unwrap()
+            .with_uninstall_on_drop(true)
+        };
+
+        // Our own copy of the function is untouched.
+        assert_eq!(2, add(1, 1));
+
+        assert!(libc::WIFSTOPPED(continue_child(pid)));
+        drop(hook);
+
+        let status = continue_child(pid);
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(32, libc::WEXITSTATUS(status));
+    }
+}
@@ -0,0 +1,710 @@
+//! Creates hooks in simulated memory, such that hook creation can be tested on any host.
+//! The produced code is never executed, it is disassembled and compared against the expected code.
+
+mod asm;
+
+mod tests {
+    use crate::asm::calculator::{
+        CALCULATOR_ADD_CDECL_X86, CALCULATOR_ADD_MSFT_X64, CALL_CALCULATOR_ADD_CDECL_X86,
+        CALL_CALCULATOR_ADD_CDECL_X86_CALL_OFFSET,
+        CALL_CALCULATOR_ADD_CDECL_X86_TARGET_FUNCTION_OFFSET, CALL_CALCULATOR_ADD_MSFT_X64,
+        CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET,
+        CALL_CALCULATOR_ADD_MSFT_X64_TARGET_FUNCTION_OFFSET,
+    };
+    use core::time::Duration;
+    use iced_x86::{Decoder, DecoderOptions, Formatter, IntelFormatter};
+    use reloaded_hooks_portable::api::buffers::simulated_buffer::SimulatedBuffer;
+    use reloaded_hooks_portable::api::buffers::simulated_buffer_factory::SimulatedBufferFactory;
+    use reloaded_hooks_portable::api::calling_convention_info::GenericCallingConvention;
+    use reloaded_hooks_portable::api::errors::assembly_hook_error::AssemblyHookError;
+    use reloaded_hooks_portable::api::errors::hook_builder_error::HookBuilderError;
+    use reloaded_hooks_portable::api::function_info::{BasicFunctionInfo, ParameterType};
+    use reloaded_hooks_portable::api::hooks::assembly::assembly_hook::create_assembly_hook;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook::create_branch_hook_with_callback;
+    use reloaded_hooks_portable::api::hooks::branch::branch_hook_fast::create_branch_hook_fast_with_callback;
+    use reloaded_hooks_portable::api::hooks::common_hook::CommonHook;
+    use reloaded_hooks_portable::api::platforms::simulated_memory::{
+        get_simulated_memory, register_simulated_memory,
+    };
+    use reloaded_hooks_portable::api::settings::assembly_hook_settings::{
+        AssemblyHookSettings, BranchIntoStolenRegionBehaviour, ForeignHookBehaviour,
+    };
+    use reloaded_hooks_portable::api::settings::basic_hook_settings::BasicHookSettings;
+    use reloaded_hooks_portable::api::settings::function_hook_settings::FunctionHookSettings;
+    use reloaded_hooks_x86_sys::x64::{
+        self, jit::JitX64, length_disassembler::LengthDisassemblerX64, rewriter::CodeRewriterX64,
+    };
+    use reloaded_hooks_x86_sys::x86::{
+        self, jit::JitX86, length_disassembler::LengthDisassemblerX86, rewriter::CodeRewriterX86,
+    };
+
+    static ADD_INFO_X64: BasicFunctionInfo =
+        BasicFunctionInfo::new(&[ParameterType::i64, ParameterType::i64]);
+
+    static ADD_INFO_X86: BasicFunctionInfo =
+        BasicFunctionInfo::new(&[ParameterType::i32, ParameterType::i32]);
+
+    /// Disassembles `len` bytes of simulated memory.
+    fn disassemble(address: usize, len: usize, bitness: u32) -> Vec<String> {
+        let code = get_simulated_memory().read(address, len);
+        let mut decoder = Decoder::with_ip(bitness, &code, address as u64, DecoderOptions::NONE);
+        let mut formatter = IntelFormatter::new();
+        decoder
+            .iter()
+            .map(|instruction| {
+                let mut result = String::new();
+                formatter.format(&instruction, &mut result);
+                result
+            })
+            .collect()
+    }
+
+    #[test]
+    fn assembly_hook_x64() {
+        register_simulated_memory();
+        let memory = get_simulated_memory();
+        let add_addr = 0x1000_0000;
+        memory.map_code(add_addr, &CALCULATOR_ADD_MSFT_X64);
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(add_addr, code.as_ptr() as usize, code.len(), 13)
+                .with_scratch_register(x64::Register::r8);
+
+        let hook = unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+            .with_uninstall_on_drop(true)
+        };
+
+        // Stolen bytes not needed for the jump are filled with nops.
+        let mut hooked = vec!["jmp 0000000010002000h"];
+        hooked.extend(["nop"; 8]);
+        hooked.push("ret");
+
+        // Stubs start with `endbr64`, so they can be reached via indirect branches with CET.
+        let mut enabled = vec!["endbr64", "inc rcx", "mov rax,rcx", "add rax,rdx"];
+        enabled.extend(["nop"; 7]);
+        enabled.push("jmp 000000001000000Dh");
+
+        let mut disabled = enabled.clone();
+        disabled.remove(1);
+
+        let stub = add_addr + 0x2000;
+        assert_eq!(disassemble(add_addr, 14, 64), hooked);
+        assert_eq!(disassemble(stub, 25, 64), enabled);
+
+        // Assembly hooks don't count calls, so there is nothing to wait on.
+        assert_eq!(hook.disable_and_wait(Duration::ZERO), None);
+        assert_eq!(disassemble(add_addr, 14, 64), hooked);
+        assert_eq!(disassemble(stub, 22, 64), disabled);
+
+        hook.enable();
+        assert_eq!(disassemble(stub, 25, 64), enabled);
+
+        drop(hook);
+        assert_eq!(memory.read(add_addr, 14), CALCULATOR_ADD_MSFT_X64);
+        assert_eq!(memory.num_unprotected_regions(), 0);
+    }
+
+    #[test]
+    fn assembly_hook_relocates_call_x64() {
+        register_simulated_memory();
+        let base = 0x2000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_MSFT_X64);
+
+        let hook_addr = base + 7;
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(hook_addr, code.as_ptr() as usize, code.len(), 9)
+                .with_scratch_register(x64::Register::r8);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        // Steal `sub rsp, 28h` and `call add_fn` from `add_wrapper`.
+        assert_eq!(
+            disassemble(hook_addr, 9, 64),
+            ["jmp 0000000020002000h", "nop", "nop", "nop", "nop"]
+        );
+        assert_eq!(
+            disassemble(base + 0x2000, 21, 64),
+            [
+                "endbr64",
+                "inc rcx",
+                "sub rsp,28h",
+                "call 0000000020000000h",
+                "jmp 0000000020000010h",
+            ]
+        );
+    }
+
+    #[test]
+    fn assembly_hook_keeps_endbr64_at_hook_address_x64() {
+        register_simulated_memory();
+        let base = 0x7000_0000;
+        let function = [
+            0xF3, 0x0F, 0x1E, 0xFA, // endbr64
+            0x48, 0x89, 0xC8, // mov rax, rcx
+            0x48, 0x01, 0xD0, // add rax, rdx
+            0xC3, // ret
+        ];
+        get_simulated_memory().map_code(base, &function);
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 10)
+                .with_scratch_register(x64::Register::r8);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        // Indirect calls must still land on `endbr64`, so the hook goes after it.
+        assert_eq!(
+            disassemble(base, 11, 64),
+            ["endbr64", "jmp 0000000070002000h", "nop", "ret"]
+        );
+        assert_eq!(
+            disassemble(base + 0x2000, 18, 64),
+            [
+                "endbr64",
+                "inc rcx",
+                "mov rax,rcx",
+                "add rax,rdx",
+                "jmp 000000007000000Ah",
+            ]
+        );
+    }
+
+    /// `add_fn`, with a foreign hook in front of it, jumping to a foreign stub at offset 0x100.
+    fn with_foreign_hook(foreign_hook: &[u8]) -> Vec<u8> {
+        let mut code = vec![0xCC; 0x100];
+        code[..foreign_hook.len()].copy_from_slice(foreign_hook);
+        code.extend_from_slice(&CALCULATOR_ADD_MSFT_X64);
+        code
+    }
+
+    fn create_assembly_hook_x64(
+        settings: &AssemblyHookSettings<x64::Register>,
+    ) -> Result<
+        CommonHook<SimulatedBuffer, JitX64, x64::Register, SimulatedBufferFactory>,
+        AssemblyHookError<x64::Register>,
+    > {
+        unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(settings)
+        }
+    }
+
+    #[test]
+    fn assembly_hook_chains_in_front_of_foreign_hook_x64() {
+        register_simulated_memory();
+        let base = 0x8000_0000;
+        let memory = get_simulated_memory();
+        memory.map_code(base, &with_foreign_hook(&[0xE9, 0xFB, 0x00, 0x00, 0x00])); // jmp base+0x100
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 13)
+                .with_scratch_register(x64::Register::r8);
+
+        let hook = create_assembly_hook_x64(&settings)
+            .unwrap()
+            .with_uninstall_on_drop(true);
+
+        // Only the foreign hook is overwritten, and our code then continues into the foreign stub.
+        assert_eq!(disassemble(base, 6, 64), ["jmp 0000000080002000h", "int3"]);
+        assert_eq!(
+            disassemble(base + 0x2000, 17, 64),
+            [
+                "endbr64",
+                "inc rcx",
+                "jmp 0000000080000100h",
+                "jmp 0000000080000005h",
+            ]
+        );
+
+        drop(hook);
+        assert_eq!(memory.read(base, 5), [0xE9, 0xFB, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn assembly_hook_hooks_foreign_hook_continuation_x64() {
+        register_simulated_memory();
+        let base = 0x9000_0000;
+        let mut jmp_rip = vec![0xFF, 0x25, 0x00, 0x00, 0x00, 0x00]; // jmp [rip+0]
+        jmp_rip.extend_from_slice(&(base as u64 + 0x100).to_le_bytes());
+        get_simulated_memory().map_code(base, &with_foreign_hook(&jmp_rip));
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 6)
+                .with_scratch_register(x64::Register::r8)
+                .with_foreign_hook_behaviour(ForeignHookBehaviour::HookContinuation);
+
+        let _hook = create_assembly_hook_x64(&settings).unwrap();
+
+        // The foreign hook is left alone, and the code it jumps to is hooked instead.
+        assert_eq!(get_simulated_memory().read(base, 14), jmp_rip);
+        assert_eq!(
+            disassemble(base + 0x100, 6, 64),
+            ["jmp 0000000090002000h", "nop"]
+        );
+        assert_eq!(
+            disassemble(base + 0x2000, 18, 64),
+            [
+                "endbr64",
+                "inc rcx",
+                "mov rax,rcx",
+                "add rax,rdx",
+                "jmp 0000000090000106h",
+            ]
+        );
+    }
+
+    #[test]
+    fn assembly_hook_relocates_short_jump_x64() {
+        register_simulated_memory();
+        let base = 0xA000_0000;
+        get_simulated_memory().map_code(base, &with_foreign_hook(&[0xEB, 0x7E])); // jmp base+0x80
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 5)
+                .with_scratch_register(x64::Register::r8);
+
+        let _hook = create_assembly_hook_x64(&settings).unwrap();
+
+        // A short jump to anything other than a hot-patch pad is the function's own code.
+        assert_eq!(disassemble(base, 5, 64), ["jmp 00000000A0002000h"]);
+        assert_eq!(
+            disassemble(base + 0x2000, 24, 64),
+            [
+                "endbr64",
+                "inc rcx",
+                "jmp 00000000A0000080h",
+                "int3",
+                "int3",
+                "int3",
+                "jmp 00000000A0000005h",
+                "endbr64",
+            ]
+        );
+    }
+
+    #[test]
+    fn assembly_hook_relocates_hot_patch_foreign_hook_x64() {
+        register_simulated_memory();
+        let base = 0xD000_0000;
+        let mut function = vec![0xCC; 0x100];
+        function[0x10..0x15].copy_from_slice(&[0xE9, 0xEB, 0x00, 0x00, 0x00]); // jmp base+0x100
+        function[0x15..0x17].copy_from_slice(&[0xEB, 0xF9]); // jmp base+0x10
+        function.extend_from_slice(&CALCULATOR_ADD_MSFT_X64);
+        get_simulated_memory().map_code(base, &function);
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(base + 0x15, code.as_ptr() as usize, code.len(), 5)
+                .with_scratch_register(x64::Register::r8);
+
+        let _hook = create_assembly_hook_x64(&settings).unwrap();
+
+        // Our jump doesn't fit over the 2 byte foreign hook, so it is relocated instead.
+        // This is known before a stub to chain in front of it is built, so the buffer allocated for
+        // that stub (at base + 0x2000) is left untouched.
+        assert_eq!(disassemble(base + 0x15, 5, 64), ["jmp 00000000D0004000h"]);
+        assert!(get_simulated_memory()
+            .read(base + 0x2000, 0x100)
+            .iter()
+            .all(|&byte| byte == 0));
+        assert_eq!(
+            disassemble(base + 0x4000, 24, 64),
+            [
+                "endbr64",
+                "inc rcx",
+                "jmp 00000000D0000010h",
+                "int3",
+                "int3",
+                "int3",
+                "jmp 00000000D000001Ah",
+                "endbr64",
+            ]
+        );
+    }
+
+    /// A function which loops back to its 2nd instruction.
+    static LOOP_AT_ENTRY_X64: [u8; 14] = [
+        0x48, 0x83, 0xEC, 0x10, // sub rsp, 10h
+        0x8B, 0x44, 0x24, 0x04, // mov eax, [rsp+4]
+        0x48, 0x89, 0xD8, // mov rax, rbx
+        0xEB, 0xF7, // jmp 4
+        0xC3, // ret
+    ];
+
+    #[test]
+    fn assembly_hook_fails_on_branch_into_stolen_region_x64() {
+        register_simulated_memory();
+        let base = 0xB000_0000;
+        get_simulated_memory().map_code(base, &LOOP_AT_ENTRY_X64);
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 13)
+                .with_scratch_register(x64::Register::r8)
+                .with_branch_scan_length(8);
+
+        assert!(matches!(
+            create_assembly_hook_x64(&settings),
+            Err(AssemblyHookError::HookBuilderError(
+                HookBuilderError::BranchIntoStolenRegion(0xB000_000B, 0xB000_0004)
+            ))
+        ));
+    }
+
+    #[test]
+    fn assembly_hook_moves_to_branch_target_x64() {
+        register_simulated_memory();
+        let base = 0xC000_0000;
+        get_simulated_memory().map_code(base, &LOOP_AT_ENTRY_X64);
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 13)
+                .with_scratch_register(x64::Register::r8)
+                .with_branch_scan_length(8)
+                .with_branch_into_stolen_region_behaviour(
+                    BranchIntoStolenRegionBehaviour::HookAtBranchTarget,
+                );
+
+        let _hook = create_assembly_hook_x64(&settings).unwrap();
+
+        // The hook is placed at the loop head, the code before it is left alone.
+        // The loop's own branch is relocated into the stub and still targets the hook.
+        assert_eq!(
+            disassemble(base, 14, 64),
+            [
+                "sub rsp,10h",
+                "jmp 00000000C0002000h",
+                "nop",
+                "nop",
+                "nop",
+                "nop",
+                "ret",
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_hook_fast_x64() {
+        register_simulated_memory();
+        let base = 0x3000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_MSFT_X64);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET;
+        let settings = BasicHookSettings::new_with_scratch_register(
+            hook_addr,
+            base + CALL_CALCULATOR_ADD_MSFT_X64_TARGET_FUNCTION_OFFSET,
+            Some(x64::Register::r8),
+        );
+
+        let mut original = 0;
+        unsafe {
+            create_branch_hook_fast_with_callback::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings, |x| original = x)
+            .unwrap();
+        }
+
+        assert_eq!(original, base);
+        assert_eq!(
+            disassemble(hook_addr, 5, 64),
+            ["call 0000000030000015h"] // target_function
+        );
+    }
+
+    #[test]
+    fn branch_hook_with_wrapper_x64() {
+        register_simulated_memory();
+        let base = 0x4000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_MSFT_X64);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_MSFT_X64_CALL_OFFSET;
+        let basic_settings = BasicHookSettings::new_with_scratch_register(
+            hook_addr,
+            0x1234_5678_9ABC,
+            Some(x64::Register::r8),
+        );
+
+        let settings = FunctionHookSettings::<
+            x64::Register,
+            BasicFunctionInfo,
+            GenericCallingConvention<x64::Register>,
+        >::new(
+            basic_settings,
+            true,
+            ADD_INFO_X64,
+            x64::calling_convention::CallingConvention::microsoft_x64(),
+            x64::calling_convention::CallingConvention::system_v(),
+            None,
+        );
+
+        let mut original = 0;
+        let hook = unsafe {
+            create_branch_hook_with_callback::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+                BasicFunctionInfo,
+                GenericCallingConvention<x64::Register>,
+            >(&settings, |x| original = x)
+            .unwrap()
+        };
+
+        // Callee saved registers in Microsoft x64 which aren't callee saved in SystemV are backed up.
+        // With 'multipushpop', the general purpose ones are pushed with a single 'sub'.
+        #[cfg(not(feature = "multipushpop"))]
+        let (push, pop, wrapper_len) = (["push rdi", "push rsi"], ["pop rsi", "pop rdi"], 179);
+        #[cfg(feature = "multipushpop")]
+        let (push, pop, wrapper_len) = (
+            ["sub rsp,10h", "mov [rsp],rsi", "mov [rsp+8],rdi"],
+            ["mov rsi,[rsp]", "mov rdi,[rsp+8]", "add rsp,10h"],
+            201,
+        );
+
+        let wrapper = [
+            &["endbr64"][..],
+            &push,
+            &[
+                "movdqu [rsp-10h],xmm6",
+                "movdqu [rsp-20h],xmm7",
+                "movdqu [rsp-30h],xmm8",
+                "movdqu [rsp-40h],xmm9",
+                "movdqu [rsp-50h],xmm10",
+                "movdqu [rsp-60h],xmm11",
+                "movdqu [rsp-70h],xmm12",
+                "movdqu [rsp-80h],xmm13",
+                "movdqu [rsp-90h],xmm14",
+                "movdqu [rsp-0A0h],xmm15",
+                "sub rsp,0A8h",
+                "mov rsi,rdx",
+                "mov rdi,rcx",
+                "mov rax,123456789ABCh",
+                "call rax",
+                "movups xmm15,[rsp+8]",
+                "movups xmm14,[rsp+18h]",
+                "movups xmm13,[rsp+28h]",
+                "movups xmm12,[rsp+38h]",
+                "movups xmm11,[rsp+48h]",
+                "movups xmm10,[rsp+58h]",
+                "movups xmm9,[rsp+68h]",
+                "movups xmm8,[rsp+78h]",
+                "movups xmm7,[rsp+88h]",
+                "movups xmm6,[rsp+98h]",
+                "sub rsp,0FFFFFFFFFFFFFF58h",
+            ][..],
+            &pop,
+            &["ret"],
+        ]
+        .concat();
+
+        let stub = base + 0x2000;
+        assert_eq!(original, base);
+        assert_eq!(disassemble(hook_addr, 5, 64), ["call 0000000040002000h"]);
+        assert_eq!(disassemble(stub, wrapper_len, 64), wrapper);
+
+        hook.disable();
+        assert_eq!(
+            disassemble(stub, 9, 64),
+            ["endbr64", "jmp 0000000040000000h"] // add_fn
+        );
+
+        hook.enable();
+        assert_eq!(disassemble(stub, wrapper_len, 64), wrapper);
+    }
+
+    #[test]
+    fn assembly_hook_x86() {
+        register_simulated_memory();
+        let add_addr = 0x5000_0000;
+        get_simulated_memory().map_code(add_addr, &CALCULATOR_ADD_CDECL_X86);
+
+        let code = &[0xffu8, 0x44, 0x24, 0x08]; // inc dword ptr [esp + 8]
+        let settings =
+            AssemblyHookSettings::new_minimal(add_addr, code.as_ptr() as usize, code.len(), 6)
+                .with_scratch_register(x86::Register::ecx);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitX86,
+                x86::Register,
+                LengthDisassemblerX86,
+                CodeRewriterX86,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        // Steal `push ebp`, `mov ebp, esp` and `mov eax, [ebp + 8]`.
+        assert_eq!(
+            disassemble(add_addr, 11, 32),
+            [
+                "jmp 50002000h",
+                "nop",
+                "add eax,[ebp+0Ch]",
+                "pop ebp",
+                "ret"
+            ]
+        );
+        assert_eq!(
+            disassemble(add_addr + 0x2000, 19, 32),
+            [
+                "endbr32",
+                "inc dword ptr [esp+8]",
+                "push ebp",
+                "mov ebp,esp",
+                "mov eax,[ebp+8]",
+                "jmp 50000006h",
+            ]
+        );
+    }
+
+    #[test]
+    fn assembly_hook_patches_pc_thunk_x86() {
+        register_simulated_memory();
+        let base = 0xE000_0000;
+        let function = [
+            0x8B, 0x1C, 0x24, // __x86.get_pc_thunk.bx: mov ebx, [esp]
+            0xC3, // ret
+            0xE8, 0xF7, 0xFF, 0xFF, 0xFF, // call __x86.get_pc_thunk.bx
+            0x81, 0xC3, 0x00, 0x10, 0x00, 0x00, // add ebx, 1000h
+            0xC3, // ret
+        ];
+        get_simulated_memory().map_code(base, &function);
+
+        let code = &[0xffu8, 0x44, 0x24, 0x08]; // inc dword ptr [esp + 8]
+        let settings =
+            AssemblyHookSettings::new_minimal(base + 4, code.as_ptr() as usize, code.len(), 5)
+                .with_scratch_register(x86::Register::ecx);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitX86,
+                x86::Register,
+                LengthDisassemblerX86,
+                CodeRewriterX86,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        // The thunk would return an address inside the stub, so it is replaced with the original
+        // return address.
+        assert_eq!(disassemble(base + 4, 5, 32), ["jmp 0E0002000h"]);
+        assert_eq!(
+            disassemble(base + 0x2000, 18, 32),
+            [
+                "endbr32",
+                "inc dword ptr [esp+8]",
+                "mov ebx,0E0000009h",
+                "jmp 0E0000009h",
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_hook_with_wrapper_x86() {
+        register_simulated_memory();
+        let base = 0x6000_0000;
+        get_simulated_memory().map_code(base, &CALL_CALCULATOR_ADD_CDECL_X86);
+
+        let hook_addr = base + CALL_CALCULATOR_ADD_CDECL_X86_CALL_OFFSET;
+        let basic_settings = BasicHookSettings::new_with_scratch_register(
+            hook_addr,
+            base + CALL_CALCULATOR_ADD_CDECL_X86_TARGET_FUNCTION_OFFSET,
+            Some(x86::Register::ecx),
+        );
+
+        let settings = FunctionHookSettings::<
+            x86::Register,
+            BasicFunctionInfo,
+            GenericCallingConvention<x86::Register>,
+        >::new(
+            basic_settings,
+            true,
+            ADD_INFO_X86,
+            x86::calling_convention::CallingConvention::cdecl(),
+            x86::calling_convention::CallingConvention::fastcall(),
+            None,
+        );
+
+        let mut original = 0;
+        let _hook = unsafe {
+            create_branch_hook_with_callback::<
+                JitX86,
+                x86::Register,
+                LengthDisassemblerX86,
+                CodeRewriterX86,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+                BasicFunctionInfo,
+                GenericCallingConvention<x86::Register>,
+            >(&settings, |x| original = x)
+            .unwrap()
+        };
+
+        assert_eq!(original, base);
+        assert_eq!(disassemble(hook_addr, 5, 32), ["call 60002000h"]);
+        assert_eq!(
+            disassemble(base + 0x2000, 20, 32),
+            [
+                "endbr32",
+                "mov edx,[esp+4]",
+                "mov ecx,[esp+8]",
+                "mov eax,6000001Ch", // target_function
+                "call eax",
+                "ret",
+            ]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Creates hooks in simulated memory, where no buffer can be allocated within relative jump range
+//! of the hooked code.
+//!
+//! Separate from `simulated_hooks.rs`, as the buffers then end up at fixed addresses, which may
+//! overlap with code mapped by other tests in the same process.
+
+mod asm;
+
+#[cfg(target_pointer_width = "64")]
+mod tests {
+    use crate::asm::calculator::CALCULATOR_ADD_MSFT_X64;
+    use iced_x86::{Decoder, DecoderOptions, Formatter, IntelFormatter};
+    use reloaded_hooks_portable::api::buffers::simulated_buffer::SimulatedBuffer;
+    use reloaded_hooks_portable::api::buffers::simulated_buffer_factory::SimulatedBufferFactory;
+    use reloaded_hooks_portable::api::hooks::assembly::assembly_hook::create_assembly_hook;
+    use reloaded_hooks_portable::api::platforms::simulated_memory::{
+        get_simulated_memory, register_simulated_memory,
+    };
+    use reloaded_hooks_portable::api::settings::assembly_hook_settings::AssemblyHookSettings;
+    use reloaded_hooks_x86_sys::x64::{
+        self, jit::JitX64, length_disassembler::LengthDisassemblerX64, rewriter::CodeRewriterX64,
+    };
+
+    /// Disassembles `len` bytes of simulated memory.
+    fn disassemble(address: usize, len: usize) -> Vec<String> {
+        let code = get_simulated_memory().read(address, len);
+        let mut decoder = Decoder::with_ip(64, &code, address as u64, DecoderOptions::NONE);
+        let mut formatter = IntelFormatter::new();
+        decoder
+            .iter()
+            .map(|instruction| {
+                let mut result = String::new();
+                formatter.format(&instruction, &mut result);
+                result
+            })
+            .collect()
+    }
+
+    #[test]
+    fn assembly_hook_relocates_foreign_hook_out_of_range_x64() {
+        register_simulated_memory();
+        let base = 0x10_0000_0000;
+        let memory = get_simulated_memory();
+        let mut function = vec![0xCC; 0x100];
+        function[..5].copy_from_slice(&[0xE9, 0xFB, 0x00, 0x00, 0x00]); // jmp base+0x100
+        function.extend_from_slice(&CALCULATOR_ADD_MSFT_X64);
+        memory.map_code(base, &function);
+        memory.reserve(base, 0x1_0000_0000);
+
+        let code = &[0x48u8, 0xFF, 0xC1]; // inc rcx
+        let settings =
+            AssemblyHookSettings::new_minimal(base, code.as_ptr() as usize, code.len(), 13)
+                .with_scratch_register(x64::Register::r8);
+
+        let _hook = unsafe {
+            create_assembly_hook::<
+                JitX64,
+                x64::Register,
+                LengthDisassemblerX64,
+                CodeRewriterX64,
+                SimulatedBuffer,
+                SimulatedBufferFactory,
+            >(&settings)
+            .unwrap()
+        };
+
+        // The stub is out of relative jump range, and the absolute jump doesn't fit over the
+        // foreign hook; so the foreign hook is relocated instead.
+        assert_eq!(
+            disassemble(base, 13),
+            [
+                "jmp qword ptr [40002000h]",
+                "nop",
+                "nop",
+                "nop",
+                "nop",
+                "nop",
+                "nop"
+            ]
+        );
+
+        let stub = u64::from_le_bytes(memory.read(0x4000_2000, 8).try_into().unwrap());
+        assert_eq!(
+            disassemble(stub as usize, 28),
+            [
+                "endbr64",
+                "inc rcx",
+                "mov r8,1000000100h",
+                "jmp r8",
+                "int3",
+                "int3",
+                "int3",
+                "int3",
+                "int3",
+                "int3",
+                "int3",
+                "int3",
+            ]
+        );
+    }
+}
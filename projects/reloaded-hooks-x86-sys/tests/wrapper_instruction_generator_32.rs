@@ -3,7 +3,7 @@ pub mod tests {
     use core::mem::size_of;
 
     use reloaded_hooks_portable::api::calling_convention_info::{
-        GenericCallingConvention, StackCleanup, StackParameterOrder,
+        GenericCallingConvention, ParameterRegisterAssignment, StackCleanup, StackParameterOrder,
     };
     use reloaded_hooks_portable::api::errors::wrapper_generation_error::WrapperGenerationError;
     use reloaded_hooks_portable::api::function_info::{BasicFunctionInfo, ParameterType};
@@ -144,12 +144,27 @@ pub mod tests {
 
         assert!(result.is_ok());
         let vec: Vec<Operation<Register>> = result.unwrap();
-        assert_eq!(vec.len(), 5);
-        assert_eq!(vec[0], Push::new(edx).into()); // push right param
-        assert_eq!(vec[1], Push::new(ecx).into()); // push left param
-        assert_eq!(vec[2], CallRel::new(4096).into());
-        assert_eq!(vec[3], StackAlloc::new(-8).into()); // pop right param and left param
-        assert_eq!(vec[4], Return::new(0).into()); // nothing to pop
+
+        #[cfg(not(feature = "multipushpop"))]
+        {
+            assert_eq!(vec.len(), 5);
+            assert_eq!(vec[0], Push::new(edx).into()); // push right param
+            assert_eq!(vec[1], Push::new(ecx).into()); // push left param
+        }
+
+        #[cfg(feature = "multipushpop")]
+        {
+            assert_eq!(vec.len(), 4);
+            assert_eq!(
+                vec[0],
+                Operation::MultiPush(smallvec::smallvec![Push::new(edx), Push::new(ecx)])
+            ); // push right param, then left param
+        }
+
+        let vec = &vec[vec.len() - 3..];
+        assert_eq!(vec[0], CallRel::new(4096).into());
+        assert_eq!(vec[1], StackAlloc::new(-8).into()); // pop right param and left param
+        assert_eq!(vec[2], Return::new(0).into()); // nothing to pop
     }
 
     // EXTRA X86-LIKE TESTS //
@@ -228,11 +243,90 @@ pub mod tests {
         assert_push_stack(&vec[2], 32, 4); // push left param
         assert_push_stack(&vec[3], 40, 4); // push right param
         assert_eq!(vec[4], CallRel::new(4096).into());
-        assert_eq!(vec[5], MovFromStack::new(-20, xmm0).into()); // callee restore xmm
+        assert_eq!(vec[5], MovFromStack::new(20, xmm0).into()); // callee restore xmm
         assert_eq!(vec[6], StackAlloc::new(-(nint as i32 * 2) - 16 - 12).into()); // caller stack cleanup (2 cdecl parameters) + 1 xmm reg + padding from xmm callee save
         assert_eq!(vec[7], Return::new(0).into());
     }
 
+    #[test]
+    fn sse_fastcall_to_cdecl_float_return_optimized() {
+        let result = two_parameters_with_float_return(
+            &SSE_FASTCALL,
+            CallingConvention::cdecl(),
+            ParameterType::f64,
+        );
+
+        assert!(result.is_ok());
+        let vec: Vec<Operation<Register>> = result.unwrap();
+        assert_eq!(vec.len(), 8);
+        assert_eq!(vec[2], CallRel::new(4096).into());
+        assert_eq!(vec[3], StackAlloc::new(8).into());
+        assert_eq!(vec[4], MovFloatToStack::new(0, 8, xmm0).into()); // movsd [esp], xmm0
+        assert_eq!(vec[5], MovFloatFromStack::new(0, 8, st0).into()); // fld qword [esp]
+        assert_eq!(vec[6], StackAlloc::new(-8).into());
+        assert_eq!(vec[7], Return::new(0).into());
+
+        let code = JitX86::compile(0, &vec).unwrap();
+        assert!(hex::encode(code).ends_with("83ec08f20f110424dd042483ecf8c3"));
+    }
+
+    #[test]
+    fn cdecl_to_sse_fastcall_float_return_optimized() {
+        let result = two_parameters_with_float_return(
+            CallingConvention::cdecl(),
+            &SSE_FASTCALL,
+            ParameterType::f32,
+        );
+
+        assert!(result.is_ok());
+        let vec: Vec<Operation<Register>> = result.unwrap();
+
+        // Parameter pushes may be merged with the 'multipushpop' feature, so check from the call onwards.
+        let call = vec.len() - 6;
+        assert_eq!(vec[call], CallRel::new(4096).into());
+        assert_eq!(vec[call + 1], StackAlloc::new(4).into());
+        assert_eq!(vec[call + 2], MovFloatToStack::new(0, 4, st0).into()); // fstp dword [esp]
+        assert_eq!(vec[call + 3], MovFloatFromStack::new(0, 4, xmm0).into()); // movss xmm0, [esp]
+        assert_eq!(vec[call + 4], StackAlloc::new(-12).into()); // merged with caller stack cleanup
+        assert_eq!(vec[call + 5], Return::new(0).into());
+
+        let code = JitX86::compile(0, &vec).unwrap();
+        assert!(hex::encode(code).contains("83ec04d91c24f30f10042483ecf4"));
+    }
+
+    #[test]
+    fn cdecl_to_stdcall_float_return_not_moved() {
+        let result = two_parameters_with_float_return(
+            CallingConvention::cdecl(),
+            CallingConvention::stdcall(),
+            ParameterType::f64,
+        );
+
+        // Both return in st0
+        assert!(result.is_ok());
+        let vec: Vec<Operation<Register>> = result.unwrap();
+        assert!(!vec.iter().any(|x| matches!(
+            x,
+            Operation::MovFloatToStack(_) | Operation::MovFloatFromStack(_)
+        )));
+    }
+
+    fn two_parameters_with_float_return(
+        conv_called: &CallingConvention,
+        conv_current: &CallingConvention,
+        return_type: ParameterType,
+    ) -> Result<Vec<Operation<Register>>, WrapperGenerationError> {
+        let mock_function = BasicFunctionInfo::new(&[ParameterType::i32, ParameterType::i32])
+            .with_return_type(return_type);
+        let options = get_common_options(true, 4096, true, &mock_function);
+
+        generate_wrapper_instructions::<
+            Register,
+            GenericCallingConvention<Register>,
+            BasicFunctionInfo,
+        >(conv_called, conv_current, &options)
+    }
+
     /// Creates the instructions responsible for wrapping one object kind to another.
     ///
     /// # Parameters
@@ -297,6 +391,9 @@ pub mod tests {
             can_generate_relative_jumps: can_generate_relative,
             enable_optimizations: optimized,
             standard_register_size: size_of::<u32>(),
+            in_flight_counter: None,
+            reentrancy_guard: None,
+            caller_filter: None,
         }
     }
 
@@ -313,12 +410,15 @@ pub mod tests {
             float_parameters: &[],
             vector_parameters: &[],
             return_register: eax,
+            float_return_register: Some(st0),
             reserved_stack_space: CDECL_WITH_STACK_EXTRA_SPACE,
             callee_saved_registers: &[ebx, esi, edi, ebp],
             always_saved_registers: &[],
             stack_cleanup: StackCleanup::Caller,
             stack_parameter_order: StackParameterOrder::RightToLeft,
             required_stack_alignment: 1,
+            uses_extension_registers: false,
+            parameter_register_assignment: ParameterRegisterAssignment::Sequential,
         },
     };
 
@@ -328,12 +428,34 @@ pub mod tests {
             float_parameters: &[],
             vector_parameters: &[],
             return_register: eax,
+            float_return_register: Some(st0),
             reserved_stack_space: 0,
             callee_saved_registers: &[ebx, esi, edi, ebp, xmm0],
             always_saved_registers: &[],
             stack_cleanup: StackCleanup::Caller,
             stack_parameter_order: StackParameterOrder::RightToLeft,
             required_stack_alignment: 1,
+            uses_extension_registers: false,
+            parameter_register_assignment: ParameterRegisterAssignment::Sequential,
+        },
+    };
+
+    /// Fastcall variant found in some games, which passes and returns floats in SSE registers.
+    static SSE_FASTCALL: CallingConvention = CallingConvention {
+        convention: GenericCallingConvention::<Register> {
+            int_parameters: &[ecx, edx],
+            float_parameters: &[xmm0, xmm1, xmm2, xmm3],
+            vector_parameters: &[],
+            return_register: eax,
+            float_return_register: Some(xmm0),
+            reserved_stack_space: 0,
+            callee_saved_registers: &[ebx, esi, edi, ebp],
+            always_saved_registers: &[],
+            stack_cleanup: StackCleanup::Callee,
+            stack_parameter_order: StackParameterOrder::RightToLeft,
+            required_stack_alignment: 1,
+            uses_extension_registers: false,
+            parameter_register_assignment: ParameterRegisterAssignment::Sequential,
         },
     };
 }
@@ -0,0 +1,266 @@
+#[cfg(test)]
+pub mod tests {
+    use core::mem::size_of;
+
+    use reloaded_hooks_portable::api::calling_convention_info::{
+        GenericCallingConvention, ParameterRegisterAssignment, StackCleanup, StackParameterOrder,
+    };
+    use reloaded_hooks_portable::api::errors::wrapper_generation_error::WrapperGenerationError;
+    use reloaded_hooks_portable::api::function_info::{BasicFunctionInfo, ParameterType};
+    use reloaded_hooks_portable::api::jit::compiler::Jit;
+    use reloaded_hooks_portable::api::jit::operation::Operation;
+    use reloaded_hooks_portable::api::jit::operation_aliases::*;
+    use reloaded_hooks_portable::api::traits::register_info::RegisterInfo;
+    use reloaded_hooks_portable::api::wrapper_instruction_generator::{
+        generate_wrapper_instructions, WrapperInstructionGeneratorOptions,
+    };
+    use reloaded_hooks_x86_sys::x64::calling_convention::CallingConvention;
+    use reloaded_hooks_x86_sys::x64::jit::JitX64;
+    use reloaded_hooks_x86_sys::x64::Register::{self, *};
+
+    // (int, __m256, double, __m512)
+    static MIXED_VECTOR_PARAMS: &[ParameterType] = &[
+        ParameterType::i32,
+        ParameterType::v256,
+        ParameterType::f64,
+        ParameterType::v512,
+    ];
+
+    #[test]
+    fn vectorcall_to_system_v_unoptimized() {
+        let vec = mixed_vector_parameters(
+            CallingConvention::system_v(),
+            CallingConvention::vectorcall(),
+            false,
+        )
+        .unwrap();
+
+        // vectorcall passes by position: rcx, ymm1, xmm2, zmm3
+        // System V shares float & vector registers: rdi, ymm0, xmm1, zmm2
+        assert_eq!(
+            pushes(&vec)[12..],
+            [
+                Push::new(zmm3),
+                Push::new(xmm2),
+                Push::new(ymm1),
+                Push::new(rcx)
+            ]
+        );
+        assert_eq!(
+            pops(&vec)[..4],
+            [
+                Pop::new(rdi),
+                Pop::new(ymm0),
+                Pop::new(xmm1),
+                Pop::new(zmm2)
+            ]
+        );
+        assert!(JitX64::compile(0, &vec).is_ok());
+    }
+
+    #[test]
+    fn vectorcall_to_system_v_optimized() {
+        let vec = mixed_vector_parameters(
+            CallingConvention::system_v(),
+            CallingConvention::vectorcall(),
+            true,
+        )
+        .unwrap();
+
+        // ymm1 must be read before xmm1 (its lower half) is written, and so on.
+        assert_eq!(
+            movs(&vec),
+            [
+                Mov::new(ymm1, ymm0),
+                Mov::new(xmm2, xmm1),
+                Mov::new(zmm3, zmm2),
+                Mov::new(rcx, rdi),
+            ]
+        );
+        assert!(JitX64::compile(0, &vec).is_ok());
+    }
+
+    #[test]
+    fn system_v_to_vectorcall_optimized() {
+        let vec = mixed_vector_parameters(
+            CallingConvention::vectorcall(),
+            CallingConvention::system_v(),
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            movs(&vec),
+            [
+                Mov::new(zmm2, zmm3),
+                Mov::new(xmm1, xmm2),
+                Mov::new(ymm0, ymm1),
+                Mov::new(rdi, rcx),
+            ]
+        );
+
+        // vmovaps zmm3, zmm2 ; movaps xmm2, xmm1 ; vmovaps ymm1, ymm0
+        let code = hex::encode(JitX64::compile(0, &vec).unwrap());
+        assert!(code.contains("62f17c4828da0f28d1c5fc28c8"));
+    }
+
+    #[test]
+    fn system_v_to_microsoft_x64_positional() {
+        // (float, int64_t) -> xmm0, rdx in Microsoft x64, xmm0, rdi in System V
+        static PARAMS: &[ParameterType] = &[ParameterType::f32, ParameterType::i64];
+        let vec = with_parameters(
+            CallingConvention::microsoft_x64(),
+            CallingConvention::system_v(),
+            true,
+            PARAMS,
+        )
+        .unwrap();
+
+        assert_eq!(movs(&vec), [Mov::new(rdi, rdx), Mov::new(xmm0, xmm0)]);
+        assert!(JitX64::compile(0, &vec).is_ok());
+    }
+
+    #[test]
+    fn system_v_to_microsoft_x64_odd_parameter_count() {
+        // The generator stores the register parameters after the stack parameters, which take
+        // one byte each. With an odd parameter count, the register parameters must still be aligned.
+        static PARAMS: &[ParameterType] =
+            &[ParameterType::i64, ParameterType::i64, ParameterType::i64];
+        let vec = with_parameters(
+            CallingConvention::microsoft_x64(),
+            CallingConvention::system_v(),
+            true,
+            PARAMS,
+        )
+        .unwrap();
+
+        assert_eq!(
+            movs(&vec),
+            [Mov::new(rdx, r8), Mov::new(rsi, rdx), Mov::new(rdi, rcx)]
+        );
+        assert!(JitX64::compile(0, &vec).is_ok());
+    }
+
+    #[test]
+    fn swap_overlapping_registers_optimized() {
+        // (float, __m256) in xmm1, ymm0 to xmm0, ymm1.
+        // xmm0 is the lower half of ymm0, so writing it first would clobber the vector.
+        static PARAMS: &[ParameterType] = &[ParameterType::f32, ParameterType::v256];
+        let vec = with_parameters(&XMM0_YMM1, &XMM1_YMM0, true, PARAMS).unwrap();
+
+        // Swapping the whole vectors also swaps their lower halves.
+        let xchgs: Vec<_> = vec
+            .iter()
+            .filter_map(|x| match x {
+                Operation::Xchg(x) => Some(*x),
+                _ => None,
+            })
+            .collect();
+        assert!(movs(&vec).is_empty());
+        assert_eq!(xchgs.len(), 1);
+        assert_eq!((xchgs[0].register1, xchgs[0].register2), (ymm1, ymm0));
+
+        // The scratch register matches the swapped registers, and isn't a parameter.
+        let scratch = xchgs[0].scratch.unwrap();
+        assert_eq!(scratch.size_in_bytes(), 32);
+        assert!(![ymm0, ymm1].contains(&scratch));
+        assert!(JitX64::compile(0, &vec).is_ok());
+    }
+
+    fn mixed_vector_parameters(
+        conv_called: &GenericCallingConvention<Register>,
+        conv_current: &GenericCallingConvention<Register>,
+        optimized: bool,
+    ) -> Result<Vec<Operation<Register>>, WrapperGenerationError> {
+        with_parameters(conv_called, conv_current, optimized, MIXED_VECTOR_PARAMS)
+    }
+
+    /// Creates the instructions responsible for wrapping one object kind to another.
+    ///
+    /// # Parameters
+    ///
+    /// - `conv_called` - The calling convention to convert to `conv_current`. This is the convention of the function (`options.target_address`) called.
+    /// - `conv_current` - The target convention to which convert to `conv_called`. This is the convention of the function returned.
+    /// - `optimized` - Whether to generate optimized code
+    /// - `parameters` - Parameters of the wrapped function.
+    fn with_parameters(
+        conv_called: &GenericCallingConvention<Register>,
+        conv_current: &GenericCallingConvention<Register>,
+        optimized: bool,
+        parameters: &'static [ParameterType],
+    ) -> Result<Vec<Operation<Register>>, WrapperGenerationError> {
+        let mock_function = BasicFunctionInfo::new(parameters);
+        let options = WrapperInstructionGeneratorOptions {
+            stack_entry_alignment: size_of::<u64>(),
+            target_address: 4096,
+            function_info: &mock_function,
+            injected_parameter: None,
+            jit_capabilities: JitX64::get_jit_capabilities(),
+            can_generate_relative_jumps: true,
+            enable_optimizations: optimized,
+            standard_register_size: size_of::<u64>(),
+            in_flight_counter: None,
+            reentrancy_guard: None,
+            caller_filter: None,
+        };
+
+        generate_wrapper_instructions::<
+            Register,
+            GenericCallingConvention<Register>,
+            BasicFunctionInfo,
+        >(conv_called, conv_current, &options)
+    }
+
+    fn movs(ops: &[Operation<Register>]) -> Vec<Mov<Register>> {
+        ops.iter()
+            .filter_map(|x| match x {
+                Operation::Mov(x) => Some(*x),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn pushes(ops: &[Operation<Register>]) -> Vec<Push<Register>> {
+        ops.iter()
+            .filter_map(|x| match x {
+                Operation::Push(x) => Some(*x),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn pops(ops: &[Operation<Register>]) -> Vec<Pop<Register>> {
+        ops.iter()
+            .filter_map(|x| match x {
+                Operation::Pop(x) => Some(*x),
+                _ => None,
+            })
+            .collect()
+    }
+
+    static XMM0_YMM1: GenericCallingConvention<Register> =
+        with_float_and_vector_registers(&[xmm0], &[ymm1]);
+    static XMM1_YMM0: GenericCallingConvention<Register> =
+        with_float_and_vector_registers(&[xmm1], &[ymm0]);
+
+    const fn with_float_and_vector_registers(
+        float_parameters: &'static [Register],
+        vector_parameters: &'static [Register],
+    ) -> GenericCallingConvention<'static, Register> {
+        GenericCallingConvention::<Register> {
+            int_parameters: &[],
+            float_parameters,
+            vector_parameters,
+            return_register: rax,
+            float_return_register: Some(xmm0),
+            reserved_stack_space: 0,
+            callee_saved_registers: &[rbp, rbx, r12, r13, r14, r15],
+            always_saved_registers: &[],
+            stack_cleanup: StackCleanup::Caller,
+            stack_parameter_order: StackParameterOrder::RightToLeft,
+            required_stack_alignment: 16,
+            uses_extension_registers: false,
+            parameter_register_assignment: ParameterRegisterAssignment::Sequential,
+        }
+    }
+}